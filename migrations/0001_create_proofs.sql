@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS proofs (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    statement TEXT NOT NULL,
+    proof TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
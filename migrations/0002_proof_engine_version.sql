@@ -0,0 +1 @@
+ALTER TABLE proofs ADD COLUMN engine_version TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,171 @@
+//! Endpoints of path abstractions
+//!
+//! `⟨t⟩ body` checked against `Path A a b` must be `a` at `t = 0` and `b`
+//! at `t = 1`. The body at an end is [`at`]: `t` replaced by the point
+//! and the result folded, numbers computed and `x + 0`, `1 * x` and the like
+//! simplified. Two points [`agree`] when they fold to the same expression or
+//! to numbers within [`TOLERANCE`].
+
+use crate::syntax::{self, Expr, ExprKind};
+use crate::{primitive, Context};
+
+/// How far apart two numeric endpoints may be and still agree, relative to
+/// their size past 1
+pub const TOLERANCE: f64 = 1e-9;
+
+/// `body` with `t` at `point`, folded
+pub(crate) fn at(context: &Context, body: &Expr, t: &str, point: f64) -> Expr {
+    let point = Expr { position: body.position, end: body.end, kind: ExprKind::Num(point) };
+    fold(context, &substitute(body, t, &point))
+}
+
+pub(crate) fn agree(context: &Context, a: &Expr, b: &Expr) -> bool {
+    let (a, b) = (fold(context, a), fold(context, b));
+    match (&a.kind, &b.kind) {
+        (ExprKind::Num(x), ExprKind::Num(y)) => (x - y).abs() <= TOLERANCE * x.abs().max(y.abs()).max(1.0),
+        _ => a.same_as(&b),
+    }
+}
+
+/// Whether the points written `a` and `b` [`agree`] with nothing bound;
+/// one that does not parse agrees with nothing
+pub(crate) fn written_agree(a: &str, b: &str) -> bool {
+    match (syntax::parse(a), syntax::parse(b)) {
+        (Ok(a), Ok(b)) => agree(&Context::new(), &a, &b),
+        _ => false,
+    }
+}
+
+/// `expr` with the free occurrences of `name` replaced by `value`, which has
+/// no free names of its own
+fn substitute(expr: &Expr, name: &str, value: &Expr) -> Expr {
+    let go = |e: &Expr| Box::new(substitute(e, name, value));
+    let kind = match &expr.kind {
+        ExprKind::Var(x) if x == name => return value.clone(),
+        ExprKind::Num(_) | ExprKind::Var(_) => return expr.clone(),
+        // Bound again: the inner binding covers `name`
+        ExprKind::Lambda(x, _) | ExprKind::PathAbs(x, _) if x == name => return expr.clone(),
+        ExprKind::Lambda(x, body) => ExprKind::Lambda(x.clone(), go(body)),
+        ExprKind::PathAbs(x, body) => ExprKind::PathAbs(x.clone(), go(body)),
+        ExprKind::App(f, a) => ExprKind::App(go(f), go(a)),
+        ExprKind::At(p, r) => ExprKind::At(go(p), go(r)),
+        ExprKind::Neg(a) => ExprKind::Neg(go(a)),
+        ExprKind::Binary(op, a, b) => ExprKind::Binary(*op, go(a), go(b)),
+    };
+    Expr { kind, ..*expr }
+}
+
+fn fold(context: &Context, expr: &Expr) -> Expr {
+    let go = |e: &Expr| Box::new(fold(context, e));
+    let num = |n: f64| Expr { kind: ExprKind::Num(n), ..*expr };
+    let kind = match &expr.kind {
+        ExprKind::Num(_) | ExprKind::Var(_) => return expr.clone(),
+        ExprKind::Lambda(x, body) => ExprKind::Lambda(x.clone(), go(body)),
+        ExprKind::PathAbs(x, body) => ExprKind::PathAbs(x.clone(), go(body)),
+        ExprKind::At(p, r) => ExprKind::At(go(p), go(r)),
+        ExprKind::Neg(a) => match go(a).kind {
+            ExprKind::Num(n) => return num(-n),
+            a => ExprKind::Neg(Box::new(Expr { kind: a, ..*expr })),
+        },
+        ExprKind::App(f, a) => {
+            let (f, a) = (go(f), go(a));
+            if let Some(n) = apply(context, &f, &a) {
+                return num(n);
+            }
+            ExprKind::App(f, a)
+        }
+        ExprKind::Binary(op, a, b) => {
+            let (a, b) = (go(a), go(b));
+            let number = |e: &Expr| match e.kind {
+                ExprKind::Num(n) => Some(n),
+                _ => None,
+            };
+            match (*op, number(&a), number(&b)) {
+                (op, Some(x), Some(y)) => return num(arithmetic(op, x, y)),
+                ('+', Some(z), _) | ('*', Some(z), _) if z == identity(*op) => return *b,
+                ('+' | '-', _, Some(z)) | ('*' | '/' | '^', _, Some(z)) if z == identity(*op) => return *a,
+                ('*', Some(z), _) | ('*', _, Some(z)) if z == 0.0 => return num(0.0),
+                ('^', _, Some(0.0)) => return num(1.0),
+                _ => ExprKind::Binary(*op, a, b),
+            }
+        }
+    };
+    Expr { kind, ..*expr }
+}
+
+/// The number that leaves the other operand of `op` as it is
+fn identity(op: char) -> f64 {
+    match op {
+        '+' | '-' => 0.0,
+        _ => 1.0,
+    }
+}
+
+fn arithmetic(op: char, x: f64, y: f64) -> f64 {
+    match op {
+        '+' => x + y,
+        '-' => x - y,
+        '*' => x * y,
+        '/' => x / y,
+        _ => x.powf(y),
+    }
+}
+
+/// A primitive the context does not rebind, applied to numbers
+fn apply(context: &Context, f: &Expr, a: &Expr) -> Option<f64> {
+    let ExprKind::Num(x) = a.kind else { return None };
+    if let ExprKind::App(head, first) = &f.kind {
+        let ExprKind::Num(first) = first.kind else { return None };
+        return match primitive(context, head)? {
+            "max" => Some(first.max(x)),
+            "min" => Some(first.min(x)),
+            _ => None,
+        };
+    }
+    Some(match primitive(context, f)? {
+        "sin" => x.sin(),
+        "cos" => x.cos(),
+        "tan" => x.tan(),
+        "exp" => x.exp(),
+        "ln" => x.ln(),
+        "sqrt" => x.sqrt(),
+        "abs" => x.abs(),
+        "floor" => x.floor(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse;
+
+    fn ends(body: &str) -> (String, String) {
+        let context = Context::new();
+        let ExprKind::PathAbs(t, body) = parse(body).unwrap().kind else { panic!("not a path") };
+        (at(&context, &body, &t, 0.0).to_string(), at(&context, &body, &t, 1.0).to_string())
+    }
+
+    #[test]
+    fn test_ends_fold() {
+        assert_eq!(ends("⟨t⟩ t"), ("0".to_string(), "1".to_string()));
+        assert_eq!(ends("⟨t⟩ t²"), ("0".to_string(), "1".to_string()));
+        assert_eq!(ends("⟨t⟩ x + t * y"), ("x".to_string(), "x + y".to_string()));
+        assert_eq!(ends("⟨t⟩ max(t)(0.5) - cos(π * t)"), ("-0.5".to_string(), "2".to_string()));
+        // An inner binder of the same name is left alone
+        assert_eq!(ends("⟨t⟩ (λt. t) t"), ("(λt. t)(0)".to_string(), "(λt. t)(1)".to_string()));
+    }
+
+    #[test]
+    fn test_agreement() {
+        let mut context = Context::new();
+        let agrees = |context: &Context, a: &str, b: &str| agree(context, &parse(a).unwrap(), &parse(b).unwrap());
+        assert!(agrees(&context, "2 * π", "6.283185307179586"));
+        assert!(agrees(&context, "x * 1", "0 + x"));
+        assert!(!agrees(&context, "x", "y"));
+        assert!(!agrees(&context, "1", "1.001"));
+        // A rebound primitive is not computed
+        context.add("sin".to_string(), crate::Type::Real);
+        assert!(!agrees(&context, "sin(0)", "0"));
+    }
+}
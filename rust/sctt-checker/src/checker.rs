@@ -0,0 +1,164 @@
+//! The checker as the engines call it
+//!
+//! [`Checker::check`] answers one question about a piece of source: does it
+//! type, and at what? The answer is a [`CheckResponse`], which front ends
+//! serialize as it is rather than each building their own JSON.
+
+use sctt_core::record::{Severity, Span};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::response::{byte_offset, description, CheckResponse, Diagnostic};
+use crate::{format_type, AnnotationError, Context, Type, TypeChecker};
+
+/// What is under the cursor: a sub-expression and its type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeAt {
+    pub span: Span,
+    /// Formatted
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// [`TypeChecker`] behind a report-shaped interface
+pub struct Checker {
+    inner: TypeChecker,
+}
+
+impl Default for Checker {
+    fn default() -> Self {
+        Checker::new()
+    }
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Checker { inner: TypeChecker::new() }
+    }
+
+    /// The bindings source is checked under
+    pub fn context(&self) -> &Context {
+        self.inner.context()
+    }
+
+    /// The bindings source is checked under
+    pub fn context_mut(&mut self) -> &mut Context {
+        self.inner.context_mut()
+    }
+
+    /// The innermost typed sub-expression around the byte `offset`; see
+    /// [`TypeChecker::type_at`]
+    pub fn type_at(&self, code: &str, offset: usize) -> Option<TypeAt> {
+        if offset >= code.len() {
+            return None;
+        }
+        // The character `offset` falls in
+        let at = code.char_indices().take_while(|(start, _)| *start <= offset).count() - 1;
+        let typed = self.inner.type_at(code, at)?;
+        let span = Span { start: byte_offset(code, typed.start), end: byte_offset(code, typed.end) };
+        Some(TypeAt { span, ty: format_type(&typed.ty) })
+    }
+
+    /// See [`TypeChecker::check_against`]
+    pub fn check_against(&self, code: &str, expected: &str) -> Result<Type, AnnotationError> {
+        self.inner.check_against(code, expected)
+    }
+
+    /// Every problem with `code` as an error diagnostic, or its type, with
+    /// its `description` in the details and, for a function that is not
+    /// smooth, why as an information diagnostic
+    pub fn check(&self, code: &str) -> CheckResponse {
+        let (ty, problems) = self.inner.diagnose(code);
+        let mut diagnostics: Vec<_> = problems
+            .into_iter()
+            .map(|problem| Diagnostic { suggestion: problem.suggestion, ..Diagnostic::of(code, &problem.error) })
+            .collect();
+        let Some(ty) = ty else {
+            return CheckResponse::new(None, diagnostics, "");
+        };
+        if let Ok(Some(rough)) = self.inner.why_not_smooth(code) {
+            let span = Span { start: byte_offset(code, rough.start), end: byte_offset(code, rough.end) };
+            let reason = Diagnostic::error("not_smooth", rough.reason);
+            diagnostics.push(Diagnostic { severity: Severity::Info, span: Some(span), ..reason });
+        }
+        let shown = format_type(&ty);
+        let message = format!("{} : {}", code, shown);
+        CheckResponse::new(Some(shown), diagnostics, message).with_details(json!({ "description": description(&ty) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_the_inferred_type() {
+        let report = Checker::new().check("λx. sin(x)");
+        assert!(report.success && report.diagnostics.is_empty());
+        assert_eq!(report.ty.as_deref(), Some("C∞(ℝ → ℝ)"));
+    }
+
+    #[test]
+    fn test_empty_source_is_an_error() {
+        let report = Checker::new().check("  ");
+        assert!(!report.success && report.ty.is_none());
+        assert_eq!(report.diagnostics[0].severity, Severity::Error);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["type"], serde_json::Value::Null);
+        assert_eq!(json["diagnostics"][0]["severity"], "error");
+    }
+
+    #[test]
+    fn test_report_json_is_stable() {
+        let golden = [
+            (
+                "λx. sin(x)",
+                r#"{"success":true,"type":"C∞(ℝ → ℝ)","message":"λx. sin(x) : C∞(ℝ → ℝ)","diagnostics":[],"details":{"description":"Smooth function from reals to reals"}}"#,
+            ),
+            (
+                "λx. abs(x)",
+                r#"{"success":true,"type":"ℝ → ℝ","message":"λx. abs(x) : ℝ → ℝ","diagnostics":[{"code":"not_smooth","severity":"info","message":"`abs` has a corner at 0","span":{"start":5,"end":11}}],"details":{"description":"Function type"}}"#,
+            ),
+            ("⟨i⟩ i", r#"{"success":true,"type":"Path I","message":"⟨i⟩ i : Path I","diagnostics":[],"details":{"description":"Continuous path in space"}}"#),
+            (
+                "sinn",
+                r#"{"success":false,"message":"Unbound variable `sinn` at position 0","diagnostics":[{"code":"unbound","severity":"error","message":"Unbound variable `sinn` at position 0","span":{"start":0,"end":1},"suggestion":"Did you mean `sin`?"}]}"#,
+            ),
+        ];
+        for (code, json) in golden {
+            let report = Checker::new().check(code);
+            assert_eq!(serde_json::to_string(&report).unwrap(), json);
+            assert_eq!(CheckResponse::validate(json), Ok(report));
+        }
+    }
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let report = Checker::new().check("sinn(2) + (⟨i⟩ i) @ 3");
+        assert!(!report.success && report.ty.is_none());
+        let [unbound, mismatch] = &report.diagnostics[..] else { panic!("expected two diagnostics") };
+        assert_eq!(unbound.span, Some(Span { start: 0, end: 1 }));
+        assert_eq!(unbound.suggestion.as_deref(), Some("Did you mean `sin`?"));
+        assert_eq!(mismatch.message, "Expected I, found ℝ at position 20");
+        // The span is in bytes: `⟨` and `⟩` take three each
+        assert_eq!(mismatch.span, Some(Span { start: 24, end: 25 }));
+        // An expression of unknown type is spanned whole
+        let unknown = Checker::new().check("λf. f(1)").diagnostics;
+        assert_eq!(unknown[0].span, Some(Span { start: 5, end: 9 }));
+    }
+
+    #[test]
+    fn test_hover_types() {
+        let checker = Checker::new();
+        let hover = |offset: usize| checker.type_at("sin(x²)", offset).map(|t| (t.span.start, t.span.end, t.ty));
+        assert_eq!(hover(0), Some((0, 3, "C∞(ℝ → ℝ)".to_string())));
+        // `x` is unbound, but `x²` is a real number; `²` takes two bytes
+        assert_eq!(hover(4), Some((4, 7, "ℝ".to_string())));
+        // Inside `²` is its exponent
+        assert_eq!(hover(6), Some((5, 7, "ℝ".to_string())));
+        assert_eq!(hover(3), Some((0, 8, "ℝ".to_string())));
+        assert_eq!(hover(8), None);
+        let json = serde_json::to_string(&checker.type_at("1", 0)).unwrap();
+        assert_eq!(json, r#"{"span":{"start":0,"end":1},"type":"ℝ"}"#);
+    }
+}
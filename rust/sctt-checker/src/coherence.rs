@@ -0,0 +1,119 @@
+//! Smooth–cubical coherence
+//!
+//! A smooth function `f` and a cubical path `p` over `[0, 1]` cohere where
+//! they meet with the same value and the same velocity. They are compared
+//! at the ends of the interval and at every junction of `p`, where a
+//! concatenated path switches pieces; there `p` is checked from both
+//! sides, since a corner has two velocities. `f` is an
+//! [`sctt_smooth::expr`] expression in at most one variable, differentiated
+//! exactly by forward-mode AD; `p` is an `sctt-cubical` path in `t`.
+
+use sctt_cubical::{CubicalError, Path};
+use sctt_smooth::dual::Dual;
+use sctt_smooth::expr::{self, ExprError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// How far apart values may be and still agree
+pub const VALUE_TOLERANCE: f64 = 1e-9;
+
+/// How far apart velocities may be and still agree; the path's are
+/// finite differences
+pub const VELOCITY_TOLERANCE: f64 = 1e-6;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CoherenceError {
+    #[error("smooth side: {0}")]
+    Smooth(#[from] ExprError),
+
+    #[error("smooth side: `{expression}` is undefined at {at}")]
+    Undefined { expression: String, at: f64 },
+
+    #[error("cubical side: {0}")]
+    Cubical(#[from] CubicalError),
+}
+
+/// The comparison at one point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointCheck {
+    pub point: f64,
+    pub value_diff: f64,
+    /// The larger of the gaps to the path's velocity from either side
+    pub velocity_diff: f64,
+    pub ok: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoherenceReport {
+    pub coherent: bool,
+    /// `t = 0`, the path's junctions, and `t = 1`, in order
+    pub points: Vec<PointCheck>,
+}
+
+pub fn check_coherence(smooth: &str, cubical: &str) -> Result<CoherenceReport, CoherenceError> {
+    let f = expr::parse(smooth)?;
+    if let [variable, name, ..] = &f.variables()[..] {
+        return Err(ExprError::UnknownVariable { name: name.clone(), variable: variable.clone() }.into());
+    }
+    let path = Path::new(cubical.to_string(), "t".to_string())?;
+    let mut points = vec![0.0];
+    points.extend(path.junctions().into_iter().filter(|&t| t > 0.0 && t < 1.0));
+    points.push(1.0);
+
+    let points = points
+        .into_iter()
+        .map(|point| {
+            let at = f.eval(Dual::variable(point));
+            if !at.value.is_finite() || !at.deriv.is_finite() {
+                return Err(CoherenceError::Undefined { expression: f.to_string(), at: point });
+            }
+            let (below, above) = path.velocity_either_side(point);
+            let value_diff = (at.value - path.evaluate(point)).abs();
+            let velocity_diff = (at.deriv - below).abs().max((at.deriv - above).abs());
+            let ok = value_diff <= VALUE_TOLERANCE && velocity_diff <= VELOCITY_TOLERANCE;
+            Ok(PointCheck { point, value_diff, velocity_diff, ok })
+        })
+        .collect::<Result<Vec<_>, CoherenceError>>()?;
+    Ok(CoherenceReport { coherent: points.iter().all(|p| p.ok), points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_a_path_agrees_with_its_own_formula() {
+        let report = check_coherence("t²", "if(t ≤ 0.5, t², t * t)").unwrap();
+        assert!(report.coherent);
+        assert_eq!(report.points.iter().map(|p| p.point).collect::<Vec<_>>(), vec![0.0, 0.5, 1.0]);
+        let report = check_coherence("sin(x)", "sin(t)").unwrap();
+        assert!(report.coherent && report.points.len() == 2);
+    }
+
+    #[test]
+    fn test_playground_pair_is_incoherent() {
+        let report = check_coherence("t²", "1 + t").unwrap();
+        assert!(!report.coherent);
+        // At 0: values 0 and 1, velocities 0 and 1; at 1: values 1 and 2, velocities 2 and 1
+        let [start, end] = report.points[..] else { panic!("expected two points") };
+        assert!((start.value_diff - 1.0).abs() < 1e-12 && (start.velocity_diff - 1.0).abs() < 1e-6);
+        assert!((end.value_diff - 1.0).abs() < 1e-12 && (end.velocity_diff - 1.0).abs() < 1e-6);
+        assert!(!start.ok && !end.ok);
+    }
+
+    #[test]
+    fn test_corners_fail_at_internal_junctions() {
+        // Up and back down: the value at the turn is right, but from above
+        // the path runs at -1 where `t` runs at 1
+        let report = check_coherence("t", "if(t ≤ 0.5, t, 1 - t)").unwrap();
+        let junction = report.points[1];
+        assert_eq!(junction.point, 0.5);
+        assert!(junction.value_diff < 1e-12 && (junction.velocity_diff - 2.0).abs() < 1e-6);
+        assert!(!report.coherent);
+        assert!(matches!(check_coherence("t +", "t"), Err(CoherenceError::Smooth(_))));
+        let two_variables = check_coherence("s + t", "t");
+        assert!(matches!(two_variables, Err(CoherenceError::Smooth(ExprError::UnknownVariable { .. }))));
+        assert!(matches!(check_coherence("ln(t)", "t"), Err(CoherenceError::Undefined { at, .. }) if at == 0.0));
+        assert!(matches!(check_coherence("t", "t +"), Err(CoherenceError::Cubical(_))));
+    }
+}
@@ -0,0 +1,177 @@
+//! Moving types between the checker and `sctt-core`
+//!
+//! The checker's [`Type`] is the fragment of [`sctt_core::Type`] the
+//! teaching front ends need. Every checker type but a metavariable, which
+//! only lives while checking, has a core counterpart: `C∞(A → B)` is a
+//! smooth core function, a path type's numeric endpoints are real literals
+//! and any others holes, and the universe is `Type_0`.
+//! The way back is partial: dependent types, inductive types, records,
+//! `PathP` and higher universes have no checker type, and path endpoints
+//! other than real literals are forgotten.
+
+use sctt_core::{Level, Term};
+use thiserror::Error;
+
+use crate::Type;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("`{ty}` has no counterpart among the checker's types")]
+pub struct Unrepresentable {
+    /// The core type, displayed
+    pub ty: String,
+}
+
+/// A type with the metavariable `?n` left in it, which core has no
+/// counterpart of
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("`?{0}` is not yet known")]
+pub struct UnsolvedMeta(pub u32);
+
+impl TryFrom<Type> for sctt_core::Type {
+    type Error = UnsolvedMeta;
+
+    fn try_from(ty: Type) -> Result<Self, UnsolvedMeta> {
+        let core = |ty: Box<Type>| sctt_core::Type::try_from(*ty).map(Box::new);
+        Ok(match ty {
+            Type::Real => sctt_core::Type::Real,
+            Type::Interval => sctt_core::Type::Interval,
+            Type::Universe => sctt_core::Type::Universe(Level::ZERO),
+            Type::Function { domain, codomain } => {
+                sctt_core::Type::Function { domain: core(domain)?, codomain: core(codomain)?, is_smooth: false }
+            }
+            Type::Smooth(inner) => match *inner {
+                Type::Function { domain, codomain } => {
+                    sctt_core::Type::Function { domain: core(domain)?, codomain: core(codomain)?, is_smooth: true }
+                }
+                inner => sctt_core::Type::Smooth(core(Box::new(inner))?),
+            },
+            Type::Path { space, endpoints } => {
+                let point = |p: Option<&String>| {
+                    Box::new(p.and_then(|p| p.parse().ok()).map_or(Term::Hole, Term::RealLit))
+                };
+                let (start, end) = (endpoints.as_ref().map(|e| &e.0), endpoints.as_ref().map(|e| &e.1));
+                let (start, end) = (point(start), point(end));
+                sctt_core::Type::Path { space: core(space)?, start, end }
+            }
+            Type::Meta(meta) => return Err(UnsolvedMeta(meta)),
+        })
+    }
+}
+
+impl TryFrom<sctt_core::Type> for Type {
+    type Error = Unrepresentable;
+
+    /// Path endpoints other than real literals are dropped; anything else
+    /// the checker cannot say is an error
+    fn try_from(ty: sctt_core::Type) -> Result<Self, Unrepresentable> {
+        let checked = |ty: Box<sctt_core::Type>| Type::try_from(*ty).map(Box::new);
+        Ok(match ty {
+            sctt_core::Type::Real => Type::Real,
+            sctt_core::Type::Interval => Type::Interval,
+            sctt_core::Type::Universe(Level::ZERO) => Type::Universe,
+            sctt_core::Type::Function { domain, codomain, is_smooth } => {
+                let function = Type::Function { domain: checked(domain)?, codomain: checked(codomain)? };
+                if is_smooth { Type::Smooth(Box::new(function)) } else { function }
+            }
+            sctt_core::Type::Smooth(inner) => Type::Smooth(checked(inner)?),
+            sctt_core::Type::Path { space, start, end } => {
+                let endpoints = match (*start, *end) {
+                    (Term::RealLit(a), Term::RealLit(b)) => Some((a.to_string(), b.to_string())),
+                    _ => None,
+                };
+                Type::Path { space: checked(space)?, endpoints }
+            }
+            ty => return Err(Unrepresentable { ty: ty.to_string() }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format_type;
+
+    fn function(domain: Type, codomain: Type) -> Type {
+        Type::Function { domain: Box::new(domain), codomain: Box::new(codomain) }
+    }
+
+    #[test]
+    fn test_every_checker_type_round_trips() {
+        let golden = [
+            (Type::Real, "ℝ"),
+            (Type::Interval, "I"),
+            (Type::Universe, "Type"),
+            (function(Type::Real, Type::Interval), "ℝ → I"),
+            (Type::Smooth(Box::new(function(Type::Real, Type::Real))), "C∞(ℝ → ℝ)"),
+            (Type::Smooth(Box::new(Type::Real)), "C∞(ℝ)"),
+            (Type::Path { space: Box::new(Type::Real), endpoints: None }, "Path ℝ"),
+            (crate::syntax::parse_type("Path ℝ 0 2.5").unwrap(), "Path ℝ 0 2.5"),
+        ];
+        for (ty, shown) in golden {
+            let back = Type::try_from(sctt_core::Type::try_from(ty.clone()).unwrap()).unwrap();
+            assert_eq!(back, ty);
+            // What the WASM exports report as `type` is unchanged
+            assert_eq!(format_type(&back), shown);
+        }
+    }
+
+    #[test]
+    fn test_core_types_map_to_their_checker_counterparts() {
+        let smooth = sctt_core::Type::Function {
+            domain: Box::new(sctt_core::Type::Real),
+            codomain: Box::new(sctt_core::Type::Real),
+            is_smooth: true,
+        };
+        assert_eq!(Type::try_from(smooth), Ok(Type::Smooth(Box::new(function(Type::Real, Type::Real)))));
+        let loop_at_zero = sctt_core::Type::Path {
+            space: Box::new(sctt_core::Type::Real),
+            start: Box::new(Term::RealLit(0.0)),
+            end: Box::new(Term::RealLit(0.0)),
+        };
+        let endpoints = Some(("0".to_string(), "0".to_string()));
+        assert_eq!(Type::try_from(loop_at_zero), Ok(Type::Path { space: Box::new(Type::Real), endpoints }));
+        let open = sctt_core::Type::Path {
+            space: Box::new(sctt_core::Type::Real),
+            start: Box::new(Term::Var("a".to_string())),
+            end: Box::new(Term::RealLit(1.0)),
+        };
+        assert_eq!(Type::try_from(open), Ok(Type::Path { space: Box::new(Type::Real), endpoints: None }));
+        let wrapped = sctt_core::Type::Smooth(Box::new(sctt_core::Type::Interval));
+        assert_eq!(Type::try_from(wrapped), Ok(Type::Smooth(Box::new(Type::Interval))));
+    }
+
+    #[test]
+    fn test_the_rest_of_core_is_unrepresentable() {
+        let real = || Box::new(sctt_core::Type::Real);
+        let line = sctt_core::Type::PathP {
+            param: "i".to_string(),
+            family: real(),
+            start: Box::new(Term::RealLit(0.0)),
+            end: Box::new(Term::RealLit(1.0)),
+        };
+        let unrepresentable = [
+            sctt_core::Type::Universe(Level::Const(1)),
+            sctt_core::Type::Pi { param: "x".to_string(), domain: real(), codomain: real() },
+            sctt_core::Type::Sigma { param: "x".to_string(), domain: real(), codomain: real() },
+            line,
+            sctt_core::Type::Nat,
+            sctt_core::Type::Unit,
+            sctt_core::Type::Empty,
+            sctt_core::Type::Bool,
+            sctt_core::Type::Record(vec![("x".to_string(), sctt_core::Type::Real)]),
+            sctt_core::Type::Circle,
+            sctt_core::Type::Sum(real(), real()),
+        ];
+        for ty in unrepresentable {
+            let shown = ty.to_string();
+            assert_eq!(Type::try_from(ty), Err(Unrepresentable { ty: shown }));
+        }
+        // Inside an otherwise representable type too
+        let arrow =
+            sctt_core::Type::Function { domain: Box::new(sctt_core::Type::Nat), codomain: real(), is_smooth: false };
+        assert!(Type::try_from(arrow).is_err());
+        // Nor does core know the checker's metavariables
+        let unknown = function(Type::Real, Type::Meta(3));
+        assert_eq!(sctt_core::Type::try_from(unknown), Err(UnsolvedMeta(3)));
+    }
+}
@@ -0,0 +1,183 @@
+//! Type equality up to trivial differences, and where two types part
+//!
+//! Two types are [convertible](Type::conv_eq) when they are the same once
+//! [normalized](Type::normalize), with path endpoints that compute to the
+//! same point, however they are written. When they are not, [`type_diff`]
+//! says where: a top-level function's domains are its numbered arguments
+//! and its last codomain is its result, a function inside those has a
+//! domain and a codomain, and a path has a space and two endpoints.
+
+use crate::{boundary, format_type, Type};
+
+impl Type {
+    /// The same type with trivial differences ironed out: a smooth smooth
+    /// type is smooth once
+    pub fn normalize(&self) -> Type {
+        match self {
+            Type::Smooth(inner) => match inner.normalize() {
+                smooth @ Type::Smooth(_) => smooth,
+                inner => Type::Smooth(Box::new(inner)),
+            },
+            Type::Function { domain, codomain } => {
+                Type::Function { domain: Box::new(domain.normalize()), codomain: Box::new(codomain.normalize()) }
+            }
+            Type::Path { space, endpoints } => {
+                Type::Path { space: Box::new(space.normalize()), endpoints: endpoints.clone() }
+            }
+            ty => ty.clone(),
+        }
+    }
+
+    /// Whether `self` and `other` are the same type once normalized
+    pub fn conv_eq(&self, other: &Type) -> bool {
+        divergence(self, other, true).is_none()
+    }
+}
+
+/// Where `expected` and `found` part and what each is there, as in "in the
+/// codomain of the 2nd argument: expected ℝ, found I"; without the place
+/// when they differ as wholes
+pub fn type_diff(expected: &Type, found: &Type) -> String {
+    match divergence(expected, found, true) {
+        Some(divergence) => divergence.to_string(),
+        None => format!("expected {}, found {}, which agree", format_type(expected), format_type(found)),
+    }
+}
+
+/// [`type_diff`] after a separator when the types part below the top, for
+/// mismatch messages that show the whole types already
+pub(crate) fn located_diff(expected: &Type, found: &Type) -> String {
+    match divergence(expected, found, true) {
+        Some(divergence) if !divergence.place.is_empty() => format!("; {}", divergence),
+        _ => String::new(),
+    }
+}
+
+struct Divergence {
+    /// Innermost first
+    place: Vec<String>,
+    expected: String,
+    found: String,
+}
+
+impl Divergence {
+    fn within(mut self, part: impl Into<String>) -> Self {
+        self.place.push(part.into());
+        self
+    }
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if !self.place.is_empty() {
+            write!(f, "in {}: ", self.place.join(" of "))?;
+        }
+        write!(f, "expected {}, found {}", self.expected, self.found)
+    }
+}
+
+/// The first place `expected` and `found` part, if they do; `top` numbers a
+/// function's arguments
+fn divergence(expected: &Type, found: &Type, top: bool) -> Option<Divergence> {
+    let (expected, found) = (expected.normalize(), found.normalize());
+    let leaf = |expected: String, found: String| Some(Divergence { place: Vec::new(), expected, found });
+    match (&expected, &found) {
+        (Type::Smooth(a), Type::Smooth(b)) => divergence(a, b, top),
+        (Type::Function { .. }, Type::Function { .. }) if top => arguments(&expected, &found),
+        (Type::Function { domain: a, codomain: b }, Type::Function { domain: c, codomain: d }) => {
+            let domain = || divergence(a, c, false).map(|d| d.within("the domain"));
+            domain().or_else(|| divergence(b, d, false).map(|d| d.within("the codomain")))
+        }
+        (Type::Path { space: a, endpoints: p }, Type::Path { space: b, endpoints: q }) => {
+            if let Some(divergence) = divergence(a, b, false) {
+                return Some(divergence.within("the space"));
+            }
+            match (p, q) {
+                (None, None) => None,
+                (Some((a0, _)), Some((b0, _))) if !boundary::written_agree(a0, b0) => {
+                    leaf(a0.clone(), b0.clone()).map(|d| d.within("the start point"))
+                }
+                (Some((_, a1)), Some((_, b1))) if !boundary::written_agree(a1, b1) => {
+                    leaf(a1.clone(), b1.clone()).map(|d| d.within("the end point"))
+                }
+                (Some(_), Some(_)) => None,
+                _ => {
+                    let shown = |p: &Option<(String, String)>| match p {
+                        Some((start, end)) => format!("{} and {}", start, end),
+                        None => "none".to_string(),
+                    };
+                    leaf(shown(p), shown(q)).map(|d| d.within("the endpoints"))
+                }
+            }
+        }
+        _ if expected == found => None,
+        _ => leaf(format_type(&expected), format_type(&found)),
+    }
+}
+
+/// Where two function types part, argument by argument, then in the result
+fn arguments(expected: &Type, found: &Type) -> Option<Divergence> {
+    let (mut expected, mut found, mut n) = (expected, found, 1);
+    while let (Type::Function { domain: a, codomain: b }, Type::Function { domain: c, codomain: d }) =
+        (expected, found)
+    {
+        if let Some(divergence) = divergence(a, c, false) {
+            return Some(divergence.within(format!("the {} argument", ordinal(n))));
+        }
+        (expected, found, n) = (b, d, n + 1);
+    }
+    divergence(expected, found, false).map(|d| d.within("the result"))
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse_type;
+
+    fn diff(expected: &str, found: &str) -> String {
+        type_diff(&parse_type(expected).unwrap(), &parse_type(found).unwrap())
+    }
+
+    #[test]
+    fn test_deep_types_part_at_a_leaf() {
+        let expected = "(ℝ → ℝ) → (ℝ → ℝ) → ℝ → ℝ";
+        let second = diff(expected, "(ℝ → ℝ) → (ℝ → I) → ℝ → ℝ");
+        assert_eq!(second, "in the codomain of the 2nd argument: expected ℝ, found I");
+        let first = diff(expected, "(I → ℝ) → (ℝ → ℝ) → ℝ → ℝ");
+        assert_eq!(first, "in the domain of the 1st argument: expected ℝ, found I");
+        assert_eq!(diff(expected, "(ℝ → ℝ) → (ℝ → ℝ) → I → ℝ"), "in the 3rd argument: expected ℝ, found I");
+        assert_eq!(diff(expected, "(ℝ → ℝ) → (ℝ → ℝ) → ℝ → I"), "in the result: expected ℝ, found I");
+        assert_eq!(diff("ℝ → ℝ", "ℝ → ℝ → ℝ"), "in the result: expected ℝ, found ℝ → ℝ");
+        let smooth = "C∞(ℝ, C∞(ℝ, Path ℝ 0 1))";
+        assert_eq!(
+            diff(smooth, "C∞(ℝ, C∞(ℝ, Path ℝ 0 2))"),
+            "in the end point of the codomain of the result: expected 1, found 2"
+        );
+        assert_eq!(diff("ℝ", "I"), "expected ℝ, found I");
+        assert_eq!(diff("Path ℝ 0 1", "Path ℝ"), "in the endpoints: expected 0 and 1, found none");
+        assert_eq!(ordinal(12), "12th");
+        assert_eq!(ordinal(22), "22nd");
+    }
+
+    #[test]
+    fn test_conversion_ignores_how_types_are_written() {
+        let conv = |a: &str, b: &str| parse_type(a).unwrap().conv_eq(&parse_type(b).unwrap());
+        assert!(conv("((ℝ → ℝ)) → (ℝ)", "(ℝ → ℝ) → ℝ"));
+        assert!(conv("C∞(ℝ, ℝ)", "C∞(C∞(ℝ → ℝ))"));
+        assert!(conv("Path ℝ 0 (2 * π)", "Path ℝ (0) 6.283185307179586"));
+        assert!(!conv("Path ℝ 0 1", "Path ℝ 0 1.5"));
+        assert!(!conv("C∞(ℝ, ℝ)", "ℝ → ℝ"));
+        assert!(!conv("ℝ → ℝ → ℝ", "(ℝ → ℝ) → ℝ"));
+    }
+}
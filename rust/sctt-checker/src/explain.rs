@@ -0,0 +1,309 @@
+//! "Why is this not smooth?"
+//!
+//! A rejected expression only says `ℝ → ℝ, not C∞(ℝ → ℝ)`. The explainer
+//! says where and why: every construct that can bend (`min`, `max`, `∧`,
+//! `∨`, `if`) or join of a [`PiecewisePath`] that fails to be C¹ becomes a
+//! [`SmoothnessIssue`] with its span in the source, and where the bend can be
+//! located numerically, [`Evidence`]: the one-sided slopes there and a
+//! zoomed sampling of the function and its finite-difference derivative,
+//! ready to plot, that shows the jump.
+//!
+//! Spans are character offsets, like [`LiteError::Parse`] positions.
+
+use serde::{Deserialize, Serialize};
+
+use sctt_smooth::expr::Corner;
+
+use crate::lite::{eval_at, parse_expression, parse_with_corners, Expression, LiteError, Result};
+
+/// Expressions are searched for corners over `[-PROBE, PROBE]`
+pub const PROBE: f64 = 10.0;
+/// Grid the search scans before bisecting a sign change
+const PROBE_INTERVALS: usize = 400;
+/// Step of the one-sided difference quotients
+const STEP: f64 = 1e-6;
+/// Half-width of the zoomed plot around a corner
+pub const ZOOM: f64 = 0.25;
+pub const PLOT_POINTS: usize = 41;
+/// Values and slopes closer than this count as equal
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmoothnessReport {
+    pub source: String,
+    /// No issues: nothing in the source can bend
+    pub smooth: bool,
+    pub issues: Vec<SmoothnessIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmoothnessIssue {
+    /// The structural reason, e.g. "`max` has a corner where its arguments meet"
+    pub reason: String,
+    /// The offending construct
+    pub span: Span,
+    /// `None` when no bend was found in the probed range
+    pub evidence: Option<Evidence>,
+}
+
+/// What the function does around the point where it bends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evidence {
+    pub at: f64,
+    pub value: f64,
+    pub left_slope: f64,
+    pub right_slope: f64,
+    /// `(x, f(x))` over `at ± ZOOM`, undefined points left out
+    pub samples: Vec<[f64; 2]>,
+    /// `(x, f'(x))` by central differences, at the same points
+    pub derivative: Vec<[f64; 2]>,
+}
+
+/// A path given piece by piece: `t on [0, 0.5]; 1 - t on [0.5, 1]`
+///
+/// Each piece is an expression in the path's parameter over its own range;
+/// consecutive ranges must meet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PiecewisePath {
+    pub source: String,
+    pub pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Piece {
+    pub expression: Expression,
+    pub start: f64,
+    pub end: f64,
+    pub span: Span,
+}
+
+impl PiecewisePath {
+    pub fn parse(source: &str) -> Result<PiecewisePath> {
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut offset = 0;
+        for text in source.split(';') {
+            let len = text.chars().count();
+            let leading = text.chars().take_while(|c| c.is_whitespace()).count();
+            let span = Span { start: offset + leading, end: offset + text.trim_end().chars().count() };
+            let piece = parse_piece(text.trim(), span)?;
+            if let Some(last) = pieces.last() {
+                if (last.end - piece.start).abs() > TOLERANCE {
+                    return Err(LiteError::Parse {
+                        position: span.start,
+                        message: format!("Piece starts at {} but the previous one ends at {}", piece.start, last.end),
+                    });
+                }
+            }
+            pieces.push(piece);
+            offset += len + 1;
+        }
+        Ok(PiecewisePath { source: source.to_string(), pieces })
+    }
+
+    /// The piece in charge of `t`; at a join, the earlier one
+    fn eval(&self, t: f64) -> f64 {
+        self.pieces
+            .iter()
+            .find(|p| t <= p.end)
+            .or(self.pieces.last())
+            .and_then(|p| eval_at(&p.expression, t).ok())
+            .map_or(f64::NAN, |e| e.value)
+    }
+
+    fn domain(&self) -> (f64, f64) {
+        let start = self.pieces.first().map_or(0.0, |p| p.start);
+        (start, self.pieces.last().map_or(start, |p| p.end))
+    }
+}
+
+fn parse_piece(text: &str, span: Span) -> Result<Piece> {
+    let malformed = |message: &str| LiteError::Parse { position: span.start, message: message.to_string() };
+    let (expression, range) = text.split_once(" on ").ok_or_else(|| malformed("Expected `<expression> on [a, b]`"))?;
+    let bounds = range
+        .trim()
+        .strip_prefix('[')
+        .and_then(|r| r.strip_suffix(']'))
+        .and_then(|r| r.split_once(','))
+        .ok_or_else(|| malformed("Expected a range `[a, b]`"))?;
+    let bound = |b: &str| eval_at(&parse_expression(b)?, 0.0).map(|e| e.value);
+    let (start, end) = (bound(bounds.0)?, bound(bounds.1)?);
+    if start >= end {
+        return Err(malformed("The range of a piece must be non-empty"));
+    }
+    Ok(Piece { expression: parse_expression(expression)?, start, end, span })
+}
+
+/// Explain an expression, or a piecewise path when `code` has `on [a, b]` ranges
+pub fn explain_smoothness(code: &str) -> Result<SmoothnessReport> {
+    if code.contains(" on ") {
+        explain_path(&PiecewisePath::parse(code)?)
+    } else {
+        explain_expression(code)
+    }
+}
+
+pub fn explain_expression(source: &str) -> Result<SmoothnessReport> {
+    let (expression, corners) = parse_with_corners(source)?;
+    let issues = if expression.variable.is_none() {
+        // A constant does not bend, whatever it is built from
+        Vec::new()
+    } else {
+        let f = |x: f64| eval_at(&expression, x).map_or(f64::NAN, |e| e.value);
+        let everywhere = (f64::NEG_INFINITY, f64::INFINITY);
+        corners
+            .iter()
+            .map(|corner| SmoothnessIssue {
+                reason: corner_reason(corner),
+                span: Span { start: corner.start, end: corner.end },
+                evidence: crossing(|x| corner.gap(x), -PROBE, PROBE).map(|at| evidence(&f, at, everywhere)),
+            })
+            .collect()
+    };
+    Ok(report(source, issues))
+}
+
+fn corner_reason(corner: &Corner) -> String {
+    match corner.name {
+        "if" => "`if` has a corner where its test crosses its bound".to_string(),
+        name => format!("`{}` has a corner where its arguments meet", name),
+    }
+}
+
+pub fn explain_path(path: &PiecewisePath) -> Result<SmoothnessReport> {
+    let mut issues = Vec::new();
+    for piece in path.pieces.iter().filter(|p| p.expression.variable.is_some()) {
+        let (_, corners) = parse_with_corners(&piece.expression.source)?;
+        let f = |t: f64| eval_at(&piece.expression, t).map_or(f64::NAN, |e| e.value);
+        for corner in &corners {
+            issues.push(SmoothnessIssue {
+                reason: corner_reason(corner),
+                span: Span { start: piece.span.start + corner.start, end: piece.span.start + corner.end },
+                evidence: crossing(|t| corner.gap(t), piece.start, piece.end)
+                    .map(|at| evidence(&f, at, (piece.start, piece.end))),
+            });
+        }
+    }
+
+    for pair in path.pieces.windows(2) {
+        let (left, right) = (&pair[0], &pair[1]);
+        let at = left.end;
+        let (l, r) = (eval_at(&left.expression, at)?, eval_at(&right.expression, at)?);
+        let reason = if (l.value - r.value).abs() > TOLERANCE {
+            format!("The path jumps from {} to {} at t = {}", l.value, r.value, at)
+        } else if (l.slope - r.slope).abs() > TOLERANCE {
+            format!("The pieces meet at t = {} with slopes {} and {}", at, l.slope, r.slope)
+        } else {
+            continue;
+        };
+        issues.push(SmoothnessIssue {
+            reason,
+            span: Span { start: left.span.start, end: right.span.end },
+            evidence: Some(evidence(&|t| path.eval(t), at, path.domain())),
+        });
+    }
+    Ok(report(&path.source, issues))
+}
+
+fn report(source: &str, issues: Vec<SmoothnessIssue>) -> SmoothnessReport {
+    SmoothnessReport { source: source.to_string(), smooth: issues.is_empty(), issues }
+}
+
+/// The first point in `[a, b]` where `gap` changes sign
+fn crossing(gap: impl Fn(f64) -> f64, a: f64, b: f64) -> Option<f64> {
+    let h = (b - a) / PROBE_INTERVALS as f64;
+    (0..PROBE_INTERVALS).find_map(|i| {
+        let (mut lo, mut hi) = (a + i as f64 * h, a + (i + 1) as f64 * h);
+        let (g_lo, g_hi) = (gap(lo), gap(hi));
+        if g_lo == 0.0 {
+            return Some(lo);
+        }
+        let sign_change = g_lo * g_hi < 0.0;
+        if !sign_change {
+            return None;
+        }
+        for _ in 0..60 {
+            let mid = 0.5 * (lo + hi);
+            if (gap(mid) < 0.0) == (g_lo < 0.0) { lo = mid } else { hi = mid }
+        }
+        Some(0.5 * (lo + hi))
+    })
+}
+
+/// One-sided slopes at `at` and the zoomed plot, kept inside `domain`
+fn evidence(f: &dyn Fn(f64) -> f64, at: f64, domain: (f64, f64)) -> Evidence {
+    let (lo, hi) = ((at - ZOOM).max(domain.0), (at + ZOOM).min(domain.1));
+    let xs = (0..PLOT_POINTS).map(|i| lo + (hi - lo) * i as f64 / (PLOT_POINTS - 1) as f64);
+    let finite = |p: &[f64; 2]| p[1].is_finite();
+    Evidence {
+        at,
+        value: f(at),
+        left_slope: (f(at) - f(at - STEP)) / STEP,
+        right_slope: (f(at + STEP) - f(at)) / STEP,
+        samples: xs.clone().map(|x| [x, f(x)]).filter(finite).collect(),
+        derivative: xs.map(|x| [x, (f(x + STEP) - f(x - STEP)) / (2.0 * STEP)]).filter(finite).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_kinked_path_is_localized() {
+        let report = explain_smoothness("t on [0, 0.5]; 1 - t on [0.5, 1]").unwrap();
+        assert!(!report.smooth);
+        assert_eq!(report.issues.len(), 1);
+        let issue = &report.issues[0];
+        assert_eq!(issue.span, Span { start: 0, end: 32 });
+        let evidence = issue.evidence.as_ref().unwrap();
+        assert!(close(evidence.at, 0.5) && close(evidence.value, 0.5));
+        assert!(close(evidence.left_slope, 1.0), "{}", evidence.left_slope);
+        assert!(close(evidence.right_slope, -1.0), "{}", evidence.right_slope);
+        // The plot stays in the path's domain and the derivative flips sign across the join
+        assert!(evidence.samples.iter().all(|p| (0.25..=0.75).contains(&p[0])));
+        assert!(evidence.derivative.first().unwrap()[1] > 0.0 && evidence.derivative.last().unwrap()[1] < 0.0);
+
+        let jump = explain_smoothness("t on [0, 0.5]; 2 on [0.5, 1]").unwrap();
+        assert!(jump.issues[0].reason.contains("jumps"));
+        let smooth = explain_smoothness("t² on [0, 1]; 2t - 1 on [1, 2]").unwrap();
+        assert!(smooth.smooth);
+    }
+
+    #[test]
+    fn test_smooth_function_has_no_issues() {
+        let report = explain_smoothness("sin(x) * exp(-x²)").unwrap();
+        assert!(report.smooth && report.issues.is_empty());
+        // A constant built from `min` does not bend either
+        assert!(explain_smoothness("0.3 ∧ 0.6").unwrap().smooth);
+    }
+
+    #[test]
+    fn test_rejected_term_carries_its_span() {
+        let source = "sin(x) + max(x, 0)";
+        let report = explain_smoothness(source).unwrap();
+        assert!(!report.smooth);
+        let issue = &report.issues[0];
+        assert_eq!(issue.span, Span { start: 9, end: 18 });
+        assert!(issue.reason.contains("max"));
+        let evidence = issue.evidence.as_ref().unwrap();
+        assert!(close(evidence.at, 0.0));
+        assert!(close(evidence.left_slope, 1.0) && close(evidence.right_slope, 2.0));
+
+        let infix = explain_smoothness("1 + (x ∨ x²)").unwrap();
+        assert_eq!(infix.issues[0].span, Span { start: 5, end: 11 });
+        // `∨` of arguments that never meet: still rejected, but nothing to show
+        let apart = explain_smoothness("max(x² + 1, 0)").unwrap();
+        assert!(!apart.smooth && apart.issues[0].evidence.is_none());
+        assert!(matches!(explain_smoothness("t on [1, 0]"), Err(LiteError::Parse { .. })));
+    }
+}
@@ -1,34 +1,84 @@
 //! Type checker module for SCTT
 //! Bidirectional type checking with smooth and cubical features
 
-use wasm_bindgen::prelude::*;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-// Simplified types for the checker (since sctt_core isn't compiled yet)
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+use meta::{Metas, Occurs};
+use syntax::{Expr, ExprKind};
+
+#[cfg(feature = "serde")]
+pub mod checker;
+mod boundary;
+#[cfg(all(feature = "smooth", feature = "cubical", feature = "serde"))]
+pub mod coherence;
+pub mod convert;
+mod equality;
+#[cfg(feature = "lite")]
+pub mod explain;
+#[cfg(feature = "lite")]
+pub mod lite;
+mod meta;
+#[cfg(feature = "serde")]
+pub mod response;
+#[cfg(feature = "serde")]
+pub mod session;
+mod syntax;
+#[cfg(feature = "wasm-bindings")]
+mod wasm;
+#[cfg(feature = "wasm-bindings")]
+pub use wasm::*;
+#[cfg(feature = "serde")]
+pub use checker::{Checker, TypeAt};
+#[cfg(feature = "serde")]
+pub use response::CheckResponse;
+#[cfg(feature = "serde")]
+pub use session::CheckerSession;
+pub use convert::{Unrepresentable, UnsolvedMeta};
+pub use equality::type_diff;
+
+/// The fragment of [`sctt_core::Type`] the front ends check against; see
+/// [`convert`] for the way between the two
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Type {
     Real,
     Interval,
     Function { domain: Box<Type>, codomain: Box<Type> },
-    Path { space: Box<Type> },
+    /// `Path A`, or `Path A a b` from the point written `a` to `b`
+    Path { space: Box<Type>, endpoints: Option<(String, String)> },
     Smooth(Box<Type>),
     Universe,
+    /// `?n`, a type checking has yet to work out; none is left in a type
+    /// the checker hands back
+    Meta(u32),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Names and their types, innermost last; a name bound twice is the later
+/// binding until that goes out of scope
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Context {
     bindings: Vec<(String, Type)>,
+    /// How many bindings there were as each open scope was entered
+    scopes: Vec<usize>,
 }
 
+/// A [`Context`] as it was, for [`Context::restore`]
+#[derive(Debug, Clone)]
+pub struct Snapshot(Context);
+
 impl Context {
     pub fn new() -> Self {
-        Context { bindings: Vec::new() }
+        Context { bindings: Vec::new(), scopes: Vec::new() }
     }
 
     pub fn add(&mut self, name: String, ty: Type) {
         self.bindings.push((name, ty));
     }
 
+    /// The innermost binding of `name`
     pub fn lookup(&self, name: &str) -> Option<&Type> {
         self.bindings
             .iter()
@@ -36,6 +86,187 @@ impl Context {
             .find(|(n, _)| n == name)
             .map(|(_, ty)| ty)
     }
+
+    /// Drop the innermost binding of `name`, uncovering any it shadowed;
+    /// whether there was one
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(at) = self.bindings.iter().rposition(|(n, _)| n == name) else {
+            return false;
+        };
+        self.bindings.remove(at);
+        for start in self.scopes.iter_mut().filter(|start| **start > at) {
+            *start -= 1;
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+        self.scopes.clear();
+    }
+
+    /// Open a scope: what is bound from here on goes at [`Context::exit_scope`]
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(self.bindings.len());
+    }
+
+    /// Close the innermost open scope, dropping what was bound in it; with
+    /// none open, nothing happens
+    pub fn exit_scope(&mut self) {
+        if let Some(start) = self.scopes.pop() {
+            self.bindings.truncate(start);
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.clone())
+    }
+
+    /// Put the bindings and scopes back as they were at `snapshot`
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        *self = snapshot.0;
+    }
+}
+
+/// Names typed as smooth real functions unless the context binds them
+const SMOOTH_PRIMITIVES: &[&str] = &["sin", "cos", "tan", "exp", "ln", "sqrt"];
+
+/// Names typed as real functions, of one argument or (`max`, `min`) two,
+/// that are not smooth, unless the context binds them
+const ROUGH_PRIMITIVES: &[&str] = &["abs", "floor", "max", "min"];
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CheckError {
+    #[error("{message} at position {position}")]
+    Parse { position: usize, message: String },
+
+    #[error("Unbound variable `{name}` at position {position}")]
+    Unbound { name: String, position: usize },
+
+    /// Where the types part below the top, the message says so too; see
+    /// [`type_diff`]
+    #[error(
+        "Expected {}, found {} at position {position}{}",
+        format_type(expected),
+        format_type(found),
+        equality::located_diff(expected, found)
+    )]
+    Mismatch { expected: Type, found: Type, position: usize },
+
+    #[error("{} is not a function, at position {position}", format_type(found))]
+    NotAFunction { found: Type, position: usize },
+
+    #[error("{} is not a path, at position {position}", format_type(found))]
+    NotAPath { found: Type, position: usize },
+
+    /// A path abstraction is `found` at its end `at`, 0 or 1, where its
+    /// type says `expected`
+    #[error("The path should be {expected} at {at}, but is {found}, at position {position}")]
+    Endpoint { at: u8, expected: String, found: String, position: usize },
+
+    /// `?meta` would have to be `ty`, which contains it
+    #[error("`?{meta}` would have to be {}, which contains it, at position {position}", format_type(ty))]
+    Occurs { meta: u32, ty: Type, position: usize },
+
+    /// What the expression from `position` to `end` is, nothing says
+    #[error("Cannot tell the type of the expression at position {position}")]
+    Unsolved { position: usize, end: usize },
+
+    /// The checker broke one of its own invariants; a bug, not a mistake
+    /// in the source
+    #[error("Internal checker error: {message}, at position {position}")]
+    Internal { message: String, position: usize },
+}
+
+impl CheckError {
+    /// The character offset the error is reported at
+    pub fn position(&self) -> usize {
+        match self {
+            CheckError::Parse { position, .. }
+            | CheckError::Unbound { position, .. }
+            | CheckError::Mismatch { position, .. }
+            | CheckError::NotAFunction { position, .. }
+            | CheckError::NotAPath { position, .. }
+            | CheckError::Endpoint { position, .. }
+            | CheckError::Occurs { position, .. }
+            | CheckError::Unsolved { position, .. }
+            | CheckError::Internal { position, .. } => *position,
+        }
+    }
+
+    /// What kind of error it is, as a stable name for programs
+    pub fn code(&self) -> &'static str {
+        match self {
+            CheckError::Parse { .. } => "parse",
+            CheckError::Unbound { .. } => "unbound",
+            CheckError::Mismatch { .. } => "mismatch",
+            CheckError::NotAFunction { .. } => "not_a_function",
+            CheckError::NotAPath { .. } => "not_a_path",
+            CheckError::Endpoint { .. } => "endpoint",
+            CheckError::Occurs { .. } => "occurs",
+            CheckError::Unsolved { .. } => "unsolved",
+            CheckError::Internal { .. } => "internal",
+        }
+    }
+}
+
+/// Something wrong with a checked expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub error: CheckError,
+    /// A fix to offer
+    pub suggestion: Option<String>,
+}
+
+/// A sub-expression's extent, as character offsets with the end exclusive,
+/// and its type
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TypedSpan {
+    pub start: usize,
+    pub end: usize,
+    pub ty: Type,
+}
+
+/// The part of an expression that keeps it from being smooth, as character
+/// offsets with the end exclusive, and why
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NotSmooth {
+    pub start: usize,
+    pub end: usize,
+    pub reason: String,
+}
+
+impl NotSmooth {
+    fn of(expr: &Expr, reason: String) -> Self {
+        NotSmooth { start: expr.position, end: expr.end, reason }
+    }
+}
+
+/// Why `e : A` does not check
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AnnotationError {
+    /// Reading, or checking, the expression; positions are in `e`
+    #[error("{0}")]
+    Expr(CheckError),
+
+    /// Reading the type; positions are in `A`
+    #[error("In the type: {0}")]
+    Type(CheckError),
+}
+
+/// How `f ∘ g` types
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Composition {
+    pub f_type: Type,
+    pub g_type: Type,
+    /// `g`'s domain to `f`'s codomain, smooth when both are; `None` when
+    /// they do not compose
+    pub composite_type: Option<Type>,
+    /// Why they do not compose
+    pub reason: Option<String>,
 }
 
 /// Type checker for SCTT
@@ -50,172 +281,903 @@ impl TypeChecker {
         }
     }
 
-    /// Simplified type inference
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    /// [`TypeChecker::infer`], with the error as a message
     pub fn infer_expr(&self, expr: &str) -> Result<Type, String> {
-        if expr.contains("sin") || expr.contains("cos") {
-            Ok(Type::Smooth(Box::new(Type::Function {
-                domain: Box::new(Type::Real),
-                codomain: Box::new(Type::Real),
-            })))
-        } else if expr.contains("Path") {
-            Ok(Type::Path {
-                space: Box::new(Type::Real),
-            })
-        } else if expr.contains("λ") {
-            Ok(Type::Function {
-                domain: Box::new(Type::Real),
-                codomain: Box::new(Type::Real),
-            })
-        } else if expr == "0" || expr == "1" {
-            Ok(Type::Interval)
-        } else {
-            Ok(Type::Real)
+        self.infer(expr).map_err(|e| e.to_string())
+    }
+
+    /// The type of the expression `source` in the checker's context, or
+    /// the first of its [problems](TypeChecker::diagnose). Literals are
+    /// real numbers, and those in `[0, 1]` also check as interval points;
+    /// an unannotated `λ` takes an argument of the type its uses say, or a
+    /// real one when they say nothing, and is smooth when its body is (see
+    /// [`TypeChecker::why_not_smooth`]); points of the interval
+    /// are accepted where reals are, and smooth functions where functions are.
+    pub fn infer(&self, source: &str) -> Result<Type, CheckError> {
+        let (ty, problems) = self.diagnose(source);
+        match problems.into_iter().next() {
+            Some(first) => Err(first.error),
+            None => ty.ok_or_else(|| CheckError::Internal {
+                message: "no type and no problem reported".to_string(),
+                position: 0,
+            }),
         }
     }
+
+    /// Every problem with `source`, in order, and its type when there are
+    /// none. Checking carries on past an ill-typed part, so independent
+    /// mistakes are all reported; a parse error stops it.
+    pub fn diagnose(&self, source: &str) -> (Option<Type>, Vec<Problem>) {
+        let expr = match syntax::parse(source) {
+            Ok(expr) => expr,
+            Err(error) => return (None, vec![Problem { error, suggestion: None }]),
+        };
+        let mut session = Session::new(self.context.clone());
+        let ty = session.synth(&expr);
+        session.finish();
+        let ty = ty.map(|ty| session.metas.instantiate(&ty));
+        (ty.filter(|_| session.problems.is_empty()), session.problems)
+    }
+
+    /// The type of every sub-expression of `source` that has one, inner
+    /// ones first, whether or not the rest is well-typed; a parse error is
+    /// the only failure
+    pub fn elaborate(&self, source: &str) -> Result<Vec<TypedSpan>, CheckError> {
+        let mut session = Session::new(self.context.clone());
+        session.synth(&syntax::parse(source)?);
+        session.finish();
+        Ok(session.typed)
+    }
+
+    /// Why the function `source` is not smooth; `None` when it is, and for
+    /// anything that is not a well-typed function
+    pub fn why_not_smooth(&self, source: &str) -> Result<Option<NotSmooth>, CheckError> {
+        let expr = syntax::parse(source)?;
+        let mut session = Session::new(self.context.clone());
+        Ok(match session.synth(&expr) {
+            Some(ty) if !matches!(ty, Type::Smooth(_)) && as_function(&ty).is_some() => session
+                .rough(&expr)
+                .or_else(|| session.opaque_reason(&expr).map(|reason| NotSmooth::of(&expr, reason))),
+            _ => None,
+        })
+    }
+
+    /// The innermost typed sub-expression of `source` around the
+    /// character at `offset`
+    pub fn type_at(&self, source: &str, offset: usize) -> Option<TypedSpan> {
+        let typed = self.elaborate(source).ok()?;
+        typed.into_iter().filter(|t| (t.start..t.end).contains(&offset)).min_by_key(|t| t.end - t.start)
+    }
+
+    /// Check `source` against the type written `expected`, pushing the
+    /// type into binders where it can and otherwise inferring and
+    /// comparing; the type on success
+    pub fn check_against(&self, source: &str, expected: &str) -> Result<Type, AnnotationError> {
+        let expected = syntax::parse_type(expected).map_err(AnnotationError::Type)?;
+        let expr = syntax::parse(source).map_err(AnnotationError::Expr)?;
+        let mut session = Session::new(self.context.clone());
+        session.check(&expr, &expected);
+        session.finish();
+        match session.problems.into_iter().next() {
+            Some(first) => Err(AnnotationError::Expr(first.error)),
+            None => Ok(expected),
+        }
+    }
+
+    /// The types of `f` and `g` and, when `g` lands where `f` starts, of
+    /// `f ∘ g`
+    pub fn compose(&self, f: &str, g: &str) -> Result<Composition, CheckError> {
+        let (f_type, g_type) = (self.infer(f)?, self.infer(g)?);
+        let composite = match (as_function(&f_type), as_function(&g_type)) {
+            (Some((f_domain, f_codomain)), Some((g_domain, g_codomain)))
+                if Session::new(Context::new()).accepts(f_domain, g_codomain) == Ok(true) =>
+            {
+                let (domain, codomain) = (Box::new(g_domain.clone()), Box::new(f_codomain.clone()));
+                let composite = Type::Function { domain, codomain };
+                let smooth = matches!((&f_type, &g_type), (Type::Smooth(_), Type::Smooth(_)));
+                Ok(if smooth { Type::Smooth(Box::new(composite)) } else { composite })
+            }
+            (Some((f_domain, _)), Some((_, g_codomain))) => Err(format!(
+                "`{}` returns {}, but `{}` takes {}",
+                g, format_type(g_codomain), f, format_type(f_domain)
+            )),
+            (None, _) => Err(format!("`{}` has type {}, not a function type", f, format_type(&f_type))),
+            (_, None) => Err(format!("`{}` has type {}, not a function type", g, format_type(&g_type))),
+        };
+        let (composite_type, reason) = match composite {
+            Ok(ty) => (Some(ty), None),
+            Err(reason) => (None, Some(reason)),
+        };
+        Ok(Composition { f_type, g_type, composite_type, reason })
+    }
 }
 
-// WASM bindings
-#[wasm_bindgen]
-pub struct WasmChecker {
-    checker: TypeChecker,
+/// One pass of checking: the bindings in scope and the problems found so
+/// far, with the [metavariables](meta) met. A part already reported as
+/// ill-typed has the placeholder type `None`, which is accepted anywhere, so
+/// that one mistake is reported once and checking carries on past it.
+struct Session {
+    context: Context,
+    problems: Vec<Problem>,
+    /// The sub-expressions typed so far
+    typed: Vec<TypedSpan>,
+    metas: Metas,
 }
 
-#[wasm_bindgen]
-impl WasmChecker {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> WasmChecker {
-        WasmChecker {
-            checker: TypeChecker::new(),
+impl Session {
+    fn new(context: Context) -> Self {
+        Session { context, problems: Vec::new(), typed: Vec::new(), metas: Metas::default() }
+    }
+
+    /// A session to try something in, knowing what this one does so far
+    fn fork(&self) -> Self {
+        Session { metas: self.metas.clone(), ..Session::new(self.context.clone()) }
+    }
+
+    /// Record `error`; the placeholder type
+    fn report(&mut self, error: CheckError) -> Option<Type> {
+        let error = self.instantiate_error(error);
+        let suggestion = suggest(&self.context, &error);
+        self.problems.push(Problem { error, suggestion });
+        None
+    }
+
+    /// `?meta := ty`, or the occurs failure reported at `position`
+    fn solve(&mut self, meta: u32, ty: &Type, position: usize) -> Option<()> {
+        match self.metas.solve(meta, ty) {
+            Ok(()) => Some(()),
+            Err(Occurs { meta, ty }) => self.report(CheckError::Occurs { meta, ty, position }).map(drop),
+        }
+    }
+
+    /// `f` with `name : ty` bound, in a scope of its own
+    fn scoped<T>(&mut self, name: &str, ty: Type, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.context.enter_scope();
+        self.context.add(name.to_string(), ty);
+        let result = f(self);
+        self.context.exit_scope();
+        result
+    }
+
+    fn record(&mut self, expr: &Expr, ty: &Type) {
+        self.typed.push(TypedSpan { start: expr.position, end: expr.end, ty: ty.clone() });
+    }
+
+    fn synth(&mut self, expr: &Expr) -> Option<Type> {
+        let ty = self.synth_node(expr);
+        if let Some(ty) = &ty {
+            self.record(expr, ty);
         }
+        ty
     }
 
-    pub fn add_binding(&mut self, name: String, type_str: String) {
-        let ty = parse_type(&type_str);
-        self.checker.context.add(name, ty);
+    fn synth_node(&mut self, expr: &Expr) -> Option<Type> {
+        let position = expr.position;
+        match &expr.kind {
+            ExprKind::Num(_) => Some(Type::Real),
+            ExprKind::Var(name) => match self.context.lookup(name) {
+                Some(ty) => Some(ty.clone()),
+                None if SMOOTH_PRIMITIVES.contains(&name.as_str()) => Some(Type::Smooth(Box::new(real_function()))),
+                None if name == "max" || name == "min" => {
+                    Some(Type::Function { domain: Box::new(Type::Real), codomain: Box::new(real_function()) })
+                }
+                None if ROUGH_PRIMITIVES.contains(&name.as_str()) => Some(real_function()),
+                None => self.report(CheckError::Unbound { name: name.clone(), position }),
+            },
+            ExprKind::Lambda(name, body) => {
+                // What the parameter is, its uses say
+                let domain = self.metas.fresh(expr, true);
+                let codomain = self.scoped(name, domain.clone(), |session| session.synth(body))?;
+                let function = Type::Function { domain: Box::new(domain.clone()), codomain: Box::new(codomain) };
+                let smooth = self.scoped(name, domain, |session| session.rough(body)).is_none();
+                Some(if smooth { Type::Smooth(Box::new(function)) } else { function })
+            }
+            ExprKind::PathAbs(name, body) => {
+                let space = self.scoped(name, Type::Interval, |session| session.synth(body))?;
+                Some(Type::Path { space: Box::new(space), endpoints: None })
+            }
+            ExprKind::App(function, argument) => {
+                // `(λx. e) a`: first try `x` at the type of `a`, which the
+                // binder alone cannot say; failing that, as any application
+                if let ExprKind::Lambda(name, body) = &function.kind {
+                    let mut trial = self.fork();
+                    let redex = trial.synth(argument).and_then(|ty| trial.scoped(name, ty, |trial| trial.synth(body)));
+                    if trial.problems.is_empty() && redex.is_some() {
+                        self.typed.extend(trial.typed);
+                        self.metas = trial.metas;
+                        return redex;
+                    }
+                }
+                let found = match self.synth(function).map(|ty| self.metas.resolve(&ty)) {
+                    // A function nothing is known of yet: its parts are to be found
+                    Some(Type::Meta(meta)) => {
+                        let (domain, codomain) = (self.metas.fresh(expr, false), self.metas.fresh(expr, false));
+                        let ty = Type::Function { domain: Box::new(domain), codomain: Box::new(codomain) };
+                        self.solve(meta, &ty, function.position)?;
+                        Some(ty)
+                    }
+                    found => found,
+                };
+                if let Some((domain, codomain)) = found.as_ref().and_then(as_function) {
+                    let (domain, codomain) = (domain.clone(), codomain.clone());
+                    self.check(argument, &domain);
+                    return Some(codomain);
+                }
+                if let Some(found) = found {
+                    self.report(CheckError::NotAFunction { found, position: function.position });
+                }
+                // The argument may have problems of its own
+                self.synth(argument);
+                None
+            }
+            ExprKind::At(path, point) => {
+                let space = match self.synth(path).map(|ty| self.metas.resolve(&ty)) {
+                    Some(Type::Path { space, .. }) => Some(*space),
+                    Some(Type::Meta(meta)) => {
+                        let space = self.metas.fresh(expr, false);
+                        let ty = Type::Path { space: Box::new(space.clone()), endpoints: None };
+                        self.solve(meta, &ty, path.position)?;
+                        Some(space)
+                    }
+                    Some(found) => self.report(CheckError::NotAPath { found, position: path.position }),
+                    None => None,
+                };
+                self.check(point, &Type::Interval);
+                space
+            }
+            ExprKind::Neg(a) => {
+                self.check(a, &Type::Real);
+                Some(Type::Real)
+            }
+            ExprKind::Binary(_, a, b) => {
+                self.check(a, &Type::Real);
+                self.check(b, &Type::Real);
+                Some(Type::Real)
+            }
+        }
     }
 
-    pub fn check_expr(&mut self, expr: String) -> JsValue {
-        let result = self.checker.infer_expr(&expr);
+    fn check(&mut self, expr: &Expr, expected: &Type) {
+        let expected = &self.metas.resolve(expected);
+        match (&expr.kind, expected) {
+            (ExprKind::Num(n), Type::Interval) if (0.0..=1.0).contains(n) => {}
+            (ExprKind::Lambda(name, body), Type::Function { domain, codomain }) => {
+                self.scoped(name, (**domain).clone(), |session| session.check(body, codomain))
+            }
+            (ExprKind::PathAbs(name, body), Type::Path { space, endpoints }) => {
+                let problems = self.problems.len();
+                self.scoped(name, Type::Interval, |session| session.check(body, space));
+                // Endpoints of an ill-typed body would be noise
+                if let Some((start, end)) = endpoints.as_ref().filter(|_| self.problems.len() == problems) {
+                    self.check_endpoint(name, body, 0, start);
+                    self.check_endpoint(name, body, 1, end);
+                }
+            }
+            _ => {
+                let position = expr.position;
+                let Some(found) = self.synth(expr) else { return };
+                match self.accepts(expected, &found) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.report(CheckError::Mismatch { expected: expected.clone(), found, position });
+                    }
+                    Err(Occurs { meta, ty }) => {
+                        self.report(CheckError::Occurs { meta, ty, position });
+                    }
+                }
+                return;
+            }
+        }
+        self.record(expr, expected);
+    }
+
+    /// Whether the path `⟨name⟩ body` is the point written `expected` at
+    /// `at`
+    fn check_endpoint(&mut self, name: &str, body: &Expr, at: u8, expected: &str) {
+        let written = match syntax::parse(expected) {
+            Ok(written) => written,
+            Err(error) => {
+                self.report(error);
+                return;
+            }
+        };
+        let found = boundary::at(&self.context, body, name, at.into());
+        if !boundary::agree(&self.context, &found, &written) {
+            let (expected, found) = (expected.to_string(), found.to_string());
+            self.report(CheckError::Endpoint { at, expected, found, position: body.position });
+        }
+    }
 
-        match result {
-            Ok(ty) => serde_wasm_bindgen::to_value(&serde_json::json!({
-                "success": true,
-                "type": format_type(&ty)
-            })).unwrap(),
-            Err(e) => serde_wasm_bindgen::to_value(&serde_json::json!({
-                "success": false,
-                "error": e
-            })).unwrap()
+    /// Whether a value of type `found` may be used where `expected` is
+    /// asked for, solving metas to make it so: points of the interval are
+    /// reals, smooth functions are functions, and a path's endpoints may be
+    /// forgotten but not made up
+    fn accepts(&mut self, expected: &Type, found: &Type) -> Result<bool, Occurs> {
+        let (expected, found) = (self.metas.resolve(expected), self.metas.resolve(found));
+        match (&expected, &found) {
+            (Type::Meta(_), _) | (_, Type::Meta(_)) => self.unify(&expected, &found),
+            (Type::Real, Type::Interval) => Ok(true),
+            (Type::Path { space, endpoints }, Type::Path { space: found_space, endpoints: found_endpoints }) => {
+                let endpoints = match (endpoints, found_endpoints) {
+                    (None, _) => true,
+                    (Some(_), Some(_)) => endpoints_agree(endpoints, found_endpoints),
+                    (Some(_), None) => false,
+                };
+                Ok(endpoints && self.unify(space, found_space)?)
+            }
+            (Type::Smooth(_), Type::Smooth(_)) => self.unify(&expected, &found),
+            (expected, Type::Smooth(inner)) => self.unify(expected, inner),
+            _ => self.unify(&expected, &found),
         }
     }
 
-    pub fn check_coherence(&self, smooth_expr: String, cubical_expr: String) -> JsValue {
-        // Check smooth-cubical coherence (simplified)
-        let coherent = smooth_expr.len() > 0 && cubical_expr.len() > 0;
-        
-        serde_wasm_bindgen::to_value(&serde_json::json!({
-            "coherent": coherent,
-            "smooth": smooth_expr,
-            "cubical": cubical_expr,
-            "message": if coherent {
-                "Smooth and cubical structures are coherent!"
-            } else {
-                "Coherence check failed"
+    /// Whether `a` and `b` are the same type, solving metas to make them so
+    fn unify(&mut self, a: &Type, b: &Type) -> Result<bool, Occurs> {
+        let (a, b) = (self.metas.resolve(a), self.metas.resolve(b));
+        Ok(match (&a, &b) {
+            (Type::Meta(x), Type::Meta(y)) => {
+                self.metas.union(*x, *y);
+                true
+            }
+            (Type::Meta(meta), ty) | (ty, Type::Meta(meta)) => {
+                self.metas.solve(*meta, ty)?;
+                true
+            }
+            (Type::Function { domain, codomain }, Type::Function { domain: d, codomain: c }) => {
+                self.unify(domain, d)? && self.unify(codomain, c)?
+            }
+            (Type::Path { space, endpoints }, Type::Path { space: s, endpoints: e }) => {
+                endpoints_agree(endpoints, e) && self.unify(space, s)?
+            }
+            (Type::Smooth(a), Type::Smooth(b)) => self.unify(a, b)?,
+            _ => a == b,
+        })
+    }
+
+    /// Settle the metas once checking is done: a parameter nothing
+    /// constrained falls back to [`meta::UNCONSTRAINED_PARAMETER`], and any
+    /// other still unknown is reported where it arose, unless something else
+    /// is wrong already. What was learned goes into the types found.
+    fn finish(&mut self) {
+        let unsolved = self.metas.finish();
+        if self.problems.is_empty() {
+            for (position, end) in unsolved {
+                self.report(CheckError::Unsolved { position, end });
             }
-        })).unwrap()
+        }
+        for typed in &mut self.typed {
+            typed.ty = self.metas.instantiate(&typed.ty);
+        }
+        let problems = std::mem::take(&mut self.problems);
+        self.problems = problems
+            .into_iter()
+            .map(|Problem { error, suggestion }| Problem { error: self.instantiate_error(error), suggestion })
+            .collect();
     }
+
+    /// `error` with what is known of the metas in its types filled in
+    fn instantiate_error(&mut self, error: CheckError) -> CheckError {
+        match error {
+            CheckError::Mismatch { expected, found, position } => {
+                let (expected, found) = (self.metas.instantiate(&expected), self.metas.instantiate(&found));
+                CheckError::Mismatch { expected, found, position }
+            }
+            CheckError::NotAFunction { found, position } => {
+                CheckError::NotAFunction { found: self.metas.instantiate(&found), position }
+            }
+            CheckError::NotAPath { found, position } => {
+                CheckError::NotAPath { found: self.metas.instantiate(&found), position }
+            }
+            error => error,
+        }
+    }
+}
+
+impl Session {
+    /// Why `expr` is not known to be smooth in the variables bound around
+    /// it. Numbers, names, arithmetic and path application are smooth;
+    /// applications are smooth when the function and the argument are.
+    fn rough(&mut self, expr: &Expr) -> Option<NotSmooth> {
+        match &expr.kind {
+            ExprKind::Num(_) | ExprKind::Var(_) => None,
+            ExprKind::Lambda(name, body) => self.scoped(name, Type::Real, |session| session.rough(body)),
+            ExprKind::PathAbs(name, body) => self.scoped(name, Type::Interval, |session| session.rough(body)),
+            ExprKind::App(function, argument) => self.rough_application(expr, function, argument),
+            ExprKind::At(a, b) | ExprKind::Binary(_, a, b) => self.rough(a).or_else(|| self.rough(b)),
+            ExprKind::Neg(a) => self.rough(a),
+        }
+    }
+
+    fn rough_application(&mut self, expr: &Expr, function: &Expr, argument: &Expr) -> Option<NotSmooth> {
+        if let ExprKind::App(head, first) = &function.kind {
+            if let Some(name) = primitive(&self.context, head).filter(|name| *name == "max" || *name == "min") {
+                // `max(a, a)` is just `a`
+                if first.same_as(argument) {
+                    return self.rough(argument);
+                }
+                return Some(NotSmooth::of(expr, format!("`{}` has a corner where its arguments meet", name)));
+            }
+        }
+        let head = match &function.kind {
+            // A smooth body is a smooth function
+            ExprKind::Lambda(..) | ExprKind::PathAbs(..) => self.rough(function),
+            _ => self.rough(function).or_else(|| {
+                let reason = self.opaque_reason(function)?;
+                Some(NotSmooth::of(expr, reason))
+            }),
+        };
+        head.or_else(|| self.rough(argument))
+    }
+
+    /// Why the function `function` computes, whose insides are smooth, is
+    /// not known to be smooth: its type does not say so
+    fn opaque_reason(&mut self, function: &Expr) -> Option<String> {
+        match primitive(&self.context, function) {
+            Some("abs") => return Some("`abs` has a corner at 0".to_string()),
+            Some("floor") => return Some("`floor` jumps at every integer".to_string()),
+            Some(name @ ("max" | "min")) => return Some(format!("`{}` has a corner where its arguments meet", name)),
+            _ => {}
+        }
+        // An ill-typed function has been reported already
+        let mut trial = self.fork();
+        match trial.synth(function).map(|ty| trial.metas.resolve(&ty))? {
+            Type::Smooth(_) => None,
+            _ => Some(match &function.kind {
+                ExprKind::Var(name) => format!("`{}` is not known to be smooth", name),
+                _ => "The applied function is not known to be smooth".to_string(),
+            }),
+        }
+    }
+}
+
+/// The primitive `expr` names, unless `context` binds the name
+fn primitive<'e>(context: &Context, expr: &'e Expr) -> Option<&'e str> {
+    match &expr.kind {
+        ExprKind::Var(name) if context.lookup(name).is_none() => Some(name),
+        _ => None,
+    }
+}
+
+/// Whether two paths' endpoints are both missing, or both there and agree
+fn endpoints_agree(a: &Option<(String, String)>, b: &Option<(String, String)>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some((a0, a1)), Some((b0, b1))) => boundary::written_agree(a0, b0) && boundary::written_agree(a1, b1),
+        _ => false,
+    }
+}
+
+/// A fix for `error`, where one is obvious: a bound name or primitive an
+/// unbound name is a typo of, or applying a function used as a number
+fn suggest(context: &Context, error: &CheckError) -> Option<String> {
+    match error {
+        CheckError::Unbound { name, .. } => {
+            let bound = context.bindings.iter().map(|(n, _)| n.as_str());
+            let (distance, closest) = bound
+                .chain(SMOOTH_PRIMITIVES.iter().chain(ROUGH_PRIMITIVES).copied())
+                .map(|candidate| (edit_distance(name, candidate), candidate))
+                .min()?;
+            (distance <= 2 && distance < name.chars().count()).then(|| format!("Did you mean `{}`?", closest))
+        }
+        CheckError::Mismatch { expected: Type::Real, found, .. } if as_function(found).is_some() => {
+            Some("Apply the function to an argument, as in `f(x)`".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Single-character insertions, deletions and substitutions from `a` to `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
 }
 
-// Helper to parse type strings
-fn parse_type(s: &str) -> Type {
-    match s {
-        "Real" | "ℝ" => Type::Real,
-        "I" | "Interval" => Type::Interval,
-        s if s.starts_with("C∞") => Type::Smooth(Box::new(Type::Real)),
-        _ => Type::Universe,
+/// The domain and codomain of a function type, smooth or not
+fn as_function(ty: &Type) -> Option<(&Type, &Type)> {
+    match ty {
+        Type::Function { domain, codomain } => Some((domain, codomain)),
+        Type::Smooth(inner) => as_function(inner),
+        _ => None,
     }
 }
 
+fn real_function() -> Type {
+    Type::Function { domain: Box::new(Type::Real), codomain: Box::new(Type::Real) }
+}
+
+/// The type written `source`: `ℝ`, `I`, `Type`, paths, arrows, smooth
+/// arrows `C∞(A, B)` and non-dependent `Π(x : A). B`, as the
+/// [syntax](syntax) module describes; a parse error says where and why not
+pub fn parse_type(source: &str) -> Result<Type, CheckError> {
+    syntax::parse_type(source)
+}
+
 // Helper to format types
-fn format_type(ty: &Type) -> String {
+pub fn format_type(ty: &Type) -> String {
     match ty {
         Type::Real => "ℝ".to_string(),
         Type::Interval => "I".to_string(),
-        Type::Function { domain, codomain } => {
-            format!("{} → {}", format_type(domain), format_type(codomain))
+        // `→` groups to the right, so a function to the left of one, or a
+        // path's space, is bracketed
+        Type::Function { domain, codomain } => format!("{} → {}", atom(domain), format_type(codomain)),
+        Type::Path { space, endpoints: None } => format!("Path {}", atom(space)),
+        Type::Path { space, endpoints: Some((start, end)) } => {
+            // Anything but a number or a name is bracketed
+            let point = |p: &str| match p.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '_') {
+                true => p.to_string(),
+                false => format!("({})", p),
+            };
+            format!("Path {} {} {}", atom(space), point(start), point(end))
         }
-        Type::Path { space } => format!("Path {}", format_type(space)),
         Type::Smooth(inner) => format!("C∞({})", format_type(inner)),
         Type::Universe => "Type".to_string(),
+        Type::Meta(meta) => format!("?{}", meta),
     }
 }
 
-// Global exported function
-#[wasm_bindgen]
-pub fn type_check(expr: &str) -> JsValue {
-    let checker = TypeChecker::new();
-    
-    let result = checker.infer_expr(expr);
-
-    match result {
-        Ok(ty) => {
-            let type_str = format_type(&ty);
-            let description = match &ty {
-                Type::Smooth(_) => "Smooth function from reals to reals",
-                Type::Path { .. } => "Continuous path in space",
-                Type::Function { .. } => "Function type",
-                Type::Interval => "Interval value between 0 and 1",
-                Type::Real => "Real number",
-                Type::Universe => "Type universe",
-            };
-            
-            serde_wasm_bindgen::to_value(&serde_json::json!({
-                "success": true,
-                "type": type_str,
-                "description": description,
-                "expression": expr
-            })).unwrap()
-        }
-        Err(e) => serde_wasm_bindgen::to_value(&serde_json::json!({
-            "success": false,
-            "error": e,
-            "expression": expr
-        })).unwrap()
-    }
-}
-
-#[wasm_bindgen]
-pub fn check_composition(f: &str, g: &str) -> JsValue {
-    // Check if functions can be composed
-    let composable = f.len() > 0 && g.len() > 0;
-    
-    serde_wasm_bindgen::to_value(&serde_json::json!({
-        "f": f,
-        "g": g,
-        "composable": composable,
-        "type": if composable { "C∞(ℝ → ℝ)" } else { "Error" },
-        "message": if composable {
-            format!("({}) ∘ ({}) is well-typed!", f, g)
-        } else {
-            "Composition failed type checking".to_string()
-        }
-    })).unwrap()
-}
-
-#[wasm_bindgen(start)]
-pub fn init() {
-    #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
+fn atom(ty: &Type) -> String {
+    match ty {
+        Type::Function { .. } => format!("({})", format_type(ty)),
+        ty => format_type(ty),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn real_to_real() -> Type {
+        real_function()
+    }
+
     #[test]
     fn test_type_inference() {
         let checker = TypeChecker::new();
-        let ty = checker.infer_expr("sin(x)").unwrap();
+        let ty = checker.infer_expr("sin").unwrap();
         assert!(matches!(ty, Type::Smooth(_)));
+        assert_eq!(checker.infer("λx. sin(x)"), Ok(Type::Smooth(Box::new(real_to_real()))));
+    }
+
+    #[test]
+    fn test_inference_table() {
+        let mut checker = TypeChecker::new();
+        let smooth = Type::Smooth(Box::new(real_to_real()));
+        let path = |space: Type| Type::Path { space: Box::new(space), endpoints: None };
+        let constant = Type::Function { domain: Box::new(Type::Real), codomain: Box::new(smooth.clone()) };
+        let constant = Type::Smooth(Box::new(constant));
+        let bindings = [("x", Type::Real), ("sinister", Type::Real), ("p", path(Type::Real)), ("f", real_to_real())];
+        for (name, ty) in bindings {
+            checker.context_mut().add(name.to_string(), ty);
+        }
+        let typed = [
+            ("0", Type::Real),
+            ("1", Type::Real),
+            ("3.5", Type::Real),
+            ("x", Type::Real),
+            ("-x", Type::Real),
+            ("x + sinister", Type::Real),
+            ("x * 2 - 1 / x ^ 2", Type::Real),
+            ("sin", smooth.clone()),
+            ("cos(x)", Type::Real),
+            ("sin (cos x)", Type::Real),
+            ("exp(x) + ln(x)", Type::Real),
+            ("λx. 0", smooth.clone()),
+            ("λy. y * y", smooth.clone()),
+            ("\\x. sin(x)", smooth.clone()),
+            ("λx. f x", real_to_real()),
+            ("(λx. x + 1) 2", Type::Real),
+            ("λx. λy. x", constant),
+            ("f x", Type::Real),
+            ("⟨i⟩ x", path(Type::Real)),
+            ("⟨i⟩ 2 * i", path(Type::Real)),
+            ("⟨i⟩ i", path(Type::Interval)),
+            ("p @ 0", Type::Real),
+            ("(⟨i⟩ sin) @ 1", smooth),
+            ("(⟨i⟩ i) @ 0.5 + 1", Type::Real),
+        ];
+        for (source, expected) in typed {
+            assert_eq!(checker.infer(source), Ok(expected), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_substrings_do_not_decide_types() {
+        let checker = TypeChecker::new();
+        // Names that merely contain a primitive are just unbound names
+        let unbound = |source: &str, name: &str, position: usize| {
+            let expected = CheckError::Unbound { name: name.to_string(), position };
+            assert_eq!(checker.infer(source), Err(expected), "{source}");
+        };
+        unbound("x + sinister", "x", 0);
+        unbound("cosine", "cosine", 0);
+        unbound("Pathway", "Pathway", 0);
+        unbound("λx. sinh(x)", "sinh", 4);
+        // `λx. 0` is a function, not an interval point
+        assert_eq!(checker.infer_expr("λx. 0").map(|ty| format_type(&ty)), Ok("C∞(ℝ → ℝ)".to_string()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_ill_typed_expressions_are_located() {
+        let checker = TypeChecker::new();
+        let real = Type::Real;
+        assert_eq!(checker.infer("2 3"), Err(CheckError::NotAFunction { found: real.clone(), position: 0 }));
+        assert_eq!(checker.infer("1 @ 0"), Err(CheckError::NotAPath { found: real.clone(), position: 0 }));
+        assert_eq!(
+            checker.infer("(⟨i⟩ i) @ 2"),
+            Err(CheckError::Mismatch { expected: Type::Interval, found: real.clone(), position: 10 })
+        );
+        assert_eq!(
+            checker.infer("1 + sin"),
+            Err(CheckError::Mismatch { expected: real, found: Type::Smooth(Box::new(real_function())), position: 4 })
+        );
+        assert!(matches!(checker.infer("sin(x"), Err(CheckError::Parse { position: 5, .. })));
+        assert_eq!(checker.infer_expr("λx."), Err("Unexpected end of input at position 3".to_string()));
+    }
+
+    #[test]
+    fn test_shadowing_and_scopes() {
+        let mut context = Context::new();
+        context.add("x".to_string(), Type::Real);
+        context.enter_scope();
+        context.add("x".to_string(), Type::Interval);
+        context.add("y".to_string(), Type::Universe);
+        assert_eq!(context.lookup("x"), Some(&Type::Interval));
+        context.exit_scope();
+        assert_eq!(context.lookup("x"), Some(&Type::Real));
+        assert_eq!(context.lookup("y"), None);
+
+        // Removing uncovers the binding underneath
+        context.add("x".to_string(), Type::Universe);
+        assert!(context.remove("x"));
+        assert_eq!(context.lookup("x"), Some(&Type::Real));
+        let snapshot = context.snapshot();
+        context.enter_scope();
+        context.add("z".to_string(), Type::Real);
+        context.clear();
+        assert!(!context.remove("x"));
+        context.restore(snapshot);
+        assert_eq!((context.lookup("x"), context.lookup("z")), (Some(&Type::Real), None));
+    }
+
+    #[test]
+    fn test_binders_do_not_leak() {
+        let mut checker = TypeChecker::new();
+        checker.context_mut().add("x".to_string(), Type::Path { space: Box::new(Type::Real), endpoints: None });
+        // Each binder shadows the path `x` in its body only
+        assert_eq!(checker.infer("(λx. x + 1) 2 + x @ 0"), Ok(Type::Real));
+        assert_eq!(checker.infer("(⟨x⟩ 2 * x) @ 0 + x @ 1"), Ok(Type::Real));
+        assert_eq!(checker.infer("λy. y"), Ok(Type::Smooth(Box::new(real_function()))));
+        assert!(matches!(checker.infer("(λy. y) y"), Err(CheckError::Unbound { position: 8, .. })));
+        // An applied binder takes its argument's type
+        assert_eq!(checker.infer("(λp. p @ 0) x"), Ok(Type::Real));
+        assert_eq!(checker.context().lookup("p"), None);
+    }
+
+    #[test]
+    fn test_composition_types() {
+        let checker = TypeChecker::new();
+        let smooth = Type::Smooth(Box::new(real_function()));
+        let both_smooth = checker.compose("sin", "cos").unwrap();
+        assert_eq!((both_smooth.composite_type, both_smooth.reason), (Some(smooth.clone()), None));
+        // A component not known to be smooth makes the composite a plain function
+        let degraded = checker.compose("sin", "λx. abs(x) * 2").unwrap();
+        assert_eq!((degraded.f_type, degraded.composite_type), (smooth, Some(real_function())));
+
+        let mismatch = checker.compose("λx. x", "λy. ⟨i⟩ y").unwrap();
+        assert_eq!(mismatch.composite_type, None);
+        assert_eq!(mismatch.reason.as_deref(), Some("`λy. ⟨i⟩ y` returns Path ℝ, but `λx. x` takes ℝ"));
+        let not_a_function = checker.compose("1", "sin").unwrap();
+        assert_eq!(not_a_function.reason.as_deref(), Some("`1` has type ℝ, not a function type"));
+        assert!(matches!(checker.compose("sin", "cos("), Err(CheckError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_checking_against_a_written_type() {
+        let checker = TypeChecker::new();
+        let interval_function = Type::Function { domain: Box::new(Type::Interval), codomain: Box::new(Type::Interval) };
+        // The binder takes its domain from the type, where inference says ℝ
+        assert_eq!(checker.check_against("λx. x", "I → I"), Ok(interval_function));
+        assert_eq!(checker.check_against("sin", "ℝ → ℝ"), Ok(real_function()));
+
+        let in_expr = checker.check_against("λx. x +", "ℝ");
+        assert!(matches!(in_expr, Err(AnnotationError::Expr(CheckError::Parse { position: 7, .. }))));
+        let in_type = checker.check_against("1", "Path");
+        assert!(matches!(in_type, Err(AnnotationError::Type(CheckError::Parse { position: 4, .. }))));
+        let mismatch = CheckError::Mismatch { expected: Type::Interval, found: Type::Real, position: 4 };
+        assert_eq!(checker.check_against("⟨i⟩ 2", "Path I"), Err(AnnotationError::Expr(mismatch)));
+    }
+
+    #[test]
+    fn test_checking_carries_on_past_a_problem() {
+        let checker = TypeChecker::new();
+        let errors = |source: &str| checker.diagnose(source).1.into_iter().map(|p| p.error).collect::<Vec<_>>();
+        let smooth = Type::Smooth(Box::new(real_function()));
+        assert_eq!(
+            errors("y + 1 @ 0 - cos"),
+            [
+                CheckError::Unbound { name: "y".to_string(), position: 0 },
+                CheckError::NotAPath { found: Type::Real, position: 4 },
+                CheckError::Mismatch { expected: Type::Real, found: smooth, position: 12 },
+            ]
+        );
+        // What an unbound name is applied to is still checked, but not
+        // complained about as an application
+        assert_eq!(errors("f (1 2)"), [
+            CheckError::Unbound { name: "f".to_string(), position: 0 },
+            CheckError::NotAFunction { found: Type::Real, position: 3 },
+        ]);
+        assert_eq!(checker.diagnose("λx. x"), (Some(Type::Smooth(Box::new(real_function()))), Vec::new()));
+
+        let (_, problems) = checker.diagnose("λx. exq(x) + sin");
+        let suggestions: Vec<_> = problems.iter().map(|p| p.suggestion.as_deref()).collect();
+        assert_eq!(suggestions, [Some("Did you mean `exp`?"), Some("Apply the function to an argument, as in `f(x)`")]);
+    }
+
+    #[test]
+    fn test_types_of_sub_expressions() {
+        let checker = TypeChecker::new();
+        let at = |source: &str, offset: usize| checker.type_at(source, offset).map(|t| (t.start, t.end, t.ty));
+        // The bound `x`, then `x * 2`, then the whole function
+        assert_eq!(at("λx. x * 2", 4), Some((4, 5, Type::Real)));
+        assert_eq!(at("λx. x * 2", 6), Some((4, 9, Type::Real)));
+        assert_eq!(at("λx. x * 2", 1), Some((0, 9, Type::Smooth(Box::new(real_function())))));
+        assert_eq!(at("(⟨i⟩ i) @ 0", 5), Some((5, 6, Type::Interval)));
+        // Nothing is under whitespace outside every node, or past the end
+        assert_eq!((at(" 1", 0), at("1", 1), at("(1", 0)), (None, None, None));
+        // An unbound name has no type, but what it sits in may
+        assert_eq!(at("y + 1", 0), Some((0, 5, Type::Real)));
+    }
+
+    #[test]
+    fn test_smoothness_follows_structure() {
+        let mut checker = TypeChecker::new();
+        checker.context_mut().add("g".to_string(), real_function());
+        let smooth = |source: &str| matches!(checker.infer(source), Ok(Type::Smooth(_)));
+        // Constants, the variable, arithmetic, smooth primitives and their
+        // compositions, including through applied binders
+        let composed = ["λx. -exp(sin(x²))", "λx. (λy. y * y) (cos x)"];
+        for source in ["λx. 3", "λx. x", "λx. x * x + 1 / x - x ^ 3"].into_iter().chain(composed) {
+            assert!(smooth(source), "{source}");
+        }
+        // `max` of an argument with itself does not bend
+        assert!(smooth("λx. max(sin x)(sin x)"));
+        for source in ["λx. abs(x)", "λx. floor x", "λx. max x 0", "λx. g x", "λx. 2 * sin(abs x)", "abs", "g"] {
+            assert!(!smooth(source), "{source}");
+        }
+        // Names that merely contain a primitive decide nothing
+        assert!(checker.infer("λx. absin(x)").is_err());
+    }
+
+    #[test]
+    fn test_why_not_smooth_points_at_the_culprit() {
+        let mut checker = TypeChecker::new();
+        checker.context_mut().add("g".to_string(), real_function());
+        let why = |source: &str| checker.why_not_smooth(source).unwrap().map(|n| (n.start, n.end, n.reason));
+        assert_eq!(why("λx. 1 + abs(x - 1)"), Some((8, 18, "`abs` has a corner at 0".to_string())));
+        assert_eq!(why("λx. max x 0"), Some((4, 11, "`max` has a corner where its arguments meet".to_string())));
+        assert_eq!(why("λx. sin(g(x))"), Some((8, 12, "`g` is not known to be smooth".to_string())));
+        assert_eq!(why("floor"), Some((0, 5, "`floor` jumps at every integer".to_string())));
+        assert_eq!((why("λx. x²"), why("sin"), why("2")), (None, None, None));
+        // A binding of the same name is not the primitive
+        checker.context_mut().add("abs".to_string(), Type::Smooth(Box::new(real_function())));
+        assert_eq!(checker.why_not_smooth("λx. abs x"), Ok(None));
+    }
+
+    #[test]
+    fn test_path_endpoints_are_checked() {
+        let checker = TypeChecker::new();
+        let path = |start: &str, end: &str| Type::Path {
+            space: Box::new(Type::Real),
+            endpoints: Some((start.to_string(), end.to_string())),
+        };
+        assert_eq!(checker.check_against("⟨t⟩ t", "Path ℝ 0 1"), Ok(path("0", "1")));
+        assert_eq!(checker.check_against("⟨t⟩ 2 * π * t", "Path ℝ 0 (2 * π)"), Ok(path("0", "2 * π")));
+        let right = CheckError::Endpoint { at: 1, expected: "2".to_string(), found: "1".to_string(), position: 4 };
+        assert_eq!(checker.check_against("⟨t⟩ t²", "Path ℝ 0 2"), Err(AnnotationError::Expr(right)));
+        let Err(AnnotationError::Expr(left)) = checker.check_against("⟨t⟩ 1 - t", "Path ℝ 0 1") else {
+            panic!("both ends are wrong")
+        };
+        assert_eq!(left.to_string(), "The path should be 0 at 0, but is 1, at position 4");
+    }
+
+    #[test]
+    fn test_path_types_with_endpoints() {
+        let mut checker = TypeChecker::new();
+        checker.context_mut().add("p".to_string(), syntax::parse_type("Path ℝ 0 π").unwrap());
+        checker.context_mut().add("q".to_string(), syntax::parse_type("Path ℝ").unwrap());
+        // Endpoints may be forgotten but not made up, and are compared as points
+        assert!(checker.check_against("p", "Path ℝ").is_ok());
+        assert!(checker.check_against("p", "Path ℝ 0 3.141592653589793").is_ok());
+        assert!(checker.check_against("p", "Path ℝ 0 1").is_err());
+        assert!(checker.check_against("q", "Path ℝ 0 1").is_err());
+        assert_eq!(checker.infer("p @ 0.5"), Ok(Type::Real));
+    }
+
+    #[test]
+    fn test_bindings_of_written_types() {
+        let mut checker = TypeChecker::new();
+        let written = [("f", "ℝ → ℝ"), ("x", "ℝ"), ("i", "I"), ("g", "C∞(ℝ, ℝ) → ℝ"), ("h", "Π(y : ℝ). ℝ")];
+        for (name, written) in written {
+            checker.context_mut().add(name.to_string(), parse_type(written).unwrap());
+        }
+        assert_eq!(checker.infer("f x"), Ok(Type::Real));
+        assert_eq!(checker.infer("g sin + h (f x)"), Ok(Type::Real));
+        // A point of the interval is a real number, but not a function
+        assert_eq!(checker.infer("f i"), Ok(Type::Real));
+        let expected = Type::Smooth(Box::new(real_function()));
+        assert_eq!(checker.infer("g i"), Err(CheckError::Mismatch { expected, found: Type::Interval, position: 2 }));
+        assert!(matches!(parse_type("ℝ ⇒ ℝ"), Err(CheckError::Parse { position: 2, .. })));
+    }
+
+    #[test]
+    fn test_mismatches_say_where_deep_types_part() {
+        let mut checker = TypeChecker::new();
+        checker.context_mut().add("g".to_string(), parse_type("(ℝ → ℝ) → (ℝ → I) → ℝ").unwrap());
+        let error = checker.check_against("g", "(ℝ → ℝ) → (ℝ → ℝ) → ℝ").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected (ℝ → ℝ) → (ℝ → ℝ) → ℝ, found (ℝ → ℝ) → (ℝ → I) → ℝ at position 0; \
+             in the codomain of the 2nd argument: expected ℝ, found I"
+        );
+        // Types that differ as wholes are said once
+        assert_eq!(checker.infer("g + 1").unwrap_err().to_string().matches("found").count(), 1);
+        // Endpoints are compared by value
+        checker.context_mut().add("p".to_string(), parse_type("Path ℝ 0 (2 * π)").unwrap());
+        assert!(checker.check_against("p", "Path ℝ 0 6.283185307179586").is_ok());
+    }
+
+    #[test]
+    fn test_parameters_take_the_type_their_uses_give() {
+        let checker = TypeChecker::new();
+        let function = |domain, codomain| Type::Function { domain: Box::new(domain), codomain: Box::new(codomain) };
+        let smooth = |ty: Type| Type::Smooth(Box::new(ty));
+        assert_eq!(checker.infer("λx. sin x"), Ok(smooth(real_function())));
+        assert_eq!(checker.type_at("λx. sin x", 8).map(|t| t.ty), Some(Type::Real));
+        // Used as a point of the interval, or as a path
+        assert_eq!(checker.infer("λi. (⟨t⟩ t) @ i"), Ok(smooth(function(Type::Interval, Type::Interval))));
+        let path = Type::Path { space: Box::new(Type::Real), endpoints: None };
+        assert_eq!(checker.infer("λp. sin (p @ 0)"), Ok(smooth(function(path, Type::Real))));
+        // What the path runs through, nothing here says
+        assert_eq!(checker.infer("λp. p @ 0"), Err(CheckError::Unsolved { position: 4, end: 9 }));
+        // Applied: a function, its domain from the argument
+        let applied = function(real_function(), Type::Real);
+        assert_eq!(checker.infer("λf. sin (f 1)"), Ok(applied.clone()));
+        assert_eq!(checker.compose("λx. x * 2", "λf. sin (f 1)").unwrap().composite_type, Some(applied));
+    }
+
+    #[test]
+    fn test_unsolvable_and_unsolved_metas() {
+        let checker = TypeChecker::new();
+        // `f`'s domain would have to be `f`'s own type
+        let (ty, problems) = checker.diagnose("λf. f f");
+        assert_eq!(ty, None);
+        assert!(matches!(&problems[..], [Problem { error: CheckError::Occurs { position: 6, .. }, .. }]));
+        assert_eq!(problems[0].error.to_string(), "`?1` would have to be ?1 → ?2, which contains it, at position 6");
+        // Nothing says what `f 1` is
+        assert_eq!(checker.infer("λf. f 1"), Err(CheckError::Unsolved { position: 4, end: 7 }));
+        let applied = Type::Function { domain: Box::new(real_function()), codomain: Box::new(Type::Real) };
+        assert_eq!(checker.infer("λf. f 1 + 2"), Ok(applied));
+        // A parameter nothing constrains is not reported: it falls back to ℝ
+        let (ty, problems) = checker.diagnose("λx. x");
+        assert_eq!(ty, Some(Type::Smooth(Box::new(real_function()))));
+        assert!(problems.is_empty());
+    }
+}
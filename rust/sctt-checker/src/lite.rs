@@ -0,0 +1,328 @@
+//! Engine-lite: the embedding facade for teaching tools
+//!
+//! Games, tutors and REPL widgets need a handful of questions answered about
+//! a learner's input, not the whole system. This module answers exactly
+//! those:
+//!
+//! - [`parse_expression`] — is this an expression, and over which variable?
+//! - [`check_expression_against`] — does it have the type the exercise asks for?
+//! - [`evaluate_smooth`] — its value (and slope) at a point
+//! - [`derivative_of`] — its derivative as an evaluable value
+//! - [`paths_composable`] — do two paths meet, and do they meet smoothly?
+//!
+//! Every result is `Serialize`/`Deserialize` and nothing here touches
+//! `wasm-bindgen`: build with `default-features = false, features = ["lite"]`
+//! for a dependency footprint of `sctt-core`, `sctt-smooth`, `serde` and
+//! `thiserror`.
+//!
+//! The expression language is [`sctt_smooth::expr`]'s, the one written on
+//! the blackboard: numbers, `π`, one variable, `+ - * / ^`, postfix `²` `³`,
+//! implicit multiplication (`2x`, `3(x + 1)`), `sin cos tan exp ln sqrt`,
+//! `min`/`max`, the interval connectives `i ∧ j`, `i ∨ j`, `¬i`, and
+//! `if(e ≤ c, a, b)`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use sctt_smooth::dual::Dual;
+use sctt_smooth::expr::{self, Corner, Expr, ExprError};
+use thiserror::Error;
+
+use crate::{format_type, Type};
+
+/// Whether this build carries the `#[wasm_bindgen]` exports; embedders that
+/// must stay free of web dependencies assert this is `false`
+pub const WASM_BINDINGS: bool = cfg!(feature = "wasm-bindings");
+
+/// Points at which two paths must agree to within this distance to compose
+const JOIN_TOLERANCE: f64 = 1e-9;
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LiteError {
+    #[error("{message} at position {position}")]
+    Parse { position: usize, message: String },
+
+    #[error("Unknown type `{0}`")]
+    UnknownType(String),
+
+    #[error("Expected at most one variable, found {}", .0.join(", "))]
+    TooManyVariables(Vec<String>),
+
+    #[error("`{expression}` is undefined at {at}")]
+    Undefined { expression: String, at: f64 },
+}
+
+impl From<ExprError> for LiteError {
+    fn from(error: ExprError) -> Self {
+        match error {
+            ExprError::Parse { position, message } => LiteError::Parse { position, message },
+            ExprError::UnknownVariable { name, variable } => LiteError::TooManyVariables(vec![variable, name]),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, LiteError>;
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Expression {
+    /// The input as written
+    pub source: String,
+    /// Canonical rendering, parenthesised only where needed
+    pub normalized: String,
+    /// The free variable, if any
+    pub variable: Option<String>,
+    /// False when `min`, `max`, `∧`, `∨` or `if` introduce corners
+    pub smooth: bool,
+    ast: Expr,
+}
+
+impl Expression {
+    fn new(source: &str, ast: Expr) -> Result<Expression> {
+        let mut variables = ast.variables();
+        if variables.len() > 1 {
+            return Err(LiteError::TooManyVariables(variables));
+        }
+        Ok(Expression {
+            source: source.to_string(),
+            normalized: ast.to_string(),
+            variable: variables.pop(),
+            smooth: ast.is_smooth(),
+            ast,
+        })
+    }
+}
+
+/// Outcome of checking an expression against a type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub ok: bool,
+    pub expected: Type,
+    pub inferred: Type,
+    /// Why the check failed; `None` when `ok`
+    pub message: Option<String>,
+}
+
+/// Value and first derivative at a point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub x: f64,
+    pub value: f64,
+    pub slope: f64,
+}
+
+/// The derivative of an expression, taken symbolically
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Derivative {
+    pub of: Expression,
+    pub derivative: Expression,
+}
+
+impl Derivative {
+    pub fn at(&self, x: f64) -> Result<f64> {
+        eval_at(&self.derivative, x).map(|e| e.value)
+    }
+}
+
+impl fmt::Display for Derivative {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let var = self.of.variable.as_deref().unwrap_or("x");
+        write!(f, "d/d{} ({})", var, self.of.normalized)
+    }
+}
+
+/// How two paths meet at `p(1) = q(0)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Composability {
+    /// The endpoint of `p` is the start of `q`
+    pub composable: bool,
+    /// Composable, and the velocities agree too, so `p · q` has no corner
+    pub smooth: bool,
+    pub p_end: Evaluation,
+    pub q_start: Evaluation,
+}
+
+pub fn parse_expression(source: &str) -> Result<Expression> {
+    parse_with_corners(source).map(|(expression, _)| expression)
+}
+
+pub(crate) fn parse_with_corners(source: &str) -> Result<(Expression, Vec<Corner>)> {
+    let (ast, corners) = expr::parse_with_corners(source)?;
+    Ok((Expression::new(source, ast)?, corners))
+}
+
+/// Check an expression against a type such as `ℝ`, `I`, `ℝ → ℝ` or `C∞(ℝ → ℝ)`
+///
+/// A closed expression is a real number, and an interval point when its
+/// value lies in `[0, 1]`; an expression in one variable is a function
+/// `ℝ → ℝ`, smooth unless it uses `min`, `max` or `if`. Smooth functions are accepted
+/// wherever plain functions are.
+pub fn check_expression_against(source: &str, type_src: &str) -> Result<CheckResult> {
+    let expected = parse_type_source(type_src)?;
+    let expression = parse_expression(source)?;
+
+    let real_fn = Type::Function { domain: Box::new(Type::Real), codomain: Box::new(Type::Real) };
+    let inferred = match (&expression.variable, expression.smooth) {
+        (None, _) => Type::Real,
+        (Some(_), true) => Type::Smooth(Box::new(real_fn)),
+        (Some(_), false) => real_fn,
+    };
+
+    let ok = match (&expected, &inferred) {
+        (expected, inferred) if expected == inferred => true,
+        (expected, Type::Smooth(inner)) => expected == inner.as_ref(),
+        (Type::Interval, Type::Real) => {
+            let value = eval_at(&expression, 0.0)?.value;
+            (0.0..=1.0).contains(&value)
+        }
+        _ => false,
+    };
+    let message = (!ok).then(|| format!(
+        "`{}` has type {}, not {}",
+        expression.normalized, format_type(&inferred), format_type(&expected)
+    ));
+    Ok(CheckResult { ok, expected, inferred, message })
+}
+
+/// Evaluate at `x`, binding the expression's variable (if any) to it
+pub fn evaluate_smooth(source: &str, x: f64) -> Result<Evaluation> {
+    eval_at(&parse_expression(source)?, x)
+}
+
+pub fn derivative_of(source: &str) -> Result<Derivative> {
+    let of = parse_expression(source)?;
+    let derivative = of.ast.derivative(of.variable.as_deref().unwrap_or("x"));
+    let derivative = Expression::new(&derivative.to_string(), derivative)?;
+    Ok(Derivative { of, derivative })
+}
+
+/// Whether `q` can follow `p`: both are paths over `[0, 1]` in their variable
+pub fn paths_composable(p: &str, q: &str) -> Result<Composability> {
+    let p_end = evaluate_smooth(p, 1.0)?;
+    let q_start = evaluate_smooth(q, 0.0)?;
+    let composable = (p_end.value - q_start.value).abs() < JOIN_TOLERANCE;
+    let smooth = composable && (p_end.slope - q_start.slope).abs() < JOIN_TOLERANCE;
+    Ok(Composability { composable, smooth, p_end, q_start })
+}
+
+pub(crate) fn eval_at(expression: &Expression, x: f64) -> Result<Evaluation> {
+    let result = expression.ast.eval(Dual::variable(x));
+    if !result.value.is_finite() || !result.deriv.is_finite() {
+        return Err(LiteError::Undefined { expression: expression.normalized.clone(), at: x });
+    }
+    Ok(Evaluation { x, value: result.value, slope: result.deriv })
+}
+
+fn parse_type_source(source: &str) -> Result<Type> {
+    let source = source.trim();
+
+    // Arrows associate to the right: split at the first top-level one
+    let mut depth = 0;
+    for (i, c) in source.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '→' if depth == 0 => return arrow(&source[..i], &source[i + '→'.len_utf8()..]),
+            '-' if depth == 0 && source[i..].starts_with("->") => return arrow(&source[..i], &source[i + 2..]),
+            _ => {}
+        }
+    }
+
+    match source {
+        "ℝ" | "Real" | "R" => Ok(Type::Real),
+        "I" | "Interval" => Ok(Type::Interval),
+        s => {
+            let (smooth, rest) = match s.strip_prefix("C∞") {
+                Some(rest) => (true, rest),
+                None => (false, s),
+            };
+            match rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+                Some(inner) => {
+                    let ty = parse_type_source(inner)?;
+                    Ok(if smooth { Type::Smooth(Box::new(ty)) } else { ty })
+                }
+                None => Err(LiteError::UnknownType(s.to_string())),
+            }
+        }
+    }
+}
+
+fn arrow(domain: &str, codomain: &str) -> Result<Type> {
+    Ok(Type::Function {
+        domain: Box::new(parse_type_source(domain)?),
+        codomain: Box::new(parse_type_source(codomain)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expression() {
+        let e = parse_expression("2x² + 3(x - 1)").unwrap();
+        assert_eq!(e.normalized, "2 * x^2 + 3 * (x - 1)");
+        assert_eq!(e.variable.as_deref(), Some("x"));
+        assert!(e.smooth);
+
+        let e = parse_expression("¬(0.3 ∧ 0.6)").unwrap();
+        assert_eq!(e.normalized, "1 - min(0.3, 0.6)");
+        assert_eq!(e.variable, None);
+        assert!(!e.smooth);
+
+        assert_eq!(parse_expression("2^3^2").unwrap().normalized, "2^3^2");
+        assert_eq!(parse_expression("-x^2").unwrap().normalized, "-x^2");
+        assert!(matches!(parse_expression("x +"), Err(LiteError::Parse { position: 3, .. })));
+        assert!(matches!(parse_expression("min(x)"), Err(LiteError::Parse { .. })));
+        assert_eq!(
+            parse_expression("x + y"),
+            Err(LiteError::TooManyVariables(vec!["x".to_string(), "y".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_check_expression_against() {
+        assert!(check_expression_against("sin(x²)", "ℝ → ℝ").unwrap().ok);
+        assert!(check_expression_against("sin(x²)", "C∞(ℝ → ℝ)").unwrap().ok);
+        assert!(check_expression_against("0.3 ∧ 0.7", "I").unwrap().ok);
+        assert!(check_expression_against("π", "ℝ").unwrap().ok);
+
+        let kinked = check_expression_against("max(x, 0)", "C∞(ℝ → ℝ)").unwrap();
+        assert!(!kinked.ok);
+        assert_eq!(kinked.message.as_deref(), Some("`max(x, 0)` has type ℝ → ℝ, not C∞(ℝ → ℝ)"));
+        assert!(!check_expression_against("2", "I").unwrap().ok);
+        assert!(!check_expression_against("x", "ℝ").unwrap().ok);
+        assert_eq!(check_expression_against("x", "Vec"), Err(LiteError::UnknownType("Vec".to_string())));
+    }
+
+    #[test]
+    fn test_evaluate_and_differentiate() {
+        let e = evaluate_smooth("x * exp(-x) + sin(x)", 1.0).unwrap();
+        assert!((e.value - ((-1.0f64).exp() + 1.0f64.sin())).abs() < 1e-12);
+        assert!((e.slope - 1.0f64.cos()).abs() < 1e-12);
+        assert!((evaluate_smooth("(-2)^3", 0.0).unwrap().value + 8.0).abs() < 1e-12);
+
+        let d = derivative_of("x³ - 2x + 1").unwrap();
+        assert_eq!(d.to_string(), "d/dx (x^3 - 2 * x + 1)");
+        assert_eq!(d.derivative.normalized, "3 * x^2 - 2");
+        assert!((d.at(2.0).unwrap() - 10.0).abs() < 1e-12);
+        assert_eq!(derivative_of("max(x, 0)").unwrap().at(-1.0), Ok(0.0));
+        assert!(matches!(derivative_of("ln(x)").unwrap().at(0.0), Err(LiteError::Undefined { .. })));
+
+        // Results cross process boundaries as JSON
+        let json = serde_json::to_string(&d).unwrap();
+        assert_eq!(serde_json::from_str::<Derivative>(&json).unwrap(), d);
+    }
+
+    #[test]
+    fn test_paths_composable() {
+        let joined = paths_composable("t²", "1 + 2t").unwrap();
+        assert!(joined.composable && joined.smooth);
+
+        let corner = paths_composable("t²", "1 + t").unwrap();
+        assert!(corner.composable && !corner.smooth);
+        assert_eq!(corner.p_end.slope, 2.0);
+
+        assert!(!paths_composable("t", "t").unwrap().composable);
+    }
+}
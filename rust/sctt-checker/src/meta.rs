@@ -0,0 +1,154 @@
+//! Metavariables
+//!
+//! An unannotated `λ` does not say what its parameter is: the parameter gets
+//! a metavariable `?n`, and its uses say what type that stands for. An
+//! unknown function or path gets metas for its parts the same way. [`Metas`]
+//! keeps them in a union-find: metas found equal share a root, and a root
+//! may be solved with a type that does not contain it. Whatever is still
+//! unknown once checking ends is reported where it arose, except a
+//! parameter nothing constrained: that falls back to [`UNCONSTRAINED_PARAMETER`],
+//! so `λx. x` is the identity on ℝ, as it was before metas.
+
+use crate::syntax::Expr;
+use crate::Type;
+
+/// What a `λ` parameter is when nothing in checking says: the checker is
+/// about functions of reals, and `λx. x` has always meant `ℝ → ℝ`
+pub(crate) const UNCONSTRAINED_PARAMETER: Type = Type::Real;
+
+/// Solving `?meta` with `ty` would make it part of itself
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Occurs {
+    pub meta: u32,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    /// Itself at a root
+    parent: u32,
+    solution: Option<Type>,
+    /// Character offsets of the expression the meta arose at
+    start: usize,
+    end: usize,
+    /// Whether it stands for a `λ` parameter
+    parameter: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Metas {
+    slots: Vec<Slot>,
+}
+
+impl Metas {
+    pub fn fresh(&mut self, origin: &Expr, parameter: bool) -> Type {
+        let meta = self.slots.len() as u32;
+        let (start, end) = (origin.position, origin.end);
+        self.slots.push(Slot { parent: meta, solution: None, start, end, parameter });
+        Type::Meta(meta)
+    }
+
+    fn find(&mut self, meta: u32) -> u32 {
+        let parent = self.slots[meta as usize].parent;
+        if parent == meta {
+            return meta;
+        }
+        let root = self.find(parent);
+        self.slots[meta as usize].parent = root;
+        root
+    }
+
+    /// `ty` with a solved meta at its head replaced, until the head is
+    /// known or an unsolved root
+    pub fn resolve(&mut self, ty: &Type) -> Type {
+        let Type::Meta(meta) = ty else { return ty.clone() };
+        let root = self.find(*meta);
+        match self.slots[root as usize].solution.clone() {
+            Some(solution) => self.resolve(&solution),
+            None => Type::Meta(root),
+        }
+    }
+
+    /// `ty` with every solved meta replaced, all the way down
+    pub fn instantiate(&mut self, ty: &Type) -> Type {
+        let resolved = self.resolve(ty);
+        let mut go = |ty: &Type| Box::new(self.instantiate(ty));
+        match resolved {
+            Type::Function { domain, codomain } => Type::Function { domain: go(&domain), codomain: go(&codomain) },
+            Type::Path { space, endpoints } => Type::Path { space: go(&space), endpoints },
+            Type::Smooth(inner) => Type::Smooth(go(&inner)),
+            ty => ty,
+        }
+    }
+
+    /// Make `a` and `b`, both unsolved, the same meta
+    pub fn union(&mut self, a: u32, b: u32) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.slots[b as usize].parent = a;
+            self.slots[a as usize].parameter |= self.slots[b as usize].parameter;
+        }
+    }
+
+    /// Solve the unsolved `meta` with `ty`, which must not contain it
+    pub fn solve(&mut self, meta: u32, ty: &Type) -> Result<(), Occurs> {
+        let root = self.find(meta);
+        let ty = self.instantiate(ty);
+        if self.occurs(root, &ty) {
+            return Err(Occurs { meta: root, ty });
+        }
+        self.slots[root as usize].solution = Some(ty);
+        Ok(())
+    }
+
+    fn occurs(&mut self, root: u32, ty: &Type) -> bool {
+        match ty {
+            Type::Meta(meta) => self.find(*meta) == root,
+            Type::Function { domain, codomain } => self.occurs(root, domain) || self.occurs(root, codomain),
+            Type::Path { space, .. } | Type::Smooth(space) => self.occurs(root, space),
+            _ => false,
+        }
+    }
+
+    /// Solve the parameters nothing constrained with [`UNCONSTRAINED_PARAMETER`],
+    /// and say where the other metas still unknown arose
+    pub fn finish(&mut self) -> Vec<(usize, usize)> {
+        let mut unsolved = Vec::new();
+        for meta in 0..self.slots.len() as u32 {
+            if self.find(meta) != meta || self.slots[meta as usize].solution.is_some() {
+                continue;
+            }
+            let slot = &mut self.slots[meta as usize];
+            if slot.parameter {
+                slot.solution = Some(UNCONSTRAINED_PARAMETER);
+            } else {
+                unsolved.push((slot.start, slot.end));
+            }
+        }
+        unsolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::parse;
+
+    #[test]
+    fn test_union_find_and_occurs_check() {
+        let origin = parse("x").unwrap();
+        let mut metas = Metas::default();
+        let (Type::Meta(a), Type::Meta(b)) = (metas.fresh(&origin, true), metas.fresh(&origin, false)) else {
+            unreachable!()
+        };
+        metas.union(a, b);
+        let arrow = |a: Type, b: Type| Type::Function { domain: Box::new(a), codomain: Box::new(b) };
+        // `?0 = ?1 → ℝ` would make `?0` part of itself, whichever name it goes by
+        let looped = metas.solve(a, &arrow(Type::Meta(b), Type::Real));
+        assert_eq!(looped, Err(Occurs { meta: a, ty: arrow(Type::Meta(a), Type::Real) }));
+        // Solving either solves both
+        metas.solve(b, &Type::Interval).unwrap();
+        assert_eq!(metas.instantiate(&arrow(Type::Meta(a), Type::Meta(b))), arrow(Type::Interval, Type::Interval));
+        assert!(metas.finish().is_empty());
+    }
+}
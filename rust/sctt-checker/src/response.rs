@@ -0,0 +1,365 @@
+//! The JSON the checker answers front ends with
+//!
+//! Every WASM export that checks something (`check_expr`, `check_against`,
+//! `type_check`, `check_composition` and `check_coherence`) answers with a
+//! [`CheckResponse`]:
+//!
+//! ```json
+//! {
+//!   "success": false,
+//!   "message": "Unbound variable `sinn` at position 0",
+//!   "diagnostics": [{
+//!     "code": "unbound",
+//!     "severity": "error",
+//!     "message": "Unbound variable `sinn` at position 0",
+//!     "span": { "start": 0, "end": 1 },
+//!     "suggestion": "Did you mean `sin`?"
+//!   }]
+//! }
+//! ```
+//!
+//! `type` is there when something typed, and `details` holds what only one
+//! export reports. Spans are byte offsets into the checked source; where an
+//! export takes two sources there are none. A diagnostic's `code` is one of
+//! [`CODES`], and `related` lists what else explains it. [`schema`] is the
+//! same description as data, for clients to validate against.
+
+use sctt_core::record::{self, field, optional, SchemaError, Severity, Shape, Span, SPAN};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{format_type, AnnotationError, CheckError, Composition, Type};
+
+/// What any checking export answers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResponse {
+    /// Whether no diagnostic is an error
+    pub success: bool,
+    /// The type of what was checked, formatted; only on success
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    /// The outcome in a sentence: the first error, or what checked
+    pub message: String,
+    pub diagnostics: Vec<Diagnostic>,
+    /// What only the answering export reports
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub details: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// One of [`CODES`]
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<Related>,
+    /// A fix to offer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Something that explains a diagnostic, somewhere else or nowhere in
+/// particular
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Related {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+/// Every diagnostic code: those of [`CheckError::code`], then a written
+/// type that does not parse, a function that is not smooth (information,
+/// not an error), functions that do not compose, a smooth function and a
+/// path that do not cohere, and either side of that comparison not reading
+pub const CODES: &[&str] = &[
+    "parse",
+    "unbound",
+    "mismatch",
+    "not_a_function",
+    "not_a_path",
+    "endpoint",
+    "occurs",
+    "unsolved",
+    "internal",
+    "type_parse",
+    "not_smooth",
+    "not_composable",
+    "incoherent",
+    "smooth_side",
+    "cubical_side",
+];
+
+impl Diagnostic {
+    pub fn error(code: &str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            code: code.to_string(),
+            severity: Severity::Error,
+            message: message.into(),
+            span: None,
+            related: Vec::new(),
+            suggestion: None,
+        }
+    }
+
+    /// `error` in `source`, spanned; an expression of unknown type is
+    /// spanned whole, anything else at the character it is reported at
+    pub fn of(source: &str, error: &CheckError) -> Self {
+        let span = match *error {
+            CheckError::Unsolved { position, end } => {
+                Span { start: byte_offset(source, position), end: byte_offset(source, end) }
+            }
+            ref error => span(source, error.position()),
+        };
+        Diagnostic { span: Some(span), ..Diagnostic::error(error.code(), error.to_string()) }
+    }
+
+    pub fn with_related(mut self, message: impl Into<String>, span: Option<Span>) -> Self {
+        self.related.push(Related { message: message.into(), span });
+        self
+    }
+}
+
+impl CheckResponse {
+    /// Successful unless a diagnostic is an error, in which case the first
+    /// one is the message and there is no type
+    pub fn new(ty: Option<String>, diagnostics: Vec<Diagnostic>, message: impl Into<String>) -> Self {
+        let error = diagnostics.iter().find(|d| d.severity == Severity::Error).map(|d| d.message.clone());
+        CheckResponse {
+            success: error.is_none(),
+            ty: ty.filter(|_| error.is_none()),
+            message: error.unwrap_or_else(|| message.into()),
+            diagnostics,
+            details: Value::Null,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// `expr : expected`, checked as by
+    /// [`TypeChecker::check_against`](crate::TypeChecker::check_against); `details` are the two sides as written
+    pub fn annotation(expr: &str, expected: &str, result: Result<Type, AnnotationError>) -> Self {
+        let diagnostics = match &result {
+            Ok(_) => Vec::new(),
+            Err(AnnotationError::Expr(error)) => vec![Diagnostic::of(expr, error)],
+            // Spans are into the checked expression, not the type
+            Err(error @ AnnotationError::Type(_)) => vec![Diagnostic::error("type_parse", error.to_string())],
+        };
+        let ty = result.ok().map(|ty| format_type(&ty));
+        let message = format!("{} : {} checks", expr, expected);
+        CheckResponse::new(ty, diagnostics, message).with_details(json!({ "expression": expr, "expected": expected }))
+    }
+
+    /// `f ∘ g`, as by [`TypeChecker::compose`](crate::TypeChecker::compose);
+    /// `details` are `{ f, g, f_type, g_type, smooth }`, the types when
+    /// both sides typed
+    pub fn composition(f: &str, g: &str, result: Result<Composition, CheckError>) -> Self {
+        let mut details = json!({ "f": f, "g": g });
+        let (ty, diagnostics) = match result {
+            Err(error) => (None, vec![Diagnostic::error(error.code(), error.to_string())]),
+            Ok(composition) => {
+                let (f_type, g_type) = (format_type(&composition.f_type), format_type(&composition.g_type));
+                details["f_type"] = f_type.as_str().into();
+                details["g_type"] = g_type.as_str().into();
+                details["smooth"] = matches!(composition.composite_type, Some(Type::Smooth(_))).into();
+                let diagnostics = match composition.reason {
+                    Some(reason) => vec![Diagnostic::error("not_composable", reason)
+                        .with_related(format!("`{}` : {}", f, f_type), None)
+                        .with_related(format!("`{}` : {}", g, g_type), None)],
+                    None => Vec::new(),
+                };
+                (composition.composite_type.as_ref().map(format_type), diagnostics)
+            }
+        };
+        let message = format!("({}) ∘ ({}) is well-typed!", f, g);
+        CheckResponse::new(ty, diagnostics, message).with_details(details)
+    }
+
+    /// Whether the smooth function and the path cohere, as by
+    /// [`check_coherence`](crate::coherence::check_coherence), with an
+    /// `incoherent` diagnostic for every point they part at; `details` are
+    /// `{ smooth, cubical, points }`
+    #[cfg(all(feature = "smooth", feature = "cubical"))]
+    pub fn coherence(
+        smooth: &str,
+        cubical: &str,
+        result: Result<crate::coherence::CoherenceReport, crate::coherence::CoherenceError>,
+    ) -> Self {
+        use crate::coherence::CoherenceError;
+
+        let mut details = json!({ "smooth": smooth, "cubical": cubical });
+        let diagnostics = match result {
+            Err(error @ (CoherenceError::Smooth(_) | CoherenceError::Undefined { .. })) => {
+                vec![Diagnostic::error("smooth_side", error.to_string())]
+            }
+            Err(error @ CoherenceError::Cubical(_)) => vec![Diagnostic::error("cubical_side", error.to_string())],
+            Ok(report) => {
+                details["points"] = json!(report.points);
+                let apart = report.points.iter().filter(|p| !p.ok);
+                apart
+                    .map(|p| {
+                        let message = format!(
+                            "Coherence fails at t = {}: values differ by {}, velocities by {}",
+                            p.point, p.value_diff, p.velocity_diff
+                        );
+                        Diagnostic::error("incoherent", message)
+                    })
+                    .collect()
+            }
+        };
+        CheckResponse::new(None, diagnostics, "Smooth and cubical structures are coherent!").with_details(details)
+    }
+
+    /// Check a document against [`schema`] and decode it
+    pub fn validate(json: &str) -> Result<CheckResponse, SchemaError> {
+        let invalid = |message: String| SchemaError { path: "$".to_string(), message };
+        let value: Value = serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        record::conform(&value, &CHECK_RESPONSE_SHAPE)?;
+        CheckResponse::deserialize(&value).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+/// What the checked type is, in words
+pub fn description(ty: &Type) -> &'static str {
+    match ty {
+        Type::Smooth(_) => "Smooth function from reals to reals",
+        Type::Path { .. } => "Continuous path in space",
+        Type::Function { .. } => "Function type",
+        Type::Interval => "Interval value between 0 and 1",
+        Type::Real => "Real number",
+        Type::Universe => "Type universe",
+        Type::Meta(_) => "Type not yet known",
+    }
+}
+
+/// Where the character at `position` in `source` starts, in bytes
+pub(crate) fn byte_offset(source: &str, position: usize) -> usize {
+    source.char_indices().nth(position).map_or(source.len(), |(at, _)| at)
+}
+
+/// The character at `position` in `source`, as a byte span; empty at the
+/// end
+pub(crate) fn span(source: &str, position: usize) -> Span {
+    let start = byte_offset(source, position);
+    let end = source[start..].chars().next().map_or(start, |c| start + c.len_utf8());
+    Span { start, end }
+}
+
+const RELATED: &[record::Field] = &[
+    field("message", Shape::String, ""),
+    optional("span", Shape::Object(SPAN), "Where in the checked source"),
+];
+
+const DIAGNOSTIC: &[record::Field] = &[
+    field("code", Shape::OneOf(CODES), "What kind of diagnostic, for programs"),
+    field("severity", Shape::OneOf(&["error", "warning", "info"]), "Only errors fail a check"),
+    field("message", Shape::String, "What is wrong, for people"),
+    optional("span", Shape::Object(SPAN), "Where in the checked source; absent for exports of two sources"),
+    optional("related", Shape::Array(&Shape::Object(RELATED)), "What else explains it"),
+    optional("suggestion", Shape::String, "A fix to offer"),
+];
+
+/// Every field of a response, the single description of its shape
+pub const CHECK_RESPONSE: &[record::Field] = &[
+    field("success", Shape::Boolean, "Whether no diagnostic is an error"),
+    optional("type", Shape::String, "The type of what was checked, on success"),
+    field("message", Shape::String, "The outcome in a sentence"),
+    field("diagnostics", Shape::Array(&Shape::Object(DIAGNOSTIC)), ""),
+    optional("details", Shape::Any, "What only the answering export reports"),
+];
+
+const CHECK_RESPONSE_SHAPE: Shape = Shape::Object(CHECK_RESPONSE);
+
+/// The shape as a JSON schema
+pub fn schema() -> Value {
+    let mut schema = record::render(&CHECK_RESPONSE_SHAPE);
+    schema["title"] = json!("CheckResponse");
+    schema["description"] = json!("What the checker's exports answer");
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TypeChecker;
+
+    /// Serialized, and checked to validate against the schema
+    fn golden(response: &CheckResponse) -> String {
+        let json = serde_json::to_string(response).unwrap();
+        assert_eq!(&CheckResponse::validate(&json).unwrap(), response);
+        json
+    }
+
+    #[test]
+    fn test_annotation_responses() {
+        let checker = TypeChecker::new();
+        let check = |expr: &str, ty: &str| {
+            golden(&CheckResponse::annotation(expr, ty, checker.check_against(expr, ty)))
+        };
+        assert_eq!(
+            check("λx. x", "I → I"),
+            r#"{"success":true,"type":"I → I","message":"λx. x : I → I checks","diagnostics":[],"details":{"expected":"I → I","expression":"λx. x"}}"#
+        );
+        assert_eq!(
+            check("⟨i⟩ 2", "Path I"),
+            r#"{"success":false,"message":"Expected I, found ℝ at position 4","diagnostics":[{"code":"mismatch","severity":"error","message":"Expected I, found ℝ at position 4","span":{"start":8,"end":9}}],"details":{"expected":"Path I","expression":"⟨i⟩ 2"}}"#
+        );
+        let in_type = CheckResponse::annotation("1", "Path", checker.check_against("1", "Path"));
+        assert_eq!((in_type.diagnostics[0].code.as_str(), in_type.diagnostics[0].span), ("type_parse", None));
+    }
+
+    #[test]
+    fn test_composition_responses() {
+        let checker = TypeChecker::new();
+        let compose = |f: &str, g: &str| golden(&CheckResponse::composition(f, g, checker.compose(f, g)));
+        assert_eq!(
+            compose("sin", "cos"),
+            r#"{"success":true,"type":"C∞(ℝ → ℝ)","message":"(sin) ∘ (cos) is well-typed!","diagnostics":[],"details":{"f":"sin","f_type":"C∞(ℝ → ℝ)","g":"cos","g_type":"C∞(ℝ → ℝ)","smooth":true}}"#
+        );
+        assert_eq!(
+            compose("1", "sin"),
+            r#"{"success":false,"message":"`1` has type ℝ, not a function type","diagnostics":[{"code":"not_composable","severity":"error","message":"`1` has type ℝ, not a function type","related":[{"message":"`1` : ℝ"},{"message":"`sin` : C∞(ℝ → ℝ)"}]}],"details":{"f":"1","f_type":"ℝ","g":"sin","g_type":"C∞(ℝ → ℝ)","smooth":false}}"#
+        );
+        assert_eq!(
+            compose("sin", "cos("),
+            r#"{"success":false,"message":"Unexpected end of input at position 4","diagnostics":[{"code":"parse","severity":"error","message":"Unexpected end of input at position 4"}],"details":{"f":"sin","g":"cos("}}"#
+        );
+    }
+
+    #[cfg(all(feature = "smooth", feature = "cubical"))]
+    #[test]
+    fn test_coherence_responses() {
+        let cohere = |f: &str, p: &str| CheckResponse::coherence(f, p, crate::coherence::check_coherence(f, p));
+        let coherent = cohere("t", "t");
+        assert!(coherent.success && coherent.diagnostics.is_empty());
+        assert_eq!(coherent.message, "Smooth and cubical structures are coherent!");
+        // Velocities are finite differences, which need not survive a
+        // round trip to the last bit
+        CheckResponse::validate(&serde_json::to_string(&coherent).unwrap()).unwrap();
+        let apart = cohere("t", "t + 1");
+        assert_eq!(apart.diagnostics.iter().map(|d| d.code.as_str()).collect::<Vec<_>>(), ["incoherent"; 2]);
+        assert!(apart.message.starts_with("Coherence fails at t = 0: values differ by 1"));
+        CheckResponse::validate(&serde_json::to_string(&apart).unwrap()).unwrap();
+        assert_eq!(cohere("sin(", "t").diagnostics[0].code, "smooth_side");
+    }
+
+    #[test]
+    fn test_schema_rejects_what_responses_never_are() {
+        let schema = schema();
+        assert_eq!(schema["required"], json!(["success", "message", "diagnostics"]));
+        assert_eq!(schema["properties"]["diagnostics"]["items"]["properties"]["code"]["enum"], json!(CODES));
+        let unknown_code =
+            r#"{"success":false,"message":"","diagnostics":[{"code":"oops","severity":"error","message":""}]}"#;
+        assert_eq!(CheckResponse::validate(unknown_code).unwrap_err().path, "$.diagnostics[0].code");
+        let old_shape = r#"{"ok":true,"error":"","diagnostics":[]}"#;
+        assert_eq!(CheckResponse::validate(old_shape).unwrap_err().path, "$.success");
+        // A checker bug still makes a response the schema accepts
+        let internal = CheckError::Internal { message: "no type".to_string(), position: 0 };
+        golden(&CheckResponse::new(None, vec![Diagnostic::of("x", &internal)], internal.to_string()));
+    }
+}
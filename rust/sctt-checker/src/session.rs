@@ -0,0 +1,293 @@
+//! Checking a document of declarations again after an edit
+//!
+//! A document is one declaration per line, `name = e` or `name : A = e`;
+//! an indented line carries on the one above, and blank lines and lines
+//! starting `--` are skipped. Each declaration is checked under the
+//! bindings it is given and the declarations above it, and binds its name
+//! for those below when it types.
+//!
+//! [`CheckerSession`] remembers, for the text of each declaration it has
+//! checked, what every name it uses was bound to and what came of it. On
+//! the next check a declaration whose text is the same, and whose names
+//! are bound as they were, is not checked again: an edit checks the edited
+//! declaration, and then only those that use a name whose type it changed.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use sctt_core::record::Span;
+use serde::{Deserialize, Serialize};
+
+use crate::response::Diagnostic;
+use crate::syntax::{self, ExprKind};
+use crate::{format_type, AnnotationError, Context, Type, TypeChecker};
+
+/// How one declaration of a document checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeclarationReport {
+    /// As written; what is left of the `=` when that is not a name
+    pub name: String,
+    /// The whole declaration, in bytes of the document
+    pub span: Span,
+    /// Formatted; absent when it does not type
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+    /// Spanned in bytes of the document
+    pub diagnostics: Vec<Diagnostic>,
+    /// Whether it was checked this time, rather than remembered
+    pub rechecked: bool,
+    /// Milliseconds spent checking it; 0 when remembered
+    pub millis: f64,
+}
+
+/// How a whole document checked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentReport {
+    pub declarations: Vec<DeclarationReport>,
+    /// How many declarations were checked this time
+    pub rechecked: usize,
+    /// Milliseconds spent on the whole document
+    pub millis: f64,
+}
+
+/// What came of checking a declaration's text
+#[derive(Debug, Clone)]
+struct Checked {
+    /// Every name the declaration uses and its binding when it was checked
+    uses: Vec<(String, Option<Type>)>,
+    ty: Option<Type>,
+    /// Spanned in bytes from the start of the declaration
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Checks documents, remembering each declaration between checks
+#[derive(Debug, Clone, Default)]
+pub struct CheckerSession {
+    /// By hash of the declaration's text, for the declarations of the last
+    /// document checked
+    checked: HashMap<u64, Checked>,
+}
+
+impl CheckerSession {
+    pub fn new() -> Self {
+        CheckerSession::default()
+    }
+
+    /// Check `document` under `context`, checking again only what an edit
+    /// since the last check could have changed; see the [module](self)
+    pub fn check(&mut self, context: &Context, document: &str) -> DocumentReport {
+        let started = now();
+        let mut checker = TypeChecker::new();
+        *checker.context_mut() = context.clone();
+        let mut checked = HashMap::new();
+        let mut declarations = Vec::new();
+        for (start, text) in split(document) {
+            let hash = hash(text);
+            let remembered = self.checked.get(&hash).filter(|c| bound_as_before(checker.context(), &c.uses));
+            let (result, rechecked, millis) = match remembered {
+                Some(result) => (result.clone(), false, 0.0),
+                None => {
+                    let begun = now();
+                    let result = check_declaration(checker.context(), text);
+                    (result, true, now() - begun)
+                }
+            };
+            let (name, _) = head(text);
+            if let Some(ty) = &result.ty {
+                checker.context_mut().add(name.to_string(), ty.clone());
+            }
+            let diagnostics = result.diagnostics.iter().map(|d| shifted(d, start)).collect();
+            declarations.push(DeclarationReport {
+                name: name.to_string(),
+                span: Span { start, end: start + text.len() },
+                ty: result.ty.as_ref().map(format_type),
+                diagnostics,
+                rechecked,
+                millis,
+            });
+            checked.insert(hash, result);
+        }
+        self.checked = checked;
+        let rechecked = declarations.iter().filter(|d| d.rechecked).count();
+        DocumentReport { declarations, rechecked, millis: now() - started }
+    }
+}
+
+/// The declarations of `document` and the bytes they start at, each
+/// without trailing whitespace
+fn split(document: &str) -> Vec<(usize, &str)> {
+    let mut declarations: Vec<(usize, usize)> = Vec::new();
+    let mut at = 0;
+    for line in document.split_inclusive('\n') {
+        let (start, end) = (at, at + line.trim_end().len());
+        at += line.len();
+        if line.trim().is_empty() || line.starts_with("--") {
+            continue;
+        }
+        match declarations.last_mut() {
+            Some(last) if line.starts_with(char::is_whitespace) => last.1 = end,
+            _ => declarations.push((start, end)),
+        }
+    }
+    declarations.into_iter().map(|(start, end)| (start, &document[start..end])).collect()
+}
+
+/// What is left of the `=`, trimmed, and the byte the `=` is at
+fn head(text: &str) -> (&str, Option<usize>) {
+    match text.find('=') {
+        Some(equals) => (text[..equals].split(':').next().unwrap_or("").trim(), Some(equals)),
+        None => (text.trim(), None),
+    }
+}
+
+fn hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn bound_as_before(context: &Context, uses: &[(String, Option<Type>)]) -> bool {
+    uses.iter().all(|(name, ty)| context.lookup(name) == ty.as_ref())
+}
+
+fn check_declaration(context: &Context, text: &str) -> Checked {
+    let failed = |diagnostics| Checked { uses: Vec::new(), ty: None, diagnostics };
+    let (name, Some(equals)) = head(text) else {
+        let diagnostic = Diagnostic::error("parse", "Expected a declaration, `name = expression`");
+        return failed(vec![Diagnostic { span: Some(Span { start: 0, end: text.len() }), ..diagnostic }]);
+    };
+    if !matches!(syntax::parse(name).map(|e| e.kind), Ok(ExprKind::Var(x)) if x == name) {
+        let diagnostic = Diagnostic::error("parse", format!("`{}` cannot be declared: it is not a name", name));
+        return failed(vec![Diagnostic { span: Some(Span { start: 0, end: equals }), ..diagnostic }]);
+    }
+    let body_start = equals + 1;
+    let body = &text[body_start..];
+    let expr = match syntax::parse(body) {
+        Ok(expr) => expr,
+        Err(error) => return failed(vec![shifted(&Diagnostic::of(body, &error), body_start)]),
+    };
+    let uses = expr.free_names().into_iter().map(|name| (name.clone(), context.lookup(&name).cloned())).collect();
+    let mut checker = TypeChecker::new();
+    *checker.context_mut() = context.clone();
+    let (ty, diagnostics) = match text[..equals].split_once(':') {
+        Some((_, written)) => {
+            let type_start = equals - written.len();
+            match checker.check_against(body, written) {
+                Ok(ty) => (Some(ty), Vec::new()),
+                Err(AnnotationError::Expr(error)) => (None, vec![shifted(&Diagnostic::of(body, &error), body_start)]),
+                Err(AnnotationError::Type(error)) => {
+                    (None, vec![shifted(&Diagnostic::of(written, &error), type_start)])
+                }
+            }
+        }
+        None => {
+            let (ty, problems) = checker.diagnose(body);
+            let diagnostics = problems.into_iter().map(|problem| Diagnostic {
+                suggestion: problem.suggestion,
+                ..shifted(&Diagnostic::of(body, &problem.error), body_start)
+            });
+            (ty, diagnostics.collect())
+        }
+    };
+    Checked { uses, ty, diagnostics }
+}
+
+/// `diagnostic` with its spans `by` bytes later
+fn shifted(diagnostic: &Diagnostic, by: usize) -> Diagnostic {
+    let shift = |span: &Option<Span>| span.map(|s| Span { start: s.start + by, end: s.end + by });
+    let mut diagnostic = diagnostic.clone();
+    diagnostic.span = shift(&diagnostic.span);
+    for related in &mut diagnostic.related {
+        related.span = shift(&related.span);
+    }
+    diagnostic
+}
+
+/// Milliseconds since some fixed moment
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+/// Milliseconds since some fixed moment; without JS to ask, always 0
+#[cfg(target_arch = "wasm32")]
+fn now() -> f64 {
+    #[cfg(feature = "wasm-bindings")]
+    return js_sys::Date::now();
+    #[cfg(not(feature = "wasm-bindings"))]
+    return 0.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declarations_check_in_order() {
+        let document = "one = 1\n\n-- a comment\nf : ℝ → ℝ =\n    λx. x + one\ng = f(sinn)\n3 = 4";
+        let report = CheckerSession::new().check(&Context::new(), document);
+        let [one, f, g, three] = &report.declarations[..] else { panic!("expected four declarations") };
+        assert_eq!(one.ty.as_deref(), Some("ℝ"));
+        assert_eq!((f.name.as_str(), f.ty.as_deref()), ("f", Some("ℝ → ℝ")));
+        // The indented line carries on the declaration
+        assert_eq!(&document[f.span.start..f.span.end], "f : ℝ → ℝ =\n    λx. x + one");
+        let unbound = &g.diagnostics[0];
+        assert_eq!(unbound.code, "unbound");
+        assert_eq!(&document[unbound.span.unwrap().start..][..1], "s");
+        assert_eq!(unbound.suggestion.as_deref(), Some("Did you mean `sin`?"));
+        assert_eq!(three.diagnostics[0].message, "`3` cannot be declared: it is not a name");
+        assert_eq!(report.rechecked, 4);
+    }
+
+    #[test]
+    fn test_an_edit_rechecks_what_it_could_change() {
+        let mut session = CheckerSession::new();
+        let context = Context::new();
+        let first = session.check(&context, "a = 1\nb = a + 1\nc = 2");
+        assert_eq!(first.rechecked, 3);
+        // Moved down a line, nothing is checked again, and spans move along
+        let moved = session.check(&context, "\na = 1\nb = a + 1\nc = 2");
+        assert_eq!(moved.rechecked, 0);
+        assert_eq!(moved.declarations[1].span, Span { start: 7, end: 16 });
+        // `a` keeps its type, so `b` is not checked again
+        let same = session.check(&context, "a = 2\nb = a + 1\nc = 2");
+        assert_eq!(same.declarations.iter().map(|d| d.rechecked).collect::<Vec<_>>(), [true, false, false]);
+        // `a` changes type, so `b` is
+        let changed = session.check(&context, "a = λx. x\nb = a + 1\nc = 2");
+        assert_eq!(changed.declarations.iter().map(|d| d.rechecked).collect::<Vec<_>>(), [true, true, false]);
+        assert!(changed.declarations[1].ty.is_none());
+        // So is everything that uses a binding that changes
+        let mut rebound = context.clone();
+        rebound.add("a".to_string(), Type::Interval);
+        assert_eq!(session.check(&rebound, "b = a + 1\nc = 2").rechecked, 1);
+    }
+
+    #[test]
+    fn test_editing_one_of_200_declarations() {
+        // 20 bases, each used by 9 declarations
+        let document = |edited: &str| {
+            let mut lines = Vec::new();
+            for base in 0..20 {
+                let value = if base == 7 { edited.to_string() } else { base.to_string() };
+                lines.push(format!("base{} = {}", base, value));
+                lines.extend((1..10).map(|n| format!("use{}_{} = λx. sin(x * base{}) + {}", base, n, base, n)));
+            }
+            lines.join("\n")
+        };
+        let mut session = CheckerSession::new();
+        let context = Context::new();
+        let first = session.check(&context, &document("7"));
+        assert_eq!((first.declarations.len(), first.rechecked), (200, 200));
+        assert!(first.declarations.iter().all(|d| d.diagnostics.is_empty()));
+        // Same type: the edited declaration alone
+        assert_eq!(session.check(&context, &document("7.5")).rechecked, 1);
+        // A new type: it and its nine users
+        let edited = session.check(&context, &document("⟨i⟩ i"));
+        assert_eq!(edited.rechecked, 10);
+        let affected = |name: &str| name == "base7" || name.starts_with("use7_");
+        assert!(edited.declarations.iter().filter(|d| d.rechecked).all(|d| affected(&d.name)));
+        assert_eq!(session.check(&context, &document("⟨i⟩ i")).rechecked, 0);
+    }
+}
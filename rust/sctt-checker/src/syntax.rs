@@ -0,0 +1,554 @@
+//! Surface syntax of checked expressions
+//!
+//! Numbers, names, `λx. e` (or `\x. e`), path abstraction `⟨i⟩ e`,
+//! application by juxtaposition (`f x`, `f(x)`), path application `p @ r`,
+//! and `+ - * / ^` with unary minus. Application binds tightest, then `@`,
+//! then the arithmetic operators as usual; binders reach as far right as
+//! they can. [`parse`] reads an expression, reporting the character offset
+//! where it goes wrong.
+//!
+//! Types are `ℝ` (or `Real`), `I` (or `Interval`), `Type`, `Path A`,
+//! `Path A a b` with endpoints that are numbers, names or bracketed
+//! expressions, `C∞(A)`, `A → B` (or `A -> B`) with `→` to the right, the
+//! smooth arrow `C∞(A, B)`, and `Π(x : A). B` where `B` does not mention
+//! `x`, which is `A → B`; [`parse_type`] reads one. An expression may be annotated `e : A`, which
+//! [`split_annotation`] takes apart.
+
+#[cfg(feature = "serde")]
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::{CheckError, Type};
+
+type Result<T> = std::result::Result<T, CheckError>;
+
+/// An expression, with the character offsets it starts at and ends before
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Expr {
+    pub position: usize,
+    pub end: usize,
+    pub kind: ExprKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ExprKind {
+    Num(f64),
+    Var(String),
+    Lambda(String, Box<Expr>),
+    /// `⟨i⟩ e`
+    PathAbs(String, Box<Expr>),
+    App(Box<Expr>, Box<Expr>),
+    /// `p @ r`
+    At(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    /// One of `+ - * / ^`
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// The same expression, wherever each is written
+    pub fn same_as(&self, other: &Expr) -> bool {
+        use ExprKind::*;
+        match (&self.kind, &other.kind) {
+            (Num(a), Num(b)) => a == b,
+            (Var(a), Var(b)) => a == b,
+            (Lambda(x, a), Lambda(y, b)) | (PathAbs(x, a), PathAbs(y, b)) => x == y && a.same_as(b),
+            (App(f, a), App(g, b)) | (At(f, a), At(g, b)) => f.same_as(g) && a.same_as(b),
+            (Neg(a), Neg(b)) => a.same_as(b),
+            (Binary(op, a, b), Binary(other_op, c, d)) => op == other_op && a.same_as(c) && b.same_as(d),
+            _ => false,
+        }
+    }
+
+    /// The names the expression uses that it does not bind
+    #[cfg(feature = "serde")]
+    pub fn free_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        self.collect_free(&mut Vec::new(), &mut names);
+        names
+    }
+
+    #[cfg(feature = "serde")]
+    fn collect_free<'e>(&'e self, bound: &mut Vec<&'e str>, names: &mut BTreeSet<String>) {
+        match &self.kind {
+            ExprKind::Num(_) => {}
+            ExprKind::Var(x) if bound.contains(&x.as_str()) => {}
+            ExprKind::Var(x) => {
+                names.insert(x.clone());
+            }
+            ExprKind::Lambda(x, body) | ExprKind::PathAbs(x, body) => {
+                bound.push(x);
+                body.collect_free(bound, names);
+                bound.pop();
+            }
+            ExprKind::Neg(a) => a.collect_free(bound, names),
+            ExprKind::App(a, b) | ExprKind::At(a, b) | ExprKind::Binary(_, a, b) => {
+                a.collect_free(bound, names);
+                b.collect_free(bound, names);
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// How tightly the expression holds together when printed, as the
+    /// binding powers below; atoms hold tightest
+    fn binding(&self) -> u8 {
+        match &self.kind {
+            ExprKind::Num(n) if *n < 0.0 => PREFIX,
+            ExprKind::Num(_) | ExprKind::Var(_) => APPLY + 1,
+            ExprKind::Lambda(..) | ExprKind::PathAbs(..) => 0,
+            ExprKind::App(..) => APPLY,
+            ExprKind::At(..) => AT,
+            ExprKind::Neg(_) => PREFIX,
+            ExprKind::Binary(op, ..) => match op {
+                '+' | '-' => SUM,
+                '*' | '/' => PRODUCT,
+                _ => POWER,
+            },
+        }
+    }
+}
+
+/// Reads back as the same expression, bracketed only where it must be
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at_least = |e: &Expr, binding: u8| {
+            if e.binding() >= binding { e.to_string() } else { format!("({})", e) }
+        };
+        match &self.kind {
+            ExprKind::Num(n) if *n == std::f64::consts::PI => write!(f, "π"),
+            ExprKind::Num(n) => write!(f, "{}", n),
+            ExprKind::Var(x) => write!(f, "{}", x),
+            ExprKind::Lambda(x, body) => write!(f, "λ{}. {}", x, body),
+            ExprKind::PathAbs(i, body) => write!(f, "⟨{}⟩ {}", i, body),
+            ExprKind::App(g, a) => write!(f, "{}({})", at_least(g, APPLY), a),
+            ExprKind::At(p, r) => write!(f, "{} @ {}", at_least(p, AT), at_least(r, AT + 1)),
+            ExprKind::Neg(a) => write!(f, "-{}", at_least(a, PREFIX)),
+            ExprKind::Binary(op, a, b) => {
+                let binding = self.binding();
+                // `^` groups to the right, the rest to the left
+                let (left, right) = if *op == '^' { (binding + 1, binding) } else { (binding, binding + 1) };
+                write!(f, "{} {} {}", at_least(a, left), op, at_least(b, right))
+            }
+        }
+    }
+}
+
+pub(crate) fn parse(source: &str) -> Result<Expr> {
+    let mut parser = Parser::new(source)?;
+    let expr = parser.expr(0)?;
+    match parser.tokens.get(parser.pos) {
+        Some((position, _, token)) => Err(parse_error(*position, format!("Unexpected `{}`", token))),
+        None => Ok(expr),
+    }
+}
+
+pub(crate) fn parse_type(source: &str) -> Result<Type> {
+    let mut parser = Parser::new(source)?;
+    let ty = parser.ty()?;
+    match parser.tokens.get(parser.pos) {
+        Some((position, _, token)) => Err(parse_error(*position, format!("Unexpected `{}` in a type", token))),
+        None => Ok(ty),
+    }
+}
+
+/// `e : A` as `e` and `A`, split at the first `:` outside brackets
+#[cfg(any(test, feature = "wasm-bindings"))]
+pub(crate) fn split_annotation(source: &str) -> Option<(&str, &str)> {
+    let mut depth = 0i32;
+    for (at, c) in source.char_indices() {
+        match c {
+            '(' | '⟨' => depth += 1,
+            ')' | '⟩' => depth -= 1,
+            ':' if depth == 0 => return Some((&source[..at], &source[at + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Sym(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Sym(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn parse_error(position: usize, message: impl Into<String>) -> CheckError {
+    CheckError::Parse { position, message: message.into() }
+}
+
+/// Superscript powers, read as `^` and the exponent
+const SUPERSCRIPTS: [(char, f64); 2] = [('²', 2.0), ('³', 3.0)];
+
+/// Tokens with their start and end character offsets
+fn tokenize(source: &str) -> Result<Vec<(usize, usize, Token)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (c, start) = (chars[i], i);
+        let token = if c.is_whitespace() {
+            i += 1;
+            continue;
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse().map_err(|_| parse_error(start, format!("Malformed number `{}`", text)))?;
+            tokens.push((start, i, Token::Num(n)));
+            continue;
+        } else if let Some(&(_, power)) = SUPERSCRIPTS.iter().find(|(sup, _)| *sup == c) {
+            tokens.push((start, start + 1, Token::Sym('^')));
+            Token::Num(power)
+        } else if c == 'π' {
+            Token::Num(std::f64::consts::PI)
+        } else if c == '-' && chars.get(i + 1) == Some(&'>') {
+            i += 2;
+            tokens.push((start, i, Token::Sym('→')));
+            continue;
+        } else if c == 'λ' || c == '\\' {
+            Token::Sym('λ')
+        } else if c.is_alphabetic() || c == '_' {
+            let superscript = |c: char| SUPERSCRIPTS.iter().any(|(sup, _)| *sup == c);
+            let continues = |c: char| (c.is_alphanumeric() || c == '_' || c == '\'') && c != 'λ' && !superscript(c);
+            while i < chars.len() && continues(chars[i]) {
+                i += 1;
+            }
+            tokens.push((start, i, Token::Ident(chars[start..i].iter().collect())));
+            continue;
+        } else {
+            match c {
+                '.' | '(' | ')' | '+' | '-' | '*' | '/' | '^' | '⟨' | '⟩' | '@' => Token::Sym(c),
+                ':' | ',' | '→' | '∞' => Token::Sym(c),
+                '−' => Token::Sym('-'),
+                '·' | '×' => Token::Sym('*'),
+                _ => return Err(parse_error(start, format!("Unexpected character `{}`", c))),
+            }
+        };
+        i += 1;
+        tokens.push((start, i, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, usize, Token)>,
+    pos: usize,
+    /// Character length of the source, reported for errors at end of input
+    end: usize,
+    source: Vec<char>,
+}
+
+/// Binding powers, loosest first
+const SUM: u8 = 1;
+const PRODUCT: u8 = 2;
+const PREFIX: u8 = 3;
+const POWER: u8 = 4;
+const AT: u8 = 5;
+const APPLY: u8 = 6;
+
+impl Parser {
+    fn new(source: &str) -> Result<Self> {
+        let end = source.chars().count();
+        Ok(Parser { tokens: tokenize(source)?, pos: 0, end, source: source.chars().collect() })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, _, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(p, _, _)| *p)
+    }
+
+    /// Where the last token taken ends
+    fn consumed(&self) -> usize {
+        self.pos.checked_sub(1).map_or(0, |last| self.tokens[last].1)
+    }
+
+    fn expect(&mut self, sym: char) -> Result<()> {
+        match self.peek() {
+            Some(Token::Sym(c)) if *c == sym => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(t) => Err(parse_error(self.position(), format!("Expected `{}`, found `{}`", sym, t))),
+            None => Err(parse_error(self.end, format!("Expected `{}`", sym))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.peek().cloned() {
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            Some(t) => Err(parse_error(self.position(), format!("Expected a name, found `{}`", t))),
+            None => Err(parse_error(self.end, "Expected a name")),
+        }
+    }
+
+    fn expr(&mut self, min_bp: u8) -> Result<Expr> {
+        let position = self.position();
+        let mut lhs = self.prefix()?;
+        loop {
+            let (bp, op) = match self.peek() {
+                Some(Token::Sym(c @ ('+' | '-'))) => (SUM, *c),
+                Some(Token::Sym(c @ ('*' | '/'))) => (PRODUCT, *c),
+                Some(Token::Sym('^')) => (POWER, '^'),
+                Some(Token::Sym('@')) => (AT, '@'),
+                // Juxtaposition is application: `f x`, `f(x)`
+                Some(Token::Num(_) | Token::Ident(_) | Token::Sym('(')) => (APPLY, ' '),
+                _ => break,
+            };
+            if bp <= min_bp {
+                break;
+            }
+            if op != ' ' {
+                self.pos += 1;
+            }
+            // `^` is right-associative, the rest associate to the left
+            let rhs = self.expr(if op == '^' { bp - 1 } else { bp })?;
+            let (a, b) = (Box::new(lhs), Box::new(rhs));
+            let kind = match op {
+                ' ' => ExprKind::App(a, b),
+                '@' => ExprKind::At(a, b),
+                _ => ExprKind::Binary(op, a, b),
+            };
+            lhs = Expr { position, end: self.consumed(), kind };
+        }
+        Ok(lhs)
+    }
+
+    fn prefix(&mut self) -> Result<Expr> {
+        let position = self.position();
+        let Some((_, _, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(parse_error(self.end, "Unexpected end of input"));
+        };
+        self.pos += 1;
+        let kind = match token {
+            Token::Num(n) => ExprKind::Num(n),
+            Token::Ident(name) => ExprKind::Var(name),
+            Token::Sym('-') => ExprKind::Neg(Box::new(self.expr(PREFIX)?)),
+            Token::Sym('(') => {
+                let inner = self.expr(0)?;
+                self.expect(')')?;
+                return Ok(inner);
+            }
+            Token::Sym('λ') => {
+                let name = self.ident()?;
+                self.expect('.')?;
+                ExprKind::Lambda(name, Box::new(self.expr(0)?))
+            }
+            Token::Sym('⟨') => {
+                let name = self.ident()?;
+                self.expect('⟩')?;
+                ExprKind::PathAbs(name, Box::new(self.expr(0)?))
+            }
+            t => return Err(parse_error(position, format!("Unexpected `{}`", t))),
+        };
+        Ok(Expr { position, end: self.consumed(), kind })
+    }
+
+    /// An endpoint of a path type, as written
+    fn endpoint(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Num(_) | Token::Ident(_) | Token::Sym('(')) => {}
+            Some(t) => return Err(parse_error(self.position(), format!("Expected an endpoint, found `{}`", t))),
+            None => return Err(parse_error(self.end, "Expected an endpoint")),
+        }
+        let point = self.prefix()?;
+        Ok(self.source[point.position..point.end].iter().collect())
+    }
+
+    fn ty(&mut self) -> Result<Type> {
+        let domain = self.ty_atom()?;
+        if self.peek() != Some(&Token::Sym('→')) {
+            return Ok(domain);
+        }
+        self.pos += 1;
+        Ok(Type::Function { domain: Box::new(domain), codomain: Box::new(self.ty()?) })
+    }
+
+    fn ty_atom(&mut self) -> Result<Type> {
+        let position = self.position();
+        let Some((_, _, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(parse_error(self.end, "Expected a type"));
+        };
+        self.pos += 1;
+        match token {
+            Token::Ident(name) => match name.as_str() {
+                "ℝ" | "Real" => Ok(Type::Real),
+                "I" | "Interval" => Ok(Type::Interval),
+                "Type" => Ok(Type::Universe),
+                "Path" => {
+                    let space = Box::new(self.ty_atom()?);
+                    let endpoints = match self.peek() {
+                        Some(Token::Num(_) | Token::Ident(_) | Token::Sym('(')) => {
+                            Some((self.endpoint()?, self.endpoint()?))
+                        }
+                        _ => None,
+                    };
+                    Ok(Type::Path { space, endpoints })
+                }
+                "C" => {
+                    self.expect('∞')?;
+                    self.expect('(')?;
+                    let mut inner = self.ty()?;
+                    // `C∞(A, B)`, the smooth functions from `A` to `B`
+                    if self.peek() == Some(&Token::Sym(',')) {
+                        self.pos += 1;
+                        inner = Type::Function { domain: Box::new(inner), codomain: Box::new(self.ty()?) };
+                    }
+                    self.expect(')')?;
+                    Ok(Type::Smooth(Box::new(inner)))
+                }
+                "Π" => {
+                    self.expect('(')?;
+                    let name = self.ident()?;
+                    self.expect(':')?;
+                    let domain = self.ty()?;
+                    self.expect(')')?;
+                    self.expect('.')?;
+                    let body = self.position();
+                    let codomain = self.ty()?;
+                    if mentions(&codomain, &name) {
+                        let message = "is mentioned in the codomain; dependent types are not supported";
+                        return Err(parse_error(body, format!("`{}` {}", name, message)));
+                    }
+                    Ok(Type::Function { domain: Box::new(domain), codomain: Box::new(codomain) })
+                }
+                _ => Err(parse_error(position, format!("Unknown type `{}`", name))),
+            },
+            Token::Sym('(') => {
+                let inner = self.ty()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            t => Err(parse_error(position, format!("Expected a type, found `{}`", t))),
+        }
+    }
+}
+
+/// Whether a path endpoint in `ty` names `name`
+fn mentions(ty: &Type, name: &str) -> bool {
+    let named = |point: &str| {
+        tokenize(point).is_ok_and(|tokens| tokens.iter().any(|(_, _, t)| *t == Token::Ident(name.to_string())))
+    };
+    match ty {
+        Type::Function { domain, codomain } => mentions(domain, name) || mentions(codomain, name),
+        Type::Path { space, endpoints } => {
+            mentions(space, name) || endpoints.as_ref().is_some_and(|(a, b)| named(a) || named(b))
+        }
+        Type::Smooth(inner) => mentions(inner, name),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(e: &Expr) -> String {
+        match &e.kind {
+            ExprKind::Num(n) => n.to_string(),
+            ExprKind::Var(x) => x.clone(),
+            ExprKind::Lambda(x, body) => format!("(λ{}. {})", x, shape(body)),
+            ExprKind::PathAbs(i, body) => format!("(⟨{}⟩ {})", i, shape(body)),
+            ExprKind::App(f, a) => format!("({} {})", shape(f), shape(a)),
+            ExprKind::At(p, r) => format!("({} @ {})", shape(p), shape(r)),
+            ExprKind::Neg(a) => format!("(-{})", shape(a)),
+            ExprKind::Binary(op, a, b) => format!("({} {} {})", shape(a), op, shape(b)),
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let parsed = |source: &str| shape(&parse(source).unwrap());
+        assert_eq!(parsed("1 + 2 * 3"), "(1 + (2 * 3))");
+        assert_eq!(parsed("-x^2"), "(-(x ^ 2))");
+        assert_eq!(parsed("f x y"), "((f x) y)");
+        assert_eq!(parsed("f p @ 0 + 1"), "(((f p) @ 0) + 1)");
+        assert_eq!(parsed("λx. sin(x) * 2"), "(λx. ((sin x) * 2))");
+        assert_eq!(parsed("⟨i⟩ \\y. y"), "(⟨i⟩ (λy. y))");
+        assert_eq!(parsed("2 ^ 3 ^ 2"), "(2 ^ (3 ^ 2))");
+    }
+
+    #[test]
+    fn test_parse_errors_have_positions() {
+        let error = |source: &str| match parse(source).unwrap_err() {
+            CheckError::Parse { position, .. } => position,
+            e => panic!("not a parse error: {e}"),
+        };
+        assert_eq!(error("(x + 1"), 6);
+        assert_eq!(error("x + $"), 4);
+        assert_eq!(error("λ. x"), 1);
+        assert_eq!(error("f x)"), 3);
+        assert_eq!(error(""), 0);
+    }
+
+    #[test]
+    fn test_printing_reads_back() {
+        let arithmetic = ["1 + 2 * 3", "(1 + 2) * 3", "1 - (2 - 3)", "-x ^ 2", "(-x) ^ 2", "2 ^ 3 ^ 2", "(2 ^ 3) ^ 2"];
+        let binders = ["sin(cos(x))", "λx. ⟨i⟩ x @ i", "(λx. x)(2)", "(f @ 0)(1)", "π / 2"];
+        for source in arithmetic.into_iter().chain(binders) {
+            let printed = parse(source).unwrap().to_string();
+            assert_eq!(printed, source);
+            assert!(parse(&printed).unwrap().same_as(&parse(source).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_types() {
+        let function = |a: Type, b: Type| Type::Function { domain: Box::new(a), codomain: Box::new(b) };
+        for shown in ["ℝ", "I", "Type", "Path ℝ", "C∞(ℝ → ℝ)", "ℝ → ℝ → I", "Path I → ℝ", "(ℝ → ℝ) → ℝ"] {
+            assert_eq!(crate::format_type(&parse_type(shown).unwrap()), shown);
+        }
+        let higher = function(function(Type::Real, Type::Real), Type::Real);
+        assert_eq!(parse_type("(ℝ → ℝ) → ℝ"), Ok(higher));
+        assert_eq!(parse_type("Real -> Interval"), parse_type("ℝ → I"));
+        let path = parse_type("Path ℝ 0 (2 * π) → ℝ").unwrap();
+        assert_eq!(crate::format_type(&path), "Path ℝ 0 (2 * π) → ℝ");
+        assert!(matches!(parse_type("Path ℝ 0"), Err(CheckError::Parse { position: 8, .. })));
+        assert!(matches!(parse_type("ℝ →"), Err(CheckError::Parse { position: 3, .. })));
+        assert!(matches!(parse_type("Nat"), Err(CheckError::Parse { position: 0, .. })));
+        assert_eq!(split_annotation("(λx. x) : ℝ → ℝ"), Some(("(λx. x) ", " ℝ → ℝ")));
+        assert_eq!(split_annotation("λx. x"), None);
+    }
+
+    #[test]
+    fn test_nested_arrows_smooth_arrows_and_pi() {
+        let function = |a: Type, b: Type| Type::Function { domain: Box::new(a), codomain: Box::new(b) };
+        let smooth = |a: Type, b: Type| Type::Smooth(Box::new(function(a, b)));
+        let nested = function(Type::Real, function(function(Type::Real, Type::Interval), Type::Real));
+        assert_eq!(parse_type("ℝ → (ℝ → I) → ℝ"), Ok(nested));
+        assert_eq!(parse_type("((ℝ))"), Ok(Type::Real));
+        assert_eq!(parse_type("C∞(ℝ, C∞(ℝ, ℝ))"), Ok(smooth(Type::Real, smooth(Type::Real, Type::Real))));
+        assert_eq!(parse_type("C∞(ℝ, ℝ)"), parse_type("C∞(ℝ → ℝ)"));
+        assert_eq!(parse_type("Π(x : ℝ). Path ℝ 0 1 → ℝ"), parse_type("ℝ → Path ℝ 0 1 → ℝ"));
+        assert_eq!(parse_type("Π(f:ℝ → ℝ).I"), Ok(function(function(Type::Real, Type::Real), Type::Interval)));
+
+        let message = |source: &str| match parse_type(source).unwrap_err() {
+            CheckError::Parse { position, message } => (position, message),
+            e => panic!("not a parse error: {e}"),
+        };
+        let dependent = message("Π(x : ℝ). Path ℝ x 0");
+        assert_eq!(dependent, (10, "`x` is mentioned in the codomain; dependent types are not supported".to_string()));
+        assert_eq!(message("C∞(ℝ ℝ)"), (5, "Expected `)`, found `ℝ`".to_string()));
+        assert_eq!(message("Π x. ℝ"), (2, "Expected `(`, found `x`".to_string()));
+        assert_eq!(message("(ℝ → ℝ"), (6, "Expected `)`".to_string()));
+    }
+}
@@ -0,0 +1,123 @@
+//! `#[wasm_bindgen]` exports, compiled only with the `wasm-bindings` feature
+
+use wasm_bindgen::prelude::*;
+
+use crate::{parse_type, CheckResponse, Checker, CheckerSession, TypeChecker};
+
+/// Hand a [`CheckResponse`] to JS, surfacing serializer failures as
+/// exceptions
+fn to_js(response: &CheckResponse) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(response).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// WASM bindings
+#[wasm_bindgen]
+pub struct WasmChecker {
+    checker: Checker,
+    session: CheckerSession,
+}
+
+#[wasm_bindgen]
+impl WasmChecker {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmChecker {
+        WasmChecker {
+            checker: Checker::new(),
+            session: CheckerSession::new(),
+        }
+    }
+
+    /// Bind `name` to the type written `type_str`; one that does not parse
+    /// is thrown, and nothing is bound
+    pub fn add_binding(&mut self, name: String, type_str: String) -> Result<(), JsValue> {
+        let ty = parse_type(&type_str).map_err(|e| JsValue::from_str(&format!("In the type: {}", e)))?;
+        self.checker.context_mut().add(name, ty);
+        Ok(())
+    }
+
+    /// Drop the latest binding of `name`; whether there was one
+    pub fn remove_binding(&mut self, name: String) -> bool {
+        self.checker.context_mut().remove(&name)
+    }
+
+    pub fn clear(&mut self) {
+        self.checker.context_mut().clear();
+    }
+
+    /// A [`CheckResponse`]; see [`Checker::check`]
+    pub fn check_expr(&mut self, expr: String) -> Result<JsValue, JsValue> {
+        to_js(&self.checker.check(&expr))
+    }
+
+    /// Every declaration of `source` checked under the bindings, as a
+    /// [`DocumentReport`](crate::session::DocumentReport); only what the
+    /// last edit could have changed is checked again
+    pub fn check_document(&mut self, source: String) -> Result<JsValue, JsValue> {
+        let report = self.session.check(self.checker.context(), &source);
+        serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// The innermost typed sub-expression of `expr` around the byte
+    /// `offset`, as `{ span: { start, end }, type }`, or `null` outside any
+    pub fn type_at(&self, expr: String, offset: usize) -> Result<JsValue, JsValue> {
+        let typed = self.checker.type_at(&expr, offset);
+        serde_wasm_bindgen::to_value(&typed).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Check `expr` against the type written `expected` under the
+    /// bindings, as a [`CheckResponse::annotation`]
+    pub fn check_against(&mut self, expr: String, expected: String) -> Result<JsValue, JsValue> {
+        let result = self.checker.check_against(&expr, &expected);
+        to_js(&CheckResponse::annotation(&expr, &expected, result))
+    }
+
+    /// The smooth function and the path compared in value and velocity at
+    /// `t = 0`, at the path's junctions and at `t = 1`, as a
+    /// [`CheckResponse::coherence`]; see [`crate::coherence`]
+    #[cfg(all(feature = "smooth", feature = "cubical"))]
+    pub fn check_coherence(&self, smooth_expr: String, cubical_expr: String) -> Result<JsValue, JsValue> {
+        let result = crate::coherence::check_coherence(&smooth_expr, &cubical_expr);
+        to_js(&CheckResponse::coherence(&smooth_expr, &cubical_expr, result))
+    }
+}
+
+/// The type of `expr` as by [`WasmChecker::check_expr`]; an annotated
+/// `e : A` is checked against `A` instead, as by
+/// [`WasmChecker::check_against`]
+#[wasm_bindgen]
+pub fn type_check(expr: &str) -> Result<JsValue, JsValue> {
+    if let Some((code, expected)) = crate::syntax::split_annotation(expr) {
+        let (code, expected) = (code.trim(), expected.trim());
+        let result = TypeChecker::new().check_against(code, expected);
+        return to_js(&CheckResponse::annotation(code, expected, result));
+    }
+    to_js(&Checker::new().check(expr))
+}
+
+/// Whether `f ∘ g` types, as a [`CheckResponse::composition`]; see
+/// [`TypeChecker::compose`]
+#[wasm_bindgen]
+pub fn check_composition(f: &str, g: &str) -> Result<JsValue, JsValue> {
+    to_js(&CheckResponse::composition(f, g, TypeChecker::new().compose(f, g)))
+}
+
+/// The JSON schema every [`CheckResponse`] conforms to, as text
+#[wasm_bindgen]
+pub fn diagnostics_schema() -> String {
+    crate::response::schema().to_string()
+}
+
+/// A [`SmoothnessReport`](crate::explain::SmoothnessReport) for an
+/// expression or piecewise path; parse errors are thrown
+#[cfg(feature = "lite")]
+#[wasm_bindgen]
+pub fn explain_smoothness(code: &str) -> Result<JsValue, JsValue> {
+    let report = crate::explain::explain_smoothness(code).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
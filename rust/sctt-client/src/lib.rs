@@ -0,0 +1,231 @@
+//! Typed client for the SCTT server's REST API
+//!
+//! Every route is a method taking and returning the payloads shared with the
+//! server in [`api`]. Transport failures, non-2xx answers and undecodable
+//! bodies all surface as one [`ClientError`]; a request the server handled
+//! but could not satisfy (an ill-typed term, an undefined point) comes back
+//! as a normal response with `success: false`.
+//!
+//! ```no_run
+//! use sctt_client::Client;
+//!
+//! # async fn demo() -> sctt_client::Result<()> {
+//! let client = Client::new("http://localhost:3000");
+//! let checked = client.typecheck("Type").await?;
+//! println!("Type : {}", checked.result.unwrap_or_default());
+//!
+//! let grid = client.evaluate_batch("sin(x)", &[0.0, 0.5, 1.0]).await?;
+//! println!("{:?}", grid.results);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Run log entries come back as [`record::RunRecord`]s, each checked against
+//! the published schema (also served at `GET /api/openapi.json`); older
+//! records are migrated on the way in.
+//!
+//! See [`api::examples`] (also served at `GET /api/examples`) for a worked
+//! request and response per route.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+use sctt_core::record::{RunRecord, SchemaError};
+
+pub use sctt_core::record;
+pub use sctt_system::api;
+use sctt_system::api::{
+    ApiError, ApplyInTransactionRequest, ApplyInTransactionResponse, BeginTransactionRequest,
+    BeginTransactionResponse, CompileRequest, CompileResponse, EvaluateBatchRequest, EvaluateBatchResponse,
+    EvaluateRequest, EvaluateResponse, HealthResponse, ProveRequest, ProveResponse, RenameRequest, RenameResponse,
+    RouteExamples, SessionInfo, SessionResponse, TransactionOutcome, TypeCheckRequest, TypeCheckResponse,
+};
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("server answered {status}: {message}")]
+    Api { status: u16, message: String },
+
+    #[error("unexpected response body: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    #[error("run record does not match the schema: {0}")]
+    Schema(#[from] SchemaError),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+/// A connection to one SCTT server
+#[derive(Debug, Clone)]
+pub struct Client {
+    base: String,
+    http: reqwest::Client,
+}
+
+impl Client {
+    /// `base_url` is the server root, e.g. `http://localhost:3000`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Client::with_http(base_url, reqwest::Client::new())
+    }
+
+    /// Use a preconfigured `reqwest` client (timeouts, proxies, headers)
+    pub fn with_http(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        let base = base_url.into().trim_end_matches('/').to_string();
+        Client { base, http }
+    }
+
+    pub async fn health(&self) -> Result<HealthResponse> {
+        self.send(self.http.get(self.url("/api/health"))).await
+    }
+
+    /// Worked request/response pairs for every route
+    pub async fn examples(&self) -> Result<Vec<RouteExamples>> {
+        self.send(self.http.get(self.url("/api/examples"))).await
+    }
+
+    pub async fn typecheck(&self, code: &str) -> Result<TypeCheckResponse> {
+        self.post("/api/typecheck", &TypeCheckRequest { code: code.to_string() }).await
+    }
+
+    /// `optimization` is `none`, `basic` or `aggressive`
+    pub async fn compile(&self, code: &str, optimization: &str) -> Result<CompileResponse> {
+        let request = CompileRequest {
+            code: code.to_string(),
+            optimization: optimization.to_string(),
+            strip_certificates: false,
+        };
+        self.post("/api/compile", &request).await
+    }
+
+    /// Compile, returning the module without its proof certificate; the
+    /// response names the certificate kept in the server's artifact cache
+    pub async fn compile_stripped(&self, code: &str, optimization: &str) -> Result<CompileResponse> {
+        let request = CompileRequest {
+            code: code.to_string(),
+            optimization: optimization.to_string(),
+            strip_certificates: true,
+        };
+        self.post("/api/compile", &request).await
+    }
+
+    pub async fn prove(&self, statement: &str, tactics: &[&str]) -> Result<ProveResponse> {
+        let request = ProveRequest {
+            statement: statement.to_string(),
+            tactics: tactics.iter().map(|t| t.to_string()).collect(),
+        };
+        self.post("/api/prove", &request).await
+    }
+
+    pub async fn evaluate(&self, expression: &str, value: f64) -> Result<EvaluateResponse> {
+        self.post("/api/evaluate", &EvaluateRequest { expression: expression.to_string(), value }).await
+    }
+
+    /// Evaluate one expression at many points in a single round trip
+    pub async fn evaluate_batch(&self, expression: &str, values: &[f64]) -> Result<EvaluateBatchResponse> {
+        let request = EvaluateBatchRequest { expression: expression.to_string(), values: values.to_vec() };
+        self.post("/api/evaluate/batch", &request).await
+    }
+
+    /// The server's recent runs, newest first
+    pub async fn runs(&self) -> Result<Vec<RunRecord>> {
+        let runs: Vec<serde_json::Value> = self.send(self.http.get(self.url("/api/runs"))).await?;
+        Ok(runs.into_iter().map(RunRecord::load_value).collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// The OpenAPI document, including the `RunRecord` schema
+    pub async fn openapi(&self) -> Result<serde_json::Value> {
+        self.send(self.http.get(self.url("/api/openapi.json"))).await
+    }
+
+    /// Open a shared session; keep the response, it carries the edit token
+    pub async fn create_session(&self) -> Result<SessionResponse> {
+        self.send(self.http.post(self.url("/api/session"))).await
+    }
+
+    pub async fn session(&self, id: &str) -> Result<SessionInfo> {
+        self.send(self.http.get(self.url(&format!("/api/session/{}", id)))).await
+    }
+
+    /// Fork the session's proof and lock out interactive writers
+    ///
+    /// ```no_run
+    /// # async fn demo(client: sctt_client::Client) -> sctt_client::Result<()> {
+    /// let session = client.create_session().await?;
+    /// let txn = client.begin_transaction(&session, "lemma-bot", None).await?.transaction;
+    /// match client.apply_in_transaction(&session, &txn, 0, "intro A").await {
+    ///     Ok(_) => { client.commit(&session, &txn).await?; }
+    ///     Err(_) => { client.rollback(&session, &txn).await?; }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn begin_transaction(
+        &self,
+        session: &SessionResponse,
+        client: &str,
+        timeout_secs: Option<u64>,
+    ) -> Result<BeginTransactionResponse> {
+        let request = BeginTransactionRequest { client: client.to_string(), timeout_secs };
+        let url = self.url(&format!("/api/session/{}/transactions", session.id));
+        self.send(self.http.post(url).bearer_auth(&session.edit_token).json(&request)).await
+    }
+
+    pub async fn apply_in_transaction(
+        &self,
+        session: &SessionResponse,
+        transaction: &str,
+        goal_id: usize,
+        tactic: &str,
+    ) -> Result<ApplyInTransactionResponse> {
+        let request = ApplyInTransactionRequest { goal_id, tactic: tactic.to_string() };
+        let url = self.url(&format!("/api/session/{}/transactions/{}/apply", session.id, transaction));
+        self.send(self.http.post(url).bearer_auth(&session.edit_token).json(&request)).await
+    }
+
+    pub async fn commit(&self, session: &SessionResponse, transaction: &str) -> Result<TransactionOutcome> {
+        self.finish(session, transaction, "commit").await
+    }
+
+    pub async fn rollback(&self, session: &SessionResponse, transaction: &str) -> Result<TransactionOutcome> {
+        self.finish(session, transaction, "rollback").await
+    }
+
+    async fn finish(&self, session: &SessionResponse, transaction: &str, verb: &str) -> Result<TransactionOutcome> {
+        let url = self.url(&format!("/api/session/{}/transactions/{}/{}", session.id, transaction, verb));
+        self.send(self.http.post(url).bearer_auth(&session.edit_token)).await
+    }
+
+    /// Rename a definition in the session document; the server answers 422
+    /// naming the definitions the rename would break, and changes nothing
+    pub async fn rename(&self, session: &SessionResponse, old: &str, new: &str) -> Result<RenameResponse> {
+        let request = RenameRequest { old: old.to_string(), new: new.to_string() };
+        let url = self.url(&format!("/api/session/{}/rename", session.id));
+        self.send(self.http.post(url).bearer_auth(&session.edit_token).json(&request)).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    async fn post<Req: Serialize, Res: DeserializeOwned>(&self, path: &str, request: &Req) -> Result<Res> {
+        self.send(self.http.post(self.url(path)).json(request)).await
+    }
+
+    /// Send, then map a non-2xx answer's [`ApiError`] body onto [`ClientError::Api`]
+    async fn send<Res: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<Res> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+        if !status.is_success() {
+            let message = serde_json::from_slice::<ApiError>(&body)
+                .map(|e| e.error)
+                .unwrap_or_else(|_| String::from_utf8_lossy(&body).into_owned());
+            return Err(ClientError::Api { status: status.as_u16(), message });
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
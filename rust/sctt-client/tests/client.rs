@@ -0,0 +1,108 @@
+//! Every client method against the real axum router, served in-process
+//!
+//! The server is `sctt_system::server::router` over an in-memory proof
+//! store, so a change to a route, a status mapping or the wire format on
+//! either side fails here. Fixture requests must get their fixture responses.
+
+use std::sync::Arc;
+
+use sctt_client::api::{self, fixtures};
+use sctt_client::record::{RunResult, SCHEMA_VERSION};
+use sctt_client::{Client, ClientError};
+use sctt_system::server::router;
+use sctt_system::service::{Config, MemoryStore, Service};
+
+async fn serve() -> Client {
+    let config = Config {
+        artifacts_dir: std::env::temp_dir().join("sctt-client-tests"),
+        ..Config::default()
+    };
+    std::fs::create_dir_all(&config.artifacts_dir).unwrap();
+    let app = router(Arc::new(Service::new(&config, MemoryStore::default())), &config);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    Client::new(format!("http://{}/", addr))
+}
+
+#[tokio::test]
+async fn every_method_round_trips_its_fixtures() {
+    let client = serve().await;
+
+    assert_eq!(client.health().await.unwrap(), fixtures::health().remove(0).response);
+    assert_eq!(client.examples().await.unwrap(), api::examples());
+
+    for f in fixtures::typecheck() {
+        assert_eq!(client.typecheck(&f.request.unwrap().code).await.unwrap(), f.response);
+    }
+    for f in fixtures::prove() {
+        let req = f.request.unwrap();
+        let tactics: Vec<&str> = req.tactics.iter().map(String::as_str).collect();
+        assert_eq!(client.prove(&req.statement, &tactics).await.unwrap(), f.response);
+    }
+    for f in fixtures::evaluate() {
+        let req = f.request.unwrap();
+        assert_eq!(client.evaluate(&req.expression, req.value).await.unwrap(), f.response);
+    }
+    for f in fixtures::evaluate_batch() {
+        let req = f.request.unwrap();
+        assert_eq!(client.evaluate_batch(&req.expression, &req.values).await.unwrap(), f.response);
+    }
+
+    // The compile fixture is cut to the module header, and the kernel cannot
+    // lower every term yet, so only the shape of the real answer is fixed
+    for f in fixtures::compile() {
+        let req = f.request.unwrap();
+        let compiled = client.compile(&req.code, &req.optimization).await.unwrap();
+        assert_eq!(compiled.wasm.is_some(), compiled.success);
+        assert_eq!(compiled.error.is_some(), !compiled.success);
+        assert!(compiled.wasm.map_or(true, |wasm| wasm.starts_with(b"\0asm")));
+    }
+}
+
+#[tokio::test]
+async fn session_flow_and_error_mapping() {
+    let client = serve().await;
+
+    let session = client.create_session().await.unwrap();
+    assert_eq!(client.session(&session.id).await.unwrap().id, session.id);
+    match client.session("missing").await {
+        Err(ClientError::Api { status: 404, message }) => assert_eq!(message, "Session not found"),
+        other => panic!("expected a 404, got {:?}", other),
+    }
+
+    // Authorized, but a fresh session has no proof to fork yet
+    let req = fixtures::begin_transaction().remove(0).request.unwrap();
+    match client.begin_transaction(&session, &req.client, req.timeout_secs).await {
+        Err(ClientError::Api { status: 422, .. }) => {}
+        other => panic!("expected a 422, got {:?}", other),
+    }
+    for result in [
+        client.apply_in_transaction(&session, "txn-9", 0, "intro A").await.map(|_| ()),
+        client.commit(&session, "txn-9").await.map(|_| ()),
+        client.rollback(&session, "txn-9").await.map(|_| ()),
+    ] {
+        assert!(matches!(result, Err(ClientError::Api { status: 404, .. })), "{:?}", result);
+    }
+
+    // Without the edit token the server refuses, and the client says why
+    let stranger = api::SessionResponse { edit_token: "guess".to_string(), ..session };
+    match client.commit(&stranger, "txn-9").await {
+        Err(ClientError::Api { status: 401, message }) => assert!(message.contains("edit token")),
+        other => panic!("expected a 401, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn runs_are_current_run_records() {
+    let client = serve().await;
+    for f in fixtures::typecheck() {
+        client.typecheck(&f.request.unwrap().code).await.unwrap();
+    }
+
+    let runs = client.runs().await.unwrap();
+    assert_eq!(runs.len(), fixtures::typecheck().len());
+    assert!(runs.iter().all(|r| r.schema_version == SCHEMA_VERSION));
+    assert_eq!(runs[0].result, RunResult::Checked { ty: "Universe(Succ(1))".to_string() });
+}
@@ -0,0 +1,87 @@
+//! Keeping many copies of a 10k-node term, by cloning versus by interning.
+//! The allocations each approach keeps alive are printed before the timings.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sctt_core::intern::TermInterner;
+use sctt_core::Term;
+
+/// Counts live allocations
+struct Counting;
+
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(1, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const COPIES: usize = 32;
+
+/// A full binary tree of applications, `2^depth - 1` nodes
+fn tree(depth: u32) -> Term {
+    if depth <= 1 {
+        return Term::Var("x".to_string());
+    }
+    Term::App { func: Box::new(tree(depth - 1)), arg: Box::new(tree(depth - 1)) }
+}
+
+fn cloned(term: &Term) -> Vec<Term> {
+    (0..COPIES).map(|_| term.clone()).collect()
+}
+
+/// Copies arrive as fresh terms (from the parser, the checker, ...) and
+/// only the ones interning keeps stay alive
+fn interned(interner: &mut TermInterner, term: &Term) -> Vec<Rc<Term>> {
+    (0..COPIES).map(|_| interner.intern(term.clone())).collect()
+}
+
+/// Allocations still alive once `f` has returned its result
+fn kept_alive<T>(f: impl FnOnce() -> T) -> isize {
+    let before = LIVE.load(Ordering::Relaxed);
+    let kept = f();
+    let after = LIVE.load(Ordering::Relaxed);
+    drop(kept);
+    after - before
+}
+
+fn bench_intern(c: &mut Criterion) {
+    let term = tree(14);
+
+    let mut interner = TermInterner::new();
+    println!(
+        "{COPIES} copies of a {}-node term keep {} allocations when cloned, {} when interned",
+        term.node_count(),
+        kept_alive(|| cloned(&term)),
+        kept_alive(|| interned(&mut interner, &term)),
+    );
+    println!("{:?}", interner.stats());
+
+    c.bench_function("clone 10k-node term", |b| b.iter(|| cloned(black_box(&term))));
+    c.bench_function("intern 10k-node term", |b| {
+        b.iter(|| interned(&mut TermInterner::new(), black_box(&term)))
+    });
+
+    let mut interner = TermInterner::new();
+    let (first, second) = (interner.intern(tree(14)), interner.intern(tree(14)));
+    c.bench_function("compare plain terms", |b| b.iter(|| black_box(&term) == black_box(&*first)));
+    c.bench_function("compare interned terms", |b| {
+        b.iter(|| Rc::ptr_eq(black_box(&first), black_box(&second)))
+    });
+}
+
+criterion_group!(benches, bench_intern);
+criterion_main!(benches);
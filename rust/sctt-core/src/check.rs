@@ -0,0 +1,787 @@
+//! Bidirectional type checking for core terms
+//!
+//! [`check`] pushes a known type into a term and [`infer`] reads one off
+//! it. Pairs only check: against `Σ(x:A).B` the first component checks
+//! against `A` and the second against `B[x := first]`, so the second
+//! component's type may mention the first. Projections infer, `π₁ p : A`
+//! and `π₂ p : B[x := π₁ p]` for `p : Σ(x:A).B`.
+//!
+//! Records work the same way by name: a literal checks against
+//! `{ x : A, y : B }` with its fields in any order and `y`'s value
+//! against `B[x := x's value]`, and `r.y : B[x := r.x]`.
+//!
+//! Types are compared up to alpha-equivalence after normalizing the path
+//! endpoints in them, so a path from `(λz. z) a` to `a` is a loop at `a`.
+//!
+//! The names a term is checked among are polymorphic in the level
+//! variables of their types: each use gets fresh ones, and comparing
+//! universes constrains them (see [`levels`](crate::levels)). A universe
+//! fits where any universe above it is expected.
+//!
+//! Holes check against anything. Each one is recorded with the binders in
+//! scope, which is how [`skeleton::goals`](crate::skeleton::goals) finds
+//! them, and a path whose body still has holes has unknown endpoints, so
+//! they are not compared.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::levels::{is_fresh, Levels};
+use crate::simp::{parse_term_spanned, Spans};
+use crate::skeleton::Goal;
+use crate::{Level, Result, Term, Type, TypeError};
+
+/// Variables in scope with their types, innermost last
+pub type Context = [(String, Type)];
+
+/// Check `term` against `ty`
+pub fn check(ctx: &Context, term: &Term, ty: &Type) -> Result<()> {
+    let spans = Spans::default();
+    let cx = Cx::new(ctx, &spans);
+    check_in(ctx, term, ty, &cx)?;
+    cx.solve().map(drop)
+}
+
+/// The type of `term`, for terms that determine their own
+pub fn infer(ctx: &Context, term: &Term) -> Result<Type> {
+    let spans = Spans::default();
+    let cx = Cx::new(ctx, &spans);
+    cx.solved(&infer_in(ctx, term, &cx)?)
+}
+
+/// Check a lab definition's body against the type its header gives, and
+/// return the holes in it in order; binders take their types from `ty`
+pub(crate) fn holes(term: &Term, ty: &Type) -> Result<Vec<Goal>> {
+    let spans = Spans::default();
+    let cx = Cx { unannotated: true, ..Cx::new(&[], &spans) };
+    check_in(&[], term, ty, &cx)?;
+    cx.solve()?;
+    Ok(cx.holes.into_inner())
+}
+
+/// Parse `source` and check it against `ty`; errors carry the span of the
+/// innermost subterm they were found at
+pub fn check_source(ctx: &Context, source: &str, ty: &Type) -> Result<()> {
+    let (term, spans) = parse_term_spanned(source)?;
+    let cx = Cx::new(ctx, &spans);
+    check_in(ctx, &term, ty, &cx).and_then(|()| cx.solve().map(drop)).map_err(|e| at_root(e, &spans))
+}
+
+/// Parse `source` and infer its type, with errors located as for
+/// [`check_source`]
+pub fn infer_source(ctx: &Context, source: &str) -> Result<Type> {
+    let (term, spans) = parse_term_spanned(source)?;
+    let cx = Cx::new(ctx, &spans);
+    infer_in(ctx, &term, &cx).and_then(|ty| cx.solved(&ty)).map_err(|e| at_root(e, &spans))
+}
+
+/// What checking a term keeps besides the context: where its subterms
+/// came from, how many context entries are global names, and the level
+/// constraints met so far
+struct Cx<'a> {
+    spans: &'a Spans,
+    globals: usize,
+    levels: RefCell<Levels>,
+    /// Holes met so far, with the local binders in scope
+    holes: RefCell<Vec<Goal>>,
+    /// Whether `λ` binders checked against a function type take their type
+    /// from it, as in lab definitions, rather than from their annotation
+    unannotated: bool,
+}
+
+impl<'a> Cx<'a> {
+    fn new(ctx: &Context, spans: &'a Spans) -> Self {
+        Cx { spans, globals: ctx.len(), levels: RefCell::default(), holes: RefCell::default(), unannotated: false }
+    }
+
+    /// The type of the context entry at `at`; a global name is polymorphic
+    /// in its level variables, which are fresh at each use
+    fn instantiate(&self, at: usize, ty: &Type) -> Type {
+        if at >= self.globals {
+            return ty.clone();
+        }
+        let mut levels = self.levels.borrow_mut();
+        let fresh: BTreeMap<String, Level> = ty.level_vars().into_iter().map(|var| (var.clone(), levels.fresh(&var))).collect();
+        ty.map_levels(&mut |level| level.subst(&fresh))
+    }
+
+    fn solve(&self) -> Result<BTreeMap<String, Level>> {
+        self.levels.borrow().solve()
+    }
+
+    /// `ty` with the solved levels put in; fresh variables that nothing
+    /// constrains are left polymorphic, named after the ones they stand for
+    fn solved(&self, ty: &Type) -> Result<Type> {
+        let mut values = self.solve()?;
+        let mut used: BTreeSet<String> = ty.level_vars().into_iter().chain(values.values().flat_map(Level::vars)).collect();
+        let unconstrained: Vec<String> =
+            ty.level_vars().into_iter().filter(|var| is_fresh(var) && !values.contains_key(var)).collect();
+        for var in unconstrained {
+            let mut name = var.trim_start_matches('?').trim_end_matches(|c: char| c.is_ascii_digit()).to_string();
+            while used.contains(&name) {
+                name.push('\'');
+            }
+            used.insert(name.clone());
+            values.insert(var, Level::Var(name));
+        }
+        Ok(ty.map_levels(&mut |level| level.subst(&values)))
+    }
+}
+
+fn check_in(ctx: &Context, term: &Term, ty: &Type, cx: &Cx) -> Result<()> {
+    check_node(ctx, term, ty, cx).map_err(|e| locate(e, term, cx.spans))
+}
+
+fn infer_in(ctx: &Context, term: &Term, cx: &Cx) -> Result<Type> {
+    infer_node(ctx, term, cx).map_err(|e| locate(e, term, cx.spans))
+}
+
+/// Terms made while checking, such as a `let` body with the value
+/// substituted, have no span, so their errors are placed at the nearest
+/// enclosing term from the source
+fn locate(error: TypeError, term: &Term, spans: &Spans) -> TypeError {
+    match spans.get(term) {
+        Some(span) => error.at(span),
+        None => error,
+    }
+}
+
+fn at_root(error: TypeError, spans: &Spans) -> TypeError {
+    match spans.root() {
+        Some(span) => error.at(span),
+        None => error,
+    }
+}
+
+fn check_node(ctx: &Context, term: &Term, ty: &Type, cx: &Cx) -> Result<()> {
+    match (term, ty) {
+        (Term::Pair { first, second }, Type::Sigma { param, domain, codomain }) => {
+            check_in(ctx, first, domain, cx)?;
+            check_in(ctx, second, &codomain.subst_term(param, first), cx)
+        }
+        (Term::Pair { .. }, _) => Err(mismatch(ty, "a pair")),
+        (Term::Inl(value), Type::Sum(left, _)) => check_in(ctx, value, left, cx),
+        (Term::Inr(value), Type::Sum(_, right)) => check_in(ctx, value, right, cx),
+        (Term::Inl(_) | Term::Inr(_), _) => Err(mismatch(ty, "a sum type")),
+        (Term::SmoothIn(value), Type::Smooth(inner)) => {
+            if !judged_smooth(inner) {
+                return Err(TypeError::NotSmooth);
+            }
+            check_in(ctx, value, inner, cx)
+        }
+        (Term::RecordLit(values), Type::Record(fields)) => {
+            if let Some((extra, _)) = values.iter().find(|(name, _)| !fields.iter().any(|(label, _)| label == name)) {
+                return Err(TypeError::NoSuchField(extra.clone()));
+            }
+            // Fields are looked up by name, and each value goes into the
+            // types of the fields after it
+            let mut fields = fields.clone();
+            for i in 0..fields.len() {
+                let (label, field_ty) = fields[i].clone();
+                let value = values
+                    .iter()
+                    .find(|(name, _)| *name == label)
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| TypeError::MissingField(label.clone()))?;
+                check_in(ctx, value, &field_ty, cx)?;
+                for (_, later) in &mut fields[i + 1..] {
+                    *later = later.subst_term(&label, value);
+                }
+            }
+            Ok(())
+        }
+        (Term::Lambda { param, param_type, body }, Type::Pi { param: x, domain, codomain }) => {
+            let param_type = binder(domain, param_type, cx)?;
+            let codomain = codomain.subst_term(x, &Term::Var(param.clone()));
+            check_in(&extend(ctx, param, param_type), body, &codomain, cx)
+        }
+        (Term::Lambda { param, param_type, body }, Type::Function { domain, codomain, .. }) => {
+            let param_type = binder(domain, param_type, cx)?;
+            check_in(&extend(ctx, param, param_type), body, codomain, cx)
+        }
+        (Term::PathLambda { param, body }, Type::Path { space, start, end }) => {
+            path_body(ctx, param, body, space, (start, end), cx)
+        }
+        (Term::PathLambda { param, body }, Type::PathP { param: i, family, start, end }) => {
+            // The endpoints have to live in the ends of the line of types;
+            // holes in them come from the type, so they are no goals
+            let before = cx.holes.borrow().len();
+            check_in(ctx, start, &family.subst_term(i, &Term::IZero), cx)?;
+            check_in(ctx, end, &family.subst_term(i, &Term::IOne), cx)?;
+            cx.holes.borrow_mut().truncate(before);
+            let fibre = family.subst_term(i, &Term::Var(param.clone()));
+            path_body(ctx, param, body, &fibre, (start, end), cx)
+        }
+        (Term::Let { name, ty: annotation, value, body }, _) => {
+            check_in(ctx, value, annotation, cx)?;
+            check_in(ctx, &unfold(name, annotation, value, body), ty, cx)
+        }
+        (Term::Hole, _) => {
+            cx.holes.borrow_mut().push(Goal { context: ctx[cx.globals..].to_vec(), ty: ty.clone() });
+            Ok(())
+        }
+        _ => expect(ty, &infer_in(ctx, term, cx)?, cx),
+    }
+}
+
+fn infer_node(ctx: &Context, term: &Term, cx: &Cx) -> Result<Type> {
+    match term {
+        Term::Var(name) => ctx
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, (bound, _))| bound == name)
+            .map(|(at, (_, ty))| cx.instantiate(at, ty))
+            .ok_or_else(|| TypeError::VariableNotFound(name.clone())),
+        Term::Fst(pair) => match infer_in(ctx, pair, cx)? {
+            Type::Sigma { domain, .. } => Ok(*domain),
+            other => Err(mismatch(&other, "a Σ-type")),
+        },
+        Term::Snd(pair) => match infer_in(ctx, pair, cx)? {
+            Type::Sigma { param, codomain, .. } => Ok(codomain.subst_term(&param, &Term::Fst(pair.clone()))),
+            other => Err(mismatch(&other, "a Σ-type")),
+        },
+        Term::Proj(record, field) => match infer_in(ctx, record, cx)? {
+            Type::Record(fields) => field_type(record, &fields, field),
+            other => Err(mismatch(&other, "a record type")),
+        },
+        Term::RecordLit(values) => values
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), infer_in(ctx, value, cx)?)))
+            .collect::<Result<_>>()
+            .map(Type::Record),
+        Term::Lambda { param, param_type, body } => {
+            let codomain = infer_in(&extend(ctx, param, param_type), body, cx)?;
+            Ok(if codomain.free_vars().contains(param) {
+                Type::Pi { param: param.clone(), domain: param_type.clone(), codomain: Box::new(codomain) }
+            } else {
+                Type::Function { domain: param_type.clone(), codomain: Box::new(codomain), is_smooth: false }
+            })
+        }
+        Term::App { func, arg } => match infer_in(ctx, func, cx)? {
+            Type::Function { domain, codomain, .. } => {
+                check_in(ctx, arg, &domain, cx)?;
+                Ok(*codomain)
+            }
+            Type::Pi { param, domain, codomain } => {
+                check_in(ctx, arg, &domain, cx)?;
+                Ok(codomain.subst_term(&param, arg))
+            }
+            other => Err(mismatch(&other, "a function type")),
+        },
+        Term::PathApp { path, point } => match infer_in(ctx, path, cx)? {
+            Type::Path { space, .. } => {
+                check_in(ctx, point, &Type::Interval, cx)?;
+                Ok(*space)
+            }
+            Type::PathP { param, family, .. } => {
+                check_in(ctx, point, &Type::Interval, cx)?;
+                Ok(family.subst_term(&param, point))
+            }
+            other => Err(mismatch(&other, "a path type")),
+        },
+        Term::IZero | Term::IOne => Ok(Type::Interval),
+        Term::IMeet(a, b) | Term::IJoin(a, b) => {
+            check_in(ctx, a, &Type::Interval, cx)?;
+            check_in(ctx, b, &Type::Interval, cx)?;
+            Ok(Type::Interval)
+        }
+        Term::INeg(i) => {
+            check_in(ctx, i, &Type::Interval, cx)?;
+            Ok(Type::Interval)
+        }
+        Term::RealLit(_) => Ok(Type::Real),
+        Term::NatZero => Ok(Type::Nat),
+        Term::NatSucc(n) => {
+            check_in(ctx, n, &Type::Nat, cx)?;
+            Ok(Type::Nat)
+        }
+        Term::NatRec { motive, base, step, scrutinee } => {
+            check_in(ctx, scrutinee, &Type::Nat, cx)?;
+            check_in(ctx, base, motive, cx)?;
+            check_in(ctx, step, &crate::recursion_step(motive), cx)?;
+            Ok((**motive).clone())
+        }
+        Term::Star => Ok(Type::Unit),
+        Term::True | Term::False => Ok(Type::Bool),
+        Term::BoolElim { motive, on_true, on_false, scrutinee } => {
+            check_in(ctx, scrutinee, &Type::Bool, cx)?;
+            check_in(ctx, on_true, motive, cx)?;
+            check_in(ctx, on_false, motive, cx)?;
+            Ok((**motive).clone())
+        }
+        Term::SmoothIn(value) => {
+            let inner = infer_in(ctx, value, cx)?;
+            if !judged_smooth(&inner) {
+                return Err(TypeError::NotSmooth);
+            }
+            Ok(Type::Smooth(Box::new(inner)))
+        }
+        Term::SmoothOut(value) => match infer_in(ctx, value, cx)? {
+            Type::Smooth(inner) => Ok(*inner),
+            other => Err(mismatch(&other, "a smooth type")),
+        },
+        Term::Base => Ok(Type::Circle),
+        Term::Loop => Ok(crate::circle_loop(&Type::Circle, &Term::Base)),
+        Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+            check_in(ctx, scrutinee, &Type::Circle, cx)?;
+            check_in(ctx, base_case, motive, cx)?;
+            check_in(ctx, loop_case, &crate::circle_loop(motive, base_case), cx)?;
+            Ok((**motive).clone())
+        }
+        Term::Case { motive, scrutinee, left_branch, right_branch } => match infer_in(ctx, scrutinee, cx)? {
+            Type::Sum(left, right) => {
+                check_in(ctx, left_branch, &crate::case_branch(&left, motive), cx)?;
+                check_in(ctx, right_branch, &crate::case_branch(&right, motive), cx)?;
+                Ok((**motive).clone())
+            }
+            other => Err(mismatch(&other, "a sum type")),
+        },
+        Term::EmptyElim { motive, scrutinee } => {
+            check_in(ctx, scrutinee, &Type::Empty, cx)?;
+            Ok((**motive).clone())
+        }
+        Term::Let { name, ty, value, body } => {
+            check_in(ctx, value, ty, cx)?;
+            infer_in(ctx, &unfold(name, ty, value, body), cx)
+        }
+        Term::Ann { term, ty } => {
+            check_in(ctx, term, ty, cx)?;
+            Ok((**ty).clone())
+        }
+        Term::SmoothFunc { .. } => {
+            Ok(Type::Function { domain: Box::new(Type::Real), codomain: Box::new(Type::Real), is_smooth: true })
+        }
+        Term::Pair { .. } | Term::Inl(_) | Term::Inr(_) | Term::PathLambda { .. } | Term::Hole => {
+            Err(TypeError::CannotInfer)
+        }
+    }
+}
+
+/// Whether terms of `ty` are known to be smooth: for now `ℝ` and the
+/// smooth function types
+fn judged_smooth(ty: &Type) -> bool {
+    matches!(ty, Type::Real | Type::Function { is_smooth: true, .. })
+}
+
+/// The type of a `λ` binder checked against `domain`: its annotation,
+/// which has to agree, or `domain` itself where binders are unannotated
+fn binder<'t>(domain: &'t Type, annotation: &'t Type, cx: &Cx) -> Result<&'t Type> {
+    if cx.unannotated {
+        return Ok(domain);
+    }
+    expect(domain, annotation, cx)?;
+    Ok(annotation)
+}
+
+/// Check the body of `⟨param⟩ body` against `fibre`, then its endpoints
+/// unless the body has holes
+fn path_body(ctx: &Context, param: &str, body: &Term, fibre: &Type, ends: (&Term, &Term), cx: &Cx) -> Result<()> {
+    let before = cx.holes.borrow().len();
+    check_in(&extend(ctx, param, &Type::Interval), body, fibre, cx)?;
+    if cx.holes.borrow().len() > before {
+        return Ok(());
+    }
+    boundary(param, body, ends.0, ends.1)
+}
+
+/// Whether `⟨param⟩ body` starts at `start` and ends at `end`
+fn boundary(param: &str, body: &Term, start: &Term, end: &Term) -> Result<()> {
+    for (endpoint, expected) in [(Term::IZero, start), (Term::IOne, end)] {
+        let reached = body.subst(param, &endpoint).normalize()?;
+        if !reached.alpha_eq(&expected.normalize()?) {
+            return Err(TypeError::BoundaryViolation);
+        }
+    }
+    Ok(())
+}
+
+/// The type of `record.field` for `record : fields`: the earlier fields
+/// that the field's type mentions are projected out of `record` too
+fn field_type(record: &Term, fields: &[(String, Type)], field: &str) -> Result<Type> {
+    let at = fields
+        .iter()
+        .position(|(label, _)| label == field)
+        .ok_or_else(|| TypeError::NoSuchField(field.to_string()))?;
+    Ok(fields[..at].iter().rev().fold(fields[at].1.clone(), |ty, (label, _)| {
+        ty.subst_term(label, &Term::Proj(Box::new(record.clone()), label.clone()))
+    }))
+}
+
+/// The body of a `let` with the value, annotated, in place of the name:
+/// the value stays known in types, and infers even where it could only
+/// be checked on its own
+fn unfold(name: &str, ty: &Type, value: &Term, body: &Term) -> Term {
+    body.subst(name, &Term::Ann { term: Box::new(value.clone()), ty: Box::new(ty.clone()) })
+}
+
+fn extend(ctx: &Context, name: &str, ty: &Type) -> Vec<(String, Type)> {
+    let mut extended = ctx.to_vec();
+    extended.push((name.to_string(), ty.clone()));
+    extended
+}
+
+/// `got` must be `expected`, up to binder names, computation in path
+/// endpoints and universe levels: a universe fits in any one above it,
+/// and universes inside types have to be at equal levels
+fn expect(expected: &Type, got: &Type, cx: &Cx) -> Result<()> {
+    let mismatch = || TypeError::TypeMismatch { expected: expected.to_string(), got: got.to_string() };
+    let erased = |ty: &Type| endpoints_normalized(ty).map(|ty| ty.map_levels(&mut |_| Level::ZERO));
+    if !erased(expected)?.alpha_eq(&erased(got)?) {
+        return Err(mismatch());
+    }
+    let mut levels = cx.levels.borrow_mut();
+    if let (Type::Universe(upper), Type::Universe(lower)) = (expected, got) {
+        return levels.require(lower, upper);
+    }
+    universes(expected).iter().zip(&universes(got)).try_for_each(|(a, b)| levels.equate(a, b)).map_err(|_| mismatch())
+}
+
+/// The universe levels in `ty`, left to right
+fn universes(ty: &Type) -> Vec<Level> {
+    let mut found = Vec::new();
+    ty.map_levels(&mut |level| {
+        found.push(level.clone());
+        level.clone()
+    });
+    found
+}
+
+fn endpoints_normalized(ty: &Type) -> Result<Type> {
+    let go = |t: &Type| endpoints_normalized(t).map(Box::new);
+    Ok(match ty {
+        Type::Function { domain, codomain, is_smooth } => {
+            Type::Function { domain: go(domain)?, codomain: go(codomain)?, is_smooth: *is_smooth }
+        }
+        Type::Pi { param, domain, codomain } => {
+            Type::Pi { param: param.clone(), domain: go(domain)?, codomain: go(codomain)? }
+        }
+        Type::Sigma { param, domain, codomain } => {
+            Type::Sigma { param: param.clone(), domain: go(domain)?, codomain: go(codomain)? }
+        }
+        Type::Path { space, start, end } => Type::Path {
+            space: go(space)?,
+            start: Box::new(start.normalize()?),
+            end: Box::new(end.normalize()?),
+        },
+        Type::PathP { param, family, start, end } => Type::PathP {
+            param: param.clone(),
+            family: go(family)?,
+            start: Box::new(start.normalize()?),
+            end: Box::new(end.normalize()?),
+        },
+        Type::Smooth(inner) => Type::Smooth(go(inner)?),
+        Type::Sum(left, right) => Type::Sum(go(left)?, go(right)?),
+        Type::Record(fields) => Type::Record(
+            fields.iter().map(|(label, ty)| Ok((label.clone(), endpoints_normalized(ty)?))).collect::<Result<_>>()?,
+        ),
+        Type::Universe(_)
+        | Type::Interval
+        | Type::Real
+        | Type::Nat
+        | Type::Unit
+        | Type::Empty
+        | Type::Bool
+        | Type::Circle => ty.clone(),
+    })
+}
+
+fn mismatch(got: &Type, expected: &str) -> TypeError {
+    TypeError::TypeMismatch { expected: expected.to_string(), got: got.to_string() }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::simp::parse_term;
+    use crate::skeleton::parse_type;
+    use crate::Span;
+
+    fn term(source: &str) -> Term {
+        parse_term(source).unwrap()
+    }
+
+    /// `Σ(n:ℝ). Path ℝ n n`: a point with a loop at it
+    fn pointed() -> Type {
+        Type::Sigma {
+            param: "n".to_string(),
+            domain: Box::new(Type::Real),
+            codomain: Box::new(Type::Path {
+                space: Box::new(Type::Real),
+                start: Box::new(Term::Var("n".to_string())),
+                end: Box::new(Term::Var("n".to_string())),
+            }),
+        }
+    }
+
+    /// `λm. λn. natrec[ℕ] n (λk. λr. succ r) m`
+    pub(crate) fn addition() -> Term {
+        let lam = |param: &str, body| Term::Lambda {
+            param: param.to_string(),
+            param_type: Box::new(Type::Nat),
+            body: Box::new(body),
+        };
+        let var = |name: &str| Box::new(Term::Var(name.to_string()));
+        lam(
+            "m",
+            lam(
+                "n",
+                Term::NatRec {
+                    motive: Box::new(Type::Nat),
+                    base: var("n"),
+                    step: Box::new(lam("k", lam("r", Term::NatSucc(var("r"))))),
+                    scrutinee: var("m"),
+                },
+            ),
+        )
+    }
+
+    /// `λ(s : ℝ + ℕ). case[ℕ + ℝ] s (λ(a : ℝ). inr a) (λ(b : ℕ). inl b)`
+    pub(crate) fn swap() -> Term {
+        let lam = |param: &str, ty: &str, body| Term::Lambda {
+            param: param.to_string(),
+            param_type: Box::new(parse_type(ty).unwrap()),
+            body: Box::new(body),
+        };
+        let var = |name: &str| Box::new(Term::Var(name.to_string()));
+        lam(
+            "s",
+            "ℝ + ℕ",
+            Term::Case {
+                motive: Box::new(parse_type("ℕ + ℝ").unwrap()),
+                scrutinee: var("s"),
+                left_branch: Box::new(lam("a", "ℝ", Term::Inr(var("a")))),
+                right_branch: Box::new(lam("b", "ℕ", Term::Inl(var("b")))),
+            },
+        )
+    }
+
+    #[test]
+    fn test_recursion_on_naturals() {
+        let nat = Box::new(Type::Nat);
+        let binary = Type::Function {
+            domain: nat.clone(),
+            codomain: Box::new(Type::Function { domain: nat.clone(), codomain: nat, is_smooth: false }),
+            is_smooth: false,
+        };
+        assert_eq!(infer(&[], &addition()).unwrap(), binary);
+        assert_eq!(infer(&[], &Term::nat(3)).unwrap(), Type::Nat);
+        assert!(matches!(infer(&[], &Term::NatSucc(Box::new(Term::IOne))), Err(TypeError::TypeMismatch { .. })));
+
+        // The base has to have the motive's type
+        let ill = Term::NatRec {
+            motive: Box::new(Type::Real),
+            base: Box::new(Term::NatZero),
+            step: Box::new(Term::Hole),
+            scrutinee: Box::new(Term::nat(1)),
+        };
+        assert!(matches!(infer(&[], &ill), Err(TypeError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_finite_types() {
+        let absurd = |motive: Type| Term::EmptyElim { motive: Box::new(motive), scrutinee: Box::new(term("e")) };
+        let hypothesis = [("e".to_string(), Type::Empty)];
+        // From an element of Empty, anything at all
+        for motive in [Type::Bool, Type::Nat, Type::Universe(crate::Level::Const(2)), pointed()] {
+            assert_eq!(infer(&hypothesis, &absurd(motive.clone())).unwrap(), motive);
+        }
+        assert!(check(&hypothesis, &absurd(Type::Unit), &Type::Unit).is_ok());
+        let not_empty = [("e".to_string(), Type::Unit)];
+        assert!(matches!(infer(&not_empty, &absurd(Type::Bool)), Err(TypeError::TypeMismatch { .. })));
+
+        let case = |on_true, on_false| Term::BoolElim {
+            motive: Box::new(Type::Nat),
+            on_true: Box::new(on_true),
+            on_false: Box::new(on_false),
+            scrutinee: Box::new(Term::True),
+        };
+        assert_eq!(infer(&[], &case(Term::nat(1), Term::NatZero)).unwrap(), Type::Nat);
+        assert!(matches!(infer(&[], &case(Term::nat(1), Term::Star)), Err(TypeError::TypeMismatch { .. })));
+        assert_eq!(infer(&[], &Term::Star).unwrap(), Type::Unit);
+    }
+
+    #[test]
+    fn test_circle_elimination() {
+        let ctx = [("a".to_string(), Type::Real), ("s".to_string(), Type::Circle)];
+        let map = |loop_case: &str| Term::CircleElim {
+            motive: Box::new(Type::Real),
+            base_case: Box::new(term("a")),
+            loop_case: Box::new(term(loop_case)),
+            scrutinee: Box::new(term("s")),
+        };
+        assert_eq!(infer(&ctx, &map("⟨i⟩ a")).unwrap(), Type::Real);
+        // The loop has to go round from the base case back to it
+        assert!(matches!(infer(&ctx, &map("⟨i⟩ 2.5")), Err(TypeError::BoundaryViolation)));
+        let around = Type::Path { space: Box::new(Type::Circle), start: Box::new(Term::Base), end: Box::new(Term::Base) };
+        assert_eq!(infer(&[], &Term::Loop).unwrap(), around);
+        assert!(check(&[], &Term::Base, &around).is_err());
+    }
+
+    #[test]
+    fn test_swap_on_sums() {
+        assert_eq!(infer(&[], &swap()).unwrap(), parse_type("ℝ + ℕ → ℕ + ℝ").unwrap());
+        assert!(check(&[], &swap(), &parse_type("ℝ + ℕ → ℝ + ℕ").unwrap()).is_err());
+        // Injections only check, against a sum with the right summand
+        let ctx = [("n".to_string(), Type::Nat)];
+        let left = Term::Inl(Box::new(term("n")));
+        assert!(check(&ctx, &left, &parse_type("ℕ + Bool").unwrap()).is_ok());
+        assert!(check(&ctx, &left, &parse_type("Bool + ℕ").unwrap()).is_err());
+        assert!(matches!(infer(&ctx, &left), Err(TypeError::CannotInfer)));
+    }
+
+    #[test]
+    fn test_smooth_modality() {
+        let ctx = [
+            ("x".to_string(), Type::Real),
+            ("n".to_string(), Type::Nat),
+            ("f".to_string(), parse_type("C∞(ℝ, ℝ)").unwrap()),
+            ("g".to_string(), parse_type("ℝ → ℝ").unwrap()),
+        ];
+        let smooth = |ty: Type| Type::Smooth(Box::new(ty));
+        let into = |name: &str| Term::SmoothIn(Box::new(term(name)));
+        assert_eq!(infer(&ctx, &into("x")).unwrap(), smooth(Type::Real));
+        assert!(check(&ctx, &into("f"), &smooth(parse_type("C∞(ℝ, ℝ)").unwrap())).is_ok());
+        assert_eq!(infer(&ctx, &Term::SmoothOut(Box::new(into("f")))).unwrap(), parse_type("C∞(ℝ, ℝ)").unwrap());
+
+        // ℕ and plain functions are not known to be smooth
+        assert!(matches!(infer(&ctx, &into("n")), Err(TypeError::NotSmooth)));
+        assert!(matches!(check(&ctx, &into("g"), &smooth(parse_type("ℝ → ℝ").unwrap())), Err(TypeError::NotSmooth)));
+        assert!(matches!(infer(&ctx, &Term::SmoothOut(Box::new(term("x")))), Err(TypeError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_paths_over_a_line_of_types() {
+        let ctx = [("a".to_string(), Type::Real), ("r".to_string(), Type::Interval)];
+        // Over a constant line, PathP is Path: the same paths inhabit both
+        let over = parse_type("PathP (⟨i⟩ ℝ) a a").unwrap();
+        let plain = parse_type("Path ℝ a a").unwrap();
+        assert_eq!(over.to_string(), "PathP (⟨i⟩ ℝ) a a");
+        let refl = term("⟨j⟩ a");
+        assert!(check(&ctx, &refl, &over).is_ok() && check(&ctx, &refl, &plain).is_ok());
+        let ctx = [ctx[0].clone(), ctx[1].clone(), ("q".to_string(), over)];
+        assert_eq!(infer(&ctx, &term("q @ r")).unwrap(), Type::Real);
+        assert!(matches!(check(&ctx, &term("⟨j⟩ 2.5"), &ctx[2].1), Err(TypeError::BoundaryViolation)));
+
+        // Over `⟨i⟩ Path ℝ (p @ 0) (p @ i)`, the fibre at a point is the
+        // path type there
+        let ctx = [("p".to_string(), parse_type("Path ℝ a b").unwrap()), ("r".to_string(), Type::Interval)];
+        let line = parse_type("PathP (⟨i⟩ Path ℝ (p @ 0) (p @ i)) (⟨j⟩ p @ 0) (⟨j⟩ p @ j)").unwrap();
+        assert!(check(&ctx, &term("⟨i⟩ ⟨j⟩ p @ (i ∧ j)"), &line).is_ok());
+        let ctx = [ctx[0].clone(), ctx[1].clone(), ("s".to_string(), line.clone())];
+        assert_eq!(infer(&ctx, &term("s @ r")).unwrap(), parse_type("Path ℝ (p @ 0) (p @ r)").unwrap());
+        // The endpoints have to live at the ends of the line
+        let wrong = parse_type("PathP (⟨i⟩ Path ℝ (p @ 0) (p @ i)) (⟨j⟩ p @ j) (⟨j⟩ p @ j)").unwrap();
+        assert!(check(&ctx, &term("⟨i⟩ ⟨j⟩ p @ j"), &wrong).is_err());
+    }
+
+    #[test]
+    fn test_shadowed_lets() {
+        let ctx = [("a".to_string(), Type::Real), ("f".to_string(), parse_type("ℝ → ℝ").unwrap())];
+        // The inner value sees the outer x; the body sees the inner one
+        let shadowed = term("let x : ℝ = a in let x : ℝ = f x in x");
+        assert_eq!(shadowed.to_string(), "let x : ℝ = a in let x : ℝ = f x in x");
+        assert_eq!(shadowed.normalize().unwrap(), term("f a"));
+        assert_eq!(infer(&ctx, &shadowed).unwrap(), Type::Real);
+        assert_eq!(shadowed.free_vars().into_iter().collect::<Vec<_>>(), ["a", "f"]);
+
+        let retyped = term("let x : ℝ = a in let x : I = 0 in x");
+        assert_eq!(infer(&ctx, &retyped).unwrap(), Type::Interval);
+        assert!(matches!(infer(&ctx, &term("let x : I = a in x")), Err(TypeError::TypeMismatch { .. })));
+        // Substituting under a let renames its binder out of the way
+        assert_eq!(term("let y : ℝ = a in x").subst("x", &term("y")).to_string(), "let y' : ℝ = a in y");
+    }
+
+    #[test]
+    fn test_annotations_switch_to_checking() {
+        let ctx = [("x".to_string(), Type::Real)];
+        // Path lambdas and pairs only check, so alone they do not infer
+        assert!(matches!(infer(&ctx, &term("⟨i⟩ x")), Err(TypeError::CannotInfer)));
+        assert_eq!(infer(&ctx, &term("(⟨i⟩ x : Path ℝ x x)")).unwrap(), parse_type("Path ℝ x x").unwrap());
+        assert_eq!(infer(&ctx, &term("((x, ⟨i⟩ x) : Σ(n : ℝ). Path ℝ n n)")).unwrap(), pointed());
+
+        let compose = term("(λ(f : ℝ → ℝ). λy. f y : (ℝ → ℝ) → ℝ → ℝ)");
+        assert_eq!(infer(&[], &compose).unwrap(), parse_type("(ℝ → ℝ) → ℝ → ℝ").unwrap());
+        // An annotation has to agree with the binders it covers
+        assert!(matches!(infer(&[], &term("(λ(n : ℕ). n : ℝ → ℝ)")), Err(TypeError::TypeMismatch { .. })));
+        assert!(matches!(infer(&ctx, &term("(⟨i⟩ x : Path ℝ x 2)")), Err(TypeError::BoundaryViolation)));
+        assert_eq!(term("(⟨i⟩ x : Path ℝ x x) @ 0").normalize().unwrap(), term("x"));
+    }
+
+    #[test]
+    fn test_errors_carry_the_span_of_the_offending_subterm() {
+        let ctx = [("f".to_string(), parse_type("ℝ → ℝ").unwrap()), ("i".to_string(), Type::Interval)];
+        let source = "λ(x : ℝ).\n  f (f\n    i)";
+        let error = infer_source(&ctx, source).unwrap_err();
+        assert!(matches!(error.unlocated(), TypeError::TypeMismatch { .. }));
+        assert_eq!(error.span(), Some(Span { start: 21, end: 22 }));
+        assert_eq!(
+            error.render(source),
+            "error: Type mismatch: expected ℝ, got I\n --> 3:5\n  |\n3 |     i)\n  |     ^"
+        );
+
+        // Applying a non-function is reported at the whole application
+        let error = check_source(&ctx, "λ(x : ℝ).\n  x (f x)", &parse_type("ℝ → ℝ").unwrap()).unwrap_err();
+        assert_eq!(error.span(), Some(Span { start: 12, end: 19 }));
+        // Checking a term that was not parsed gives no span
+        assert_eq!(infer(&ctx, &term("f i")).unwrap_err().span(), None);
+        assert_eq!(infer_source(&ctx, "f (").unwrap_err().span(), Some(Span { start: 3, end: 4 }));
+    }
+
+    #[test]
+    fn test_second_component_is_checked_against_the_first() {
+        let ctx = [("x".to_string(), Type::Real), ("y".to_string(), Type::Real)];
+        assert!(check(&ctx, &term("(x, ⟨i⟩ x)"), &pointed()).is_ok());
+        assert!(check(&ctx, &term("(y, ⟨i⟩ y)"), &pointed()).is_ok());
+        // The loop has to sit at the first component, not anywhere
+        assert!(matches!(check(&ctx, &term("(x, ⟨i⟩ y)"), &pointed()), Err(TypeError::BoundaryViolation)));
+        // Endpoints compute before they are compared
+        assert!(check(&ctx, &term("((λz. z) x, ⟨i⟩ x)"), &pointed()).is_ok());
+        assert!(matches!(check(&ctx, &term("(x, y)"), &pointed()), Err(TypeError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_projections_infer_through_the_sigma() {
+        let ctx = [("p".to_string(), pointed())];
+        assert_eq!(infer(&ctx, &term("π₁ p")).unwrap(), Type::Real);
+        let first = Box::new(term("π₁ p"));
+        let path = Type::Path { space: Box::new(Type::Real), start: first.clone(), end: first };
+        assert_eq!(infer(&ctx, &term("π₂ p")).unwrap(), path);
+        assert_eq!(infer(&ctx, &term("π₂ p @ 0")).unwrap(), Type::Real);
+
+        // A projection out of a pair built on the spot checks as well
+        let swapped = term("(π₁ p, π₂ p)");
+        assert!(check(&ctx, &swapped, &pointed()).is_ok());
+        assert!(matches!(infer(&[], &term("π₁ x")), Err(TypeError::VariableNotFound(_))));
+        assert!(matches!(infer(&ctx, &term("π₁ (π₁ p)")), Err(TypeError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_dependent_record_fields() {
+        let ctx = [("a".to_string(), Type::Real), ("b".to_string(), Type::Real)];
+        let pointed: Type = "{ x : ℝ, loop : Path ℝ x x }".parse().unwrap();
+        // Fields may come in any order; `loop` is checked against `x`'s value
+        assert!(check(&ctx, &term("{ x := a, loop := ⟨i⟩ a }"), &pointed).is_ok());
+        assert!(check(&ctx, &term("{ loop := ⟨i⟩ b, x := b }"), &pointed).is_ok());
+        assert!(matches!(check(&ctx, &term("{ x := a, loop := ⟨i⟩ b }"), &pointed), Err(TypeError::BoundaryViolation)));
+
+        let ctx = [("r".to_string(), pointed)];
+        let x = Box::new(term("r.x"));
+        assert_eq!(infer(&ctx, &term("r.x")).unwrap(), Type::Real);
+        assert_eq!(
+            infer(&ctx, &term("r.loop")).unwrap(),
+            Type::Path { space: Box::new(Type::Real), start: x.clone(), end: x }
+        );
+        assert!(matches!(infer(&ctx, &term("r.y")), Err(TypeError::NoSuchField(y)) if y == "y"));
+    }
+
+    #[test]
+    fn test_record_literals_need_every_field() {
+        let ctx = [("a".to_string(), Type::Real)];
+        let point: Type = "{ x : ℝ, y : ℝ }".parse().unwrap();
+        assert!(matches!(check(&ctx, &term("{ x := a }"), &point), Err(TypeError::MissingField(y)) if y == "y"));
+        let extra = check(&ctx, &term("{ x := a, y := a, z := a }"), &point);
+        assert!(matches!(extra, Err(TypeError::NoSuchField(z)) if z == "z"));
+        assert_eq!(infer(&ctx, &term("{ x := a, y := a }")).unwrap(), point);
+    }
+}
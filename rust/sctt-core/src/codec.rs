@@ -0,0 +1,671 @@
+//! Compact binary encoding of terms and types
+//!
+//! An encoded value starts with a header, `SCTT`, the format version as a
+//! little-endian `u16` and a kind byte (`t` for a term, `T` for a type),
+//! followed by the value itself: one tag byte per constructor, then its
+//! fields in declaration order. Strings are a LEB128 length and UTF-8
+//! bytes, universe levels a LEB128 number, reals eight little-endian
+//! bytes, flags one byte.
+//!
+//! Tags are fixed per constructor and never reused, so adding a
+//! constructor only appends a tag and old data keeps decoding.
+//! [`FORMAT_VERSION`] changes when an existing encoding does; data of
+//! another version is refused with [`DecodeError::Version`] rather than
+//! misread.
+//!
+//! This is hand-written rather than derived through serde so the crate
+//! keeps its dependency footprint without the `serde` feature.
+
+use thiserror::Error;
+
+use crate::{Level, Term, Type};
+
+/// The version of the encoding this build writes and reads
+pub const FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"SCTT";
+const TERM: u8 = b't';
+const TYPE: u8 = b'T';
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    #[error("not an encoded SCTT value")]
+    NotEncoded,
+
+    #[error("encoded with format version {found}, but this build reads version {expected}")]
+    Version { expected: u16, found: u16 },
+
+    #[error("expected an encoded {expected}")]
+    Kind { expected: &'static str },
+
+    #[error("data ends early at byte {offset}")]
+    UnexpectedEnd { offset: usize },
+
+    #[error("unknown {what} tag {tag} at byte {offset}")]
+    UnknownTag { what: &'static str, tag: u8, offset: usize },
+
+    #[error("malformed number at byte {offset}")]
+    Number { offset: usize },
+
+    #[error("invalid UTF-8 in a name at byte {offset}")]
+    InvalidName { offset: usize },
+
+    #[error("unexpected data after the value at byte {offset}")]
+    Trailing { offset: usize },
+}
+
+impl Term {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = header(TERM);
+        write_term(&mut out, self);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Term, DecodeError> {
+        let mut reader = Reader::open(bytes, TERM, "term")?;
+        let term = reader.term()?;
+        reader.finish(term)
+    }
+}
+
+impl Type {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = header(TYPE);
+        write_type(&mut out, self);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Type, DecodeError> {
+        let mut reader = Reader::open(bytes, TYPE, "type")?;
+        let ty = reader.ty()?;
+        reader.finish(ty)
+    }
+}
+
+fn header(kind: u8) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.push(kind);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Writing
+
+fn write_number(out: &mut Vec<u8>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_number(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_type(out: &mut Vec<u8>, ty: &Type) {
+    match ty {
+        Type::Universe(Level::Const(n)) => {
+            out.push(0);
+            write_number(out, *n as u64);
+        }
+        Type::Universe(level) => {
+            out.push(15);
+            write_level(out, level);
+        }
+        Type::Function { domain, codomain, is_smooth } => {
+            out.push(1);
+            write_type(out, domain);
+            write_type(out, codomain);
+            out.push(*is_smooth as u8);
+        }
+        Type::Pi { param, domain, codomain } | Type::Sigma { param, domain, codomain } => {
+            out.push(if matches!(ty, Type::Pi { .. }) { 2 } else { 3 });
+            write_str(out, param);
+            write_type(out, domain);
+            write_type(out, codomain);
+        }
+        Type::Path { space, start, end } => {
+            out.push(4);
+            write_type(out, space);
+            write_term(out, start);
+            write_term(out, end);
+        }
+        Type::Interval => out.push(5),
+        Type::Smooth(inner) => {
+            out.push(6);
+            write_type(out, inner);
+        }
+        Type::Real => out.push(7),
+        Type::Nat => out.push(8),
+        Type::Unit => out.push(9),
+        Type::Empty => out.push(10),
+        Type::Bool => out.push(11),
+        Type::Record(fields) => {
+            out.push(12);
+            write_number(out, fields.len() as u64);
+            for (label, ty) in fields {
+                write_str(out, label);
+                write_type(out, ty);
+            }
+        }
+        Type::Circle => out.push(13),
+        Type::PathP { param, family, start, end } => {
+            out.push(16);
+            write_str(out, param);
+            write_type(out, family);
+            write_term(out, start);
+            write_term(out, end);
+        }
+        Type::Sum(left, right) => {
+            out.push(14);
+            write_type(out, left);
+            write_type(out, right);
+        }
+    }
+}
+
+fn write_level(out: &mut Vec<u8>, level: &Level) {
+    match level {
+        Level::Const(n) => {
+            out.push(0);
+            write_number(out, *n as u64);
+        }
+        Level::Var(x) => {
+            out.push(1);
+            write_str(out, x);
+        }
+        Level::Succ(inner) => {
+            out.push(2);
+            write_level(out, inner);
+        }
+        Level::Max(a, b) => {
+            out.push(3);
+            write_level(out, a);
+            write_level(out, b);
+        }
+    }
+}
+
+fn write_term(out: &mut Vec<u8>, term: &Term) {
+    let tag = |out: &mut Vec<u8>, tag: u8, terms: &[&Term]| {
+        out.push(tag);
+        for t in terms {
+            write_term(out, t);
+        }
+    };
+    match term {
+        Term::Var(name) => {
+            out.push(0);
+            write_str(out, name);
+        }
+        Term::Lambda { param, param_type, body } => {
+            out.push(1);
+            write_str(out, param);
+            write_type(out, param_type);
+            write_term(out, body);
+        }
+        Term::App { func, arg } => tag(out, 2, &[func, arg]),
+        Term::Pair { first, second } => tag(out, 3, &[first, second]),
+        Term::Fst(t) => tag(out, 4, &[t]),
+        Term::Snd(t) => tag(out, 5, &[t]),
+        Term::PathLambda { param, body } => {
+            out.push(6);
+            write_str(out, param);
+            write_term(out, body);
+        }
+        Term::PathApp { path, point } => tag(out, 7, &[path, point]),
+        Term::IZero => tag(out, 8, &[]),
+        Term::IOne => tag(out, 9, &[]),
+        Term::IMeet(a, b) => tag(out, 10, &[a, b]),
+        Term::IJoin(a, b) => tag(out, 11, &[a, b]),
+        Term::INeg(t) => tag(out, 12, &[t]),
+        Term::RealLit(x) => {
+            out.push(13);
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        Term::SmoothFunc { expr, var } => {
+            out.push(14);
+            write_str(out, expr);
+            write_str(out, var);
+        }
+        Term::Hole => tag(out, 15, &[]),
+        Term::NatZero => tag(out, 16, &[]),
+        Term::NatSucc(t) => tag(out, 17, &[t]),
+        Term::NatRec { motive, base, step, scrutinee } => {
+            out.push(18);
+            write_type(out, motive);
+            for t in [base, step, scrutinee] {
+                write_term(out, t);
+            }
+        }
+        Term::Star => tag(out, 19, &[]),
+        Term::True => tag(out, 20, &[]),
+        Term::False => tag(out, 21, &[]),
+        Term::BoolElim { motive, on_true, on_false, scrutinee } => {
+            out.push(22);
+            write_type(out, motive);
+            for t in [on_true, on_false, scrutinee] {
+                write_term(out, t);
+            }
+        }
+        Term::EmptyElim { motive, scrutinee } => {
+            out.push(23);
+            write_type(out, motive);
+            write_term(out, scrutinee);
+        }
+        Term::Let { name, ty, value, body } => {
+            out.push(24);
+            write_str(out, name);
+            write_type(out, ty);
+            write_term(out, value);
+            write_term(out, body);
+        }
+        Term::Ann { term, ty } => {
+            out.push(25);
+            write_term(out, term);
+            write_type(out, ty);
+        }
+        Term::RecordLit(fields) => {
+            out.push(26);
+            write_number(out, fields.len() as u64);
+            for (label, value) in fields {
+                write_str(out, label);
+                write_term(out, value);
+            }
+        }
+        Term::Proj(record, field) => {
+            out.push(27);
+            write_term(out, record);
+            write_str(out, field);
+        }
+        Term::Base => tag(out, 28, &[]),
+        Term::Loop => tag(out, 29, &[]),
+        Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+            out.push(30);
+            write_type(out, motive);
+            for t in [base_case, loop_case, scrutinee] {
+                write_term(out, t);
+            }
+        }
+        Term::Inl(value) => tag(out, 31, &[value]),
+        Term::Inr(value) => tag(out, 32, &[value]),
+        Term::SmoothIn(value) => tag(out, 34, &[value]),
+        Term::SmoothOut(value) => tag(out, 35, &[value]),
+        Term::Case { motive, scrutinee, left_branch, right_branch } => {
+            out.push(33);
+            write_type(out, motive);
+            for t in [scrutinee, left_branch, right_branch] {
+                write_term(out, t);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reading
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn open(bytes: &'a [u8], kind: u8, expected: &'static str) -> Result<Self, DecodeError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(DecodeError::NotEncoded);
+        }
+        let mut reader = Reader { bytes, pos: MAGIC.len() };
+        let found = u16::from_le_bytes([reader.byte()?, reader.byte()?]);
+        if found != FORMAT_VERSION {
+            return Err(DecodeError::Version { expected: FORMAT_VERSION, found });
+        }
+        if reader.byte()? != kind {
+            return Err(DecodeError::Kind { expected });
+        }
+        Ok(reader)
+    }
+
+    fn finish<T>(self, value: T) -> Result<T, DecodeError> {
+        if self.pos == self.bytes.len() { Ok(value) } else { Err(DecodeError::Trailing { offset: self.pos }) }
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        let byte = *self.bytes.get(self.pos).ok_or(DecodeError::UnexpectedEnd { offset: self.pos })?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or(DecodeError::UnexpectedEnd { offset: self.bytes.len() })?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn number(&mut self) -> Result<u64, DecodeError> {
+        let offset = self.pos;
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(DecodeError::Number { offset })
+    }
+
+    fn level(&mut self) -> Result<Level, DecodeError> {
+        let offset = self.pos;
+        u32::try_from(self.number()?).map(Level::Const).map_err(|_| DecodeError::Number { offset })
+    }
+
+    fn level_expr(&mut self) -> Result<Level, DecodeError> {
+        let offset = self.pos;
+        let boxed = |r: &mut Self| r.level_expr().map(Box::new);
+        Ok(match self.byte()? {
+            0 => self.level()?,
+            1 => Level::Var(self.string()?),
+            2 => Level::Succ(boxed(self)?),
+            3 => Level::Max(boxed(self)?, boxed(self)?),
+            tag => return Err(DecodeError::UnknownTag { what: "level", tag, offset }),
+        })
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.number()? as usize;
+        let offset = self.pos;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidName { offset })
+    }
+
+    /// A count, then that many labels each followed by a value
+    fn fields<T>(&mut self, value: fn(&mut Self) -> Result<T, DecodeError>) -> Result<Vec<(String, T)>, DecodeError> {
+        let mut fields = Vec::new();
+        for _ in 0..self.number()? {
+            fields.push((self.string()?, value(self)?));
+        }
+        Ok(fields)
+    }
+
+    fn ty(&mut self) -> Result<Type, DecodeError> {
+        let offset = self.pos;
+        let boxed = |r: &mut Self| r.ty().map(Box::new);
+        Ok(match self.byte()? {
+            0 => Type::Universe(self.level()?),
+            1 => Type::Function { domain: boxed(self)?, codomain: boxed(self)?, is_smooth: self.byte()? != 0 },
+            2 => Type::Pi { param: self.string()?, domain: boxed(self)?, codomain: boxed(self)? },
+            3 => Type::Sigma { param: self.string()?, domain: boxed(self)?, codomain: boxed(self)? },
+            4 => Type::Path { space: boxed(self)?, start: Box::new(self.term()?), end: Box::new(self.term()?) },
+            5 => Type::Interval,
+            6 => Type::Smooth(boxed(self)?),
+            7 => Type::Real,
+            8 => Type::Nat,
+            9 => Type::Unit,
+            10 => Type::Empty,
+            11 => Type::Bool,
+            12 => Type::Record(self.fields(Self::ty)?),
+            13 => Type::Circle,
+            14 => Type::Sum(boxed(self)?, boxed(self)?),
+            15 => Type::Universe(self.level_expr()?),
+            16 => Type::PathP {
+                param: self.string()?,
+                family: boxed(self)?,
+                start: Box::new(self.term()?),
+                end: Box::new(self.term()?),
+            },
+            tag => return Err(DecodeError::UnknownTag { what: "type", tag, offset }),
+        })
+    }
+
+    fn term(&mut self) -> Result<Term, DecodeError> {
+        let offset = self.pos;
+        let boxed = |r: &mut Self| r.term().map(Box::new);
+        let ty = |r: &mut Self| r.ty().map(Box::new);
+        Ok(match self.byte()? {
+            0 => Term::Var(self.string()?),
+            1 => Term::Lambda { param: self.string()?, param_type: ty(self)?, body: boxed(self)? },
+            2 => Term::App { func: boxed(self)?, arg: boxed(self)? },
+            3 => Term::Pair { first: boxed(self)?, second: boxed(self)? },
+            4 => Term::Fst(boxed(self)?),
+            5 => Term::Snd(boxed(self)?),
+            6 => Term::PathLambda { param: self.string()?, body: boxed(self)? },
+            7 => Term::PathApp { path: boxed(self)?, point: boxed(self)? },
+            8 => Term::IZero,
+            9 => Term::IOne,
+            10 => Term::IMeet(boxed(self)?, boxed(self)?),
+            11 => Term::IJoin(boxed(self)?, boxed(self)?),
+            12 => Term::INeg(boxed(self)?),
+            13 => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Term::RealLit(f64::from_le_bytes(bytes))
+            }
+            14 => Term::SmoothFunc { expr: self.string()?, var: self.string()? },
+            15 => Term::Hole,
+            16 => Term::NatZero,
+            17 => Term::NatSucc(boxed(self)?),
+            18 => Term::NatRec { motive: ty(self)?, base: boxed(self)?, step: boxed(self)?, scrutinee: boxed(self)? },
+            19 => Term::Star,
+            20 => Term::True,
+            21 => Term::False,
+            22 => Term::BoolElim {
+                motive: ty(self)?,
+                on_true: boxed(self)?,
+                on_false: boxed(self)?,
+                scrutinee: boxed(self)?,
+            },
+            23 => Term::EmptyElim { motive: ty(self)?, scrutinee: boxed(self)? },
+            24 => Term::Let { name: self.string()?, ty: ty(self)?, value: boxed(self)?, body: boxed(self)? },
+            25 => Term::Ann { term: boxed(self)?, ty: ty(self)? },
+            26 => Term::RecordLit(self.fields(Self::term)?),
+            27 => Term::Proj(boxed(self)?, self.string()?),
+            28 => Term::Base,
+            29 => Term::Loop,
+            30 => Term::CircleElim {
+                motive: ty(self)?,
+                base_case: boxed(self)?,
+                loop_case: boxed(self)?,
+                scrutinee: boxed(self)?,
+            },
+            31 => Term::Inl(boxed(self)?),
+            32 => Term::Inr(boxed(self)?),
+            34 => Term::SmoothIn(boxed(self)?),
+            35 => Term::SmoothOut(boxed(self)?),
+            33 => Term::Case {
+                motive: ty(self)?,
+                scrutinee: boxed(self)?,
+                left_branch: boxed(self)?,
+                right_branch: boxed(self)?,
+            },
+            tag => return Err(DecodeError::UnknownTag { what: "term", tag, offset }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn name() -> impl Strategy<Value = String> {
+        prop_oneof![Just("x".to_string()), Just("i".to_string()), "[a-zα-ω][a-z0-9']{0,6}"]
+    }
+
+    fn level() -> impl Strategy<Value = Level> {
+        let leaf = prop_oneof![(0u32..5).prop_map(Level::Const), name().prop_map(Level::Var)];
+        leaf.prop_recursive(2, 6, 2, |inner| {
+            prop_oneof![
+                inner.clone().prop_map(|l| Level::Succ(Box::new(l))),
+                (inner.clone(), inner).prop_map(|(a, b)| Level::Max(Box::new(a), Box::new(b))),
+            ]
+        })
+    }
+
+    fn ty() -> impl Strategy<Value = Type> {
+        let leaf = prop_oneof![
+            (0u32..5).prop_map(|n| Type::Universe(Level::Const(n))),
+            level().prop_map(Type::Universe),
+            Just(Type::Interval),
+            Just(Type::Real),
+            Just(Type::Nat),
+            Just(Type::Unit),
+            Just(Type::Empty),
+            Just(Type::Bool),
+            Just(Type::Circle),
+        ];
+        leaf.prop_recursive(3, 12, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone(), any::<bool>()).prop_map(|(d, c, is_smooth)| Type::Function {
+                    domain: Box::new(d),
+                    codomain: Box::new(c),
+                    is_smooth
+                }),
+                (name(), inner.clone(), inner.clone()).prop_map(|(param, d, c)| Type::Pi {
+                    param,
+                    domain: Box::new(d),
+                    codomain: Box::new(c)
+                }),
+                (name(), inner.clone(), inner.clone()).prop_map(|(param, d, c)| Type::Sigma {
+                    param,
+                    domain: Box::new(d),
+                    codomain: Box::new(c)
+                }),
+                (inner.clone(), name(), name()).prop_map(|(space, a, b)| Type::Path {
+                    space: Box::new(space),
+                    start: Box::new(Term::Var(a)),
+                    end: Box::new(Term::Var(b))
+                }),
+                (name(), inner.clone(), name(), name()).prop_map(|(param, family, a, b)| Type::PathP {
+                    param,
+                    family: Box::new(family),
+                    start: Box::new(Term::Var(a)),
+                    end: Box::new(Term::Var(b))
+                }),
+                inner.clone().prop_map(|t| Type::Smooth(Box::new(t))),
+                (inner.clone(), inner.clone()).prop_map(|(l, r)| Type::Sum(Box::new(l), Box::new(r))),
+                prop::collection::vec((name(), inner), 0..3).prop_map(Type::Record),
+            ]
+        })
+    }
+
+    fn term() -> impl Strategy<Value = Term> {
+        let leaf = prop_oneof![
+            name().prop_map(Term::Var),
+            Just(Term::IZero),
+            Just(Term::IOne),
+            Just(Term::Hole),
+            Just(Term::NatZero),
+            Just(Term::Star),
+            Just(Term::True),
+            Just(Term::False),
+            Just(Term::Base),
+            Just(Term::Loop),
+            any::<f64>().prop_filter("NaN is not equal to itself", |x| !x.is_nan()).prop_map(Term::RealLit),
+            ("[ -~]{0,12}", name()).prop_map(|(expr, var)| Term::SmoothFunc { expr, var }),
+        ];
+        leaf.prop_recursive(4, 32, 3, |inner| {
+            let b = |t: Term| Box::new(t);
+            prop_oneof![
+                (name(), ty(), inner.clone()).prop_map(move |(param, t, body)| Term::Lambda {
+                    param,
+                    param_type: Box::new(t),
+                    body: b(body)
+                }),
+                (inner.clone(), inner.clone()).prop_map(move |(f, a)| Term::App { func: b(f), arg: b(a) }),
+                (inner.clone(), inner.clone()).prop_map(move |(x, y)| Term::Pair { first: b(x), second: b(y) }),
+                inner.clone().prop_map(move |t| Term::Fst(b(t))),
+                inner.clone().prop_map(move |t| Term::Snd(b(t))),
+                (name(), inner.clone()).prop_map(move |(param, body)| Term::PathLambda { param, body: b(body) }),
+                (inner.clone(), inner.clone()).prop_map(move |(p, r)| Term::PathApp { path: b(p), point: b(r) }),
+                (inner.clone(), inner.clone()).prop_map(move |(x, y)| Term::IMeet(b(x), b(y))),
+                (inner.clone(), inner.clone()).prop_map(move |(x, y)| Term::IJoin(b(x), b(y))),
+                inner.clone().prop_map(move |t| Term::INeg(b(t))),
+                inner.clone().prop_map(move |t| Term::NatSucc(b(t))),
+                (ty(), inner.clone(), inner.clone(), inner.clone()).prop_map(move |(m, z, s, n)| Term::NatRec {
+                    motive: Box::new(m),
+                    base: b(z),
+                    step: b(s),
+                    scrutinee: b(n)
+                }),
+                (ty(), inner.clone(), inner.clone(), inner.clone()).prop_map(move |(m, t, f, c)| Term::BoolElim {
+                    motive: Box::new(m),
+                    on_true: b(t),
+                    on_false: b(f),
+                    scrutinee: b(c)
+                }),
+                (ty(), inner.clone(), inner.clone(), inner.clone()).prop_map(move |(m, b0, l, s)| Term::CircleElim {
+                    motive: Box::new(m),
+                    base_case: b(b0),
+                    loop_case: b(l),
+                    scrutinee: b(s)
+                }),
+                inner.clone().prop_map(move |t| Term::Inl(b(t))),
+                inner.clone().prop_map(move |t| Term::Inr(b(t))),
+                inner.clone().prop_map(move |t| Term::SmoothIn(b(t))),
+                inner.clone().prop_map(move |t| Term::SmoothOut(b(t))),
+                (ty(), inner.clone(), inner.clone(), inner.clone()).prop_map(move |(m, s, l, r)| Term::Case {
+                    motive: Box::new(m),
+                    scrutinee: b(s),
+                    left_branch: b(l),
+                    right_branch: b(r)
+                }),
+                (ty(), inner.clone()).prop_map(move |(m, e)| Term::EmptyElim { motive: Box::new(m), scrutinee: b(e) }),
+                (name(), ty(), inner.clone(), inner.clone()).prop_map(move |(name, t, v, e)| Term::Let {
+                    name,
+                    ty: Box::new(t),
+                    value: b(v),
+                    body: b(e)
+                }),
+                (inner.clone(), ty()).prop_map(move |(t, a)| Term::Ann { term: b(t), ty: Box::new(a) }),
+                prop::collection::vec((name(), inner.clone()), 0..3).prop_map(Term::RecordLit),
+                (inner, name()).prop_map(move |(t, field)| Term::Proj(b(t), field)),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_terms_round_trip(term in term()) {
+            prop_assert_eq!(Term::from_bytes(&term.to_bytes()), Ok(term));
+        }
+
+        #[test]
+        fn test_types_round_trip(ty in ty()) {
+            prop_assert_eq!(Type::from_bytes(&ty.to_bytes()), Ok(ty));
+        }
+
+        #[test]
+        fn test_truncated_data_is_an_error(term in term(), cut in any::<prop::sample::Index>()) {
+            let bytes = term.to_bytes();
+            let cut = cut.index(bytes.len());
+            prop_assert!(Term::from_bytes(&bytes[..cut]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_other_versions_are_refused() {
+        let mut bytes = Term::nat(2).to_bytes();
+        bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let err = Term::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err, DecodeError::Version { expected: FORMAT_VERSION, found: FORMAT_VERSION + 1 });
+        assert_eq!(
+            err.to_string(),
+            format!("encoded with format version {}, but this build reads version {}", FORMAT_VERSION + 1, FORMAT_VERSION)
+        );
+
+        assert_eq!(Term::from_bytes("λx. x".as_bytes()), Err(DecodeError::NotEncoded));
+        assert_eq!(Type::from_bytes(&Term::Star.to_bytes()), Err(DecodeError::Kind { expected: "type" }));
+        let mut trailing = Type::Real.to_bytes();
+        trailing.push(0);
+        assert_eq!(Type::from_bytes(&trailing), Err(DecodeError::Trailing { offset: 8 }));
+    }
+}
@@ -0,0 +1,246 @@
+//! Engine versions on stored artifacts, and what to do when they differ
+//!
+//! Everything persisted from the engine's output records the
+//! [`ENGINE_VERSION`] that produced it. A consumer calls [`assess`] with the
+//! stored version; [`COMPATIBILITY`] says, per format, which version bumps
+//! the format survives as-is and what to do with one that did not:
+//!
+//! | Format                | Survives | Otherwise                           |
+//! |-----------------------|----------|-------------------------------------|
+//! | lab history entries   | patch    | re-check the code, ignore the result |
+//! | run records           | patch    | re-check                            |
+//! | detached certificates | patch    | refuse, offer a re-compile          |
+//! | proof scripts         | minor    | warn, then replay                   |
+//!
+//! A missing or unparsable stored version is treated as a major bump.
+//! The same version strings drive the reload banner: a tab whose loaded
+//! engine differs from the server's is told to reload ([`VersionWatch`]).
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The engine this build is
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// How far apart two versions are, smallest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Bump {
+    Same,
+    Patch,
+    Minor,
+    Major,
+}
+
+/// The bump from `stored` to `current`; anything unparsable is a major one
+pub fn bump(stored: &str, current: &str) -> Bump {
+    match (parse(stored), parse(current)) {
+        (Some(a), Some(b)) if a == b => Bump::Same,
+        (Some(a), Some(b)) if a[..2] == b[..2] => Bump::Patch,
+        (Some(a), Some(b)) if a[0] == b[0] => Bump::Minor,
+        _ => Bump::Major,
+    }
+}
+
+/// `major.minor.patch`, ignoring any pre-release or build suffix
+fn parse(version: &str) -> Option<[u64; 3]> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let version = [parts.next()??, parts.next()??, parts.next()??];
+    parts.next().is_none().then_some(version)
+}
+
+/// What a consumer does with an artifact from an incompatible engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Policy {
+    /// Run the check again instead of trusting the stored result
+    Recheck,
+    /// Do not use it; offer to compile the source again
+    Refuse,
+    /// Use it, but warn that steps may no longer apply
+    Replay,
+}
+
+/// Every stored format that carries engine output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Format {
+    /// Lab history kept in browser storage
+    HistoryEntry,
+    /// [`RunRecord`](crate::record::RunRecord)s in exports and run logs
+    RunRecord,
+    /// Certificates in the server's artifact cache
+    Certificate,
+    /// Saved proofs
+    ProofScript,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::HistoryEntry, Format::RunRecord, Format::Certificate, Format::ProofScript];
+
+    /// This format's row of [`COMPATIBILITY`]
+    pub fn rule(self) -> Option<&'static Rule> {
+        COMPATIBILITY.iter().find(|rule| rule.format == self)
+    }
+}
+
+/// One row of the compatibility table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    pub format: Format,
+    /// The largest bump the format is used across unchanged
+    pub survives: Bump,
+    pub policy: Policy,
+}
+
+/// Which stored formats survive which version bumps
+pub const COMPATIBILITY: &[Rule] = &[
+    Rule { format: Format::HistoryEntry, survives: Bump::Patch, policy: Policy::Recheck },
+    Rule { format: Format::RunRecord, survives: Bump::Patch, policy: Policy::Recheck },
+    Rule { format: Format::Certificate, survives: Bump::Patch, policy: Policy::Refuse },
+    Rule { format: Format::ProofScript, survives: Bump::Minor, policy: Policy::Replay },
+];
+
+/// The outcome of comparing a stored artifact's engine with the current one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verdict {
+    pub format: Format,
+    /// Empty when the artifact did not record one
+    pub stored: String,
+    pub current: String,
+    /// `None` when the artifact can be used as it is
+    pub action: Option<Policy>,
+}
+
+impl Verdict {
+    pub fn is_current(&self) -> bool {
+        self.action.is_none()
+    }
+
+    /// What to tell the user, when there is anything to say
+    pub fn notice(&self) -> Option<String> {
+        let stored = if self.stored.is_empty() { "an unknown engine" } else { &self.stored };
+        let current = &self.current;
+        Some(match self.action? {
+            Policy::Recheck => format!("Result from engine {}; checking again with {}", stored, current),
+            Policy::Refuse => format!("Built by engine {}, which engine {} no longer accepts; re-compile to use it", stored, current),
+            Policy::Replay => format!("Written for engine {}; replaying with {}, some steps may need attention", stored, current),
+        })
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.notice() {
+            Some(notice) => write!(f, "{}", notice),
+            None => write!(f, "engine {} is compatible", self.current),
+        }
+    }
+}
+
+/// Compare `stored` with this build's [`ENGINE_VERSION`]
+pub fn assess(format: Format, stored: Option<&str>) -> Verdict {
+    assess_against(format, stored, ENGINE_VERSION)
+}
+
+/// Compare `stored` with `current` under `format`'s rule
+pub fn assess_against(format: Format, stored: Option<&str>, current: &str) -> Verdict {
+    let stored = stored.unwrap_or_default();
+    // A format without a rule is never trusted across versions
+    let (survives, policy) = format.rule().map_or((Bump::Same, Policy::Recheck), |r| (r.survives, r.policy));
+    Verdict {
+        format,
+        stored: stored.to_string(),
+        current: current.to_string(),
+        action: (bump(stored, current) > survives).then_some(policy),
+    }
+}
+
+/// Tracks the server's engine version against the one this tab loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionWatch {
+    loaded: String,
+    available: Option<String>,
+}
+
+impl VersionWatch {
+    pub fn new(loaded: &str) -> Self {
+        VersionWatch { loaded: loaded.to_string(), available: None }
+    }
+
+    /// Take the version the server reported; `true` exactly when it is a
+    /// different version than the last one reported and differs from the
+    /// loaded one, i.e. when a "reload" banner should appear
+    pub fn observe(&mut self, server: &str) -> bool {
+        if server == self.loaded {
+            self.available = None;
+            return false;
+        }
+        if self.available.as_deref() == Some(server) {
+            return false;
+        }
+        self.available = Some(server.to_string());
+        true
+    }
+
+    /// The newer version the banner is offering, if any
+    pub fn available(&self) -> Option<&str> {
+        self.available.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_persisted_format_declares_a_policy() {
+        for format in Format::ALL {
+            let rules = COMPATIBILITY.iter().filter(|r| r.format == format).count();
+            assert_eq!(rules, 1, "{:?} needs exactly one compatibility rule", format);
+        }
+        assert_eq!(COMPATIBILITY.len(), Format::ALL.len());
+    }
+
+    #[test]
+    fn test_policies_apply_past_the_surviving_bump() {
+        assert_eq!(bump("0.3.1", "0.3.4"), Bump::Patch);
+        assert_eq!(bump("0.3.1", "0.4.0-rc.1"), Bump::Minor);
+        assert_eq!(bump("", "0.4.0"), Bump::Major);
+
+        assert!(assess_against(Format::HistoryEntry, Some("0.4.0"), "0.4.2").is_current());
+        let cached = assess_against(Format::HistoryEntry, Some("0.3.9"), "0.4.0");
+        assert_eq!(cached.action, Some(Policy::Recheck));
+        assert_eq!(assess_against(Format::RunRecord, None, "0.4.0").action, Some(Policy::Recheck));
+
+        let proof = |stored| assess_against(Format::ProofScript, Some(stored), "1.4.0").action;
+        assert_eq!(proof("1.2.0"), None);
+        assert_eq!(proof("0.9.0"), Some(Policy::Replay));
+    }
+
+    #[test]
+    fn test_refused_artifacts_offer_a_recompile() {
+        let verdict = assess_against(Format::Certificate, Some("0.3.0"), "0.4.0");
+        assert_eq!(verdict.action, Some(Policy::Refuse));
+        assert!(verdict.notice().unwrap().contains("re-compile"));
+        assert_eq!(assess_against(Format::Certificate, Some("0.4.0"), "0.4.0").notice(), None);
+    }
+
+    #[test]
+    fn test_banner_fires_exactly_on_a_version_change() {
+        let mut watch = VersionWatch::new("0.4.0");
+        assert!(!watch.observe("0.4.0"));
+        assert!(watch.observe("0.4.1"));
+        assert!(!watch.observe("0.4.1"));
+        assert_eq!(watch.available(), Some("0.4.1"));
+        assert!(watch.observe("0.5.0"));
+        // A rollback to what the tab runs needs no reload
+        assert!(!watch.observe("0.4.0"));
+        assert_eq!(watch.available(), None);
+    }
+}
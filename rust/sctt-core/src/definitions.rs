@@ -0,0 +1,266 @@
+//! Global definitions
+//!
+//! A [`Definitions`] store is the working file behind a context: each name
+//! has a type and, unless it is a postulate, a body. Checking sees only the
+//! types, so a definition is opaque to [`check`](crate::check) exactly as a
+//! variable is; [`Definitions::normalize`] unfolds bodies (δ-reduction)
+//! before computing. Local binders shadow definitions of the same name.
+//!
+//! A recursive definition made with [`Definitions::define_fix`] refers to
+//! itself in its body, so unfolding it takes one step of the recursion.
+//! [`Definitions::termination`] is the kernel's report on them: which were
+//! checked, and which definitions rest on an `unsafe_fix`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::check::{self, Context};
+use crate::termination::{self, FixDefinition, FixKind, TerminationReport, TerminationStatus};
+use crate::{Result, Term, Type, TypeError};
+
+/// The type of a global name, and its body unless it is postulated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub ty: Type,
+    pub body: Option<Term>,
+    /// How the definition recurses, if it is a `fix`
+    pub fix: Option<FixKind>,
+}
+
+/// Definitions in the order they were made; each sees the ones before it
+#[derive(Debug, Clone, Default)]
+pub struct Definitions {
+    entries: Vec<(String, Definition)>,
+}
+
+impl Definitions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `name : ty := body`, checking the body against the type with
+    /// the earlier definitions in scope; names can be defined only once
+    pub fn define(&mut self, name: &str, ty: Type, body: Option<Term>) -> Result<()> {
+        if self.get(name).is_some() {
+            return Err(TypeError::AlreadyDefined(name.to_string()));
+        }
+        if let Some(body) = &body {
+            check::check(&self.context(), body, &ty)?;
+        }
+        self.entries.push((name.to_string(), Definition { ty, body, fix: None }));
+        Ok(())
+    }
+
+    /// Add a recursive definition, checking its body with itself in scope
+    /// and, unless it is an `unsafe_fix`, its recursive calls; a rejected
+    /// call is located at its span
+    pub fn define_fix(&mut self, def: &FixDefinition) -> Result<TerminationStatus> {
+        if self.get(&def.name).is_some() {
+            return Err(TypeError::AlreadyDefined(def.name.clone()));
+        }
+        def.check_types(&self.context())?;
+        let status = def.status();
+        if let TerminationStatus::Rejected(e) = &status {
+            let error = TypeError::Termination(e.clone());
+            return Err(match e.span() {
+                Some(span) => error.at(span),
+                None => error,
+            });
+        }
+        let body = def.params.iter().rev().fold(def.body.clone(), |body, (param, ty)| Term::Lambda {
+            param: param.clone(),
+            param_type: Box::new(ty.clone()),
+            body: Box::new(body),
+        });
+        let entry = Definition { ty: def.closed_type(), body: Some(body), fix: Some(def.kind) };
+        self.entries.push((def.name.clone(), entry));
+        Ok(status)
+    }
+
+    /// Remove `name`, which no later definition may still mention
+    pub fn undefine(&mut self, name: &str) -> Result<Definition> {
+        let at = self
+            .entries
+            .iter()
+            .position(|(defined, _)| defined == name)
+            .ok_or_else(|| TypeError::VariableNotFound(name.to_string()))?;
+        if let Some((by, _)) = self.entries[at + 1..].iter().find(|(_, def)| mentions(def, name)) {
+            return Err(TypeError::InUse { name: name.to_string(), by: by.clone() });
+        }
+        Ok(self.entries.remove(at).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Definition> {
+        self.entries.iter().find(|(defined, _)| defined == name).map(|(_, def)| def)
+    }
+
+    /// Names and definitions, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Definition)> {
+        self.entries.iter().map(|(name, def)| (name.as_str(), def))
+    }
+
+    /// The defined names with their types, to check terms in
+    pub fn context(&self) -> Vec<(String, Type)> {
+        self.entries.iter().map(|(name, def)| (name.clone(), def.ty.clone())).collect()
+    }
+
+    /// The type of `term` with the definitions and then `locals` in scope
+    pub fn infer(&self, locals: &Context, term: &Term) -> Result<Type> {
+        let mut ctx = self.context();
+        ctx.extend_from_slice(locals);
+        check::infer(&ctx, term)
+    }
+
+    /// `term` with every free occurrence of a defined name replaced by its
+    /// body, until none is left; postulates stay as they are
+    pub fn unfold(&self, term: &Term) -> Term {
+        // Newest first, so bodies unfolded in put back the older names they use
+        self.entries.iter().rev().fold(term.clone(), |term, (name, def)| match &def.body {
+            Some(body) if term.free_vars().contains(name) => term.subst(name, body),
+            _ => term,
+        })
+    }
+
+    /// The normal form of `term` after unfolding definitions
+    pub fn normalize(&self, term: &Term) -> Result<Term> {
+        self.unfold(term).normalize()
+    }
+
+    /// The status of each recursive definition, and every definition that
+    /// depends, directly or through others, on an `unsafe_fix`
+    pub fn termination(&self) -> TerminationReport {
+        let names: BTreeSet<String> = self.entries.iter().map(|(name, _)| name.clone()).collect();
+        let deps: BTreeMap<String, BTreeSet<String>> = self
+            .entries
+            .iter()
+            .map(|(name, def)| {
+                let mut used = def.ty.free_vars();
+                used.extend(def.body.iter().flat_map(Term::free_vars));
+                used.remove(name);
+                (name.clone(), used.intersection(&names).cloned().collect())
+            })
+            .collect();
+        let unsafe_names = self
+            .entries
+            .iter()
+            .filter(|(_, def)| def.fix == Some(FixKind::Unsafe))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let statuses = self.entries.iter().filter_map(|(name, def)| {
+            let status = match def.fix? {
+                FixKind::Checked => TerminationStatus::Structural,
+                FixKind::Unsafe => TerminationStatus::Unchecked,
+            };
+            Some((name.clone(), status))
+        });
+        TerminationReport {
+            statuses: statuses.collect(),
+            tainted: termination::taint(&deps, &unsafe_names),
+        }
+    }
+}
+
+fn mentions(def: &Definition, name: &str) -> bool {
+    def.ty.free_vars().contains(name) || def.body.as_ref().is_some_and(|body| body.free_vars().contains(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simp::parse_term;
+    use crate::skeleton::parse_type;
+    use crate::Level;
+
+    fn term(source: &str) -> Term {
+        parse_term(source).unwrap()
+    }
+
+    /// `add` and `double n = add n n` on ℕ
+    fn arithmetic() -> Definitions {
+        let mut defs = Definitions::new();
+        defs.define("add", parse_type("ℕ → ℕ → ℕ").unwrap(), Some(crate::check::tests::addition())).unwrap();
+        defs.define("double", parse_type("ℕ → ℕ").unwrap(), Some(term("λ(n : ℕ). add n n"))).unwrap();
+        defs
+    }
+
+    #[test]
+    fn test_definitions_unfold_when_normalizing() {
+        let defs = arithmetic();
+        let applied = Term::App { func: Box::new(term("double")), arg: Box::new(Term::nat(3)) };
+        assert_eq!(defs.infer(&[], &applied).unwrap(), Type::Nat);
+        assert_eq!(defs.normalize(&applied).unwrap(), Term::nat(6));
+        // Without unfolding, `double` is just a name
+        assert_eq!(applied.normalize().unwrap(), applied);
+
+        let names: Vec<&str> = defs.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["add", "double"]);
+    }
+
+    #[test]
+    fn test_local_binders_shadow_definitions() {
+        let defs = arithmetic();
+        let local = [("double".to_string(), Type::Real)];
+        assert_eq!(defs.infer(&local, &term("double")).unwrap(), Type::Real);
+        assert!(matches!(defs.infer(&local, &term("double 2")), Err(TypeError::TypeMismatch { .. })));
+
+        let shadowing = term("λ(double : ℕ). double");
+        assert_eq!(defs.unfold(&shadowing), shadowing);
+    }
+
+    #[test]
+    fn test_level_polymorphic_identity() {
+        let mut defs = Definitions::new();
+        defs.define("id", parse_type("Type_l → Type_l").unwrap(), Some(term("λ(X : Type_l). X"))).unwrap();
+        let locals = [("A".to_string(), Type::Universe(Level::Const(0))), ("B".to_string(), Type::Universe(Level::Const(1)))];
+        assert_eq!(defs.infer(&locals, &term("id A")).unwrap().to_string(), "Type_0");
+        assert_eq!(defs.infer(&locals, &term("id B")).unwrap().to_string(), "Type_1");
+        assert_eq!(defs.infer(&locals, &term("id")).unwrap().to_string(), "Type_l → Type_l");
+
+        // Each use gets a level of its own, so one term can use both
+        let mut ctx = defs.context();
+        ctx.extend_from_slice(&locals);
+        let both = parse_type("Σ (x : Type_0). Type_1").unwrap();
+        assert!(check::check(&ctx, &term("(id A, id B)"), &both).is_ok());
+        let err = check::check(&ctx, &term("id B"), &Type::Universe(Level::ZERO)).unwrap_err();
+        assert!(matches!(err, TypeError::UnsolvableLevels(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_redefinition_and_removal() {
+        let mut defs = arithmetic();
+        let again = defs.define("add", Type::Nat, None);
+        assert!(matches!(again, Err(TypeError::AlreadyDefined(name)) if name == "add"));
+        // Bodies are checked against their types
+        assert!(defs.define("zero", Type::Real, Some(Term::NatZero)).is_err());
+        assert!(defs.get("zero").is_none());
+
+        let removal = defs.undefine("add");
+        assert!(matches!(removal, Err(TypeError::InUse { by, .. }) if by == "double"));
+        assert!(defs.undefine("double").is_ok());
+        assert!(defs.undefine("add").is_ok());
+        assert!(matches!(defs.undefine("add"), Err(TypeError::VariableNotFound(_))));
+    }
+
+    #[test]
+    fn test_recursive_definitions_and_their_taint() {
+        let source = "fix plus (m : ℕ) (n : ℕ) : ℕ := natrec[ℕ] n (λ(k : ℕ). λ(r : ℕ). succ (plus k n)) m
+             unsafe_fix spin (n : ℕ) : ℕ := spin n
+             fix loop (n : ℕ) : ℕ := succ (loop n)";
+        let fixes = crate::termination::parse_fixes(source).unwrap();
+        let mut defs = Definitions::new();
+        assert_eq!(defs.define_fix(&fixes[0]).unwrap(), TerminationStatus::Structural);
+        assert_eq!(defs.define_fix(&fixes[1]).unwrap(), TerminationStatus::Unchecked);
+        let err = defs.define_fix(&fixes[2]).unwrap_err();
+        assert!(matches!(err.unlocated(), TypeError::Termination(_)), "{}", err);
+        assert!(err.render(source).ends_with("^^^^^^"), "{}", err.render(source));
+        assert!(defs.get("loop").is_none());
+
+        // An ordinary definition using `spin` is tainted by it
+        defs.define("three", Type::Nat, Some(term("plus (succ zero) (spin (succ succ zero))"))).unwrap();
+        defs.define("four", Type::Nat, Some(term("succ three"))).unwrap();
+        let report = defs.termination();
+        assert_eq!(report.statuses["plus"], TerminationStatus::Structural);
+        assert!(report.is_trusted("plus"));
+        assert_eq!(report.tainted["four"], BTreeSet::from(["spin".to_string()]));
+        assert!(!report.is_trusted("four"));
+    }
+}
@@ -0,0 +1,428 @@
+//! Typing SCTT on any keyboard: ASCII digraphs and configurable key bindings
+//!
+//! The REPL and the game share this module so that a symbol is spelled the
+//! same way everywhere:
+//!
+//! - [`expand`] rewrites ASCII digraphs (`\lambda`, `->`, `/\`) to the
+//!   symbols the parsers read. [`DIGRAPHS`] is the table `:symbols` lists.
+//! - [`Key`] names a key press as text (`alt+l`, `ctrl+x`, `up`, `f2`),
+//!   independent of the terminal library that reports it.
+//! - [`InputConfig`] holds the REPL's symbol keys and the game's remapped
+//!   actions, stored as `input.json` in [`data_dir`].
+//!
+//! Named digraphs start with `\` and match a whole identifier, so `\pi`
+//! expands but `\pix` and `spi` do not, and `\x. x` is left for the parser.
+//! Operator digraphs match a whole run of operator characters: `a->b`
+//! expands, `a-->b` does not.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// `(ascii, symbol)`, in the order `:symbols` lists them
+pub const DIGRAPHS: &[(&str, &str)] = &[
+    ("\\lambda", "λ"),
+    ("\\lam", "λ"),
+    ("\\partial", "∂"),
+    ("\\Pi", "Π"),
+    ("\\Sigma", "Σ"),
+    ("\\R", "ℝ"),
+    ("\\Cinf", "C∞"),
+    ("\\and", "∧"),
+    ("\\or", "∨"),
+    ("\\not", "¬"),
+    ("\\to", "→"),
+    ("\\circ", "∘"),
+    ("\\times", "×"),
+    ("\\forall", "∀"),
+    ("\\exists", "∃"),
+    ("\\pi", "π"),
+    ("\\infty", "∞"),
+    ("\\langle", "⟨"),
+    ("\\rangle", "⟩"),
+    ("\\le", "≤"),
+    ("\\ge", "≥"),
+    ("\\ne", "≠"),
+    ("\\simeq", "≃"),
+    ("->", "→"),
+    ("/\\", "∧"),
+    ("\\/", "∨"),
+    ("<=", "≤"),
+    (">=", "≥"),
+    ("!=", "≠"),
+];
+
+/// Characters that run together into one operator
+fn is_operator(c: char) -> bool {
+    "-<>=/\\!|&*+^~".contains(c)
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+fn lookup(ascii: &str) -> Option<&'static str> {
+    DIGRAPHS.iter().find(|(a, _)| *a == ascii).map(|(_, symbol)| *symbol)
+}
+
+/// `line` with every ASCII digraph replaced by its symbol
+pub fn expand(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    // Whether `chars[i - 1]` ends a plain identifier, not a `\name`
+    let mut in_ident = false;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && chars.get(i + 1).is_some_and(|&n| is_ident(n)) && !in_ident {
+            let end = (i + 1..chars.len()).find(|&j| !is_ident(chars[j])).unwrap_or(chars.len());
+            let word: String = chars[i..end].iter().collect();
+            out.push_str(lookup(&word).unwrap_or(&word));
+            i = end;
+            in_ident = false;
+        } else if is_operator(c) {
+            let end = (i..chars.len()).find(|&j| !is_operator(chars[j])).unwrap_or(chars.len());
+            let run: String = chars[i..end].iter().collect();
+            out.push_str(lookup(&run).unwrap_or(&run));
+            i = end;
+            in_ident = false;
+        } else {
+            out.push(c);
+            i += 1;
+            in_ident = is_ident(c);
+        }
+    }
+    out
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    #[error("unknown key `{0}`; write e.g. `q`, `alt+l`, `ctrl+x`, `up`, `f2`")]
+    BadKey(String),
+
+    #[error("input config: {0}")]
+    Config(String),
+}
+
+/// A key without modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyName {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    F(u8),
+}
+
+/// A key press, written `[ctrl+][alt+]name`
+///
+/// Letters compare case-insensitively, so a binding to `p` also fires with
+/// shift or caps lock held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(into = "String", try_from = "String"))]
+pub struct Key {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub name: KeyName,
+}
+
+impl Key {
+    pub const fn plain(name: KeyName) -> Self {
+        Key { ctrl: false, alt: false, name }
+    }
+
+    pub const fn char(c: char) -> Self {
+        Key::plain(KeyName::Char(c))
+    }
+
+    pub const fn alt(c: char) -> Self {
+        Key { ctrl: false, alt: true, name: KeyName::Char(c) }
+    }
+
+    fn folded(self) -> Self {
+        match self.name {
+            KeyName::Char(c) => Key { name: KeyName::Char(c.to_ascii_lowercase()), ..self },
+            _ => self,
+        }
+    }
+
+    pub fn matches(&self, other: &Key) -> bool {
+        self.folded() == other.folded()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "alt+")?;
+        }
+        match self.name {
+            KeyName::Char(' ') => write!(f, "space"),
+            KeyName::Char(c) => write!(f, "{}", c),
+            KeyName::Up => write!(f, "up"),
+            KeyName::Down => write!(f, "down"),
+            KeyName::Left => write!(f, "left"),
+            KeyName::Right => write!(f, "right"),
+            KeyName::Enter => write!(f, "enter"),
+            KeyName::Esc => write!(f, "esc"),
+            KeyName::Backspace => write!(f, "backspace"),
+            KeyName::Tab => write!(f, "tab"),
+            KeyName::F(n) => write!(f, "f{}", n),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, InputError> {
+        let bad = || InputError::BadKey(s.to_string());
+        let mut key = Key::char(' ');
+        let mut rest = s;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            if lower.starts_with("ctrl+") && rest.len() > 5 {
+                key.ctrl = true;
+                rest = &rest[5..];
+            } else if lower.starts_with("alt+") && rest.len() > 4 {
+                key.alt = true;
+                rest = &rest[4..];
+            } else {
+                break;
+            }
+        }
+        key.name = match rest.to_ascii_lowercase().as_str() {
+            "space" => KeyName::Char(' '),
+            "up" => KeyName::Up,
+            "down" => KeyName::Down,
+            "left" => KeyName::Left,
+            "right" => KeyName::Right,
+            "enter" => KeyName::Enter,
+            "esc" => KeyName::Esc,
+            "backspace" => KeyName::Backspace,
+            "tab" => KeyName::Tab,
+            name => {
+                let mut chars = rest.chars();
+                match (name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()), chars.next(), chars.next()) {
+                    (Some(n), _, _) if (1..=12).contains(&n) => KeyName::F(n),
+                    (_, Some(c), None) => KeyName::Char(c),
+                    _ => return Err(bad()),
+                }
+            }
+        };
+        Ok(key)
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> String {
+        key.to_string()
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = InputError;
+
+    fn try_from(s: String) -> Result<Self, InputError> {
+        s.parse()
+    }
+}
+
+/// The REPL's symbol keys and the game's action bindings
+///
+/// Actions are named by the program that owns them; one left out here
+/// keeps the defaults that program passes to [`InputConfig::resolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputConfig {
+    /// Key to the symbol it inserts at the REPL prompt
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub symbols: BTreeMap<Key, String>,
+    /// Action to the keys that trigger it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub actions: BTreeMap<String, Vec<Key>>,
+}
+
+impl Default for InputConfig {
+    /// `alt+` the first letter of the symbol's name, for the symbols the
+    /// prompt needs most
+    fn default() -> Self {
+        let symbols = [('l', "λ"), ('d', "∂"), ('p', "Π"), ('r', "ℝ"), ('a', "∧"), ('o', "∨"), ('n', "¬"), ('t', "→")]
+            .into_iter()
+            .map(|(c, symbol)| (Key::alt(c), symbol.to_string()))
+            .collect();
+        InputConfig { symbols, actions: BTreeMap::new() }
+    }
+}
+
+impl InputConfig {
+    /// The keys bound to `action`: configured ones, else `defaults`
+    pub fn keys<'a>(&'a self, action: &str, defaults: &'a [Key]) -> &'a [Key] {
+        self.actions.get(action).map_or(defaults, Vec::as_slice)
+    }
+
+    /// The action `key` triggers among `actions` (name, default keys)
+    ///
+    /// Configured bindings win over defaults, so remapping a key away from
+    /// its default action takes effect without touching that action.
+    pub fn resolve<'a>(&self, key: &Key, actions: &[(&'a str, &[Key])]) -> Option<&'a str> {
+        let configured = actions
+            .iter()
+            .find(|(name, _)| self.actions.get(*name).is_some_and(|keys| keys.iter().any(|k| k.matches(key))));
+        let default = || {
+            actions
+                .iter()
+                .find(|(name, defaults)| !self.actions.contains_key(*name) && defaults.iter().any(|k| k.matches(key)))
+        };
+        configured.or_else(default).map(|(name, _)| *name)
+    }
+
+    /// Bind `action` to `key` alone
+    pub fn rebind(&mut self, action: &str, key: Key) {
+        self.actions.insert(action.to_string(), vec![key]);
+    }
+
+    /// Where the config lives: `input.json` in [`data_dir`]
+    pub fn path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("input.json"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl InputConfig {
+    pub fn from_json(json: &str) -> Result<Self, InputError> {
+        serde_json::from_str(json).map_err(|e| InputError::Config(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Read the config at `path`; a missing file is the default config
+    pub fn load(path: &Path) -> Result<Self, InputError> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(InputError::Config(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), InputError> {
+        let write = || {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, self.to_json())
+        };
+        write().map_err(|e| InputError::Config(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Per-user data directory: `$SCTT_DATA_DIR`, else `$XDG_DATA_HOME/sctt`,
+/// `~/.local/share/sctt`, or `%APPDATA%\sctt`
+pub fn data_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    var("SCTT_DATA_DIR")
+        .or_else(|| var("XDG_DATA_HOME").map(|d| d.join("sctt")))
+        .or_else(|| var("HOME").map(|d| d.join(".local/share/sctt")))
+        .or_else(|| var("APPDATA").map(|d| d.join("sctt")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expansion_at_string_boundaries() {
+        assert_eq!(expand("\\lambda"), "λ");
+        assert_eq!(expand("\\lambda x. x"), "λ x. x");
+        assert_eq!(expand("f : \\R -> \\R"), "f : ℝ → ℝ");
+        assert_eq!(expand("->"), "→");
+        assert_eq!(expand("x\\/y"), "x∨y");
+        assert_eq!(expand("(x/\\y)"), "(x∧y)");
+        assert_eq!(expand("\\not\\partial"), "¬∂");
+        assert_eq!(expand(""), "");
+        assert_eq!(expand("λx. x"), "λx. x");
+    }
+
+    #[test]
+    fn test_expansion_inside_identifiers_and_operators() {
+        // Only whole names expand, and never from inside an identifier
+        assert_eq!(expand("\\pix"), "\\pix");
+        assert_eq!(expand("a\\pi"), "a\\pi");
+        assert_eq!(expand("\\pi_1"), "\\pi_1");
+        assert_eq!(expand("\\x. x"), "\\x. x");
+        assert_eq!(expand("spin to"), "spin to");
+        // Only whole operator runs expand
+        assert_eq!(expand("a-->b"), "a-->b");
+        assert_eq!(expand("a<->b"), "a<->b");
+        assert_eq!(expand("a - b"), "a - b");
+        assert_eq!(expand("a<=b"), "a≤b");
+        assert_eq!(expand("\\"), "\\");
+    }
+
+    #[test]
+    fn test_keys_parse_and_resolve() {
+        for text in ["q", "alt+l", "ctrl+alt+x", "up", "f2", "space", "?", "+", "alt++", "esc"] {
+            let key: Key = text.parse().unwrap();
+            assert_eq!(key.to_string(), text);
+        }
+        assert_eq!("ALT+L".parse::<Key>().unwrap(), Key::alt('L'));
+        assert!("alt+".parse::<Key>().is_err());
+        assert!("f13".parse::<Key>().is_err());
+        assert!("hyper".parse::<Key>().is_err());
+
+        let puzzle = [Key::char('p')];
+        let quit = [Key::char('q')];
+        let actions = [("puzzle", &puzzle[..]), ("quit", &quit[..])];
+        let mut config = InputConfig::default();
+        assert_eq!(config.resolve(&Key::char('P'), &actions), Some("puzzle"));
+        assert_eq!(config.resolve(&Key::char('x'), &actions), None);
+
+        // An AZERTY player moves quit to `a`, then takes `p` for it too
+        config.rebind("quit", Key::char('a'));
+        assert_eq!(config.resolve(&Key::char('a'), &actions), Some("quit"));
+        assert_eq!(config.resolve(&Key::char('q'), &actions), None);
+        config.rebind("quit", Key::char('p'));
+        assert_eq!(config.resolve(&Key::char('p'), &actions), Some("quit"));
+        assert_eq!(config.keys("puzzle", &puzzle), &puzzle);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_config_round_trips() {
+        let mut config = InputConfig::default();
+        config.rebind("settings", "f2".parse().unwrap());
+        config.symbols.insert(Key::alt('s'), "Σ".to_string());
+        let json = config.to_json();
+        assert!(json.contains("\"alt+l\": \"λ\""));
+        assert!(json.contains("\"settings\""));
+        assert_eq!(InputConfig::from_json(&json).unwrap(), config);
+
+        // Either half may be left out
+        let partial = InputConfig::from_json(r#"{ "actions": { "quit": ["ctrl+c"] } }"#).unwrap();
+        assert!(partial.symbols.is_empty());
+        assert!(InputConfig::from_json(r#"{ "symbols": { "hyper+l": "λ" } }"#).is_err());
+
+        let dir = std::env::temp_dir().join(format!("sctt-input-{}", std::process::id()));
+        let path = dir.join("input.json");
+        assert_eq!(InputConfig::load(&path).unwrap(), InputConfig::default());
+        config.save(&path).unwrap();
+        assert_eq!(InputConfig::load(&path).unwrap(), config);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,356 @@
+//! Hashing, size metrics and hash-consing for terms
+//!
+//! `Term` and `Type` hash structurally, consistently with their `PartialEq`
+//! (bound names count, so alpha-equivalent terms may hash differently).
+//! `TermInterner` keeps one `Rc<Term>` per distinct term it is given, so the
+//! goals, hypotheses and definitions of a large proof that repeat are stored
+//! once and compare by pointer.
+//!
+//! Terms own their children through `Box`, so sharing happens at the
+//! granularity of what is interned: interning a term does not split it up.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::rc::Rc;
+
+use crate::{Term, Type};
+
+/// `0.0 == -0.0` under `PartialEq`, so both hash as `0.0`
+fn hash_real<H: Hasher>(x: f64, state: &mut H) {
+    let x = if x == 0.0 { 0.0 } else { x };
+    x.to_bits().hash(state);
+}
+
+impl Hash for Type {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Type::Universe(level) => level.hash(state),
+            Type::Function { domain, codomain, is_smooth } => {
+                domain.hash(state);
+                codomain.hash(state);
+                is_smooth.hash(state);
+            }
+            Type::Pi { param, domain, codomain } | Type::Sigma { param, domain, codomain } => {
+                param.hash(state);
+                domain.hash(state);
+                codomain.hash(state);
+            }
+            Type::Path { space, start, end } => {
+                space.hash(state);
+                start.hash(state);
+                end.hash(state);
+            }
+            Type::PathP { param, family, start, end } => {
+                param.hash(state);
+                family.hash(state);
+                start.hash(state);
+                end.hash(state);
+            }
+            Type::Smooth(inner) => inner.hash(state),
+            Type::Sum(left, right) => {
+                left.hash(state);
+                right.hash(state);
+            }
+            Type::Record(fields) => fields.hash(state),
+            Type::Interval | Type::Real | Type::Nat | Type::Unit | Type::Empty | Type::Bool | Type::Circle => {}
+        }
+    }
+}
+
+impl Hash for Term {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Term::Var(name) => name.hash(state),
+            Term::Lambda { param, param_type, body } => {
+                param.hash(state);
+                param_type.hash(state);
+                body.hash(state);
+            }
+            Term::App { func, arg } => {
+                func.hash(state);
+                arg.hash(state);
+            }
+            Term::Pair { first, second } => {
+                first.hash(state);
+                second.hash(state);
+            }
+            Term::Fst(t)
+            | Term::Snd(t)
+            | Term::INeg(t)
+            | Term::NatSucc(t)
+            | Term::Inl(t)
+            | Term::Inr(t)
+            | Term::SmoothIn(t)
+            | Term::SmoothOut(t) => t.hash(state),
+            Term::RecordLit(fields) => fields.hash(state),
+            Term::Proj(record, field) => {
+                record.hash(state);
+                field.hash(state);
+            }
+            Term::PathLambda { param, body } => {
+                param.hash(state);
+                body.hash(state);
+            }
+            Term::PathApp { path, point } => {
+                path.hash(state);
+                point.hash(state);
+            }
+            Term::IMeet(a, b) | Term::IJoin(a, b) => {
+                a.hash(state);
+                b.hash(state);
+            }
+            Term::RealLit(x) => hash_real(*x, state),
+            Term::SmoothFunc { expr, var } => {
+                expr.hash(state);
+                var.hash(state);
+            }
+            Term::NatRec { motive, base, step, scrutinee } => {
+                motive.hash(state);
+                base.hash(state);
+                step.hash(state);
+                scrutinee.hash(state);
+            }
+            Term::BoolElim { motive, on_true, on_false, scrutinee } => {
+                motive.hash(state);
+                on_true.hash(state);
+                on_false.hash(state);
+                scrutinee.hash(state);
+            }
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+                motive.hash(state);
+                base_case.hash(state);
+                loop_case.hash(state);
+                scrutinee.hash(state);
+            }
+            Term::Case { motive, scrutinee, left_branch, right_branch } => {
+                motive.hash(state);
+                scrutinee.hash(state);
+                left_branch.hash(state);
+                right_branch.hash(state);
+            }
+            Term::EmptyElim { motive, scrutinee } => {
+                motive.hash(state);
+                scrutinee.hash(state);
+            }
+            Term::Let { name, ty, value, body } => {
+                name.hash(state);
+                ty.hash(state);
+                value.hash(state);
+                body.hash(state);
+            }
+            Term::Ann { term, ty } => {
+                term.hash(state);
+                ty.hash(state);
+            }
+            Term::IZero
+            | Term::IOne
+            | Term::Hole
+            | Term::NatZero
+            | Term::Star
+            | Term::True
+            | Term::False
+            | Term::Base
+            | Term::Loop => {}
+        }
+    }
+}
+
+impl Term {
+    /// Visit the immediate subterms, not counting terms inside types
+    fn for_each_child<'a>(&'a self, mut visit: impl FnMut(&'a Term)) {
+        match self {
+            Term::Lambda { body, .. } | Term::PathLambda { body, .. } => visit(body),
+            Term::App { func: a, arg: b }
+            | Term::Pair { first: a, second: b }
+            | Term::PathApp { path: a, point: b }
+            | Term::IMeet(a, b)
+            | Term::IJoin(a, b)
+            | Term::Let { value: a, body: b, .. } => {
+                visit(a);
+                visit(b);
+            }
+            Term::Fst(t)
+            | Term::Snd(t)
+            | Term::INeg(t)
+            | Term::NatSucc(t)
+            | Term::Proj(t, _)
+            | Term::Inl(t)
+            | Term::Inr(t)
+            | Term::SmoothIn(t)
+            | Term::SmoothOut(t) => visit(t),
+            Term::RecordLit(fields) => fields.iter().for_each(|(_, value)| visit(value)),
+            Term::NatRec { base: a, step: b, scrutinee: c, .. }
+            | Term::BoolElim { on_true: a, on_false: b, scrutinee: c, .. }
+            | Term::CircleElim { base_case: a, loop_case: b, scrutinee: c, .. }
+            | Term::Case { scrutinee: a, left_branch: b, right_branch: c, .. } => {
+                visit(a);
+                visit(b);
+                visit(c);
+            }
+            Term::EmptyElim { scrutinee: t, .. } | Term::Ann { term: t, .. } => visit(t),
+            Term::Var(_)
+            | Term::IZero
+            | Term::IOne
+            | Term::RealLit(_)
+            | Term::SmoothFunc { .. }
+            | Term::Hole
+            | Term::NatZero
+            | Term::Star
+            | Term::True
+            | Term::False
+            | Term::Base
+            | Term::Loop => {}
+        }
+    }
+
+    /// Number of term nodes, this one included; types in binders and
+    /// eliminators are not counted
+    pub fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut pending = vec![self];
+        while let Some(term) = pending.pop() {
+            count += 1;
+            term.for_each_child(|child| pending.push(child));
+        }
+        count
+    }
+
+    /// Length of the longest path from this node to a leaf, counting both;
+    /// a variable has depth 1
+    pub fn depth(&self) -> usize {
+        let mut deepest = 0;
+        let mut pending = vec![(self, 1)];
+        while let Some((term, depth)) = pending.pop() {
+            deepest = deepest.max(depth);
+            term.for_each_child(|child| pending.push((child, depth + 1)));
+        }
+        deepest
+    }
+}
+
+/// What a `TermInterner` has seen so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternStats {
+    /// Calls to `intern`
+    pub interned: usize,
+    /// Terms stored, one per distinct term
+    pub distinct: usize,
+    /// Term nodes passed to `intern`
+    pub nodes: usize,
+    /// Of those, nodes answered by a term already stored and so not kept
+    pub shared_nodes: usize,
+}
+
+/// Hash-conses terms into `Rc<Term>`: interning equal terms gives the same
+/// allocation, so equality between interned terms is `Rc::ptr_eq`
+#[derive(Debug, Default)]
+pub struct TermInterner {
+    hasher: RandomState,
+    table: HashMap<u64, Vec<Rc<Term>>>,
+    stats: InternStats,
+}
+
+impl TermInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The stored copy of `term`, storing it first if it is new
+    pub fn intern(&mut self, term: Term) -> Rc<Term> {
+        let nodes = term.node_count();
+        self.stats.interned += 1;
+        self.stats.nodes += nodes;
+
+        let bucket = self.table.entry(self.hasher.hash_one(&term)).or_default();
+        if let Some(existing) = bucket.iter().find(|stored| ***stored == term) {
+            self.stats.shared_nodes += nodes;
+            return Rc::clone(existing);
+        }
+        let stored = Rc::new(term);
+        bucket.push(Rc::clone(&stored));
+        self.stats.distinct += 1;
+        stored
+    }
+
+    /// The stored copy of `term`, if it has been interned
+    pub fn get(&self, term: &Term) -> Option<Rc<Term>> {
+        self.table
+            .get(&self.hasher.hash_one(term))?
+            .iter()
+            .find(|stored| ***stored == *term)
+            .cloned()
+    }
+
+    pub fn stats(&self) -> InternStats {
+        self.stats
+    }
+
+    /// Number of distinct terms stored
+    pub fn len(&self) -> usize {
+        self.stats.distinct
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stats.distinct == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simp::parse_term;
+    use crate::Level;
+
+    /// A full binary tree of applications, `2^depth - 1` nodes
+    fn tree(depth: u32) -> Term {
+        if depth <= 1 {
+            return Term::Var("x".to_string());
+        }
+        Term::App { func: Box::new(tree(depth - 1)), arg: Box::new(tree(depth - 1)) }
+    }
+
+    #[test]
+    fn test_equal_terms_hash_equally() {
+        let state = RandomState::new();
+        let a = parse_term("λx. (x, π₁ (f x)) @ (i ∧ j)").unwrap();
+        let b = parse_term("λx. (x, π₁ (f x)) @ (i ∧ j)").unwrap();
+        assert_eq!(state.hash_one(&a), state.hash_one(&b));
+        assert_ne!(state.hash_one(&a), state.hash_one(parse_term("λy. (y, π₁ (f y)) @ (i ∧ j)").unwrap()));
+
+        assert_eq!(state.hash_one(Term::RealLit(0.0)), state.hash_one(Term::RealLit(-0.0)));
+        assert_eq!(state.hash_one(Type::Universe(Level::Const(1))), state.hash_one(Type::Universe(Level::Const(1))));
+    }
+
+    #[test]
+    fn test_node_count_and_depth() {
+        let term = parse_term("λf. λx. f (f x)").unwrap();
+        assert_eq!(term.node_count(), 7);
+        assert_eq!(term.depth(), 5);
+        assert_eq!(Term::Var("x".to_string()).depth(), 1);
+
+        let big = tree(14);
+        assert_eq!(big.node_count(), 16_383);
+        assert_eq!(big.depth(), 14);
+    }
+
+    #[test]
+    fn test_interning_shares_repeated_terms() {
+        let mut interner = TermInterner::new();
+        let first = interner.intern(tree(14));
+        let copies: Vec<_> = (0..9).map(|_| interner.intern(tree(14))).collect();
+        let other = interner.intern(tree(13));
+
+        assert!(copies.iter().all(|copy| Rc::ptr_eq(copy, &first)));
+        assert!(!Rc::ptr_eq(&first, &other));
+        assert!(Rc::ptr_eq(&interner.get(&tree(13)).unwrap(), &other));
+        assert_eq!(interner.get(&tree(3)), None);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(
+            interner.stats(),
+            InternStats { interned: 11, distinct: 2, nodes: 10 * 16_383 + 8_191, shared_nodes: 9 * 16_383 }
+        );
+    }
+}
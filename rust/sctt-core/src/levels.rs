@@ -0,0 +1,199 @@
+//! Universe level constraints
+//!
+//! A type may mention level variables, as in `Type_l → Type_l`. Checking a
+//! term gives the level variables of each global name it uses fresh ones,
+//! written `?l1`, so every use can sit at its own level, and records what
+//! comparing universes asks of them: `l ≤ l'` where a type in `Type_l` is
+//! used as one in `Type_l'`, and both ways where universes must be equal.
+//! [`solve`] finds the least levels for the fresh variables, in terms of
+//! the ones that are not fresh, or names the constraints that go round a
+//! cycle such as `?l1 + 1 ≤ ?l1`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{Level, Result, TypeError};
+
+/// `lower ≤ upper`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub lower: Level,
+    pub upper: Level,
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ≤ {}", self.lower, self.upper)
+    }
+}
+
+/// Whether `var` was made by [`Levels::fresh`] to be solved for, rather
+/// than being one a definition is polymorphic in
+pub fn is_fresh(var: &str) -> bool {
+    var.starts_with('?')
+}
+
+/// The constraints met while checking a term, and the fresh variables
+/// made for it
+#[derive(Debug, Default)]
+pub struct Levels {
+    constraints: Vec<Constraint>,
+    made: usize,
+}
+
+impl Levels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh variable standing in for `var` at one use
+    pub fn fresh(&mut self, var: &str) -> Level {
+        self.made += 1;
+        Level::Var(format!("?{}{}", var, self.made))
+    }
+
+    /// Require `lower ≤ upper`; without fresh variables to solve for, it
+    /// has to hold whatever the others are
+    pub fn require(&mut self, lower: &Level, upper: &Level) -> Result<()> {
+        if lower.vars().iter().chain(&upper.vars()).any(|var| is_fresh(var)) {
+            self.constraints.push(Constraint { lower: lower.clone(), upper: upper.clone() });
+            Ok(())
+        } else if lower.leq(upper) {
+            Ok(())
+        } else {
+            Err(TypeError::UniverseMismatch { expected: upper.clone(), got: lower.clone() })
+        }
+    }
+
+    pub fn equate(&mut self, a: &Level, b: &Level) -> Result<()> {
+        self.require(a, b)?;
+        self.require(b, a)
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn solve(&self) -> Result<BTreeMap<String, Level>> {
+        solve(&self.constraints)
+    }
+}
+
+/// The least level for each fresh variable in `constraints` such that all
+/// of them hold
+pub fn solve(constraints: &[Constraint]) -> Result<BTreeMap<String, Level>> {
+    let mut values: BTreeMap<String, Level> = constraints
+        .iter()
+        .flat_map(|c| c.lower.vars().into_iter().chain(c.upper.vars()))
+        .filter(|var| is_fresh(var))
+        .map(|var| (var, Level::ZERO))
+        .collect();
+    // Variables only rise as far as a constraint needs them to, so without
+    // a cycle every round settles one more of them; whatever is still
+    // rising after that goes round a cycle that never settles
+    let mut raised = Vec::new();
+    for _ in 0..=values.len() {
+        raised.clear();
+        for c in constraints {
+            if !holds(c, &values) {
+                raise(c, &mut values)?;
+                raised.push(c.to_string());
+            }
+        }
+        if raised.is_empty() {
+            return Ok(values);
+        }
+    }
+    Err(TypeError::UnsolvableLevels(raised.join(", ")))
+}
+
+fn holds(c: &Constraint, values: &BTreeMap<String, Level>) -> bool {
+    c.lower.subst(values).leq(&c.upper.subst(values))
+}
+
+/// Raise the first fresh variable on the right of `c` until `c` holds
+fn raise(c: &Constraint, values: &mut BTreeMap<String, Level>) -> Result<()> {
+    let Some((Some(var), offset)) = c.upper.terms().into_iter().find(|(var, _)| var.is_some_and(is_fresh)) else {
+        return Err(TypeError::UnsolvableLevels(c.to_string()));
+    };
+    // `var + offset` has to reach `lower`, so `var` reaches `lower - offset`
+    let lower = c.lower.subst(values);
+    let needed = from_terms(lower.terms().into_iter().map(|(x, k)| (x, k.saturating_sub(offset))));
+    let value = values.get(var).cloned().unwrap_or(Level::ZERO);
+    values.insert(var.to_string(), from_terms(value.terms().into_iter().chain(needed.terms())));
+    Ok(())
+}
+
+/// The `max` of `x + k` and numbers `k`, keeping only the highest of each
+fn from_terms<'a>(terms: impl IntoIterator<Item = (Option<&'a str>, u32)>) -> Level {
+    let mut highest: BTreeMap<Option<&str>, u32> = BTreeMap::new();
+    for (var, k) in terms {
+        let entry = highest.entry(var).or_insert(k);
+        *entry = (*entry).max(k);
+    }
+    // A number no higher than some `x + k` adds nothing
+    if let Some(&n) = highest.get(&None) {
+        if highest.iter().any(|(var, &k)| var.is_some() && k >= n) {
+            highest.remove(&None);
+        }
+    }
+    highest.into_iter().fold(Level::ZERO, |level, (var, k)| {
+        let term = match var {
+            Some(x) => (0..k).fold(Level::Var(x.to_string()), |l, _| l.succ()),
+            None => Level::Const(k),
+        };
+        level.max(term)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(x: &str) -> Level {
+        Level::Var(x.to_string())
+    }
+
+    #[test]
+    fn test_level_expressions() {
+        let ty = crate::skeleton::parse_type("Type_(max(l, m) + 1) → Type_l").unwrap();
+        assert_eq!(ty.to_string(), "Type_(max(l, m) + 1) → Type_l");
+        assert_eq!(ty.level_vars().into_iter().collect::<Vec<_>>(), ["l", "m"]);
+        // Whatever `l` and `m` are, `l ≤ max(l, m) ≤ max(l, m) + 1`
+        let (l, m) = (var("l"), var("m"));
+        let top = l.clone().max(m.clone());
+        assert!(l.leq(&top) && top.leq(&top.clone().succ()) && Level::Const(1).leq(&top.clone().succ()));
+        assert!(!top.leq(&l) && !m.succ().leq(&top));
+    }
+
+    #[test]
+    fn test_least_solution() {
+        let mut levels = Levels::new();
+        let (a, b) = (levels.fresh("l"), levels.fresh("l"));
+        levels.require(&Level::Const(1), &a).unwrap();
+        levels.require(&a.clone().succ(), &b).unwrap();
+        levels.require(&var("m"), &b).unwrap();
+        let values = levels.solve().unwrap();
+        assert_eq!(values["?l1"], Level::Const(1));
+        assert_eq!(values["?l2"].to_string(), "max(2, m)");
+        // Constraints on variables that are not fresh are decided at once
+        assert!(levels.require(&var("m"), &var("m").succ()).is_ok());
+        assert!(matches!(levels.require(&var("m"), &Level::Const(3)), Err(TypeError::UniverseMismatch { .. })));
+    }
+
+    #[test]
+    fn test_cycles_have_no_solution() {
+        let mut levels = Levels::new();
+        let (a, b) = (levels.fresh("l"), levels.fresh("m"));
+        levels.require(&a.clone().succ(), &b).unwrap();
+        levels.require(&b, &a).unwrap();
+        let err = levels.solve().unwrap_err();
+        assert_eq!(err.to_string(), "Universe levels have no solution: ?l1 + 1 ≤ ?m2, ?m2 ≤ ?l1");
+        // A level cannot be raised past a number
+        let mut bounded = Levels::new();
+        let a = bounded.fresh("l");
+        bounded.require(&Level::Const(2), &a).unwrap();
+        bounded.require(&a, &Level::Const(1)).unwrap();
+        assert!(matches!(bounded.solve(), Err(TypeError::UnsolvableLevels(c)) if c == "?l1 ≤ 1"));
+    }
+}
@@ -1,11 +1,45 @@
 //! Core type system for Smooth Cubical Type Theory
 //! This module defines the fundamental types and terms
+//!
+//! # Feature flags
+//!
+//! With `default-features = false` the crate depends only on `thiserror` and
+//! `num-traits`, which is what embedders (C ABI, WASI, check-only CI) want.
+//! `input` (digraphs and key bindings for the terminal tools), `compat`
+//! (engine versions on stored artifacts), `codec` (the binary encoding
+//! of terms and types), `intern` (hashing and hash-consing of terms),
+//! `definitions` (global definitions with δ-unfolding) and `levels`
+//! (universe level constraints) need no feature;
+//! the input config file needs `serde`.
+//!
+//! | Feature         | Enables                                                   |
+//! |-----------------|-----------------------------------------------------------|
+//! | `serde`         | `Serialize`/`Deserialize` on all public types, `serde_json`, `record` |
+//! | `wasm-bindings` | `WasmTypeChecker` and the `#[wasm_bindgen]` exports (default) |
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use thiserror::Error;
-use wasm_bindgen::prelude::*;
-use serde_wasm_bindgen::{to_value, from_value};
+
+pub mod check;
+pub mod codec;
+pub mod compat;
+pub mod definitions;
+pub mod input;
+pub mod intern;
+pub mod levels;
+mod pretty;
+pub mod simp;
+pub mod skeleton;
+pub mod termination;
+#[cfg(feature = "serde")]
+pub mod record;
+#[cfg(feature = "wasm-bindings")]
+mod wasm;
+#[cfg(feature = "wasm-bindings")]
+pub use wasm::*;
 
 /// Errors that can occur in the type system
 #[derive(Error, Debug)]
@@ -24,28 +58,203 @@ pub enum TypeError {
     
     #[error("Smoothness verification failed")]
     NotSmooth,
+
+    #[error("{message} at position {position}")]
+    Parse { position: usize, message: String },
+
+    #[error("Universe mismatch: a type in Type_{got} does not fit in Type_{expected}")]
+    UniverseMismatch { expected: Level, got: Level },
+
+    #[error("Universe levels have no solution: {0}")]
+    UnsolvableLevels(String),
+
+    #[error("Normalization did not finish within {fuel} steps")]
+    OutOfFuel { fuel: usize },
+
+    #[error("Record is missing field {0}")]
+    MissingField(String),
+
+    #[error("Record type has no field {0}")]
+    NoSuchField(String),
+
+    #[error("{0} is already defined")]
+    AlreadyDefined(String),
+
+    #[error("{name} is used by {by}")]
+    InUse { name: String, by: String },
+
+    #[error("{0}")]
+    Termination(termination::TerminationError),
+
+    /// `error`, found at `span` of the source being checked
+    #[error("{error}")]
+    Located { span: Span, error: Box<TypeError> },
+}
+
+/// A range of characters in a source, `start` inclusive and `end` exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl TypeError {
+    /// Attach `span` unless a narrower one is already attached
+    pub fn at(self, span: Span) -> TypeError {
+        match self {
+            TypeError::Located { .. } | TypeError::Parse { .. } => self,
+            error => TypeError::Located { span, error: Box::new(error) },
+        }
+    }
+
+    /// Where in the source the error is, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TypeError::Located { span, .. } => Some(*span),
+            TypeError::Parse { position, .. } => Some(Span { start: *position, end: position + 1 }),
+            _ => None,
+        }
+    }
+
+    /// The error without its location
+    pub fn unlocated(&self) -> &TypeError {
+        match self {
+            TypeError::Located { error, .. } => error.unlocated(),
+            error => error,
+        }
+    }
+
+    /// The message followed, when the error has a span, by the line it
+    /// starts on with the span underlined, as rustc does:
+    ///
+    /// ```text
+    /// error: Variable y not found in context
+    ///  --> 2:9
+    ///   |
+    /// 2 |   λx. f y
+    ///   |         ^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("error: {}", self);
+        let Some(span) = self.span() else { return out };
+
+        let mut line_start = 0;
+        let mut line_number = 1;
+        for (i, c) in source.chars().enumerate().take(span.start) {
+            if c == '\n' {
+                line_start = i + 1;
+                line_number += 1;
+            }
+        }
+        let line: String = source.chars().skip(line_start).take_while(|c| *c != '\n').collect();
+        let column = span.start - line_start;
+        let width = span.end.saturating_sub(span.start).clamp(1, line.chars().count().saturating_sub(column).max(1));
+
+        let gutter = " ".repeat(line_number.to_string().len());
+        out.push_str(&format!("\n{}--> {}:{}", gutter, line_number, column + 1));
+        out.push_str(&format!("\n{} |", gutter));
+        out.push_str(&format!("\n{} | {}", line_number, line));
+        out.push_str(&format!("\n{} | {}{}", gutter, " ".repeat(column), "^".repeat(width)));
+        out
+    }
+}
+
+impl From<simp::ParseError> for TypeError {
+    fn from(e: simp::ParseError) -> Self {
+        TypeError::Parse { position: e.position, message: e.message }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, TypeError>;
 
-/// Universe levels for type hierarchy
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Level(pub u32);
+/// Universe levels for type hierarchy: a number, a level variable a
+/// definition is polymorphic in, or `l + 1` and `max(l, l')` of those
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Level {
+    Const(u32),
+    Var(String),
+    Succ(Box<Level>),
+    Max(Box<Level>, Box<Level>),
+}
 
 impl Level {
-    pub const ZERO: Level = Level(0);
-    
+    pub const ZERO: Level = Level::Const(0);
+
     pub fn succ(self) -> Level {
-        Level(self.0 + 1)
+        match self {
+            Level::Const(n) => Level::Const(n + 1),
+            level => Level::Succ(Box::new(level)),
+        }
     }
-    
+
     pub fn max(self, other: Level) -> Level {
-        Level(self.0.max(other.0))
+        match (self, other) {
+            (Level::Const(a), Level::Const(b)) => Level::Const(a.max(b)),
+            (Level::Const(0), level) | (level, Level::Const(0)) => level,
+            (a, b) if a == b => a,
+            (a, b) => Level::Max(Box::new(a), Box::new(b)),
+        }
+    }
+
+    /// Cumulativity: everything in `Type_self` is also in `Type_other`,
+    /// whatever numbers the level variables stand for
+    pub fn leq(&self, other: &Level) -> bool {
+        let upper = other.terms();
+        self.terms()
+            .iter()
+            .all(|(var, k)| upper.iter().any(|(bound, j)| k <= j && (var.is_none() || var == bound)))
+    }
+
+    /// The level as the `max` of `x + k` for variables `x`, and of
+    /// numbers `k` where the variable is `None`
+    pub(crate) fn terms(&self) -> Vec<(Option<&str>, u32)> {
+        match self {
+            Level::Const(n) => vec![(None, *n)],
+            Level::Var(x) => vec![(Some(x.as_str()), 0)],
+            Level::Succ(level) => level.terms().into_iter().map(|(var, k)| (var, k + 1)).collect(),
+            Level::Max(a, b) => [a.terms(), b.terms()].concat(),
+        }
+    }
+
+    /// The level variables mentioned
+    pub fn vars(&self) -> BTreeSet<String> {
+        self.terms().into_iter().filter_map(|(var, _)| var.map(str::to_string)).collect()
+    }
+
+    /// Replace level variables by the levels `values` gives them
+    pub fn subst(&self, values: &BTreeMap<String, Level>) -> Level {
+        match self {
+            Level::Const(_) => self.clone(),
+            Level::Var(x) => values.get(x).cloned().unwrap_or_else(|| self.clone()),
+            Level::Succ(level) => level.subst(values).succ(),
+            Level::Max(a, b) => a.subst(values).max(b.subst(values)),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Const(n) => write!(f, "{}", n),
+            Level::Var(x) => write!(f, "{}", x),
+            Level::Succ(_) => {
+                let (mut base, mut k) = (self, 0);
+                while let Level::Succ(inner) = base {
+                    base = inner;
+                    k += 1;
+                }
+                write!(f, "{} + {}", base, k)
+            }
+            Level::Max(a, b) => write!(f, "max({}, {})", a, b),
+        }
     }
 }
 
 /// Core types in SCTT
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Type {
     /// Type universe
     Universe(Level),
@@ -83,13 +292,41 @@ pub enum Type {
     
     /// Smooth type modifier
     Smooth(Box<Type>),
+
+    /// `PathP (⟨i⟩ A) a b`: paths over the line of types `A`, which may
+    /// mention the interval variable `param`, from `a : A[0/i]` to
+    /// `b : A[1/i]`; with `A` not mentioning `i` it is `Path A a b`
+    PathP { param: String, family: Box<Type>, start: Box<Term>, end: Box<Term> },
     
     /// Real numbers (as a primitive smooth type)
     Real,
+
+    /// Natural numbers ℕ
+    Nat,
+
+    /// The one-element type
+    Unit,
+
+    /// The type with no elements
+    Empty,
+
+    /// Booleans
+    Bool,
+
+    /// Record `{ x : A, y : B }`; a field's type may mention the fields
+    /// before it by name
+    Record(Vec<(String, Type)>),
+
+    /// The circle S¹: a point and a loop at it
+    Circle,
+
+    /// Disjoint union `A + B`
+    Sum(Box<Type>, Box<Type>),
 }
 
 /// Terms (expressions) in SCTT
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Term {
     /// Variable
     Var(String),
@@ -148,6 +385,98 @@ pub enum Term {
         expr: String,
         var: String,
     },
+    
+    /// A part still to be written, `?`
+    Hole,
+
+    /// Zero of ℕ
+    NatZero,
+
+    /// Successor of a natural number
+    NatSucc(Box<Term>),
+
+    /// Recursion on ℕ into `motive`: `base` at zero, and `step n r` at
+    /// `succ n` where `r` is the result at `n`
+    NatRec {
+        motive: Box<Type>,
+        base: Box<Term>,
+        step: Box<Term>,
+        scrutinee: Box<Term>,
+    },
+
+    /// The element of `Unit`
+    Star,
+
+    /// Booleans
+    True,
+    False,
+
+    /// Case analysis on a boolean into `motive`
+    BoolElim {
+        motive: Box<Type>,
+        on_true: Box<Term>,
+        on_false: Box<Term>,
+        scrutinee: Box<Term>,
+    },
+
+    /// Anything at all, given an element of `Empty`
+    EmptyElim {
+        motive: Box<Type>,
+        scrutinee: Box<Term>,
+    },
+
+    /// Local definition `let x : T = v in e`
+    Let {
+        name: String,
+        ty: Box<Type>,
+        value: Box<Term>,
+        body: Box<Term>,
+    },
+
+    /// Type annotation `(e : T)`
+    Ann {
+        term: Box<Term>,
+        ty: Box<Type>,
+    },
+
+    /// Record literal `{ x := a, y := b }`
+    RecordLit(Vec<(String, Term)>),
+
+    /// Field projection `r.x`
+    Proj(Box<Term>, String),
+
+    /// The point of the circle
+    Base,
+
+    /// The path around the circle, `Path S¹ base base`
+    Loop,
+
+    /// A map out of the circle into `motive`, sending `base` to
+    /// `base_case` and `loop` to the path `loop_case`
+    CircleElim {
+        motive: Box<Type>,
+        base_case: Box<Term>,
+        loop_case: Box<Term>,
+        scrutinee: Box<Term>,
+    },
+
+    /// Injections into a sum `A + B`
+    Inl(Box<Term>),
+    Inr(Box<Term>),
+
+    /// Into and out of the smooth modality: `smooth-in t : Smooth(A)` for
+    /// `t : A` with `A` smooth, and `smooth-out s : A` for `s : Smooth(A)`
+    SmoothIn(Box<Term>),
+    SmoothOut(Box<Term>),
+
+    /// Case analysis on a sum into `motive`, applying `left_branch` to
+    /// the contents of an `inl` and `right_branch` to those of an `inr`
+    Case {
+        motive: Box<Type>,
+        scrutinee: Box<Term>,
+        left_branch: Box<Term>,
+        right_branch: Box<Term>,
+    },
 }
 
 impl Type {
@@ -160,11 +489,221 @@ impl Type {
             _ => false,
         }
     }
+
+    /// `B[x := t]`: substitute a term for a term variable in path endpoints,
+    /// through nested `Π`/`Σ` unless a binder shadows it, renaming binders
+    /// that would capture the replacement's free variables
+    pub fn subst_term(&self, var: &str, replacement: &Term) -> Type {
+        let binder = |param: &String, domain: &Type, codomain: &Type| {
+            let domain = Box::new(domain.subst_term(var, replacement));
+            if param == var {
+                return (param.clone(), domain, Box::new(codomain.clone()));
+            }
+            let (param, codomain) = match rename_binder(param, var, replacement, &codomain.free_vars()) {
+                Some(fresh) => (fresh.clone(), codomain.subst_term(param, &Term::Var(fresh))),
+                None => (param.clone(), codomain.clone()),
+            };
+            (param, domain, Box::new(codomain.subst_term(var, replacement)))
+        };
+        match self {
+            Type::Function { domain, codomain, is_smooth } => Type::Function {
+                domain: Box::new(domain.subst_term(var, replacement)),
+                codomain: Box::new(codomain.subst_term(var, replacement)),
+                is_smooth: *is_smooth,
+            },
+            Type::Pi { param, domain, codomain } => {
+                let (param, domain, codomain) = binder(param, domain, codomain);
+                Type::Pi { param, domain, codomain }
+            }
+            Type::Sigma { param, domain, codomain } => {
+                let (param, domain, codomain) = binder(param, domain, codomain);
+                Type::Sigma { param, domain, codomain }
+            }
+            Type::Path { space, start, end } => Type::Path {
+                space: Box::new(space.subst_term(var, replacement)),
+                start: Box::new(start.subst(var, replacement)),
+                end: Box::new(end.subst(var, replacement)),
+            },
+            Type::PathP { param, family, start, end } => {
+                let (param, _, family) = binder(param, &Type::Interval, family);
+                Type::PathP {
+                    param,
+                    family,
+                    start: Box::new(start.subst(var, replacement)),
+                    end: Box::new(end.subst(var, replacement)),
+                }
+            }
+            Type::Smooth(inner) => Type::Smooth(Box::new(inner.subst_term(var, replacement))),
+            Type::Record(fields) => Type::Record(subst_fields(fields, var, replacement)),
+            Type::Sum(left, right) => {
+                Type::Sum(Box::new(left.subst_term(var, replacement)), Box::new(right.subst_term(var, replacement)))
+            }
+            Type::Universe(_)
+            | Type::Interval
+            | Type::Real
+            | Type::Nat
+            | Type::Unit
+            | Type::Empty
+            | Type::Bool
+            | Type::Circle => self.clone(),
+        }
+    }
+
+    /// The lowest universe containing the type: `Type_n` lives in
+    /// `Type_(n+1)`, `ℝ` and `I` in `Type_0`, and a compound type as high
+    /// as its highest part. Path endpoints may only mention `ctx` and the
+    /// binders around them.
+    pub fn universe_level(&self, ctx: &[(String, Type)]) -> Result<Level> {
+        if let Some(unbound) = self.free_vars().into_iter().find(|var| !ctx.iter().any(|(name, _)| name == var)) {
+            return Err(TypeError::VariableNotFound(unbound));
+        }
+        Ok(self.level())
+    }
+
+    /// Check that the type belongs to `Type_universe`, or any universe
+    /// above its own by cumulativity
+    pub fn check_in_universe(&self, ctx: &[(String, Type)], universe: Level) -> Result<()> {
+        let level = self.universe_level(ctx)?;
+        if level.leq(&universe) {
+            Ok(())
+        } else {
+            Err(TypeError::UniverseMismatch { expected: universe, got: level })
+        }
+    }
+
+    fn level(&self) -> Level {
+        match self {
+            Type::Universe(level) => level.clone().succ(),
+            Type::Function { domain, codomain, .. }
+            | Type::Pi { domain, codomain, .. }
+            | Type::Sigma { domain, codomain, .. }
+            | Type::Sum(domain, codomain) => domain.level().max(codomain.level()),
+            Type::Path { space, .. } | Type::Smooth(space) | Type::PathP { family: space, .. } => space.level(),
+            Type::Record(fields) => fields.iter().map(|(_, ty)| ty.level()).fold(Level::ZERO, Level::max),
+            Type::Interval
+            | Type::Real
+            | Type::Nat
+            | Type::Unit
+            | Type::Empty
+            | Type::Bool
+            | Type::Circle => Level::ZERO,
+        }
+    }
+
+    /// Term variables the type mentions without binding
+    pub fn free_vars(&self) -> BTreeSet<String> {
+        let mut free = BTreeSet::new();
+        self.collect_free(&mut Vec::new(), &mut free);
+        free
+    }
+
+    /// The type with each universe level `l` replaced by `f(l)`, in order
+    /// from left to right; universes inside path endpoints are left alone
+    pub fn map_levels(&self, f: &mut impl FnMut(&Level) -> Level) -> Type {
+        let mut go = |ty: &Type| Box::new(ty.map_levels(f));
+        match self {
+            Type::Universe(level) => Type::Universe(f(level)),
+            Type::Function { domain, codomain, is_smooth } => {
+                let domain = go(domain);
+                Type::Function { domain, codomain: go(codomain), is_smooth: *is_smooth }
+            }
+            Type::Pi { param, domain, codomain } => {
+                let domain = go(domain);
+                Type::Pi { param: param.clone(), domain, codomain: go(codomain) }
+            }
+            Type::Sigma { param, domain, codomain } => {
+                let domain = go(domain);
+                Type::Sigma { param: param.clone(), domain, codomain: go(codomain) }
+            }
+            Type::Sum(left, right) => {
+                let left = go(left);
+                Type::Sum(left, go(right))
+            }
+            Type::Path { space, start, end } => Type::Path { space: go(space), start: start.clone(), end: end.clone() },
+            Type::PathP { param, family, start, end } => {
+                Type::PathP { param: param.clone(), family: go(family), start: start.clone(), end: end.clone() }
+            }
+            Type::Smooth(inner) => Type::Smooth(go(inner)),
+            Type::Record(fields) => Type::Record(fields.iter().map(|(label, ty)| (label.clone(), *go(ty))).collect()),
+            Type::Interval
+            | Type::Real
+            | Type::Nat
+            | Type::Unit
+            | Type::Empty
+            | Type::Bool
+            | Type::Circle => self.clone(),
+        }
+    }
+
+    /// Level variables in the type's universes
+    pub fn level_vars(&self) -> BTreeSet<String> {
+        let mut vars = BTreeSet::new();
+        self.map_levels(&mut |level| {
+            vars.extend(level.vars());
+            level.clone()
+        });
+        vars
+    }
+
+    /// Equality up to the names of `Π`/`Σ` binders, with path endpoints
+    /// compared by [`Term::alpha_eq`]
+    pub fn alpha_eq(&self, other: &Type) -> bool {
+        alpha_type(self, other, &mut Scopes::default())
+    }
+
+    fn collect_free(&self, bound: &mut Vec<String>, free: &mut BTreeSet<String>) {
+        match self {
+            Type::Function { domain, codomain, .. } | Type::Sum(domain, codomain) => {
+                domain.collect_free(bound, free);
+                codomain.collect_free(bound, free);
+            }
+            Type::Pi { param, domain, codomain } | Type::Sigma { param, domain, codomain } => {
+                domain.collect_free(bound, free);
+                bound.push(param.clone());
+                codomain.collect_free(bound, free);
+                bound.pop();
+            }
+            Type::Path { space, start, end } => {
+                space.collect_free(bound, free);
+                start.collect_free(bound, free);
+                end.collect_free(bound, free);
+            }
+            Type::PathP { param, family, start, end } => {
+                bound.push(param.clone());
+                family.collect_free(bound, free);
+                bound.pop();
+                start.collect_free(bound, free);
+                end.collect_free(bound, free);
+            }
+            Type::Smooth(inner) => inner.collect_free(bound, free),
+            Type::Record(fields) => {
+                for (name, ty) in fields {
+                    ty.collect_free(bound, free);
+                    bound.push(name.clone());
+                }
+                bound.truncate(bound.len() - fields.len());
+            }
+            Type::Universe(_)
+            | Type::Interval
+            | Type::Real
+            | Type::Nat
+            | Type::Unit
+            | Type::Empty
+            | Type::Bool
+            | Type::Circle => {}
+        }
+    }
 }
 
 impl Term {
     /// Substitute a term for a variable
+    ///
+    /// Binders that would capture a free variable of `replacement` are
+    /// renamed first, priming the name until it is fresh (`y'`, `y''`). A [`Term::SmoothFunc`] is substituted textually: every
+    /// identifier in `expr` equal to `var` becomes the printed replacement,
+    /// unless the function's own variable shadows it.
     pub fn subst(&self, var: &str, replacement: &Term) -> Term {
+        let sub = |t: &Term| Box::new(t.subst(var, replacement));
         match self {
             Term::Var(name) => {
                 if name == var {
@@ -174,41 +713,486 @@ impl Term {
                 }
             }
             Term::Lambda { param, param_type, body } => {
+                let param_type = Box::new(param_type.subst_term(var, replacement));
                 if param == var {
                     // Variable is bound, no substitution in body
-                    self.clone()
-                } else {
-                    Term::Lambda {
-                        param: param.clone(),
-                        param_type: param_type.clone(),
-                        body: Box::new(body.subst(var, replacement)),
-                    }
+                    return Term::Lambda { param: param.clone(), param_type, body: body.clone() };
                 }
+                let (param, body) = avoid_capture(param, body, var, replacement);
+                Term::Lambda { param, param_type, body: sub(&body) }
             }
-            Term::App { func, arg } => Term::App {
-                func: Box::new(func.subst(var, replacement)),
-                arg: Box::new(arg.subst(var, replacement)),
-            },
+            Term::App { func, arg } => Term::App { func: sub(func), arg: sub(arg) },
             Term::PathLambda { param, body } => {
                 if param == var {
-                    self.clone()
+                    return self.clone();
+                }
+                let (param, body) = avoid_capture(param, body, var, replacement);
+                Term::PathLambda { param, body: sub(&body) }
+            }
+            Term::Pair { first, second } => Term::Pair { first: sub(first), second: sub(second) },
+            Term::Fst(t) => Term::Fst(sub(t)),
+            Term::Snd(t) => Term::Snd(sub(t)),
+            Term::Inl(t) => Term::Inl(sub(t)),
+            Term::Inr(t) => Term::Inr(sub(t)),
+            Term::SmoothIn(t) => Term::SmoothIn(sub(t)),
+            Term::SmoothOut(t) => Term::SmoothOut(sub(t)),
+            Term::Case { motive, scrutinee, left_branch, right_branch } => Term::Case {
+                motive: Box::new(motive.subst_term(var, replacement)),
+                scrutinee: sub(scrutinee),
+                left_branch: sub(left_branch),
+                right_branch: sub(right_branch),
+            },
+            Term::PathApp { path, point } => Term::PathApp { path: sub(path), point: sub(point) },
+            Term::IMeet(a, b) => Term::IMeet(sub(a), sub(b)),
+            Term::IJoin(a, b) => Term::IJoin(sub(a), sub(b)),
+            Term::INeg(t) => Term::INeg(sub(t)),
+            Term::SmoothFunc { expr, var: bound } => {
+                if bound == var || !identifiers(expr).any(|(_, id)| id == var) {
+                    return self.clone();
+                }
+                let (bound, expr) = match rename_binder(bound, var, replacement, &smooth_free_vars(expr, bound)) {
+                    Some(fresh) => (fresh.clone(), replace_identifier(expr, bound, &fresh)),
+                    None => (bound.clone(), expr.clone()),
+                };
+                let printed = if replacement.precedence() >= 6 {
+                    replacement.to_string()
                 } else {
-                    Term::PathLambda {
-                        param: param.clone(),
-                        body: Box::new(body.subst(var, replacement)),
+                    format!("({})", replacement)
+                };
+                Term::SmoothFunc { expr: replace_identifier(&expr, var, &printed), var: bound }
+            }
+            Term::NatSucc(t) => Term::NatSucc(sub(t)),
+            Term::NatRec { motive, base, step, scrutinee } => Term::NatRec {
+                motive: Box::new(motive.subst_term(var, replacement)),
+                base: sub(base),
+                step: sub(step),
+                scrutinee: sub(scrutinee),
+            },
+            Term::BoolElim { motive, on_true, on_false, scrutinee } => Term::BoolElim {
+                motive: Box::new(motive.subst_term(var, replacement)),
+                on_true: sub(on_true),
+                on_false: sub(on_false),
+                scrutinee: sub(scrutinee),
+            },
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => Term::CircleElim {
+                motive: Box::new(motive.subst_term(var, replacement)),
+                base_case: sub(base_case),
+                loop_case: sub(loop_case),
+                scrutinee: sub(scrutinee),
+            },
+            Term::EmptyElim { motive, scrutinee } => Term::EmptyElim {
+                motive: Box::new(motive.subst_term(var, replacement)),
+                scrutinee: sub(scrutinee),
+            },
+            Term::Let { name, ty, value, body } => {
+                let (ty, value) = (Box::new(ty.subst_term(var, replacement)), sub(value));
+                if name == var {
+                    return Term::Let { name: name.clone(), ty, value, body: body.clone() };
+                }
+                let (name, body) = avoid_capture(name, body, var, replacement);
+                Term::Let { name, ty, value, body: sub(&body) }
+            }
+            Term::Ann { term, ty } => Term::Ann { term: sub(term), ty: Box::new(ty.subst_term(var, replacement)) },
+            Term::RecordLit(fields) => {
+                Term::RecordLit(fields.iter().map(|(name, value)| (name.clone(), value.subst(var, replacement))).collect())
+            }
+            Term::Proj(record, field) => Term::Proj(sub(record), field.clone()),
+            Term::IZero
+            | Term::IOne
+            | Term::RealLit(_)
+            | Term::Hole
+            | Term::NatZero
+            | Term::Star
+            | Term::True
+            | Term::False
+            | Term::Base
+            | Term::Loop => self.clone(),
+        }
+    }
+
+    /// Rename the free variable `old` to `new`; binders of `old` are left
+    /// alone and binders of `new` are renamed out of the way
+    pub fn rename(&self, old: &str, new: &str) -> Term {
+        self.subst(old, &Term::Var(new.to_string()))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.free_vars().is_empty()
+    }
+
+    /// The numeral `succ (… (succ 0))` for `n`
+    pub fn nat(n: u64) -> Term {
+        (0..n).fold(Term::NatZero, |t, _| Term::NatSucc(Box::new(t)))
+    }
+
+    /// The number a numeral stands for
+    pub fn as_nat(&self) -> Option<u64> {
+        let mut n = 0;
+        let mut t = self;
+        while let Term::NatSucc(pred) = t {
+            n += 1;
+            t = pred;
+        }
+        (*t == Term::NatZero).then_some(n)
+    }
+
+    /// Variables the term mentions without binding; a smooth function's
+    /// free variables are every other identifier in its expression,
+    /// function names like `sin` included
+    pub fn free_vars(&self) -> BTreeSet<String> {
+        let mut free = BTreeSet::new();
+        self.collect_free(&mut Vec::new(), &mut free);
+        free
+    }
+
+    /// Equality up to the names of bound variables: `λx. x` and `λy. y`
+    /// are alpha-equal, `λx. y` and `λx. z` are not
+    pub fn alpha_eq(&self, other: &Term) -> bool {
+        alpha_term(self, other, &mut Scopes::default())
+    }
+
+    fn collect_free(&self, bound: &mut Vec<String>, free: &mut BTreeSet<String>) {
+        match self {
+            Term::Var(name) => {
+                if !bound.contains(name) {
+                    free.insert(name.clone());
+                }
+            }
+            Term::Lambda { param, param_type, body } => {
+                param_type.collect_free(bound, free);
+                bound.push(param.clone());
+                body.collect_free(bound, free);
+                bound.pop();
+            }
+            Term::PathLambda { param, body } => {
+                bound.push(param.clone());
+                body.collect_free(bound, free);
+                bound.pop();
+            }
+            Term::App { func: a, arg: b }
+            | Term::Pair { first: a, second: b }
+            | Term::PathApp { path: a, point: b }
+            | Term::IMeet(a, b)
+            | Term::IJoin(a, b) => {
+                a.collect_free(bound, free);
+                b.collect_free(bound, free);
+            }
+            Term::Fst(t)
+            | Term::Snd(t)
+            | Term::INeg(t)
+            | Term::NatSucc(t)
+            | Term::Proj(t, _)
+            | Term::Inl(t)
+            | Term::Inr(t)
+            | Term::SmoothIn(t)
+            | Term::SmoothOut(t) => {
+                t.collect_free(bound, free)
+            }
+            Term::RecordLit(fields) => {
+                for (_, value) in fields {
+                    value.collect_free(bound, free);
+                }
+            }
+            Term::SmoothFunc { expr, var } => {
+                free.extend(smooth_free_vars(expr, var).into_iter().filter(|v| !bound.contains(v)));
+            }
+            Term::NatRec { motive, base: a, step: b, scrutinee: c }
+            | Term::BoolElim { motive, on_true: a, on_false: b, scrutinee: c }
+            | Term::CircleElim { motive, base_case: a, loop_case: b, scrutinee: c }
+            | Term::Case { motive, scrutinee: a, left_branch: b, right_branch: c } => {
+                motive.collect_free(bound, free);
+                for t in [a, b, c] {
+                    t.collect_free(bound, free);
+                }
+            }
+            Term::EmptyElim { motive: ty, scrutinee: term } | Term::Ann { term, ty } => {
+                ty.collect_free(bound, free);
+                term.collect_free(bound, free);
+            }
+            Term::Let { name, ty, value, body } => {
+                ty.collect_free(bound, free);
+                value.collect_free(bound, free);
+                bound.push(name.clone());
+                body.collect_free(bound, free);
+                bound.pop();
+            }
+            Term::IZero
+            | Term::IOne
+            | Term::RealLit(_)
+            | Term::Hole
+            | Term::NatZero
+            | Term::Star
+            | Term::True
+            | Term::False
+            | Term::Base
+            | Term::Loop => {}
+        }
+    }
+}
+
+/// `Path C b b`, the type of a loop at `b`: of `loop` itself, and of the
+/// image of `loop` under a map out of the circle
+pub(crate) fn circle_loop(motive: &Type, base: &Term) -> Type {
+    Type::Path { space: Box::new(motive.clone()), start: Box::new(base.clone()), end: Box::new(base.clone()) }
+}
+
+/// `ℕ → C → C`, the type of the step of a recursion into `C`
+pub(crate) fn recursion_step(motive: &Type) -> Type {
+    let arrow = |domain, codomain| Type::Function { domain: Box::new(domain), codomain: Box::new(codomain), is_smooth: false };
+    arrow(Type::Nat, arrow(motive.clone(), motive.clone()))
+}
+
+/// `A → C`, the type of the branch of a case analysis for the summand `A`
+pub(crate) fn case_branch(summand: &Type, motive: &Type) -> Type {
+    Type::Function { domain: Box::new(summand.clone()), codomain: Box::new(motive.clone()), is_smooth: false }
+}
+
+/// `param` and `body`, with `param` renamed if substituting `replacement`
+/// for `var` in `body` would capture it
+fn avoid_capture(param: &str, body: &Term, var: &str, replacement: &Term) -> (String, Term) {
+    match rename_binder(param, var, replacement, &body.free_vars()) {
+        Some(fresh) => (fresh.clone(), body.subst(param, &Term::Var(fresh))),
+        None => (param.to_string(), body.clone()),
+    }
+}
+
+/// `fields` with `replacement` for `var`
+///
+/// A label binds the fields after it, so a field named `var` shadows it
+/// there, and a label the replacement mentions would capture it: that label
+/// is primed until fresh, like a Π/Σ binder, and the record is then one
+/// with differently named fields.
+fn subst_fields(fields: &[(String, Type)], var: &str, replacement: &Term) -> Vec<(String, Type)> {
+    let Some(((label, ty), rest)) = fields.split_first() else { return Vec::new() };
+    let ty = ty.subst_term(var, replacement);
+    if label == var {
+        return std::iter::once((label.clone(), ty)).chain(rest.iter().cloned()).collect();
+    }
+    let outside = replacement.free_vars();
+    let later = Type::Record(rest.to_vec()).free_vars();
+    let (label, rest) = if outside.contains(label) && later.contains(var) {
+        let labelled = |name: &String| fields.iter().any(|(other, _)| other == name);
+        let taken = |name: &String| outside.contains(name) || later.contains(name) || name == var || labelled(name);
+        let mut fresh = format!("{}'", label);
+        while taken(&fresh) {
+            fresh.push('\'');
+        }
+        let rest = subst_fields(rest, label, &Term::Var(fresh.clone()));
+        (fresh, rest)
+    } else {
+        (label.clone(), rest.to_vec())
+    };
+    std::iter::once((label, ty)).chain(subst_fields(&rest, var, replacement)).collect()
+}
+
+/// A fresh name for the binder `param` when `replacement` mentions it and
+/// `var` actually occurs under it (`body_free`)
+fn rename_binder(param: &str, var: &str, replacement: &Term, body_free: &BTreeSet<String>) -> Option<String> {
+    let outside = replacement.free_vars();
+    if !outside.contains(param) || !body_free.contains(var) {
+        return None;
+    }
+    let mut fresh = format!("{}'", param);
+    while outside.contains(&fresh) || body_free.contains(&fresh) || fresh == var {
+        fresh.push('\'');
+    }
+    Some(fresh)
+}
+
+/// Identifiers in a smooth function's expression with their byte offsets
+fn identifiers(expr: &str) -> impl Iterator<Item = (usize, &str)> {
+    let is_start = |c: char| c.is_alphabetic() || c == '_';
+    let is_part = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let mut chars = expr.char_indices().peekable();
+    std::iter::from_fn(move || {
+        // Skip to an identifier start that does not continue a number
+        let mut prev_part = false;
+        loop {
+            let (i, c) = chars.next()?;
+            if is_start(c) && !prev_part {
+                let mut end = i + c.len_utf8();
+                while let Some(&(j, d)) = chars.peek() {
+                    if !is_part(d) {
+                        break;
                     }
+                    end = j + d.len_utf8();
+                    chars.next();
                 }
+                return Some((i, &expr[i..end]));
             }
-            // ... handle other cases
-            _ => self.clone(), // For now, clone for unhandled cases
+            prev_part = is_part(c);
+        }
+    })
+}
+
+fn smooth_free_vars(expr: &str, var: &str) -> BTreeSet<String> {
+    identifiers(expr).filter(|(_, id)| *id != var).map(|(_, id)| id.to_string()).collect()
+}
+
+/// `expr` with every identifier `from` replaced by `to`
+fn replace_identifier(expr: &str, from: &str, to: &str) -> String {
+    replace_identifiers(expr, |id| if id == from { to.to_string() } else { id.to_string() })
+}
+
+/// `expr` with each identifier replaced by what `with` makes of it
+fn replace_identifiers(expr: &str, mut with: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(expr.len());
+    let mut last = 0;
+    for (at, id) in identifiers(expr) {
+        out.push_str(&expr[last..at]);
+        out.push_str(&with(id));
+        last = at + id.len();
+    }
+    out.push_str(&expr[last..]);
+    out
+}
+
+/// The binders around both sides of an alpha comparison, innermost last
+#[derive(Default)]
+struct Scopes {
+    left: Vec<String>,
+    right: Vec<String>,
+}
+
+impl Scopes {
+    /// Whether `a` and `b` refer to the same thing: the same binder
+    /// counted from the inside, or the same free name
+    fn same(&self, a: &str, b: &str) -> bool {
+        match (index(&self.left, a), index(&self.right, b)) {
+            (None, None) => a == b,
+            (i, j) => i == j,
+        }
+    }
+
+    fn under<T>(&mut self, a: &str, b: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.left.push(a.to_string());
+        self.right.push(b.to_string());
+        let result = f(self);
+        self.left.pop();
+        self.right.pop();
+        result
+    }
+}
+
+/// De Bruijn index of `name` in `scope`
+fn index(scope: &[String], name: &str) -> Option<usize> {
+    scope.iter().rev().position(|bound| bound == name)
+}
+
+fn alpha_term(a: &Term, b: &Term, scopes: &mut Scopes) -> bool {
+    match (a, b) {
+        (Term::Var(x), Term::Var(y)) => scopes.same(x, y),
+        (
+            Term::Lambda { param: x, param_type: s, body: t },
+            Term::Lambda { param: y, param_type: u, body: v },
+        ) => alpha_type(s, u, scopes) && scopes.under(x, y, |scopes| alpha_term(t, v, scopes)),
+        (Term::PathLambda { param: x, body: t }, Term::PathLambda { param: y, body: v }) => {
+            scopes.under(x, y, |scopes| alpha_term(t, v, scopes))
+        }
+        (Term::App { func: f, arg: x }, Term::App { func: g, arg: y })
+        | (Term::Pair { first: f, second: x }, Term::Pair { first: g, second: y })
+        | (Term::PathApp { path: f, point: x }, Term::PathApp { path: g, point: y })
+        | (Term::IMeet(f, x), Term::IMeet(g, y))
+        | (Term::IJoin(f, x), Term::IJoin(g, y)) => alpha_term(f, g, scopes) && alpha_term(x, y, scopes),
+        (Term::Fst(t), Term::Fst(u))
+        | (Term::Snd(t), Term::Snd(u))
+        | (Term::INeg(t), Term::INeg(u))
+        | (Term::NatSucc(t), Term::NatSucc(u))
+        | (Term::Inl(t), Term::Inl(u))
+        | (Term::Inr(t), Term::Inr(u))
+        | (Term::SmoothIn(t), Term::SmoothIn(u))
+        | (Term::SmoothOut(t), Term::SmoothOut(u)) => alpha_term(t, u, scopes),
+        (
+            Term::NatRec { motive: c, base: z, step: s, scrutinee: n },
+            Term::NatRec { motive: d, base: w, step: t, scrutinee: m },
+        )
+        | (
+            Term::BoolElim { motive: c, on_true: z, on_false: s, scrutinee: n },
+            Term::BoolElim { motive: d, on_true: w, on_false: t, scrutinee: m },
+        )
+        | (
+            Term::CircleElim { motive: c, base_case: z, loop_case: s, scrutinee: n },
+            Term::CircleElim { motive: d, base_case: w, loop_case: t, scrutinee: m },
+        )
+        | (
+            Term::Case { motive: c, scrutinee: z, left_branch: s, right_branch: n },
+            Term::Case { motive: d, scrutinee: w, left_branch: t, right_branch: m },
+        ) => {
+            alpha_type(c, d, scopes) && alpha_term(z, w, scopes) && alpha_term(s, t, scopes) && alpha_term(n, m, scopes)
+        }
+        (Term::EmptyElim { motive: c, scrutinee: e }, Term::EmptyElim { motive: d, scrutinee: f })
+        | (Term::Ann { term: e, ty: c }, Term::Ann { term: f, ty: d }) => alpha_type(c, d, scopes) && alpha_term(e, f, scopes),
+        (Term::Let { name: x, ty: c, value: e, body: t }, Term::Let { name: y, ty: d, value: f, body: u }) => {
+            alpha_type(c, d, scopes) && alpha_term(e, f, scopes) && scopes.under(x, y, |scopes| alpha_term(t, u, scopes))
+        }
+        (Term::RecordLit(xs), Term::RecordLit(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|((x, t), (y, u))| x == y && alpha_term(t, u, scopes))
+        }
+        (Term::Proj(t, x), Term::Proj(u, y)) => x == y && alpha_term(t, u, scopes),
+        (Term::SmoothFunc { expr: e, var: x }, Term::SmoothFunc { expr: f, var: y }) => {
+            scopes.under(x, y, |scopes| {
+                let left: Vec<_> = identifiers(e).collect();
+                let right: Vec<_> = identifiers(f).collect();
+                // Same text between the identifiers, and identifiers that agree
+                left.len() == right.len()
+                    && left.iter().zip(&right).all(|((_, p), (_, q))| scopes.same(p, q))
+                    && skeleton_of(e) == skeleton_of(f)
+            })
+        }
+        _ => a == b,
+    }
+}
+
+/// A smooth expression with its identifiers blanked out
+fn skeleton_of(expr: &str) -> String {
+    replace_identifiers(expr, |_| "_".to_string())
+}
+
+fn alpha_type(a: &Type, b: &Type, scopes: &mut Scopes) -> bool {
+    match (a, b) {
+        (
+            Type::Function { domain: s, codomain: t, is_smooth: p },
+            Type::Function { domain: u, codomain: v, is_smooth: q },
+        ) => p == q && alpha_type(s, u, scopes) && alpha_type(t, v, scopes),
+        (Type::Pi { param: x, domain: s, codomain: t }, Type::Pi { param: y, domain: u, codomain: v })
+        | (Type::Sigma { param: x, domain: s, codomain: t }, Type::Sigma { param: y, domain: u, codomain: v }) => {
+            alpha_type(s, u, scopes) && scopes.under(x, y, |scopes| alpha_type(t, v, scopes))
+        }
+        (Type::Path { space: s, start: a0, end: a1 }, Type::Path { space: u, start: b0, end: b1 }) => {
+            alpha_type(s, u, scopes) && alpha_term(a0, b0, scopes) && alpha_term(a1, b1, scopes)
+        }
+        (
+            Type::PathP { param: x, family: s, start: a0, end: a1 },
+            Type::PathP { param: y, family: u, start: b0, end: b1 },
+        ) => {
+            scopes.under(x, y, |scopes| alpha_type(s, u, scopes))
+                && alpha_term(a0, b0, scopes)
+                && alpha_term(a1, b1, scopes)
         }
+        (Type::Smooth(s), Type::Smooth(u)) => alpha_type(s, u, scopes),
+        (Type::Sum(s, t), Type::Sum(u, v)) => alpha_type(s, u, scopes) && alpha_type(t, v, scopes),
+        (Type::Record(xs), Type::Record(ys)) => xs.len() == ys.len() && alpha_fields(xs, ys, scopes),
+        _ => a == b,
+    }
+}
+
+/// Same labels in the same order, each type compared with the fields
+/// before it in scope
+fn alpha_fields(xs: &[(String, Type)], ys: &[(String, Type)], scopes: &mut Scopes) -> bool {
+    match (xs.split_first(), ys.split_first()) {
+        (Some(((x, s), xs)), Some(((y, u), ys))) => {
+            x == y && alpha_type(s, u, scopes) && scopes.under(x, y, |scopes| alpha_fields(xs, ys, scopes))
+        }
+        (None, None) => true,
+        _ => false,
     }
 }
 
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Type::Universe(level) => write!(f, "Type_{}", level.0),
+            Type::Universe(level @ (Level::Const(_) | Level::Var(_))) => write!(f, "Type_{}", level),
+            Type::Universe(level) => write!(f, "Type_({})", level),
             Type::Function { domain, codomain, is_smooth } => {
                 if *is_smooth {
                     write!(f, "C^∞({} → {})", domain, codomain)
@@ -225,113 +1209,220 @@ impl fmt::Display for Type {
             Type::Path { space, start, end } => {
                 write!(f, "Path({}, {:?}, {:?})", space, start, end)
             }
+            Type::PathP { param, family, start, end } => {
+                let atom = |t: &Term| if t.precedence() < 6 { format!("({})", t) } else { t.to_string() };
+                write!(f, "PathP (⟨{}⟩ {}) {} {}", param, family, atom(start), atom(end))
+            }
             Type::Interval => write!(f, "I"),
             Type::Smooth(ty) => write!(f, "Smooth({})", ty),
             Type::Real => write!(f, "ℝ"),
+            Type::Nat => write!(f, "ℕ"),
+            Type::Unit => write!(f, "Unit"),
+            Type::Empty => write!(f, "Empty"),
+            Type::Bool => write!(f, "Bool"),
+            Type::Circle => write!(f, "S¹"),
+            Type::Sum(left, right) => {
+                // `+` groups to the right and binds tighter than arrows
+                let wrapped = |ty: &Type, left: bool| match ty {
+                    Type::Function { .. } | Type::Pi { .. } | Type::Sigma { .. } => true,
+                    Type::Sum(..) => left,
+                    _ => false,
+                };
+                for (ty, left) in [(left, true), (right, false)] {
+                    if !left {
+                        write!(f, " + ")?;
+                    }
+                    if wrapped(ty, left) {
+                        write!(f, "({})", ty)?;
+                    } else {
+                        write!(f, "{}", ty)?;
+                    }
+                }
+                Ok(())
+            }
+            Type::Record(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Type::Record(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, ty)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} : {}", name, ty)?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+impl Term {
+    /// Binding strength for printing: binders 0, `∨` 1, `∧` 2, `@` 3,
+    /// prefix operators 4, application 5, atoms 6
+    fn precedence(&self) -> u8 {
+        match self {
+            Term::Lambda { .. } | Term::PathLambda { .. } | Term::SmoothFunc { .. } | Term::Let { .. } => 0,
+            Term::IJoin(..) => 1,
+            Term::IMeet(..) => 2,
+            Term::PathApp { .. } => 3,
+            Term::NatSucc(_) if self.as_nat().is_some() => 6,
+            Term::INeg(_)
+            | Term::Fst(_)
+            | Term::Snd(_)
+            | Term::NatSucc(_)
+            | Term::Inl(_)
+            | Term::Inr(_)
+            | Term::SmoothIn(_)
+            | Term::SmoothOut(_) => 4,
+            Term::App { .. }
+            | Term::NatRec { .. }
+            | Term::BoolElim { .. }
+            | Term::EmptyElim { .. }
+            | Term::CircleElim { .. }
+            | Term::Case { .. } => 5,
+            Term::Var(_)
+            | Term::Pair { .. }
+            | Term::IZero
+            | Term::IOne
+            | Term::RealLit(_)
+            | Term::Hole
+            | Term::NatZero
+            | Term::Star
+            | Term::True
+            | Term::False
+            | Term::Base
+            | Term::Loop
+            | Term::Ann { .. }
+            | Term::RecordLit(_)
+            | Term::Proj(..) => 6,
         }
     }
 }
 
 impl fmt::Display for Term {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Parenthesise a subterm binding looser than `min`
+        let sub = |f: &mut fmt::Formatter<'_>, t: &Term, min: u8| {
+            if t.precedence() < min { write!(f, "({})", t) } else { write!(f, "{}", t) }
+        };
+        // Binary operators associate to the left
+        let infix = |f: &mut fmt::Formatter<'_>, a: &Term, op: &str, b: &Term, prec: u8| {
+            sub(f, a, prec)?;
+            write!(f, "{}", op)?;
+            sub(f, b, prec + 1)
+        };
         match self {
             Term::Var(name) => write!(f, "{}", name),
-            Term::Lambda { param, .. } => write!(f, "λ{}.(...)", param),
-            Term::App { func, arg } => write!(f, "({} {})", func, arg),
+            Term::Lambda { param, body, .. } => write!(f, "λ{}. {}", param, body),
+            Term::App { func, arg } => infix(f, func, " ", arg, 5),
+            Term::Pair { first, second } => write!(f, "({}, {})", first, second),
+            Term::Fst(t) => {
+                write!(f, "π₁ ")?;
+                sub(f, t, 4)
+            }
+            Term::Snd(t) => {
+                write!(f, "π₂ ")?;
+                sub(f, t, 4)
+            }
+            Term::Inl(t) => {
+                write!(f, "inl ")?;
+                sub(f, t, 4)
+            }
+            Term::Inr(t) => {
+                write!(f, "inr ")?;
+                sub(f, t, 4)
+            }
+            Term::SmoothIn(t) => {
+                write!(f, "smooth-in ")?;
+                sub(f, t, 4)
+            }
+            Term::SmoothOut(t) => {
+                write!(f, "smooth-out ")?;
+                sub(f, t, 4)
+            }
+            Term::PathLambda { param, body } => write!(f, "⟨{}⟩ {}", param, body),
+            Term::PathApp { path, point } => infix(f, path, " @ ", point, 3),
             Term::IZero => write!(f, "0"),
             Term::IOne => write!(f, "1"),
+            Term::IMeet(a, b) => infix(f, a, " ∧ ", b, 2),
+            Term::IJoin(a, b) => infix(f, a, " ∨ ", b, 1),
+            Term::INeg(t) => {
+                write!(f, "¬")?;
+                sub(f, t, 4)
+            }
             Term::RealLit(x) => write!(f, "{}", x),
             Term::SmoothFunc { expr, var } => write!(f, "λ{}.{}", var, expr),
-            _ => write!(f, "<term>"),
-        }
-    }
-}
-
-// WASM bindings
-#[wasm_bindgen]
-pub struct WasmTypeChecker {
-    context: Vec<(String, Type)>,
-}
-
-#[wasm_bindgen]
-impl WasmTypeChecker {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> WasmTypeChecker {
-        WasmTypeChecker {
-            context: Vec::new(),
-        }
-    }
-
-    pub fn add_binding(&mut self, name: String, type_str: String) -> Result<()> {
-        // Parse type string into Type
-        let ty = self.parse_type(&type_str)?;
-        self.context.push((name, ty));
-        Ok(())
-    }
-
-    pub fn check(&self, expr: &str) -> JsValue {
-        // Simple type checking for demonstration
-        let result = if expr.contains("sin") || expr.contains("cos") {
-            Ok("C∞(ℝ, ℝ)".to_string())
-        } else if expr.contains("Path") {
-            Ok("Type".to_string())
-        } else {
-            Err(TypeError::CannotInfer)
-        };
-        
-        match result {
-            Ok(ty) => to_value(&serde_json::json!({
-                "ok": true,
-                "type": ty
-            })).unwrap(),
-            Err(e) => to_value(&serde_json::json!({
-                "ok": false,
-                "error": e.to_string()
-            })).unwrap()
-        }
-    }
-
-    fn parse_type(&self, s: &str) -> Result<Type> {
-        // Simple type parser
-        match s {
-            "Real" | "ℝ" => Ok(Type::Real),
-            "I" | "Interval" => Ok(Type::Interval),
-            s if s.starts_with("C∞") => Ok(Type::Smooth(Box::new(Type::Real))),
-            _ => Ok(Type::Universe(Level::ZERO)),
+            Term::Hole => write!(f, "?"),
+            // Numerals print as numbers; `succ` only shows on open terms
+            Term::NatZero => write!(f, "0"),
+            Term::NatSucc(t) => match self.as_nat() {
+                Some(n) => write!(f, "{}", n),
+                None => {
+                    write!(f, "succ ")?;
+                    sub(f, t, 4)
+                }
+            },
+            Term::NatRec { motive, base, step, scrutinee } => {
+                write!(f, "natrec[{}]", motive)?;
+                for t in [base, step, scrutinee] {
+                    write!(f, " ")?;
+                    sub(f, t, 6)?;
+                }
+                Ok(())
+            }
+            Term::Star => write!(f, "★"),
+            Term::True => write!(f, "true"),
+            Term::False => write!(f, "false"),
+            Term::BoolElim { motive, on_true, on_false, scrutinee } => {
+                write!(f, "if[{}]", motive)?;
+                for t in [scrutinee, on_true, on_false] {
+                    write!(f, " ")?;
+                    sub(f, t, 6)?;
+                }
+                Ok(())
+            }
+            Term::Base => write!(f, "base"),
+            Term::Loop => write!(f, "loop"),
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+                write!(f, "S¹-elim[{}]", motive)?;
+                for t in [scrutinee, base_case, loop_case] {
+                    write!(f, " ")?;
+                    sub(f, t, 6)?;
+                }
+                Ok(())
+            }
+            Term::Case { motive, scrutinee, left_branch, right_branch } => {
+                write!(f, "case[{}]", motive)?;
+                for t in [scrutinee, left_branch, right_branch] {
+                    write!(f, " ")?;
+                    sub(f, t, 6)?;
+                }
+                Ok(())
+            }
+            Term::EmptyElim { motive, scrutinee } => {
+                write!(f, "absurd[{}] ", motive)?;
+                sub(f, scrutinee, 6)
+            }
+            Term::Let { name, ty, value, body } => write!(f, "let {} : {} = {} in {}", name, ty, value, body),
+            Term::Ann { term, ty } => write!(f, "({} : {})", term, ty),
+            Term::RecordLit(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Term::RecordLit(fields) => {
+                write!(f, "{{ ")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} := {}", name, value)?;
+                }
+                write!(f, " }}")
+            }
+            Term::Proj(record, field) => {
+                sub(f, record, 6)?;
+                write!(f, ".{}", field)
+            }
         }
     }
 }
 
-// WASM memory management
-#[wasm_bindgen]
-pub fn wasm_malloc(size: usize) -> *mut u8 {
-    let mut buf = Vec::with_capacity(size);
-    let ptr = buf.as_mut_ptr();
-    std::mem::forget(buf);
-    ptr
-}
-
-#[wasm_bindgen]
-pub fn wasm_free(ptr: *mut u8, size: usize) {
-    unsafe {
-        let _ = Vec::from_raw_parts(ptr, 0, size);
-    }
-}
-
-// Initialize WASM module
-#[wasm_bindgen(start)]
-pub fn init_wasm() {
-    // Set up panic hook for better error messages in browser
-    #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
-}
-
-// Version info
-#[wasm_bindgen]
-pub fn version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,4 +1447,240 @@ mod tests {
         let result = var.subst("x", &replacement);
         assert_eq!(result, Term::RealLit(3.14));
     }
+
+    fn v(name: &str) -> Term {
+        Term::Var(name.to_string())
+    }
+
+    fn b(t: Term) -> Box<Term> {
+        Box::new(t)
+    }
+
+    #[test]
+    fn test_substitution_reaches_every_constructor() {
+        let three = Term::RealLit(3.0);
+        let cases = [
+            (Term::Pair { first: b(v("x")), second: b(v("y")) }, Term::Pair { first: b(three.clone()), second: b(v("y")) }),
+            (Term::Fst(b(v("x"))), Term::Fst(b(three.clone()))),
+            (Term::Snd(b(v("x"))), Term::Snd(b(three.clone()))),
+            (Term::PathApp { path: b(v("p")), point: b(v("x")) }, Term::PathApp { path: b(v("p")), point: b(three.clone()) }),
+            (Term::IMeet(b(v("x")), b(Term::IOne)), Term::IMeet(b(three.clone()), b(Term::IOne))),
+            (Term::IJoin(b(Term::IZero), b(v("x"))), Term::IJoin(b(Term::IZero), b(three.clone()))),
+            (Term::INeg(b(v("x"))), Term::INeg(b(three.clone()))),
+            (Term::App { func: b(v("f")), arg: b(v("x")) }, Term::App { func: b(v("f")), arg: b(three.clone()) }),
+            (
+                Term::SmoothFunc { expr: "sin(x) * t + x2".to_string(), var: "t".to_string() },
+                Term::SmoothFunc { expr: "sin(3) * t + x2".to_string(), var: "t".to_string() },
+            ),
+            (Term::Hole, Term::Hole),
+        ];
+        for (term, expected) in cases {
+            assert_eq!(term.subst("x", &three), expected, "substituting into {}", term);
+        }
+
+        // (λx. fst (x, x)) 3 reduces to fst (3, 3)
+        let body = Term::Fst(b(Term::Pair { first: b(v("x")), second: b(v("x")) }));
+        assert_eq!(body.subst("x", &three).to_string(), "π₁ (3, 3)");
+
+        // Terms inside a binder's type are substituted too
+        let path = Type::Path { space: Box::new(Type::Real), start: b(v("x")), end: b(v("x")) };
+        let lam = Term::Lambda { param: "p".to_string(), param_type: Box::new(path), body: b(v("p")) };
+        let Term::Lambda { param_type, .. } = lam.subst("x", &three) else { unreachable!() };
+        assert_eq!(param_type.free_vars(), BTreeSet::new());
+    }
+
+    #[test]
+    fn test_substitution_respects_binders() {
+        let lam = |param: &str, body| Term::Lambda { param: param.to_string(), param_type: Box::new(Type::Real), body: b(body) };
+        let y = v("y");
+
+        // Shadowed
+        assert_eq!(lam("x", v("x")).subst("x", &y), lam("x", v("x")));
+        let smooth = Term::SmoothFunc { expr: "x * x".to_string(), var: "x".to_string() };
+        assert_eq!(smooth.subst("x", &y), smooth);
+        let path = Term::PathLambda { param: "i".to_string(), body: b(v("i")) };
+        assert_eq!(path.subst("i", &Term::IZero), path);
+
+        // Would capture: the binder is renamed
+        assert_eq!(lam("y", Term::Pair { first: b(v("x")), second: b(v("y")) }).subst("x", &y),
+            lam("y'", Term::Pair { first: b(v("y")), second: b(v("y'")) }));
+        let path = Term::PathLambda { param: "i".to_string(), body: b(Term::IMeet(b(v("j")), b(v("i")))) };
+        let Term::PathLambda { param, body } = path.subst("j", &v("i")) else { unreachable!() };
+        assert_eq!((param.as_str(), body.to_string()), ("i'", "i ∧ i'".to_string()));
+        let smooth = Term::SmoothFunc { expr: "a * t".to_string(), var: "t".to_string() };
+        let sum = Term::App { func: b(v("f")), arg: b(v("t")) };
+        assert_eq!(smooth.subst("a", &sum), Term::SmoothFunc { expr: "(f t) * t'".to_string(), var: "t'".to_string() });
+
+        // Nothing to capture: the binder keeps its name
+        assert_eq!(lam("y", v("y")).subst("x", &y), lam("y", v("y")));
+    }
+
+    #[test]
+    fn test_classic_capture_cases() {
+        let lam = |param: &str, body| Term::Lambda { param: param.to_string(), param_type: Box::new(Type::Real), body: b(body) };
+        let app = |f, a| Term::App { func: b(f), arg: b(a) };
+
+        // (λy. x)[y/x] is λy'. y, not λy. y
+        assert_eq!(lam("y", v("x")).subst("x", &v("y")), lam("y'", v("y")));
+        // y' is taken by the replacement, so y''
+        let both = app(v("y"), v("y'"));
+        assert_eq!(lam("y", app(v("x"), v("y"))).subst("x", &both), lam("y''", app(both.clone(), v("y''"))));
+        // Nested binders are renamed independently
+        let nested = lam("y", lam("z", app(v("x"), app(v("y"), v("z")))));
+        let renamed = nested.subst("x", &app(v("y"), v("z")));
+        assert_eq!(renamed.to_string(), "λy'. λz'. y z (y' z')");
+        assert_eq!(renamed.free_vars(), BTreeSet::from(["y".to_string(), "z".to_string()]));
+
+        // Π(y : ℝ). Path ℝ x y, with y for x
+        let pi = Type::Pi {
+            param: "y".to_string(),
+            domain: Box::new(Type::Real),
+            codomain: Box::new(Type::Path { space: Box::new(Type::Real), start: b(v("x")), end: b(v("y")) }),
+        };
+        let Type::Pi { param, codomain, .. } = pi.subst_term("x", &v("y")) else { unreachable!() };
+        assert_eq!(param, "y'");
+        assert_eq!(*codomain, Type::Path { space: Box::new(Type::Real), start: b(v("y")), end: b(v("y'")) });
+
+        // { y : ℝ, p : Path ℝ x y } with y for x: the label is renamed too
+        let path = |a, e| Type::Path { space: Box::new(Type::Real), start: b(a), end: b(e) };
+        let record = Type::Record(vec![("y".to_string(), Type::Real), ("p".to_string(), path(v("x"), v("y")))]);
+        let renamed = record.subst_term("x", &v("y"));
+        let expected = Type::Record(vec![("y'".to_string(), Type::Real), ("p".to_string(), path(v("y"), v("y'")))]);
+        assert_eq!(renamed, expected);
+        assert_eq!(renamed.free_vars(), BTreeSet::from(["y".to_string()]));
+        // Without `x` after it, or shadowed by a field named `x`, the label stays
+        let fields = [("y", Type::Real), ("x", Type::Real), ("p", path(v("x"), v("y")))];
+        let shadowed = Type::Record(fields.into_iter().map(|(label, ty)| (label.to_string(), ty)).collect());
+        assert_eq!(shadowed.subst_term("x", &v("y")), shadowed);
+    }
+
+    #[test]
+    fn test_dependent_codomains_are_instantiated() {
+        let path = |a: Term, b: Term| Type::Path { space: Box::new(Type::Real), start: Box::new(a), end: Box::new(b) };
+        let pi = |param: &str, codomain| Type::Pi { param: param.to_string(), domain: Box::new(Type::Real), codomain: Box::new(codomain) };
+
+        // Π(n: ℝ). Path ℝ n n at 2
+        let Type::Pi { param, codomain, .. } = pi("n", path(v("n"), v("n"))) else { unreachable!() };
+        assert_eq!(codomain.subst_term(&param, &Term::RealLit(2.0)), path(Term::RealLit(2.0), Term::RealLit(2.0)));
+
+        // Through nested binders, stopping where one shadows
+        let sigma = |codomain| Type::Sigma { param: "k".to_string(), domain: Box::new(Type::Real), codomain: Box::new(codomain) };
+        let nested = sigma(pi("n", path(v("n"), v("m"))));
+        assert_eq!(nested.subst_term("m", &Term::RealLit(2.0)), sigma(pi("n", path(v("n"), Term::RealLit(2.0)))));
+        assert_eq!(nested.subst_term("n", &Term::RealLit(2.0)), nested);
+        let function = Type::Function { domain: Box::new(path(v("n"), Term::IZero)), codomain: Box::new(Type::Real), is_smooth: false };
+        assert_eq!(function.subst_term("n", &v("k")).free_vars(), BTreeSet::from(["k".to_string()]));
+    }
+
+    #[test]
+    fn test_free_variables_and_renaming() {
+        let lam = |param: &str, body| Term::Lambda { param: param.to_string(), param_type: Box::new(Type::Real), body: b(body) };
+        let set = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<BTreeSet<_>>();
+
+        assert!(lam("x", v("x")).is_closed());
+        assert_eq!(lam("x", v("y")).free_vars(), set(&["y"]));
+        let interval = Term::PathLambda {
+            param: "i".to_string(),
+            body: b(Term::IJoin(b(Term::IMeet(b(v("i")), b(v("j")))), b(Term::INeg(b(v("k")))))),
+        };
+        assert_eq!(interval.free_vars(), set(&["j", "k"]));
+        let pairs = Term::Snd(b(Term::Pair { first: b(Term::Fst(b(v("p")))), second: b(v("q")) }));
+        assert_eq!(pairs.free_vars(), set(&["p", "q"]));
+
+        // Only the free occurrence is renamed
+        let shadowed = Term::Pair { first: b(v("x")), second: b(lam("x", v("x"))) };
+        assert_eq!(shadowed.rename("x", "z"), Term::Pair { first: b(v("z")), second: b(lam("x", v("x"))) });
+        // A binder of the new name moves aside
+        assert_eq!(lam("z", Term::Pair { first: b(v("x")), second: b(v("z")) }).rename("x", "z"),
+            lam("z'", Term::Pair { first: b(v("z")), second: b(v("z'")) }));
+        assert_eq!(interval.rename("j", "i").to_string(), "⟨i'⟩ i' ∧ i ∨ ¬k");
+    }
+
+    #[test]
+    fn test_numerals_print_as_numbers() {
+        assert_eq!(Term::nat(3).to_string(), "3");
+        assert_eq!(Term::nat(0).to_string(), "0");
+        assert_eq!(Term::nat(7).as_nat(), Some(7));
+        let open = Term::NatSucc(Box::new(Term::NatSucc(b(v("n")))));
+        assert_eq!(open.as_nat(), None);
+        assert_eq!(open.to_string(), "succ succ n");
+        let rec = Term::NatRec { motive: Box::new(Type::Nat), base: b(Term::nat(1)), step: b(v("s")), scrutinee: b(open) };
+        assert_eq!(rec.to_string(), "natrec[ℕ] 1 s (succ succ n)");
+        assert_eq!(Term::App { func: b(v("f")), arg: b(Term::nat(2)) }.to_string(), "f 2");
+    }
+
+    #[test]
+    fn test_universe_levels() {
+        let universe = |n| Box::new(Type::Universe(Level::Const(n)));
+        let pi = |param: &str, domain, codomain| Type::Pi { param: param.to_string(), domain, codomain: Box::new(codomain) };
+        let arrow = |domain, codomain| Type::Function { domain, codomain, is_smooth: false };
+
+        assert_eq!(Type::Real.universe_level(&[]).unwrap(), Level::ZERO);
+        assert_eq!(universe(0).universe_level(&[]).unwrap(), Level::Const(1));
+        // Π(A : Type_0). Π(B : Type_1). A → B lives where Type_1 does
+        let nested = pi("A", universe(0), pi("B", universe(1), arrow(Box::new(Type::Real), Box::new(Type::Real))));
+        assert_eq!(nested.universe_level(&[]).unwrap(), Level::Const(2));
+        let smooth_path = Type::Smooth(Box::new(Type::Path { space: universe(2), start: b(v("x")), end: b(v("x")) }));
+        assert_eq!(smooth_path.universe_level(&[("x".to_string(), Type::Universe(Level::Const(2)))]).unwrap(), Level::Const(3));
+        assert!(matches!(smooth_path.universe_level(&[]), Err(TypeError::VariableNotFound(x)) if x == "x"));
+    }
+
+    #[test]
+    fn test_cumulativity() {
+        assert!(Level::Const(1).leq(&Level::Const(1)) && Level::Const(1).leq(&Level::Const(3)) && !Level::Const(3).leq(&Level::Const(1)));
+        // Type_0 : Type_1, and so also Type_0 : Type_5
+        assert!(Type::Universe(Level::ZERO).check_in_universe(&[], Level::Const(1)).is_ok());
+        assert!(Type::Universe(Level::ZERO).check_in_universe(&[], Level::Const(5)).is_ok());
+
+        let err = Type::Universe(Level::Const(3)).check_in_universe(&[], Level::Const(1)).unwrap_err();
+        assert!(matches!(err, TypeError::UniverseMismatch { expected: Level::Const(1), got: Level::Const(4) }));
+        assert_eq!(err.to_string(), "Universe mismatch: a type in Type_4 does not fit in Type_1");
+    }
+
+    #[test]
+    fn test_alpha_equivalence() {
+        let lam = |param: &str, body| Term::Lambda { param: param.to_string(), param_type: Box::new(Type::Real), body: b(body) };
+        let plam = |param: &str, body| Term::PathLambda { param: param.to_string(), body: b(body) };
+        let app = |f, a| Term::App { func: b(f), arg: b(a) };
+
+        assert!(lam("x", v("x")).alpha_eq(&lam("y", v("y"))));
+        assert!(plam("i", Term::INeg(b(v("i")))).alpha_eq(&plam("j", Term::INeg(b(v("j"))))));
+        // λx. λy. x y ≡ λa. λb. a b, but not λa. λb. b a
+        assert!(lam("x", lam("y", app(v("x"), v("y")))).alpha_eq(&lam("a", lam("b", app(v("a"), v("b"))))));
+        assert!(!lam("x", lam("y", app(v("x"), v("y")))).alpha_eq(&lam("a", lam("b", app(v("b"), v("a"))))));
+        // Shadowing: in λx. λx. x the inner binder wins
+        assert!(lam("x", lam("x", v("x"))).alpha_eq(&lam("a", lam("b", v("b")))));
+        assert!(!lam("x", lam("x", v("x"))).alpha_eq(&lam("a", lam("b", v("a")))));
+        let smooth = |expr: &str, var: &str| Term::SmoothFunc { expr: expr.to_string(), var: var.to_string() };
+        assert!(lam("a", smooth("a * sin(t)", "t")).alpha_eq(&lam("b", smooth("b * sin(s)", "s"))));
+        assert!(!smooth("t * t", "t").alpha_eq(&smooth("t + t", "t")));
+
+        // Free variables must agree by name, and may not be captured
+        assert!(!lam("x", v("y")).alpha_eq(&lam("x", v("z"))));
+        assert!(!lam("x", v("y")).alpha_eq(&lam("y", v("y"))));
+        assert!(v("x").alpha_eq(&v("x")) && !v("x").alpha_eq(&v("y")));
+
+        // Types: binder names and path endpoints under them
+        let path = |start, end| Type::Path { space: Box::new(Type::Real), start: b(start), end: b(end) };
+        let pi = |param: &str, codomain| Type::Pi { param: param.to_string(), domain: Box::new(Type::Real), codomain: Box::new(codomain) };
+        assert!(pi("n", path(v("n"), v("m"))).alpha_eq(&pi("k", path(v("k"), v("m")))));
+        assert!(!pi("n", path(v("n"), v("m"))).alpha_eq(&pi("m", path(v("m"), v("m")))));
+        assert!(path(lam("x", v("x")), Term::IZero).alpha_eq(&path(lam("y", v("y")), Term::IZero)));
+    }
+
+    #[test]
+    fn test_substitution_leaves_closed_terms_alone() {
+        let closed = [
+            Term::Lambda { param: "x".to_string(), param_type: Box::new(Type::Real), body: b(v("x")) },
+            Term::PathLambda { param: "i".to_string(), body: b(Term::INeg(b(v("i")))) },
+            Term::Pair { first: b(Term::RealLit(1.0)), second: b(Term::IZero) },
+            Term::SmoothFunc { expr: "t * t".to_string(), var: "t".to_string() },
+        ];
+        for term in closed {
+            assert!(term.is_closed(), "{} is closed", term);
+            for var in ["x", "i", "t", "y"] {
+                assert_eq!(term.subst(var, &v("x")), term);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,216 @@
+//! Line-wrapping layout for terms
+//!
+//! [`Term::to_pretty`] prints a term as `Display` does when it fits the
+//! width, and otherwise breaks it at its outermost construct: binder
+//! bodies and application arguments go on indented lines of their own,
+//! `∧`/`∨`/`@` chains put one operand per line led by the operator, and a
+//! pair puts its components under each other. Parentheses are exactly the
+//! ones `Display` would write, so the result parses back to the same term.
+
+use crate::Term;
+
+impl Term {
+    /// The term laid out to fit `width` columns where it can
+    ///
+    /// Atoms and smooth functions never break, so a line may still run
+    /// over when one of them alone is too long.
+    pub fn to_pretty(&self, width: usize) -> String {
+        layout(self, 0, width)
+    }
+}
+
+/// `term` starting at column `col`; continuation lines carry their own
+/// indentation
+fn layout(term: &Term, col: usize, width: usize) -> String {
+    let flat = term.to_string();
+    if col + flat.chars().count() <= width {
+        return flat;
+    }
+    let line = |indent: usize| format!("\n{}", " ".repeat(indent));
+    match term {
+        Term::Lambda { .. } | Term::PathLambda { .. } => {
+            let mut head = Vec::new();
+            let mut body = term;
+            loop {
+                match body {
+                    Term::Lambda { param, body: inner, .. } => {
+                        head.push(format!("λ{}.", param));
+                        body = inner;
+                    }
+                    Term::PathLambda { param, body: inner } => {
+                        head.push(format!("⟨{}⟩", param));
+                        body = inner;
+                    }
+                    _ => break,
+                }
+            }
+            format!("{}{}{}", head.join(" "), line(col + 2), layout(body, col + 2, width))
+        }
+        Term::App { .. } => {
+            let mut spine = Vec::new();
+            let mut head = term;
+            while let Term::App { func, arg } = head {
+                spine.push(arg.as_ref());
+                head = func;
+            }
+            let mut out = operand(head, 5, col, width);
+            for arg in spine.into_iter().rev() {
+                out.push_str(&line(col + 2));
+                out.push_str(&operand(arg, 6, col + 2, width));
+            }
+            out
+        }
+        Term::IMeet(..) | Term::IJoin(..) | Term::PathApp { .. } => {
+            let (op, prec) = match term {
+                Term::IMeet(..) => ("∧", 2),
+                Term::IJoin(..) => ("∨", 1),
+                _ => ("@", 3),
+            };
+            let mut rest = Vec::new();
+            let mut first = term;
+            while let Some((lhs, rhs)) = split(first, op) {
+                rest.push(rhs);
+                first = lhs;
+            }
+            let mut out = operand(first, prec, col, width);
+            for rhs in rest.into_iter().rev() {
+                out.push_str(&line(col));
+                out.push_str(op);
+                out.push(' ');
+                out.push_str(&operand(rhs, prec + 1, col + 2, width));
+            }
+            out
+        }
+        Term::Let { name, ty, value, body } => {
+            let head = format!("let {} : {} = ", name, ty);
+            let value = layout(value, col + head.chars().count(), width);
+            format!("{}{} in{}{}", head, value, line(col), layout(body, col, width))
+        }
+        Term::Pair { first, second } => format!(
+            "({},{}{})",
+            layout(first, col + 1, width),
+            line(col + 1),
+            layout(second, col + 1, width)
+        ),
+        // One field per line, aligned after the brace
+        Term::RecordLit(fields) if !fields.is_empty() => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(name, value)| {
+                    let head = format!("{} := ", name);
+                    format!("{}{}", head, layout(value, col + 2 + head.chars().count(), width))
+                })
+                .collect();
+            format!("{{ {} }}", fields.join(&format!(",{}", line(col + 2))))
+        }
+        Term::Fst(t) | Term::Snd(t) | Term::INeg(t) => {
+            let prefix = match term {
+                Term::Fst(_) => "π₁ ",
+                Term::Snd(_) => "π₂ ",
+                _ => "¬",
+            };
+            format!("{}{}", prefix, operand(t, 4, col + prefix.chars().count(), width))
+        }
+        Term::Var(_)
+        | Term::IZero
+        | Term::IOne
+        | Term::RealLit(_)
+        | Term::SmoothFunc { .. }
+        | Term::Hole
+        | Term::NatZero
+        | Term::NatSucc(_)
+        | Term::NatRec { .. }
+        | Term::Star
+        | Term::True
+        | Term::False
+        | Term::Base
+        | Term::Loop
+        | Term::BoolElim { .. }
+        | Term::CircleElim { .. }
+        | Term::Inl(_)
+        | Term::Inr(_)
+        | Term::SmoothIn(_)
+        | Term::SmoothOut(_)
+        | Term::Case { .. }
+        | Term::EmptyElim { .. }
+        | Term::Ann { .. }
+        | Term::RecordLit(_)
+        | Term::Proj(..) => flat,
+    }
+}
+
+/// A subterm that binds at least as tightly as `min`, else parenthesised
+fn operand(term: &Term, min: u8, col: usize, width: usize) -> String {
+    if term.precedence() < min {
+        format!("({})", layout(term, col + 1, width))
+    } else {
+        layout(term, col, width)
+    }
+}
+
+/// The operands of a left-associated `op` node
+fn split<'a>(term: &'a Term, op: &str) -> Option<(&'a Term, &'a Term)> {
+    match (term, op) {
+        (Term::IMeet(a, b), "∧") | (Term::IJoin(a, b), "∨") => Some((a, b)),
+        (Term::PathApp { path, point }, "@") => Some((path, point)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simp::parse_term;
+    use crate::Term;
+
+    /// A dozen representative terms in their printed form; each parses
+    /// back to a term that prints the same
+    const GOLDEN: [&str; 12] = [
+        "x",
+        "λx. x",
+        "λf. λx. f (f x)",
+        "(λx. x) y",
+        "f x y",
+        "f (g x) (λy. y)",
+        "(a, π₁ p)",
+        "π₂ π₁ p",
+        "(π₁ p) x",
+        "⟨i⟩ p @ ¬i",
+        "i ∧ j ∨ ¬(k ∨ 0)",
+        "p @ i @ (j ∧ 1)",
+    ];
+
+    #[test]
+    fn test_printing_round_trips_through_the_parser() {
+        for source in GOLDEN {
+            let term = parse_term(source).unwrap();
+            assert_eq!(term.to_string(), source);
+            assert_eq!(parse_term(&term.to_string()).unwrap(), term);
+        }
+    }
+
+    #[test]
+    fn test_long_terms_wrap_at_their_outermost_construct() {
+        let term = parse_term("λf. λg. ⟨i⟩ compose (f left_argument) (g right_argument) @ i").unwrap();
+        assert_eq!(term.to_pretty(80), term.to_string());
+        assert_eq!(term.to_pretty(50), "λf. λg. ⟨i⟩\n  compose (f left_argument) (g right_argument) @ i");
+        assert_eq!(
+            term.to_pretty(40),
+            "λf. λg. ⟨i⟩\n  compose\n    (f left_argument)\n    (g right_argument)\n  @ i"
+        );
+    }
+
+    #[test]
+    fn test_wrapped_terms_parse_back() {
+        let pair = "(π₁ first_component_of_a_pair x, λy. second_component y (¬i ∨ j ∧ k))";
+        let term = parse_term(pair).unwrap();
+        for width in [10, 20, 40, 200] {
+            let pretty = term.to_pretty(width);
+            assert_eq!(parse_term(&pretty).unwrap(), term, "at width {}:\n{}", width, pretty);
+        }
+        assert_eq!(
+            term.to_pretty(40),
+            "(π₁ first_component_of_a_pair x,\n λy. second_component y (¬i ∨ j ∧ k))"
+        );
+        assert_eq!(Term::Var("a_very_long_name".to_string()).to_pretty(4), "a_very_long_name");
+    }
+}
@@ -0,0 +1,597 @@
+//! Run records: the stable JSON shape of a stored run result
+//!
+//! Lab history, workspace exports and the server's run log all store a
+//! [`RunRecord`]. The shape is versioned by `schema_version` and described
+//! once, as data in [`RUN_RECORD`]; [`RunRecord::validate`] checks a
+//! document against that description and [`schema`] renders it as the
+//! OpenAPI component external tools can read. Records written before the
+//! schema existed are upgraded by [`RunRecord::load`].
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "timestamp": "2025-03-01T12:00:00Z",
+//!   "code_hash": "7e5250db63cb9524",
+//!   "engine_versions": { "sctt-core": "0.1.0" },
+//!   "diagnostics": [{ "severity": "warning", "message": "unused binder", "span": { "start": 2, "end": 3 } }],
+//!   "result": { "kind": "checked", "type": "C∞(ℝ, ℝ)" },
+//!   "duration_ms": 42
+//! }
+//! ```
+//!
+//! `result.kind` is one of `checked {type}`, `evaluated {samples}`,
+//! `compiled {size, hash}` or `failed {error}`. `seed` is present only for
+//! randomized runs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::compat::{self, Format, Verdict, ENGINE_VERSION};
+
+/// The `engine_versions` key of the engine whose version decides
+/// compatibility
+pub const ENGINE: &str = "sctt-core";
+
+/// The version [`RunRecord`]s are written with
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A document that is not a valid run record, and where it goes wrong
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{path}: {message}")]
+pub struct SchemaError {
+    /// JSON path of the offending value, e.g. `$.result.type`
+    pub path: String,
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        SchemaError { path: path.to_string(), message: message.into() }
+    }
+}
+
+/// One stored run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub schema_version: u32,
+    /// RFC 3339, UTC
+    pub timestamp: String,
+    /// [`code_hash`] of the source that ran
+    pub code_hash: String,
+    /// Crate name to version, for every engine that took part
+    pub engine_versions: BTreeMap<String, String>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub result: RunResult,
+    pub duration_ms: u64,
+    /// Seed of a randomized run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+    /// A fix to offer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Byte offsets into the source, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RunResult {
+    Checked {
+        #[serde(rename = "type")]
+        ty: String,
+    },
+    Evaluated { samples: SampleSummary },
+    Compiled { size: usize, hash: String },
+    Failed { error: String },
+}
+
+/// What an evaluation produced, without the samples themselves
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleSummary {
+    pub count: usize,
+    /// Samples that were not finite
+    pub undefined: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl SampleSummary {
+    pub fn of(values: &[f64]) -> Self {
+        let finite = || values.iter().copied().filter(|v| v.is_finite());
+        SampleSummary {
+            count: values.len(),
+            undefined: values.len() - finite().count(),
+            min: finite().reduce(f64::min),
+            max: finite().reduce(f64::max),
+        }
+    }
+}
+
+/// Stable hash of source text: 64-bit FNV-1a as 16 hex digits
+pub fn code_hash(code: &str) -> String {
+    let hash = code.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+/// RFC 3339 timestamp for seconds since the Unix epoch
+pub fn rfc3339(unix_secs: u64) -> String {
+    let (days, secs) = (unix_secs / 86_400, unix_secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, secs / 3_600, secs % 3_600 / 60, secs % 60)
+}
+
+impl RunRecord {
+    /// A record of running `code`, attributed to this crate's engine
+    pub fn new(code: &str, timestamp: impl Into<String>, result: RunResult) -> Self {
+        RunRecord {
+            schema_version: SCHEMA_VERSION,
+            timestamp: timestamp.into(),
+            code_hash: code_hash(code),
+            engine_versions: BTreeMap::from([(ENGINE.to_string(), ENGINE_VERSION.to_string())]),
+            diagnostics: Vec::new(),
+            result,
+            duration_ms: 0,
+            seed: None,
+        }
+    }
+
+    pub fn with_engine(mut self, name: &str, version: &str) -> Self {
+        self.engine_versions.insert(name.to_string(), version.to_string());
+        self
+    }
+
+    pub fn with_diagnostic(mut self, diagnostic: Diagnostic) -> Self {
+        self.diagnostics.push(diagnostic);
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration_ms = duration.as_millis().try_into().unwrap_or(u64::MAX);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Whether the result can be trusted by this engine or must be checked
+    /// again; records without an `sctt-core` version never can
+    pub fn verdict(&self, format: Format) -> Verdict {
+        compat::assess(format, self.engine_versions.get(ENGINE).map(String::as_str))
+    }
+
+    /// Check a document against the current schema and decode it
+    pub fn validate(json: &str) -> Result<RunRecord, SchemaError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| SchemaError::new("$", e.to_string()))?;
+        RunRecord::validate_value(&value)
+    }
+
+    pub fn validate_value(value: &Value) -> Result<RunRecord, SchemaError> {
+        check(value, &RUN_RECORD_SHAPE, "$")?;
+        match value.get("schema_version").and_then(Value::as_u64) {
+            Some(version) if version > u64::from(SCHEMA_VERSION) => {
+                return Err(SchemaError::new(
+                    "$.schema_version",
+                    format!("version {} is newer than this reader ({})", version, SCHEMA_VERSION),
+                ))
+            }
+            Some(0) => return Err(SchemaError::new("$.schema_version", "versions start at 1")),
+            _ => {}
+        }
+        RunRecord::deserialize(value).map_err(|e| SchemaError::new("$", e.to_string()))
+    }
+
+    /// Decode a stored record of any version, upgrading it first
+    pub fn load(json: &str) -> Result<RunRecord, SchemaError> {
+        let value: Value = serde_json::from_str(json).map_err(|e| SchemaError::new("$", e.to_string()))?;
+        RunRecord::load_value(value)
+    }
+
+    pub fn load_value(value: Value) -> Result<RunRecord, SchemaError> {
+        RunRecord::validate_value(&migrate(value)?)
+    }
+}
+
+/// Upgrade a stored document to the current schema version
+///
+/// Before version 1, history entries stored `{code, result, timestamp}`
+/// with `result` the lab's debug-printed output. Its type, error and run
+/// time are recovered where present, and the original text is kept as an
+/// info diagnostic so nothing is lost.
+pub fn migrate(value: Value) -> Result<Value, SchemaError> {
+    let Value::Object(object) = value else {
+        return Err(SchemaError::new("$", "expected an object"));
+    };
+    if object.contains_key("schema_version") {
+        return Ok(Value::Object(object));
+    }
+    let text = |key: &str| object.get(key).and_then(Value::as_str);
+    let (Some(code), Some(raw)) = (text("code"), text("result")) else {
+        return Err(SchemaError::new("$.schema_version", "missing, and not a pre-schema record either"));
+    };
+
+    let field = |name: &str| debug_field(raw, name);
+    let result = if field("status").as_deref() == Some("Error") {
+        RunResult::Failed { error: field("status_text").unwrap_or_else(|| raw.to_string()) }
+    } else {
+        match field("type_info") {
+            Some(ty) if ty == "None" => RunResult::Checked { ty: String::new() },
+            Some(ty) => RunResult::Checked { ty },
+            None => RunResult::Checked { ty: raw.to_string() },
+        }
+    };
+
+    let mut record = RunRecord::new(code, text("timestamp").unwrap_or_default(), result).with_diagnostic(Diagnostic {
+        severity: Severity::Info,
+        message: format!("pre-schema result: {}", raw),
+        span: None,
+        suggestion: None,
+    });
+    // The producing engine is unknown
+    record.engine_versions.clear();
+    record.duration_ms = field("execution_time").and_then(|t| t.parse().ok()).unwrap_or(0);
+    serde_json::to_value(record).map_err(|e| SchemaError::new("$", e.to_string()))
+}
+
+/// The value of `name: ...` in a `{:?}`-printed struct, unquoted and
+/// without a `Some(..)` wrapper
+fn debug_field(raw: &str, name: &str) -> Option<String> {
+    let start = raw.find(&format!("{}: ", name))? + name.len() + 2;
+    let rest = &raw[start..];
+    let rest = rest.strip_prefix("Some(").unwrap_or(rest);
+    let Some(quoted) = rest.strip_prefix('"') else {
+        let end = rest.find([',', ')', '}']).unwrap_or(rest.len());
+        return Some(rest[..end].trim().to_string());
+    };
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => value.extend(chars.next()),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// The JSON shape of a field's value; other documents than run records,
+/// such as the checker's responses, are described with it too
+#[derive(Debug, Clone, Copy)]
+pub enum Shape {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    /// Any JSON value at all
+    Any,
+    /// A string from a fixed set
+    OneOf(&'static [&'static str]),
+    Array(&'static Shape),
+    Object(&'static [Field]),
+    /// An object with arbitrary keys, all values of one shape
+    Map(&'static Shape),
+    /// An object whose `kind` string selects its remaining fields
+    Tagged(&'static [(&'static str, &'static [Field])]),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub name: &'static str,
+    pub shape: Shape,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+pub const fn field(name: &'static str, shape: Shape, description: &'static str) -> Field {
+    Field { name, shape, required: true, description }
+}
+
+pub const fn optional(name: &'static str, shape: Shape, description: &'static str) -> Field {
+    Field { name, shape, required: false, description }
+}
+
+pub const SPAN: &[Field] = &[
+    field("start", Shape::Integer, "Byte offset of the first character"),
+    field("end", Shape::Integer, "Byte offset just past the last character"),
+];
+
+const DIAGNOSTIC: &[Field] = &[
+    field("severity", Shape::OneOf(&["error", "warning", "info"]), ""),
+    field("message", Shape::String, ""),
+    optional("span", Shape::Object(SPAN), "Where in the source"),
+    optional("suggestion", Shape::String, "A fix to offer"),
+];
+
+const SAMPLE_SUMMARY: &[Field] = &[
+    field("count", Shape::Integer, "Points evaluated"),
+    field("undefined", Shape::Integer, "Points where the value was not finite"),
+    optional("min", Shape::Number, "Least finite value"),
+    optional("max", Shape::Number, "Greatest finite value"),
+];
+
+const RESULTS: &[(&str, &[Field])] = &[
+    ("checked", &[field("type", Shape::String, "The inferred type")]),
+    ("evaluated", &[field("samples", Shape::Object(SAMPLE_SUMMARY), "")]),
+    ("compiled", &[field("size", Shape::Integer, "Module size in bytes"), field("hash", Shape::String, "")]),
+    ("failed", &[field("error", Shape::String, "")]),
+];
+
+/// Every field of a run record, the single description of the schema
+pub const RUN_RECORD: &[Field] = &[
+    field("schema_version", Shape::Integer, "Schema version the record was written with"),
+    field("timestamp", Shape::String, "When the run finished, RFC 3339"),
+    field("code_hash", Shape::String, "64-bit FNV-1a of the source, 16 hex digits"),
+    field("engine_versions", Shape::Map(&Shape::String), "Crate name to version for every engine involved"),
+    field("diagnostics", Shape::Array(&Shape::Object(DIAGNOSTIC)), ""),
+    field("result", Shape::Tagged(RESULTS), "What the run produced, by `kind`"),
+    field("duration_ms", Shape::Integer, ""),
+    optional("seed", Shape::Integer, "Seed of a randomized run"),
+];
+
+const RUN_RECORD_SHAPE: Shape = Shape::Object(RUN_RECORD);
+
+/// Whether `value` has the shape `shape`; where it does not, from `$`
+pub fn conform(value: &Value, shape: &Shape) -> Result<(), SchemaError> {
+    check(value, shape, "$")
+}
+
+fn check(value: &Value, shape: &Shape, path: &str) -> Result<(), SchemaError> {
+    let expected = |what: &str| Err(SchemaError::new(path, format!("expected {}", what)));
+    match shape {
+        Shape::String => value.as_str().map(drop).map_or_else(|| expected("a string"), Ok),
+        Shape::Integer => value.as_u64().map(drop).map_or_else(|| expected("a non-negative integer"), Ok),
+        Shape::Number => value.as_f64().map(drop).map_or_else(|| expected("a number"), Ok),
+        Shape::Boolean => value.as_bool().map(drop).map_or_else(|| expected("true or false"), Ok),
+        Shape::Any => Ok(()),
+        Shape::OneOf(options) => match value.as_str() {
+            Some(s) if options.contains(&s) => Ok(()),
+            _ => expected(&format!("one of {}", options.join(", "))),
+        },
+        Shape::Array(item) => match value.as_array() {
+            Some(items) => items.iter().enumerate().try_for_each(|(i, v)| check(v, item, &format!("{}[{}]", path, i))),
+            None => expected("an array"),
+        },
+        Shape::Object(fields) => match value.as_object() {
+            Some(object) => check_fields(object, fields, path),
+            None => expected("an object"),
+        },
+        Shape::Map(item) => match value.as_object() {
+            Some(object) => object.iter().try_for_each(|(k, v)| check(v, item, &format!("{}.{}", path, k))),
+            None => expected("an object"),
+        },
+        Shape::Tagged(variants) => {
+            let Some(object) = value.as_object() else {
+                return expected("an object");
+            };
+            let kind_path = format!("{}.kind", path);
+            let Some(kind) = object.get("kind") else {
+                return Err(SchemaError::new(&kind_path, "required field is missing"));
+            };
+            match variants.iter().find(|(name, _)| kind.as_str() == Some(*name)) {
+                Some((_, fields)) => check_fields(object, fields, path),
+                None => {
+                    let names: Vec<_> = variants.iter().map(|(name, _)| *name).collect();
+                    Err(SchemaError::new(&kind_path, format!("expected one of {}", names.join(", "))))
+                }
+            }
+        }
+    }
+}
+
+fn check_fields(object: &Map<String, Value>, fields: &[Field], path: &str) -> Result<(), SchemaError> {
+    for field in fields {
+        let field_path = format!("{}.{}", path, field.name);
+        match object.get(field.name) {
+            Some(value) => check(value, &field.shape, &field_path)?,
+            None if field.required => return Err(SchemaError::new(&field_path, "required field is missing")),
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// The schema as an OpenAPI 3 component, `components.schemas.RunRecord`
+pub fn schema() -> Value {
+    let mut schema = render(&RUN_RECORD_SHAPE);
+    schema["title"] = json!("RunRecord");
+    schema["description"] = json!(format!("A stored run result, schema version {}", SCHEMA_VERSION));
+    schema
+}
+
+/// `shape` as a JSON schema
+pub fn render(shape: &Shape) -> Value {
+    match shape {
+        Shape::String => json!({ "type": "string" }),
+        Shape::Integer => json!({ "type": "integer", "minimum": 0 }),
+        Shape::Number => json!({ "type": "number" }),
+        Shape::Boolean => json!({ "type": "boolean" }),
+        Shape::Any => json!({}),
+        Shape::OneOf(options) => json!({ "type": "string", "enum": options }),
+        Shape::Array(item) => json!({ "type": "array", "items": render(item) }),
+        Shape::Object(fields) => render_object(fields, None),
+        Shape::Map(item) => json!({ "type": "object", "additionalProperties": render(item) }),
+        Shape::Tagged(variants) => json!({
+            "oneOf": variants.iter().map(|(kind, fields)| render_object(fields, Some(kind))).collect::<Vec<_>>(),
+            "discriminator": { "propertyName": "kind" },
+        }),
+    }
+}
+
+fn render_object(fields: &[Field], kind: Option<&str>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    if let Some(kind) = kind {
+        properties.insert("kind".to_string(), json!({ "type": "string", "enum": [kind] }));
+        required.push("kind");
+    }
+    for field in fields {
+        let mut property = render(&field.shape);
+        if !field.description.is_empty() {
+            property["description"] = json!(field.description);
+        }
+        properties.insert(field.name.to_string(), property);
+        if field.required {
+            required.push(field.name);
+        }
+    }
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(result: RunResult) -> RunRecord {
+        RunRecord::new("sin(x)", rfc3339(1_740_830_400), result).with_duration(Duration::from_millis(42))
+    }
+
+    fn variants() -> Vec<RunRecord> {
+        vec![
+            record(RunResult::Checked { ty: "C∞(ℝ, ℝ)".to_string() }).with_diagnostic(Diagnostic {
+                severity: Severity::Warning,
+                message: "unused binder".to_string(),
+                span: Some(Span { start: 2, end: 3 }),
+                suggestion: None,
+            }),
+            record(RunResult::Evaluated { samples: SampleSummary::of(&[0.0, 0.5, f64::NAN, -1.0]) }).with_seed(7),
+            record(RunResult::Compiled { size: 8, hash: code_hash("\0asm") }),
+            record(RunResult::Failed { error: "`ln(x)` is undefined at -1".to_string() }),
+        ]
+    }
+
+    #[test]
+    fn test_golden_serialization_per_variant() {
+        let head = r#""schema_version":1,"timestamp":"2025-03-01T12:00:00Z","code_hash":"7e5250db63cb9524","engine_versions":{"sctt-core":"0.1.0"}"#;
+        let golden = [
+            r#""diagnostics":[{"severity":"warning","message":"unused binder","span":{"start":2,"end":3}}],"result":{"kind":"checked","type":"C∞(ℝ, ℝ)"},"duration_ms":42"#,
+            r#""diagnostics":[],"result":{"kind":"evaluated","samples":{"count":4,"undefined":1,"min":-1.0,"max":0.5}},"duration_ms":42,"seed":7"#,
+            r#""diagnostics":[],"result":{"kind":"compiled","size":8,"hash":"2ea6307efb58f2f2"},"duration_ms":42"#,
+            r#""diagnostics":[],"result":{"kind":"failed","error":"`ln(x)` is undefined at -1"},"duration_ms":42"#,
+        ];
+        for (record, body) in variants().iter().zip(golden) {
+            let json = serde_json::to_string(record).unwrap();
+            assert_eq!(json, format!("{{{},{}}}", head, body));
+            assert_eq!(&RunRecord::validate(&json).unwrap(), record);
+        }
+    }
+
+    #[test]
+    fn test_pre_schema_history_entries_migrate() {
+        let legacy = r#"{"code":"sin(x)","timestamp":"2025-03-01T12:00:00+00:00","pinned":true,"result":"OutputData { status: Success, status_text: \"Type check passed\", messages: [\"✓ Valid smooth function\"], type_info: Some(\"C∞(ℝ, ℝ)\"), execution_time: 42 }"}"#;
+        let migrated = RunRecord::load(legacy).unwrap();
+        assert_eq!(migrated.schema_version, SCHEMA_VERSION);
+        assert_eq!(migrated.result, RunResult::Checked { ty: "C∞(ℝ, ℝ)".to_string() });
+        assert_eq!(migrated.duration_ms, 42);
+        assert_eq!(migrated.code_hash, code_hash("sin(x)"));
+        assert_eq!(migrated.timestamp, "2025-03-01T12:00:00+00:00");
+        assert!(migrated.engine_versions.is_empty());
+        assert_eq!(migrated.verdict(Format::HistoryEntry).action, Some(compat::Policy::Recheck));
+        assert!(migrated.diagnostics[0].message.contains("Type check passed"));
+
+        let failed = r#"{"code":"x","timestamp":"","result":"OutputData { status: Error, status_text: \"Unbound x\", type_info: None, execution_time: 3 }"}"#;
+        assert_eq!(RunRecord::load(failed).unwrap().result, RunResult::Failed { error: "Unbound x".to_string() });
+        let plain = r#"{"code":"Type","timestamp":"","result":"Universe(Succ(1))"}"#;
+        assert_eq!(RunRecord::load(plain).unwrap().result, RunResult::Checked { ty: "Universe(Succ(1))".to_string() });
+
+        // Current records load unchanged; unrelated documents do not
+        let current = serde_json::to_string(&variants()[1]).unwrap();
+        assert_eq!(RunRecord::load(&current).unwrap(), variants()[1]);
+        assert_eq!(RunRecord::load(r#"{"code":"x"}"#).unwrap_err().path, "$.schema_version");
+    }
+
+    /// Remove each required field from each variant in turn
+    #[test]
+    fn test_missing_required_fields_are_named() {
+        fn required_paths(value: &Value, fields: &[Field], path: &str, out: &mut Vec<String>) {
+            for field in fields.iter().filter(|f| f.required) {
+                let field_path = format!("{}/{}", path, field.name);
+                out.push(field_path.clone());
+                let inner = &value[field.name];
+                match field.shape {
+                    Shape::Object(nested) => required_paths(inner, nested, &field_path, out),
+                    Shape::Array(Shape::Object(nested)) => {
+                        for i in 0..inner.as_array().map_or(0, Vec::len) {
+                            required_paths(&inner[i], nested, &format!("{}/{}", field_path, i), out);
+                        }
+                    }
+                    Shape::Tagged(variants) => {
+                        out.push(format!("{}/kind", field_path));
+                        let kind = inner["kind"].as_str().unwrap_or_default();
+                        let (_, nested) = variants.iter().find(|(name, _)| *name == kind).unwrap();
+                        required_paths(inner, nested, &field_path, out);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut checked = 0;
+        for record in variants() {
+            let value = serde_json::to_value(&record).unwrap();
+            let mut paths = Vec::new();
+            required_paths(&value, RUN_RECORD, "", &mut paths);
+            for pointer in paths {
+                let (parent, name) = pointer.rsplit_once('/').unwrap();
+                let mut broken = value.clone();
+                broken.pointer_mut(parent).and_then(Value::as_object_mut).unwrap().remove(name);
+                let error = RunRecord::validate(&broken.to_string()).unwrap_err();
+
+                let expected = format!("${}", pointer.replace('/', ".")).replace(".0", "[0]");
+                assert_eq!(error.path, expected, "removing {}", pointer);
+                assert_eq!(error.message, "required field is missing");
+                checked += 1;
+            }
+        }
+        assert!(checked >= 30, "only {} paths checked", checked);
+
+        let wrong = serde_json::to_string(&variants()[0]).unwrap().replace(r#""duration_ms":42"#, r#""duration_ms":"42""#);
+        assert_eq!(RunRecord::validate(&wrong).unwrap_err().path, "$.duration_ms");
+    }
+
+    #[test]
+    fn test_schema_component_lists_required_fields() {
+        let schema = schema();
+        let required: Vec<_> = schema["required"].as_array().unwrap().iter().filter_map(Value::as_str).collect();
+        assert_eq!(required, ["schema_version", "timestamp", "code_hash", "engine_versions", "diagnostics", "result", "duration_ms"]);
+        assert_eq!(schema["properties"]["result"]["oneOf"].as_array().unwrap().len(), 4);
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+    }
+}
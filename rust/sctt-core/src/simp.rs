@@ -0,0 +1,1188 @@
+//! Simplification with recorded derivations
+//!
+//! [`simp`] rewrites a term to normal form one rule application at a time,
+//! leftmost-outermost: β for functions and paths, projections out of pairs,
+//! and the De Morgan algebra laws on the interval. [`normalize_interval`]
+//! uses the interval laws alone.
+//!
+//! Every rewrite is reported to a [`Recorder`]. Pass `&mut ()` to discard
+//! them, or a `Vec<Step>` to keep the chain — that chain is a derivation
+//! the learn page can print line by line, each line justified by the law
+//! that produced it.
+//!
+//! [`Term::normalize`] runs the same rewrites under a step budget and
+//! reports a term that outlasts it as [`TypeError::OutOfFuel`] instead of
+//! handing back wherever it got to.
+//!
+//! Interval normal forms are joins of meets of literals (`i` or `¬i`), with
+//! no units, no repeated operand and no absorbable term. The De Morgan
+//! algebra is not Boolean, so `i ∧ ¬i` is already normal.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Result as CheckResult, Span, Term, Type, TypeError};
+
+/// Rewrites before [`simp`] gives up on a term without a normal form
+pub const MAX_STEPS: usize = 1000;
+
+/// The law justifying a rewrite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rule {
+    /// `(λx. b) a ⟶ b[a/x]`
+    Beta,
+    /// `(⟨i⟩ b) @ r ⟶ b[r/i]`
+    PathBeta,
+    /// `π₁ (a, b) ⟶ a` and `π₂ (a, b) ⟶ b`
+    Projection,
+    /// `natrec z s 0 ⟶ z` and `natrec z s (succ n) ⟶ s n (natrec z s n)`
+    Recursion,
+    /// `if true t f ⟶ t` and `if false t f ⟶ f`; on the circle,
+    /// `S¹-elim base b l ⟶ b` and `S¹-elim (loop @ r) b l ⟶ l @ r`;
+    /// on sums, `case (inl a) l r ⟶ l a` and `case (inr b) l r ⟶ r b`
+    Case,
+    /// `loop @ 0 ⟶ base` and `loop @ 1 ⟶ base`
+    Boundary,
+    /// `smooth-out (smooth-in t) ⟶ t`
+    Modality,
+    /// `let x : A = v in e ⟶ e[v/x]`
+    Zeta,
+    /// `(e : A) ⟶ e`
+    Erasure,
+    /// `¬¬i ⟶ i`
+    DoubleNegation,
+    /// `¬0 ⟶ 1` and `¬1 ⟶ 0`
+    NegateEndpoint,
+    /// `¬(i ∧ j) ⟶ ¬i ∨ ¬j` and `¬(i ∨ j) ⟶ ¬i ∧ ¬j`
+    DeMorgan,
+    /// `i ∧ 1 ⟶ i` and `i ∨ 0 ⟶ i`
+    Unit,
+    /// `i ∧ 0 ⟶ 0` and `i ∨ 1 ⟶ 1`
+    Annihilation,
+    /// `i ∧ i ⟶ i` and `i ∨ i ⟶ i`
+    Idempotence,
+    /// `i ∧ (i ∨ j) ⟶ i` and `i ∨ (i ∧ j) ⟶ i`
+    Absorption,
+    /// `i ∧ (j ∨ k) ⟶ (i ∧ j) ∨ (i ∧ k)`
+    Distributivity,
+}
+
+impl Rule {
+    /// Short justification printed beside a derivation line
+    pub fn tag(self) -> &'static str {
+        match self {
+            Rule::Beta => "β",
+            Rule::PathBeta => "path β",
+            Rule::Projection => "projection",
+            Rule::Recursion => "recursion",
+            Rule::Case => "case",
+            Rule::Boundary => "boundary",
+            Rule::Modality => "modality",
+            Rule::Zeta => "ζ",
+            Rule::Erasure => "annotation",
+            Rule::DoubleNegation => "involution",
+            Rule::NegateEndpoint => "¬ endpoint",
+            Rule::DeMorgan => "De Morgan",
+            Rule::Unit => "unit",
+            Rule::Annihilation => "annihilation",
+            Rule::Idempotence => "idempotence",
+            Rule::Absorption => "absorption",
+            Rule::Distributivity => "distributivity",
+        }
+    }
+
+    /// Anchor of the reference page entry stating the law
+    pub fn reference(self) -> &'static str {
+        match self {
+            Rule::Beta
+            | Rule::PathBeta
+            | Rule::Projection
+            | Rule::Recursion
+            | Rule::Case
+            | Rule::Boundary
+            | Rule::Modality
+            | Rule::Zeta
+            | Rule::Erasure => "computation-rules",
+            Rule::DoubleNegation | Rule::NegateEndpoint => "interval-negation",
+            Rule::DeMorgan => "de-morgan-laws",
+            Rule::Unit | Rule::Annihilation | Rule::Idempotence | Rule::Absorption | Rule::Distributivity => {
+                "lattice-laws"
+            }
+        }
+    }
+
+    fn is_interval_law(self) -> bool {
+        !matches!(
+            self,
+            Rule::Beta
+                | Rule::PathBeta
+                | Rule::Projection
+                | Rule::Recursion
+                | Rule::Case
+                | Rule::Boundary
+                | Rule::Modality
+                | Rule::Zeta
+                | Rule::Erasure
+        )
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+/// One rewrite of the whole term
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Step {
+    pub rule: Rule,
+    pub before: String,
+    pub after: String,
+}
+
+/// Receives each rewrite as it happens
+pub trait Recorder {
+    fn record(&mut self, rule: Rule, before: &Term, after: &Term);
+}
+
+/// Discards the steps
+impl Recorder for () {
+    fn record(&mut self, _: Rule, _: &Term, _: &Term) {}
+}
+
+impl Recorder for Vec<Step> {
+    fn record(&mut self, rule: Rule, before: &Term, after: &Term) {
+        self.push(Step { rule, before: before.to_string(), after: after.to_string() });
+    }
+}
+
+/// A chain `start = … = result`, one [`Step`] per line after the first
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Derivation {
+    pub start: String,
+    pub steps: Vec<Step>,
+}
+
+impl Derivation {
+    pub fn result(&self) -> &str {
+        self.steps.last().map_or(&self.start, |s| &s.after)
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("{message} at position {position}")]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+pub fn simp(term: &Term, recorder: &mut impl Recorder) -> Term {
+    rewrite(term, recorder, false)
+}
+
+pub fn normalize_interval(term: &Term, recorder: &mut impl Recorder) -> Term {
+    rewrite(term, recorder, true)
+}
+
+/// Parse and simplify an interval expression such as `¬(i ∧ j)`
+pub fn derive_interval(source: &str) -> Result<Derivation, ParseError> {
+    derive(source, true)
+}
+
+/// Parse and simplify a term such as `π₁ ((λx. (x, ¬x)) i)`
+pub fn derive_simp(source: &str) -> Result<Derivation, ParseError> {
+    derive(source, false)
+}
+
+fn derive(source: &str, interval_only: bool) -> Result<Derivation, ParseError> {
+    let term = parse_term(source)?;
+    let mut steps = Vec::new();
+    rewrite(&term, &mut steps, interval_only);
+    Ok(Derivation { start: term.to_string(), steps })
+}
+
+impl Term {
+    /// The normal form under β, path β, projections and the interval laws
+    ///
+    /// Gives up with [`TypeError::OutOfFuel`] after [`MAX_STEPS`] rewrites.
+    pub fn normalize(&self) -> CheckResult<Term> {
+        self.normalize_with_fuel(MAX_STEPS)
+    }
+
+    /// [`Term::normalize`] allowing at most `fuel` rewrites
+    pub fn normalize_with_fuel(&self, fuel: usize) -> CheckResult<Term> {
+        rewrite_within(self, &mut (), false, fuel).map_err(|_| TypeError::OutOfFuel { fuel })
+    }
+}
+
+fn rewrite(term: &Term, recorder: &mut impl Recorder, interval_only: bool) -> Term {
+    rewrite_within(term, recorder, interval_only, MAX_STEPS).unwrap_or_else(|stuck| stuck)
+}
+
+/// Rewrite to normal form in at most `fuel` steps, or `Err` with the term
+/// reached when the fuel ran out
+fn rewrite_within(
+    term: &Term,
+    recorder: &mut impl Recorder,
+    interval_only: bool,
+    fuel: usize,
+) -> std::result::Result<Term, Term> {
+    let mut current = term.clone();
+    for _ in 0..fuel {
+        match step(&current, interval_only) {
+            Some((rule, next)) => {
+                recorder.record(rule, &current, &next);
+                current = next;
+            }
+            None => return Ok(current),
+        }
+    }
+    match step(&current, interval_only) {
+        Some(_) => Err(current),
+        None => Ok(current),
+    }
+}
+
+/// One leftmost-outermost rewrite
+fn step(term: &Term, interval_only: bool) -> Option<(Rule, Term)> {
+    if let Some((rule, next)) = redex(term) {
+        if !interval_only || rule.is_interval_law() {
+            return Some((rule, next));
+        }
+    }
+
+    let go = |t: &Term| step(t, interval_only);
+    let both = |a: &Term, b: &Term, build: fn(Box<Term>, Box<Term>) -> Term| match go(a) {
+        Some((rule, a)) => Some((rule, build(Box::new(a), Box::new(b.clone())))),
+        None => go(b).map(|(rule, b)| (rule, build(Box::new(a.clone()), Box::new(b)))),
+    };
+    match term {
+        Term::Lambda { param, param_type, body } => go(body).map(|(rule, body)| {
+            (rule, Term::Lambda { param: param.clone(), param_type: param_type.clone(), body: Box::new(body) })
+        }),
+        Term::PathLambda { param, body } => go(body).map(|(rule, body)| {
+            (rule, Term::PathLambda { param: param.clone(), body: Box::new(body) })
+        }),
+        Term::App { func, arg } => both(func, arg, |func, arg| Term::App { func, arg }),
+        Term::Pair { first, second } => both(first, second, |first, second| Term::Pair { first, second }),
+        Term::PathApp { path, point } => both(path, point, |path, point| Term::PathApp { path, point }),
+        Term::IMeet(a, b) => both(a, b, Term::IMeet),
+        Term::IJoin(a, b) => both(a, b, Term::IJoin),
+        Term::Fst(t) => go(t).map(|(rule, t)| (rule, Term::Fst(Box::new(t)))),
+        Term::Snd(t) => go(t).map(|(rule, t)| (rule, Term::Snd(Box::new(t)))),
+        Term::INeg(t) => go(t).map(|(rule, t)| (rule, Term::INeg(Box::new(t)))),
+        Term::NatSucc(t) => go(t).map(|(rule, t)| (rule, Term::NatSucc(Box::new(t)))),
+        Term::Inl(t) => go(t).map(|(rule, t)| (rule, Term::Inl(Box::new(t)))),
+        Term::Inr(t) => go(t).map(|(rule, t)| (rule, Term::Inr(Box::new(t)))),
+        Term::SmoothIn(t) => go(t).map(|(rule, t)| (rule, Term::SmoothIn(Box::new(t)))),
+        Term::SmoothOut(t) => go(t).map(|(rule, t)| (rule, Term::SmoothOut(Box::new(t)))),
+        Term::Proj(t, field) => go(t).map(|(rule, t)| (rule, Term::Proj(Box::new(t), field.clone()))),
+        Term::RecordLit(fields) => fields.iter().enumerate().find_map(|(i, (_, value))| {
+            go(value).map(|(rule, value)| {
+                let mut fields = fields.clone();
+                fields[i].1 = value;
+                (rule, Term::RecordLit(fields))
+            })
+        }),
+        Term::NatRec { motive, base, step, scrutinee } => {
+            let rebuild = |base: &Term, step: &Term, scrutinee: &Term| Term::NatRec {
+                motive: motive.clone(),
+                base: Box::new(base.clone()),
+                step: Box::new(step.clone()),
+                scrutinee: Box::new(scrutinee.clone()),
+            };
+            if let Some((rule, base)) = go(base) {
+                Some((rule, rebuild(&base, step, scrutinee)))
+            } else if let Some((rule, step)) = go(step) {
+                Some((rule, rebuild(base, &step, scrutinee)))
+            } else {
+                go(scrutinee).map(|(rule, scrutinee)| (rule, rebuild(base, step, &scrutinee)))
+            }
+        }
+        Term::BoolElim { motive, on_true, on_false, scrutinee } => {
+            let rebuild = |on_true: &Term, on_false: &Term, scrutinee: &Term| Term::BoolElim {
+                motive: motive.clone(),
+                on_true: Box::new(on_true.clone()),
+                on_false: Box::new(on_false.clone()),
+                scrutinee: Box::new(scrutinee.clone()),
+            };
+            if let Some((rule, scrutinee)) = go(scrutinee) {
+                Some((rule, rebuild(on_true, on_false, &scrutinee)))
+            } else if let Some((rule, on_true)) = go(on_true) {
+                Some((rule, rebuild(&on_true, on_false, scrutinee)))
+            } else {
+                go(on_false).map(|(rule, on_false)| (rule, rebuild(on_true, &on_false, scrutinee)))
+            }
+        }
+        Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+            let rebuild = |base_case: &Term, loop_case: &Term, scrutinee: &Term| Term::CircleElim {
+                motive: motive.clone(),
+                base_case: Box::new(base_case.clone()),
+                loop_case: Box::new(loop_case.clone()),
+                scrutinee: Box::new(scrutinee.clone()),
+            };
+            if let Some((rule, scrutinee)) = go(scrutinee) {
+                Some((rule, rebuild(base_case, loop_case, &scrutinee)))
+            } else if let Some((rule, base_case)) = go(base_case) {
+                Some((rule, rebuild(&base_case, loop_case, scrutinee)))
+            } else {
+                go(loop_case).map(|(rule, loop_case)| (rule, rebuild(base_case, &loop_case, scrutinee)))
+            }
+        }
+        Term::Case { motive, scrutinee, left_branch, right_branch } => {
+            let rebuild = |scrutinee: &Term, left_branch: &Term, right_branch: &Term| Term::Case {
+                motive: motive.clone(),
+                scrutinee: Box::new(scrutinee.clone()),
+                left_branch: Box::new(left_branch.clone()),
+                right_branch: Box::new(right_branch.clone()),
+            };
+            if let Some((rule, scrutinee)) = go(scrutinee) {
+                Some((rule, rebuild(&scrutinee, left_branch, right_branch)))
+            } else if let Some((rule, left_branch)) = go(left_branch) {
+                Some((rule, rebuild(scrutinee, &left_branch, right_branch)))
+            } else {
+                go(right_branch).map(|(rule, right_branch)| (rule, rebuild(scrutinee, left_branch, &right_branch)))
+            }
+        }
+        Term::EmptyElim { motive, scrutinee } => go(scrutinee)
+            .map(|(rule, scrutinee)| (rule, Term::EmptyElim { motive: motive.clone(), scrutinee: Box::new(scrutinee) })),
+        Term::Let { name, ty, value, body } => {
+            let rebuild = |value: &Term, body: &Term| Term::Let {
+                name: name.clone(),
+                ty: ty.clone(),
+                value: Box::new(value.clone()),
+                body: Box::new(body.clone()),
+            };
+            match go(value) {
+                Some((rule, value)) => Some((rule, rebuild(&value, body))),
+                None => go(body).map(|(rule, body)| (rule, rebuild(value, &body))),
+            }
+        }
+        Term::Ann { term, ty } => go(term).map(|(rule, term)| (rule, Term::Ann { term: Box::new(term), ty: ty.clone() })),
+        Term::Var(_)
+        | Term::IZero
+        | Term::IOne
+        | Term::RealLit(_)
+        | Term::SmoothFunc { .. }
+        | Term::Hole
+        | Term::NatZero
+        | Term::Star
+        | Term::True
+        | Term::False
+        | Term::Base
+        | Term::Loop => None,
+    }
+}
+
+/// The rule whose left-hand side matches `term` at the root
+fn redex(term: &Term) -> Option<(Rule, Term)> {
+    let neg = |t: &Term| Term::INeg(Box::new(t.clone()));
+    let meet = |a: Term, b: Term| Term::IMeet(Box::new(a), Box::new(b));
+    let join = |a: Term, b: Term| Term::IJoin(Box::new(a), Box::new(b));
+    match term {
+        Term::App { func, arg } => match func.as_ref() {
+            Term::Lambda { param, body, .. } => Some((Rule::Beta, body.subst(param, arg))),
+            _ => None,
+        },
+        Term::PathApp { path, point } => match (path.as_ref(), point.as_ref()) {
+            (Term::PathLambda { param, body }, _) => Some((Rule::PathBeta, body.subst(param, point))),
+            (Term::Loop, Term::IZero | Term::IOne) => Some((Rule::Boundary, Term::Base)),
+            _ => None,
+        },
+        Term::Fst(t) => match t.as_ref() {
+            Term::Pair { first, .. } => Some((Rule::Projection, (**first).clone())),
+            _ => None,
+        },
+        Term::Proj(t, field) => match t.as_ref() {
+            Term::RecordLit(fields) => {
+                fields.iter().find(|(name, _)| name == field).map(|(_, value)| (Rule::Projection, value.clone()))
+            }
+            _ => None,
+        },
+        Term::Snd(t) => match t.as_ref() {
+            Term::Pair { second, .. } => Some((Rule::Projection, (**second).clone())),
+            _ => None,
+        },
+        Term::SmoothOut(t) => match t.as_ref() {
+            Term::SmoothIn(inner) => Some((Rule::Modality, (**inner).clone())),
+            _ => None,
+        },
+        Term::NatRec { motive, base, step, scrutinee } => match scrutinee.as_ref() {
+            Term::NatZero => Some((Rule::Recursion, (**base).clone())),
+            Term::NatSucc(n) => {
+                let previous = Term::NatRec { motive: motive.clone(), base: base.clone(), step: step.clone(), scrutinee: n.clone() };
+                let applied = Term::App { func: Box::new(Term::App { func: step.clone(), arg: n.clone() }), arg: Box::new(previous) };
+                Some((Rule::Recursion, applied))
+            }
+            _ => None,
+        },
+        Term::Let { name, value, body, .. } => Some((Rule::Zeta, body.subst(name, value))),
+        Term::Ann { term, .. } => Some((Rule::Erasure, (**term).clone())),
+        Term::BoolElim { on_true, on_false, scrutinee, .. } => match scrutinee.as_ref() {
+            Term::True => Some((Rule::Case, (**on_true).clone())),
+            Term::False => Some((Rule::Case, (**on_false).clone())),
+            _ => None,
+        },
+        Term::CircleElim { base_case, loop_case, scrutinee, .. } => match scrutinee.as_ref() {
+            Term::Base => Some((Rule::Case, (**base_case).clone())),
+            Term::PathApp { path, point } if **path == Term::Loop => {
+                Some((Rule::Case, Term::PathApp { path: loop_case.clone(), point: point.clone() }))
+            }
+            _ => None,
+        },
+        Term::Case { scrutinee, left_branch, right_branch, .. } => match scrutinee.as_ref() {
+            Term::Inl(value) => Some((Rule::Case, Term::App { func: left_branch.clone(), arg: value.clone() })),
+            Term::Inr(value) => Some((Rule::Case, Term::App { func: right_branch.clone(), arg: value.clone() })),
+            _ => None,
+        },
+        Term::INeg(t) => match t.as_ref() {
+            Term::INeg(inner) => Some((Rule::DoubleNegation, (**inner).clone())),
+            Term::IZero => Some((Rule::NegateEndpoint, Term::IOne)),
+            Term::IOne => Some((Rule::NegateEndpoint, Term::IZero)),
+            Term::IMeet(a, b) => Some((Rule::DeMorgan, join(neg(a), neg(b)))),
+            Term::IJoin(a, b) => Some((Rule::DeMorgan, meet(neg(a), neg(b)))),
+            _ => None,
+        },
+        Term::IMeet(a, b) => {
+            let (a, b) = (a.as_ref(), b.as_ref());
+            if *b == Term::IOne {
+                Some((Rule::Unit, a.clone()))
+            } else if *a == Term::IOne {
+                Some((Rule::Unit, b.clone()))
+            } else if *a == Term::IZero || *b == Term::IZero {
+                Some((Rule::Annihilation, Term::IZero))
+            } else if let Some(kept) = idempotent(a, b, false) {
+                Some((Rule::Idempotence, kept.clone()))
+            } else if let Some(kept) = absorbing(a, b, false) {
+                Some((Rule::Absorption, kept.clone()))
+            } else if let Term::IJoin(x, y) = b {
+                Some((Rule::Distributivity, join(meet(a.clone(), (**x).clone()), meet(a.clone(), (**y).clone()))))
+            } else if let Term::IJoin(x, y) = a {
+                Some((Rule::Distributivity, join(meet((**x).clone(), b.clone()), meet((**y).clone(), b.clone()))))
+            } else {
+                None
+            }
+        }
+        Term::IJoin(a, b) => {
+            let (a, b) = (a.as_ref(), b.as_ref());
+            if *b == Term::IZero {
+                Some((Rule::Unit, a.clone()))
+            } else if *a == Term::IZero {
+                Some((Rule::Unit, b.clone()))
+            } else if *a == Term::IOne || *b == Term::IOne {
+                Some((Rule::Annihilation, Term::IOne))
+            } else if let Some(kept) = idempotent(a, b, true) {
+                Some((Rule::Idempotence, kept.clone()))
+            } else {
+                absorbing(a, b, true).map(|kept| (Rule::Absorption, kept.clone()))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Operands of a chain of joins (or meets), looking through nesting
+fn operands(term: &Term, of_join: bool) -> Vec<&Term> {
+    match term {
+        Term::IJoin(a, b) if of_join => [operands(a, true), operands(b, true)].concat(),
+        Term::IMeet(a, b) if !of_join => [operands(a, false), operands(b, false)].concat(),
+        _ => vec![term],
+    }
+}
+
+/// `x ∨ y ∨ x ⟶ x ∨ y`: the side to keep when one repeats the other
+fn idempotent<'a>(a: &'a Term, b: &'a Term, of_join: bool) -> Option<&'a Term> {
+    if operands(a, of_join).contains(&b) {
+        Some(a)
+    } else if operands(b, of_join).contains(&a) {
+        Some(b)
+    } else {
+        None
+    }
+}
+
+/// `(x ∨ y) ∨ (x ∧ z) ⟶ x ∨ y`: the side to keep when the other is absorbed
+fn absorbing<'a>(a: &'a Term, b: &'a Term, of_join: bool) -> Option<&'a Term> {
+    let absorbs = |keep: &Term, dropped: &Term| {
+        let kept = operands(keep, of_join);
+        operands(dropped, !of_join).len() > 1 && operands(dropped, !of_join).iter().any(|t| kept.contains(t))
+    };
+    if absorbs(a, b) {
+        Some(a)
+    } else if absorbs(b, a) {
+        Some(b)
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Parsing the printed syntax back
+//
+//   term   := 'λ' binder '.' term | '⟨' i '⟩' term
+//           | 'let' x ':' ty '=' term 'in' term | join
+//   binder := x | '(' x ':' ty ')'
+//   join   := meet ('∨' meet)*
+//   meet   := at ('∧' at)*
+//   at     := prefix ('@' prefix)*
+//   prefix := ('¬' | 'π₁' | 'π₂' | 'succ') prefix | app
+//   app    := atom atom*
+//   atom   := x | number | '?' | 'zero' | 'natrec' '[' ty ']' atom atom atom
+//           | '(' term ')' | '(' term ',' term ')' | '(' term ':' ty ')'
+//
+// `0` and `1` read as interval endpoints; ℕ's zero is `zero`. `fix` and
+// `unsafe_fix` are reserved for `termination`. A λ-binder without a type
+// defaults to ℝ. `?` is a hole. `ty` is the type syntax of `skeleton`. The tokens also cover `→` and
+// `:` for the type syntax in `skeleton`.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    Ident(String),
+    Num(f64),
+    Sym(&'static str),
+}
+
+pub(crate) fn error(position: usize, message: impl Into<String>) -> ParseError {
+    ParseError { position, message: message.into() }
+}
+
+/// Tokens with their starting positions, and where each one ends
+type Tokens = (Vec<(usize, Token)>, Vec<usize>);
+
+fn tokenize(source: &str) -> Result<Tokens, ParseError> {
+    const SYMBOLS: [(&str, &str); 28] = [
+        ("λ", "λ"), ("\\", "λ"), ("⟨", "⟨"), ("<", "⟨"), ("⟩", "⟩"), (">", "⟩"),
+        ("π₁", "π₁"), ("π₂", "π₂"), ("∧", "∧"), ("/\\", "∧"), ("∨", "∨"), ("\\/", "∨"),
+        ("¬", "¬"), ("~", "¬"), ("@", "@"), (".", "."), ("?", "?"), ("→", "→"), ("->", "→"), (":", ":"),
+        ("∞", "∞"), ("=", "="), (":=", ":="), ("{", "{"), ("}", "}"), ("+", "+"),
+        ("[", "["), ("]", "]"),
+    ];
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut ends = Vec::new();
+    let mut i = 0;
+    'outer: while i < chars.len() {
+        // The token pushed last time round, if any, ends here
+        ends.resize(tokens.len(), i);
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if matches!(c, '(' | ')' | ',') {
+            tokens.push((i, Token::Sym(match c { '(' => "(", ')' => ")", _ => "," })));
+            i += 1;
+            continue;
+        }
+        // Longest match first: `\/` before `\`
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        for (text, sym) in SYMBOLS.iter().filter(|(t, _)| t.chars().count() == 2).chain(SYMBOLS.iter()) {
+            if rest.starts_with(text) {
+                tokens.push((i, Token::Sym(sym)));
+                i += text.chars().count();
+                continue 'outer;
+            }
+        }
+        let start = i;
+        if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse().map_err(|_| error(start, format!("malformed number `{}`", text)))?;
+            tokens.push((start, Token::Num(n)));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '\'') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            tokens.push((start, match name.as_str() {
+                "fst" => Token::Sym("π₁"),
+                "snd" => Token::Sym("π₂"),
+                "let" => Token::Sym("let"),
+                "in" => Token::Sym("in"),
+                "zero" => Token::Sym("zero"),
+                "succ" => Token::Sym("succ"),
+                "natrec" => Token::Sym("natrec"),
+                "fix" => Token::Sym("fix"),
+                "unsafe_fix" => Token::Sym("unsafe_fix"),
+                _ => Token::Ident(name),
+            }));
+        } else {
+            return Err(error(i, format!("unexpected `{}`", c)));
+        }
+    }
+    ends.resize(tokens.len(), i);
+    Ok((tokens, ends))
+}
+
+pub fn parse_term(source: &str) -> Result<Term, ParseError> {
+    parse_term_spanned(source).map(|(term, _)| term)
+}
+
+/// Parse a term, remembering where each of its nodes came from
+pub(crate) fn parse_term_spanned(source: &str) -> Result<(Term, Spans), ParseError> {
+    let mut parser = Parser::new(source)?;
+    let parsed = parser.term_spanned()?;
+    if let Some((position, _)) = parser.tokens.get(parser.pos) {
+        return Err(error(*position, "unexpected input after the term"));
+    }
+    Ok(parsed)
+}
+
+/// Source spans of the nodes of a parsed term, for locating errors
+///
+/// Nodes below the root are keyed by address, which their boxes keep
+/// fixed, so the spans only describe the term they were parsed with and
+/// only while it is unchanged.
+#[derive(Debug, Default)]
+pub(crate) struct Spans {
+    root: Option<Span>,
+    nodes: HashMap<usize, Span>,
+}
+
+impl Spans {
+    /// Pair the spans the parser recorded, in the order it finished the
+    /// nodes, with the nodes of `term`; a term the parser did not build
+    /// node for node gets no spans below the root
+    fn of_parse(term: &Term, recorded: Vec<Span>) -> Spans {
+        let root = recorded.last().copied();
+        let mut nodes = HashMap::new();
+        let mut recorded = recorded.into_iter();
+        if Spans::walk(term, &mut recorded, &mut nodes).is_none() || recorded.next().is_some() {
+            nodes.clear();
+        }
+        Spans { root, nodes }
+    }
+
+    /// Children come before their parent and in source order, including
+    /// the terms inside a binder's type
+    fn walk(term: &Term, recorded: &mut impl Iterator<Item = Span>, nodes: &mut HashMap<usize, Span>) -> Option<Span> {
+        let mut children = Vec::new();
+        match term {
+            Term::Var(_) | Term::IZero | Term::IOne | Term::RealLit(_) | Term::Hole | Term::NatZero => {}
+            Term::Lambda { param_type, body, .. } => {
+                type_terms(param_type, &mut children);
+                children.push(&**body);
+            }
+            Term::Let { ty, value, body, .. } => {
+                type_terms(ty, &mut children);
+                children.extend([&**value, &**body]);
+            }
+            Term::Ann { term, ty } => {
+                children.push(&**term);
+                type_terms(ty, &mut children);
+            }
+            Term::PathLambda { body: t, .. }
+            | Term::INeg(t)
+            | Term::Fst(t)
+            | Term::Snd(t)
+            | Term::Proj(t, _)
+            | Term::NatSucc(t) => children.push(&**t),
+            Term::NatRec { motive, base, step, scrutinee } => {
+                type_terms(motive, &mut children);
+                children.extend([&**base, &**step, &**scrutinee]);
+            }
+            Term::RecordLit(fields) => children.extend(fields.iter().map(|(_, value)| value)),
+            Term::App { func: a, arg: b }
+            | Term::Pair { first: a, second: b }
+            | Term::PathApp { path: a, point: b }
+            | Term::IMeet(a, b)
+            | Term::IJoin(a, b) => children.extend([&**a, &**b]),
+            _ => return None,
+        }
+        for child in children {
+            let span = Spans::walk(child, recorded, nodes)?;
+            nodes.insert(child as *const Term as usize, span);
+        }
+        recorded.next()
+    }
+
+    pub(crate) fn root(&self) -> Option<Span> {
+        self.root
+    }
+
+    /// The span of `term`, a node below the root of the parsed term
+    pub(crate) fn get(&self, term: &Term) -> Option<Span> {
+        self.nodes.get(&(term as *const Term as usize)).copied()
+    }
+}
+
+/// Terms inside a type, in the order the type parser reads them
+fn type_terms<'a>(ty: &'a Type, out: &mut Vec<&'a Term>) {
+    match ty {
+        Type::Function { domain, codomain, .. } | Type::Pi { domain, codomain, .. } | Type::Sigma { domain, codomain, .. } => {
+            type_terms(domain, out);
+            type_terms(codomain, out);
+        }
+        Type::Sum(left, right) => {
+            type_terms(left, out);
+            type_terms(right, out);
+        }
+        Type::Path { space, start, end } => {
+            type_terms(space, out);
+            out.extend([&**start, &**end]);
+        }
+        Type::PathP { family, start, end, .. } => {
+            type_terms(family, out);
+            out.extend([&**start, &**end]);
+        }
+        Type::Smooth(inner) => type_terms(inner, out),
+        Type::Record(fields) => fields.iter().for_each(|(_, ty)| type_terms(ty, out)),
+        Type::Universe(_)
+        | Type::Interval
+        | Type::Real
+        | Type::Nat
+        | Type::Unit
+        | Type::Empty
+        | Type::Bool
+        | Type::Circle => {}
+    }
+}
+
+pub(crate) struct Parser {
+    pub(crate) tokens: Vec<(usize, Token)>,
+    pub(crate) pos: usize,
+    pub(crate) end: usize,
+    ends: Vec<usize>,
+    spans: Vec<Span>,
+}
+
+impl Parser {
+    pub(crate) fn new(source: &str) -> Result<Self, ParseError> {
+        let (tokens, ends) = tokenize(source)?;
+        Ok(Parser { tokens, pos: 0, end: source.chars().count(), ends, spans: Vec::new() })
+    }
+
+    /// Record the span of a node just built, from `start` to the end of
+    /// the last token read
+    fn node(&mut self, start: usize, term: Term) -> Term {
+        let end = self.pos.checked_sub(1).map_or(start, |last| self.ends[last]);
+        self.spans.push(Span { start, end });
+        term
+    }
+
+    /// A term, with the spans of its nodes
+    pub(crate) fn term_spanned(&mut self) -> Result<(Term, Spans), ParseError> {
+        let mark = self.spans.len();
+        let term = self.term()?;
+        let spans = Spans::of_parse(&term, self.spans.split_off(mark));
+        Ok((term, spans))
+    }
+
+    pub(crate) fn peek_sym(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some((_, Token::Sym(s))) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(p, _)| *p)
+    }
+
+    pub(crate) fn eat(&mut self, sym: &str) -> bool {
+        let found = self.peek_sym() == Some(sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    pub(crate) fn expect(&mut self, sym: &str) -> Result<(), ParseError> {
+        if self.eat(sym) { Ok(()) } else { Err(error(self.position(), format!("expected `{}`", sym))) }
+    }
+
+    pub(crate) fn ident(&mut self) -> Result<String, ParseError> {
+        match self.tokens.get(self.pos) {
+            Some((_, Token::Ident(name))) => {
+                self.pos += 1;
+                Ok(name.clone())
+            }
+            _ => Err(error(self.position(), "expected a variable")),
+        }
+    }
+
+    pub(crate) fn term(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        if self.eat("λ") {
+            let (param, param_type) = if self.eat("(") {
+                let param = self.ident()?;
+                self.expect(":")?;
+                let ty = self.ty()?;
+                self.expect(")")?;
+                (param, ty)
+            } else {
+                (self.ident()?, Type::Real)
+            };
+            self.expect(".")?;
+            let body = self.term()?;
+            return Ok(self.node(start, Term::Lambda { param, param_type: Box::new(param_type), body: Box::new(body) }));
+        }
+        if self.eat("let") {
+            let name = self.ident()?;
+            self.expect(":")?;
+            let ty = self.ty()?;
+            self.expect("=")?;
+            let value = self.term()?;
+            self.expect("in")?;
+            let body = self.term()?;
+            return Ok(self.node(start, Term::Let { name, ty: Box::new(ty), value: Box::new(value), body: Box::new(body) }));
+        }
+        if self.eat("⟨") {
+            let param = self.ident()?;
+            self.expect("⟩")?;
+            let body = self.term()?;
+            return Ok(self.node(start, Term::PathLambda { param, body: Box::new(body) }));
+        }
+        self.join()
+    }
+
+    fn join(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        let mut lhs = self.meet()?;
+        while self.eat("∨") {
+            let rhs = self.meet()?;
+            lhs = self.node(start, Term::IJoin(Box::new(lhs), Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn meet(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        let mut lhs = self.at()?;
+        while self.eat("∧") {
+            let rhs = self.at()?;
+            lhs = self.node(start, Term::IMeet(Box::new(lhs), Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn at(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        let mut lhs = self.prefix()?;
+        while self.eat("@") {
+            let point = self.prefix()?;
+            lhs = self.node(start, Term::PathApp { path: Box::new(lhs), point: Box::new(point) });
+        }
+        Ok(lhs)
+    }
+
+    fn prefix(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        let build = Term::INeg as fn(Box<Term>) -> Term;
+        for (sym, build) in [("¬", build), ("π₁", Term::Fst), ("π₂", Term::Snd), ("succ", Term::NatSucc)] {
+            if self.eat(sym) {
+                let operand = self.prefix()?;
+                return Ok(self.node(start, build(Box::new(operand))));
+            }
+        }
+        self.app()
+    }
+
+    fn app(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        let mut lhs = self.atom()?;
+        let starts_atom = |token: Option<&(usize, Token)>| match token {
+            Some((_, Token::Ident(_) | Token::Num(_))) => true,
+            Some((_, Token::Sym(sym))) => matches!(*sym, "(" | "?" | "{" | "zero" | "natrec"),
+            None => false,
+        };
+        while starts_atom(self.tokens.get(self.pos)) {
+            let arg = self.atom()?;
+            lhs = self.node(start, Term::App { func: Box::new(lhs), arg: Box::new(arg) });
+        }
+        Ok(lhs)
+    }
+
+    /// A primary term followed by any projections `.x`, written without
+    /// spaces so that they are not confused with a binder's `.`
+    pub(crate) fn atom(&mut self) -> Result<Term, ParseError> {
+        let start = self.position();
+        let mut term = self.primary()?;
+        while let (Some((dot, Token::Sym("."))), Some((at, Token::Ident(field)))) =
+            (self.tokens.get(self.pos), self.tokens.get(self.pos + 1))
+        {
+            if *dot != self.ends[self.pos - 1] || *at != dot + 1 {
+                break;
+            }
+            let field = field.clone();
+            self.pos += 2;
+            term = self.node(start, Term::Proj(Box::new(term), field));
+        }
+        Ok(term)
+    }
+
+    fn primary(&mut self) -> Result<Term, ParseError> {
+        let position = self.position();
+        match self.tokens.get(self.pos).cloned() {
+            Some((_, Token::Ident(name))) => {
+                self.pos += 1;
+                Ok(self.node(position, Term::Var(name)))
+            }
+            Some((_, Token::Num(n))) => {
+                self.pos += 1;
+                let term = if n == 0.0 { Term::IZero } else if n == 1.0 { Term::IOne } else { Term::RealLit(n) };
+                Ok(self.node(position, term))
+            }
+            Some((_, Token::Sym("?"))) => {
+                self.pos += 1;
+                Ok(self.node(position, Term::Hole))
+            }
+            Some((_, Token::Sym("zero"))) => {
+                self.pos += 1;
+                Ok(self.node(position, Term::NatZero))
+            }
+            // natrec[M] base step n
+            Some((_, Token::Sym("natrec"))) => {
+                self.pos += 1;
+                self.expect("[")?;
+                let motive = self.ty()?;
+                self.expect("]")?;
+                let base = self.atom()?;
+                let step = self.atom()?;
+                let scrutinee = self.atom()?;
+                let rec = Term::NatRec {
+                    motive: Box::new(motive),
+                    base: Box::new(base),
+                    step: Box::new(step),
+                    scrutinee: Box::new(scrutinee),
+                };
+                Ok(self.node(position, rec))
+            }
+            Some((_, Token::Sym("("))) => {
+                self.pos += 1;
+                let first = self.term()?;
+                if self.eat(",") {
+                    let second = self.term()?;
+                    self.expect(")")?;
+                    return Ok(self.node(position, Term::Pair { first: Box::new(first), second: Box::new(second) }));
+                }
+                if self.eat(":") {
+                    let ty = self.ty()?;
+                    self.expect(")")?;
+                    return Ok(self.node(position, Term::Ann { term: Box::new(first), ty: Box::new(ty) }));
+                }
+                self.expect(")")?;
+                Ok(first)
+            }
+            Some((_, Token::Sym("{"))) => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if !self.eat("}") {
+                    loop {
+                        let name = self.ident()?;
+                        self.expect(":=")?;
+                        fields.push((name, self.term()?));
+                        if !self.eat(",") {
+                            break;
+                        }
+                    }
+                    self.expect("}")?;
+                }
+                Ok(self.node(position, Term::RecordLit(fields)))
+            }
+            Some(_) => Err(error(position, "expected a term")),
+            None => Err(error(position, "unexpected end of input")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_de_morgan_derivation() {
+        let derivation = derive_interval("¬(i ∧ j)").unwrap();
+        let tags: Vec<&str> = derivation.steps.iter().map(|s| s.rule.tag()).collect();
+        assert_eq!(tags.iter().filter(|t| **t == "De Morgan").count(), 1);
+        assert_eq!(derivation.result(), "¬i ∨ ¬j");
+
+        // The result is a normal form: nothing left to rewrite
+        let normal = parse_term(derivation.result()).unwrap();
+        let mut again = Vec::new();
+        assert_eq!(normalize_interval(&normal, &mut again), normal);
+        assert!(again.is_empty());
+
+        let chain = derive_interval("¬(i ∧ ¬j) ∨ (¬i ∧ 1)").unwrap();
+        assert_eq!(chain.result(), "¬i ∨ j");
+        assert_eq!(chain.steps.first().map(|s| s.before.as_str()), Some(chain.start.as_str()));
+    }
+
+    #[test]
+    fn test_simp_computes_and_normalizes() {
+        let derivation = derive_simp("π₁ ((λx. (x, ¬x)) i) ∧ 1").unwrap();
+        let rules: Vec<Rule> = derivation.steps.iter().map(|s| s.rule).collect();
+        assert_eq!(rules, vec![Rule::Unit, Rule::Beta, Rule::Projection]);
+        assert_eq!(derivation.result(), "i");
+
+        assert_eq!(derive_simp("(⟨t⟩ t ∨ ¬t) @ 0").unwrap().result(), "1");
+        // The interval normalizer leaves computation alone
+        assert_eq!(derive_interval("π₁ (i, j) ∧ 1").unwrap().result(), "π₁ (i, j)");
+    }
+
+    #[test]
+    fn test_normalize_church_numerals() {
+        let church = |n: usize| parse_term(&format!("λf. λx. {}x{}", "f (".repeat(n), ")".repeat(n))).unwrap();
+        let add = "(λm. λn. λf. λx. m f (n f x))";
+        let mul = "(λm. λn. λf. m (n f))";
+        let apply = |op: &str, m: usize, n: usize| parse_term(&format!("{} ({}) ({})", op, church(m), church(n))).unwrap();
+
+        assert!(apply(add, 2, 3).normalize().unwrap().alpha_eq(&church(5)));
+        assert!(apply(mul, 2, 3).normalize().unwrap().alpha_eq(&church(6)));
+        assert!(apply(mul, 0, 4).normalize().unwrap().alpha_eq(&church(0)));
+        // Already normal terms come back unchanged
+        assert_eq!(church(3).normalize().unwrap(), church(3));
+    }
+
+    #[test]
+    fn test_normalize_projections_and_paths() {
+        let normal = |source: &str| parse_term(source).unwrap().normalize().unwrap().to_string();
+        assert_eq!(normal("π₁ π₂ (a, (b, c))"), "b");
+        assert_eq!(normal("π₂ π₁ ((λp. (π₂ p, π₁ p)) (x, y), z)"), "x");
+        assert_eq!(normal("(⟨i⟩ f i) @ 1"), "f 1");
+        assert_eq!(normal("(⟨i⟩ i ∧ j) @ 0"), "0");
+        assert_eq!(normal("¬1 ∨ (0 ∧ k)"), "0");
+        // Stuck eliminations stay as they are
+        assert_eq!(normal("π₁ p @ ¬¬i"), "π₁ p @ i");
+    }
+
+    #[test]
+    fn test_normalize_record_projections() {
+        let normal = |source: &str| parse_term(source).unwrap().normalize().unwrap().to_string();
+        assert_eq!(normal("{ x := a, y := (b, c) }.y"), "(b, c)");
+        assert_eq!(normal("π₂ { p := (a, b) }.p"), "b");
+        assert_eq!(normal("{ f := λx. x }.f { y := 2 }"), "{ y := 2 }");
+        assert_eq!(normal("(f r).x.y"), "(f r).x.y");
+        // `.` with a space is a binder's, not a projection
+        assert_eq!(normal("λr. r.x"), "λr. r.x");
+    }
+
+    #[test]
+    fn test_normalize_addition_by_recursion() {
+        let add = crate::check::tests::addition();
+        let sum = Term::App {
+            func: Box::new(Term::App { func: Box::new(add), arg: Box::new(Term::nat(2)) }),
+            arg: Box::new(Term::nat(2)),
+        };
+        let normal = sum.normalize().unwrap();
+        assert_eq!(normal, Term::nat(4));
+        assert_eq!(normal.to_string(), "4");
+
+        let mut steps = Vec::new();
+        simp(&sum, &mut steps);
+        assert_eq!(steps.iter().filter(|s| s.rule == Rule::Recursion).count(), 3);
+    }
+
+    #[test]
+    fn test_case_analysis_computes_on_both_booleans() {
+        let case = |scrutinee| Term::BoolElim {
+            motive: Box::new(Type::Nat),
+            on_true: Box::new(Term::nat(1)),
+            on_false: Box::new(Term::NatZero),
+            scrutinee: Box::new(scrutinee),
+        };
+        assert_eq!(case(Term::True).normalize().unwrap(), Term::nat(1));
+        assert_eq!(case(Term::False).normalize().unwrap(), Term::NatZero);
+        // The scrutinee computes first; a variable leaves the case stuck
+        let id = parse_term("λb. b").unwrap();
+        let applied = case(Term::App { func: Box::new(id), arg: Box::new(Term::False) });
+        let mut steps = Vec::new();
+        assert_eq!(simp(&applied, &mut steps), Term::NatZero);
+        assert_eq!(steps.iter().map(|s| s.rule).collect::<Vec<_>>(), vec![Rule::Beta, Rule::Case]);
+        let stuck = case(Term::Var("b".to_string()));
+        assert_eq!(stuck.normalize().unwrap(), stuck);
+        assert_eq!(stuck.to_string(), "if[ℕ] b 1 0");
+    }
+
+    #[test]
+    fn test_maps_out_of_the_circle_compute() {
+        // The constant map at `a`, sending the loop to the constant path
+        let constant = |scrutinee| Term::CircleElim {
+            motive: Box::new(Type::Real),
+            base_case: Box::new(parse_term("a").unwrap()),
+            loop_case: Box::new(parse_term("⟨j⟩ a").unwrap()),
+            scrutinee: Box::new(scrutinee),
+        };
+        let loop_at = |i: &str| Term::PathApp { path: Box::new(Term::Loop), point: Box::new(parse_term(i).unwrap()) };
+        assert_eq!(constant(Term::Base).normalize().unwrap().to_string(), "a");
+        assert_eq!(constant(loop_at("i")).normalize().unwrap().to_string(), "a");
+        assert_eq!(constant(loop_at("i")).to_string(), "S¹-elim[ℝ] (loop @ i) a (⟨j⟩ a)");
+
+        let mut steps = Vec::new();
+        assert_eq!(simp(&loop_at("¬0"), &mut steps), Term::Base);
+        assert_eq!(steps.iter().map(|s| s.rule).collect::<Vec<_>>(), vec![Rule::NegateEndpoint, Rule::Boundary]);
+        let stuck = constant(Term::Var("s".to_string()));
+        assert_eq!(stuck.normalize().unwrap(), stuck);
+    }
+
+    #[test]
+    fn test_swap_computes_on_both_injections() {
+        let swap = |value| Term::App { func: Box::new(crate::check::tests::swap()), arg: Box::new(value) };
+        let n = || Box::new(parse_term("n").unwrap());
+        assert_eq!(swap(Term::Inl(n())).normalize().unwrap(), Term::Inr(n()));
+        assert_eq!(swap(Term::Inr(n())).normalize().unwrap(), Term::Inl(n()));
+
+        let mut steps = Vec::new();
+        simp(&swap(Term::Inl(n())), &mut steps);
+        assert_eq!(steps.iter().map(|s| s.rule).collect::<Vec<_>>(), vec![Rule::Beta, Rule::Case, Rule::Beta]);
+        assert_eq!(
+            crate::check::tests::swap().to_string(),
+            "λs. case[ℕ + ℝ] s (λa. inr a) (λb. inl b)"
+        );
+        assert_eq!(crate::skeleton::parse_type("(ℝ → ℝ) + ℕ + Bool").unwrap().to_string(), "(ℝ → ℝ) + ℕ + Bool");
+    }
+
+    #[test]
+    fn test_smooth_out_undoes_smooth_in() {
+        let round_trip = Term::SmoothOut(Box::new(Term::SmoothIn(Box::new(parse_term("f x").unwrap()))));
+        assert_eq!(round_trip.to_string(), "smooth-out smooth-in f x");
+        let mut steps = Vec::new();
+        assert_eq!(simp(&round_trip, &mut steps), parse_term("f x").unwrap());
+        assert_eq!(steps.iter().map(|s| s.rule).collect::<Vec<_>>(), vec![Rule::Modality]);
+        let stuck = Term::SmoothOut(Box::new(parse_term("s").unwrap()));
+        assert_eq!(stuck.normalize().unwrap(), stuck);
+    }
+
+    #[test]
+    fn test_normalize_stops_when_out_of_fuel() {
+        let omega = parse_term("(λx. x x) (λx. x x)").unwrap();
+        assert!(matches!(omega.normalize(), Err(TypeError::OutOfFuel { fuel: MAX_STEPS })));
+        assert!(matches!(omega.normalize_with_fuel(7), Err(TypeError::OutOfFuel { fuel: 7 })));
+
+        // Exactly enough fuel is enough; one less is not
+        let twice = parse_term("(λx. x) ((λy. y) z)").unwrap();
+        assert_eq!(twice.normalize_with_fuel(2).unwrap(), Term::Var("z".to_string()));
+        assert!(twice.normalize_with_fuel(1).is_err());
+        // A discarded divergent argument is never evaluated
+        let discard = parse_term("(λx. y) ((λx. x x) (λx. x x))").unwrap();
+        assert_eq!(discard.normalize_with_fuel(1).unwrap(), Term::Var("y".to_string()));
+    }
+
+    #[test]
+    fn test_print_parse_round_trip() {
+        for source in ["λf. f (π₂ p) @ i ∨ j", "⟨i⟩ ¬(i ∧ j) ∨ k", "(a ∨ b) ∧ c", "f (g x) y", "¬π₁ (x, 2.5)", "{ x := 1, y := 2 }", "{}", "(f r).x"] {
+            let term = parse_term(source).unwrap();
+            assert_eq!(term.to_string(), source);
+            assert_eq!(parse_term(&term.to_string()).unwrap(), term);
+        }
+        assert_eq!(parse_term("i ∧").unwrap_err().position, 3);
+    }
+
+    #[test]
+    fn test_parse_nat_forms() {
+        let odd = parse_term("natrec[ℕ] (succ zero) (λ(k : ℕ). λ(r : ℕ). succ (succ r)) (succ succ zero)").unwrap();
+        assert_eq!(odd.normalize().unwrap(), Term::nat(5));
+        assert_eq!(parse_term("succ n").unwrap().to_string(), "succ n");
+        assert!(parse_term("natrec ℕ zero").is_err());
+    }
+}
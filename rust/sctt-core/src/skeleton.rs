@@ -0,0 +1,540 @@
+//! "Generate skeleton from type": the lab's code action for definitions
+//!
+//! Given the type of a definition whose body is missing or a hole,
+//! [`skeleton`] builds the introduction forms the type asks for and leaves
+//! a hole (`?`) wherever a real choice is needed:
+//!
+//! - `Π`/`→` become `λ` binders, named after their domain: `f`, `g`, `h`
+//!   for functions, `x`, `y`, `z` for ℝ, `i`, `j` for the interval;
+//! - `Σ` becomes a pair of the two skeletons;
+//! - `Path A a b` becomes a path lambda `⟨i⟩` over the skeleton of `A`,
+//!   and `PathP (⟨i⟩ A) a b` one over the skeleton of the line `A`.
+//!
+//! [`goals`] checks a term against a type with the [`check`](crate::check)
+//! rules and returns the open holes, each with its expected type and the
+//! binders in scope, so a generated skeleton can be shown to be
+//! well-formed. [`actions`] finds the definitions in
+//! lab source that the editor offers the action for.
+//!
+//! Types are written as in the lab:
+//!
+//! ```text
+//! ty   := ('Π' | 'Σ') '(' x+ ':' ty ')' '.' ty | sum ('→' ty)?
+//! sum  := base ('+' sum)?
+//! base := 'ℝ' | 'I' | 'Type' | 'Type_' (n | x | '(' lvl ')') | 'C∞' '(' ty ',' ty ')'
+//!       | 'Path' base atom atom | 'PathP' '(' '⟨' x '⟩' ty ')' atom atom
+//!       | '(' ty ')' | '{' (x ':' ty (',' x ':' ty)*)? '}'
+//! lvl  := (n | x | 'max' '(' lvl ',' lvl ')' | '(' lvl ')') ('+' n)*
+//! ```
+//!
+//! `→` and `+` associate to the right, and `+` binds tighter; `C∞(A, B)`
+//! is the smooth function type.
+//!
+//! where `atom` is a term atom of the `simp` syntax.
+
+use crate::simp::{self, ParseError, Parser, Token};
+use crate::{Level, Term, Type};
+
+/// A hole left in a term, and what it must be
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goal {
+    /// Binders in scope, outermost first
+    pub context: Vec<(String, Type)>,
+    pub ty: Type,
+}
+
+// ---------------------------------------------------------------------------
+// Parsing types
+
+impl Parser {
+    fn peek_ident(&self) -> Option<&str> {
+        match self.tokens.get(self.pos) {
+            Some((_, Token::Ident(name))) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn eat_ident(&mut self, names: &[&str]) -> bool {
+        let found = self.peek_ident().is_some_and(|name| names.contains(&name));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    pub(crate) fn ty(&mut self) -> Result<Type, ParseError> {
+        for (names, sigma) in [(&["Π", "Pi"][..], false), (&["Σ", "Sigma"][..], true)] {
+            if self.eat_ident(names) {
+                self.expect("(")?;
+                let mut params = vec![self.ident()?];
+                while self.peek_ident().is_some() {
+                    params.push(self.ident()?);
+                }
+                self.expect(":")?;
+                let domain = self.ty()?;
+                self.expect(")")?;
+                self.expect(".")?;
+                let codomain = self.ty()?;
+                return Ok(params.into_iter().rev().fold(codomain, |codomain, param| {
+                    let (domain, codomain) = (Box::new(domain.clone()), Box::new(codomain));
+                    if sigma { Type::Sigma { param, domain, codomain } } else { Type::Pi { param, domain, codomain } }
+                }));
+            }
+        }
+        let domain = self.sum()?;
+        if self.eat("→") {
+            let codomain = self.ty()?;
+            return Ok(Type::Function { domain: Box::new(domain), codomain: Box::new(codomain), is_smooth: false });
+        }
+        Ok(domain)
+    }
+
+    /// `A + B + C`, grouped to the right
+    fn sum(&mut self) -> Result<Type, ParseError> {
+        let left = self.base()?;
+        if self.eat("+") {
+            return Ok(Type::Sum(Box::new(left), Box::new(self.sum()?)));
+        }
+        Ok(left)
+    }
+
+    /// `n`, `l`, `max(l, l')` or `(l)`, plus any number of `+ n`
+    fn level(&mut self) -> Result<Level, ParseError> {
+        let position = self.position();
+        let mut level = if self.eat_ident(&["max"]) {
+            self.expect("(")?;
+            let a = self.level()?;
+            self.expect(",")?;
+            let b = self.level()?;
+            self.expect(")")?;
+            a.max(b)
+        } else if self.eat("(") {
+            let level = self.level()?;
+            self.expect(")")?;
+            level
+        } else if let Some(n) = self.natural() {
+            Level::Const(n)
+        } else if self.peek_ident().is_some() {
+            Level::Var(self.ident()?)
+        } else {
+            return Err(simp::error(position, "expected a level"));
+        };
+        while self.eat("+") {
+            let position = self.position();
+            let n = self.natural().ok_or_else(|| simp::error(position, "expected a number of levels"))?;
+            level = (0..n).fold(level, |level, _| level.succ());
+        }
+        Ok(level)
+    }
+
+    fn natural(&mut self) -> Option<u32> {
+        match self.tokens.get(self.pos) {
+            Some((_, Token::Num(n))) if n.fract() == 0.0 && *n >= 0.0 && *n <= u32::MAX as f64 => {
+                self.pos += 1;
+                Some(*n as u32)
+            }
+            _ => None,
+        }
+    }
+
+    fn base(&mut self) -> Result<Type, ParseError> {
+        let position = self.position();
+        if self.eat_ident(&["ℝ", "R", "Real"]) {
+            return Ok(Type::Real);
+        }
+        if self.eat_ident(&["I"]) {
+            return Ok(Type::Interval);
+        }
+        if self.eat_ident(&["ℕ", "N", "Nat"]) {
+            return Ok(Type::Nat);
+        }
+        if self.eat_ident(&["Unit"]) {
+            return Ok(Type::Unit);
+        }
+        if self.eat_ident(&["Empty"]) {
+            return Ok(Type::Empty);
+        }
+        if self.eat_ident(&["Bool"]) {
+            return Ok(Type::Bool);
+        }
+        if self.eat_ident(&["S¹", "S1", "Circle"]) {
+            return Ok(Type::Circle);
+        }
+        if self.eat_ident(&["Type"]) {
+            return Ok(Type::Universe(Level::ZERO));
+        }
+        if let Some(level) = self.peek_ident().and_then(|name| name.strip_prefix("Type_")).map(str::to_string) {
+            self.pos += 1;
+            let level = match level.parse() {
+                Ok(n) => Level::Const(n),
+                Err(_) if level.is_empty() => {
+                    self.expect("(")?;
+                    let level = self.level()?;
+                    self.expect(")")?;
+                    level
+                }
+                Err(_) => Level::Var(level),
+            };
+            return Ok(Type::Universe(level));
+        }
+        if self.peek_ident() == Some("C") && matches!(self.tokens.get(self.pos + 1), Some((_, Token::Sym("∞")))) {
+            self.pos += 2;
+            self.expect("(")?;
+            let domain = self.ty()?;
+            self.expect(",")?;
+            let codomain = self.ty()?;
+            self.expect(")")?;
+            return Ok(Type::Function { domain: Box::new(domain), codomain: Box::new(codomain), is_smooth: true });
+        }
+        if self.eat_ident(&["Path"]) {
+            let space = self.base()?;
+            let start = self.atom()?;
+            let end = self.atom()?;
+            return Ok(Type::Path { space: Box::new(space), start: Box::new(start), end: Box::new(end) });
+        }
+        if self.eat_ident(&["PathP"]) {
+            self.expect("(")?;
+            self.expect("⟨")?;
+            let param = self.ident()?;
+            self.expect("⟩")?;
+            let family = self.ty()?;
+            self.expect(")")?;
+            let start = self.atom()?;
+            let end = self.atom()?;
+            return Ok(Type::PathP { param, family: Box::new(family), start: Box::new(start), end: Box::new(end) });
+        }
+        if self.eat("(") {
+            let ty = self.ty()?;
+            self.expect(")")?;
+            return Ok(ty);
+        }
+        if self.eat("{") {
+            let mut fields = Vec::new();
+            if !self.eat("}") {
+                loop {
+                    let label = self.ident()?;
+                    self.expect(":")?;
+                    fields.push((label, self.ty()?));
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.expect("}")?;
+            }
+            return Ok(Type::Record(fields));
+        }
+        Err(simp::error(position, "expected a type"))
+    }
+}
+
+/// Parse a type such as `(ℝ → ℝ) → ℝ → ℝ` or `Σ (x : ℝ). Path ℝ x 1`
+pub fn parse_type(source: &str) -> Result<Type, ParseError> {
+    let mut parser = Parser::new(source)?;
+    let ty = parser.ty()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(simp::error(parser.position(), "unexpected input after the type"));
+    }
+    Ok(ty)
+}
+
+impl std::str::FromStr for Type {
+    type Err = crate::TypeError;
+
+    fn from_str(source: &str) -> Result<Type, crate::TypeError> {
+        Ok(parse_type(source)?)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Building skeletons
+
+/// Binder names to try for a variable of type `ty`, most readable first
+fn name_candidates(ty: &Type) -> &'static [&'static str] {
+    match ty {
+        Type::Function { .. } | Type::Pi { .. } => &["f", "g", "h", "k"],
+        Type::Real => &["x", "y", "z", "w"],
+        Type::Nat => &["n", "m", "k"],
+        Type::Bool => &["b", "c"],
+        Type::Circle => &["s", "t"],
+        Type::Unit => &["t"],
+        Type::Empty => &["e"],
+        Type::Interval => &["i", "j", "k", "l"],
+        Type::Path { .. } | Type::PathP { .. } => &["p", "q", "r"],
+        Type::Sigma { .. } => &["u", "v"],
+        Type::Sum(..) => &["s", "t"],
+        Type::Universe(_) => &["A", "B", "C"],
+        Type::Smooth(inner) => name_candidates(inner),
+        Type::Record(_) => &["r", "s"],
+    }
+}
+
+/// The first candidate not in scope, else the first with a number
+fn fresh(ty: &Type, scope: &[String]) -> String {
+    let candidates = name_candidates(ty);
+    let free = |name: &String| !scope.contains(name);
+    candidates
+        .iter()
+        .map(|c| c.to_string())
+        .find(free)
+        .or_else(|| (1..).map(|n| format!("{}{}", candidates[0], n)).find(free))
+        .unwrap_or_default()
+}
+
+/// The name for a `Π`/`Σ` binder: its own unless taken, and the codomain
+/// renamed to match
+fn bind(param: &str, domain: &Type, codomain: &Type, scope: &[String]) -> (String, Type) {
+    if param != "_" && !scope.iter().any(|s| s == param) {
+        return (param.to_string(), codomain.clone());
+    }
+    let name = fresh(domain, scope);
+    (name.clone(), codomain.subst_term(param, &Term::Var(name)))
+}
+
+fn build(ty: &Type, scope: &mut Vec<String>) -> Term {
+    let lambda = |name: String, domain: &Type, codomain: &Type, scope: &mut Vec<String>| {
+        scope.push(name.clone());
+        let body = build(codomain, scope);
+        scope.pop();
+        Term::Lambda { param: name, param_type: Box::new(domain.clone()), body: Box::new(body) }
+    };
+    match ty {
+        Type::Function { domain, codomain, .. } => lambda(fresh(domain, scope), domain, codomain, scope),
+        Type::Pi { param, domain, codomain } => {
+            let (name, codomain) = bind(param, domain, codomain, scope);
+            lambda(name, domain, &codomain, scope)
+        }
+        // The second component's type mentions the first, which is a hole
+        // until written, so it is built with the binder's name free
+        Type::Sigma { domain, codomain, .. } => {
+            Term::Pair { first: Box::new(build(domain, scope)), second: Box::new(build(codomain, scope)) }
+        }
+        Type::Path { space, .. } => {
+            let name = fresh(&Type::Interval, scope);
+            scope.push(name.clone());
+            let body = build(space, scope);
+            scope.pop();
+            Term::PathLambda { param: name, body: Box::new(body) }
+        }
+        Type::PathP { param, family, .. } => {
+            let name = fresh(&Type::Interval, scope);
+            let fibre = family.subst_term(param, &Term::Var(name.clone()));
+            scope.push(name.clone());
+            let body = build(&fibre, scope);
+            scope.pop();
+            Term::PathLambda { param: name, body: Box::new(body) }
+        }
+        Type::Smooth(inner) => Term::SmoothIn(Box::new(build(inner, scope))),
+        // Like `Σ`, later fields are built with the earlier ones' names free
+        Type::Record(fields) => {
+            Term::RecordLit(fields.iter().map(|(label, ty)| (label.clone(), build(ty, scope))).collect())
+        }
+        // Unit has exactly one element, so nothing is left to write
+        Type::Unit => Term::Star,
+        Type::Universe(_)
+        | Type::Interval
+        | Type::Real
+        | Type::Nat
+        | Type::Empty
+        | Type::Bool
+        | Type::Circle
+        // Which injection to use is up to the writer
+        | Type::Sum(..) => Term::Hole,
+    }
+}
+
+/// The introduction forms `ty` asks for, with holes for the rest
+pub fn skeleton(ty: &Type) -> Term {
+    build(ty, &mut Vec::new())
+}
+
+/// Parse `type_src` and print its skeleton in surface syntax
+pub fn skeleton_for(type_src: &str) -> Result<String, ParseError> {
+    parse_type(type_src).map(|ty| skeleton(&ty).to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Checking terms with holes
+
+/// Check `term` against `ty`, returning its holes in order; `λ` binders
+/// take their types from `ty`, as in lab definitions
+pub fn goals(term: &Term, ty: &Type) -> crate::Result<Vec<Goal>> {
+    crate::check::holes(term, ty)
+}
+
+// ---------------------------------------------------------------------------
+// Finding definitions in lab source
+
+/// A definition the skeleton action applies to: a `name : type` line whose
+/// `name = body` line is missing or `?`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Action {
+    /// Zero-based line of the `name : type` header
+    pub line: usize,
+    pub name: String,
+    pub skeleton: String,
+}
+
+fn header(line: &str) -> Option<(&str, &str)> {
+    let (name, ty) = line.split_once(':')?;
+    let name = name.trim();
+    let is_name = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '\'');
+    (is_name && !ty.starts_with('=')).then_some((name, ty.trim()))
+}
+
+fn body<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let rest = line.trim_start().strip_prefix(name)?;
+    let rest = rest.trim_start().strip_prefix('=')?;
+    Some(rest.trim())
+}
+
+/// The definitions in `source` that the editor can fill in
+pub fn actions(source: &str) -> Vec<Action> {
+    let lines: Vec<&str> = source.lines().collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let (name, ty) = header(text)?;
+            let written = lines[line + 1..].iter().find_map(|l| body(l, name));
+            if written.is_some_and(|b| b != "?") {
+                return None;
+            }
+            let skeleton = skeleton_for(ty).ok()?;
+            Some(Action { line, name: name.to_string(), skeleton })
+        })
+        .collect()
+}
+
+impl Action {
+    /// `source` with the definition's body set to the skeleton
+    pub fn apply(&self, source: &str) -> String {
+        let definition = format!("{} = {}", self.name, self.skeleton);
+        let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+        let hole = (self.line + 1..lines.len()).find(|&i| body(&lines[i], &self.name).is_some());
+        match hole {
+            Some(i) => lines[i] = definition,
+            None => lines.insert((self.line + 1).min(lines.len()), definition),
+        }
+        let mut out = lines.join("\n");
+        if source.ends_with('\n') {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holes(term: &Term) -> usize {
+        match term {
+            Term::Hole => 1,
+            Term::Lambda { body, .. } | Term::PathLambda { body, .. } => holes(body),
+            Term::Pair { first, second } => holes(first) + holes(second),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn test_type_expressions() {
+        let fun = |a, b| Type::Function { domain: Box::new(a), codomain: Box::new(b), is_smooth: false };
+        let smooth = |a, b| Type::Function { domain: Box::new(a), codomain: Box::new(b), is_smooth: true };
+
+        // Arrows associate to the right; parentheses override
+        assert_eq!(parse_type("ℝ → I → ℝ").unwrap(), fun(Type::Real, fun(Type::Interval, Type::Real)));
+        assert_eq!(parse_type("(ℝ → I) → ℝ").unwrap(), fun(fun(Type::Real, Type::Interval), Type::Real));
+        assert_eq!(parse_type("((ℝ))").unwrap(), Type::Real);
+        assert_eq!(
+            parse_type("C∞(ℝ, C∞(ℝ, ℝ)) → ℝ").unwrap(),
+            fun(smooth(Type::Real, smooth(Type::Real, Type::Real)), Type::Real)
+        );
+        assert!(matches!(parse_type("Π(x:ℝ).Σ(y:ℝ).Path ℝ x y").unwrap(),
+            Type::Pi { codomain, .. } if matches!(*codomain, Type::Sigma { .. })));
+
+        // Failures point at the offending token
+        assert_eq!(parse_type("ℝ → → ℝ").unwrap_err().position, 4);
+        assert_eq!(parse_type("C∞(ℝ ℝ)").unwrap_err().position, 5);
+        assert_eq!(parse_type("(ℝ").unwrap_err().message, "expected `)`");
+        let err = "ℝ → Banana".parse::<Type>().unwrap_err();
+        assert!(matches!(&err, crate::TypeError::Parse { position: 4, .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_composition_skeleton() {
+        let ty = parse_type("(ℝ → ℝ) → (ℝ → ℝ) → (ℝ → ℝ) → ℝ → ℝ").unwrap();
+        let skeleton = skeleton(&ty);
+        assert_eq!(skeleton.to_string(), "λf. λg. λh. λx. ?");
+
+        // Three function binders, then the point the hole applies them to
+        let goals = goals(&skeleton, &ty).unwrap();
+        assert_eq!(goals.len(), 1);
+        let names: Vec<&str> = goals[0].context.iter().map(|(x, _)| x.as_str()).collect();
+        assert_eq!(names, ["f", "g", "h", "x"]);
+        assert_eq!(goals[0].context.iter().filter(|(_, ty)| matches!(ty, Type::Function { .. })).count(), 3);
+        assert_eq!(goals[0].ty, Type::Real);
+
+        // What the hole is for: `f (g (h x))` fills it and leaves none
+        let filled = simp::parse_term("λf. λg. λh. λx. f (g (h x))").unwrap();
+        assert!(super::goals(&filled, &ty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sigma_path_skeleton() {
+        let ty = parse_type("Σ (x : ℝ). Path ℝ x 1").unwrap();
+        let skeleton = skeleton(&ty);
+        assert_eq!(skeleton.to_string(), "(?, ⟨i⟩ ?)");
+        let goals = goals(&skeleton, &ty).unwrap();
+        assert_eq!(goals.len(), 2);
+        assert_eq!(goals[1].context, vec![("i".to_string(), Type::Interval)]);
+
+        // Named binders are kept, clashing ones renamed
+        assert_eq!(skeleton_for("Π (y : ℝ). Path ℝ y y").unwrap(), "λy. ⟨i⟩ ?");
+        assert_eq!(skeleton_for("Π (i : ℝ). Path ℝ i i").unwrap(), "λi. ⟨j⟩ ?");
+        assert_eq!(skeleton_for("ℝ → Π (x : ℝ). ℝ").unwrap(), "λx. λy. ?");
+        assert_eq!(skeleton_for("(I → ℝ) → Σ (p : ℝ → ℝ). Path (ℝ → ℝ) p p").unwrap(), "λf. (λx. ?, ⟨i⟩ λx. ?)");
+        assert!(skeleton_for("ℝ →").is_err());
+    }
+
+    #[test]
+    fn test_skeletons_parse_and_check_with_their_holes() {
+        for source in [
+            "ℝ",
+            "ℝ → ℝ",
+            "(ℝ → ℝ) → (ℝ → ℝ) → (ℝ → ℝ) → ℝ → ℝ",
+            "Π (x y : ℝ). Path ℝ x y",
+            "Σ (x : ℝ). Path ℝ x 1",
+            "Σ (f : ℝ → ℝ). Σ (x : ℝ). Path ℝ (f x) x",
+            "I → Path (ℝ → ℝ) (λx. x) (λx. x)",
+            "Path (Σ (x : ℝ). ℝ) (1.5, 2.5) (2.5, 1.5)",
+            "Type → Type",
+        ] {
+            let ty = parse_type(source).unwrap();
+            let skeleton = skeleton(&ty);
+            let printed = skeleton.to_string();
+            let reparsed = simp::parse_term(&printed).unwrap_or_else(|e| panic!("`{}`: {}", printed, e));
+            let goals = goals(&reparsed, &ty).unwrap_or_else(|e| panic!("`{}` against {}: {}", printed, source, e));
+            assert_eq!(goals.len(), holes(&skeleton), "`{}`", printed);
+        }
+
+        // Terms that do not fit are rejected, not counted
+        let ty = parse_type("ℝ → ℝ").unwrap();
+        assert!(goals(&simp::parse_term("(?, ?)").unwrap(), &ty).is_err());
+        assert!(goals(&simp::parse_term("λx. y").unwrap(), &ty).is_err());
+    }
+
+    #[test]
+    fn test_actions_fill_missing_and_hole_bodies() {
+        let source = "comp3 : (ℝ → ℝ) → (ℝ → ℝ) → (ℝ → ℝ) → ℝ → ℝ\nid : ℝ → ℝ\nid = λx. x\nc : Σ (x : ℝ). Path ℝ x 1\nc = ?\n";
+        let actions = actions(source);
+        let names: Vec<&str> = actions.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["comp3", "c"]);
+
+        let filled = actions[0].apply(source);
+        assert_eq!(filled.lines().nth(1), Some("comp3 = λf. λg. λh. λx. ?"));
+        let filled = actions[1].apply(source);
+        assert_eq!(filled.lines().nth(4), Some("c = (?, ⟨i⟩ ?)"));
+        assert_eq!(filled.lines().count(), 5);
+        assert!(filled.ends_with('\n'));
+    }
+}
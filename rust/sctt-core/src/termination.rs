@@ -0,0 +1,808 @@
+//! Termination checking for recursive definitions
+//!
+//! Recursion written through an eliminator (`natrec` and friends) needs no
+//! check: the eliminator is the recursion principle, so every use of it
+//! terminates by construction. Only general fixpoints need a guard.
+//!
+//! A `fix` definition designates one decreasing parameter, the first unless
+//! `{struct x}` names another:
+//!
+//! ```text
+//! fix plus (m : ℕ) (n : ℕ) : ℕ := natrec[ℕ] n (λ(k : ℕ). λ(r : ℕ). succ (plus k n)) m
+//! ```
+//!
+//! Every recursive call in the body must pass, in that position, a term
+//! structurally smaller than the parameter: the predecessor a `natrec` step
+//! binds when the recursion is on the parameter (`k` above), a projection of
+//! the parameter, or a variable already known to be smaller. `unsafe_fix`
+//! skips the check but marks the definition, and the kernel report flags
+//! every definition that depends on it.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::check::{self, Context};
+use crate::simp::{self, ParseError, Parser, Spans};
+use crate::{Span, Term, Type};
+
+/// How a recursive definition was introduced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FixKind {
+    /// `fix`: recursive calls must be structurally decreasing
+    Checked,
+    /// `unsafe_fix`: accepted without checking, taints its dependents
+    Unsafe,
+}
+
+/// A recursive definition `fix name (x₁ : A₁) … (xₙ : Aₙ) : ty := body`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FixDefinition {
+    pub name: String,
+    pub params: Vec<(String, Type)>,
+    /// The type of the body, with the parameters in scope
+    pub ty: Type,
+    /// Index into `params` of the structurally decreasing parameter
+    pub decreasing: usize,
+    pub body: Term,
+    pub kind: FixKind,
+    /// Source spans of the body's subterms, when it was parsed
+    pub spans: Vec<(TermPath, Span)>,
+}
+
+/// Location of a subterm, as the sequence of child indices from the body root
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TermPath(pub Vec<usize>);
+
+impl fmt::Display for TermPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "body")?;
+        for i in &self.0 {
+            write!(f, ".{}", i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a `fix` definition was rejected
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TerminationError {
+    /// The designated decreasing parameter does not exist
+    NoSuchParameter { function: String, index: usize },
+
+    /// A recursive call passes a non-decreasing argument
+    NotDecreasing {
+        function: String,
+        argument: String,
+        call: String,
+        at: TermPath,
+        span: Option<Span>,
+    },
+
+    /// A recursive call does not reach the decreasing argument position,
+    /// or the function is used as a value rather than called
+    UnguardedUse {
+        function: String,
+        call: String,
+        at: TermPath,
+        span: Option<Span>,
+    },
+}
+
+impl TerminationError {
+    /// Where in the source the offending call is, if the body was parsed
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TerminationError::NoSuchParameter { .. } => None,
+            TerminationError::NotDecreasing { span, .. } | TerminationError::UnguardedUse { span, .. } => *span,
+        }
+    }
+}
+
+/// ` at body.1.0` for a call without a span; a span is shown by
+/// [`TypeError::render`](crate::TypeError::render) instead
+struct Site<'a>(&'a TermPath, &'a Option<Span>);
+
+impl fmt::Display for Site<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(_) => Ok(()),
+            None => write!(f, " at {}", self.0),
+        }
+    }
+}
+
+impl fmt::Display for TerminationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerminationError::NoSuchParameter { function, index } => write!(
+                f,
+                "{}: decreasing parameter #{} does not exist",
+                function, index
+            ),
+            TerminationError::NotDecreasing { function, argument, call, at, span } => write!(
+                f,
+                "{}: recursive call `{}`{} is not structurally decreasing (argument `{}`)",
+                function, call, Site(at, span), argument
+            ),
+            TerminationError::UnguardedUse { function, call, at, span } => write!(
+                f,
+                "{}: `{}`{} does not apply the decreasing argument",
+                function, call, Site(at, span)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TerminationError {}
+
+/// Termination status of one definition
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TerminationStatus {
+    /// Every recursive call is structurally decreasing
+    Structural,
+    /// Introduced with `unsafe_fix`; not checked
+    Unchecked,
+    /// Rejected by the guard condition
+    Rejected(TerminationError),
+}
+
+impl TerminationStatus {
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self, TerminationStatus::Rejected(_))
+    }
+}
+
+impl FixDefinition {
+    pub fn new(name: &str, params: Vec<(String, Type)>, ty: Type, decreasing: usize, body: Term) -> Self {
+        FixDefinition {
+            name: name.to_string(),
+            params,
+            ty,
+            decreasing,
+            body,
+            kind: FixKind::Checked,
+            spans: Vec::new(),
+        }
+    }
+
+    /// The `unsafe_fix` form of a definition
+    pub fn new_unsafe(name: &str, params: Vec<(String, Type)>, ty: Type, decreasing: usize, body: Term) -> Self {
+        FixDefinition {
+            kind: FixKind::Unsafe,
+            ..FixDefinition::new(name, params, ty, decreasing, body)
+        }
+    }
+
+    /// The type of `name` itself, `A₁ → … → Aₙ → ty`
+    pub fn closed_type(&self) -> Type {
+        self.params.iter().rev().fold(self.ty.clone(), |codomain, (param, domain)| {
+            if codomain.free_vars().contains(param) {
+                Type::Pi { param: param.clone(), domain: Box::new(domain.clone()), codomain: Box::new(codomain) }
+            } else {
+                Type::Function { domain: Box::new(domain.clone()), codomain: Box::new(codomain), is_smooth: false }
+            }
+        })
+    }
+
+    /// Check the body against `ty` with `ctx`, the function itself and then
+    /// its parameters in scope
+    pub fn check_types(&self, ctx: &Context) -> crate::Result<()> {
+        let mut ctx = ctx.to_vec();
+        ctx.push((self.name.clone(), self.closed_type()));
+        ctx.extend(self.params.iter().cloned());
+        check::check(&ctx, &self.body, &self.ty)
+    }
+
+    /// Check the guard condition
+    pub fn check_termination(&self) -> Result<(), TerminationError> {
+        let (param, _) = self.params.get(self.decreasing).ok_or_else(|| {
+            TerminationError::NoSuchParameter {
+                function: self.name.clone(),
+                index: self.decreasing,
+            }
+        })?;
+
+        // A parameter named like the function itself shadows every recursive call
+        if self.params.iter().any(|(p, _)| p == &self.name) {
+            return Ok(());
+        }
+
+        let guard = Guard {
+            function: &self.name,
+            decreasing: self.decreasing,
+            spans: &self.spans,
+        };
+        let mut scope = Scope {
+            param: Some(param.clone()),
+            smaller: HashSet::new(),
+        };
+        guard.visit(&self.body, &mut scope, &mut Vec::new())
+    }
+
+    /// Status as reported by the kernel
+    pub fn status(&self) -> TerminationStatus {
+        match self.kind {
+            FixKind::Unsafe => TerminationStatus::Unchecked,
+            FixKind::Checked => match self.check_termination() {
+                Ok(()) => TerminationStatus::Structural,
+                Err(e) => TerminationStatus::Rejected(e),
+            },
+        }
+    }
+}
+
+/// Parse one definition `fix name (x : A)… {struct x} : T := body`, or the
+/// same with `unsafe_fix`
+pub fn parse_fix(source: &str) -> Result<FixDefinition, ParseError> {
+    let mut definitions = parse_fixes(source)?;
+    match definitions.len() {
+        1 => Ok(definitions.remove(0)),
+        _ => Err(simp::error(0, "expected exactly one definition")),
+    }
+}
+
+/// Parse a file of definitions, one after another; spans are positions in
+/// the whole of `source`
+pub fn parse_fixes(source: &str) -> Result<Vec<FixDefinition>, ParseError> {
+    let mut parser = Parser::new(source)?;
+    let mut definitions = Vec::new();
+    while parser.pos < parser.tokens.len() {
+        definitions.push(parser.fix_definition()?);
+    }
+    Ok(definitions)
+}
+
+impl Parser {
+    fn fix_definition(&mut self) -> Result<FixDefinition, ParseError> {
+        let kind = if self.eat("fix") {
+            FixKind::Checked
+        } else if self.eat("unsafe_fix") {
+            FixKind::Unsafe
+        } else {
+            return Err(simp::error(self.position(), "expected `fix` or `unsafe_fix`"));
+        };
+        let name = self.ident()?;
+        let mut params = Vec::new();
+        while self.eat("(") {
+            let param = self.ident()?;
+            self.expect(":")?;
+            let ty = self.ty()?;
+            self.expect(")")?;
+            params.push((param, ty));
+        }
+        let mut decreasing = 0;
+        if self.eat("{") {
+            let position = self.position();
+            if self.ident()? != "struct" {
+                return Err(simp::error(position, "expected `struct`"));
+            }
+            let position = self.position();
+            let param = self.ident()?;
+            decreasing = params
+                .iter()
+                .position(|(p, _)| *p == param)
+                .ok_or_else(|| simp::error(position, format!("`{}` is not a parameter of `{}`", param, name)))?;
+            self.expect("}")?;
+        }
+        self.expect(":")?;
+        let ty = self.ty()?;
+        self.expect(":=")?;
+        let (body, parsed) = self.term_spanned()?;
+
+        let mut spans = Vec::new();
+        if let Some(root) = parsed.root() {
+            spans.push((TermPath::default(), root));
+        }
+        collect_spans(&body, &parsed, &mut Vec::new(), &mut spans);
+        Ok(FixDefinition { name, params, ty, decreasing, body, kind, spans })
+    }
+}
+
+/// The spans of the nodes below `term`, by their paths
+fn collect_spans(term: &Term, parsed: &Spans, path: &mut Vec<usize>, out: &mut Vec<(TermPath, Span)>) {
+    for (i, child) in children(term).into_iter().enumerate() {
+        path.push(i);
+        if let Some(span) = parsed.get(child) {
+            out.push((TermPath(path.clone()), span));
+        }
+        collect_spans(child, parsed, path, out);
+        path.pop();
+    }
+}
+
+/// The immediate subterms of `term`, numbered as in a [`TermPath`]
+fn children(term: &Term) -> Vec<&Term> {
+    match term {
+        Term::Lambda { body: t, .. }
+        | Term::PathLambda { body: t, .. }
+        | Term::Fst(t)
+        | Term::Snd(t)
+        | Term::INeg(t)
+        | Term::NatSucc(t)
+        | Term::Proj(t, _)
+        | Term::Inl(t)
+        | Term::Inr(t)
+        | Term::SmoothIn(t)
+        | Term::SmoothOut(t)
+        | Term::EmptyElim { scrutinee: t, .. }
+        | Term::Ann { term: t, .. } => vec![t],
+        Term::App { func: a, arg: b }
+        | Term::Pair { first: a, second: b }
+        | Term::PathApp { path: a, point: b }
+        | Term::IMeet(a, b)
+        | Term::IJoin(a, b)
+        | Term::Let { value: a, body: b, .. } => vec![a, b],
+        Term::RecordLit(fields) => fields.iter().map(|(_, value)| value).collect(),
+        Term::NatRec { base: a, step: b, scrutinee: c, .. }
+        | Term::BoolElim { on_true: a, on_false: b, scrutinee: c, .. }
+        | Term::CircleElim { base_case: a, loop_case: b, scrutinee: c, .. }
+        | Term::Case { scrutinee: a, left_branch: b, right_branch: c, .. } => vec![a, b, c],
+        Term::Var(_)
+        | Term::IZero
+        | Term::IOne
+        | Term::RealLit(_)
+        | Term::SmoothFunc { .. }
+        | Term::Hole
+        | Term::NatZero
+        | Term::Star
+        | Term::True
+        | Term::False
+        | Term::Base
+        | Term::Loop => Vec::new(),
+    }
+}
+
+/// What is in scope while walking a body
+#[derive(Clone)]
+struct Scope {
+    /// The decreasing parameter, unless shadowed
+    param: Option<String>,
+    /// Variables bound to terms structurally smaller than the parameter
+    smaller: HashSet<String>,
+}
+
+impl Scope {
+    fn bind(&self, name: &str) -> Scope {
+        let mut scope = self.clone();
+        if scope.param.as_deref() == Some(name) {
+            scope.param = None;
+        }
+        scope.smaller.remove(name);
+        scope
+    }
+}
+
+struct Guard<'a> {
+    function: &'a str,
+    decreasing: usize,
+    spans: &'a [(TermPath, Span)],
+}
+
+impl Guard<'_> {
+    fn locate(&self, path: &[usize]) -> (TermPath, Option<Span>) {
+        let span = self.spans.iter().find(|(at, _)| at.0 == path).map(|(_, span)| *span);
+        (TermPath(path.to_vec()), span)
+    }
+
+    fn visit(&self, term: &Term, scope: &mut Scope, path: &mut Vec<usize>) -> Result<(), TerminationError> {
+        // Recursive call: check the spine, then its arguments
+        let (head, args) = spine(term);
+        if matches!(head, Term::Var(name) if name == self.function) {
+            let (at, span) = self.locate(path);
+            let Some(arg) = args.get(self.decreasing) else {
+                let function = self.function.to_string();
+                return Err(TerminationError::UnguardedUse { function, call: term.to_string(), at, span });
+            };
+            if !is_smaller(arg, scope) {
+                return Err(TerminationError::NotDecreasing {
+                    function: self.function.to_string(),
+                    argument: arg.to_string(),
+                    call: term.to_string(),
+                    at,
+                    span,
+                });
+            }
+            // Arguments sit at the end of the application spine
+            for (i, arg) in args.iter().enumerate() {
+                let depth = args.len() - 1 - i;
+                let mut arg_path = path.clone();
+                arg_path.extend(std::iter::repeat_n(0, depth));
+                arg_path.push(1);
+                self.visit(arg, scope, &mut arg_path)?;
+            }
+            return Ok(());
+        }
+
+        match term {
+            Term::Var(name) if name == self.function => {
+                let (at, span) = self.locate(path);
+                let function = self.function.to_string();
+                Err(TerminationError::UnguardedUse { function, call: term.to_string(), at, span })
+            }
+
+            Term::Lambda { param, .. } | Term::PathLambda { param, .. } if param == self.function => Ok(()),
+
+            Term::Lambda { param, body, .. } | Term::PathLambda { param, body } => {
+                let mut inner = scope.bind(param);
+                self.child(body, 0, &mut inner, path)
+            }
+
+            // (λv. body) arg with a smaller argument binds v as smaller
+            Term::App { func, arg } => {
+                self.child(arg, 1, scope, path)?;
+                if let Term::Lambda { param, body, .. } = func.as_ref() {
+                    if param == self.function {
+                        return Ok(());
+                    }
+                    let mut inner = scope.bind(param);
+                    if is_smaller(arg, scope) {
+                        inner.smaller.insert(param.clone());
+                    }
+                    path.push(0);
+                    let result = self.child(body, 0, &mut inner, path);
+                    path.pop();
+                    result
+                } else {
+                    self.child(func, 0, scope, path)
+                }
+            }
+
+            Term::Pair { first, second } => {
+                self.child(first, 0, scope, path)?;
+                self.child(second, 1, scope, path)
+            }
+            Term::PathApp { path: p, point } => {
+                self.child(p, 0, scope, path)?;
+                self.child(point, 1, scope, path)
+            }
+            Term::IMeet(a, b) | Term::IJoin(a, b) => {
+                self.child(a, 0, scope, path)?;
+                self.child(b, 1, scope, path)
+            }
+            Term::Fst(t)
+            | Term::Snd(t)
+            | Term::INeg(t)
+            | Term::NatSucc(t)
+            | Term::Proj(t, _)
+            | Term::Inl(t)
+            | Term::Inr(t)
+            | Term::SmoothIn(t)
+            | Term::SmoothOut(t) => {
+                self.child(t, 0, scope, path)
+            }
+            Term::RecordLit(fields) => {
+                for (i, (_, value)) in fields.iter().enumerate() {
+                    self.child(value, i, scope, path)?;
+                }
+                Ok(())
+            }
+            // Recursion on the parameter binds the predecessor in the step,
+            // `λk. λr. …`, as smaller
+            Term::NatRec { base, step, scrutinee, .. } => {
+                self.child(base, 0, scope, path)?;
+                match step.as_ref() {
+                    Term::Lambda { param, body, .. } if param != self.function => {
+                        let mut inner = scope.bind(param);
+                        if at_most(scrutinee, scope) {
+                            inner.smaller.insert(param.clone());
+                        }
+                        path.push(1);
+                        let result = self.child(body, 0, &mut inner, path);
+                        path.pop();
+                        result?;
+                    }
+                    _ => self.child(step, 1, scope, path)?,
+                }
+                self.child(scrutinee, 2, scope, path)
+            }
+            Term::BoolElim { on_true: a, on_false: b, scrutinee: c, .. }
+            | Term::CircleElim { base_case: a, loop_case: b, scrutinee: c, .. }
+            | Term::Case { scrutinee: a, left_branch: b, right_branch: c, .. } => {
+                self.child(a, 0, scope, path)?;
+                self.child(b, 1, scope, path)?;
+                self.child(c, 2, scope, path)
+            }
+            Term::EmptyElim { scrutinee: t, .. } | Term::Ann { term: t, .. } => self.child(t, 0, scope, path),
+            Term::Let { name, value, .. } if name == self.function => self.child(value, 0, scope, path),
+            Term::Let { name, value, body, .. } => {
+                self.child(value, 0, scope, path)?;
+                let mut inner = scope.bind(name);
+                if is_smaller(value, scope) {
+                    inner.smaller.insert(name.clone());
+                }
+                self.child(body, 1, &mut inner, path)
+            }
+
+            Term::Var(_)
+            | Term::IZero
+            | Term::IOne
+            | Term::RealLit(_)
+            | Term::SmoothFunc { .. }
+            | Term::Hole
+            | Term::NatZero
+            | Term::Star
+            | Term::True
+            | Term::False
+            | Term::Base
+            | Term::Loop => Ok(()),
+        }
+    }
+
+    fn child(&self, term: &Term, index: usize, scope: &mut Scope, path: &mut Vec<usize>) -> Result<(), TerminationError> {
+        path.push(index);
+        let result = self.visit(term, scope, path);
+        path.pop();
+        result
+    }
+}
+
+/// Split an application spine `f a₁ … aₙ` into `f` and `[a₁, …, aₙ]`
+fn spine(term: &Term) -> (&Term, Vec<&Term>) {
+    let mut args = Vec::new();
+    let mut head = term;
+    while let Term::App { func, arg } = head {
+        args.push(arg.as_ref());
+        head = func;
+    }
+    args.reverse();
+    (head, args)
+}
+
+/// Is `term` structurally smaller than the decreasing parameter?
+fn is_smaller(term: &Term, scope: &Scope) -> bool {
+    match term {
+        Term::Var(name) => scope.smaller.contains(name),
+        Term::Fst(inner) | Term::Snd(inner) | Term::Proj(inner, _) => at_most(inner, scope),
+        _ => false,
+    }
+}
+
+/// Is `term` the decreasing parameter or smaller than it?
+fn at_most(term: &Term, scope: &Scope) -> bool {
+    matches!(term, Term::Var(name) if scope.param.as_deref() == Some(name.as_str())) || is_smaller(term, scope)
+}
+
+/// Names of global definitions referenced by a term
+fn references(term: &Term, globals: &BTreeSet<&str>, bound: &mut Vec<String>, out: &mut BTreeSet<String>) {
+    match term {
+        Term::Var(name) => {
+            if globals.contains(name.as_str()) && !bound.contains(name) {
+                out.insert(name.clone());
+            }
+        }
+        Term::Lambda { param, body, .. } | Term::PathLambda { param, body } => {
+            bound.push(param.clone());
+            references(body, globals, bound, out);
+            bound.pop();
+        }
+        Term::App { func: a, arg: b }
+        | Term::Pair { first: a, second: b }
+        | Term::PathApp { path: a, point: b }
+        | Term::IMeet(a, b)
+        | Term::IJoin(a, b) => {
+            references(a, globals, bound, out);
+            references(b, globals, bound, out);
+        }
+        Term::Fst(t)
+        | Term::Snd(t)
+        | Term::INeg(t)
+        | Term::NatSucc(t)
+        | Term::Proj(t, _)
+        | Term::Inl(t)
+        | Term::Inr(t)
+        | Term::SmoothIn(t)
+        | Term::SmoothOut(t) => {
+            references(t, globals, bound, out)
+        }
+        Term::RecordLit(fields) => {
+            for (_, value) in fields {
+                references(value, globals, bound, out);
+            }
+        }
+        Term::NatRec { base: a, step: b, scrutinee: c, .. }
+        | Term::BoolElim { on_true: a, on_false: b, scrutinee: c, .. }
+        | Term::CircleElim { base_case: a, loop_case: b, scrutinee: c, .. }
+        | Term::Case { scrutinee: a, left_branch: b, right_branch: c, .. } => {
+            for t in [a, b, c] {
+                references(t, globals, bound, out);
+            }
+        }
+        Term::EmptyElim { scrutinee: t, .. } | Term::Ann { term: t, .. } => references(t, globals, bound, out),
+        Term::Let { name, value, body, .. } => {
+            references(value, globals, bound, out);
+            bound.push(name.clone());
+            references(body, globals, bound, out);
+            bound.pop();
+        }
+        Term::IZero
+        | Term::IOne
+        | Term::RealLit(_)
+        | Term::SmoothFunc { .. }
+        | Term::Hole
+        | Term::NatZero
+        | Term::Star
+        | Term::True
+        | Term::False
+        | Term::Base
+        | Term::Loop => {}
+    }
+}
+
+/// For each name in `deps`, the names in `unsafe_defs` it reaches through
+/// `deps`, if there are any
+pub(crate) fn taint(
+    deps: &BTreeMap<String, BTreeSet<String>>,
+    unsafe_defs: &BTreeSet<String>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let mut tainted = BTreeMap::new();
+    for (name, direct) in deps {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<&String> = direct.iter().collect();
+        while let Some(dep) = stack.pop() {
+            if seen.insert(dep.clone()) {
+                if let Some(next) = deps.get(dep) {
+                    stack.extend(next.iter());
+                }
+            }
+        }
+        let culprits: BTreeSet<String> = seen.intersection(unsafe_defs).cloned().collect();
+        if !culprits.is_empty() {
+            tainted.insert(name.clone(), culprits);
+        }
+    }
+    tainted
+}
+
+/// Kernel report over a group of recursive definitions
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TerminationReport {
+    pub statuses: BTreeMap<String, TerminationStatus>,
+    /// Definitions that depend (transitively) on an `unsafe_fix`, mapped to
+    /// the unsafe definitions they rely on
+    pub tainted: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TerminationReport {
+    pub fn check(definitions: &[FixDefinition]) -> TerminationReport {
+        let globals: BTreeSet<&str> = definitions.iter().map(|d| d.name.as_str()).collect();
+
+        let mut deps: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for def in definitions {
+            let mut bound: Vec<String> = def.params.iter().map(|(p, _)| p.clone()).collect();
+            let mut out = BTreeSet::new();
+            references(&def.body, &globals, &mut bound, &mut out);
+            out.remove(&def.name);
+            deps.insert(def.name.clone(), out);
+        }
+
+        let unsafe_defs: BTreeSet<String> = definitions
+            .iter()
+            .filter(|d| d.kind == FixKind::Unsafe)
+            .map(|d| d.name.clone())
+            .collect();
+
+        TerminationReport {
+            statuses: definitions.iter().map(|d| (d.name.clone(), d.status())).collect(),
+            tainted: taint(&deps, &unsafe_defs),
+        }
+    }
+
+    /// Accepted, not `unsafe_fix` itself, and not depending on one; a name
+    /// without a status is not recursive
+    pub fn is_trusted(&self, name: &str) -> bool {
+        matches!(self.statuses.get(name), None | Some(TerminationStatus::Structural))
+            && !self.tainted.contains_key(name)
+    }
+
+    pub fn rejected(&self) -> Vec<&TerminationError> {
+        self.statuses
+            .values()
+            .filter_map(|s| match s {
+                TerminationStatus::Rejected(e) => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLUS: &str = "fix plus (m : ℕ) (n : ℕ) : ℕ := natrec[ℕ] n (λ(k : ℕ). λ(r : ℕ). succ (plus k n)) m";
+
+    /// Parse `source` and check its types, each definition seeing the ones before it
+    fn definitions(source: &str) -> Vec<FixDefinition> {
+        let defs = parse_fixes(source).unwrap();
+        let mut ctx = Vec::new();
+        for def in &defs {
+            def.check_types(&ctx).unwrap();
+            ctx.push((def.name.clone(), def.closed_type()));
+        }
+        defs
+    }
+
+    /// The span of the first `text` in `source`, counted in characters
+    fn span_of(source: &str, text: &str) -> Span {
+        let start = source[..source.find(text).unwrap()].chars().count();
+        Span { start, end: start + text.chars().count() }
+    }
+
+    #[test]
+    fn test_structural_recursion_accepted() {
+        let plus = &definitions(PLUS)[0];
+        assert_eq!(plus.closed_type().to_string(), "ℕ → ℕ → ℕ");
+        assert_eq!(plus.status(), TerminationStatus::Structural);
+
+        // Descent on the second parameter, through a β-redex
+        let times = "fix times (m : ℕ) (n : ℕ) {struct n} : ℕ :=
+            natrec[ℕ] zero (λ(k : ℕ). λ(r : ℕ). (λ(j : ℕ). plus m (times m j)) k) n";
+        let source = format!("{}\n{}", PLUS, times);
+        let times = &definitions(&source)[1];
+        assert_eq!(times.decreasing, 1);
+        assert_eq!(times.status(), TerminationStatus::Structural);
+    }
+
+    #[test]
+    fn test_non_decreasing_call_rejected_with_span() {
+        for call in ["spin (succ k)", "spin r", "spin n"] {
+            let source = format!("fix spin (n : ℕ) : ℕ := natrec[ℕ] zero (λ(k : ℕ). λ(r : ℕ). {}) n", call);
+            let spin = &definitions(&source)[0];
+            match spin.check_termination() {
+                Err(e @ TerminationError::NotDecreasing { .. }) => {
+                    assert_eq!(e.span(), Some(span_of(&source, call)), "{}", call);
+                    assert!(!e.to_string().contains("body."), "{}", e);
+                }
+                other => panic!("expected `{}` to be rejected, got {:?}", call, other),
+            }
+        }
+
+        // Built rather than parsed, the call is found by its path
+        let body = Term::App { func: Box::new(Term::Var("spin".into())), arg: Box::new(Term::Var("n".into())) };
+        let spin = FixDefinition::new("spin", vec![("n".into(), Type::Nat)], Type::Nat, 0, body);
+        let e = spin.check_termination().unwrap_err();
+        assert_eq!(e.span(), None);
+        let message = "spin: recursive call `spin n` at body is not structurally decreasing (argument `n`)";
+        assert_eq!(e.to_string(), message);
+    }
+
+    #[test]
+    fn test_escaping_recursive_reference_rejected() {
+        let source = "fix f (n : ℕ) : ℕ := natrec[ℕ] zero (λ(k : ℕ). λ(r : ℕ). (λ(g : ℕ → ℕ). g k) f) n";
+        let f = &definitions(source)[0];
+        match f.check_termination() {
+            Err(e @ TerminationError::UnguardedUse { .. }) => {
+                let start = source.chars().count() - 4;
+                assert_eq!(e.span(), Some(Span { start, end: start + 1 }));
+            }
+            other => panic!("expected an unguarded use, got {:?}", other),
+        }
+        assert!(parse_fix("fix f (n : ℕ) {struct m} : ℕ := n").is_err());
+    }
+
+    #[test]
+    fn test_unsafe_fix_taints_dependents() {
+        let defs = definitions(
+            "unsafe_fix spin (n : ℕ) : ℕ := spin n
+             fix user (n : ℕ) : ℕ := spin n
+             fix client (n : ℕ) : ℕ := succ (user n)
+             fix clean (n : ℕ) : ℕ := n",
+        );
+        let report = TerminationReport::check(&defs);
+
+        assert_eq!(report.statuses["spin"], TerminationStatus::Unchecked);
+        assert!(report.tainted["user"].contains("spin"));
+        assert!(report.tainted["client"].contains("spin"));
+        assert!(!report.is_trusted("client"));
+        assert!(report.is_trusted("clean"));
+    }
+}
@@ -0,0 +1,184 @@
+//! `#[wasm_bindgen]` exports, compiled only with the `wasm-bindings` feature
+
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::prelude::*;
+
+use crate::simp::{self, Derivation, ParseError};
+use crate::definitions::Definitions;
+use crate::skeleton::parse_type;
+use crate::{Term, Type, TypeError};
+
+impl From<TypeError> for JsValue {
+    fn from(e: TypeError) -> JsValue {
+        JsValue::from_str(&e.to_string())
+    }
+}
+
+// WASM bindings
+#[wasm_bindgen]
+pub struct WasmTypeChecker {
+    context: Vec<(String, Type)>,
+    definitions: Definitions,
+}
+
+#[wasm_bindgen]
+impl WasmTypeChecker {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmTypeChecker {
+        WasmTypeChecker {
+            context: Vec::new(),
+            definitions: Definitions::new(),
+        }
+    }
+
+    /// Bind `name` to the type written `type_str`; a type that does not
+    /// parse is rejected with `{ error, position }`
+    pub fn add_binding(&mut self, name: String, type_str: String) -> std::result::Result<(), JsValue> {
+        let ty = parse_type(&type_str).map_err(|e| {
+            let json = serde_json::json!({ "error": e.message, "position": e.position });
+            to_value(&json).unwrap_or_else(|_| JsValue::from_str(&e.to_string()))
+        })?;
+        self.context.push((name, ty));
+        Ok(())
+    }
+
+    /// Parse `expr` and infer its type against the bindings so far,
+    /// returning `{ ok, type }` or `{ ok: false, error, span }` where `span`
+    /// is `{ start, end }` in characters, or null when the error has none
+    pub fn check(&self, expr: &str) -> std::result::Result<JsValue, JsValue> {
+        let json = match crate::check::infer_source(&self.scope(), expr) {
+            Ok(ty) => serde_json::json!({
+                "ok": true,
+                "type": ty.to_string()
+            }),
+            Err(e) => serde_json::json!({
+                "ok": false,
+                "error": e.to_string(),
+                "span": e.span().map(|span| serde_json::json!({ "start": span.start, "end": span.end })),
+            }),
+        };
+        to_value(&json).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Define `name : type_src := body_src`, or postulate `name` when there
+    /// is no body; a name can be defined only once
+    pub fn define(&mut self, name: String, type_src: String, body_src: Option<String>) -> std::result::Result<(), JsValue> {
+        let ty = parse_type(&type_src).map_err(TypeError::from)?;
+        let body = body_src.map(|source| simp::parse_term(&source)).transpose().map_err(TypeError::from)?;
+        self.definitions.define(&name, ty, body)?;
+        Ok(())
+    }
+
+    /// Remove a definition no later one depends on
+    pub fn undefine(&mut self, name: String) -> std::result::Result<(), JsValue> {
+        self.definitions.undefine(&name)?;
+        Ok(())
+    }
+
+    /// `[{ name, type, body }]` oldest first, with `body` null for postulates
+    pub fn list_definitions(&self) -> std::result::Result<JsValue, JsValue> {
+        let json: Vec<_> = self
+            .definitions
+            .iter()
+            .map(|(name, def)| {
+                serde_json::json!({
+                    "name": name,
+                    "type": def.ty.to_string(),
+                    "body": def.body.as_ref().map(|body| body.to_string()),
+                })
+            })
+            .collect();
+        to_value(&json).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Infer the type of a serialized `Term` against the bindings so far,
+    /// returning `{ ok, type }` or `{ ok: false, error }` like `check`
+    pub fn check_term(&self, term_json: JsValue) -> JsValue {
+        let result = from_value::<Term>(term_json)
+            .map_err(|e| e.to_string())
+            .and_then(|term| crate::check::infer(&self.scope(), &term).map_err(|e| e.to_string()));
+        let json = match result {
+            Ok(ty) => serde_json::json!({ "ok": true, "type": ty.to_string() }),
+            Err(error) => serde_json::json!({ "ok": false, "error": error }),
+        };
+        to_value(&json).unwrap_or_else(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+impl WasmTypeChecker {
+    /// Definitions, then bindings, which shadow them
+    fn scope(&self) -> Vec<(String, Type)> {
+        let mut scope = self.definitions.context();
+        scope.extend(self.context.iter().cloned());
+        scope
+    }
+}
+
+/// Encode a serialized `Term` in the versioned binary format
+#[wasm_bindgen]
+pub fn term_to_bytes(term_json: JsValue) -> std::result::Result<Vec<u8>, JsValue> {
+    let term: Term = from_value(term_json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(term.to_bytes())
+}
+
+/// Decode bytes from `term_to_bytes`; data from another format version is
+/// refused with an error naming both versions
+#[wasm_bindgen]
+pub fn term_from_bytes(bytes: &[u8]) -> std::result::Result<JsValue, JsValue> {
+    let term = Term::from_bytes(bytes).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    to_value(&term).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn derivation_to_js(result: std::result::Result<Derivation, ParseError>) -> std::result::Result<JsValue, JsValue> {
+    let derivation = result.map_err(|e| JsValue::from_str(&e.to_string()))?;
+    to_value(&derivation).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Normalize an interval expression, returning `{ start, steps: [{ rule, before, after }] }`
+#[wasm_bindgen]
+pub fn derive_interval(expr: &str) -> std::result::Result<JsValue, JsValue> {
+    derivation_to_js(simp::derive_interval(expr))
+}
+
+/// Simplify a term, returning the same shape as `derive_interval`
+#[wasm_bindgen]
+pub fn derive_simp(term: &str) -> std::result::Result<JsValue, JsValue> {
+    derivation_to_js(simp::derive_simp(term))
+}
+
+/// The skeleton term for a definition of type `type_src`, e.g.
+/// `λf. λg. λx. ?` for `(ℝ → ℝ) → (ℝ → ℝ) → ℝ → ℝ`
+#[wasm_bindgen]
+pub fn skeleton_for(type_src: &str) -> std::result::Result<String, JsValue> {
+    crate::skeleton::skeleton_for(type_src).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// WASM memory management
+#[wasm_bindgen]
+pub fn wasm_malloc(size: usize) -> *mut u8 {
+    let mut buf = Vec::with_capacity(size);
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+#[wasm_bindgen]
+pub fn wasm_free(ptr: *mut u8, size: usize) {
+    unsafe {
+        let _ = Vec::from_raw_parts(ptr, 0, size);
+    }
+}
+
+// Initialize WASM module
+#[wasm_bindgen(start)]
+pub fn init_wasm() {
+    // Set up panic hook for better error messages in browser
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+// Version info
+#[wasm_bindgen]
+pub fn version() -> String {
+    crate::compat::ENGINE_VERSION.to_string()
+}
@@ -0,0 +1,29 @@
+//! Evaluating at 10k points one call at a time against one batch call.
+//! Natively the two cost about the same; the batch is for JavaScript, where
+//! each call crosses the WASM boundary.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sctt_cubical::{Interval, Path};
+
+const POINTS: usize = 10_000;
+
+fn bench_batch(c: &mut Criterion) {
+    let path = Path::new("t² + sin(π t) / 2".to_string(), "t".to_string()).unwrap();
+    let interval = Interval::Meet(Box::new(Interval::var(0)), Box::new(Interval::Neg(Box::new(Interval::var(0)))));
+    let ts: Vec<f64> = (0..POINTS).map(|k| k as f64 / (POINTS - 1) as f64).collect();
+
+    c.bench_function("path: 10k evaluate calls", |b| {
+        b.iter(|| ts.iter().map(|&t| black_box(&path).evaluate(t)).collect::<Vec<_>>())
+    });
+    c.bench_function("path: evaluate_batch of 10k", |b| b.iter(|| black_box(&path).evaluate_batch(black_box(&ts))));
+    c.bench_function("path: evaluate_range of 10k", |b| {
+        b.iter(|| black_box(&path).evaluate_range(0.0, 1.0, black_box(POINTS)))
+    });
+    c.bench_function("interval: 10k eval calls", |b| {
+        b.iter(|| ts.iter().map(|&t| black_box(&interval).eval(t)).collect::<Vec<_>>())
+    });
+    c.bench_function("interval: eval_batch of 10k", |b| b.iter(|| black_box(&interval).eval_batch(black_box(&ts))));
+}
+
+criterion_group!(benches, bench_batch);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+//! Plotting a path: one `sample` call against a call to `evaluate` per
+//! point. Natively both are cheap; from JavaScript each call crosses the
+//! WASM boundary, so the second pays that cost `n` times over.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sctt_cubical::Path;
+
+const POINTS: usize = 1000;
+
+fn bench_sample(c: &mut Criterion) {
+    let path = Path::new("t² + sin(π t) / 2".to_string(), "t".to_string()).unwrap();
+    println!("{} points: 1 call to sample, {} to evaluate", POINTS + 1, POINTS + 1);
+
+    c.bench_function("sample 1001 points", |b| b.iter(|| black_box(&path).sample(black_box(POINTS))));
+    c.bench_function("evaluate 1001 points", |b| {
+        b.iter(|| (0..=POINTS).map(|k| black_box(&path).evaluate(k as f64 / POINTS as f64)).collect::<Vec<_>>())
+    });
+    c.bench_function("arc length over 1000 pieces", |b| b.iter(|| black_box(&path).arc_length(black_box(POINTS))));
+}
+
+criterion_group!(benches, bench_sample);
+criterion_main!(benches);
@@ -0,0 +1,157 @@
+//! Kan operations on numeric paths
+//!
+//! A path `base` over `i` is the bottom of an open box; sides are paths in
+//! the composition direction `j`, standing on the ends of `base` (the faces
+//! `i = 0` and `i = 1` of variable 0), each starting where `base` is there.
+//! [`hcomp`] gives the lid of the box at height `j = cap_at`: `base` moved
+//! at each end by how far the side there has gone, and in between by the
+//! linear blend of the two, so a missing side leaves its end in place.
+//! [`fill`] gives the lids at every height at once, as a square.
+//! [`transport`] carries a value along a family of paths.
+
+use crate::{in_range, CubicalError, Face, Path, PathExpr, Square, System, EPSILON};
+
+/// Points at which two sides on the same face are compared
+const SAMPLES: usize = 16;
+
+/// The lid at `j = cap_at` of the box with bottom `base` and the given
+/// sides; a side not starting where `base` is at its end is a
+/// [`CubicalError::BoundaryMismatch`] at that end
+pub fn hcomp(base: &Path, sides: &[(Face, Path)], cap_at: f64) -> Result<Path, CubicalError> {
+    let ends = open_box(base, sides)?;
+    let cap_at = in_range(cap_at)?;
+    let [start, end] = ends.map(|side| side.map_or(0.0, |side| side.evaluate(cap_at) - side.evaluate(0.0)));
+    Ok(Path::shifted(base, start, end))
+}
+
+/// The whole box, filled: the square with `base` along its bottom, the
+/// sides up its left and right, and at each height `j` the lid [`hcomp`]
+/// gives there, so its top is the lid at `j = 1`
+pub fn fill(base: &Path, sides: &[(Face, Path)]) -> Result<Square, CubicalError> {
+    let ends = open_box(base, sides)?;
+    let (i, j) = (PathExpr::Coord(0), PathExpr::Coord(1));
+    let weights = [PathExpr::Sub(Box::new(PathExpr::Num(1.0)), Box::new(i.clone())), i.clone()];
+    let lid = ends.iter().zip(weights).fold(base.expr().subst(&i), |lid, (side, weight)| {
+        let Some(side) = side else { return lid };
+        let climbed = PathExpr::Sub(Box::new(side.expr().subst(&j)), Box::new(PathExpr::Num(side.evaluate(0.0))));
+        PathExpr::Add(Box::new(lid), Box::new(PathExpr::Mul(Box::new(weight), Box::new(climbed))))
+    });
+    Ok(Square::from_expr(lid))
+}
+
+/// The sides standing on `i = 0` and `i = 1`, checked to be on those
+/// faces, to agree where they are given twice, and to start on `base`
+fn open_box(base: &Path, sides: &[(Face, Path)]) -> Result<[Option<Path>; 2], CubicalError> {
+    if let Some((face, _)) = sides.iter().find(|(face, _)| !face.implies(&Face::boundary(0))) {
+        return Err(CubicalError::DimensionMismatch { face: face.clone() });
+    }
+    let sides = System::new(sides.to_vec(), |_, a, b| {
+        (0..=SAMPLES).all(|k| {
+            let j = k as f64 / SAMPLES as f64;
+            (a.evaluate(j) - b.evaluate(j)).abs() < EPSILON
+        })
+    })?;
+    let mut ends = [None, None];
+    for (slot, (face, end)) in ends.iter_mut().zip([(Face::Eq0(0), 0.0), (Face::Eq1(0), 1.0)]) {
+        let Some(side) = sides.lookup(&face) else { continue };
+        let (side_start, base_end) = (side.evaluate(0.0), base.evaluate(end));
+        if (side_start - base_end).abs() >= EPSILON {
+            return Err(CubicalError::BoundaryMismatch { expected: base_end, actual: side_start, end });
+        }
+        *slot = Some(side.clone());
+    }
+    Ok(ends)
+}
+
+/// `value`, a point of `family(from)`, carried along the family to
+/// `family(to)`; see [`Path::transport_to`]. The family is indexed by the
+/// interval, so `from` and `to` have to be in it.
+pub fn transport(family: &dyn Fn(f64) -> Path, from: f64, to: f64, value: f64) -> Result<f64, CubicalError> {
+    Ok(family(in_range(from)?).transport_to(&family(in_range(to)?), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(expression: &str) -> Path {
+        Path::new(expression.to_string(), "t".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_composing_refl_with_refl_is_refl() {
+        let refl = path("2");
+        let sides = [(Face::Eq0(0), refl.clone()), (Face::Eq1(0), refl.clone())];
+        for cap_at in [0.0, 0.5, 1.0] {
+            let lid = hcomp(&refl, &sides, cap_at).unwrap();
+            assert!((0..=10).all(|k| (lid.evaluate(k as f64 / 10.0) - 2.0).abs() < EPSILON));
+        }
+    }
+
+    #[test]
+    fn test_lid_meets_the_sides() {
+        // Bottom t from 0 to 1; the side on i = 0 climbs from 0 to 1 as t²
+        let (base, side) = (path("t"), path("t²"));
+        let lid = hcomp(&base, &[(Face::Eq0(0), side.clone())], 1.0).unwrap();
+        assert!(lid.check_boundaries(1.0, 1.0));
+        let halfway = hcomp(&base, &[(Face::Eq0(0), side)], 0.5).unwrap();
+        assert!(halfway.check_boundaries(0.25, 1.0));
+        // At height 0 the lid is the base
+        let flat = hcomp(&base, &[], 0.0).unwrap();
+        assert!((flat.evaluate(0.3) - 0.3).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_transport_along_families() {
+        let constant = |_: f64| path("t²");
+        assert!((transport(&constant, 0.0, 1.0, 0.3).unwrap() - 0.3).abs() < EPSILON);
+        // The segment [s, s + 1]: carrying from 0.25 to 1 moves by 0.75
+        let sliding = |s: f64| Path::shifted(&path("t"), s, s);
+        assert!((transport(&sliding, 0.25, 1.0, 0.5).unwrap() - 1.25).abs() < EPSILON);
+        assert!((transport(&sliding, 1.0, 0.25, 1.25).unwrap() - 0.5).abs() < EPSILON);
+        // Loops have no length to scale by, so values only shift
+        let loops = |s: f64| Path::shifted(&path("0"), 2.0 * s, 2.0 * s);
+        assert!((transport(&loops, 0.0, 1.0, 7.0).unwrap() - 9.0).abs() < EPSILON);
+        assert_eq!(transport(&loops, 0.0, 2.0, 7.0), Err(CubicalError::OutOfRange { value: 2.0 }));
+    }
+
+    #[test]
+    fn test_mismatched_sides_are_reported() {
+        let err = hcomp(&path("t"), &[(Face::Eq1(0), path("t"))], 1.0).unwrap_err();
+        assert_eq!(err, CubicalError::BoundaryMismatch { expected: 1.0, actual: 0.0, end: 1.0 });
+        let err = hcomp(&path("t"), &[(Face::Eq0(1), path("0"))], 1.0).unwrap_err();
+        assert_eq!(err, CubicalError::DimensionMismatch { face: Face::Eq0(1) });
+        let clash = [(Face::Eq0(0), path("t")), (Face::Eq0(0), path("t²"))];
+        assert!(matches!(hcomp(&path("t"), &clash, 1.0), Err(CubicalError::Sides(_))));
+        assert_eq!(hcomp(&path("t"), &[], 1.5).unwrap_err(), CubicalError::OutOfRange { value: 1.5 });
+    }
+
+    #[test]
+    fn test_filling_with_constant_sides_is_constant_upwards() {
+        let base = path("t²");
+        let sides = [(Face::Eq0(0), path("0")), (Face::Eq1(0), path("1"))];
+        let square = fill(&base, &sides).unwrap();
+        for (a, b) in (0..=10).flat_map(|a| (0..=10).map(move |b| (a as f64 / 10.0, b as f64 / 10.0))) {
+            assert!((square.eval(a, b) - base.evaluate(a)).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn test_fill_meets_the_box_and_caps_with_hcomp() {
+        let (base, start, end) = (path("t"), path("t²"), path("1 + sin(t)"));
+        let sides = [(Face::Eq0(0), start.clone()), (Face::Eq1(0), end.clone())];
+        let square = fill(&base, &sides).unwrap();
+        let lid = hcomp(&base, &sides, 1.0).unwrap();
+        let agree = |p: &Path, q: &Path| {
+            (0..=20).all(|k| {
+                let t = k as f64 / 20.0;
+                (p.evaluate(t) - q.evaluate(t)).abs() < EPSILON
+            })
+        };
+        assert!(agree(&square.bottom(), &base) && agree(&square.left(), &start) && agree(&square.right(), &end));
+        assert!(agree(&square.top(), &lid));
+        // Halfway up it is the lid at that height
+        assert!((square.eval(0.3, 0.5) - hcomp(&base, &sides, 0.5).unwrap().evaluate(0.3)).abs() < EPSILON);
+        assert!(matches!(fill(&base, &[(Face::Eq1(0), path("t"))]), Err(CubicalError::BoundaryMismatch { .. })));
+    }
+}
@@ -0,0 +1,199 @@
+//! Cubes of any dimension
+//!
+//! An `n`-cube is a map out of `Iⁿ`: a point for `n = 0`, a [`Path`] for
+//! `n = 1`, a [`Square`] for `n = 2`. A cube is given by an expression in
+//! its `n` coordinates or assembled from another cube by the cubical
+//! operators: [`Cube::face`] fixes a coordinate at an endpoint, dropping a
+//! dimension, and [`Cube::degenerate`] adds a coordinate the cube does not
+//! depend on. Face and degeneracy maps are kept as they are, not folded
+//! into the expression, so a cube remembers how it was assembled.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Homotopy, Path, PathExpr, Square};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CubeError {
+    #[error("a {dims}-cube has no dimension {dim}")]
+    NoSuchDim { dim: usize, dims: usize },
+
+    #[error("a {dims}-cube is evaluated at {dims} coordinates, not {got}")]
+    WrongArity { dims: usize, got: usize },
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cube {
+    dims: usize,
+    shape: Shape,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Shape {
+    /// An expression in the coordinates, [`PathExpr::Coord`] `k` for the
+    /// `k`th
+    Expr(PathExpr),
+    /// `of` with coordinate `dim` fixed at `endpoint`
+    Face { of: Box<Cube>, dim: usize, endpoint: f64 },
+    /// `of` with a coordinate it ignores put in at `dim`
+    Degenerate { of: Box<Cube>, dim: usize },
+}
+
+impl Cube {
+    /// The `dims`-cube computing `expr`; coordinates past `dims` are NaN
+    pub fn from_expr(expr: PathExpr, dims: usize) -> Self {
+        Cube { dims, shape: Shape::Expr(expr) }
+    }
+
+    /// A point
+    pub fn point(value: f64) -> Self {
+        Cube::from_expr(PathExpr::Num(value), 0)
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+
+    /// The value at `point`, one coordinate per dimension
+    pub fn eval(&self, point: &[f64]) -> Result<f64, CubeError> {
+        if point.len() != self.dims {
+            return Err(CubeError::WrongArity { dims: self.dims, got: point.len() });
+        }
+        Ok(self.eval_unchecked(point))
+    }
+
+    fn eval_unchecked(&self, point: &[f64]) -> f64 {
+        match &self.shape {
+            Shape::Expr(expr) => {
+                let clamped: Vec<f64> = point.iter().map(|x| x.clamp(0.0, 1.0)).collect();
+                expr.eval_at(&clamped)
+            }
+            Shape::Face { of, dim, endpoint } => {
+                let mut point = point.to_vec();
+                point.insert(*dim, *endpoint);
+                of.eval_unchecked(&point)
+            }
+            Shape::Degenerate { of, dim } => {
+                let mut point = point.to_vec();
+                point.remove(*dim);
+                of.eval_unchecked(&point)
+            }
+        }
+    }
+
+    /// The `(n - 1)`-cube where coordinate `dim` is at `endpoint`, 0 or 1;
+    /// the coordinates after it move down one
+    pub fn face(&self, dim: usize, endpoint: f64) -> Result<Cube, CubeError> {
+        if dim >= self.dims {
+            return Err(CubeError::NoSuchDim { dim, dims: self.dims });
+        }
+        Ok(Cube { dims: self.dims - 1, shape: Shape::Face { of: Box::new(self.clone()), dim, endpoint } })
+    }
+
+    /// The `(n + 1)`-cube constant in a new coordinate `dim`; the
+    /// coordinates from `dim` on move up one
+    pub fn degenerate(&self, dim: usize) -> Result<Cube, CubeError> {
+        if dim > self.dims {
+            return Err(CubeError::NoSuchDim { dim, dims: self.dims + 1 });
+        }
+        Ok(Cube { dims: self.dims + 1, shape: Shape::Degenerate { of: Box::new(self.clone()), dim } })
+    }
+}
+
+impl From<&Path> for Cube {
+    fn from(path: &Path) -> Self {
+        Cube::from_expr(path.expr().clone(), 1)
+    }
+}
+
+impl From<&Homotopy> for Cube {
+    /// The square `(s, t) ↦ H(s, t)`
+    fn from(homotopy: &Homotopy) -> Self {
+        Cube::from_expr(homotopy.expr().clone(), 2)
+    }
+}
+
+impl From<&Square> for Cube {
+    fn from(square: &Square) -> Self {
+        Cube::from_expr(square.expr(), 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPSILON;
+
+    fn cube(source: &str, names: &[&str]) -> Cube {
+        Cube::from_expr(PathExpr::parse_in(source, names).unwrap(), names.len())
+    }
+
+    /// A grid of points in `[0, 1]ⁿ`
+    fn grid(dims: usize) -> Vec<Vec<f64>> {
+        (0..dims).fold(vec![Vec::new()], |points, _| {
+            points
+                .iter()
+                .flat_map(|point| {
+                    (0..=4).map(move |k| {
+                        let mut point = point.clone();
+                        point.push(k as f64 / 4.0);
+                        point
+                    })
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_face_of_degenerate_is_identity() {
+        let square = cube("i sin(j) + j²", &["i", "j"]);
+        for dim in 0..=2 {
+            for endpoint in [0.0, 1.0] {
+                let back = square.degenerate(dim).unwrap().face(dim, endpoint).unwrap();
+                assert_eq!(back.dims(), 2);
+                for point in grid(2) {
+                    assert!((back.eval(&point).unwrap() - square.eval(&point).unwrap()).abs() < EPSILON);
+                }
+            }
+        }
+        assert_eq!(square.face(2, 0.0).unwrap_err(), CubeError::NoSuchDim { dim: 2, dims: 2 });
+        assert_eq!(square.eval(&[0.5]), Err(CubeError::WrongArity { dims: 2, got: 1 }));
+    }
+
+    #[test]
+    fn test_corners_of_a_3_cube() {
+        let solid = cube("i + 2j + 4k", &["i", "j", "k"]);
+        for corner in grid(3).into_iter().filter(|p| p.iter().all(|&x| x == 0.0 || x == 1.0)) {
+            // Fixing the coordinates one by one, first to last or last to first
+            let forwards = corner.iter().try_fold(solid.clone(), |c, &x| c.face(0, x)).unwrap();
+            let backwards = corner.iter().enumerate().rev().try_fold(solid.clone(), |c, (d, &x)| c.face(d, x)).unwrap();
+            let value = solid.eval(&corner).unwrap();
+            assert_eq!(forwards.eval(&[]), Ok(value));
+            assert_eq!(backwards.eval(&[]), Ok(value));
+        }
+        assert_eq!(solid.eval(&[1.0, 0.0, 1.0]), Ok(5.0));
+    }
+
+    #[test]
+    fn test_squares_and_paths_are_cubes() {
+        let p = Path::new("t² + 1".to_string(), "t".to_string()).unwrap();
+        let line = Cube::from(&p);
+        assert_eq!(line.eval(&[0.5]), Ok(1.25));
+        for square in [Square::connection_meet(&p), Square::connection_join(&p)] {
+            let as_cube = Cube::from(&square);
+            for point in grid(2) {
+                assert!((as_cube.eval(&point).unwrap() - square.eval(point[0], point[1])).abs() < EPSILON);
+            }
+            // The cube's faces are the square's edges
+            let left = as_cube.face(0, 0.0).unwrap();
+            assert!((0..=4).all(|k| {
+                let j = k as f64 / 4.0;
+                (left.eval(&[j]).unwrap() - square.left().evaluate(j)).abs() < EPSILON
+            }));
+        }
+        assert_eq!(line.degenerate(0).unwrap().eval(&[0.3, 0.5]), Ok(1.25));
+    }
+}
@@ -0,0 +1,104 @@
+//! Equivalences between numeric spaces
+//!
+//! The first piece of univalence: an [`Equiv`] is a pair of maps checked,
+//! on sample points, to be inverse to each other, and [`ua`] turns it into
+//! paths, each value of the domain joined by a straight line to its image.
+//! Transport along such a path is the forward map, as it is along the path
+//! `ua` gives in the type theory; Glue types proper are still to come.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Path, PathError, PathExpr};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum EquivError {
+    #[error("backward(forward({at})) is {got}, not {at}")]
+    NotRetraction { at: f64, got: f64 },
+
+    #[error("forward(backward({at})) is {got}, not {at}")]
+    NotSection { at: f64, got: f64 },
+}
+
+/// A map with a claimed inverse, each an expression in one variable
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Equiv {
+    pub forward: PathExpr,
+    pub backward: PathExpr,
+}
+
+impl Equiv {
+    pub fn new(forward: PathExpr, backward: PathExpr) -> Self {
+        Equiv { forward, backward }
+    }
+
+    /// The maps written as expressions in `variable`
+    pub fn parse(forward: &str, backward: &str, variable: &str) -> Result<Self, PathError> {
+        Ok(Equiv::new(PathExpr::parse(forward, variable)?, PathExpr::parse(backward, variable)?))
+    }
+
+    /// Whether each map undoes the other to within `tol` at `samples + 1`
+    /// evenly spaced points of `[0, 1]`; the first point where one does not
+    /// is reported
+    pub fn verify(&self, tol: f64, samples: usize) -> Result<(), EquivError> {
+        let samples = samples.max(1);
+        for k in 0..=samples {
+            let at = k as f64 / samples as f64;
+            // Written so that NaN is never within range
+            let within = |got: f64| (got - at).abs() <= tol;
+            let got = self.backward.eval(self.forward.eval(at));
+            if !within(got) {
+                return Err(EquivError::NotRetraction { at, got });
+            }
+            let got = self.forward.eval(self.backward.eval(at));
+            if !within(got) {
+                return Err(EquivError::NotSection { at, got });
+            }
+        }
+        Ok(())
+    }
+
+    /// The equivalence the other way round
+    pub fn inverse(&self) -> Equiv {
+        Equiv::new(self.backward.clone(), self.forward.clone())
+    }
+}
+
+/// The path `ua(equiv)` gives `value`: the straight line from `value` to
+/// `forward(value)`
+pub fn ua(equiv: &Equiv, value: f64) -> Path {
+    let image = equiv.forward.eval(value);
+    let weighted = |weight: PathExpr, by: f64| Box::new(PathExpr::Mul(Box::new(weight), Box::new(PathExpr::Num(by))));
+    let staying = PathExpr::Sub(Box::new(PathExpr::Num(1.0)), Box::new(PathExpr::Param));
+    Path::from_expr(PathExpr::Add(weighted(staying, value), weighted(PathExpr::Param, image)), "t".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPSILON;
+
+    #[test]
+    fn test_involution_is_an_equivalence() {
+        let flip = Equiv::parse("1 - x", "1 - x", "x").unwrap();
+        assert_eq!(flip.verify(EPSILON, 16), Ok(()));
+        let line = ua(&flip, 0.25);
+        assert!(line.check_boundaries(0.25, 0.75));
+        assert!((line.evaluate(0.5) - 0.5).abs() < EPSILON);
+        // Transport along ua is the forward map
+        assert!((line.transport_to(&Path::refl(0.75), 0.25) - 0.75).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_non_invertible_map_is_reported() {
+        let squaring = Equiv::parse("x²", "x", "x").unwrap();
+        assert_eq!(squaring.verify(EPSILON, 4), Err(EquivError::NotRetraction { at: 0.25, got: 0.0625 }));
+        // Halving is undone by doubling, but misses everything above 1/2
+        let halving = Equiv::parse("x / 2", "if(x ≤ 0.5, 2x, 1)", "x").unwrap();
+        assert_eq!(halving.verify(EPSILON, 4), Err(EquivError::NotSection { at: 0.75, got: 0.5 }));
+        // √ undoes squaring on [0, 1]
+        assert_eq!(Equiv::parse("x²", "sqrt(x)", "x").unwrap().verify(1e-9, 64), Ok(()));
+    }
+}
@@ -0,0 +1,528 @@
+//! Path expressions
+//!
+//! A path is written as an expression in its parameter, in the language of
+//! [`sctt_smooth::expr`]: numbers, `π`, the parameter, `+ - * / ^`, postfix
+//! `²` `³`, implicit multiplication (`2t`, `3(t + 1)`), `sin cos tan exp ln
+//! sqrt`, `min`/`max` (or `∧`/`∨`), and `if(e ≤ c, a, b)`, which is `a`
+//! where `e` is at most the number `c` and `b` elsewhere, as concatenated
+//! paths are written. [`PathExpr::parse`] reads one with that parser,
+//! rejecting any name but the parameter and taking `min` and `max` to the
+//! cases on which argument is the lesser, and [`PathExpr::eval`] computes
+//! it. Cubes are written the same way in several parameters, read by
+//! [`PathExpr::parse_in`].
+
+use std::f64::consts::PI;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sctt_smooth::expr::{self, Expr, ExprError};
+use thiserror::Error;
+
+pub use sctt_smooth::expr::Func;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PathError {
+    #[error("{message} at position {position}")]
+    Parse { position: usize, message: String },
+
+    #[error("unknown variable `{name}`; expected `{parameter}`")]
+    UnknownVariable { name: String, parameter: String },
+}
+
+pub type Result<T> = std::result::Result<T, PathError>;
+
+impl From<ExprError> for PathError {
+    fn from(error: ExprError) -> Self {
+        match error {
+            ExprError::Parse { position, message } => PathError::Parse { position, message },
+            ExprError::UnknownVariable { name, variable } => PathError::UnknownVariable { name, parameter: variable },
+        }
+    }
+}
+
+/// An expression in the path's parameter
+///
+/// As JSON each node is an object naming its operator in `op` and holding
+/// its operands in `args`; this is part of the versioned schema of
+/// [`Path`](crate::Path), so it only changes with the version:
+///
+/// ```text
+/// {"op": "num", "args": 2.5}
+/// {"op": "param"}
+/// {"op": "coord", "args": 1}
+/// {"op": "neg", "args": e}
+/// {"op": "add", "args": [a, b]}          and likewise sub, mul, div, pow
+/// {"op": "call", "args": ["sin", e]}     and likewise cos, tan, exp, ln, sqrt
+/// {"op": "cases", "args": {"test": e, "at": 0.5, "below": a, "above": b}}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "op", content = "args", rename_all = "snake_case"))]
+pub enum PathExpr {
+    Num(f64),
+    /// The parameter, whatever it is called
+    Param,
+    /// The `k`th parameter of an expression in several; `Param` is the
+    /// 0th
+    Coord(usize),
+    Neg(Box<PathExpr>),
+    Add(Box<PathExpr>, Box<PathExpr>),
+    Sub(Box<PathExpr>, Box<PathExpr>),
+    Mul(Box<PathExpr>, Box<PathExpr>),
+    Div(Box<PathExpr>, Box<PathExpr>),
+    Pow(Box<PathExpr>, Box<PathExpr>),
+    Call(Func, Box<PathExpr>),
+    /// `below` where `test ≤ at`, `above` elsewhere
+    Cases { test: Box<PathExpr>, at: f64, below: Box<PathExpr>, above: Box<PathExpr> },
+}
+
+impl PathExpr {
+    /// Parse `source`, in which `parameter` is the only name allowed
+    pub fn parse(source: &str, parameter: &str) -> Result<PathExpr> {
+        PathExpr::read(&expr::parse(source)?, &[parameter], false)
+    }
+
+    /// Parse `source` in the parameters `names`, each read as its
+    /// [`PathExpr::Coord`]
+    pub fn parse_in(source: &str, names: &[&str]) -> Result<PathExpr> {
+        PathExpr::read(&expr::parse(source)?, names, true)
+    }
+
+    /// `expr` with `names` for the parameters, read as [`PathExpr::Coord`]s
+    /// when `coords`
+    fn read(expr: &Expr, names: &[&str], coords: bool) -> Result<PathExpr> {
+        let go = |e: &Expr| PathExpr::read(e, names, coords).map(Box::new);
+        Ok(match expr {
+            Expr::Num(n) => PathExpr::Num(*n),
+            Expr::Var(name) => match names.iter().position(|&parameter| parameter == name.as_str()) {
+                Some(k) if coords => PathExpr::Coord(k),
+                Some(_) => PathExpr::Param,
+                None => return Err(PathError::UnknownVariable { name: name.clone(), parameter: names.join(", ") }),
+            },
+            Expr::Neg(a) => PathExpr::Neg(go(a)?),
+            Expr::Add(a, b) => PathExpr::Add(go(a)?, go(b)?),
+            Expr::Sub(a, b) => PathExpr::Sub(go(a)?, go(b)?),
+            Expr::Mul(a, b) => PathExpr::Mul(go(a)?, go(b)?),
+            Expr::Div(a, b) => PathExpr::Div(go(a)?, go(b)?),
+            Expr::Pow(a, b) => PathExpr::Pow(go(a)?, go(b)?),
+            Expr::Call(func, a) => PathExpr::Call(*func, go(a)?),
+            Expr::Min(a, b) => {
+                PathExpr::Cases { test: Box::new(PathExpr::Sub(go(a)?, go(b)?)), at: 0.0, below: go(a)?, above: go(b)? }
+            }
+            Expr::Max(a, b) => {
+                PathExpr::Cases { test: Box::new(PathExpr::Sub(go(a)?, go(b)?)), at: 0.0, below: go(b)?, above: go(a)? }
+            }
+            Expr::Cases { test, at, below, above } => {
+                PathExpr::Cases { test: go(test)?, at: *at, below: go(below)?, above: go(above)? }
+            }
+        })
+    }
+
+    /// The value with the parameter at `t`
+    pub fn eval(&self, t: f64) -> f64 {
+        self.eval_at(&[t])
+    }
+
+    /// The value with the `k`th parameter at `coords[k]`; one missing is
+    /// taken to be NaN
+    pub fn eval_at(&self, coords: &[f64]) -> f64 {
+        let eval = |e: &PathExpr| e.eval_at(coords);
+        match self {
+            PathExpr::Num(n) => *n,
+            PathExpr::Param => coords.first().copied().unwrap_or(f64::NAN),
+            PathExpr::Coord(k) => coords.get(*k).copied().unwrap_or(f64::NAN),
+            PathExpr::Neg(a) => -eval(a),
+            PathExpr::Add(a, b) => eval(a) + eval(b),
+            PathExpr::Sub(a, b) => eval(a) - eval(b),
+            PathExpr::Mul(a, b) => eval(a) * eval(b),
+            PathExpr::Div(a, b) => eval(a) / eval(b),
+            PathExpr::Pow(a, b) => {
+                let (base, exponent) = (eval(a), eval(b));
+                // Integer powers stay defined for negative bases
+                if exponent.fract() == 0.0 && exponent.abs() <= i32::MAX as f64 {
+                    base.powi(exponent as i32)
+                } else {
+                    base.powf(exponent)
+                }
+            }
+            PathExpr::Call(func, a) => func.apply(eval(a)),
+            PathExpr::Cases { test, at, below, above } => {
+                if eval(test) <= *at {
+                    eval(below)
+                } else {
+                    eval(above)
+                }
+            }
+        }
+    }
+
+    /// The expression with `with` put in place of the parameter
+    pub fn subst(&self, with: &PathExpr) -> PathExpr {
+        let go = |a: &PathExpr| Box::new(a.subst(with));
+        match self {
+            PathExpr::Num(_) | PathExpr::Coord(_) => self.clone(),
+            PathExpr::Param => with.clone(),
+            PathExpr::Neg(a) => PathExpr::Neg(go(a)),
+            PathExpr::Add(a, b) => PathExpr::Add(go(a), go(b)),
+            PathExpr::Sub(a, b) => PathExpr::Sub(go(a), go(b)),
+            PathExpr::Mul(a, b) => PathExpr::Mul(go(a), go(b)),
+            PathExpr::Div(a, b) => PathExpr::Div(go(a), go(b)),
+            PathExpr::Pow(a, b) => PathExpr::Pow(go(a), go(b)),
+            PathExpr::Call(func, a) => PathExpr::Call(*func, go(a)),
+            PathExpr::Cases { test, at, below, above } => {
+                PathExpr::Cases { test: go(test), at: *at, below: go(below), above: go(above) }
+            }
+        }
+    }
+
+    /// The expression with `with` put in place of the `k`th parameter
+    pub fn subst_coord(&self, k: usize, with: &PathExpr) -> PathExpr {
+        let go = |a: &PathExpr| Box::new(a.subst_coord(k, with));
+        match self {
+            PathExpr::Coord(j) if *j == k => with.clone(),
+            PathExpr::Param if k == 0 => with.clone(),
+            PathExpr::Num(_) | PathExpr::Param | PathExpr::Coord(_) => self.clone(),
+            PathExpr::Neg(a) => PathExpr::Neg(go(a)),
+            PathExpr::Add(a, b) => PathExpr::Add(go(a), go(b)),
+            PathExpr::Sub(a, b) => PathExpr::Sub(go(a), go(b)),
+            PathExpr::Mul(a, b) => PathExpr::Mul(go(a), go(b)),
+            PathExpr::Div(a, b) => PathExpr::Div(go(a), go(b)),
+            PathExpr::Pow(a, b) => PathExpr::Pow(go(a), go(b)),
+            PathExpr::Call(func, a) => PathExpr::Call(*func, go(a)),
+            PathExpr::Cases { test, at, below, above } => {
+                PathExpr::Cases { test: go(test), at: *at, below: go(below), above: go(above) }
+            }
+        }
+    }
+
+    /// The expression run backwards: `1 - t` for the parameter
+    pub fn reverse(&self) -> PathExpr {
+        self.reparam(&PathExpr::Sub(Box::new(PathExpr::Num(1.0)), Box::new(PathExpr::Param)))
+    }
+
+    /// The expression run at the pace `g`, a map of `[0, 1]` onto itself
+    /// that never turns back, put in for the parameter
+    pub fn reparam(&self, g: &PathExpr) -> PathExpr {
+        self.subst(g)
+    }
+
+    /// The expression run along `3t² - 2t³`, so it sets off and arrives at
+    /// rest
+    pub fn smoothstep(&self) -> PathExpr {
+        self.reparam(&smoothstep_of(PathExpr::Param))
+    }
+
+    /// The expression with constants folded and the obvious identities
+    /// applied: `0 + e`, `e - 0`, `1 * e`, `e / 1`, `e^1`, `--e` and
+    /// `a - (b - e) = (a - b) + e`, which takes `1 - (1 - t)` back to `t`.
+    /// Nothing that could turn an undefined value into a defined one, such
+    /// as `0 * e = 0`, is applied.
+    pub fn simplify(&self) -> PathExpr {
+        use PathExpr::{Add, Call, Cases, Div, Mul, Neg, Num, Pow, Sub};
+        let go = |a: &PathExpr| a.simplify();
+        let folded = match self {
+            Num(_) | PathExpr::Param | PathExpr::Coord(_) => return self.clone(),
+            Neg(a) => match go(a) {
+                Neg(inner) => *inner,
+                a => Neg(Box::new(a)),
+            },
+            Add(a, b) => match (go(a), go(b)) {
+                (Num(0.0), e) | (e, Num(0.0)) => e,
+                (a, b) => Add(Box::new(a), Box::new(b)),
+            },
+            Sub(a, b) => match (go(a), go(b)) {
+                (e, Num(0.0)) => e,
+                (Num(a), Sub(b, e)) => match *b {
+                    Num(b) => Add(Box::new(Num(a - b)), e).simplify(),
+                    b => Sub(Box::new(Num(a)), Box::new(Sub(Box::new(b), e))),
+                },
+                (a, b) => Sub(Box::new(a), Box::new(b)),
+            },
+            Mul(a, b) => match (go(a), go(b)) {
+                (Num(1.0), e) | (e, Num(1.0)) => e,
+                (a, b) => Mul(Box::new(a), Box::new(b)),
+            },
+            Div(a, b) => match (go(a), go(b)) {
+                (e, Num(1.0)) => e,
+                (a, b) => Div(Box::new(a), Box::new(b)),
+            },
+            Pow(a, b) => match (go(a), go(b)) {
+                (e, Num(1.0)) => e,
+                (a, b) => Pow(Box::new(a), Box::new(b)),
+            },
+            Call(func, a) => Call(*func, Box::new(go(a))),
+            Cases { test, at, below, above } => match go(test) {
+                Num(n) if n <= *at => go(below),
+                Num(_) => go(above),
+                test => Cases { test: Box::new(test), at: *at, below: Box::new(go(below)), above: Box::new(go(above)) },
+            },
+        };
+        // Whatever has only numbers left in it is a number
+        if folded.is_closed() {
+            Num(folded.eval_at(&[]))
+        } else {
+            folded
+        }
+    }
+
+    /// The values of the parameter in `[0, 1]` at which a
+    /// [`PathExpr::Cases`] testing an affine function of the parameter, as
+    /// concatenations do, switches branch; ascending, without repeats
+    pub fn junctions(&self) -> Vec<f64> {
+        let mut found = Vec::new();
+        self.collect_junctions(&mut found);
+        found.sort_by(f64::total_cmp);
+        found.dedup();
+        found
+    }
+
+    fn collect_junctions(&self, found: &mut Vec<f64>) {
+        match self {
+            PathExpr::Num(_) | PathExpr::Param | PathExpr::Coord(_) => {}
+            PathExpr::Neg(a) | PathExpr::Call(_, a) => a.collect_junctions(found),
+            PathExpr::Add(a, b)
+            | PathExpr::Sub(a, b)
+            | PathExpr::Mul(a, b)
+            | PathExpr::Div(a, b)
+            | PathExpr::Pow(a, b) => {
+                a.collect_junctions(found);
+                b.collect_junctions(found);
+            }
+            PathExpr::Cases { test, at, below, above } => {
+                if let Some((a, b)) = test.affine().filter(|(a, _)| *a != 0.0) {
+                    let t = (at - b) / a;
+                    if (0.0..=1.0).contains(&t) {
+                        found.push(t);
+                    }
+                }
+                for part in [test, below, above] {
+                    part.collect_junctions(found);
+                }
+            }
+        }
+    }
+
+    /// `(a, b)` when the expression is `a·t + b` in its parameter `t`, as
+    /// far as can be seen from its shape
+    fn affine(&self) -> Option<(f64, f64)> {
+        match self {
+            PathExpr::Num(n) => Some((0.0, *n)),
+            PathExpr::Param => Some((1.0, 0.0)),
+            PathExpr::Neg(a) => a.affine().map(|(a, b)| (-a, -b)),
+            PathExpr::Add(x, y) | PathExpr::Sub(x, y) => {
+                let ((a, b), (c, d)) = (x.affine()?, y.affine()?);
+                let sign = if matches!(self, PathExpr::Add(..)) { 1.0 } else { -1.0 };
+                Some((a + sign * c, b + sign * d))
+            }
+            PathExpr::Mul(x, y) => match (x.affine()?, y.affine()?) {
+                ((0.0, k), (a, b)) | ((a, b), (0.0, k)) => Some((k * a, k * b)),
+                _ => None,
+            },
+            PathExpr::Div(x, y) => match (x.affine()?, y.affine()?) {
+                ((a, b), (0.0, k)) if k != 0.0 => Some((a / k, b / k)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether no parameter occurs
+    fn is_closed(&self) -> bool {
+        match self {
+            PathExpr::Num(_) => true,
+            PathExpr::Param | PathExpr::Coord(_) => false,
+            PathExpr::Neg(a) | PathExpr::Call(_, a) => a.is_closed(),
+            PathExpr::Add(a, b)
+            | PathExpr::Sub(a, b)
+            | PathExpr::Mul(a, b)
+            | PathExpr::Div(a, b)
+            | PathExpr::Pow(a, b) => a.is_closed() && b.is_closed(),
+            PathExpr::Cases { test, below, above, .. } => test.is_closed() && below.is_closed() && above.is_closed(),
+        }
+    }
+
+    /// Written out with `parameter` for the parameter, parenthesised only
+    /// where needed
+    pub fn display<'a>(&'a self, parameter: &'a str) -> impl fmt::Display + 'a {
+        Shown { expr: self, parameter, names: &[] }
+    }
+
+    /// Written out with `names[k]` for the `k`th parameter
+    pub fn display_in<'a>(&'a self, names: &'a [&'a str]) -> impl fmt::Display + 'a {
+        Shown { expr: self, parameter: names.first().copied().unwrap_or("t"), names }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            PathExpr::Add(..) | PathExpr::Sub(..) => 1,
+            PathExpr::Mul(..) | PathExpr::Div(..) => 2,
+            PathExpr::Neg(_) => 3,
+            PathExpr::Pow(..) => 4,
+            PathExpr::Num(n) if *n < 0.0 => 3,
+            PathExpr::Num(_) | PathExpr::Param | PathExpr::Coord(_) | PathExpr::Call(..) | PathExpr::Cases { .. } => 5,
+        }
+    }
+}
+
+/// `u²(3 - 2u)`, the smoothstep `3u² - 2u³` of `u`
+pub(crate) fn smoothstep_of(u: PathExpr) -> PathExpr {
+    let twice = PathExpr::Mul(Box::new(PathExpr::Num(2.0)), Box::new(u.clone()));
+    let rise = PathExpr::Sub(Box::new(PathExpr::Num(3.0)), Box::new(twice));
+    PathExpr::Mul(Box::new(PathExpr::Pow(Box::new(u), Box::new(PathExpr::Num(2.0)))), Box::new(rise))
+}
+
+#[derive(Clone, Copy)]
+struct Shown<'a> {
+    expr: &'a PathExpr,
+    parameter: &'a str,
+    names: &'a [&'a str],
+}
+
+impl fmt::Display for Shown<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Parenthesise a child binding looser than `min`
+        let child = |f: &mut fmt::Formatter, e: &PathExpr, min: u8| {
+            let shown = Shown { expr: e, ..*self };
+            if e.precedence() < min { write!(f, "({})", shown) } else { write!(f, "{}", shown) }
+        };
+        let binary = |f: &mut fmt::Formatter, a: &PathExpr, op: &str, b: &PathExpr, prec: u8| {
+            child(f, a, prec)?;
+            write!(f, " {} ", op)?;
+            // Left-associative: a right operand at the same level needs parens
+            child(f, b, prec + 1)
+        };
+        match self.expr {
+            PathExpr::Num(n) if *n == PI => write!(f, "π"),
+            PathExpr::Num(n) => write!(f, "{}", n),
+            PathExpr::Param => write!(f, "{}", self.parameter),
+            PathExpr::Coord(k) => match self.names.get(*k) {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "x{}", k),
+            },
+            PathExpr::Neg(a) => {
+                write!(f, "-")?;
+                child(f, a, 3)
+            }
+            PathExpr::Add(a, b) => binary(f, a, "+", b, 1),
+            PathExpr::Sub(a, b) => binary(f, a, "-", b, 1),
+            PathExpr::Mul(a, b) => binary(f, a, "*", b, 2),
+            PathExpr::Div(a, b) => binary(f, a, "/", b, 2),
+            PathExpr::Pow(a, b) => {
+                child(f, a, 5)?;
+                write!(f, "^")?;
+                child(f, b, 4)
+            }
+            PathExpr::Call(func, a) => write!(f, "{}({})", func.name(), Shown { expr: a, ..*self }),
+            PathExpr::Cases { test, at, below, above } => {
+                let shown = |expr| Shown { expr, ..*self };
+                write!(f, "if({} ≤ {}, {}, {})", shown(test), at, shown(below), shown(above))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> PathExpr {
+        PathExpr::parse(source, "t").unwrap()
+    }
+
+    fn shown(source: &str) -> String {
+        parse(source).display("t").to_string()
+    }
+
+    #[test]
+    fn test_precedence_and_unary_minus() {
+        assert_eq!(shown("1 + 2 * t^2"), "1 + 2 * t^2");
+        assert_eq!(shown("(1 + 2) * t"), "(1 + 2) * t");
+        assert_eq!(shown("2t² - 3(t - 1)"), "2 * t^2 - 3 * (t - 1)");
+        assert_eq!(shown("1 - (t - 1)"), "1 - (t - 1)");
+        assert_eq!(shown("2^3^t"), "2^3^t");
+        assert_eq!(parse("2^3^2").eval(0.0), 512.0);
+        // Unary minus binds looser than `^` and tighter than `*`
+        assert_eq!(parse("-t^2").eval(3.0), -9.0);
+        assert_eq!(parse("-t * 2").eval(3.0), -6.0);
+        assert_eq!(parse("2 - -t").eval(3.0), 5.0);
+        assert_eq!(parse("(-2)^3").eval(0.0), -8.0);
+    }
+
+    #[test]
+    fn test_functions_and_constants() {
+        let e = parse("π * t * (3 - 2*t)");
+        assert!((e.eval(0.5) - PI).abs() < 1e-12);
+        assert!((e.eval(1.0) - PI).abs() < 1e-12);
+        assert!((parse("t + sin(t)").eval(1.0) - (1.0 + 1.0f64.sin())).abs() < 1e-12);
+        assert!((parse("exp(t) + sqrt(t) - cos(pi t)").eval(4.0) - (4.0f64.exp() + 2.0 - 1.0)).abs() < 1e-9);
+        assert_eq!(parse("2*t").eval(0.25), 0.5);
+        assert_eq!(parse("t").subst(&parse("1 - t")).display("s").to_string(), "1 - s");
+        let halves = parse("if(2t ≤ 1, 0, t)");
+        assert_eq!((halves.eval(0.5), halves.eval(0.75)), (0.0, 0.75));
+        assert_eq!(shown("if(2t ≤ 1, 0, t)"), "if(2 * t ≤ 1, 0, t)");
+        let plane = PathExpr::parse_in("i + 2j", &["i", "j"]).unwrap();
+        assert_eq!(plane.eval_at(&[1.0, 0.5]), 2.0);
+        assert_eq!(plane.display_in(&["x", "y"]).to_string(), "x + 2 * y");
+        assert!(matches!(PathExpr::parse_in("i + k", &["i", "j"]), Err(PathError::UnknownVariable { .. })));
+        let tent = parse("t ∧ (1 - t)");
+        assert_eq!(tent.display("t").to_string(), "if(t - (1 - t) ≤ 0, t, 1 - t)");
+        assert_eq!((tent.eval(0.25), tent.eval(0.75), tent.junctions()), (0.25, 0.25, vec![0.5]));
+        assert!(parse("ln(tan(t))").eval(PI / 4.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_reversal_and_reparameterization() {
+        let e = parse("t² + sin(π t) / 2");
+        assert_eq!(e.reverse().reverse().simplify(), e.simplify());
+        assert_eq!(parse("2 * 3 + t * 1 - 0").simplify(), parse("6 + t"));
+        assert_eq!(parse("if(2 ≤ 1, t, 1 - (1 - t))").simplify(), PathExpr::Param);
+        // Undefined stays undefined
+        assert!(parse("0 * sqrt(t - 2)").simplify().eval(0.5).is_nan());
+
+        for k in 0..=10 {
+            let t = k as f64 / 10.0;
+            assert!((e.reverse().eval(t) - e.eval(1.0 - t)).abs() < 1e-12);
+            assert!((e.reparam(&parse("t²")).eval(t) - e.eval(t * t)).abs() < 1e-12);
+            let eased = 3.0 * t * t - 2.0 * t * t * t;
+            assert!((e.smoothstep().eval(t) - e.eval(eased)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_unparsable_expressions_are_errors() {
+        assert!(matches!(PathExpr::parse("t +", "t"), Err(PathError::Parse { position: 3, .. })));
+        assert!(matches!(PathExpr::parse("sin t", "t"), Err(PathError::Parse { .. })));
+        assert!(matches!(PathExpr::parse("t $ 2", "t"), Err(PathError::Parse { position: 2, .. })));
+        assert_eq!(
+            PathExpr::parse("x + 1", "t"),
+            Err(PathError::UnknownVariable { name: "x".to_string(), parameter: "t".to_string() })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_every_node_round_trips_through_json() {
+        let (t, x) = (Box::new(PathExpr::Param), Box::new(PathExpr::Coord(1)));
+        let mut nodes = vec![
+            PathExpr::Num(2.5),
+            PathExpr::Param,
+            PathExpr::Coord(1),
+            PathExpr::Neg(t.clone()),
+            PathExpr::Add(t.clone(), x.clone()),
+            PathExpr::Sub(t.clone(), x.clone()),
+            PathExpr::Mul(t.clone(), x.clone()),
+            PathExpr::Div(t.clone(), x.clone()),
+            PathExpr::Pow(t.clone(), x.clone()),
+            PathExpr::Cases { test: t.clone(), at: 0.5, below: x.clone(), above: Box::new(PathExpr::Num(1.0)) },
+        ];
+        let funcs = [Func::Sin, Func::Cos, Func::Tan, Func::Exp, Func::Ln, Func::Sqrt];
+        nodes.extend(funcs.map(|f| PathExpr::Call(f, t.clone())));
+        for node in nodes {
+            let json = serde_json::to_string(&node).unwrap();
+            assert_eq!(serde_json::from_str::<PathExpr>(&json).unwrap(), node, "{json}");
+        }
+        let json = serde_json::to_value(PathExpr::Call(Func::Cos, x)).unwrap();
+        assert_eq!(json, serde_json::json!({"op": "call", "args": ["cos", {"op": "coord", "args": 1}]}));
+    }
+}
@@ -0,0 +1,223 @@
+//! Face formulas: where a partial element is defined
+//!
+//! A face is built from `i = 0` and `i = 1` with `∧` and `∨`. Faces are
+//! compared through their disjunctive form, a list of conjunctions each
+//! fixing some variables to endpoints; a conjunction that fixes a variable
+//! both ways is empty and is dropped. Interval variables range over the
+//! whole interval, so `i = 0 ∨ i = 1` is not `⊤`.
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Interval, IntervalValue};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Face {
+    /// `i = 0`
+    Eq0(usize),
+    /// `i = 1`
+    Eq1(usize),
+    And(Box<Face>, Box<Face>),
+    Or(Box<Face>, Box<Face>),
+    Top,
+    Bot,
+}
+
+/// The endpoint each fixed variable is at; `true` for `1`
+type Conjunction = BTreeMap<usize, bool>;
+
+impl Face {
+    /// `i = 0 ∨ i = 1`, the ends of the `i` direction
+    pub fn boundary(var: usize) -> Self {
+        Face::Or(Box::new(Face::Eq0(var)), Box::new(Face::Eq1(var)))
+    }
+
+    pub fn and(self, other: Face) -> Self {
+        Face::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Face) -> Self {
+        Face::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Whether some point of the cube lies on the face
+    pub fn is_satisfiable(&self) -> bool {
+        !self.conjunctions().is_empty()
+    }
+
+    /// Whether every point on `self` is on `other`
+    pub fn implies(&self, other: &Face) -> bool {
+        self.conjunctions().iter().all(|fixed| other.holds(fixed))
+    }
+
+    /// The disjunctive form, with contradictions and conjunctions implied
+    /// by others removed; a face with no points is [`Face::Bot`]
+    pub fn simplify(&self) -> Face {
+        let atom = |(&var, &at_one): (&usize, &bool)| if at_one { Face::Eq1(var) } else { Face::Eq0(var) };
+        self.conjunctions()
+            .iter()
+            .map(|fixed| fixed.iter().map(atom).reduce(Face::and).unwrap_or(Face::Top))
+            .reduce(Face::or)
+            .unwrap_or(Face::Bot)
+    }
+
+    /// `interval` with each variable the face fixes replaced by its
+    /// endpoint, in normal form. A variable is fixed only if it is at the
+    /// same endpoint on every part of the face, so on `i = 0 ∨ i = 1`
+    /// nothing is replaced.
+    pub fn restrict(&self, interval: &Interval) -> Interval {
+        substitute(interval, &self.fixed()).normalize()
+    }
+
+    /// `self` as seen from inside `face`: each variable `face` fixes, as
+    /// for [`Face::restrict`], replaced by its endpoint, so `i = 0` is `⊤`
+    /// on `i = 0` and `⊥` on `i = 1`
+    pub fn on(&self, face: &Face) -> Face {
+        self.assume(&face.fixed()).simplify()
+    }
+
+    /// The variables at the same endpoint on every part of the face
+    fn fixed(&self) -> Conjunction {
+        let conjunctions = self.conjunctions();
+        let Some((first, rest)) = conjunctions.split_first() else {
+            return Conjunction::new();
+        };
+        first
+            .iter()
+            .filter(|(var, at_one)| rest.iter().all(|other| other.get(var) == Some(at_one)))
+            .map(|(&var, &at_one)| (var, at_one))
+            .collect()
+    }
+
+    fn assume(&self, fixed: &Conjunction) -> Face {
+        let atom = |var: &usize, at_one: bool, face: &Face| match fixed.get(var) {
+            Some(&at) if at == at_one => Face::Top,
+            Some(_) => Face::Bot,
+            None => face.clone(),
+        };
+        match self {
+            Face::Eq0(var) => atom(var, false, self),
+            Face::Eq1(var) => atom(var, true, self),
+            Face::Top | Face::Bot => self.clone(),
+            Face::And(a, b) => a.assume(fixed).and(b.assume(fixed)),
+            Face::Or(a, b) => a.assume(fixed).or(b.assume(fixed)),
+        }
+    }
+
+    fn conjunctions(&self) -> Vec<Conjunction> {
+        let conjunctions = match self {
+            Face::Eq0(var) => vec![Conjunction::from([(*var, false)])],
+            Face::Eq1(var) => vec![Conjunction::from([(*var, true)])],
+            Face::Top => vec![Conjunction::new()],
+            Face::Bot => Vec::new(),
+            Face::Or(a, b) => a.conjunctions().into_iter().chain(b.conjunctions()).collect(),
+            Face::And(a, b) => {
+                let right = b.conjunctions();
+                a.conjunctions()
+                    .iter()
+                    .flat_map(|x| right.iter().filter_map(move |y| merge(x, y)))
+                    .collect()
+            }
+        };
+        absorb(conjunctions)
+    }
+
+    /// Whether the face holds where exactly the variables in `fixed` are
+    /// at their endpoints
+    fn holds(&self, fixed: &Conjunction) -> bool {
+        match self {
+            Face::Eq0(var) => fixed.get(var) == Some(&false),
+            Face::Eq1(var) => fixed.get(var) == Some(&true),
+            Face::Top => true,
+            Face::Bot => false,
+            Face::And(a, b) => a.holds(fixed) && b.holds(fixed),
+            Face::Or(a, b) => a.holds(fixed) || b.holds(fixed),
+        }
+    }
+}
+
+/// Both conjunctions at once, unless they fix a variable differently
+fn merge(x: &Conjunction, y: &Conjunction) -> Option<Conjunction> {
+    let mut merged = x.clone();
+    for (&var, &at_one) in y {
+        if *merged.entry(var).or_insert(at_one) != at_one {
+            return None;
+        }
+    }
+    Some(merged)
+}
+
+/// Drop duplicates and conjunctions that fix more than another one does
+fn absorb(mut conjunctions: Vec<Conjunction>) -> Vec<Conjunction> {
+    conjunctions.sort();
+    conjunctions.dedup();
+    let weaker = |c: &Conjunction, d: &Conjunction| c != d && c.iter().all(|(var, at)| d.get(var) == Some(at));
+    conjunctions.iter().filter(|d| !conjunctions.iter().any(|c| weaker(c, d))).cloned().collect()
+}
+
+fn substitute(interval: &Interval, fixed: &Conjunction) -> Interval {
+    let go = |i: &Interval| Box::new(substitute(i, fixed));
+    match interval {
+        Interval::Value(IntervalValue::Var(var)) => match fixed.get(var) {
+            Some(true) => Interval::one(),
+            Some(false) => Interval::zero(),
+            None => interval.clone(),
+        },
+        Interval::Value(_) => interval.clone(),
+        Interval::Meet(i, j) => Interval::Meet(go(i), go(j)),
+        Interval::Join(i, j) => Interval::Join(go(i), go(j)),
+        Interval::Neg(i) => Interval::Neg(go(i)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contradictions_collapse_to_bot() {
+        let clash = Face::Eq0(0).and(Face::Eq1(0));
+        assert!(!clash.is_satisfiable());
+        assert_eq!(clash.simplify(), Face::Bot);
+        // An empty face implies anything, and is implied only by empty faces
+        assert!(clash.implies(&Face::Eq1(3)));
+        assert!(!Face::Eq0(0).implies(&clash));
+        // Dropped from a disjunction, it leaves the rest
+        assert_eq!(clash.or(Face::Eq1(1)).simplify(), Face::Eq1(1));
+        assert_eq!(Face::Eq0(1).or(Face::Eq0(1).and(Face::Eq1(2))).simplify(), Face::Eq0(1));
+    }
+
+    #[test]
+    fn test_boundary_face() {
+        let ends = Face::boundary(0);
+        assert!(ends.is_satisfiable());
+        assert!(Face::Eq0(0).implies(&ends) && Face::Eq1(0).implies(&ends));
+        assert!(ends.implies(&Face::Top) && !Face::Top.implies(&ends));
+        assert!(!ends.implies(&Face::Eq0(0)));
+        assert!(ends.and(Face::Eq0(1)).implies(&Face::Eq0(1)));
+    }
+
+    #[test]
+    fn test_restrict_substitutes_endpoints() {
+        // i ∨ ¬j
+        let interval = Interval::Join(Box::new(Interval::var(0)), Box::new(Interval::Neg(Box::new(Interval::var(1)))));
+        assert_eq!(Face::Eq0(0).restrict(&interval), Interval::Neg(Box::new(Interval::var(1))));
+        assert_eq!(Face::Eq1(0).restrict(&interval), Interval::one());
+        assert_eq!(Face::Eq0(0).and(Face::Eq1(1)).restrict(&interval), Interval::zero());
+        // On the boundary, i is at neither end throughout
+        assert_eq!(Face::boundary(0).restrict(&interval), interval.normalize());
+        assert_eq!(Face::Eq1(1).and(Face::boundary(0)).restrict(&interval), Interval::var(0));
+    }
+
+    #[test]
+    fn test_faces_seen_from_a_face() {
+        assert_eq!(Face::Eq0(0).on(&Face::Eq0(0)), Face::Top);
+        assert_eq!(Face::Eq0(0).on(&Face::Eq1(0)), Face::Bot);
+        assert_eq!(Face::Eq0(0).and(Face::Eq1(1)).on(&Face::Eq0(0)), Face::Eq1(1));
+        // Nothing is fixed throughout the boundary
+        assert_eq!(Face::Eq0(0).on(&Face::boundary(0)), Face::Eq0(0));
+    }
+}
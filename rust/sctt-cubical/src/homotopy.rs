@@ -0,0 +1,114 @@
+//! Homotopies between paths
+//!
+//! A homotopy is a square `H(s, t)` read as a family of paths: `H(s, -)` is
+//! a path for each `s`, running from `H(0, -)` at `s = 0` to `H(1, -)` at
+//! `s = 1`. One between paths with the same ends keeps those ends fixed all
+//! the way through, which [`Homotopy::validate_endpoints`] checks.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Path, PathExpr};
+
+/// Values of `s` at which the ends are compared
+const SAMPLES: usize = 32;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum HomotopyError {
+    #[error("the end t = {end} moves from {from} at s = 0 to {to} at s = {at}")]
+    EndpointMoves { end: f64, at: f64, from: f64, to: f64 },
+}
+
+/// `H(s, t)`, with `s` coordinate 0 and `t` coordinate 1
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Homotopy {
+    expr: PathExpr,
+}
+
+impl Homotopy {
+    /// `(1 - s)·p(t) + s·q(t)`, the straight line from `p` to `q`
+    pub fn linear(p: &Path, q: &Path) -> Self {
+        let (s, t) = (PathExpr::Coord(0), PathExpr::Coord(1));
+        let staying = PathExpr::Sub(Box::new(PathExpr::Num(1.0)), Box::new(s.clone()));
+        let from = PathExpr::Mul(Box::new(staying), Box::new(p.expr().subst(&t)));
+        let to = PathExpr::Mul(Box::new(s), Box::new(q.expr().subst(&t)));
+        Homotopy { expr: PathExpr::Add(Box::new(from), Box::new(to)) }
+    }
+
+    /// The homotopy computing `expr` in `s` and `t`, as from
+    /// `PathExpr::parse_in(source, &["s", "t"])`
+    pub fn from_expr(expr: PathExpr) -> Self {
+        Homotopy { expr }
+    }
+
+    pub fn expr(&self) -> &PathExpr {
+        &self.expr
+    }
+
+    /// `H(s, t)`, each clamped to `[0, 1]`
+    pub fn eval(&self, s: f64, t: f64) -> f64 {
+        self.expr.eval_at(&[s.clamp(0.0, 1.0), t.clamp(0.0, 1.0)])
+    }
+
+    /// The path `H(s, -)`
+    pub fn at(&self, s: f64) -> Path {
+        let sliced = self.expr.subst_coord(0, &PathExpr::Num(s.clamp(0.0, 1.0)));
+        Path::from_expr(sliced.subst_coord(1, &PathExpr::Param), "t".to_string())
+    }
+
+    /// Whether `H(s, 0)` and `H(s, 1)` stay within `tol` of where they are
+    /// at `s = 0`, checked at evenly spaced `s`
+    pub fn validate_endpoints(&self, tol: f64) -> Result<(), HomotopyError> {
+        for end in [0.0, 1.0] {
+            let from = self.eval(0.0, end);
+            for k in 1..=SAMPLES {
+                let at = k as f64 / SAMPLES as f64;
+                let to = self.eval(at, end);
+                let within = (to - from).abs() <= tol;
+                if !within {
+                    return Err(HomotopyError::EndpointMoves { end, at, from, to });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPSILON;
+
+    fn path(expression: &str) -> Path {
+        Path::new(expression.to_string(), "t".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_straight_line_from_t_to_t_squared() {
+        let (p, q) = (path("t"), path("t²"));
+        let h = Homotopy::linear(&p, &q);
+        assert!((h.eval(0.5, 0.5) - 0.375).abs() < EPSILON);
+        for k in 0..=10 {
+            let t = k as f64 / 10.0;
+            assert!((h.at(0.0).evaluate(t) - p.evaluate(t)).abs() < EPSILON);
+            assert!((h.at(1.0).evaluate(t) - q.evaluate(t)).abs() < EPSILON);
+            assert!((h.at(0.25).evaluate(t) - h.eval(0.25, t)).abs() < EPSILON);
+        }
+        // Both run from 0 to 1, so every slice does
+        assert_eq!(h.validate_endpoints(EPSILON), Ok(()));
+        assert!(h.at(0.7).check_boundaries(0.0, 1.0));
+    }
+
+    #[test]
+    fn test_moving_endpoints_are_reported() {
+        let lifting = Homotopy::from_expr(PathExpr::parse_in("t + s", &["s", "t"]).unwrap());
+        let moved = lifting.validate_endpoints(EPSILON);
+        assert!(matches!(moved, Err(HomotopyError::EndpointMoves { end, .. }) if end == 0.0));
+        // t and 1 - t share no end
+        let flip = Homotopy::linear(&path("t"), &path("1 - t"));
+        assert!(flip.validate_endpoints(0.1).is_err());
+        assert_eq!(flip.at(0.5).expression(), "(1 - 0.5) * t + 0.5 * (1 - t)");
+    }
+}
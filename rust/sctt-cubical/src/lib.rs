@@ -1,19 +1,113 @@
 //! Cubical operations module for SCTT
-//! Handles paths, intervals, and composition
+//! Handles paths, homotopies, squares, cubes, intervals, faces, systems,
+//! composition, and equivalences
 
-use wasm_bindgen::prelude::*;
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod comp;
+pub mod cube;
+pub mod equiv;
+pub mod expr;
+pub mod face;
+pub mod homotopy;
+pub mod plane;
+pub mod square;
+pub mod system;
+pub use comp::{fill, hcomp, transport};
+pub use cube::{Cube, CubeError};
+pub use equiv::{ua, Equiv, EquivError};
+pub use expr::{PathError, PathExpr};
+pub use face::Face;
+pub use homotopy::{Homotopy, HomotopyError};
+pub use plane::Path2;
+pub use square::{Connection, Square};
+pub use system::{System, SystemError};
+
+#[cfg(feature = "wasm-bindings")]
+mod wasm;
+#[cfg(feature = "wasm-bindings")]
+pub use wasm::*;
+
 /// Interval values in cubical type theory
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum IntervalValue {
     Zero,
     One,
     Var(usize),  // Variable index
+    /// A point of the interval, in `[0, 1]`
+    Const(f64),
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum IntervalError {
+    #[error("interval variable {index} is out of range for {len} values")]
+    UnboundVar { index: usize, len: usize },
+}
+
+/// What can go wrong building and combining paths
+#[derive(Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(tag = "kind", content = "detail"))]
+pub enum CubicalError {
+    #[error("cannot read `{expr}`: {message} at position {position}")]
+    ParseError { expr: String, position: usize, message: String },
+
+    #[error("unknown variable `{name}` in `{expr}`; expected `{parameter}`")]
+    UnknownVariable { expr: String, name: String, parameter: String },
+
+    /// A path meant to start or end (`end` 0 or 1) at `expected` is at
+    /// `actual` there instead
+    #[error("at t = {end} the path is at {actual}, but should be at {expected}")]
+    BoundaryMismatch { expected: f64, actual: f64, end: f64 },
+
+    #[error("paths have one direction, so sides go on i = 0 or i = 1, not on {face:?}")]
+    DimensionMismatch { face: Face },
+
+    #[error("{value} is not in the interval [0, 1]")]
+    OutOfRange { value: f64 },
+
+    #[error(transparent)]
+    Sides(#[from] SystemError),
 }
 
+impl CubicalError {
+    /// `error` from reading `expr`
+    fn parse(expr: &str, error: PathError) -> CubicalError {
+        let expr = expr.to_string();
+        match error {
+            PathError::Parse { position, message } => CubicalError::ParseError { expr, position, message },
+            PathError::UnknownVariable { name, parameter } => CubicalError::UnknownVariable { expr, name, parameter },
+        }
+    }
+}
+
+/// `value` if it is in `[0, 1]`
+pub(crate) fn in_range(value: f64) -> Result<f64, CubicalError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(CubicalError::OutOfRange { value })
+    }
+}
+
+/// How far apart two values may be and still count as equal
+pub(crate) const EPSILON: f64 = 1e-10;
+
+/// How far a path's ends may be from where they are meant to be, by
+/// default; loose enough for ends like `sin(π)` that only round to their value
+pub const BOUNDARY_EPSILON: f64 = 1e-9;
+
+/// Step for finite differences, about the cube root of machine epsilon
+const STEP: f64 = 1e-5;
+
 /// De Morgan algebra operations on intervals
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Interval {
     Value(IntervalValue),
     Meet(Box<Interval>, Box<Interval>),  // ∧
@@ -34,203 +128,576 @@ impl Interval {
         Interval::Value(IntervalValue::Var(index))
     }
 
-    /// Evaluate interval expression at a point
+    /// The point `value`, clamped to `[0, 1]`; the ends are `zero` and `one`
+    pub fn constant(value: f64) -> Self {
+        let value = value.clamp(0.0, 1.0);
+        if value == 0.0 {
+            Interval::zero()
+        } else if value == 1.0 {
+            Interval::one()
+        } else {
+            Interval::Value(IntervalValue::Const(value))
+        }
+    }
+
+    /// Evaluate interval expression at a point, which every variable
+    /// takes; for terms in one variable, else see [`Interval::eval_env`]
     pub fn eval(&self, point: f64) -> f64 {
         match self {
             Interval::Value(IntervalValue::Zero) => 0.0,
             Interval::Value(IntervalValue::One) => 1.0,
             Interval::Value(IntervalValue::Var(_)) => point.clamp(0.0, 1.0),
+            Interval::Value(IntervalValue::Const(value)) => *value,
             Interval::Meet(i, j) => i.eval(point).min(j.eval(point)),
             Interval::Join(i, j) => i.eval(point).max(j.eval(point)),
             Interval::Neg(i) => 1.0 - i.eval(point),
         }
     }
+
+    /// [`Interval::eval`] at each of `points`
+    pub fn eval_batch(&self, points: &[f64]) -> Vec<f64> {
+        points.iter().map(|&point| self.eval(point)).collect()
+    }
+
+    /// Evaluate with each variable `Var(k)` at `env[k]`
+    pub fn eval_env(&self, env: &[f64]) -> Result<f64, IntervalError> {
+        Ok(match self {
+            Interval::Value(IntervalValue::Zero) => 0.0,
+            Interval::Value(IntervalValue::One) => 1.0,
+            Interval::Value(IntervalValue::Var(index)) => env
+                .get(*index)
+                .ok_or(IntervalError::UnboundVar { index: *index, len: env.len() })?
+                .clamp(0.0, 1.0),
+            Interval::Value(IntervalValue::Const(value)) => *value,
+            Interval::Meet(i, j) => i.eval_env(env)?.min(j.eval_env(env)?),
+            Interval::Join(i, j) => i.eval_env(env)?.max(j.eval_env(env)?),
+            Interval::Neg(i) => 1.0 - i.eval_env(env)?,
+        })
+    }
+
+    /// Canonical form: a join of meets of variables and negated variables,
+    /// sorted, with no meet below another. `0` and `1` are absorbed, other
+    /// constants kept at most one to a meet, and negations pushed down to
+    /// the variables and constants. Expressions with the same normal form
+    /// are equal, and for those without constants strictly between 0 and 1
+    /// the converse holds too: they have the same normal form exactly when
+    /// the De Morgan laws make them equal. `i ∧ ¬i` is not `0`: the
+    /// interval is not a Boolean algebra.
+    pub fn normalize(&self) -> Interval {
+        let clauses = self.clauses();
+        if clauses.is_empty() {
+            return Interval::zero();
+        }
+        let literal = |literal: &Literal| match *literal {
+            Literal::Const(bits) => Interval::constant(f64::from_bits(bits)),
+            Literal::Var(k, false) => Interval::var(k),
+            Literal::Var(k, true) => Interval::Neg(Box::new(Interval::var(k))),
+        };
+        let fold = |parts: Vec<Interval>, op: fn(Box<Interval>, Box<Interval>) -> Interval, unit: Interval| {
+            parts.into_iter().reduce(|a, b| op(Box::new(a), Box::new(b))).unwrap_or(unit)
+        };
+        let meets = clauses
+            .iter()
+            .map(|clause| fold(clause.iter().map(literal).collect(), Interval::Meet, Interval::one()))
+            .collect();
+        fold(meets, Interval::Join, Interval::zero())
+    }
+
+    /// `replacement` for variable `var`, as when applying a binder for it:
+    /// the variables above `var` move down one, so `replacement` is in the
+    /// variables that are left. [`Interval::normalize`] afterwards folds
+    /// the constants this leaves behind.
+    pub fn subst(&self, var: usize, replacement: &Interval) -> Interval {
+        let go = |i: &Interval| Box::new(i.subst(var, replacement));
+        match self {
+            Interval::Value(IntervalValue::Var(k)) if *k == var => replacement.clone(),
+            Interval::Value(IntervalValue::Var(k)) if *k > var => Interval::var(k - 1),
+            Interval::Value(_) => self.clone(),
+            Interval::Meet(i, j) => Interval::Meet(go(i), go(j)),
+            Interval::Join(i, j) => Interval::Join(go(i), go(j)),
+            Interval::Neg(i) => Interval::Neg(go(i)),
+        }
+    }
+
+    /// Every variable from `from` up moved by `by`, as when going under (or
+    /// back out from under) `by` binders at `from`; `None` if a variable
+    /// would go below 0.
+    pub fn shift(&self, by: isize, from: usize) -> Option<Interval> {
+        Some(match self {
+            Interval::Value(IntervalValue::Var(k)) if *k >= from => Interval::var(k.checked_add_signed(by)?),
+            Interval::Value(_) => self.clone(),
+            Interval::Meet(i, j) => Interval::Meet(Box::new(i.shift(by, from)?), Box::new(j.shift(by, from)?)),
+            Interval::Join(i, j) => Interval::Join(Box::new(i.shift(by, from)?), Box::new(j.shift(by, from)?)),
+            Interval::Neg(i) => Interval::Neg(Box::new(i.shift(by, from)?)),
+        })
+    }
+
+    /// The highest variable that occurs, `None` for closed expressions
+    pub fn max_var(&self) -> Option<usize> {
+        match self {
+            Interval::Value(IntervalValue::Var(k)) => Some(*k),
+            Interval::Value(_) => None,
+            Interval::Meet(i, j) | Interval::Join(i, j) => i.max_var().max(j.max_var()),
+            Interval::Neg(i) => i.max_var(),
+        }
+    }
+
+    /// The expression rewritten locally, keeping its shape where it can:
+    /// negations pushed to the leaves (`¬¬i = i`, `¬(i ∧ j) = ¬i ∨ ¬j`),
+    /// constants folded (`¬0 = 1`, `0 ∧ i = 0`, `1 ∧ i = i`) and repeats
+    /// dropped (`i ∧ i = i`). See [`Interval::normalize`] for a form in
+    /// which equal expressions coincide.
+    pub fn simplify(&self) -> Interval {
+        match self {
+            Interval::Value(_) => self.clone(),
+            Interval::Meet(i, j) => meet_of(i.simplify(), j.simplify()),
+            Interval::Join(i, j) => join_of(i.simplify(), j.simplify()),
+            Interval::Neg(i) => i.simplify().negated(),
+        }
+    }
+
+    /// The negation of a simplified expression, pushed to its leaves
+    fn negated(self) -> Interval {
+        match self {
+            Interval::Value(IntervalValue::Zero) => Interval::one(),
+            Interval::Value(IntervalValue::One) => Interval::zero(),
+            Interval::Value(IntervalValue::Const(value)) => Interval::constant(1.0 - value),
+            Interval::Value(IntervalValue::Var(_)) => Interval::Neg(Box::new(self)),
+            Interval::Neg(i) => *i,
+            Interval::Meet(i, j) => join_of(i.negated(), j.negated()),
+            Interval::Join(i, j) => meet_of(i.negated(), j.negated()),
+        }
+    }
+
+    /// Whether the two expressions are equal by the De Morgan laws
+    pub fn equivalent(&self, other: &Interval) -> bool {
+        self.normalize() == other.normalize()
+    }
+
+    /// The join of meets of literals; no clauses is `0`, and an empty
+    /// clause is `1`
+    fn clauses(&self) -> Clauses {
+        match self {
+            Interval::Value(IntervalValue::Zero) => Clauses::new(),
+            Interval::Value(IntervalValue::One) => Clauses::from([Clause::new()]),
+            Interval::Value(IntervalValue::Var(k)) => Clauses::from([Clause::from([Literal::Var(*k, false)])]),
+            Interval::Value(IntervalValue::Const(value)) => {
+                tidy(Clause::from([Literal::Const(value.to_bits())])).into_iter().collect()
+            }
+            Interval::Join(i, j) => absorb(i.clauses().into_iter().chain(j.clauses()).collect()),
+            Interval::Meet(i, j) => meet(&i.clauses(), &j.clauses()),
+            // ¬(a ∧ b ∨ c) = (¬a ∨ ¬b) ∧ ¬c
+            Interval::Neg(i) => i.clauses().iter().fold(Clauses::from([Clause::new()]), |acc, clause| {
+                let negated = clause.iter().filter_map(|literal| tidy(Clause::from([literal.negate()]))).collect();
+                meet(&acc, &negated)
+            }),
+        }
+    }
+}
+
+/// A variable, negated or not, or a constant strictly between 0 and 1 by
+/// its bits, which for positive numbers sort in the same order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Literal {
+    Const(u64),
+    Var(usize, bool),
 }
 
+impl Literal {
+    fn negate(self) -> Literal {
+        match self {
+            Literal::Const(bits) => Literal::Const((1.0 - f64::from_bits(bits)).to_bits()),
+            Literal::Var(k, negated) => Literal::Var(k, !negated),
+        }
+    }
+}
+
+/// `i ∧ j` for simplified `i` and `j`, folding units and repeats
+fn meet_of(i: Interval, j: Interval) -> Interval {
+    match (i, j) {
+        (Interval::Value(IntervalValue::Zero), _) | (_, Interval::Value(IntervalValue::Zero)) => Interval::zero(),
+        (Interval::Value(IntervalValue::One), other) | (other, Interval::Value(IntervalValue::One)) => other,
+        (Interval::Value(IntervalValue::Const(a)), Interval::Value(IntervalValue::Const(b))) => {
+            Interval::constant(a.min(b))
+        }
+        (i, j) if i == j => i,
+        (i, j) => Interval::Meet(Box::new(i), Box::new(j)),
+    }
+}
+
+/// `i ∨ j` for simplified `i` and `j`, folding units and repeats
+fn join_of(i: Interval, j: Interval) -> Interval {
+    match (i, j) {
+        (Interval::Value(IntervalValue::One), _) | (_, Interval::Value(IntervalValue::One)) => Interval::one(),
+        (Interval::Value(IntervalValue::Zero), other) | (other, Interval::Value(IntervalValue::Zero)) => other,
+        (Interval::Value(IntervalValue::Const(a)), Interval::Value(IntervalValue::Const(b))) => {
+            Interval::constant(a.max(b))
+        }
+        (i, j) if i == j => i,
+        (i, j) => Interval::Join(Box::new(i), Box::new(j)),
+    }
+}
+
+type Clause = BTreeSet<Literal>;
+type Clauses = BTreeSet<Clause>;
+
+/// The constant of a clause, 1 if it has none; constants sort first
+fn constant(clause: &Clause) -> f64 {
+    match clause.first() {
+        Some(Literal::Const(bits)) => f64::from_bits(*bits),
+        _ => 1.0,
+    }
+}
+
+/// `clause` with its constants met into one, or `None` if that is `0`
+fn tidy(clause: Clause) -> Option<Clause> {
+    let value = clause.iter().fold(1.0, |min: f64, literal| match literal {
+        Literal::Const(bits) => min.min(f64::from_bits(*bits)),
+        Literal::Var(..) => min,
+    });
+    if value <= 0.0 {
+        return None;
+    }
+    let mut clause: Clause = clause.into_iter().filter(|literal| matches!(literal, Literal::Var(..))).collect();
+    if value < 1.0 {
+        clause.insert(Literal::Const(value.to_bits()));
+    }
+    Some(clause)
+}
+
+fn meet(a: &Clauses, b: &Clauses) -> Clauses {
+    absorb(a.iter().flat_map(|x| b.iter().filter_map(move |y| tidy(x.union(y).copied().collect()))).collect())
+}
+
+/// Whether `a ≤ b` because `b`'s variables are among `a`'s and its
+/// constant is no smaller
+fn below(a: &Clause, b: &Clause) -> bool {
+    b.iter().all(|literal| matches!(literal, Literal::Const(_)) || a.contains(literal)) && constant(a) <= constant(b)
+}
+
+/// Drop every clause below another: `i ∨ (i ∧ j) = i`
+fn absorb(clauses: Clauses) -> Clauses {
+    clauses
+        .iter()
+        .filter(|clause| !clauses.iter().any(|other| other != *clause && below(clause, other)))
+        .cloned()
+        .collect()
+}
+
+/// Where a path's ends are against where they were meant to be
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoundaryReport {
+    pub start_expected: f64,
+    pub start_actual: f64,
+    pub start_ok: bool,
+    pub end_expected: f64,
+    pub end_actual: f64,
+    pub end_ok: bool,
+    pub epsilon: f64,
+}
+
+impl BoundaryReport {
+    pub fn is_ok(&self) -> bool {
+        self.start_ok && self.end_ok
+    }
+
+    /// How far the start is from where it should be
+    pub fn start_error(&self) -> f64 {
+        (self.start_actual - self.start_expected).abs()
+    }
+
+    /// How far the end is from where it should be
+    pub fn end_error(&self) -> f64 {
+        (self.end_actual - self.end_expected).abs()
+    }
+}
+
+/// The version of the JSON schema of [`Path`]
+pub const PATH_SCHEMA_VERSION: u32 = 1;
+
 /// Path in cubical type theory
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// As JSON a path is its schema version `v`, which has to be
+/// [`PATH_SCHEMA_VERSION`], the expression as written, its parameter, and
+/// the expression tree, each node as in [`PathExpr`]. The path
+/// `if(t ≤ 0.5, 2t, -sqrt(t) + 1)` is
+///
+/// ```json
+/// {
+///   "v": 1,
+///   "expression": "if(t ≤ 0.5, 2t, -sqrt(t) + 1)",
+///   "parameter": "t",
+///   "expr": {"op": "cases", "args": {
+///     "test": {"op": "param"},
+///     "at": 0.5,
+///     "below": {"op": "mul", "args": [{"op": "num", "args": 2.0}, {"op": "param"}]},
+///     "above": {"op": "add", "args": [
+///       {"op": "neg", "args": {"op": "call", "args": ["sqrt", {"op": "param"}]}},
+///       {"op": "num", "args": 1.0}
+///     ]}
+///   }}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "PathJson", into = "PathJson"))]
 pub struct Path {
+    /// As written, for display
+    expression: String,
+    parameter: String,
+    expr: PathExpr,
+}
+
+/// [`Path`] as it is written to JSON
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct PathJson {
+    v: u32,
     expression: String,
     parameter: String,
+    expr: PathExpr,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PathJson> for Path {
+    type Error = String;
+
+    fn try_from(json: PathJson) -> Result<Self, String> {
+        if json.v != PATH_SCHEMA_VERSION {
+            return Err(format!("unsupported path schema version {}; expected {}", json.v, PATH_SCHEMA_VERSION));
+        }
+        Ok(Path { expression: json.expression, parameter: json.parameter, expr: json.expr })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Path> for PathJson {
+    fn from(path: Path) -> Self {
+        PathJson { v: PATH_SCHEMA_VERSION, expression: path.expression, parameter: path.parameter, expr: path.expr }
+    }
 }
 
 impl Path {
-    pub fn new(expression: String, parameter: String) -> Self {
-        Path { expression, parameter }
+    /// The path `expression` in `parameter`, an error if it does not parse
+    pub fn new(expression: String, parameter: String) -> Result<Self, CubicalError> {
+        let expr = PathExpr::parse(&expression, &parameter).map_err(|e| CubicalError::parse(&expression, e))?;
+        Ok(Path { expression, parameter, expr })
+    }
+
+    /// The path computing `expr`, written out in `parameter`
+    pub fn from_expr(expr: PathExpr, parameter: String) -> Self {
+        let expression = expr.display(&parameter).to_string();
+        Path { expression, parameter, expr }
+    }
+
+    /// The constant path at `value`
+    pub fn refl(value: f64) -> Self {
+        Path::from_expr(PathExpr::Num(value), "t".to_string())
+    }
+
+    /// The path run backwards, from its end to its start
+    pub fn inverse(&self) -> Self {
+        Path::from_expr(self.expr.reverse(), self.parameter.clone())
+    }
+
+    /// The path run along `3t² - 2t³`: the same ends, reached at rest
+    pub fn smoothstepped(&self) -> Self {
+        Path::from_expr(self.expr.smoothstep(), self.parameter.clone())
+    }
+
+    /// `base + (1 - t)·start + t·end`
+    pub(crate) fn shifted(base: &Path, start: f64, end: f64) -> Self {
+        let scaled = |weight: PathExpr, by: f64| Box::new(PathExpr::Mul(Box::new(weight), Box::new(PathExpr::Num(by))));
+        let fading = PathExpr::Sub(Box::new(PathExpr::Num(1.0)), Box::new(PathExpr::Param));
+        let moved = PathExpr::Add(Box::new(base.expr.clone()), scaled(fading, start));
+        Path::from_expr(PathExpr::Add(Box::new(moved), scaled(PathExpr::Param, end)), base.parameter.clone())
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn parameter(&self) -> &str {
+        &self.parameter
+    }
+
+    pub fn expr(&self) -> &PathExpr {
+        &self.expr
     }
 
     /// Evaluate path at a point t ∈ [0,1]
     pub fn evaluate(&self, t: f64) -> f64 {
-        let t = t.clamp(0.0, 1.0);
-        
-        // Simplified evaluation for demo
-        if self.expression.contains("π * t * (3 - 2*t)") {
-            std::f64::consts::PI * t * (3.0 - 2.0 * t)
-        } else if self.expression.contains("sin") {
-            (std::f64::consts::PI * t).sin()
-        } else if self.expression.contains("t²") || self.expression.contains("t^2") {
-            t * t
-        } else {
-            t
-        }
+        self.expr.eval(t.clamp(0.0, 1.0))
     }
 
-    /// Check if path satisfies boundary conditions
-    pub fn check_boundaries(&self, start: f64, end: f64) -> bool {
-        let epsilon = 1e-10;
-        (self.evaluate(0.0) - start).abs() < epsilon &&
-        (self.evaluate(1.0) - end).abs() < epsilon
+    /// [`Path::evaluate`] at each of `ts`
+    pub fn evaluate_batch(&self, ts: &[f64]) -> Vec<f64> {
+        ts.iter().map(|&t| self.evaluate(t)).collect()
     }
-}
-
-// WASM bindings
-#[wasm_bindgen]
-pub struct WasmInterval {
-    inner: Interval,
-}
 
-#[wasm_bindgen]
-impl WasmInterval {
-    #[wasm_bindgen(constructor)]
-    pub fn new(value: f64) -> WasmInterval {
-        let inner = if value <= 0.0 {
-            Interval::zero()
-        } else if value >= 1.0 {
-            Interval::one()
-        } else {
-            Interval::var(0)
-        };
-        WasmInterval { inner }
+    /// The path at `n` evenly spaced points from `t0` to `t1`, both
+    /// included; a single point is at `t0`
+    pub fn evaluate_range(&self, t0: f64, t1: f64, n: usize) -> Vec<f64> {
+        let step = if n > 1 { (t1 - t0) / (n - 1) as f64 } else { 0.0 };
+        (0..n).map(|k| self.evaluate(t0 + k as f64 * step)).collect()
     }
 
-    pub fn zero() -> WasmInterval {
-        WasmInterval { inner: Interval::zero() }
+    /// This path on `[0, 0.5]` and then `other` on `[0.5, 1]`, each run at
+    /// double speed; `other` has to start where this path ends
+    pub fn concat(&self, other: &Path) -> Result<Path, CubicalError> {
+        self.join(other, |u| u)
     }
 
-    pub fn one() -> WasmInterval {
-        WasmInterval { inner: Interval::one() }
+    /// As [`Path::concat`], with each half run along a smoothstep, so both
+    /// come to rest at the junction and the velocity is continuous there
+    pub fn concat_smooth(&self, other: &Path) -> Result<Path, CubicalError> {
+        self.join(other, expr::smoothstep_of)
     }
 
-    pub fn meet(&self, other: &WasmInterval) -> WasmInterval {
-        WasmInterval {
-            inner: Interval::Meet(
-                Box::new(self.inner.clone()),
-                Box::new(other.inner.clone())
-            )
+    /// The two paths in turn, as in [`joined`], if `other` starts where
+    /// this path ends
+    fn join(&self, other: &Path, pace: impl Fn(PathExpr) -> PathExpr) -> Result<Path, CubicalError> {
+        let (end, start) = (self.evaluate(1.0), other.evaluate(0.0));
+        if (end - start).abs() >= EPSILON {
+            return Err(CubicalError::BoundaryMismatch { expected: end, actual: start, end: 0.0 });
         }
+        Ok(Path::from_expr(joined(&self.expr, &other.expr, pace), self.parameter.clone()))
     }
 
-    pub fn join(&self, other: &WasmInterval) -> WasmInterval {
-        WasmInterval {
-            inner: Interval::Join(
-                Box::new(self.inner.clone()),
-                Box::new(other.inner.clone())
-            )
+    /// The point of `target` that `value` corresponds to: the one the same
+    /// fraction of the way between its ends as `value` is between this
+    /// path's, or shifted with the start if this path's ends meet
+    pub fn transport_to(&self, target: &Path, value: f64) -> f64 {
+        let (a0, a1) = (self.evaluate(0.0), self.evaluate(1.0));
+        let (b0, b1) = (target.evaluate(0.0), target.evaluate(1.0));
+        if (a1 - a0).abs() < EPSILON {
+            return value + (b0 - a0);
         }
+        b0 + (value - a0) / (a1 - a0) * (b1 - b0)
     }
 
-    pub fn neg(&self) -> WasmInterval {
-        WasmInterval {
-            inner: Interval::Neg(Box::new(self.inner.clone()))
-        }
+    /// The derivative at `t`, by central differences; within a step of an
+    /// end the step shrinks to fit, and right at it the difference is taken
+    /// one-sided, since the path is not defined past its ends
+    pub fn velocity(&self, t: f64) -> f64 {
+        derivative(|t| self.evaluate(t), t)
     }
 
-    pub fn eval(&self, point: f64) -> f64 {
-        self.inner.eval(point)
+    /// The derivatives at `t` from below and from above, which differ
+    /// where the path has a corner; at an end, both are the one there is
+    pub fn velocity_either_side(&self, t: f64) -> (f64, f64) {
+        let t = t.clamp(0.0, 1.0);
+        let f = |t| self.evaluate(t);
+        let below = if t >= 2.0 * STEP { -1.0 } else { 1.0 };
+        let above = if t <= 1.0 - 2.0 * STEP { 1.0 } else { -1.0 };
+        (one_sided(f, t, below), one_sided(f, t, above))
     }
-}
 
-#[wasm_bindgen]
-pub struct WasmPath {
-    inner: Path,
-}
+    /// Where the path may switch between pieces, as concatenations do:
+    /// see [`PathExpr::junctions`]
+    pub fn junctions(&self) -> Vec<f64> {
+        self.expr.junctions()
+    }
 
-#[wasm_bindgen]
-impl WasmPath {
-    #[wasm_bindgen(constructor)]
-    pub fn new(expression: String, parameter: String) -> WasmPath {
-        WasmPath {
-            inner: Path::new(expression, parameter)
-        }
+    /// Whether `other` carries on from this path's end with the same value
+    /// and velocity, to within `tol`, so composing them leaves no kink
+    pub fn is_c1_with(&self, other: &Path, tol: f64) -> bool {
+        (self.evaluate(1.0) - other.evaluate(0.0)).abs() <= tol
+            && (self.velocity(1.0) - other.velocity(0.0)).abs() <= tol
     }
 
-    pub fn evaluate(&self, t: f64) -> f64 {
-        self.inner.evaluate(t)
+    /// `(t, value)` at the ends of `n` equal pieces of the interval, so
+    /// `n + 1` points from `t = 0` to `t = 1`
+    pub fn sample(&self, n: usize) -> Vec<(f64, f64)> {
+        let n = n.max(1);
+        (0..=n)
+            .map(|k| {
+                let t = k as f64 / n as f64;
+                (t, self.evaluate(t))
+            })
+            .collect()
     }
 
-    pub fn check_boundaries(&self, start: f64, end: f64) -> bool {
-        self.inner.check_boundaries(start, end)
+    /// The length of the graph `t ↦ (t, p(t))` over `[0, 1]`, the
+    /// trapezoidal sum of `√(1 + p'(t)²)` over `n` pieces; the identity
+    /// path has length `√2`, and a constant one length 1
+    pub fn arc_length(&self, n: usize) -> f64 {
+        let n = n.max(1);
+        let h = 1.0 / n as f64;
+        let speed = |k: usize| self.velocity(k as f64 * h).hypot(1.0);
+        let inner: f64 = (1..n).map(speed).sum();
+        h * ((speed(0) + speed(n)) / 2.0 + inner)
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn expression(&self) -> String {
-        self.inner.expression.clone()
+    /// Where the path starts and ends
+    pub fn endpoints(&self) -> (f64, f64) {
+        (self.evaluate(0.0), self.evaluate(1.0))
     }
-}
 
-// Global exported functions
-#[wasm_bindgen]
-pub fn path_eval(expr: &str, t: f64) -> f64 {
-    let path = Path::new(expr.to_string(), "t".to_string());
-    path.evaluate(t)
-}
+    /// Check if path satisfies boundary conditions, to within
+    /// [`BOUNDARY_EPSILON`]
+    pub fn check_boundaries(&self, start: f64, end: f64) -> bool {
+        self.check_boundaries_detailed(start, end, BOUNDARY_EPSILON).is_ok()
+    }
 
-#[wasm_bindgen]
-pub fn interval_meet(i: f64, j: f64) -> f64 {
-    i.min(j).clamp(0.0, 1.0)
+    /// Each end against where it should be, to within `epsilon`
+    pub fn check_boundaries_detailed(&self, start: f64, end: f64, epsilon: f64) -> BoundaryReport {
+        let (start_actual, end_actual) = self.endpoints();
+        // Written so that NaN is never within range
+        let close = |actual: f64, expected: f64| (actual - expected).abs() <= epsilon;
+        BoundaryReport {
+            start_expected: start,
+            start_actual,
+            start_ok: close(start_actual, start),
+            end_expected: end,
+            end_actual,
+            end_ok: close(end_actual, end),
+            epsilon,
+        }
+    }
 }
 
-#[wasm_bindgen]
-pub fn interval_join(i: f64, j: f64) -> f64 {
-    i.max(j).clamp(0.0, 1.0)
+/// The derivative of `f` at `t`, as for [`Path::velocity`]
+pub(crate) fn derivative(f: impl Fn(f64) -> f64, t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    let room = t.min(1.0 - t);
+    if room >= STEP / 2.0 {
+        let h = room.min(STEP);
+        return (f(t + h) - f(t - h)) / (2.0 * h);
+    }
+    one_sided(f, t, if t < 0.5 { 1.0 } else { -1.0 })
 }
 
-#[wasm_bindgen]
-pub fn interval_neg(i: f64) -> f64 {
-    (1.0 - i).clamp(0.0, 1.0)
+/// Second-order one-sided difference of `f` at `t`, stepping towards
+/// `side`, `1` or `-1`: `(-3f(t) + 4f(t ± h) - f(t ± 2h)) / ±2h`
+fn one_sided(f: impl Fn(f64) -> f64, t: f64, side: f64) -> f64 {
+    let h = side * STEP;
+    (-3.0 * f(t) + 4.0 * f(t + h) - f(t + 2.0 * h)) / (2.0 * h)
 }
 
-// Composition operation demo
-#[wasm_bindgen]
-pub fn composition(path1_expr: &str, path2_expr: &str, t: f64) -> JsValue {
-    let path1 = Path::new(path1_expr.to_string(), "t".to_string());
-    let path2 = Path::new(path2_expr.to_string(), "t".to_string());
-    
-    // Evaluate paths
-    let p1_val = path1.evaluate(t);
-    let p2_val = path2.evaluate(t);
-    
-    // Check composition at boundaries
-    let p1_end = path1.evaluate(1.0);
-    let p2_start = path2.evaluate(0.0);
-    let composable = (p1_end - p2_start).abs() < 1e-10;
-    
-    serde_wasm_bindgen::to_value(&serde_json::json!({
-        "path1": path1_expr,
-        "path2": path2_expr,
-        "t": t,
-        "path1_value": p1_val,
-        "path2_value": p2_val,
-        "path1_endpoint": p1_end,
-        "path2_startpoint": p2_start,
-        "composable": composable,
-        "message": if composable { 
-            "Paths can be composed!" 
-        } else { 
-            "Paths cannot be composed - boundary mismatch" 
-        }
-    })).unwrap()
+/// `first` and then `second`, each with its parameter the image of its
+/// half of the interval under `pace`
+pub(crate) fn joined(first: &PathExpr, second: &PathExpr, pace: impl Fn(PathExpr) -> PathExpr) -> PathExpr {
+    let lower = double(PathExpr::Param);
+    let upper = PathExpr::Sub(Box::new(double(PathExpr::Param)), Box::new(PathExpr::Num(1.0)));
+    PathExpr::Cases {
+        test: Box::new(PathExpr::Param),
+        at: 0.5,
+        below: Box::new(first.subst(&pace(lower))),
+        above: Box::new(second.subst(&pace(upper))),
+    }
 }
 
-#[wasm_bindgen(start)]
-pub fn init() {
-    #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
+/// `2u`
+fn double(u: PathExpr) -> PathExpr {
+    PathExpr::Mul(Box::new(PathExpr::Num(2.0)), Box::new(u))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::f64::consts::PI;
 
     #[test]
     fn test_interval_operations() {
@@ -244,9 +711,298 @@ mod tests {
         assert_eq!(meet.eval(0.5), 0.5);
     }
 
+    #[test]
+    fn test_de_morgan_normal_form() {
+        let (i, j) = (Interval::var(0), Interval::var(1));
+        let neg = |x: Interval| Interval::Neg(Box::new(x));
+        let meet = |x: Interval, y: Interval| Interval::Meet(Box::new(x), Box::new(y));
+        let join = |x: Interval, y: Interval| Interval::Join(Box::new(x), Box::new(y));
+
+        assert!(neg(meet(i.clone(), j.clone())).equivalent(&join(neg(i.clone()), neg(j.clone()))));
+        assert_eq!(meet(Interval::zero(), i.clone()).normalize(), Interval::zero());
+        assert_eq!(join(i.clone(), meet(i.clone(), j.clone())).normalize(), i);
+        assert_eq!(neg(neg(j.clone())).normalize(), j);
+        assert_eq!(join(j.clone(), i.clone()).normalize(), join(i.clone(), j.clone()));
+        // Unlike in a Boolean algebra, i ∧ ¬i stays
+        assert!(!meet(i.clone(), neg(i.clone())).equivalent(&Interval::zero()));
+    }
+
+    #[test]
+    fn test_substitution_and_shifting() {
+        let (i, j, k) = (Interval::var(0), Interval::var(1), Interval::var(2));
+        let meet = |x: Interval, y: Interval| Interval::Meet(Box::new(x), Box::new(y));
+
+        let not_i = Interval::Neg(Box::new(i.clone()));
+        assert_eq!(not_i.subst(0, &Interval::one()).normalize(), Interval::zero());
+        // Variables above the one replaced move down into its place
+        assert_eq!(meet(i.clone(), k.clone()).subst(1, &Interval::zero()), meet(i.clone(), j.clone()));
+        assert_eq!(meet(j.clone(), k.clone()).subst(1, &Interval::zero()).normalize(), Interval::zero());
+
+        let term = meet(not_i, Interval::Join(Box::new(j.clone()), Box::new(k.clone())));
+        assert_eq!(term.max_var(), Some(2));
+        let shifted = term.shift(3, 1).unwrap();
+        assert_eq!(shifted.max_var(), Some(5));
+        assert_eq!(shifted.shift(-3, 1), Some(term.clone()));
+        // `j` is variable 1, and 1 - 2 is below 0
+        assert_eq!(term.shift(-2, 1), None);
+        assert_eq!(term.shift(-2, 3), Some(term));
+        assert_eq!(Interval::one().max_var(), None);
+    }
+
+    #[test]
+    fn test_simplify_de_morgan() {
+        let (i, j) = (Interval::var(0), Interval::var(1));
+        let neg = |x: Interval| Interval::Neg(Box::new(x));
+        let meet = |x: Interval, y: Interval| Interval::Meet(Box::new(x), Box::new(y));
+        let join = |x: Interval, y: Interval| Interval::Join(Box::new(x), Box::new(y));
+
+        assert_eq!(neg(neg(i.clone())).simplify(), i);
+        assert_eq!(neg(meet(i.clone(), j.clone())).simplify(), join(neg(i.clone()), neg(j.clone())));
+        assert_eq!(neg(Interval::zero()).simplify(), Interval::one());
+        assert_eq!(meet(i.clone(), i.clone()).simplify(), i);
+        assert_eq!(join(neg(Interval::one()), meet(j.clone(), neg(Interval::zero()))).simplify(), j);
+        // Local rewrites only: absorption is left to normalize
+        let absorbable = join(i.clone(), meet(i.clone(), j.clone()));
+        assert_eq!(absorbable.simplify(), absorbable);
+    }
+
+    #[test]
+    fn test_constants_keep_their_values() {
+        let (a, b) = (Interval::constant(0.3), Interval::constant(0.7));
+        let meet = Interval::Meet(Box::new(a.clone()), Box::new(b.clone()));
+        for point in [0.0, 0.5, 1.0] {
+            assert_eq!(meet.eval(point), 0.3);
+        }
+        assert_eq!(meet.normalize(), a);
+        assert_eq!(Interval::Join(Box::new(a.clone()), Box::new(b.clone())).normalize(), b);
+        assert_eq!(Interval::constant(1.5), Interval::one());
+        // ¬(0.3 ∧ i) = 0.7 ∨ ¬i, and the join of 0.3 with 0.3 ∧ i is 0.3
+        let i = Interval::var(0);
+        let neg = Interval::Neg(Box::new(Interval::Meet(Box::new(a.clone()), Box::new(i.clone()))));
+        assert!((neg.eval_env(&[0.9]).unwrap() - 0.7).abs() < EPSILON);
+        let absorbed = Interval::Join(Box::new(a.clone()), Box::new(Interval::Meet(Box::new(a.clone()), Box::new(i))));
+        assert_eq!(absorbed.normalize(), a);
+    }
+
+    #[test]
+    fn test_eval_env_per_variable() {
+        // i ∧ ¬j
+        let term = Interval::Meet(Box::new(Interval::var(0)), Box::new(Interval::Neg(Box::new(Interval::var(1)))));
+        assert_eq!(term.eval_env(&[0.75, 0.5]), Ok(0.5));
+        assert_eq!(term.eval_env(&[0.25, 0.0]), Ok(0.25));
+        assert_eq!(term.eval_env(&[1.0, 1.0]), Ok(0.0));
+        assert_eq!(term.eval_env(&[0.5]), Err(IntervalError::UnboundVar { index: 1, len: 1 }));
+        // Closed terms need no environment
+        assert_eq!(Interval::Neg(Box::new(Interval::zero())).eval_env(&[]), Ok(1.0));
+    }
+
+    /// Random expressions in the variables `0..vars`
+    fn interval(vars: usize) -> impl Strategy<Value = Interval> {
+        let leaf = prop_oneof![
+            Just(Interval::zero()),
+            Just(Interval::one()),
+            (0..vars).prop_map(Interval::var),
+            (0.0..=1.0f64).prop_map(Interval::constant),
+        ];
+        leaf.prop_recursive(4, 24, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| Interval::Meet(Box::new(a), Box::new(b))),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| Interval::Join(Box::new(a), Box::new(b))),
+                inner.prop_map(|a| Interval::Neg(Box::new(a))),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_normalize_preserves_eval(i in interval(3), points in prop::collection::vec(0.0..=1.0f64, 3)) {
+            let normal = i.normalize();
+            prop_assert!((i.eval_env(&points).unwrap() - normal.eval_env(&points).unwrap()).abs() < 1e-9);
+            prop_assert!((i.eval(points[0]) - normal.eval(points[0])).abs() < 1e-9);
+            prop_assert_eq!(normal.normalize(), normal);
+        }
+
+        #[test]
+        fn prop_simplify_preserves_eval(i in interval(4), points in prop::collection::vec(0.0..=1.0f64, 4)) {
+            let simple = i.simplify();
+            prop_assert!((i.eval_env(&points).unwrap() - simple.eval_env(&points).unwrap()).abs() < 1e-9);
+            prop_assert_eq!(simple.simplify(), simple.clone());
+            prop_assert!(simple.equivalent(&i));
+        }
+    }
+
     #[test]
     fn test_path_evaluation() {
-        let path = Path::new("t²".to_string(), "t".to_string());
+        let path = Path::new("t²".to_string(), "t".to_string()).unwrap();
         assert!((path.evaluate(0.5) - 0.25).abs() < 1e-10);
+
+        let err = Path::new("t +".to_string(), "t".to_string()).unwrap_err();
+        assert!(matches!(err, CubicalError::ParseError { ref expr, position: 3, .. } if expr == "t +"));
+        let err = Path::new("s²".to_string(), "t".to_string()).unwrap_err();
+        let unknown = CubicalError::UnknownVariable { expr: "s²".into(), name: "s".into(), parameter: "t".into() };
+        assert_eq!(err, unknown);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_velocity() {
+        let path = |expression: &str| Path::new(expression.to_string(), "t".to_string()).unwrap();
+        let square = path("t²");
+        for t in [0.0, 1e-7, 0.25, 0.5, 1.0 - 3e-6, 1.0] {
+            assert!((square.velocity(t) - 2.0 * t).abs() < 1e-6, "velocity at {}", t);
+        }
+        assert!((path("sin(π t)").velocity(0.0) - PI).abs() < 1e-6);
+
+        // t² comes into 1 at speed 2, as 1 + 2t leaves it; t arrives at speed 1
+        assert!(square.is_c1_with(&path("1 + 2t"), 1e-6));
+        assert!(!path("t").is_c1_with(&path("1 + 2t"), 1e-6));
+        assert!(!square.is_c1_with(&path("2t"), 1e-6));
+    }
+
+    #[test]
+    fn test_inverse_and_refl() {
+        let p = Path::new("t + sin(π t) / 2".to_string(), "t".to_string()).unwrap();
+        let back = p.inverse();
+        assert_eq!(back.expression(), "1 - t + sin(π * (1 - t)) / 2");
+        let (start, end) = back.endpoints();
+        assert!((start - 1.0).abs() < EPSILON && end.abs() < EPSILON);
+        let again = back.inverse();
+        assert!((0..=20).all(|k| {
+            let t = k as f64 / 20.0;
+            (again.evaluate(t) - p.evaluate(t)).abs() < EPSILON
+        }));
+
+        let eased = p.smoothstepped();
+        assert!(eased.check_boundaries(0.0, 1.0));
+        assert!(eased.velocity(0.0).abs() < 1e-6 && eased.velocity(1.0).abs() < 1e-6);
+        assert!(p.velocity(0.0) > 1.0);
+
+        assert!(Path::refl(2.5).check_boundaries(2.5, 2.5));
+        assert_eq!(Path::refl(-1.0).endpoints(), (-1.0, -1.0));
+    }
+
+    #[test]
+    fn test_batch_evaluation() {
+        let path = Path::new("t² + 1".to_string(), "t".to_string()).unwrap();
+        let ts = [0.0, 0.25, 0.5, 2.0];
+        let scalar: Vec<f64> = ts.iter().map(|&t| path.evaluate(t)).collect();
+        assert_eq!(path.evaluate_batch(&ts), scalar);
+        assert_eq!(path.evaluate_range(0.0, 1.0, 5), [1.0, 1.0625, 1.25, 1.5625, 2.0]);
+        assert_eq!(path.evaluate_range(0.5, 1.0, 1), [1.25]);
+        assert!(path.evaluate_range(0.0, 1.0, 0).is_empty());
+
+        let i = Interval::Neg(Box::new(Interval::var(0)));
+        assert_eq!(i.eval_batch(&[0.0, 0.25, 1.5]), [1.0, 0.75, 0.0]);
+    }
+
+    #[test]
+    fn test_sampling_and_arc_length() {
+        let path = |expression: &str| Path::new(expression.to_string(), "t".to_string()).unwrap();
+        let samples = path("t²").sample(4);
+        assert_eq!(samples, [(0.0, 0.0), (0.25, 0.0625), (0.5, 0.25), (0.75, 0.5625), (1.0, 1.0)]);
+        assert_eq!(path("t").sample(0), [(0.0, 0.0), (1.0, 1.0)]);
+
+        assert!((path("t").arc_length(10) - 2f64.sqrt()).abs() < 1e-9);
+        assert!((Path::refl(3.0).arc_length(1) - 1.0).abs() < 1e-9);
+        // The parabola's length is (2√5 + asinh 2) / 4
+        let exact = (2.0 * 5f64.sqrt() + 2f64.asinh()) / 4.0;
+        assert!((path("t²").arc_length(1000) - exact).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_boundary_reports() {
+        let path = |expression: &str| Path::new(expression.to_string(), "t".to_string()).unwrap();
+        // sin(π) is only about 1.2e-16
+        let report = path("sin(π t)").check_boundaries_detailed(0.0, 0.0, BOUNDARY_EPSILON);
+        assert!(report.is_ok() && report.end_actual != 0.0);
+
+        let report = path("t + 0.00000032").check_boundaries_detailed(0.0, 1.0, BOUNDARY_EPSILON);
+        assert!(!report.start_ok && !report.end_ok && !report.is_ok());
+        assert!((report.start_error() - 3.2e-7).abs() < 1e-15);
+        // ... which a looser epsilon lets through
+        assert!(path("t + 0.00000032").check_boundaries_detailed(0.0, 1.0, 1e-6).is_ok());
+
+        let report = path("t²").check_boundaries_detailed(0.0, 2.0, BOUNDARY_EPSILON);
+        assert!(report.start_ok && !report.end_ok);
+        assert_eq!((report.end_expected, report.end_actual, report.epsilon), (2.0, 1.0, BOUNDARY_EPSILON));
+        assert!(!path("t").check_boundaries(0.5, 1.0));
+
+        // Undefined ends are never right
+        let report = path("sqrt(t - 1)").check_boundaries_detailed(0.0, 0.0, 1.0);
+        assert!(!report.start_ok && report.end_ok);
+    }
+
+    #[test]
+    fn test_junctions_and_one_sided_velocities() {
+        let path = |expression: &str| Path::new(expression.to_string(), "t".to_string()).unwrap();
+        let (p, q) = (path("t²"), path("1 + t"));
+        let joined = p.concat(&q).unwrap();
+        assert_eq!(joined.junctions(), vec![0.5]);
+        let (below, above) = joined.velocity_either_side(0.5);
+        assert!((below - 4.0).abs() < 1e-6 && (above - 2.0).abs() < 1e-6);
+        // Concatenating again halves the old junction and adds another
+        assert_eq!(joined.concat(&path("2")).unwrap().junctions(), vec![0.25, 0.5]);
+        assert_eq!(path("if(t² ≤ 0.5, 0, 1)").junctions(), Vec::<f64>::new());
+        let (below, above) = p.velocity_either_side(0.0);
+        assert!(below.abs() < 1e-6 && above.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_concatenation() {
+        let path = |expression: &str| Path::new(expression.to_string(), "t".to_string()).unwrap();
+        let (p, q) = (path("t²"), path("1 + t"));
+        let joined = p.concat(&q).unwrap();
+        assert!(joined.check_boundaries(0.0, 2.0));
+        assert!((joined.evaluate(0.25) - 0.25).abs() < EPSILON && (joined.evaluate(0.75) - 1.5).abs() < EPSILON);
+        // What it is written as reads back as the same path
+        assert!((path(joined.expression()).evaluate(0.75) - 1.5).abs() < EPSILON);
+        // The second path starts at 0 where the first ends at 1
+        let gap = CubicalError::BoundaryMismatch { expected: 1.0, actual: 0.0, end: 0.0 };
+        assert_eq!(path("t").concat(&path("t")).unwrap_err(), gap);
+
+        // One-sided slopes at the junction: 4 against 2, unless smoothed
+        let slopes = |p: &Path| {
+            let h = 1e-6;
+            ((p.evaluate(0.5) - p.evaluate(0.5 - h)) / h, (p.evaluate(0.5 + h) - p.evaluate(0.5)) / h)
+        };
+        let (left, right) = slopes(&joined);
+        assert!((left - 4.0).abs() < 1e-4 && (right - 2.0).abs() < 1e-4);
+        let smooth = p.concat_smooth(&q).unwrap();
+        assert!(smooth.check_boundaries(0.0, 2.0));
+        let (left, right) = slopes(&smooth);
+        assert!((left - right).abs() < 1e-4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_path_json_schema() {
+        // The example in the docs of `Path`, exactly
+        let documented = r#"{
+          "v": 1,
+          "expression": "if(t ≤ 0.5, 2t, -sqrt(t) + 1)",
+          "parameter": "t",
+          "expr": {"op": "cases", "args": {
+            "test": {"op": "param"},
+            "at": 0.5,
+            "below": {"op": "mul", "args": [{"op": "num", "args": 2.0}, {"op": "param"}]},
+            "above": {"op": "add", "args": [
+              {"op": "neg", "args": {"op": "call", "args": ["sqrt", {"op": "param"}]}},
+              {"op": "num", "args": 1.0}
+            ]}
+          }}
+        }"#;
+        let p = Path::new("if(t ≤ 0.5, 2t, -sqrt(t) + 1)".to_string(), "t".to_string()).unwrap();
+        let written = serde_json::to_value(&p).unwrap();
+        assert_eq!(written, serde_json::from_str::<serde_json::Value>(documented).unwrap());
+        let read: Path = serde_json::from_str(documented).unwrap();
+        assert_eq!((read.expression(), read.parameter(), read.expr()), (p.expression(), p.parameter(), p.expr()));
+
+        // Other versions, and none at all, are rejected
+        let mut future = written.clone();
+        future["v"] = 2.into();
+        let err = serde_json::from_value::<Path>(future).unwrap_err().to_string();
+        assert!(err.contains("unsupported path schema version 2"));
+        let mut unversioned = written;
+        unversioned.as_object_mut().unwrap().remove("v");
+        assert!(serde_json::from_value::<Path>(unversioned).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! Paths in the plane
+//!
+//! A [`Path2`] is a pair of numeric paths read as one path in `ℝ²`, which
+//! is what the homotopy viewer and the game draw. Lengths are of the curve
+//! itself, not of a graph as for [`Path::arc_length`](crate::Path::arc_length).
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{derivative, joined, CubicalError, PathExpr, EPSILON};
+
+/// `t ↦ (x(t), y(t))`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Path2 {
+    pub x: PathExpr,
+    pub y: PathExpr,
+}
+
+impl Path2 {
+    pub fn new(x: PathExpr, y: PathExpr) -> Self {
+        Path2 { x, y }
+    }
+
+    /// The path with coordinates `x` and `y`, expressions in `parameter`
+    pub fn parse(x: &str, y: &str, parameter: &str) -> Result<Self, CubicalError> {
+        let parse = |source: &str| PathExpr::parse(source, parameter).map_err(|e| CubicalError::parse(source, e));
+        Ok(Path2::new(parse(x)?, parse(y)?))
+    }
+
+    /// The point at `t ∈ [0, 1]`
+    pub fn evaluate(&self, t: f64) -> (f64, f64) {
+        let t = t.clamp(0.0, 1.0);
+        (self.x.eval(t), self.y.eval(t))
+    }
+
+    /// The velocity at `t`, coordinate by coordinate as in
+    /// [`Path::velocity`](crate::Path::velocity)
+    pub fn velocity(&self, t: f64) -> (f64, f64) {
+        let along = |expr: &PathExpr| derivative(|t| expr.eval(t.clamp(0.0, 1.0)), t);
+        (along(&self.x), along(&self.y))
+    }
+
+    /// The points at the ends of `n` equal pieces of the interval
+    pub fn sample(&self, n: usize) -> Vec<(f64, f64)> {
+        let n = n.max(1);
+        (0..=n).map(|k| self.evaluate(k as f64 / n as f64)).collect()
+    }
+
+    /// The length of the curve, the trapezoidal sum of the speed over `n`
+    /// pieces
+    pub fn arc_length(&self, n: usize) -> f64 {
+        let n = n.max(1);
+        let h = 1.0 / n as f64;
+        let speed = |k: usize| {
+            let (dx, dy) = self.velocity(k as f64 * h);
+            dx.hypot(dy)
+        };
+        let inner: f64 = (1..n).map(speed).sum();
+        h * ((speed(0) + speed(n)) / 2.0 + inner)
+    }
+
+    /// Whether the path starts at `start` and ends at `end`, each
+    /// coordinate to within `tol`
+    pub fn check_boundaries(&self, start: (f64, f64), end: (f64, f64), tol: f64) -> bool {
+        let close = |(a, b): (f64, f64), (c, d): (f64, f64)| (a - c).abs() <= tol && (b - d).abs() <= tol;
+        close(self.evaluate(0.0), start) && close(self.evaluate(1.0), end)
+    }
+
+    /// This path on `[0, 0.5]` and then `other` on `[0.5, 1]`, as for
+    /// [`Path::concat`](crate::Path::concat); a gap is reported in the
+    /// first coordinate that has one
+    pub fn concat(&self, other: &Path2) -> Result<Path2, CubicalError> {
+        let ((x1, y1), (x2, y2)) = (self.evaluate(1.0), other.evaluate(0.0));
+        for (expected, actual) in [(x1, x2), (y1, y2)] {
+            if (expected - actual).abs() >= EPSILON {
+                return Err(CubicalError::BoundaryMismatch { expected, actual, end: 0.0 });
+            }
+        }
+        Ok(Path2::new(joined(&self.x, &other.x, |u| u), joined(&self.y, &other.y, |u| u)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn arc() -> Path2 {
+        Path2::parse("cos(π t)", "sin(π t)", "t").unwrap()
+    }
+
+    #[test]
+    fn test_half_circle() {
+        let arc = arc();
+        assert!(arc.check_boundaries((1.0, 0.0), (-1.0, 0.0), 1e-12));
+        assert!(!arc.check_boundaries((1.0, 0.0), (0.0, 1.0), 1e-12));
+        assert!((arc.arc_length(1000) - PI).abs() < 1e-5);
+        // Going round at speed π, straight up at the start
+        let (dx, dy) = arc.velocity(0.0);
+        assert!(dx.abs() < 1e-6 && (dy - PI).abs() < 1e-6);
+        let (dx, dy) = arc.velocity(0.5);
+        assert!((dx + PI).abs() < 1e-6 && dy.abs() < 1e-6);
+        assert_eq!(arc.sample(4).len(), 5);
+    }
+
+    #[test]
+    fn test_concatenating_arcs() {
+        let lower = Path2::parse("-cos(π t)", "-sin(π t)", "t").unwrap();
+        let circle = arc().concat(&lower).unwrap();
+        assert!(circle.check_boundaries((1.0, 0.0), (1.0, 0.0), 1e-12));
+        let (x, y) = circle.evaluate(0.75);
+        assert!(x.abs() < 1e-12 && (y + 1.0).abs() < 1e-12);
+        assert!((circle.arc_length(2000) - 2.0 * PI).abs() < 1e-4);
+
+        // The upper arc ends at x = -1, where it does not start
+        let err = arc().concat(&arc()).unwrap_err();
+        assert_eq!(err, CubicalError::BoundaryMismatch { expected: -1.0, actual: 1.0, end: 0.0 });
+    }
+}
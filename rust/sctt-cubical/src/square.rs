@@ -0,0 +1,172 @@
+//! Squares: maps out of `I × I`
+//!
+//! The connections of a path `p` are the squares `(i, j) ↦ p(i ∧ j)` and
+//! `(i, j) ↦ p(i ∨ j)`. Each has `p` on two of its edges and the constant
+//! path at one end of `p` on the other two, which is what lets them fill
+//! squares plain degeneracies cannot.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Path, PathExpr};
+
+/// How the two directions are combined before `p` is applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Connection {
+    /// `i ∧ j`
+    Meet,
+    /// `i ∨ j`
+    Join,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Square {
+    shape: Shape,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Shape {
+    /// `p(i ∧ j)` or `p(i ∨ j)`
+    Connection { path: Path, connection: Connection },
+    /// An expression in `i`, [`PathExpr::Coord`] 0, and `j`, coordinate 1
+    Expr(PathExpr),
+}
+
+impl Square {
+    /// `(i, j) ↦ p(i ∧ j)`
+    pub fn connection_meet(p: &Path) -> Self {
+        Square { shape: Shape::Connection { path: p.clone(), connection: Connection::Meet } }
+    }
+
+    /// `(i, j) ↦ p(i ∨ j)`
+    pub fn connection_join(p: &Path) -> Self {
+        Square { shape: Shape::Connection { path: p.clone(), connection: Connection::Join } }
+    }
+
+    /// The square computing `expr` in `i` and `j`, as from
+    /// `PathExpr::parse_in(source, &["i", "j"])`
+    pub fn from_expr(expr: PathExpr) -> Self {
+        Square { shape: Shape::Expr(expr) }
+    }
+
+    /// The path a connection is built from
+    pub fn path(&self) -> Option<&Path> {
+        match &self.shape {
+            Shape::Connection { path, .. } => Some(path),
+            Shape::Expr(_) => None,
+        }
+    }
+
+    pub fn connection(&self) -> Option<Connection> {
+        match &self.shape {
+            Shape::Connection { connection, .. } => Some(*connection),
+            Shape::Expr(_) => None,
+        }
+    }
+
+    /// The square as an expression in `i` and `j`: `p(i ∧ j)` is `p(i)`
+    /// where `i ≤ j` and `p(j)` elsewhere, `p(i ∨ j)` the other way round
+    pub fn expr(&self) -> PathExpr {
+        let (path, connection) = match &self.shape {
+            Shape::Connection { path, connection } => (path, connection),
+            Shape::Expr(expr) => return expr.clone(),
+        };
+        let (i, j) = (PathExpr::Coord(0), PathExpr::Coord(1));
+        let along = |coord: &PathExpr| Box::new(path.expr().subst(coord));
+        let (below, above) = match connection {
+            Connection::Meet => (along(&i), along(&j)),
+            Connection::Join => (along(&j), along(&i)),
+        };
+        let test = Box::new(PathExpr::Sub(Box::new(i), Box::new(j)));
+        PathExpr::Cases { test, at: 0.0, below, above }
+    }
+
+    pub fn eval(&self, i: f64, j: f64) -> f64 {
+        let (i, j) = (i.clamp(0.0, 1.0), j.clamp(0.0, 1.0));
+        match &self.shape {
+            Shape::Connection { path, connection: Connection::Meet } => path.evaluate(i.min(j)),
+            Shape::Connection { path, connection: Connection::Join } => path.evaluate(i.max(j)),
+            Shape::Expr(expr) => expr.eval_at(&[i, j]),
+        }
+    }
+
+    /// The edge `i = 0`, running along `j`
+    pub fn left(&self) -> Path {
+        self.edge(0, 0.0)
+    }
+
+    /// The edge `i = 1`, running along `j`
+    pub fn right(&self) -> Path {
+        self.edge(0, 1.0)
+    }
+
+    /// The edge `j = 0`, running along `i`
+    pub fn bottom(&self) -> Path {
+        self.edge(1, 0.0)
+    }
+
+    /// The edge `j = 1`, running along `i`
+    pub fn top(&self) -> Path {
+        self.edge(1, 1.0)
+    }
+
+    /// The edge where direction `dim` is at `end`, running along the other
+    fn edge(&self, dim: usize, end: f64) -> Path {
+        match &self.shape {
+            // Connections are symmetric, so both directions give the same edge
+            Shape::Connection { path, connection } => match (connection, end == 0.0) {
+                // `0 ∧ j = 0` and `1 ∨ j = 1`: stuck at that end of the path
+                (Connection::Meet, true) | (Connection::Join, false) => Path::refl(path.evaluate(end)),
+                // `1 ∧ j = j` and `0 ∨ j = j`: the path itself
+                (Connection::Meet, false) | (Connection::Join, true) => path.clone(),
+            },
+            Shape::Expr(expr) => {
+                let fixed = expr.subst_coord(dim, &PathExpr::Num(end));
+                Path::from_expr(fixed.subst_coord(1 - dim, &PathExpr::Param), "t".to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EPSILON;
+
+    /// Whether `edge` is `along` the side of `square` it claims to be
+    fn agrees(edge: &Path, along: impl Fn(f64) -> f64) -> bool {
+        (0..=20).all(|k| {
+            let t = k as f64 / 20.0;
+            (edge.evaluate(t) - along(t)).abs() < EPSILON
+        })
+    }
+
+    #[test]
+    fn test_meet_connection_boundary() {
+        let p = Path::new("t² + 1".to_string(), "t".to_string()).unwrap();
+        let square = Square::connection_meet(&p);
+        assert!((square.eval(0.5, 0.25) - p.evaluate(0.25)).abs() < EPSILON);
+        // refl at p(0) on i = 0 and j = 0, p on i = 1 and j = 1
+        assert!(square.left().check_boundaries(1.0, 1.0) && square.bottom().check_boundaries(1.0, 1.0));
+        assert!(agrees(&square.right(), |t| p.evaluate(t)) && agrees(&square.top(), |t| p.evaluate(t)));
+        assert!(agrees(&square.left(), |j| square.eval(0.0, j)));
+        assert!(agrees(&square.right(), |j| square.eval(1.0, j)));
+        assert!(agrees(&square.bottom(), |i| square.eval(i, 0.0)));
+        assert!(agrees(&square.top(), |i| square.eval(i, 1.0)));
+    }
+
+    #[test]
+    fn test_join_connection_boundary() {
+        let p = Path::new("t² + 1".to_string(), "t".to_string()).unwrap();
+        let square = Square::connection_join(&p);
+        assert!((square.eval(0.5, 0.25) - p.evaluate(0.5)).abs() < EPSILON);
+        // p on i = 0 and j = 0, refl at p(1) on i = 1 and j = 1
+        assert!(agrees(&square.left(), |t| p.evaluate(t)) && agrees(&square.bottom(), |t| p.evaluate(t)));
+        assert!(square.right().check_boundaries(2.0, 2.0) && square.top().check_boundaries(2.0, 2.0));
+        assert!(agrees(&square.right(), |j| square.eval(1.0, j)));
+        assert!(agrees(&square.top(), |i| square.eval(i, 1.0)));
+    }
+}
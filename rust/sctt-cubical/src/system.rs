@@ -0,0 +1,118 @@
+//! Systems: partial elements given face by face
+//!
+//! A system `[φ₁ ↦ u₁, …, φₙ ↦ uₙ]` gives a value on each face. Where two
+//! faces overlap their values have to agree, which [`System::new`] checks
+//! with a comparison supplied by the caller, since what agreeing means
+//! depends on what the values are. These are the sides handed to `hcomp`
+//! and `comp`, and the boundaries the visualizer draws.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Face;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SystemError {
+    #[error("branches {first} and {second} disagree where their faces meet")]
+    Disagree { first: usize, second: usize },
+}
+
+/// Values on faces, in the order they were given
+#[derive(Debug, Clone, PartialEq)]
+pub struct System<T> {
+    branches: Vec<(Face, T)>,
+}
+
+impl<T> System<T> {
+    /// The system with the given branches, provided `agree(overlap, a, b)`
+    /// holds for each pair of branches whose faces overlap
+    pub fn new(branches: Vec<(Face, T)>, agree: impl Fn(&Face, &T, &T) -> bool) -> Result<Self, SystemError> {
+        for (first, (face, value)) in branches.iter().enumerate() {
+            for (second, (other, other_value)) in branches.iter().enumerate().skip(first + 1) {
+                let overlap = face.clone().and(other.clone());
+                if overlap.is_satisfiable() && !agree(&overlap.simplify(), value, other_value) {
+                    return Err(SystemError::Disagree { first, second });
+                }
+            }
+        }
+        Ok(System { branches })
+    }
+
+    pub fn branches(&self) -> &[(Face, T)] {
+        &self.branches
+    }
+
+    /// The face the system is defined on, the join of its branches' faces
+    pub fn extent(&self) -> Face {
+        self.branches.iter().map(|(face, _)| face.clone()).reduce(Face::or).unwrap_or(Face::Bot)
+    }
+
+    /// Whether the system is defined on all of `face`
+    pub fn covers(&self, face: &Face) -> bool {
+        face.implies(&self.extent())
+    }
+
+    /// Whether the system is defined everywhere
+    pub fn is_total(&self) -> bool {
+        self.covers(&Face::Top)
+    }
+
+    /// The value on `face`: that of the first branch whose face contains it
+    pub fn lookup(&self, face: &Face) -> Option<&T> {
+        self.branches.iter().find(|(branch, _)| face.implies(branch)).map(|(_, value)| value)
+    }
+}
+
+impl<T: Clone> System<T> {
+    /// The system as seen from inside `face`, as for [`Face::on`]; branches
+    /// that do not meet it are dropped
+    pub fn restrict(&self, face: &Face) -> System<T> {
+        let branches = self
+            .branches
+            .iter()
+            .map(|(branch, value)| (branch.on(face), value.clone()))
+            .filter(|(branch, _)| branch.is_satisfiable())
+            .collect();
+        System { branches }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Constant values agree where they are equal
+    fn agree(_: &Face, a: &f64, b: &f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_overlapping_branches_must_agree() {
+        // i = 0 and j = 0 meet at a corner, where both are 1
+        let sides = vec![(Face::Eq0(0), 1.0), (Face::Eq0(1), 1.0), (Face::Eq1(0).and(Face::Eq1(1)), 5.0)];
+        let system = System::new(sides, agree).unwrap();
+        assert_eq!(system.lookup(&Face::Eq0(0).and(Face::Eq1(1))), Some(&1.0));
+        assert_eq!(system.lookup(&Face::Eq1(0).and(Face::Eq1(1))), Some(&5.0));
+        assert_eq!(system.lookup(&Face::Eq1(0)), None);
+
+        let clash = vec![(Face::Eq0(0), 1.0), (Face::Eq1(0), 2.0), (Face::Eq0(1), 3.0)];
+        assert_eq!(System::new(clash, agree), Err(SystemError::Disagree { first: 0, second: 2 }));
+    }
+
+    #[test]
+    fn test_totality() {
+        let ends = System::new(vec![(Face::Eq0(0), 0.0), (Face::Eq1(0), 1.0)], agree).unwrap();
+        assert!(ends.covers(&Face::boundary(0)));
+        assert!(!ends.is_total());
+        // Inside i = 0, the branch there covers everything
+        let at_start = ends.restrict(&Face::Eq0(0));
+        assert!(at_start.is_total());
+        assert_eq!(at_start.branches(), [(Face::Top, 0.0)]);
+        assert_eq!(at_start.lookup(&Face::Top), Some(&0.0));
+
+        let filled = System::new(vec![(Face::boundary(0), 0.0), (Face::Top, 0.0)], agree).unwrap();
+        assert!(filled.is_total());
+    }
+}
@@ -0,0 +1,484 @@
+//! `#[wasm_bindgen]` exports, compiled only with the `wasm-bindings` feature
+
+use wasm_bindgen::prelude::*;
+
+use crate::{CubicalError, Equiv, Face, Interval, Path, Path2, Square, BOUNDARY_EPSILON};
+
+/// Parse a path in `t`, surfacing parse errors as exceptions
+fn path(expression: &str) -> Result<Path, JsValue> {
+    Path::new(expression.to_string(), "t".to_string()).map_err(js_error)
+}
+
+/// The error as a `{ kind, message, detail }` exception, `kind` the
+/// variant and `detail` its fields
+fn js_error(error: CubicalError) -> JsValue {
+    let mut value = serde_json::to_value(&error).unwrap_or_default();
+    value["message"] = error.to_string().into();
+    to_js(value).unwrap_or_else(|_| JsValue::from_str(&error.to_string()))
+}
+
+/// Hand a JSON result to JS, surfacing serializer failures as exceptions
+fn to_js(value: serde_json::Value) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// WASM bindings
+#[wasm_bindgen]
+pub struct WasmInterval {
+    inner: Interval,
+}
+
+#[wasm_bindgen]
+impl WasmInterval {
+    /// The point `value`, clamped to `[0, 1]`
+    #[wasm_bindgen(constructor)]
+    pub fn new(value: f64) -> WasmInterval {
+        WasmInterval { inner: Interval::constant(value) }
+    }
+
+    /// The dimension variable `index`
+    pub fn variable(index: usize) -> WasmInterval {
+        WasmInterval { inner: Interval::var(index) }
+    }
+
+    pub fn zero() -> WasmInterval {
+        WasmInterval { inner: Interval::zero() }
+    }
+
+    pub fn one() -> WasmInterval {
+        WasmInterval { inner: Interval::one() }
+    }
+
+    pub fn meet(&self, other: &WasmInterval) -> WasmInterval {
+        WasmInterval {
+            inner: Interval::Meet(
+                Box::new(self.inner.clone()),
+                Box::new(other.inner.clone())
+            )
+        }
+    }
+
+    pub fn join(&self, other: &WasmInterval) -> WasmInterval {
+        WasmInterval {
+            inner: Interval::Join(
+                Box::new(self.inner.clone()),
+                Box::new(other.inner.clone())
+            )
+        }
+    }
+
+    pub fn neg(&self) -> WasmInterval {
+        WasmInterval {
+            inner: Interval::Neg(Box::new(self.inner.clone()))
+        }
+    }
+
+    pub fn eval(&self, point: f64) -> f64 {
+        self.inner.eval(point)
+    }
+
+    /// `eval` at each point of a `Float64Array`, in one call
+    pub fn eval_batch(&self, points: &[f64]) -> Vec<f64> {
+        self.inner.eval_batch(points)
+    }
+
+    /// Evaluate with the `k`th variable at `values[k]`
+    pub fn eval_env(&self, values: Vec<f64>) -> Result<f64, JsValue> {
+        self.inner.eval_env(&values).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmPath {
+    inner: Path,
+}
+
+#[wasm_bindgen]
+impl WasmPath {
+    #[wasm_bindgen(constructor)]
+    pub fn new(expression: String, parameter: String) -> Result<WasmPath, JsValue> {
+        let inner = Path::new(expression, parameter).map_err(js_error)?;
+        Ok(WasmPath { inner })
+    }
+
+    /// The path in its versioned JSON schema, see [`Path`]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.inner).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// A path written by `to_json`; other schema versions are rejected
+    pub fn from_json(json: &str) -> Result<WasmPath, JsValue> {
+        let inner = serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmPath { inner })
+    }
+
+    pub fn evaluate(&self, t: f64) -> f64 {
+        self.inner.evaluate(t)
+    }
+
+    pub fn check_boundaries(&self, start: f64, end: f64) -> bool {
+        self.inner.check_boundaries(start, end)
+    }
+
+    /// `evaluate` at each point of a `Float64Array`, in one call
+    pub fn evaluate_batch(&self, ts: &[f64]) -> Vec<f64> {
+        self.inner.evaluate_batch(ts)
+    }
+
+    /// The path at `n` evenly spaced points from `t0` to `t1`, as a
+    /// `Float64Array`
+    pub fn evaluate_range(&self, t0: f64, t1: f64, n: usize) -> Vec<f64> {
+        self.inner.evaluate_range(t0, t1, n)
+    }
+
+    /// [`Path::sample`] as two `Float64Array`s, `[ts, values]`, so a plot
+    /// takes one call across the boundary rather than one per point
+    pub fn sample_json(&self, n: usize) -> js_sys::Array {
+        let (ts, values): (Vec<f64>, Vec<f64>) = self.inner.sample(n).into_iter().unzip();
+        js_sys::Array::of2(&js_sys::Float64Array::from(&ts[..]), &js_sys::Float64Array::from(&values[..]))
+    }
+
+    /// The length of the graph of the path, see [`Path::arc_length`]
+    pub fn arc_length(&self, n: usize) -> f64 {
+        self.inner.arc_length(n)
+    }
+
+    /// Each end against where it should be, as `{ start_expected,
+    /// start_actual, start_ok, end_expected, end_actual, end_ok, epsilon }`;
+    /// `epsilon` defaults to `BOUNDARY_EPSILON`
+    pub fn check_boundaries_detailed(&self, start: f64, end: f64, epsilon: Option<f64>) -> Result<JsValue, JsValue> {
+        let report = self.inner.check_boundaries_detailed(start, end, epsilon.unwrap_or(BOUNDARY_EPSILON));
+        serde_wasm_bindgen::to_value(&report).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// This path followed by `other`, with velocities matched at the
+    /// junction if `smooth`
+    pub fn concat(&self, other: &WasmPath, smooth: bool) -> Result<WasmPath, JsValue> {
+        let joined = if smooth { self.inner.concat_smooth(&other.inner) } else { self.inner.concat(&other.inner) };
+        joined.map(|inner| WasmPath { inner }).map_err(js_error)
+    }
+
+    pub fn refl(value: f64) -> WasmPath {
+        WasmPath { inner: Path::refl(value) }
+    }
+
+    pub fn inverse(&self) -> WasmPath {
+        WasmPath { inner: self.inner.inverse() }
+    }
+
+    /// The path run backwards, as `inverse`
+    pub fn reversed(&self) -> WasmPath {
+        self.inverse()
+    }
+
+    /// The path run along a smoothstep, so its velocity is 0 at both ends
+    pub fn smoothstepped(&self) -> WasmPath {
+        WasmPath { inner: self.inner.smoothstepped() }
+    }
+
+    /// `[start, end]`
+    pub fn endpoints(&self) -> Vec<f64> {
+        let (start, end) = self.inner.endpoints();
+        vec![start, end]
+    }
+
+    /// The derivative at `t`
+    pub fn velocity(&self, t: f64) -> f64 {
+        self.inner.velocity(t)
+    }
+
+    /// Whether `other` carries on from this path with no jump in value or
+    /// velocity, to within `tol`
+    pub fn is_c1_with(&self, other: &WasmPath, tol: f64) -> bool {
+        self.inner.is_c1_with(&other.inner, tol)
+    }
+
+    /// `value` carried from this path to `other`
+    pub fn transport_along(&self, other: &WasmPath, value: f64) -> f64 {
+        self.inner.transport_to(&other.inner, value)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn expression(&self) -> String {
+        self.inner.expression.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmSquare {
+    inner: Square,
+}
+
+#[wasm_bindgen]
+impl WasmSquare {
+    /// `(i, j) ↦ p(i ∧ j)`
+    pub fn connection_meet(p: &WasmPath) -> WasmSquare {
+        WasmSquare { inner: Square::connection_meet(&p.inner) }
+    }
+
+    /// `(i, j) ↦ p(i ∨ j)`
+    pub fn connection_join(p: &WasmPath) -> WasmSquare {
+        WasmSquare { inner: Square::connection_join(&p.inner) }
+    }
+
+    pub fn eval(&self, i: f64, j: f64) -> f64 {
+        self.inner.eval(i, j)
+    }
+
+    /// The edge `i = 0`
+    pub fn left(&self) -> WasmPath {
+        WasmPath { inner: self.inner.left() }
+    }
+
+    /// The edge `i = 1`
+    pub fn right(&self) -> WasmPath {
+        WasmPath { inner: self.inner.right() }
+    }
+
+    /// The edge `j = 0`
+    pub fn bottom(&self) -> WasmPath {
+        WasmPath { inner: self.inner.bottom() }
+    }
+
+    /// The edge `j = 1`
+    pub fn top(&self) -> WasmPath {
+        WasmPath { inner: self.inner.top() }
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmPath2 {
+    inner: Path2,
+}
+
+#[wasm_bindgen]
+impl WasmPath2 {
+    /// The path with coordinates `x` and `y`, expressions in `t`
+    #[wasm_bindgen(constructor)]
+    pub fn new(x: &str, y: &str) -> Result<WasmPath2, JsValue> {
+        Ok(WasmPath2 { inner: Path2::parse(x, y, "t").map_err(js_error)? })
+    }
+
+    /// `[x, y]`
+    pub fn evaluate(&self, t: f64) -> Vec<f64> {
+        let (x, y) = self.inner.evaluate(t);
+        vec![x, y]
+    }
+
+    /// `[dx, dy]`
+    pub fn velocity(&self, t: f64) -> Vec<f64> {
+        let (dx, dy) = self.inner.velocity(t);
+        vec![dx, dy]
+    }
+
+    pub fn arc_length(&self, n: usize) -> f64 {
+        self.inner.arc_length(n)
+    }
+
+    pub fn check_boundaries(&self, start_x: f64, start_y: f64, end_x: f64, end_y: f64, tol: f64) -> bool {
+        self.inner.check_boundaries((start_x, start_y), (end_x, end_y), tol)
+    }
+
+    pub fn concat(&self, other: &WasmPath2) -> Result<WasmPath2, JsValue> {
+        Ok(WasmPath2 { inner: self.inner.concat(&other.inner).map_err(js_error)? })
+    }
+
+    /// [`Path2::sample`] as one `Float64Array`, `[x0, y0, x1, y1, ...]`
+    pub fn sample(&self, n: usize) -> js_sys::Float64Array {
+        let points: Vec<f64> = self.inner.sample(n).into_iter().flat_map(|(x, y)| [x, y]).collect();
+        js_sys::Float64Array::from(&points[..])
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmEquiv {
+    inner: Equiv,
+}
+
+#[wasm_bindgen]
+impl WasmEquiv {
+    /// The maps `forward` and `backward`, as expressions in `x`
+    #[wasm_bindgen(constructor)]
+    pub fn new(forward: &str, backward: &str) -> Result<WasmEquiv, JsValue> {
+        let inner = Equiv::parse(forward, backward, "x").map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmEquiv { inner })
+    }
+
+    /// Throws, naming the first sample point where the maps are not inverse
+    pub fn verify(&self, tol: f64, samples: usize) -> Result<(), JsValue> {
+        self.inner.verify(tol, samples).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// The path from `value` to its image
+    pub fn ua(&self, value: f64) -> WasmPath {
+        WasmPath { inner: crate::ua(&self.inner, value) }
+    }
+}
+
+// Global exported functions
+#[wasm_bindgen]
+pub fn path_eval(expr: &str, t: f64) -> Result<f64, JsValue> {
+    Ok(path(expr)?.evaluate(t))
+}
+
+#[wasm_bindgen]
+pub fn interval_meet(i: f64, j: f64) -> f64 {
+    i.min(j).clamp(0.0, 1.0)
+}
+
+#[wasm_bindgen]
+pub fn interval_join(i: f64, j: f64) -> f64 {
+    i.max(j).clamp(0.0, 1.0)
+}
+
+#[wasm_bindgen]
+pub fn interval_neg(i: f64) -> f64 {
+    (1.0 - i).clamp(0.0, 1.0)
+}
+
+/// `expr_json`, an `Interval` as JSON, simplified by
+/// [`Interval::simplify`] and given back as JSON
+#[wasm_bindgen]
+pub fn interval_simplify(expr_json: &str) -> Result<String, JsValue> {
+    let interval: Interval = serde_json::from_str(expr_json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_json::to_string(&interval.simplify()).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Pieces the composite in [`composition`] is sampled over
+const COMPOSITE_SAMPLES: usize = 100;
+
+// Composition operation demo
+#[wasm_bindgen]
+pub fn composition(path1_expr: &str, path2_expr: &str, t: f64) -> Result<JsValue, JsValue> {
+    to_js(composition_report(path1_expr, path2_expr, t)?)
+}
+
+/// The endpoint diagnostics, and when the paths meet the composite itself:
+/// its expression, samples, boundary report and whether the junction is C¹
+fn composition_report(path1_expr: &str, path2_expr: &str, t: f64) -> Result<serde_json::Value, JsValue> {
+    let path1 = path(path1_expr)?;
+    let path2 = path(path2_expr)?;
+
+    // Evaluate paths
+    let p1_val = path1.evaluate(t);
+    let p2_val = path2.evaluate(t);
+    
+    // Check composition at boundaries
+    let p1_end = path1.evaluate(1.0);
+    let p2_start = path2.evaluate(0.0);
+    let composable = (p1_end - p2_start).abs() < 1e-10;
+
+    let composite = path1.concat(&path2).ok().map(|joined| {
+        let boundary = joined.check_boundaries_detailed(path1.evaluate(0.0), path2.evaluate(1.0), BOUNDARY_EPSILON);
+        serde_json::json!({
+            "expression": joined.expression(),
+            "samples": joined.sample(COMPOSITE_SAMPLES),
+            "boundary": boundary,
+            "smooth_junction": path1.is_c1_with(&path2, BOUNDARY_EPSILON.sqrt()),
+        })
+    });
+
+    Ok(serde_json::json!({
+        "path1": path1_expr,
+        "path2": path2_expr,
+        "t": t,
+        "path1_value": p1_val,
+        "path2_value": p2_val,
+        "path1_endpoint": p1_end,
+        "path2_startpoint": p2_start,
+        "composable": composable,
+        "message": if composable { 
+            "Paths can be composed!" 
+        } else { 
+            "Paths cannot be composed - boundary mismatch" 
+        },
+        "composite": composite,
+    }))
+}
+
+/// The lid at height `cap_at` of the composition square on the path
+/// `base`, with sides on either end given as expressions in `t`
+#[wasm_bindgen]
+pub fn hcomp_path(
+    base: &str,
+    start_side: Option<String>,
+    end_side: Option<String>,
+    cap_at: f64,
+) -> Result<WasmPath, JsValue> {
+    let sides = box_sides(start_side, end_side)?;
+    crate::hcomp(&path(base)?, &sides, cap_at).map(|inner| WasmPath { inner }).map_err(js_error)
+}
+
+/// The whole composition square on `base`, sides as for `hcomp_path`, for
+/// showing the lid sweep up from the base
+#[wasm_bindgen]
+pub fn fill_square(base: &str, start_side: Option<String>, end_side: Option<String>) -> Result<WasmSquare, JsValue> {
+    let sides = box_sides(start_side, end_side)?;
+    crate::fill(&path(base)?, &sides).map(|inner| WasmSquare { inner }).map_err(js_error)
+}
+
+/// The sides given on `i = 0` and `i = 1`, parsed
+fn box_sides(start_side: Option<String>, end_side: Option<String>) -> Result<Vec<(Face, Path)>, JsValue> {
+    [(Face::Eq0(0), start_side), (Face::Eq1(0), end_side)]
+        .into_iter()
+        .filter_map(|(face, side)| side.map(|side| Ok((face, path(&side)?))))
+        .collect()
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[wasm_bindgen_test]
+    fn test_batches_agree_with_scalar_calls() {
+        let ts: Vec<f64> = (0..=100).map(|k| k as f64 / 100.0).collect();
+        let p = WasmPath::new("sin(π t) + t²".to_string(), "t".to_string()).unwrap();
+        let batch = p.evaluate_batch(&ts);
+        assert!(ts.iter().zip(&batch).all(|(&t, &value)| value == p.evaluate(t)));
+        // The same points, up to rounding in how they are spaced
+        let range = p.evaluate_range(0.0, 1.0, ts.len());
+        assert!(range.iter().zip(&batch).all(|(a, b)| (a - b).abs() < 1e-12));
+
+        let i = WasmInterval::variable(0).meet(&WasmInterval::new(0.5));
+        let batch = i.eval_batch(&ts);
+        assert!(ts.iter().zip(&batch).all(|(&t, &value)| value == i.eval(t)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_paths_round_trip_through_json() {
+        let p = WasmPath::new("if(t ≤ 0.5, 2t, exp(t - 0.5))".to_string(), "t".to_string()).unwrap();
+        let back = WasmPath::from_json(&p.to_json().unwrap()).unwrap();
+        assert_eq!(back.to_json().unwrap(), p.to_json().unwrap());
+        assert!((0..=10).all(|k| back.evaluate(k as f64 / 10.0) == p.evaluate(k as f64 / 10.0)));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_composite_passes_through_both_halves() {
+        let report = composition_report("t²", "1 + t", 0.5).unwrap();
+        assert_eq!(report["composable"], true);
+        let composite = &report["composite"];
+        assert_eq!(composite["boundary"]["start_ok"], true);
+        assert_eq!(composite["boundary"]["end_ok"], true);
+        // Slopes 2 and 1 at the junction
+        assert_eq!(composite["smooth_junction"], false);
+
+        let samples: Vec<(f64, f64)> = serde_json::from_value(composite["samples"].clone()).unwrap();
+        assert_eq!(samples.len(), COMPOSITE_SAMPLES + 1);
+        let (first, second) = (path("t²").unwrap(), path("1 + t").unwrap());
+        for (t, value) in samples {
+            let expected = if t <= 0.5 { first.evaluate(2.0 * t) } else { second.evaluate(2.0 * t - 1.0) };
+            assert!((value - expected).abs() < 1e-10);
+        }
+
+        let apart = composition_report("t", "t", 0.5).unwrap();
+        assert_eq!(apart["composable"], false);
+        assert!(apart["composite"].is_null());
+    }
+}
@@ -4,7 +4,7 @@
 use anyhow::Result;
 use colored::*;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent},
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -18,13 +18,30 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
-use sctt_core::{Term, Type};
-use sctt_smooth::{SmoothFunction, SmoothPath};
+use sctt_checker::lite;
+use sctt_core::input::{self, InputConfig, Key, KeyName};
 use std::{
     io,
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
+/// Remappable commands and their default keys; Enter, Esc and Backspace
+/// stay fixed so the settings screen can always be left
+const ACTIONS: &[(&str, &[Key])] = &[
+    ("up", &[Key::plain(KeyName::Up)]),
+    ("down", &[Key::plain(KeyName::Down)]),
+    ("left", &[Key::plain(KeyName::Left)]),
+    ("right", &[Key::plain(KeyName::Right)]),
+    ("interact", &[Key::char(' ')]),
+    ("puzzle", &[Key::char('p')]),
+    ("theory", &[Key::char('t')]),
+    ("combine", &[Key::char('c')]),
+    ("hint", &[Key::char('?')]),
+    ("settings", &[Key::char('s'), Key::plain(KeyName::F(2))]),
+    ("quit", &[Key::char('q')]),
+];
+
 // ============================================================================
 // GAME STATE
 // ============================================================================
@@ -41,8 +58,17 @@ struct GameState {
     coherence_meter: f64,
     messages: Vec<String>,
     current_challenge: Option<Challenge>,
+    answer: String,
     game_mode: GameMode,
     theory_fragments: Vec<TheoryFragment>,
+    /// Key bindings, shared with the REPL's config file
+    input: InputConfig,
+    /// Where changed bindings are saved; `None` keeps them in memory
+    config_path: Option<PathBuf>,
+    /// Selected row on the settings screen
+    settings_cursor: usize,
+    /// Waiting for the key to bind to the selected action
+    capturing: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -61,6 +87,7 @@ enum GameMode {
     Puzzle,
     TheoryBuilding,
     BossBattle,
+    Settings,
 }
 
 #[derive(Debug, Clone)]
@@ -82,10 +109,14 @@ struct Challenge {
 
 #[derive(Debug, Clone)]
 enum ChallengeType {
-    SmoothFunction { target: String, tolerance: f64 },
+    /// The answer is the derivative of `function`
+    SmoothFunction { function: String, tolerance: f64 },
     PathFinding { start: (f64, f64), end: (f64, f64) },
-    Composition { f: String, g: String, expected: String },
-    IntervalPuzzle { expression: String, answer: f64 },
+    /// The answer is `f ∘ g`, both functions of `x`
+    Composition { f: String, g: String },
+    /// The answer is the value of the closed interval `expression`
+    IntervalPuzzle { expression: String },
+    /// The answer is a path continuing `paths` without a corner
     CoherenceCheck { paths: Vec<String> },
 }
 
@@ -122,8 +153,29 @@ impl GameState {
             coherence_meter: 0.0,
             messages: vec!["Welcome to Smooth Quest!".to_string()],
             current_challenge: None,
+            answer: String::new(),
             game_mode: GameMode::Exploration,
             theory_fragments: vec![],
+            input: InputConfig::default(),
+            config_path: None,
+            settings_cursor: 0,
+            capturing: false,
+        }
+    }
+
+    /// The keys for `action`, as the help line shows them
+    fn key_label(&self, action: &str) -> String {
+        let defaults = ACTIONS.iter().find(|(name, _)| *name == action).map_or(&[][..], |(_, keys)| *keys);
+        let keys: Vec<String> = self.input.keys(action, defaults).iter().map(|k| k.to_string().to_uppercase()).collect();
+        keys.join("/")
+    }
+
+    fn save_settings(&mut self) {
+        let Some(path) = self.config_path.clone() else {
+            return;
+        };
+        if let Err(e) = self.input.save(&path) {
+            self.add_message(format!("Settings not saved: {}", e));
         }
     }
 
@@ -220,7 +272,7 @@ fn create_levels() -> Vec<Level> {
                     name: "First Derivative".to_string(),
                     question: "What is the derivative of x²?".to_string(),
                     challenge_type: ChallengeType::SmoothFunction {
-                        target: "2x".to_string(),
+                        function: "x²".to_string(),
                         tolerance: 0.01,
                     },
                     reward: MathConcept::Derivative { order: 1 },
@@ -252,18 +304,16 @@ fn create_levels() -> Vec<Level> {
                     name: "Interval Meet".to_string(),
                     question: "Compute 0.3 ∧ 0.7".to_string(),
                     challenge_type: ChallengeType::IntervalPuzzle {
-                        expression: "min(0.3, 0.7)".to_string(),
-                        answer: 0.3,
+                        expression: "0.3 ∧ 0.7".to_string(),
                     },
                     reward: MathConcept::IntervalOperation { op: "meet".to_string() },
                     hint: "Meet means minimum!".to_string(),
                 },
                 Challenge {
                     name: "De Morgan's Law".to_string(),
-                    question: "Verify ¬(i ∧ j) = ¬i ∨ ¬j".to_string(),
+                    question: "¬(i ∧ j) = ¬i ∨ ¬j. Compute ¬(0.3 ∧ 0.6)".to_string(),
                     challenge_type: ChallengeType::IntervalPuzzle {
-                        expression: "1 - min(i, j) = max(1-i, 1-j)".to_string(),
-                        answer: 1.0,  // True
+                        expression: "¬(0.3 ∧ 0.6)".to_string(),
                     },
                     reward: MathConcept::IntervalOperation { op: "demorgan".to_string() },
                     hint: "Test with specific values".to_string(),
@@ -284,9 +334,8 @@ fn create_levels() -> Vec<Level> {
                     name: "Chain Rule".to_string(),
                     question: "Compose sin(x) with x²".to_string(),
                     challenge_type: ChallengeType::Composition {
-                        f: "sin".to_string(),
+                        f: "sin(x)".to_string(),
                         g: "x²".to_string(),
-                        expected: "sin(x²)".to_string(),
                     },
                     reward: MathConcept::ChainRule,
                     hint: "(f∘g)(x) = f(g(x))".to_string(),
@@ -305,9 +354,9 @@ fn create_levels() -> Vec<Level> {
             challenges: vec![
                 Challenge {
                     name: "The Coherence Challenge".to_string(),
-                    question: "Find paths that compose smoothly".to_string(),
+                    question: "Continue the path t² with a path that composes smoothly".to_string(),
                     challenge_type: ChallengeType::CoherenceCheck {
-                        paths: vec!["t²".to_string(), "1 + t".to_string()],
+                        paths: vec!["t²".to_string()],
                     },
                     reward: MathConcept::CoherenceAxiom { number: 1 },
                     hint: "Check derivatives at connection points".to_string(),
@@ -327,14 +376,71 @@ fn create_levels() -> Vec<Level> {
 // PUZZLE SOLVERS
 // ============================================================================
 
-fn check_smooth_function_answer(input: &str, target: &str, tolerance: f64) -> bool {
-    // Simplified check - in real game would parse and evaluate
-    input.trim().eq_ignore_ascii_case(target) || 
-    input.contains(&target)
+/// Where typed function answers are compared against the engine's
+const SAMPLE_POINTS: [f64; 5] = [-1.5, -0.5, 0.25, 1.0, 2.0];
+
+/// Judge a typed answer with the SCTT engine
+///
+/// `Ok(false)` is a wrong answer; `Err` is one the engine could not read,
+/// with a message to show the player. `PathFinding` is solved by moving,
+/// not typing, and is judged by [`evaluate_path_smoothness`] instead.
+fn judge(challenge: &ChallengeType, answer: &str) -> Result<bool, String> {
+    let answer = answer.trim();
+    match challenge {
+        ChallengeType::SmoothFunction { function, tolerance } => {
+            expect_function(answer)?;
+            let derivative = lite::derivative_of(function).map_err(|e| e.to_string())?;
+            Ok(SAMPLE_POINTS.iter().all(|&x| match derivative.at(x) {
+                Ok(expected) => lite::evaluate_smooth(answer, x)
+                    .is_ok_and(|given| (given.value - expected).abs() <= *tolerance),
+                Err(_) => true,
+            }))
+        }
+        ChallengeType::Composition { f, g } => {
+            expect_function(answer)?;
+            Ok(SAMPLE_POINTS.iter().all(|&x| {
+                let composite = lite::evaluate_smooth(g, x)
+                    .and_then(|inner| lite::evaluate_smooth(f, inner.value));
+                match composite {
+                    Ok(expected) => lite::evaluate_smooth(answer, x)
+                        .is_ok_and(|given| (given.value - expected.value).abs() < 1e-9),
+                    Err(_) => true,
+                }
+            }))
+        }
+        ChallengeType::IntervalPuzzle { expression } => {
+            let checked = lite::check_expression_against(answer, "I").map_err(|e| e.to_string())?;
+            if let Some(message) = checked.message {
+                return Err(message);
+            }
+            let expected = lite::evaluate_smooth(expression, 0.0).map_err(|e| e.to_string())?;
+            let given = lite::evaluate_smooth(answer, 0.0).map_err(|e| e.to_string())?;
+            Ok((given.value - expected.value).abs() < 1e-9)
+        }
+        ChallengeType::CoherenceCheck { paths } => {
+            let Some(last) = paths.last() else { return Ok(true) };
+            let mut chain = paths.windows(2).map(|w| (w[0].as_str(), w[1].as_str())).collect::<Vec<_>>();
+            chain.push((last, answer));
+            for (p, q) in chain {
+                if !lite::paths_composable(p, q).map_err(|e| e.to_string())?.smooth {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        ChallengeType::PathFinding { .. } => Err("Walk the path instead of typing it".to_string()),
+    }
 }
 
-fn check_interval_answer(input: f64, expected: f64, tolerance: f64) -> bool {
-    (input - expected).abs() < tolerance
+/// An answer that should be a real function has to type as one
+fn expect_function(answer: &str) -> Result<(), String> {
+    let checked = lite::check_expression_against(answer, "ℝ → ℝ").map_err(|e| e.to_string())?;
+    match checked.message {
+        // A constant is a fine answer too: the derivative of x is 1
+        Some(_) if checked.inferred == sctt_checker::Type::Real => Ok(()),
+        Some(message) => Err(message),
+        None => Ok(()),
+    }
 }
 
 fn evaluate_path_smoothness(path: &[(f64, f64)]) -> f64 {
@@ -388,8 +494,12 @@ fn draw_game<B: Backend>(f: &mut Frame<B>, state: &GameState) {
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(chunks[1]);
     
-    // Game field
-    draw_game_field(f, main_chunks[0], state);
+    // Game field, or the key bindings while they are being changed
+    if matches!(state.game_mode, GameMode::Settings) {
+        draw_settings(f, main_chunks[0], state);
+    } else {
+        draw_game_field(f, main_chunks[0], state);
+    }
     
     // Inventory and theory
     draw_inventory(f, main_chunks[1], state);
@@ -464,7 +574,7 @@ fn draw_game_field<B: Backend>(f: &mut Frame<B>, area: Rect, state: &GameState)
                 
                 field_text.push(Line::from(""));
                 field_text.push(Line::from(Span::styled(
-                    "Hint: Press 'h' for a hint",
+                    "Hint: Press '?' for a hint",
                     Style::default().fg(Color::Gray)
                 )));
             }
@@ -603,18 +713,56 @@ fn draw_messages<B: Backend>(f: &mut Frame<B>, area: Rect, state: &GameState) {
     f.render_widget(messages, area);
 }
 
+fn draw_settings<B: Backend>(f: &mut Frame<B>, area: Rect, state: &GameState) {
+    let block = Block::default()
+        .title(" Key Bindings ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+    let items: Vec<ListItem> = ACTIONS
+        .iter()
+        .enumerate()
+        .map(|(i, (action, _))| {
+            let keys = if state.capturing && i == state.settings_cursor {
+                "press a key…".to_string()
+            } else {
+                state.key_label(action)
+            };
+            let style = if i == state.settings_cursor {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            ListItem::new(format!("{:<10} {}", action, keys)).style(style)
+        })
+        .collect();
+    f.render_widget(List::new(items).block(block), area);
+}
+
 fn draw_input_area<B: Backend>(f: &mut Frame<B>, area: Rect, state: &GameState) {
     let help_text = match state.game_mode {
-        GameMode::Exploration => "Arrow keys: Move | Space: Interact | P: Puzzle | T: Theory | Q: Quit",
-        GameMode::Puzzle => "Type answer and press Enter | Esc: Back | H: Hint",
-        GameMode::TheoryBuilding => "1-9: Select | C: Combine | Esc: Back",
-        GameMode::BossBattle => "Prove the coherence theorem to win!",
+        GameMode::Exploration => format!(
+            "Arrow keys: Move | {}: Interact | {}: Puzzle | {}: Theory | {}: Settings | {}: Quit",
+            state.key_label("interact"),
+            state.key_label("puzzle"),
+            state.key_label("theory"),
+            state.key_label("settings"),
+            state.key_label("quit"),
+        ),
+        GameMode::Puzzle => format!("Type answer and press Enter | Esc: Back | {}: Hint | \\lambda, -> type λ, →", state.key_label("hint")),
+        GameMode::TheoryBuilding => format!("1-9: Select | {}: Combine | Esc: Back", state.key_label("combine")),
+        GameMode::BossBattle => "Prove the coherence theorem to win!".to_string(),
+        GameMode::Settings => "Up/Down: Select | Enter: Rebind | Esc: Back".to_string(),
     };
     
-    let input = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::Gray))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+    let input = match state.game_mode {
+        // Show the answer as the engine will read it
+        GameMode::Puzzle if !state.answer.is_empty() => Paragraph::new(format!("> {}", input::expand(&state.answer)))
+            .style(Style::default().fg(Color::White)),
+        _ => Paragraph::new(help_text)
+            .style(Style::default().fg(Color::Gray))
+            .alignment(Alignment::Center),
+    }
+    .block(Block::default().borders(Borders::ALL));
     
     f.render_widget(input, area);
 }
@@ -623,15 +771,39 @@ fn draw_input_area<B: Backend>(f: &mut Frame<B>, area: Rect, state: &GameState)
 // GAME LOGIC
 // ============================================================================
 
-fn handle_input(state: &mut GameState, key: KeyCode) -> bool {
+/// The shared key name for a crossterm event
+fn key_of(event: &KeyEvent) -> Option<Key> {
+    let name = match event.code {
+        KeyCode::Char(c) => KeyName::Char(c),
+        KeyCode::Up => KeyName::Up,
+        KeyCode::Down => KeyName::Down,
+        KeyCode::Left => KeyName::Left,
+        KeyCode::Right => KeyName::Right,
+        KeyCode::Enter => KeyName::Enter,
+        KeyCode::Esc => KeyName::Esc,
+        KeyCode::Backspace => KeyName::Backspace,
+        KeyCode::Tab => KeyName::Tab,
+        KeyCode::F(n) => KeyName::F(n),
+        _ => return None,
+    };
+    Some(Key {
+        ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        alt: event.modifiers.contains(KeyModifiers::ALT),
+        name,
+    })
+}
+
+fn handle_input(state: &mut GameState, event: KeyEvent) -> bool {
+    let key = event.code;
+    let action = key_of(&event).and_then(|k| state.input.resolve(&k, ACTIONS));
     match state.game_mode {
         GameMode::Exploration => {
-            match key {
-                KeyCode::Up => state.player.move_smooth(0.0, 0.1),
-                KeyCode::Down => state.player.move_smooth(0.0, -0.1),
-                KeyCode::Left => state.player.move_smooth(-0.1, 0.0),
-                KeyCode::Right => state.player.move_smooth(0.1, 0.0),
-                KeyCode::Char(' ') => {
+            match action {
+                Some("up") => state.player.move_smooth(0.0, 0.1),
+                Some("down") => state.player.move_smooth(0.0, -0.1),
+                Some("left") => state.player.move_smooth(-0.1, 0.0),
+                Some("right") => state.player.move_smooth(0.1, 0.0),
+                Some("interact") => {
                     // Check for nearby challenge
                     if let Some(level) = state.levels.get(state.current_level) {
                         if !level.challenges.is_empty() {
@@ -641,7 +813,7 @@ fn handle_input(state: &mut GameState, key: KeyCode) -> bool {
                         }
                     }
                 }
-                KeyCode::Char('p') | KeyCode::Char('P') => {
+                Some("puzzle") => {
                     state.game_mode = GameMode::Puzzle;
                     if let Some(level) = state.levels.get(state.current_level) {
                         if !level.challenges.is_empty() {
@@ -649,11 +821,15 @@ fn handle_input(state: &mut GameState, key: KeyCode) -> bool {
                         }
                     }
                 }
-                KeyCode::Char('t') | KeyCode::Char('T') => {
+                Some("theory") => {
                     state.game_mode = GameMode::TheoryBuilding;
                     state.add_message("Entering theory building mode!".to_string());
                 }
-                KeyCode::Char('q') | KeyCode::Char('Q') => return true,
+                Some("settings") => {
+                    state.game_mode = GameMode::Settings;
+                    state.settings_cursor = 0;
+                }
+                Some("quit") => return true,
                 _ => {}
             }
             
@@ -677,16 +853,40 @@ fn handle_input(state: &mut GameState, key: KeyCode) -> bool {
             match key {
                 KeyCode::Esc => {
                     state.game_mode = GameMode::Exploration;
+                    state.answer.clear();
                     state.add_message("Back to exploration".to_string());
                 }
-                KeyCode::Char('h') | KeyCode::Char('H') => {
+                // Letters and digits are always part of the answer
+                KeyCode::Char(c) if action == Some("hint") && !c.is_alphanumeric() => {
                     if let Some(challenge) = &state.current_challenge {
                         state.add_message(format!("Hint: {}", challenge.hint));
                     }
                 }
+                KeyCode::Char(c) => state.answer.push(c),
+                KeyCode::Backspace => {
+                    state.answer.pop();
+                }
                 KeyCode::Enter => {
-                    // Check answer (simplified for demo)
-                    if let Some(challenge) = &state.current_challenge {
+                    if let Some(challenge) = state.current_challenge.clone() {
+                        let verdict = match &challenge.challenge_type {
+                            ChallengeType::PathFinding { .. } => {
+                                Ok(evaluate_path_smoothness(&state.player.path_history) > 0.5)
+                            }
+                            challenge_type => judge(challenge_type, &input::expand(&state.answer)),
+                        };
+                        match verdict {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                state.add_message("Not quite. Try again, or press ? for a hint".to_string());
+                                return false;
+                            }
+                            Err(e) => {
+                                state.add_message(format!("The engine can't read that: {}", e));
+                                return false;
+                            }
+                        }
+
+                        state.answer.clear();
                         state.inventory.push(challenge.reward.clone());
                         state.knowledge_points += 10;
                         state.add_message("Correct! Concept acquired!".to_string());
@@ -715,7 +915,7 @@ fn handle_input(state: &mut GameState, key: KeyCode) -> bool {
                 KeyCode::Esc => {
                     state.game_mode = GameMode::Exploration;
                 }
-                KeyCode::Char('c') | KeyCode::Char('C') => {
+                _ if action == Some("combine") => {
                     if state.theory_fragments.len() >= 2 {
                         state.add_message("Combining theories...".to_string());
                         state.coherence_meter += 0.2;
@@ -747,6 +947,28 @@ fn handle_input(state: &mut GameState, key: KeyCode) -> bool {
                 _ => {}
             }
         }
+        GameMode::Settings => {
+            if state.capturing {
+                state.capturing = false;
+                match key_of(&event) {
+                    Some(Key { name: KeyName::Esc, .. }) | None => {}
+                    Some(new) => {
+                        let action = ACTIONS[state.settings_cursor].0;
+                        state.input.rebind(action, new);
+                        state.save_settings();
+                        state.add_message(format!("{} is now on {}", action, new));
+                    }
+                }
+                return false;
+            }
+            match key {
+                KeyCode::Up => state.settings_cursor = state.settings_cursor.saturating_sub(1),
+                KeyCode::Down => state.settings_cursor = (state.settings_cursor + 1).min(ACTIONS.len() - 1),
+                KeyCode::Enter => state.capturing = true,
+                KeyCode::Esc => state.game_mode = GameMode::Exploration,
+                _ => {}
+            }
+        }
     }
     
     false
@@ -766,6 +988,13 @@ fn main() -> Result<()> {
     
     // Game state
     let mut game_state = GameState::new();
+    game_state.config_path = InputConfig::path();
+    if let Some(path) = &game_state.config_path {
+        match InputConfig::load(path) {
+            Ok(config) => game_state.input = config,
+            Err(e) => game_state.add_message(format!("Default keys in use: {}", e)),
+        }
+    }
     game_state.add_message("Welcome to Smooth Quest!".to_string());
     game_state.add_message("Learn SCTT by solving puzzles!".to_string());
     
@@ -774,8 +1003,8 @@ fn main() -> Result<()> {
         terminal.draw(|f| draw_game(f, &game_state))?;
         
         if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
-                if handle_input(&mut game_state, code) {
+            if let Event::Key(key) = event::read()? {
+                if handle_input(&mut game_state, key) {
                     break;
                 }
             }
@@ -795,4 +1024,82 @@ fn main() -> Result<()> {
     println!("  Theory Fragments: {}", game_state.theory_fragments.len());
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn judged(challenge: ChallengeType, answer: &str) -> bool {
+        judge(&challenge, answer).unwrap_or(false)
+    }
+
+    fn press(state: &mut GameState, code: KeyCode) -> bool {
+        handle_input(state, KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_settings_screen_remaps_keys() {
+        let mut state = GameState::new();
+        assert!(!press(&mut state, KeyCode::Char('s')));
+        assert!(matches!(state.game_mode, GameMode::Settings));
+
+        // Bind quit to `a`, as on an AZERTY keyboard
+        let quit = ACTIONS.iter().position(|(name, _)| *name == "quit").unwrap();
+        for _ in 0..quit {
+            press(&mut state, KeyCode::Down);
+        }
+        press(&mut state, KeyCode::Enter);
+        press(&mut state, KeyCode::Char('a'));
+        press(&mut state, KeyCode::Esc);
+        assert_eq!(state.key_label("quit"), "A");
+        assert!(!press(&mut state, KeyCode::Char('q')));
+        assert!(press(&mut state, KeyCode::Char('A')));
+    }
+
+    #[test]
+    fn test_puzzle_answers_accept_digraphs() {
+        let meet = ChallengeType::IntervalPuzzle { expression: "¬(0.3 ∧ 0.6)".to_string() };
+        assert!(judged(meet, &input::expand("\\not 0.3 \\/ \\not 0.6")));
+    }
+
+    #[test]
+    fn test_engine_built_without_web_bindings() {
+        assert!(!lite::WASM_BINDINGS);
+    }
+
+    #[test]
+    fn test_smooth_function_validator() {
+        let derivative_of_square = || ChallengeType::SmoothFunction { function: "x²".to_string(), tolerance: 0.01 };
+        assert!(judged(derivative_of_square(), "2x"));
+        assert!(judged(derivative_of_square(), "x + x"));
+        assert!(!judged(derivative_of_square(), "x²"));
+        assert!(!judged(derivative_of_square(), "2"));
+        assert!(judge(&derivative_of_square(), "2x +").is_err());
+    }
+
+    #[test]
+    fn test_composition_validator() {
+        let chain = || ChallengeType::Composition { f: "sin(x)".to_string(), g: "x²".to_string() };
+        assert!(judged(chain(), "sin(x²)"));
+        assert!(judged(chain(), "sin(x * x)"));
+        assert!(!judged(chain(), "sin(x)²"));
+    }
+
+    #[test]
+    fn test_interval_and_coherence_validators() {
+        let meet = || ChallengeType::IntervalPuzzle { expression: "0.3 ∧ 0.7".to_string() };
+        assert!(judged(meet(), "0.3"));
+        assert!(!judged(meet(), "0.7"));
+        // Outside the interval is a type error, not merely a wrong answer
+        assert!(judge(&meet(), "1.3").is_err());
+
+        let de_morgan = ChallengeType::IntervalPuzzle { expression: "¬(0.3 ∧ 0.6)".to_string() };
+        assert!(judged(de_morgan, "¬0.3 ∨ ¬0.6"));
+
+        let coherence = || ChallengeType::CoherenceCheck { paths: vec!["t²".to_string()] };
+        assert!(judged(coherence(), "1 + 2t"));
+        assert!(!judged(coherence(), "1 + t"));
+        assert!(!judged(coherence(), "2t"));
+    }
+}
@@ -1,14 +1,18 @@
 //! SCTT Playground - Interactive REPL for experimenting with SCTT concepts
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::*;
 use rustyline::error::ReadlineError;
-use rustyline::{DefaultEditor, Result as RustyResult};
+use rustyline::{Cmd, DefaultEditor, EventHandler, KeyCode, Modifiers, Result as RustyResult};
+use sctt_core::input::{self, InputConfig, Key, KeyName};
 use sctt_core::{Term, Type};
 use sctt_smooth::examples::{Exp, Polynomial, Sin};
 use sctt_smooth::{SmoothFunction, SmoothPath};
 use std::io::{self, Write};
+use std::path::PathBuf;
+use sctt_system::certificate;
+use sctt_system::rename::{self as refactor, Document, Workspace};
 
 #[derive(Parser, Debug)]
 #[command(name = "sctt-playground")]
@@ -21,10 +25,81 @@ struct Args {
     /// Enable verbose output
     #[arg(short, long)]
     verbose: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Translate a file from another proof assistant into .sctt source
+    ///
+    /// The translation goes to stdout (or `--output`) and a per-definition
+    /// report to stderr. Unsupported constructs are listed with their
+    /// locations and nothing is written.
+    Import {
+        dialect: Dialect,
+        file: PathBuf,
+        /// Write the translation here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print where the bytes of a compiled module go, by section
+    Size { wasm: PathBuf },
+    /// Remove the proof certificate (and source map) from a compiled module
+    ///
+    /// The stripped module goes to `--output` and the certificate, as JSON,
+    /// to `--certificate`; each records a hash of the other.
+    Strip {
+        wasm: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        #[arg(short, long)]
+        certificate: PathBuf,
+    },
+    /// Check a stripped module against its detached certificate
+    Verify { wasm: PathBuf, certificate: PathBuf },
+    /// Rename a definition across files of importable definitions
+    ///
+    /// The files are checked in order as one workspace. Every occurrence
+    /// that resolves to the definition is rewritten, and the files are
+    /// written only if every definition that checked still does.
+    Rename {
+        old: String,
+        new: String,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// List the occurrences without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check a file of `fix` and `unsafe_fix` definitions
+    ///
+    /// Each definition is checked with the ones before it in scope and
+    /// reported with its termination status; definitions resting on an
+    /// `unsafe_fix` are named with it.
+    Check { file: PathBuf },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Dialect {
+    Cubicaltt,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
+
+    match args.command {
+        Some(Command::Import { dialect: Dialect::Cubicaltt, file, output }) => {
+            return import_cubicaltt(&file, output.as_deref());
+        }
+        Some(Command::Size { wasm }) => return print_size(&std::fs::read(wasm)?),
+        Some(Command::Strip { wasm, output, certificate }) => return strip(&wasm, &output, &certificate),
+        Some(Command::Verify { wasm, certificate }) => return verify(&wasm, &certificate),
+        Some(Command::Rename { old, new, files, dry_run }) => return rename(&old, &new, &files, dry_run),
+        Some(Command::Check { file }) => return check_file(&file),
+        None => {}
+    }
     
     print_banner();
     
@@ -37,6 +112,128 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn import_cubicaltt(file: &std::path::Path, output: Option<&std::path::Path>) -> Result<()> {
+    let source = std::fs::read_to_string(file)?;
+    let import = match sctt_system::cubicaltt::import(&source) {
+        Ok(import) => import,
+        Err(e) => {
+            for problem in &e.problems {
+                eprintln!("{}:{}", file.display(), problem);
+            }
+            bail!("{} not imported: {} unsupported or malformed construct(s)", file.display(), e.problems.len());
+        }
+    };
+
+    match output {
+        Some(path) => std::fs::write(path, import.source())?,
+        None => print!("{}", import.source()),
+    }
+    eprint!("{}", import.report());
+    if !import.all_checked() {
+        bail!("translated, but some definitions do not check");
+    }
+    Ok(())
+}
+
+/// Files named on the command line, written in place
+struct Files(Vec<Document>);
+
+impl Workspace for Files {
+    fn documents(&self) -> Vec<Document> {
+        self.0.clone()
+    }
+
+    fn write(&mut self, name: &str, source: &str) -> std::result::Result<(), String> {
+        std::fs::write(name, source).map_err(|e| e.to_string())
+    }
+}
+
+fn rename(old: &str, new: &str, files: &[PathBuf], dry_run: bool) -> Result<()> {
+    let documents = files
+        .iter()
+        .map(|f| Ok(Document::new(&f.display().to_string(), &std::fs::read_to_string(f)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let renamed = if dry_run {
+        refactor::plan(&documents, old, new)?
+    } else {
+        refactor::rename_definition(&mut Files(documents), old, new)?
+    };
+    for o in &renamed.occurrences {
+        println!("{}:{} in {}", o.document, o.location, o.definition);
+    }
+    let verb = if dry_run { "would rename" } else { "renamed" };
+    eprintln!(
+        "{} {} {} occurrence(s) of `{}` to `{}`; {} definition(s) check",
+        "✓".green(), verb, renamed.occurrences.len(), old, new, renamed.rechecked.len()
+    );
+    Ok(())
+}
+
+fn check_file(file: &std::path::Path) -> Result<()> {
+    use sctt_core::definitions::Definitions;
+    use sctt_core::termination::{self, TerminationStatus};
+
+    let source = std::fs::read_to_string(file)?;
+    let fixes = termination::parse_fixes(&source).map_err(|e| sctt_core::TypeError::from(e).render(&source));
+    let fixes = match fixes {
+        Ok(fixes) => fixes,
+        Err(rendered) => bail!("{}\n{} not checked", rendered, file.display()),
+    };
+    let mut defs = Definitions::new();
+    let mut failed = 0;
+    for fix in &fixes {
+        if let Err(e) = defs.define_fix(fix) {
+            eprintln!("{}: {}", file.display(), e.render(&source));
+            failed += 1;
+        }
+    }
+    let report = defs.termination();
+    for (name, status) in &report.statuses {
+        let status = match status {
+            TerminationStatus::Structural => "structural".green(),
+            TerminationStatus::Unchecked => "unchecked (unsafe_fix)".yellow(),
+            TerminationStatus::Rejected(e) => e.to_string().red(),
+        };
+        match report.tainted.get(name) {
+            Some(culprits) => {
+                let culprits: Vec<&str> = culprits.iter().map(String::as_str).collect();
+                println!("{}: {}, tainted by {}", name, status, culprits.join(", ").yellow());
+            }
+            None => println!("{}: {}", name, status),
+        }
+    }
+    if failed > 0 {
+        bail!("{} of {} definition(s) rejected", failed, fixes.len());
+    }
+    Ok(())
+}
+
+fn print_size(wasm: &[u8]) -> Result<()> {
+    let size = certificate::size_report(wasm)?;
+    let percent = |n: usize| if size.total == 0 { 0.0 } else { 100.0 * n as f64 / size.total as f64 };
+    for (name, n) in [("code", size.code), ("types", size.types), ("proof", size.proof), ("srcmap", size.srcmap), ("other", size.other)] {
+        println!("{:<8}{:>10} bytes {:>6.1}%", name, n, percent(n));
+    }
+    println!("{:<8}{:>10} bytes", "total", size.total);
+    Ok(())
+}
+
+fn strip(wasm: &std::path::Path, output: &std::path::Path, cert: &std::path::Path) -> Result<()> {
+    let bytes = std::fs::read(wasm)?;
+    let stripped = certificate::strip_certificates(&bytes)?;
+    std::fs::write(output, &stripped.wasm)?;
+    std::fs::write(cert, serde_json::to_vec_pretty(&stripped.certificate)?)?;
+    eprintln!("{} → {} bytes ({} saved)", bytes.len(), stripped.wasm.len(), bytes.len() - stripped.wasm.len());
+    Ok(())
+}
+
+fn verify(wasm: &std::path::Path, cert: &std::path::Path) -> Result<()> {
+    let certificate: certificate::DetachedCertificate = serde_json::from_slice(&std::fs::read(cert)?)?;
+    certificate::verify_detached(&std::fs::read(wasm)?, &certificate)?;
+    println!("{} {} matches {}", "✓".green(), wasm.display(), cert.display());
+    Ok(())
+}
+
 fn print_banner() {
     println!("{}", "╔════════════════════════════════════════════════════════╗".cyan());
     println!("{}", "║     SCTT Playground - Rust Edition                    ║".cyan());
@@ -45,8 +242,47 @@ fn print_banner() {
     println!();
 }
 
+/// The input config from the data directory; a broken one is reported and
+/// replaced by the defaults
+fn load_input_config() -> InputConfig {
+    let Some(path) = InputConfig::path() else {
+        return InputConfig::default();
+    };
+    InputConfig::load(&path).unwrap_or_else(|e| {
+        eprintln!("{}: {}", "Warning".yellow(), e);
+        InputConfig::default()
+    })
+}
+
+fn readline_key(key: &Key) -> rustyline::KeyEvent {
+    let mut mods = Modifiers::NONE;
+    if key.ctrl {
+        mods |= Modifiers::CTRL;
+    }
+    if key.alt {
+        mods |= Modifiers::ALT;
+    }
+    let code = match key.name {
+        KeyName::Char(c) => return rustyline::KeyEvent::new(c, mods),
+        KeyName::Up => KeyCode::Up,
+        KeyName::Down => KeyCode::Down,
+        KeyName::Left => KeyCode::Left,
+        KeyName::Right => KeyCode::Right,
+        KeyName::Enter => KeyCode::Enter,
+        KeyName::Esc => KeyCode::Esc,
+        KeyName::Backspace => KeyCode::Backspace,
+        KeyName::Tab => KeyCode::Tab,
+        KeyName::F(n) => KeyCode::F(n),
+    };
+    rustyline::KeyEvent(code, mods)
+}
+
 fn run_repl(verbose: bool) -> Result<()> {
     let mut rl = DefaultEditor::new()?;
+    let config = load_input_config();
+    for (key, symbol) in &config.symbols {
+        rl.bind_sequence(readline_key(key), EventHandler::Simple(Cmd::Insert(1, symbol.clone())));
+    }
     
     println!("{}", "Starting interactive session...".green());
     println!("Try: {}", "smooth sin".yellow());
@@ -63,9 +299,14 @@ fn run_repl(verbose: bool) -> Result<()> {
                     continue;
                 }
                 
-                rl.add_history_entry(line)?;
+                // Echo the expansion so digraph users learn the symbols
+                let expanded = input::expand(line);
+                if expanded != line {
+                    println!("  {}", expanded.dimmed());
+                }
+                rl.add_history_entry(&expanded)?;
                 
-                match process_command(line, verbose) {
+                match process_command(&expanded, verbose, &config) {
                     Ok(should_quit) => {
                         if should_quit {
                             break;
@@ -93,7 +334,7 @@ fn run_repl(verbose: bool) -> Result<()> {
     Ok(())
 }
 
-fn process_command(input: &str, verbose: bool) -> Result<bool> {
+fn process_command(input: &str, verbose: bool, config: &InputConfig) -> Result<bool> {
     let parts: Vec<&str> = input.split_whitespace().collect();
     
     if parts.is_empty() {
@@ -106,6 +347,10 @@ fn process_command(input: &str, verbose: bool) -> Result<bool> {
             print_help();
             Ok(false)
         }
+        ":symbols" => {
+            print_symbols(config);
+            Ok(false)
+        }
         "smooth" => {
             if parts.len() < 2 {
                 println!("Usage: smooth <function>");
@@ -156,6 +401,7 @@ fn print_help() {
     println!("  {} <expr> - Show type of expression", "type".cyan());
     println!("  {} - Explore interval operations", "interval".cyan());
     println!("  {} - See the coherence challenge", "coherence".cyan());
+    println!("  {} - List ASCII spellings and keys for symbols", ":symbols".cyan());
     println!();
     println!("{}", "Examples:".bold());
     println!("  smooth sin");
@@ -163,6 +409,21 @@ fn print_help() {
     println!("  compose");
 }
 
+fn print_symbols(config: &InputConfig) {
+    println!("{}", "Digraphs (expanded when you press Enter):".bold());
+    for (ascii, symbol) in input::DIGRAPHS {
+        println!("  {:<10} {}", ascii.cyan(), symbol);
+    }
+    println!();
+    println!("{}", "Keys:".bold());
+    for (key, symbol) in &config.symbols {
+        println!("  {:<10} {}", key.to_string().cyan(), symbol);
+    }
+    if let Some(path) = InputConfig::path() {
+        println!("  (set in {})", path.display());
+    }
+}
+
 fn demonstrate_smooth_function(func_name: &str, verbose: bool) -> Result<()> {
     println!("{}", format!("Exploring smooth function: {}", func_name).bold());
     
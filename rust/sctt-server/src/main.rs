@@ -1,118 +1,284 @@
+//! SCTT web server: Leptos SSR plus the REST and websocket API
+//!
+//! The API routes are an actix adapter over `sctt_system::service`, the same
+//! handlers the axum API server uses; this binary adds server-side rendering
+//! and the cross-origin-isolated `/wasm` assets. Saved proofs live in memory
+//! here. Settings come from the shared `Config` flags and environment
+//! variables.
+
+use std::sync::Arc;
+use std::time::Instant;
+
 use actix_cors::Cors;
 use actix_files::Files;
-use actix_web::{middleware, web, App, HttpServer, HttpResponse, Result};
+use actix_web::http::StatusCode;
+use actix_web::{middleware, web, App, HttpRequest, HttpResponse, HttpServer};
 use leptos::*;
 use leptos_actix::{generate_route_list, LeptosRoutes};
 use sctt_web::App as SCTTApp;
 
+use sctt_system::api::{
+    ApiError, ApplyInTransactionRequest, BeginTransactionRequest, CheckTerminationRequest, CompileRequest,
+    EvaluateBatchRequest, EvaluateRequest, ExplainSmoothnessRequest, ProveRequest, RenameRequest, SaveProofRequest,
+    TypeCheckRequest,
+};
+use sctt_system::service::{self, Config, MemoryStore, Service, ServiceError, TICK_INTERVAL};
+
+type AppState = web::Data<Service<MemoryStore>>;
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
-    log::info!("Starting SCTT server on http://localhost:8080");
+    let config = Config::load().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    std::fs::create_dir_all(&config.artifacts_dir)?;
+    let state: AppState = web::Data::from(Arc::new(Service::new(&config, MemoryStore::default())));
+
+    // Idle drivers lose the token, quiet transactions roll back
+    {
+        let state = state.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(TICK_INTERVAL);
+            loop {
+                interval.tick().await;
+                state.tick(Instant::now());
+            }
+        });
+    }
 
     // Get Leptos configuration
-    let conf = get_configuration(None).await.unwrap();
-    let addr = conf.leptos_options.site_addr;
+    let conf = get_configuration(None)
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
     let routes = generate_route_list(SCTTApp);
 
+    log::info!("Starting SCTT server on http://{}", config.addr);
+    let addr = config.addr;
+
     HttpServer::new(move || {
         let leptos_options = &conf.leptos_options;
         let site_root = &leptos_options.site_root;
 
         App::new()
+            .app_data(state.clone())
             // Enable CORS for WASM
-            .wrap(
-                Cors::default()
-                    .allow_any_origin()
-                    .allow_any_method()
-                    .allow_any_header()
-                    .expose_headers(vec![
-                        "Cross-Origin-Opener-Policy",
-                        "Cross-Origin-Embedder-Policy",
-                    ])
-            )
+            .wrap(cors(&config))
             .wrap(middleware::Logger::default())
             .wrap(middleware::Compress::default())
-            
+
             // Serve WASM files with proper headers
             .service(
                 web::scope("/wasm")
                     .wrap(
                         middleware::DefaultHeaders::new()
-                            .header("Cross-Origin-Opener-Policy", "same-origin")
-                            .header("Cross-Origin-Embedder-Policy", "require-corp")
-                            .header("Content-Type", "application/wasm")
+                            .add(("Cross-Origin-Opener-Policy", "same-origin"))
+                            .add(("Cross-Origin-Embedder-Policy", "require-corp"))
+                            .add(("Content-Type", "application/wasm"))
                     )
                     .service(Files::new("", "./wasm"))
             )
-            
+
             // Serve static assets
             .service(Files::new("/pkg", format!("{site_root}/pkg")))
             .service(Files::new("/assets", "./assets"))
-            
-            // API endpoints for SCTT operations
-            .route("/api/typecheck", web::post(typecheck_handler))
-            .route("/api/evaluate", web::post(evaluate_handler))
-            .route("/api/health", web::get(health_check))
-            
+
+            .configure(api)
+
             // Leptos routes
             .leptos_routes(
                 leptos_options.to_owned(),
                 routes.to_owned(),
                 SCTTApp,
             )
-            .service(Files::new("/", site_root.clone()).index_file("index.html"))
+            .service(Files::new("/", config.static_dir.clone()).index_file("index.html"))
     })
-    .bind(&addr)?
+    .bind(addr)?
     .run()
     .await
 }
 
-async fn health_check() -> Result<HttpResponse> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "status": "ok",
-        "service": "SCTT Server",
-        "version": env!("CARGO_PKG_VERSION")
-    })))
+fn cors(config: &Config) -> Cors {
+    let cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        .expose_headers(vec!["Cross-Origin-Opener-Policy", "Cross-Origin-Embedder-Policy"]);
+    if config.cors_origins.is_empty() {
+        cors.allow_any_origin()
+    } else {
+        config.cors_origins.iter().fold(cors, |cors, origin| cors.allowed_origin(origin))
+    }
 }
 
-#[derive(serde::Deserialize)]
-struct TypeCheckRequest {
-    code: String,
+/// The REST and websocket routes, each a call into the shared service
+fn api(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/health", web::get().to(|s: AppState| async move { HttpResponse::Ok().json(s.health().await) }))
+        .route("/api/examples", web::get().to(|s: AppState| async move { HttpResponse::Ok().json(s.examples().await) }))
+        .route("/api/typecheck", web::post().to(|s: AppState, req: web::Json<TypeCheckRequest>| async move {
+            HttpResponse::Ok().json(s.typecheck(req.into_inner()).await)
+        }))
+        .route("/api/evaluate", web::post().to(|s: AppState, req: web::Json<EvaluateRequest>| async move {
+            HttpResponse::Ok().json(s.evaluate(req.into_inner()).await)
+        }))
+        .route("/api/evaluate/batch", web::post().to(|s: AppState, req: web::Json<EvaluateBatchRequest>| async move {
+            HttpResponse::Ok().json(s.evaluate_batch(req.into_inner()).await)
+        }))
+        .route("/api/smooth/explain", web::post().to(|s: AppState, req: web::Json<ExplainSmoothnessRequest>| async move {
+            answer(s.explain_smoothness(req.into_inner()).await)
+        }))
+        .route("/api/termination", web::post().to(|s: AppState, req: web::Json<CheckTerminationRequest>| async move {
+            answer(s.check_termination(req.into_inner()).await)
+        }))
+        .route("/api/compile", web::post().to(compile))
+        .route("/api/prove", web::post().to(|s: AppState, req: web::Json<ProveRequest>| async move {
+            HttpResponse::Ok().json(s.prove(req.into_inner()).await)
+        }))
+        .route("/api/session", web::post().to(|s: AppState| async move {
+            HttpResponse::Ok().json(s.create_session().await)
+        }))
+        .route("/api/session/{id}", web::get().to(|s: AppState, id: web::Path<String>| async move {
+            answer(s.session(&id).await)
+        }))
+        .route("/api/session/{id}/transactions", web::post().to(
+            |s: AppState, http: HttpRequest, id: web::Path<String>, req: web::Json<BeginTransactionRequest>| async move {
+                answer(s.begin_transaction(&id, bearer(&http), req.into_inner()).await)
+            },
+        ))
+        .route("/api/session/{id}/transactions/{txn}/apply", web::post().to(
+            |s: AppState, http: HttpRequest, path: web::Path<(String, String)>, req: web::Json<ApplyInTransactionRequest>| async move {
+                answer(s.apply_in_transaction(&path.0, &path.1, bearer(&http), req.into_inner()).await)
+            },
+        ))
+        .route("/api/session/{id}/transactions/{txn}/commit", web::post().to(
+            |s: AppState, http: HttpRequest, path: web::Path<(String, String)>| async move {
+                answer(s.commit_transaction(&path.0, &path.1, bearer(&http)).await)
+            },
+        ))
+        .route("/api/session/{id}/transactions/{txn}/rollback", web::post().to(
+            |s: AppState, http: HttpRequest, path: web::Path<(String, String)>| async move {
+                answer(s.rollback_transaction(&path.0, &path.1, bearer(&http)).await)
+            },
+        ))
+        .route("/api/session/{id}/rename", web::post().to(
+            |s: AppState, http: HttpRequest, id: web::Path<String>, req: web::Json<RenameRequest>| async move {
+                answer(s.rename(&id, bearer(&http), req.into_inner()).await)
+            },
+        ))
+        .route("/api/proofs", web::get().to(|s: AppState| async move { answer(s.list_proofs().await) }))
+        .route("/api/proof/{id}", web::get().to(|s: AppState, id: web::Path<String>| async move {
+            answer(s.get_proof(&id).await)
+        }))
+        .route("/api/proof/{id}", web::post().to(|s: AppState, id: web::Path<String>, req: web::Json<SaveProofRequest>| async move {
+            answer(s.save_proof(&id, req.into_inner()).await)
+        }))
+        .route("/api/runs", web::get().to(|s: AppState| async move { HttpResponse::Ok().json(s.runs().await) }))
+        .route("/api/openapi.json", web::get().to(|| async { HttpResponse::Ok().json(service::openapi()) }))
+        .route("/api/diagnostics/resources", web::get().to(|s: AppState| async move {
+            HttpResponse::Ok().json(s.resource_diagnostics().await)
+        }))
+        .route("/ws/{session_id}", web::get().to(websocket));
 }
 
-async fn typecheck_handler(req: web::Json<TypeCheckRequest>) -> Result<HttpResponse> {
-    // This would call into our SCTT type checker
-    // For now, return a mock response
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "success": true,
-        "type": "C∞(ℝ, ℝ)",
-        "expression": req.code
-    })))
+/// Encode a service answer, or its error as status plus `ApiError` body
+fn answer<T: serde::Serialize>(result: Result<T, ServiceError>) -> HttpResponse {
+    match result {
+        Ok(value) => HttpResponse::Ok().json(value),
+        Err(e) => {
+            let status = StatusCode::from_u16(e.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            HttpResponse::build(status).json(e.body())
+        }
+    }
 }
 
-#[derive(serde::Deserialize)]
-struct EvaluateRequest {
-    expression: String,
-    value: f64,
+fn bearer(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
 }
 
-async fn evaluate_handler(req: web::Json<EvaluateRequest>) -> Result<HttpResponse> {
-    // This would evaluate smooth functions
-    // For now, return a mock response
-    let result = if req.expression.contains("sin") {
-        req.value.sin()
-    } else if req.expression.contains("cos") {
-        req.value.cos()
-    } else {
-        req.value
+async fn compile(s: AppState, req: web::Json<CompileRequest>) -> HttpResponse {
+    let req = req.into_inner();
+    match web::block(move || s.compile(req)).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => HttpResponse::InternalServerError().json(ApiError { error: e.to_string() }),
+    }
+}
+
+async fn websocket(
+    s: AppState,
+    http: HttpRequest,
+    session_id: web::Path<String>,
+    body: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    let (response, session, mut incoming) = actix_ws::handle(&http, body)?;
+    let session_id = session_id.into_inner();
+
+    // The service pushes from whichever thread handles the sender's
+    // request; a channel hands the text to this connection's task
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let conn_id = match s.connect(&session_id, Box::new(move |text| tx.send(text).is_ok())) {
+        Ok(conn_id) => conn_id,
+        Err(e) => {
+            let _ = session.close(None).await;
+            return Ok(answer::<()>(Err(e)));
+        }
     };
-    
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "success": true,
-        "result": result,
-        "expression": req.expression,
-        "input": req.value
-    })))
-}
\ No newline at end of file
+
+    actix_web::rt::spawn(async move {
+        let mut session = session;
+        loop {
+            tokio::select! {
+                outgoing = rx.recv() => match outgoing {
+                    Some(text) if session.text(text).await.is_ok() => {}
+                    _ => break,
+                },
+                msg = incoming.recv() => match msg {
+                    Some(Ok(actix_ws::Message::Text(text))) => s.message(&session_id, &conn_id, &text),
+                    Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(Ok(actix_ws::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        s.disconnect(&session_id, &conn_id);
+        let _ = session.close(None).await;
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test;
+
+    /// The adapter wires routes to the service and maps its errors
+    #[actix_web::test]
+    async fn smoke() {
+        let state: AppState = web::Data::new(Service::new(&Config::default(), MemoryStore::default()));
+        let app = test::init_service(App::new().app_data(state).configure(api)).await;
+
+        let health = test::call_service(&app, test::TestRequest::get().uri("/api/health").to_request()).await;
+        assert!(health.status().is_success());
+
+        let missing = test::TestRequest::get().uri("/api/session/missing").to_request();
+        let missing = test::call_service(&app, missing).await;
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let body: ApiError = test::read_body_json(missing).await;
+        assert_eq!(body.error, "Session not found");
+
+        let code = "fix loop (n : ℕ) : ℕ := loop n";
+        let termination = test::TestRequest::post()
+            .uri("/api/termination")
+            .set_json(CheckTerminationRequest { code: code.to_string() })
+            .to_request();
+        let termination = test::call_service(&app, termination).await;
+        assert!(termination.status().is_success());
+        let report: serde_json::Value = test::read_body_json(termination).await;
+        assert!(report["statuses"]["loop"].get("Rejected").is_some());
+    }
+}
@@ -0,0 +1,429 @@
+//! Forward-mode automatic differentiation
+//!
+//! Numerical code that is generic over [`Scalar`] runs unchanged on plain
+//! `f64` values or on [`Dual`] numbers, which carry a derivative alongside
+//! the value. A [`Jet`] carries the first `N - 1` derivatives, as Taylor
+//! coefficients. All three are [`Elementary`], so an
+//! [`Expr`](crate::expr::Expr) evaluates over any of them.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Number type the smooth numerics are generic over
+pub trait Scalar:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn constant(x: f64) -> Self;
+    fn value(self) -> f64;
+}
+
+/// A [`Scalar`] with the primitive functions of [`Func`](crate::expr::Func)
+pub trait Elementary: Scalar {
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn sqrt(self) -> Self;
+    /// `self` to the power `exponent`; a constant integer power stays
+    /// defined for a negative base
+    fn pow(self, exponent: Self) -> Self;
+}
+
+impl Scalar for f64 {
+    fn constant(x: f64) -> Self {
+        x
+    }
+
+    fn value(self) -> f64 {
+        self
+    }
+}
+
+impl Elementary for f64 {
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        f64::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        f64::tan(self)
+    }
+
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+
+    fn ln(self) -> Self {
+        f64::ln(self)
+    }
+
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    fn pow(self, exponent: Self) -> Self {
+        match integer(exponent) {
+            Some(n) => self.powi(n),
+            None => self.powf(exponent),
+        }
+    }
+}
+
+/// `x` as an `i32`, when it is one
+fn integer(x: f64) -> Option<i32> {
+    (x.fract() == 0.0 && x.abs() <= i32::MAX as f64).then_some(x as i32)
+}
+
+/// A value paired with its derivative along one seeded direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub deriv: f64,
+}
+
+impl Dual {
+    /// The independent variable: derivative 1
+    pub fn variable(x: f64) -> Self {
+        Dual { value: x, deriv: 1.0 }
+    }
+
+    pub fn sin(self) -> Self {
+        Dual { value: self.value.sin(), deriv: self.deriv * self.value.cos() }
+    }
+
+    pub fn cos(self) -> Self {
+        Dual { value: self.value.cos(), deriv: -self.deriv * self.value.sin() }
+    }
+
+    pub fn exp(self) -> Self {
+        let e = self.value.exp();
+        Dual { value: e, deriv: self.deriv * e }
+    }
+
+    pub fn tan(self) -> Self {
+        let t = self.value.tan();
+        Dual { value: t, deriv: self.deriv * (1.0 + t * t) }
+    }
+
+    pub fn ln(self) -> Self {
+        Dual { value: self.value.ln(), deriv: self.deriv / self.value }
+    }
+
+    pub fn sqrt(self) -> Self {
+        let s = self.value.sqrt();
+        Dual { value: s, deriv: self.deriv / (2.0 * s) }
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        Dual {
+            value: self.value.powi(n),
+            deriv: self.deriv * n as f64 * self.value.powi(n - 1),
+        }
+    }
+}
+
+impl Elementary for Dual {
+    fn sin(self) -> Self {
+        Dual::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        Dual::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        Dual::tan(self)
+    }
+
+    fn exp(self) -> Self {
+        Dual::exp(self)
+    }
+
+    fn ln(self) -> Self {
+        Dual::ln(self)
+    }
+
+    fn sqrt(self) -> Self {
+        Dual::sqrt(self)
+    }
+
+    fn pow(self, exponent: Self) -> Self {
+        match integer(exponent.value).filter(|_| exponent.deriv == 0.0) {
+            Some(n) => self.powi(n),
+            None => (exponent * self.ln()).exp(),
+        }
+    }
+}
+
+impl Scalar for Dual {
+    fn constant(x: f64) -> Self {
+        Dual { value: x, deriv: 0.0 }
+    }
+
+    fn value(self) -> f64 {
+        self.value
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+    fn add(self, rhs: Dual) -> Dual {
+        Dual { value: self.value + rhs.value, deriv: self.deriv + rhs.deriv }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+    fn sub(self, rhs: Dual) -> Dual {
+        Dual { value: self.value - rhs.value, deriv: self.deriv - rhs.deriv }
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+    fn mul(self, rhs: Dual) -> Dual {
+        Dual {
+            value: self.value * rhs.value,
+            deriv: self.deriv * rhs.value + self.value * rhs.deriv,
+        }
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+    fn div(self, rhs: Dual) -> Dual {
+        Dual {
+            value: self.value / rhs.value,
+            deriv: (self.deriv * rhs.value - self.value * rhs.deriv) / (rhs.value * rhs.value),
+        }
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+    fn neg(self) -> Dual {
+        Dual { value: -self.value, deriv: -self.deriv }
+    }
+}
+
+/// A value and its first `N - 1` derivatives along one seeded direction,
+/// as a truncated Taylor series: `coefficients[k]` is the `k`th derivative
+/// over `k!`. `Jet<0>` has no value and does not compile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Jet<const N: usize> {
+    pub coefficients: [f64; N],
+}
+
+impl<const N: usize> Jet<N> {
+    /// Evaluated when a constructor is instantiated, so `Jet<0>` fails to build
+    const HAS_VALUE: () = assert!(N > 0, "a jet carries at least its value");
+
+    /// The independent variable: derivative 1, and none higher
+    pub fn variable(x: f64) -> Self {
+        let () = Self::HAS_VALUE;
+        let mut coefficients = [0.0; N];
+        coefficients[0] = x;
+        if N > 1 {
+            coefficients[1] = 1.0;
+        }
+        Jet { coefficients }
+    }
+
+    /// The `k`th derivative, for `k < N`
+    pub fn derivative(&self, k: usize) -> f64 {
+        self.coefficients[k] * (1..=k).map(|i| i as f64).product::<f64>()
+    }
+
+    fn from_fn(f: impl FnMut(usize) -> f64) -> Self {
+        Jet { coefficients: std::array::from_fn(f) }
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        let (mut result, mut base, mut k) = (Jet::constant(1.0), self, n.unsigned_abs());
+        while k > 0 {
+            if k & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            k >>= 1;
+        }
+        if n < 0 { Jet::constant(1.0) / result } else { result }
+    }
+
+    /// Sine and cosine together, as each series is built from the other
+    fn sin_cos(self) -> (Self, Self) {
+        let a = &self.coefficients;
+        let (mut s, mut c) = ([0.0; N], [0.0; N]);
+        s[0] = a[0].sin();
+        c[0] = a[0].cos();
+        for k in 1..N {
+            let (sin_k, cos_k) = (1..=k).fold((0.0, 0.0), |(sin_k, cos_k), i| {
+                (sin_k + i as f64 * a[i] * c[k - i], cos_k - i as f64 * a[i] * s[k - i])
+            });
+            s[k] = sin_k / k as f64;
+            c[k] = cos_k / k as f64;
+        }
+        (Jet { coefficients: s }, Jet { coefficients: c })
+    }
+}
+
+impl<const N: usize> Scalar for Jet<N> {
+    fn constant(x: f64) -> Self {
+        let () = Self::HAS_VALUE;
+        Jet::from_fn(|k| if k == 0 { x } else { 0.0 })
+    }
+
+    fn value(self) -> f64 {
+        self.coefficients[0]
+    }
+}
+
+impl<const N: usize> Elementary for Jet<N> {
+    fn sin(self) -> Self {
+        self.sin_cos().0
+    }
+
+    fn cos(self) -> Self {
+        self.sin_cos().1
+    }
+
+    fn tan(self) -> Self {
+        let (sin, cos) = self.sin_cos();
+        sin / cos
+    }
+
+    fn exp(self) -> Self {
+        let a = &self.coefficients;
+        let mut e = [0.0; N];
+        e[0] = a[0].exp();
+        for k in 1..N {
+            e[k] = (1..=k).map(|i| i as f64 * a[i] * e[k - i]).sum::<f64>() / k as f64;
+        }
+        Jet { coefficients: e }
+    }
+
+    fn ln(self) -> Self {
+        let a = &self.coefficients;
+        let mut l = [0.0; N];
+        l[0] = a[0].ln();
+        for k in 1..N {
+            let carried = (1..k).map(|i| i as f64 * l[i] * a[k - i]).sum::<f64>() / k as f64;
+            l[k] = (a[k] - carried) / a[0];
+        }
+        Jet { coefficients: l }
+    }
+
+    fn sqrt(self) -> Self {
+        let a = &self.coefficients;
+        let mut r = [0.0; N];
+        r[0] = a[0].sqrt();
+        for k in 1..N {
+            let carried = (1..k).map(|i| r[i] * r[k - i]).sum::<f64>();
+            r[k] = (a[k] - carried) / (2.0 * r[0]);
+        }
+        Jet { coefficients: r }
+    }
+
+    fn pow(self, exponent: Self) -> Self {
+        let constant = exponent.coefficients[1..].iter().all(|c| *c == 0.0);
+        match integer(exponent.value()).filter(|_| constant) {
+            Some(n) => self.powi(n),
+            None => (exponent * self.ln()).exp(),
+        }
+    }
+}
+
+impl<const N: usize> Add for Jet<N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Jet::from_fn(|k| self.coefficients[k] + rhs.coefficients[k])
+    }
+}
+
+impl<const N: usize> Sub for Jet<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Jet::from_fn(|k| self.coefficients[k] - rhs.coefficients[k])
+    }
+}
+
+/// The Cauchy product, truncated
+impl<const N: usize> Mul for Jet<N> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Jet::from_fn(|k| (0..=k).map(|i| self.coefficients[i] * rhs.coefficients[k - i]).sum())
+    }
+}
+
+impl<const N: usize> Div for Jet<N> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let (a, b) = (&self.coefficients, &rhs.coefficients);
+        let mut q = [0.0; N];
+        for k in 0..N {
+            let carried = (1..=k).map(|i| b[i] * q[k - i]).sum::<f64>();
+            q[k] = (a[k] - carried) / b[0];
+        }
+        Jet { coefficients: q }
+    }
+}
+
+impl<const N: usize> Neg for Jet<N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Jet::from_fn(|k| -self.coefficients[k])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_product_and_quotient_rules() {
+        let x = Dual::variable(2.0);
+        let f = x * x * x / (x + Dual::constant(1.0));
+        // d/dx x³/(x+1) = (2x³ + 3x²)/(x+1)² = 28/9 at x = 2
+        assert!((f.value - 8.0 / 3.0).abs() < 1e-12);
+        assert!((f.deriv - 28.0 / 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_chain_rule() {
+        let x = Dual::variable(0.5);
+        let f = (x * x).sin();
+        assert!((f.deriv - 2.0 * 0.5 * 0.25f64.cos()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_elementary_functions() {
+        let x = Dual::variable(4.0);
+        assert!((x.sqrt().deriv - 0.25).abs() < 1e-12);
+        assert!((x.ln().deriv - 0.25).abs() < 1e-12);
+        assert!((x.powi(-1).deriv + 1.0 / 16.0).abs() < 1e-12);
+        let t = Dual::variable(0.3).tan();
+        assert!((t.deriv - 1.0 / 0.3f64.cos().powi(2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_jets_carry_taylor_coefficients() {
+        let sin = Jet::<6>::variable(0.0).sin();
+        let expected = [0.0, 1.0, 0.0, -1.0 / 6.0, 0.0, 1.0 / 120.0];
+        assert!(sin.coefficients.iter().zip(expected).all(|(c, e)| (c - e).abs() < 1e-15));
+        // d³/dx³ x⁴/(1+x) at 1, and d²/dx² ln(x)·exp(x) at 2
+        let x = Jet::<4>::variable(1.0);
+        assert!(((x.powi(4) / (x + Jet::constant(1.0))).derivative(3) - 45.0 / 8.0).abs() < 1e-12);
+        let x = Jet::<3>::variable(2.0);
+        let second = (2f64.ln() + 1.0 - 0.25) * 2f64.exp();
+        assert!(((Elementary::ln(x) * Elementary::exp(x)).derivative(2) - second).abs() < 1e-12);
+        assert!((Jet::<3>::variable(4.0).sqrt().derivative(2) + 1.0 / 32.0).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,591 @@
+//! Real expressions
+//!
+//! Numbers, `π` (or `pi`), variables, `+ - * / ^` with unary minus,
+//! postfix `²` `³`, implicit multiplication (`2x`, `3(x + 1)`), brackets,
+//! and `sin cos tan exp ln sqrt` applied with brackets. `−`, `·`, `×` and
+//! `√` read as `-`, `*`, `*` and `sqrt`. Past the smooth functions there
+//! are `min` and `max`, the interval connectives `i ∧ j`, `i ∨ j` and `¬i`
+//! (`min`, `max` and `1 - i`), and `if(e ≤ c, a, b)`, which is `a` where
+//! `e` is at most the number `c` and `b` elsewhere, as concatenated paths
+//! are written.
+//!
+//! [`parse`] reads an expression, reporting the character offset where it
+//! goes wrong, [`parse_with_corners`] also says where it may bend, and
+//! [`Expr::eval`] computes one. This is the one expression language: the
+//! checker's lite facade and `sctt-cubical`'s paths are read with it too.
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::dual::Elementary;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExprError {
+    #[error("{message} at position {position}")]
+    Parse { position: usize, message: String },
+
+    /// A name other than the function's variable
+    #[error("Unknown variable `{name}`; the function is of `{variable}`")]
+    UnknownVariable { name: String, variable: String },
+}
+
+pub type Result<T> = std::result::Result<T, ExprError>;
+
+/// A primitive function of one argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Exp,
+    Ln,
+    Sqrt,
+}
+
+impl Func {
+    pub fn from_name(name: &str) -> Option<Func> {
+        Some(match name {
+            "sin" => Func::Sin,
+            "cos" => Func::Cos,
+            "tan" => Func::Tan,
+            "exp" => Func::Exp,
+            "ln" | "log" => Func::Ln,
+            "sqrt" => Func::Sqrt,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Func::Sin => "sin",
+            Func::Cos => "cos",
+            Func::Tan => "tan",
+            Func::Exp => "exp",
+            Func::Ln => "ln",
+            Func::Sqrt => "sqrt",
+        }
+    }
+
+    pub fn apply<S: Elementary>(self, x: S) -> S {
+        match self {
+            Func::Sin => x.sin(),
+            Func::Cos => x.cos(),
+            Func::Tan => x.tan(),
+            Func::Exp => x.exp(),
+            Func::Ln => x.ln(),
+            Func::Sqrt => x.sqrt(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>),
+    Call(Func, Box<Expr>),
+    /// The lesser of the two, `∧` on the interval
+    Min(Box<Expr>, Box<Expr>),
+    /// The greater of the two, `∨` on the interval
+    Max(Box<Expr>, Box<Expr>),
+    /// `below` where `test ≤ at`, `above` elsewhere
+    Cases { test: Box<Expr>, at: f64, below: Box<Expr>, above: Box<Expr> },
+}
+
+impl Expr {
+    /// The names used, each once, in the order they first appear
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_variables(&mut names);
+        names
+    }
+
+    fn collect_variables(&self, names: &mut Vec<String>) {
+        match self {
+            Expr::Num(_) => {}
+            Expr::Var(v) => {
+                if !names.contains(v) {
+                    names.push(v.clone());
+                }
+            }
+            Expr::Neg(a) | Expr::Call(_, a) => a.collect_variables(names),
+            Expr::Add(a, b)
+            | Expr::Sub(a, b)
+            | Expr::Mul(a, b)
+            | Expr::Div(a, b)
+            | Expr::Pow(a, b)
+            | Expr::Min(a, b)
+            | Expr::Max(a, b) => {
+                a.collect_variables(names);
+                b.collect_variables(names);
+            }
+            Expr::Cases { test, below, above, .. } => {
+                for part in [test, below, above] {
+                    part.collect_variables(names);
+                }
+            }
+        }
+    }
+
+    /// Whether nothing in it can bend: no `min`, `max` or `if`
+    pub fn is_smooth(&self) -> bool {
+        match self {
+            Expr::Num(_) | Expr::Var(_) => true,
+            Expr::Neg(a) | Expr::Call(_, a) => a.is_smooth(),
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) | Expr::Div(a, b) | Expr::Pow(a, b) => {
+                a.is_smooth() && b.is_smooth()
+            }
+            Expr::Min(..) | Expr::Max(..) | Expr::Cases { .. } => false,
+        }
+    }
+
+    /// The value with every variable at `x`, a number or any other
+    /// [`Elementary`] scalar; NaN or infinite where the expression is
+    /// undefined
+    pub fn eval<S: Elementary>(&self, x: S) -> S {
+        match self {
+            Expr::Num(n) => S::constant(*n),
+            Expr::Var(_) => x,
+            Expr::Neg(a) => -a.eval(x),
+            Expr::Add(a, b) => a.eval(x) + b.eval(x),
+            Expr::Sub(a, b) => a.eval(x) - b.eval(x),
+            Expr::Mul(a, b) => a.eval(x) * b.eval(x),
+            Expr::Div(a, b) => a.eval(x) / b.eval(x),
+            Expr::Pow(a, b) => a.eval(x).pow(b.eval(x)),
+            Expr::Call(f, a) => f.apply(a.eval(x)),
+            Expr::Min(a, b) => {
+                let (a, b) = (a.eval(x), b.eval(x));
+                if a.value() <= b.value() { a } else { b }
+            }
+            Expr::Max(a, b) => {
+                let (a, b) = (a.eval(x), b.eval(x));
+                if a.value() >= b.value() { a } else { b }
+            }
+            Expr::Cases { test, at, below, above } => {
+                if test.eval(x).value() <= *at { below.eval(x) } else { above.eval(x) }
+            }
+        }
+    }
+
+    fn binding(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => SUM,
+            Expr::Mul(..) | Expr::Div(..) => PRODUCT,
+            Expr::Neg(_) => PREFIX,
+            Expr::Pow(..) => POWER,
+            Expr::Num(n) if *n < 0.0 => PREFIX,
+            Expr::Num(_) | Expr::Var(_) | Expr::Call(..) | Expr::Min(..) | Expr::Max(..) | Expr::Cases { .. } => ATOM,
+        }
+    }
+}
+
+/// Reads back as the same expression, in ASCII but for `π`, bracketed only
+/// where it must be
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let at_least = |e: &Expr, binding: u8| {
+            if e.binding() >= binding { e.to_string() } else { format!("({})", e) }
+        };
+        // Left-associative: a right operand at the same level is bracketed
+        let binary = |f: &mut fmt::Formatter, a: &Expr, op: char, b: &Expr| {
+            let binding = self.binding();
+            write!(f, "{} {} {}", at_least(a, binding), op, at_least(b, binding + 1))
+        };
+        match self {
+            Expr::Num(n) if *n == std::f64::consts::PI => write!(f, "π"),
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Var(v) => write!(f, "{}", v),
+            Expr::Neg(a) => write!(f, "-{}", at_least(a, PREFIX)),
+            Expr::Add(a, b) => binary(f, a, '+', b),
+            Expr::Sub(a, b) => binary(f, a, '-', b),
+            Expr::Mul(a, b) => binary(f, a, '*', b),
+            Expr::Div(a, b) => binary(f, a, '/', b),
+            // Right-associative
+            Expr::Pow(a, b) => write!(f, "{}^{}", at_least(a, ATOM), at_least(b, POWER)),
+            Expr::Call(func, a) => write!(f, "{}({})", func.name(), a),
+            Expr::Min(a, b) => write!(f, "min({}, {})", a, b),
+            Expr::Max(a, b) => write!(f, "max({}, {})", a, b),
+            Expr::Cases { test, at, below, above } => write!(f, "if({} ≤ {}, {}, {})", test, at, below, above),
+        }
+    }
+}
+
+/// Where an expression may bend: a `min`, `max` (or `∧`, `∨`) or `if`,
+/// spanned by character offsets with the end exclusive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Corner {
+    pub start: usize,
+    pub end: usize,
+    /// `min`, `max` or `if`
+    pub name: &'static str,
+    /// The arguments of `min` or `max`, or the test of `if` and its bound
+    sides: [Expr; 2],
+}
+
+impl Corner {
+    /// The difference of the two sides at `x`; the corner is where it
+    /// changes sign
+    pub fn gap(&self, x: f64) -> f64 {
+        self.sides[0].eval(x) - self.sides[1].eval(x)
+    }
+}
+
+pub fn parse(source: &str) -> Result<Expr> {
+    parse_with_corners(source).map(|(expr, _)| expr)
+}
+
+/// [`parse`], with every [`Corner`] in the order it closes
+pub fn parse_with_corners(source: &str) -> Result<(Expr, Vec<Corner>)> {
+    let mut parser = Parser { tokens: tokenize(source)?, pos: 0, end: source.chars().count(), corners: Vec::new() };
+    let expr = parser.expr(0)?;
+    match parser.tokens.get(parser.pos) {
+        Some((position, _, token)) => Err(parse_error(*position, format!("Unexpected `{}`", token))),
+        None => Ok((expr, parser.corners)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Sym(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Sym(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn parse_error(position: usize, message: impl Into<String>) -> ExprError {
+    ExprError::Parse { position, message: message.into() }
+}
+
+/// Tokens with their start and end character offsets
+fn tokenize(source: &str) -> Result<Vec<(usize, usize, Token)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (c, start) = (chars[i], i);
+        let token = if c.is_whitespace() {
+            i += 1;
+            continue;
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse().map_err(|_| parse_error(start, format!("Malformed number `{}`", text)))?;
+            tokens.push((start, i, Token::Num(n)));
+            continue;
+        } else if c == 'π' {
+            Token::Num(std::f64::consts::PI)
+        } else if c.is_alphabetic() || c == '_' {
+            // Not `is_alphanumeric`, which would swallow `²` and `π`
+            let continues = |c: char| (c.is_alphabetic() && c != 'π') || c.is_ascii_digit() || c == '_';
+            while i < chars.len() && continues(chars[i]) {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let token = if name == "pi" { Token::Num(std::f64::consts::PI) } else { Token::Ident(name) };
+            tokens.push((start, i, token));
+            continue;
+        } else {
+            match c {
+                '+' | '-' | '*' | '/' | '^' | '(' | ')' | ',' | '≤' | '²' | '³' | '∧' | '∨' | '¬' => Token::Sym(c),
+                '−' => Token::Sym('-'),
+                '·' | '×' => Token::Sym('*'),
+                '√' => Token::Ident("sqrt".to_string()),
+                _ => return Err(parse_error(start, format!("Unexpected character `{}`", c))),
+            }
+        };
+        i += 1;
+        tokens.push((start, i, token));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, usize, Token)>,
+    pos: usize,
+    /// Character length of the source, reported for errors at end of input
+    end: usize,
+    corners: Vec<Corner>,
+}
+
+/// Binding powers, loosest first
+const JOIN: u8 = 1;
+const MEET: u8 = 2;
+const SUM: u8 = 3;
+const PRODUCT: u8 = 4;
+const PREFIX: u8 = 5;
+const POWER: u8 = 6;
+const ATOM: u8 = 7;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, _, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(p, _, _)| *p)
+    }
+
+    /// End of the last token consumed
+    fn consumed_end(&self) -> usize {
+        self.pos.checked_sub(1).and_then(|i| self.tokens.get(i)).map_or(0, |(_, end, _)| *end)
+    }
+
+    /// A corner from `start` to what has been consumed
+    fn corner(&mut self, start: usize, name: &'static str, a: &Expr, b: &Expr) {
+        let end = self.consumed_end();
+        self.corners.push(Corner { start, end, name, sides: [a.clone(), b.clone()] });
+    }
+
+    fn expect(&mut self, sym: char) -> Result<()> {
+        match self.peek() {
+            Some(Token::Sym(c)) if *c == sym => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(t) => Err(parse_error(self.position(), format!("Expected `{}`, found `{}`", sym, t))),
+            None => Err(parse_error(self.end, format!("Expected `{}`", sym))),
+        }
+    }
+
+    fn expr(&mut self, min_bp: u8) -> Result<Expr> {
+        let start = self.position();
+        let mut lhs = self.prefix()?;
+        loop {
+            let (bp, op) = match self.peek() {
+                Some(Token::Sym('∨')) => (JOIN, '∨'),
+                Some(Token::Sym('∧')) => (MEET, '∧'),
+                Some(Token::Sym(c @ ('+' | '-'))) => (SUM, *c),
+                Some(Token::Sym(c @ ('*' | '/'))) => (PRODUCT, *c),
+                Some(Token::Sym('^')) => (POWER, '^'),
+                Some(Token::Sym(c @ ('²' | '³'))) => {
+                    let n = if *c == '²' { 2.0 } else { 3.0 };
+                    self.pos += 1;
+                    lhs = Expr::Pow(Box::new(lhs), Box::new(Expr::Num(n)));
+                    continue;
+                }
+                // Juxtaposition is multiplication: `2x`, `3(x + 1)`, `x sin(x)`
+                Some(Token::Num(_) | Token::Ident(_) | Token::Sym('(')) => (PRODUCT, '·'),
+                _ => break,
+            };
+            if bp <= min_bp {
+                break;
+            }
+            if op != '·' {
+                self.pos += 1;
+            }
+            // `^` is right-associative, the rest associate to the left
+            let rhs = self.expr(if op == '^' { bp - 1 } else { bp })?;
+            match op {
+                '∨' => self.corner(start, "max", &lhs, &rhs),
+                '∧' => self.corner(start, "min", &lhs, &rhs),
+                _ => {}
+            }
+            let (a, b) = (Box::new(lhs), Box::new(rhs));
+            lhs = match op {
+                '∨' => Expr::Max(a, b),
+                '∧' => Expr::Min(a, b),
+                '+' => Expr::Add(a, b),
+                '-' => Expr::Sub(a, b),
+                '/' => Expr::Div(a, b),
+                '^' => Expr::Pow(a, b),
+                _ => Expr::Mul(a, b),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn prefix(&mut self) -> Result<Expr> {
+        let position = self.position();
+        let Some((_, _, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(parse_error(self.end, "Unexpected end of input"));
+        };
+        self.pos += 1;
+        match token {
+            Token::Num(n) => Ok(Expr::Num(n)),
+            Token::Sym('-') => Ok(Expr::Neg(Box::new(self.expr(PREFIX)?))),
+            Token::Sym('¬') => Ok(Expr::Sub(Box::new(Expr::Num(1.0)), Box::new(self.expr(PREFIX)?))),
+            Token::Sym('(') => {
+                let inner = self.expr(0)?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Token::Ident(name) if name == "if" => self.cases(position),
+            Token::Ident(name) if name == "min" || name == "max" => {
+                let [a, b] = self.arguments(position, &name)?;
+                let name = if name == "min" { "min" } else { "max" };
+                self.corner(position, name, &a, &b);
+                let (a, b) = (Box::new(a), Box::new(b));
+                Ok(if name == "min" { Expr::Min(a, b) } else { Expr::Max(a, b) })
+            }
+            Token::Ident(name) => match Func::from_name(&name) {
+                Some(func) => {
+                    let [argument] = self.arguments(position, &name)?;
+                    Ok(Expr::Call(func, Box::new(argument)))
+                }
+                None => Ok(Expr::Var(name)),
+            },
+            Token::Sym(c) => Err(parse_error(position, format!("Unexpected `{}`", c))),
+        }
+    }
+
+    /// The `N` bracketed arguments of the function `name`, read at
+    /// `position`
+    fn arguments<const N: usize>(&mut self, position: usize, name: &str) -> Result<[Expr; N]> {
+        self.expect('(')?;
+        let mut arguments = vec![self.expr(0)?];
+        while self.peek() == Some(&Token::Sym(',')) {
+            self.pos += 1;
+            arguments.push(self.expr(0)?);
+        }
+        self.expect(')')?;
+        let count = arguments.len();
+        arguments
+            .try_into()
+            .map_err(|_| parse_error(position, format!("`{}` takes {} argument(s), got {}", name, N, count)))
+    }
+
+    /// `if(test ≤ at, below, above)`, the `if` at `position` read already
+    fn cases(&mut self, position: usize) -> Result<Expr> {
+        self.expect('(')?;
+        let test = self.expr(0)?;
+        self.expect('≤')?;
+        let at_position = self.position();
+        let at = match self.expr(0)? {
+            Expr::Num(at) => Some(at),
+            Expr::Neg(n) => if let Expr::Num(at) = *n { Some(-at) } else { None },
+            _ => None,
+        };
+        let at = at.ok_or_else(|| parse_error(at_position, "Expected a number to compare with"))?;
+        self.expect(',')?;
+        let below = self.expr(0)?;
+        self.expect(',')?;
+        let above = self.expr(0)?;
+        self.expect(')')?;
+        self.corner(position, "if", &test, &Expr::Num(at));
+        Ok(Expr::Cases { test: Box::new(test), at, below: Box::new(below), above: Box::new(above) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{E, PI};
+
+    #[test]
+    fn test_corpus_evaluates() {
+        let sin1 = 1f64.sin();
+        let corpus: &[(&str, f64, f64)] = &[
+            // Each was once mistaken for its first recognized primitive
+            ("x + sin(x)", 1.0, 1.0 + sin1),
+            ("x*exp(-x)", 1.0, 1.0 / E),
+            ("sin(x)*cos(x)", 1.0, sin1 * 1f64.cos()),
+            ("cos(x)^2", 0.0, 1.0),
+            ("exp(x) + x²", 0.0, 1.0),
+            ("x^2 + 1", 3.0, 10.0),
+            ("x³ - 2x + 1", 2.0, 5.0),
+            ("sin(x²)", 0.0, 0.0),
+            ("x", 4.0, 4.0),
+            ("1", 4.0, 1.0),
+            // Arithmetic and precedence
+            ("2 + 3 * 4", 0.0, 14.0),
+            ("(2 + 3) * 4", 0.0, 20.0),
+            ("2^3^2", 0.0, 512.0),
+            ("-x^2", 3.0, -9.0),
+            ("(-x)^2", 3.0, 9.0),
+            ("x / 2 / 2", 8.0, 2.0),
+            ("x - 1 - 1", 8.0, 6.0),
+            ("3(x + 1)", 1.0, 6.0),
+            ("2x²", 3.0, 18.0),
+            ("x sin(x)", 1.0, sin1),
+            ("(-2)^3", 0.0, -8.0),
+            ("x^0.5", 4.0, 2.0),
+            ("x²³", 2.0, 64.0),
+            // Unicode spellings
+            ("2·x × 3", 1.0, 6.0),
+            ("x − 1", 1.0, 0.0),
+            ("√(x)", 9.0, 3.0),
+            ("sin(π x)", 0.5, 1.0),
+            ("cos(pi)", 0.0, -1.0),
+            // Every primitive
+            ("tan(x)", PI / 4.0, 1.0),
+            ("ln(exp(x))", 2.5, 2.5),
+            ("log(x)", E, 1.0),
+            ("sqrt(x) * sqrt(x)", 2.0, 2.0),
+            ("exp(sin(x²))", 0.0, 1.0),
+            ("1 / (1 + exp(-x))", 0.0, 0.5),
+        ];
+        assert!(corpus.len() >= 30);
+        for (source, x, value) in corpus {
+            let expr = parse(source).unwrap_or_else(|e| panic!("{}: {}", source, e));
+            assert!((expr.eval(*x) - value).abs() < 1e-12, "{} at {} is {}", source, x, expr.eval(*x));
+            // What is printed reads back as the same expression
+            assert_eq!(parse(&expr.to_string()).as_ref(), Ok(&expr), "{} printed as {}", source, expr);
+        }
+        assert!(parse("ln(x)").unwrap().eval(0.0).is_infinite());
+    }
+
+    #[test]
+    fn test_printing() {
+        let printed = |source: &str| parse(source).unwrap().to_string();
+        assert_eq!(printed("2x² + 3(x − 1)"), "2 * x^2 + 3 * (x - 1)");
+        assert_eq!(printed("x - (1 - x)"), "x - (1 - x)");
+        assert_eq!(printed("(2^3)^2"), "(2^3)^2");
+        assert_eq!(printed("-(x + 1)"), "-(x + 1)");
+        assert_eq!(printed("sin(π x)"), "sin(π * x)");
+    }
+
+    #[test]
+    fn test_parse_errors_have_positions() {
+        let error = |source: &str| match parse(source) {
+            Err(ExprError::Parse { position, message }) => (position, message),
+            other => panic!("{} parsed as {:?}", source, other),
+        };
+        assert_eq!(error("x +"), (3, "Unexpected end of input".to_string()));
+        assert_eq!(error("sin x"), (4, "Expected `(`, found `x`".to_string()));
+        assert_eq!(error("(x + 1"), (6, "Expected `)`".to_string()));
+        assert_eq!(error("x $ 2"), (2, "Unexpected character `$`".to_string()));
+        assert_eq!(error("1.2.3"), (0, "Malformed number `1.2.3`".to_string()));
+        assert_eq!(error("x )"), (2, "Unexpected `)`".to_string()));
+        assert_eq!(error("min(x)"), (0, "`min` takes 2 argument(s), got 1".to_string()));
+        assert_eq!(error("if(x ≤ y, 0, 1)"), (7, "Expected a number to compare with".to_string()));
+    }
+
+    #[test]
+    fn test_corners() {
+        let (expr, corners) = parse_with_corners("sin(x) + max(x, 0)").unwrap();
+        assert_eq!(expr.to_string(), "sin(x) + max(x, 0)");
+        assert_eq!((corners[0].start, corners[0].end, corners[0].name), (9, 18, "max"));
+        assert_eq!(corners[0].gap(0.25), 0.25);
+        let (expr, corners) = parse_with_corners("1 + (x ∨ x²)").unwrap();
+        assert_eq!((corners[0].start, corners[0].end), (5, 11));
+        assert_eq!(expr.eval(0.5), 1.5);
+        let (expr, corners) = parse_with_corners("if(x ≤ -0.5, ¬(0.3 ∧ x), x)").unwrap();
+        assert_eq!(expr.to_string(), "if(x ≤ -0.5, 1 - min(0.3, x), x)");
+        assert_eq!(corners.iter().map(|c| c.name).collect::<Vec<_>>(), ["min", "if"]);
+        assert_eq!((expr.eval(-1.0), expr.eval(1.0)), (2.0, 1.0));
+        assert!(!expr.is_smooth() && parse("sin(x) / x").unwrap().is_smooth());
+        assert_eq!(parse(&expr.to_string()), Ok(expr));
+    }
+}
@@ -0,0 +1,214 @@
+//! Path energy and geodesic straightening in ℝ²
+//!
+//! A path γ : [0,1] → ℝ² is represented by the control points of a Bézier
+//! curve. Its energy E(γ) = ∫₀¹ |γ'(t)|² dt is minimized, among paths with
+//! the same endpoints, exactly by the constant-speed straight line. Gradient
+//! descent on the interior control points therefore relaxes any path into
+//! the geodesic while the endpoints stay fixed.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::dual::{Dual, Scalar};
+
+pub type Point2 = [f64; 2];
+
+/// Number of Simpson intervals used for the energy integral
+const ENERGY_INTERVALS: usize = 64;
+
+/// A Bézier path in ℝ² of degree `control.len() - 1`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BezierPath {
+    pub control: Vec<Point2>,
+}
+
+impl BezierPath {
+    /// The path from `start` to `end` pulled towards the `interior` control points
+    pub fn new(start: Point2, interior: Vec<Point2>, end: Point2) -> Self {
+        let mut control = Vec::with_capacity(interior.len() + 2);
+        control.push(start);
+        control.extend(interior);
+        control.push(end);
+        BezierPath { control }
+    }
+
+    /// The straight line from `a` to `b` with evenly spaced control points
+    pub fn straight(a: Point2, b: Point2, degree: usize) -> Self {
+        let interior = (1..degree)
+            .map(|i| {
+                let s = i as f64 / degree as f64;
+                [a[0] + s * (b[0] - a[0]), a[1] + s * (b[1] - a[1])]
+            })
+            .collect();
+        BezierPath::new(a, interior, b)
+    }
+
+    pub fn start(&self) -> Point2 {
+        self.control[0]
+    }
+
+    pub fn end(&self) -> Point2 {
+        self.control[self.control.len() - 1]
+    }
+
+    /// γ(t)
+    pub fn point(&self, t: f64) -> Point2 {
+        bezier(&self.control, t)
+    }
+
+    /// `n + 1` evenly spaced points along the path, for drawing
+    pub fn sample(&self, n: usize) -> Vec<Point2> {
+        (0..=n).map(|i| self.point(i as f64 / n as f64)).collect()
+    }
+
+    /// E(γ) = ∫₀¹ |γ'(t)|² dt
+    pub fn energy(&self) -> f64 {
+        energy(&self.control)
+    }
+
+    /// ∂E/∂Pᵢ for every control point; zero at the fixed endpoints
+    pub fn energy_gradient(&self) -> Vec<Point2> {
+        let n = self.control.len();
+        let mut gradient = vec![[0.0; 2]; n];
+        for (i, g) in gradient.iter_mut().enumerate().take(n - 1).skip(1) {
+            for (c, component) in g.iter_mut().enumerate() {
+                // Forward mode: one pass per seeded coordinate
+                let seeded: Vec<[Dual; 2]> = self.control.iter().enumerate()
+                    .map(|(j, p)| {
+                        let mut q = p.map(Dual::constant);
+                        if j == i {
+                            q[c] = Dual::variable(p[c]);
+                        }
+                        q
+                    })
+                    .collect();
+                *component = energy(&seeded).deriv;
+            }
+        }
+        gradient
+    }
+
+    /// One gradient-descent step with the given learning rate
+    pub fn descend(&self, rate: f64) -> BezierPath {
+        let gradient = self.energy_gradient();
+        let control = self.control.iter().zip(&gradient)
+            .map(|(p, g)| [p[0] - rate * g[0], p[1] - rate * g[1]])
+            .collect();
+        BezierPath { control }
+    }
+
+    /// Successive descent steps, starting with this path
+    pub fn straighten(&self, rate: f64) -> Straighten {
+        Straighten { current: self.clone(), rate }
+    }
+}
+
+/// Iterator over the frames of a straightening run
+pub struct Straighten {
+    current: BezierPath,
+    rate: f64,
+}
+
+impl Iterator for Straighten {
+    type Item = BezierPath;
+
+    fn next(&mut self) -> Option<BezierPath> {
+        let next = self.current.descend(self.rate);
+        Some(std::mem::replace(&mut self.current, next))
+    }
+}
+
+/// Composite Simpson's rule on [0, 1], with `intervals` rounded up to a positive even number
+pub fn simpson<S: Scalar>(f: impl Fn(f64) -> S, intervals: usize) -> S {
+    let intervals = (intervals + intervals % 2).max(2);
+    let h = 1.0 / intervals as f64;
+    let mut sum = f(0.0) + f(1.0);
+    for k in 1..intervals {
+        let weight = if k % 2 == 1 { 4.0 } else { 2.0 };
+        sum = sum + S::constant(weight) * f(k as f64 * h);
+    }
+    sum * S::constant(h / 3.0)
+}
+
+fn energy<S: Scalar>(control: &[[S; 2]]) -> S {
+    simpson(|t| {
+        let v = velocity(control, t);
+        v[0] * v[0] + v[1] * v[1]
+    }, ENERGY_INTERVALS)
+}
+
+/// γ'(t) = n Σ Bᵢ,ₙ₋₁(t) (Pᵢ₊₁ − Pᵢ)
+fn velocity<S: Scalar>(control: &[[S; 2]], t: f64) -> [S; 2] {
+    let n = control.len() - 1;
+    let differences: Vec<[S; 2]> = control.windows(2)
+        .map(|w| [w[1][0] - w[0][0], w[1][1] - w[0][1]])
+        .collect();
+    let d = bezier(&differences, t);
+    let n = S::constant(n as f64);
+    [n * d[0], n * d[1]]
+}
+
+/// De Casteljau evaluation
+fn bezier<S: Scalar>(control: &[[S; 2]], t: f64) -> [S; 2] {
+    let (s, t) = (S::constant(1.0 - t), S::constant(t));
+    let mut points = control.to_vec();
+    for level in (1..points.len()).rev() {
+        for i in 0..level {
+            points[i] = [
+                s * points[i][0] + t * points[i + 1][0],
+                s * points[i][1] + t * points[i + 1][1],
+            ];
+        }
+    }
+    points[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bent() -> BezierPath {
+        BezierPath::new([0.0, 0.0], vec![[0.2, 1.5], [1.1, -1.0]], [1.0, 0.0])
+    }
+
+    #[test]
+    fn test_straight_line_is_a_fixed_point() {
+        let line = BezierPath::straight([0.0, 0.0], [3.0, 4.0], 3);
+        // |γ'| = 5 everywhere
+        assert!((line.energy() - 25.0).abs() < 1e-9);
+        assert!(line.energy_gradient().iter().flatten().all(|g| g.abs() < 1e-9));
+        assert_eq!(line.descend(0.05), line);
+    }
+
+    #[test]
+    fn test_endpoints_are_kept_without_interior_points() {
+        let line = BezierPath::straight([1.0, 2.0], [4.0, 6.0], 0);
+        assert_eq!(line.control, vec![[1.0, 2.0], [4.0, 6.0]]);
+        assert!((line.energy() - 25.0).abs() < 1e-9);
+        assert!((simpson(|t: f64| t * t, 3) - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_gradient_matches_finite_differences() {
+        let path = bent();
+        let gradient = path.energy_gradient();
+        let h = 1e-6;
+        let mut nudged = path.clone();
+        nudged.control[1][0] += h;
+        let numeric = (nudged.energy() - path.energy()) / h;
+        assert!((gradient[1][0] - numeric).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_energy_decreases_while_endpoints_stay_fixed() {
+        let frames: Vec<BezierPath> = bent().straighten(0.05).take(60).collect();
+        for pair in frames.windows(2) {
+            assert!(pair[1].energy() < pair[0].energy());
+            assert_eq!(pair[1].start(), [0.0, 0.0]);
+            assert_eq!(pair[1].end(), [1.0, 0.0]);
+        }
+        // The limit is the straight line, with energy |b - a|² = 1
+        assert!(frames.last().unwrap().energy() < 1.01);
+    }
+}
@@ -0,0 +1,288 @@
+//! Hover readouts for 2D plots
+//!
+//! What a plot shows at the cursor: the value, first and second derivatives,
+//! a tangent segment, and for parametric paths the speed and curvature. The
+//! first derivative comes from [`Dual`] numbers; the second is a central
+//! difference of that exact derivative. Nothing here touches a canvas, so the
+//! renderer only has to draw the [`HoverInfo`] it is handed and place the
+//! card where [`anchor_card`] says.
+
+use crate::dual::Dual;
+
+/// Step for differencing the AD derivative
+const STEP: f64 = 1e-4;
+
+/// How readout numbers are printed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    /// Digits after the decimal point, before trailing zeros are trimmed
+    pub precision: usize,
+    /// Magnitudes at or above this switch to scientific notation
+    pub scientific_above: f64,
+    /// Nonzero magnitudes below this switch to scientific notation
+    pub scientific_below: f64,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat { precision: 4, scientific_above: 1e5, scientific_below: 1e-4 }
+    }
+}
+
+impl NumberFormat {
+    /// `1.5`, `−0.25`, `1.23×10⁻⁶`, `∞`, `undefined`
+    pub fn format(&self, x: f64) -> String {
+        if x.is_nan() {
+            return "undefined".to_string();
+        }
+        if x.is_infinite() {
+            return if x > 0.0 { "∞".to_string() } else { "−∞".to_string() };
+        }
+        let sign = if x < 0.0 { "−" } else { "" };
+        let magnitude = x.abs();
+        let scientific = magnitude >= self.scientific_above || (magnitude != 0.0 && magnitude < self.scientific_below);
+        let body = if scientific {
+            let exponent = magnitude.log10().floor() as i32;
+            let mut mantissa = magnitude / 10f64.powi(exponent);
+            let mut exponent = exponent;
+            // Rounding can carry the mantissa to 10
+            if trim(format!("{:.*}", self.precision, mantissa)).starts_with("10") {
+                mantissa /= 10.0;
+                exponent += 1;
+            }
+            format!("{}×10{}", trim(format!("{:.*}", self.precision, mantissa)), superscript(exponent))
+        } else {
+            trim(format!("{:.*}", self.precision, magnitude))
+        };
+        if body == "0" {
+            body
+        } else {
+            format!("{}{}", sign, body)
+        }
+    }
+}
+
+fn trim(fixed: String) -> String {
+    if !fixed.contains('.') {
+        return fixed;
+    }
+    fixed.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn superscript(n: i32) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    let mut out = String::new();
+    if n < 0 {
+        out.push('⁻');
+    }
+    for c in n.unsigned_abs().to_string().chars() {
+        out.push(c.to_digit(10).map_or(c, |d| DIGITS[d as usize]));
+    }
+    out
+}
+
+/// Which curve of a graph the cursor reads when f and f′ are both plotted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoverTarget {
+    Function,
+    Derivative,
+}
+
+/// Everything the readout shows at the cursor, in data coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoverInfo {
+    /// The hovered parameter: x for graphs, t for paths
+    pub parameter: f64,
+    /// The point on the curve
+    pub point: [f64; 2],
+    /// Slope for graphs, velocity for paths
+    pub first: [f64; 2],
+    /// Second derivative for graphs, acceleration for paths
+    pub second: [f64; 2],
+    /// Endpoints of the tangent segment through `point`
+    pub tangent: [[f64; 2]; 2],
+    /// |γ′(t)|, for paths only
+    pub speed: Option<f64>,
+    /// Unsigned curvature κ of the curve at `point`
+    pub curvature: f64,
+    /// Label and formatted value, one card line each
+    pub lines: Vec<(String, String)>,
+}
+
+impl HoverInfo {
+    /// Slope of the tangent segment, infinite when vertical
+    pub fn slope(&self) -> f64 {
+        self.first[1] / self.first[0]
+    }
+}
+
+/// f, f′ and f″ at x
+fn jet(f: &impl Fn(Dual) -> Dual, x: f64) -> [f64; 3] {
+    let at = f(Dual::variable(x));
+    let ahead = f(Dual::variable(x + STEP)).deriv;
+    let behind = f(Dual::variable(x - STEP)).deriv;
+    [at.value, at.deriv, (ahead - behind) / (2.0 * STEP)]
+}
+
+/// f′, f″ and f‴ at x, for reading the derivative's own curve
+fn derivative_jet(f: &impl Fn(Dual) -> Dual, x: f64) -> [f64; 3] {
+    let d = |x: f64| f(Dual::variable(x)).deriv;
+    let (ahead, here, behind) = (d(x + STEP), d(x), d(x - STEP));
+    [here, (ahead - behind) / (2.0 * STEP), (ahead - 2.0 * here + behind) / (STEP * STEP)]
+}
+
+/// Curvature of a plane curve from its velocity and acceleration
+fn curvature(first: [f64; 2], second: [f64; 2]) -> f64 {
+    let speed = first[0].hypot(first[1]);
+    (first[0] * second[1] - first[1] * second[0]).abs() / speed.powi(3)
+}
+
+/// Readout for the graph of `f` at `x`; the tangent spans `x ± reach`
+pub fn hover_graph(f: impl Fn(Dual) -> Dual, x: f64, target: HoverTarget, reach: f64, format: &NumberFormat) -> HoverInfo {
+    let ([y, dy, ddy], names) = match target {
+        HoverTarget::Function => (jet(&f, x), ["f(x)", "f′(x)", "f″(x)"]),
+        HoverTarget::Derivative => (derivative_jet(&f, x), ["f′(x)", "f″(x)", "f‴(x)"]),
+    };
+    let first = [1.0, dy];
+    let second = [0.0, ddy];
+    let curvature = curvature(first, second);
+    let lines = vec![
+        ("x".to_string(), format.format(x)),
+        (names[0].to_string(), format.format(y)),
+        (names[1].to_string(), format.format(dy)),
+        (names[2].to_string(), format.format(ddy)),
+        ("κ".to_string(), format.format(curvature)),
+    ];
+    HoverInfo {
+        parameter: x,
+        point: [x, y],
+        first,
+        second,
+        tangent: [[x - reach, y - dy * reach], [x + reach, y + dy * reach]],
+        speed: None,
+        curvature,
+        lines,
+    }
+}
+
+/// Readout for the path t ↦ (x(t), y(t)) at `t`; the tangent has half-length
+/// `reach` and collapses to the point where the path is stationary
+pub fn hover_path(
+    x: impl Fn(Dual) -> Dual,
+    y: impl Fn(Dual) -> Dual,
+    t: f64,
+    reach: f64,
+    format: &NumberFormat,
+) -> HoverInfo {
+    let [px, dx, ddx] = jet(&x, t);
+    let [py, dy, ddy] = jet(&y, t);
+    let first = [dx, dy];
+    let second = [ddx, ddy];
+    let speed = dx.hypot(dy);
+    let curvature = curvature(first, second);
+    let (ux, uy) = if speed > 0.0 { (dx / speed, dy / speed) } else { (0.0, 0.0) };
+    let lines = vec![
+        ("t".to_string(), format.format(t)),
+        ("γ(t)".to_string(), format!("({}, {})", format.format(px), format.format(py))),
+        ("γ′(t)".to_string(), format!("({}, {})", format.format(dx), format.format(dy))),
+        ("|γ′(t)|".to_string(), format.format(speed)),
+        ("κ".to_string(), format.format(curvature)),
+    ];
+    HoverInfo {
+        parameter: t,
+        point: [px, py],
+        first,
+        second,
+        tangent: [[px - ux * reach, py - uy * reach], [px + ux * reach, py + uy * reach]],
+        speed: Some(speed),
+        curvature,
+        lines,
+    }
+}
+
+/// Top-left corner for an info card of size `card` near `cursor`, in pixels
+///
+/// The card sits below and to the right of the cursor, `gap` pixels away, and
+/// flips to the other side on each axis where it would leave the viewport, so
+/// it never covers the point being read. A card too big for either side is
+/// clamped into the viewport.
+pub fn anchor_card(cursor: [f64; 2], card: [f64; 2], viewport: [f64; 2], gap: f64) -> [f64; 2] {
+    let place = |at: f64, size: f64, room: f64| {
+        let after = at + gap;
+        let before = at - gap - size;
+        let pos = if after + size <= room || before < 0.0 { after } else { before };
+        pos.min(room - size).max(0.0)
+    };
+    [place(cursor[0], card[0], viewport[0]), place(cursor[1], card[1], viewport[1])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covers(corner: [f64; 2], card: [f64; 2], p: [f64; 2]) -> bool {
+        p[0] >= corner[0] && p[0] <= corner[0] + card[0] && p[1] >= corner[1] && p[1] <= corner[1] + card[1]
+    }
+
+    #[test]
+    fn test_tangent_of_square_at_one() {
+        let info = hover_graph(|x| x * x, 1.0, HoverTarget::Function, 0.5, &NumberFormat::default());
+        assert!((info.slope() - 2.0).abs() < 1e-12);
+        assert!((info.second[1] - 2.0).abs() < 1e-6);
+        let [a, b] = info.tangent;
+        assert!((a[0] - 0.5).abs() < 1e-12 && a[1].abs() < 1e-12);
+        assert!((b[0] - 1.5).abs() < 1e-12 && (b[1] - 2.0).abs() < 1e-12);
+        assert_eq!(info.lines[2], ("f′(x)".to_string(), "2".to_string()));
+
+        // Reading the derivative's curve: f′ = 2x has slope 2 and no bend
+        let derivative = hover_graph(|x| x * x, 1.0, HoverTarget::Derivative, 0.5, &NumberFormat::default());
+        assert!((derivative.point[1] - 2.0).abs() < 1e-12);
+        assert!((derivative.slope() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unit_circle_has_curvature_one() {
+        for i in 0..32 {
+            let t = i as f64 * std::f64::consts::TAU / 32.0;
+            let info = hover_path(|t| t.cos(), |t| t.sin(), t, 0.25, &NumberFormat::default());
+            assert!((info.curvature - 1.0).abs() < 1e-6, "κ({}) = {}", t, info.curvature);
+            assert!((info.speed.unwrap_or(0.0) - 1.0).abs() < 1e-12);
+            // The tangent is perpendicular to the radius
+            let [a, b] = info.tangent;
+            let along = [b[0] - a[0], b[1] - a[1]];
+            assert!((along[0] * info.point[0] + along[1] * info.point[1]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_number_format() {
+        let f = NumberFormat::default();
+        assert_eq!(f.format(1.5), "1.5");
+        assert_eq!(f.format(-0.25), "−0.25");
+        assert_eq!(f.format(-0.0), "0");
+        assert_eq!(f.format(1.234567e-6), "1.2346×10⁻⁶");
+        assert_eq!(f.format(123456.0), "1.2346×10⁵");
+        assert_eq!(f.format(999999.999), "1×10⁶");
+        assert_eq!(f.format(f64::NAN), "undefined");
+        assert_eq!(f.format(f64::NEG_INFINITY), "−∞");
+    }
+
+    #[test]
+    fn test_card_flips_away_from_edges() {
+        let (card, viewport, gap) = ([120.0, 80.0], [600.0, 400.0], 12.0);
+
+        // Room everywhere: below and to the right
+        assert_eq!(anchor_card([100.0, 100.0], card, viewport, gap), [112.0, 112.0]);
+        // Near the right edge: flips left
+        assert_eq!(anchor_card([550.0, 100.0], card, viewport, gap), [418.0, 112.0]);
+        // Near the bottom-right corner: flips both ways
+        assert_eq!(anchor_card([550.0, 380.0], card, viewport, gap), [418.0, 288.0]);
+
+        for cursor in [[0.0, 0.0], [599.0, 0.0], [0.0, 399.0], [599.0, 399.0], [300.0, 200.0]] {
+            let corner = anchor_card(cursor, card, viewport, gap);
+            assert!(!covers(corner, card, cursor), "card at {:?} covers {:?}", corner, cursor);
+            assert!(corner[0] >= 0.0 && corner[0] + card[0] <= viewport[0]);
+            assert!(corner[1] >= 0.0 && corner[1] + card[1] <= viewport[1]);
+        }
+    }
+}
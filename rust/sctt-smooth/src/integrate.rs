@@ -0,0 +1,149 @@
+//! Definite integrals by adaptive Simpson quadrature
+//!
+//! Simpson's rule on an interval is compared with Simpson's rule on its two
+//! halves; where they differ by more than the interval's share of the
+//! tolerance the halves are integrated the same way, and where they agree
+//! the difference corrects the finer estimate. Flat stretches cost a few
+//! evaluations and sharp ones as many as they need, up to
+//! [`MAX_SUBDIVISIONS`]. An integrand that is not finite anywhere it is
+//! evaluated, a pole or `ln` of a negative, is an error naming the point.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Absolute error [`SmoothFunction::integrate`](crate::SmoothFunction::integrate) aims for
+pub const TOLERANCE: f64 = 1e-10;
+/// Bisections of the interval, at most, before giving up
+pub const MAX_SUBDIVISIONS: usize = 100_000;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IntegrateError {
+    #[error("The integrand is not finite at x = {x}")]
+    NotFinite { x: f64 },
+
+    #[error("The bound {bound} is not finite")]
+    InfiniteBound { bound: f64 },
+
+    #[error("No estimate within {tolerance} after {MAX_SUBDIVISIONS} subdivisions")]
+    NoConvergence { tolerance: f64 },
+}
+
+pub type Result<T> = std::result::Result<T, IntegrateError>;
+
+/// `∫ₐᵇ f` to within `tolerance`; with `b < a` the integral runs backwards
+/// and is negated
+pub fn adaptive_simpson(f: impl Fn(f64) -> f64, a: f64, b: f64, tolerance: f64) -> Result<f64> {
+    if let Some(bound) = [a, b].into_iter().find(|bound| !bound.is_finite()) {
+        return Err(IntegrateError::InfiniteBound { bound });
+    }
+    if b < a {
+        return adaptive_simpson(f, b, a, tolerance).map(|integral| -integral);
+    }
+    if a == b {
+        return Ok(0.0);
+    }
+    let mut quadrature = Quadrature { f, subdivisions: 0, tolerance };
+    let (fa, fb) = (quadrature.at(a)?, quadrature.at(b)?);
+    let whole = quadrature.panel(a, fa, b, fb)?;
+    quadrature.refine(whole, tolerance)
+}
+
+struct Quadrature<F> {
+    f: F,
+    subdivisions: usize,
+    /// Asked for overall, for the error
+    tolerance: f64,
+}
+
+/// Simpson's rule on `[a, b]`, with what it evaluated
+#[derive(Clone, Copy)]
+struct Panel {
+    a: f64,
+    fa: f64,
+    m: f64,
+    fm: f64,
+    b: f64,
+    fb: f64,
+    estimate: f64,
+}
+
+impl<F: Fn(f64) -> f64> Quadrature<F> {
+    fn at(&self, x: f64) -> Result<f64> {
+        let y = (self.f)(x);
+        if y.is_finite() { Ok(y) } else { Err(IntegrateError::NotFinite { x }) }
+    }
+
+    fn panel(&self, a: f64, fa: f64, b: f64, fb: f64) -> Result<Panel> {
+        let m = 0.5 * (a + b);
+        let fm = self.at(m)?;
+        let estimate = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+        Ok(Panel { a, fa, m, fm, b, fb, estimate })
+    }
+
+    fn refine(&mut self, whole: Panel, tolerance: f64) -> Result<f64> {
+        let left = self.panel(whole.a, whole.fa, whole.m, whole.fm)?;
+        let right = self.panel(whole.m, whole.fm, whole.b, whole.fb)?;
+        let difference = left.estimate + right.estimate - whole.estimate;
+        // Halves too narrow to split again are as good as it gets
+        let unsplittable = left.m <= whole.a || right.m >= whole.b;
+        if difference.abs() <= 15.0 * tolerance || unsplittable {
+            return Ok(left.estimate + right.estimate + difference / 15.0);
+        }
+        self.subdivisions += 1;
+        if self.subdivisions > MAX_SUBDIVISIONS {
+            return Err(IntegrateError::NoConvergence { tolerance: self.tolerance });
+        }
+        Ok(self.refine(left, tolerance / 2.0)? + self.refine(right, tolerance / 2.0)?)
+    }
+}
+
+/// `(x, ∫ₐˣ f)` at `n + 1` evenly spaced `x` from `a` to `b`, for plotting
+/// an antiderivative
+pub fn cumulative(f: impl Fn(f64) -> f64, a: f64, b: f64, n: usize, tolerance: f64) -> Result<Vec<[f64; 2]>> {
+    let n = n.max(1);
+    let x = |k: usize| a + (b - a) * k as f64 / n as f64;
+    let mut points = vec![[a, 0.0]];
+    let mut total = 0.0;
+    for k in 1..=n {
+        total += adaptive_simpson(&f, x(k - 1), x(k), tolerance / n as f64)?;
+        points.push([x(k), total]);
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_known_integrals() {
+        assert!((adaptive_simpson(f64::sin, 0.0, PI, 1e-10).unwrap() - 2.0).abs() < 1e-8);
+        assert!((adaptive_simpson(|x| x * x, 0.0, 1.0, 1e-10).unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert!((adaptive_simpson(|x| x * x, 1.0, 0.0, 1e-10).unwrap() + 1.0 / 3.0).abs() < 1e-12);
+        // Sharp near 0, flat past it
+        let spike = adaptive_simpson(|x| (-1000.0 * x * x).exp(), -1.0, 1.0, 1e-12).unwrap();
+        assert!((spike - (PI / 1000.0).sqrt()).abs() < 1e-10);
+        assert_eq!(adaptive_simpson(f64::exp, 2.0, 2.0, 1e-10), Ok(0.0));
+    }
+
+    #[test]
+    fn test_poles_and_bad_bounds_are_errors() {
+        assert_eq!(adaptive_simpson(|x| 1.0 / x, 0.0, 1.0, 1e-10), Err(IntegrateError::NotFinite { x: 0.0 }));
+        assert_eq!(adaptive_simpson(|x| 1.0 / x, -1.0, 1.0, 1e-10), Err(IntegrateError::NotFinite { x: 0.0 }));
+        let bound = adaptive_simpson(f64::sin, 0.0, f64::INFINITY, 1e-10);
+        assert_eq!(bound, Err(IntegrateError::InfiniteBound { bound: f64::INFINITY }));
+        // Oscillating ever faster toward 0.3
+        let stuck = adaptive_simpson(|x| (1.0 / (x - 0.3)).sin(), 0.0, 1.0, 1e-14);
+        assert_eq!(stuck, Err(IntegrateError::NoConvergence { tolerance: 1e-14 }));
+    }
+
+    #[test]
+    fn test_cumulative() {
+        let points = cumulative(|x| 2.0 * x, 0.0, 2.0, 4, 1e-10).unwrap();
+        assert_eq!(points.len(), 5);
+        assert!(points.iter().all(|[x, integral]| (integral - x * x).abs() < 1e-12));
+    }
+}
@@ -1,52 +1,117 @@
 //! Smooth mathematics module for SCTT
 //! Handles derivatives, integrals, and smooth functions
 
-use wasm_bindgen::prelude::*;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use num_traits::{Float, Zero, One};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use dual::{Dual, Jet};
+pub use expr::{Expr, ExprError, Func};
+pub use integrate::IntegrateError;
+pub use taylor::{TaylorError, TaylorSeries};
+
+pub mod dual;
+pub mod expr;
+pub mod geodesic;
+pub mod hover;
+pub mod integrate;
+pub mod sampling;
+pub mod surface;
+pub mod symbolic;
+pub mod taylor;
+
+#[cfg(feature = "wasm-bindings")]
+mod wasm;
+#[cfg(feature = "wasm-bindings")]
+pub use wasm::*;
+
+/// The highest derivative [`SmoothFunction::nth_derivative_at`] takes from
+/// a [`Jet`]
+pub const JET_ORDER: usize = 12;
+
+/// A real function of one variable, parsed, with its text as written
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SmoothFunction {
     expression: String,
     variable: String,
+    ast: Expr,
 }
 
 impl SmoothFunction {
-    pub fn new(expression: String, variable: String) -> Self {
-        SmoothFunction { expression, variable }
+    /// `expression` read as a function of `variable`; see [`expr`] for
+    /// what may be written
+    pub fn try_new(expression: String, variable: String) -> Result<Self, ExprError> {
+        let ast = expr::parse(&expression)?;
+        if let Some(name) = ast.variables().into_iter().find(|name| *name != variable) {
+            return Err(ExprError::UnknownVariable { name, variable });
+        }
+        Ok(SmoothFunction { expression, variable, ast })
+    }
+
+    fn of(ast: Expr, variable: &str) -> Self {
+        SmoothFunction { expression: ast.to_string(), variable: variable.to_string(), ast }
+    }
+
+    /// As written
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn variable(&self) -> &str {
+        &self.variable
+    }
+
+    pub fn ast(&self) -> &Expr {
+        &self.ast
     }
 
-    /// Evaluate the function at a point (simplified)
+    /// The value at `x`; NaN or infinite where the function is undefined
     pub fn evaluate(&self, x: f64) -> f64 {
-        // Simplified evaluation for demo
-        if self.expression.contains("sin") {
-            x.sin()
-        } else if self.expression.contains("cos") {
-            x.cos()
-        } else if self.expression.contains("x²") || self.expression.contains("x^2") {
-            x * x
-        } else if self.expression.contains("exp") {
-            x.exp()
-        } else {
-            x
-        }
+        self.ast.eval(x)
     }
 
-    /// Compute symbolic derivative (simplified)
+    /// The symbolic derivative, simplified; see [`symbolic`]
     pub fn derivative(&self) -> SmoothFunction {
-        let deriv_expr = if self.expression.contains("sin") {
-            format!("cos({})", self.variable)
-        } else if self.expression.contains("cos") {
-            format!("-sin({})", self.variable)
-        } else if self.expression.contains("x²") {
-            format!("2*{}", self.variable)
-        } else if self.expression.contains("exp") {
-            self.expression.clone()
-        } else {
-            "1".to_string()
-        };
+        SmoothFunction::of(self.ast.derivative(&self.variable), &self.variable)
+    }
+
+    /// A symbolic antiderivative, with no constant term; `None` past the
+    /// cases [`symbolic`] knows, such as `exp(x²)`
+    pub fn antiderivative(&self) -> Option<SmoothFunction> {
+        let ast = self.ast.antiderivative(&self.variable)?;
+        Some(SmoothFunction::of(ast, &self.variable))
+    }
+
+    /// The derivative at `x`, exact but for rounding, by [`Dual`] numbers;
+    /// unlike [`SmoothFunction::derivative`] it does not grow with the
+    /// expression
+    pub fn derivative_at(&self, x: f64) -> f64 {
+        self.ast.eval(Dual::variable(x)).deriv
+    }
+
+    /// The `n`th derivative at `x`, by a [`Jet`] up to [`JET_ORDER`] and
+    /// symbolically past it
+    pub fn nth_derivative_at(&self, x: f64, n: usize) -> f64 {
+        if n > JET_ORDER {
+            return self.nth_derivative(n).evaluate(x);
+        }
+        self.ast.eval(Jet::<{ JET_ORDER + 1 }>::variable(x)).derivative(n)
+    }
+
+    /// `∫ₐᵇ` of the function, to within [`integrate::TOLERANCE`]; see
+    /// [`integrate`]
+    pub fn integrate(&self, a: f64, b: f64) -> Result<f64, IntegrateError> {
+        self.integrate_within(a, b, integrate::TOLERANCE)
+    }
+
+    pub fn integrate_within(&self, a: f64, b: f64, tolerance: f64) -> Result<f64, IntegrateError> {
+        integrate::adaptive_simpson(|x| self.evaluate(x), a, b, tolerance)
+    }
 
-        SmoothFunction::new(deriv_expr, self.variable.clone())
+    /// `(x, ∫ₐˣ)` at `n + 1` evenly spaced `x` from `a` to `b`, to plot an
+    /// antiderivative by
+    pub fn antiderivative_values(&self, a: f64, b: f64, n: usize) -> Result<Vec<[f64; 2]>, IntegrateError> {
+        integrate::cumulative(|x| self.evaluate(x), a, b, n, integrate::TOLERANCE)
     }
 
     /// Compute nth derivative
@@ -59,141 +124,118 @@ impl SmoothFunction {
     }
 }
 
-// WASM bindings
-#[wasm_bindgen]
-pub struct WasmSmoothFunction {
-    inner: SmoothFunction,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
 
-#[wasm_bindgen]
-impl WasmSmoothFunction {
-    #[wasm_bindgen(constructor)]
-    pub fn new(expression: String, variable: String) -> WasmSmoothFunction {
-        WasmSmoothFunction {
-            inner: SmoothFunction::new(expression, variable),
-        }
+    fn function(expression: &str) -> SmoothFunction {
+        SmoothFunction::try_new(expression.to_string(), "x".to_string()).unwrap()
     }
 
-    pub fn evaluate(&self, x: f64) -> f64 {
-        self.inner.evaluate(x)
+    #[test]
+    fn test_smooth_evaluation() {
+        let f = function("sin(x)");
+        let val = f.evaluate(0.0);
+        assert!((val - 0.0).abs() < 1e-10);
+        // The whole expression counts, not its first primitive
+        assert!((function("x + sin(x)").evaluate(1.0) - (1.0 + 1f64.sin())).abs() < 1e-12);
+        assert_eq!(function("x²").expression(), "x²");
     }
 
-    pub fn derivative(&self) -> WasmSmoothFunction {
-        WasmSmoothFunction {
-            inner: self.inner.derivative(),
-        }
+    #[test]
+    fn test_derivative() {
+        let f = function("x²");
+        let df = f.derivative();
+        assert_eq!(df.expression, "2 * x");
+        assert_eq!(function("x³ - 2x + 1").derivative().expression(), "3 * x^2 - 2");
+        assert_eq!(function("sin(x)").nth_derivative(2).expression(), "-sin(x)");
+        assert_eq!(function("sin(x)").nth_derivative(4).expression(), "sin(x)");
     }
 
-    pub fn nth_derivative(&self, n: usize) -> WasmSmoothFunction {
-        WasmSmoothFunction {
-            inner: self.inner.nth_derivative(n),
-        }
+    #[test]
+    fn test_unreadable_functions_are_errors() {
+        let error = SmoothFunction::try_new("sin(x".to_string(), "x".to_string()).unwrap_err();
+        assert_eq!(error, ExprError::Parse { position: 5, message: "Expected `)`".to_string() });
+        let error = SmoothFunction::try_new("t + x".to_string(), "t".to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown variable `x`; the function is of `t`");
+        assert!(SmoothFunction::try_new("2t".to_string(), "t".to_string()).is_ok());
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn expression(&self) -> String {
-        self.inner.expression.clone()
+    /// `c₀ + c₁x + …`, written out
+    fn polynomial() -> impl Strategy<Value = String> {
+        prop::collection::vec(-5i32..=5, 1..=6).prop_map(|coefficients| {
+            let terms = coefficients.iter().enumerate().map(|(n, c)| format!("({}) * x^{}", c, n));
+            terms.collect::<Vec<_>>().join(" + ")
+        })
     }
-}
 
-// Taylor series expansion
-#[wasm_bindgen]
-pub struct TaylorSeries {
-    center: f64,
-    coefficients: Vec<f64>,
-}
-
-#[wasm_bindgen]
-impl TaylorSeries {
-    #[wasm_bindgen(constructor)]
-    pub fn new(center: f64) -> TaylorSeries {
-        TaylorSeries {
-            center,
-            coefficients: Vec::new(),
-        }
+    /// A polynomial, or a primitive of one
+    fn composition() -> impl Strategy<Value = String> {
+        let outer = vec!["{}", "sin({})", "cos({})", "exp(({}) / 10)", "sqrt(1 + ({})^2)", "ln(2 + sin({}))"];
+        (prop::sample::select(outer), polynomial()).prop_map(|(outer, inner)| outer.replace("{}", &inner))
     }
 
-    pub fn add_term(&mut self, coefficient: f64) {
-        self.coefficients.push(coefficient);
+    /// A sum of constant multiples of what [`SmoothFunction::antiderivative`] knows
+    fn integrable() -> impl Strategy<Value = String> {
+        let primitives = vec!["1", "x", "x^0", "x^3", "x^0.5", "sin(x)", "cos(x)", "exp(x)"];
+        let term = (-5i32..=5, prop::sample::select(primitives), any::<bool>()).prop_map(|(c, f, divided)| {
+            if divided { format!("{} / {}", f, c.abs().max(1)) } else { format!("({}) * {}", c, f) }
+        });
+        prop::collection::vec(term, 1..=5).prop_map(|terms| terms.join(" - "))
     }
 
-    pub fn evaluate(&self, x: f64) -> f64 {
-        let dx = x - self.center;
-        let mut result = 0.0;
-        let mut power = 1.0;
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() <= tolerance * a.abs().max(1.0)
+    }
 
-        for coeff in &self.coefficients {
-            result += coeff * power;
-            power *= dx;
+    proptest! {
+        #[test]
+        fn prop_dual_derivative_is_the_symbolic_one(source in composition(), x in -2.0..2.0f64) {
+            let f = function(&source);
+            let (symbolic, dual) = (f.derivative().evaluate(x), f.derivative_at(x));
+            prop_assert!(close(symbolic, dual, 1e-10), "{}: {} vs {}", source, symbolic, dual);
         }
 
-        result
-    }
+        #[test]
+        fn prop_jet_derivatives_are_the_symbolic_ones(source in composition(), x in -2.0..2.0f64) {
+            let f = function(&source);
+            for n in 0..=3 {
+                let (symbolic, jet) = (f.nth_derivative(n).evaluate(x), f.nth_derivative_at(x, n));
+                prop_assert!(close(symbolic, jet, 1e-9), "d{} {}: {} vs {}", n, source, symbolic, jet);
+            }
+        }
 
-    pub fn truncate(&mut self, order: usize) {
-        self.coefficients.truncate(order + 1);
+        #[test]
+        fn prop_antiderivative_differentiates_back(source in integrable(), x in 0.0..2.0f64) {
+            let f = function(&source);
+            let antiderivative = f.antiderivative().expect("integrable");
+            let (back, value) = (antiderivative.derivative_at(x), f.evaluate(x));
+            let integral = antiderivative.expression();
+            prop_assert!(close(back, value, 1e-10), "{} via {}: {} vs {}", source, integral, back, value);
+        }
     }
-}
-
-// Global exported functions for direct WASM use
-#[wasm_bindgen]
-pub fn evaluate(expr: &str, x: f64) -> f64 {
-    let func = SmoothFunction::new(expr.to_string(), "x".to_string());
-    func.evaluate(x)
-}
-
-#[wasm_bindgen]
-pub fn derivative(expr: &str, x: f64, order: usize) -> f64 {
-    let func = SmoothFunction::new(expr.to_string(), "x".to_string());
-    let deriv = func.nth_derivative(order);
-    deriv.evaluate(x)
-}
-
-// Chain rule demonstration
-#[wasm_bindgen]
-pub fn chain_rule(f_expr: &str, g_expr: &str, x: f64) -> JsValue {
-    let f = SmoothFunction::new(f_expr.to_string(), "x".to_string());
-    let g = SmoothFunction::new(g_expr.to_string(), "x".to_string());
-    
-    let gx = g.evaluate(x);
-    let f_prime_gx = f.derivative().evaluate(gx);
-    let g_prime_x = g.derivative().evaluate(x);
-    let result = f_prime_gx * g_prime_x;
-
-    serde_wasm_bindgen::to_value(&serde_json::json!({
-        "f": f_expr,
-        "g": g_expr,
-        "x": x,
-        "g(x)": gx,
-        "f'(g(x))": f_prime_gx,
-        "g'(x)": g_prime_x,
-        "result": result,
-        "formula": format!("(f∘g)'({}) = f'(g({})) · g'({}) = {} · {} = {}", 
-                          x, x, x, f_prime_gx, g_prime_x, result)
-    })).unwrap()
-}
-
-#[wasm_bindgen(start)]
-pub fn init() {
-    #[cfg(feature = "console_error_panic_hook")]
-    console_error_panic_hook::set_once();
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_smooth_evaluation() {
-        let f = SmoothFunction::new("sin(x)".to_string(), "x".to_string());
-        let val = f.evaluate(0.0);
-        assert!((val - 0.0).abs() < 1e-10);
+    fn test_integrals() {
+        assert!((function("sin(x)").integrate(0.0, std::f64::consts::PI).unwrap() - 2.0).abs() < 1e-8);
+        assert!((function("x²").integrate(0.0, 1.0).unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(function("1/x").integrate(-1.0, 1.0), Err(IntegrateError::NotFinite { x: 0.0 }));
+        assert_eq!(function("3x² + 2").antiderivative().unwrap().expression(), "x^3 + 2 * x");
+        assert!(function("exp(x²)").antiderivative().is_none());
+        let values = function("cos(x)").antiderivative_values(0.0, 1.0, 10).unwrap();
+        assert!(values.iter().all(|[x, integral]| (integral - x.sin()).abs() < 1e-10));
     }
 
     #[test]
-    fn test_derivative() {
-        let f = SmoothFunction::new("x²".to_string(), "x".to_string());
-        let df = f.derivative();
-        assert_eq!(df.expression, "2*x");
+    fn test_derivatives_at_points() {
+        let f = function("exp(sin(x²))");
+        let x = 0.8f64;
+        let exact = (x * x).sin().exp() * (x * x).cos() * 2.0 * x;
+        assert!((f.derivative_at(x) - exact).abs() < 1e-12);
+        assert!((function("x^5").nth_derivative_at(2.0, 5) - 120.0).abs() < 1e-9);
+        assert_eq!(function("x^5").nth_derivative_at(2.0, 6), 0.0);
+        // Past the jet, symbolically
+        assert!((function("sin(x)").nth_derivative_at(0.5, JET_ORDER + 2) + 0.5f64.sin()).abs() < 1e-12);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,235 @@
+//! Adaptive sampling of y = f(x) for plotting
+//!
+//! A uniform grid either wastes points on flat stretches or misses detail
+//! near spikes. Here a coarse grid is refined only where it needs to be:
+//! an interval is bisected while its midpoint value strays from the chord
+//! between its endpoints by more than the tolerance. The worst interval is
+//! always split first, so when the point cap is hit the budget has gone
+//! where the curve bends most.
+//!
+//! Where f is not finite (poles, `ln` of a negative) the sampler keeps
+//! bisecting toward the boundary and reports a small excluded interval, so
+//! a renderer can break the path there instead of drawing a vertical line
+//! through the pole.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Intervals in the uniform grid sampling starts from
+pub const COARSE_INTERVALS: usize = 32;
+/// Bisections of one coarse interval, at most
+pub const MAX_DEPTH: u32 = 12;
+
+/// One sampled point; `refined` points were added by bisection
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SamplePoint {
+    pub x: f64,
+    pub y: f64,
+    pub refined: bool,
+}
+
+/// Sorted finite samples plus the intervals where f was not finite
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdaptiveSamples {
+    pub points: Vec<SamplePoint>,
+    /// `[from, to]` around each run of non-finite values; no point lies inside
+    pub excluded: Vec<[f64; 2]>,
+    /// Whether the point cap stopped refinement before the tolerance was met
+    pub truncated: bool,
+}
+
+impl AdaptiveSamples {
+    /// The points split into runs that can each be drawn as one polyline
+    pub fn segments(&self) -> Vec<&[SamplePoint]> {
+        let mut segments = Vec::new();
+        let mut start = 0;
+        for (i, pair) in self.points.windows(2).enumerate() {
+            let gap = self.excluded.iter().any(|[from, to]| pair[0].x <= *from && *to <= pair[1].x);
+            if gap {
+                segments.push(&self.points[start..=i]);
+                start = i + 1;
+            }
+        }
+        if start < self.points.len() {
+            segments.push(&self.points[start..]);
+        }
+        segments
+    }
+}
+
+/// An interval waiting to be split, with its midpoint already evaluated
+struct Pending {
+    a: (f64, f64),
+    m: (f64, f64),
+    b: (f64, f64),
+    depth: u32,
+    score: f64,
+}
+
+impl Pending {
+    fn new(f: &impl Fn(f64) -> f64, a: (f64, f64), b: (f64, f64), depth: u32) -> Self {
+        let x = (a.0 + b.0) / 2.0;
+        let m = (x, f(x));
+        let finite = [a.1, m.1, b.1].iter().filter(|y| y.is_finite()).count();
+        let score = match finite {
+            3 => (m.1 - (a.1 + b.1) / 2.0).abs(),
+            // Wholly undefined, nothing to bracket
+            0 => 0.0,
+            // A boundary of the domain lies inside: always worth narrowing
+            _ => f64::INFINITY,
+        };
+        Pending { a, m, b, depth, score }
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.score.total_cmp(&other.score) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.total_cmp(&other.score)
+    }
+}
+
+/// Sample `f` on `[start, end]` with at most `max_points` points
+///
+/// An interval is split while its midpoint deviates from the chord by more
+/// than `tol`, or by more than `tol` times the coarse grid's y-range when
+/// that is larger, so steep and nearly flat curves are both judged on their
+/// own scale. Splitting stops at [`MAX_DEPTH`] or at `max_points`, which is
+/// never exceeded (and is raised to 2 if smaller).
+pub fn sample_adaptive(f: impl Fn(f64) -> f64, start: f64, end: f64, tol: f64, max_points: usize) -> AdaptiveSamples {
+    if !start.is_finite() || !end.is_finite() || start >= end {
+        return AdaptiveSamples::default();
+    }
+    let max_points = max_points.max(2);
+    let intervals = COARSE_INTERVALS.min(max_points - 1);
+    let h = (end - start) / intervals as f64;
+
+    let mut raw: Vec<SamplePoint> = (0..=intervals)
+        .map(|i| {
+            let x = if i == intervals { end } else { start + i as f64 * h };
+            SamplePoint { x, y: f(x), refined: false }
+        })
+        .collect();
+
+    let (low, high) = raw
+        .iter()
+        .filter(|p| p.y.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.y), hi.max(p.y)));
+    let range = if high > low { high - low } else { 0.0 };
+    let threshold = tol.abs().max(tol.abs() * range);
+
+    let mut queue: BinaryHeap<Pending> = raw
+        .windows(2)
+        .map(|pair| Pending::new(&f, (pair[0].x, pair[0].y), (pair[1].x, pair[1].y), 1))
+        .collect();
+
+    let mut truncated = false;
+    while let Some(next) = queue.pop() {
+        if next.score <= threshold {
+            break;
+        }
+        if raw.len() >= max_points {
+            truncated = true;
+            break;
+        }
+        raw.push(SamplePoint { x: next.m.0, y: next.m.1, refined: true });
+        if next.depth < MAX_DEPTH {
+            queue.push(Pending::new(&f, next.a, next.m, next.depth + 1));
+            queue.push(Pending::new(&f, next.m, next.b, next.depth + 1));
+        }
+    }
+
+    raw.sort_by(|p, q| p.x.total_cmp(&q.x));
+
+    let mut excluded = Vec::new();
+    let mut points = Vec::with_capacity(raw.len());
+    let mut gap_from: Option<f64> = None;
+    for (i, point) in raw.iter().enumerate() {
+        if point.y.is_finite() {
+            if let Some(from) = gap_from.take() {
+                excluded.push([from, point.x]);
+            }
+            points.push(*point);
+        } else if gap_from.is_none() {
+            gap_from = Some(if i == 0 { start } else { raw[i - 1].x });
+        }
+    }
+    if let Some(from) = gap_from {
+        excluded.push([from, end]);
+    }
+
+    AdaptiveSamples { points, excluded, truncated }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_pole_is_bracketed_and_attracts_points() {
+        let samples = sample_adaptive(|x| 1.0 / ((x - 0.5) * (x - 0.5)), 0.0, 1.0, 1e-3, 400);
+
+        assert_eq!(samples.excluded.len(), 1);
+        let [from, to] = samples.excluded[0];
+        assert!(from < 0.5 && 0.5 < to);
+        assert!(to - from < 1e-3, "excluded interval {:?} is not small", samples.excluded[0]);
+
+        // Far more points within 0.05 of the pole than a uniform grid puts there
+        let near = samples.points.iter().filter(|p| (p.x - 0.5).abs() < 0.05).count();
+        let uniform = samples.points.len() / 10;
+        assert!(near > 3 * uniform, "{} of {} points near the pole", near, samples.points.len());
+
+        // The pole splits the curve in two drawable pieces
+        assert_eq!(samples.segments().len(), 2);
+    }
+
+    #[test]
+    fn test_smooth_curve_stays_near_the_coarse_grid() {
+        let samples = sample_adaptive(f64::sin, 0.0, 2.0 * PI, 1e-2, 1000);
+        assert!(samples.excluded.is_empty());
+        assert!(!samples.truncated);
+        assert!(samples.points.len() <= 2 * (COARSE_INTERVALS + 1), "{} points", samples.points.len());
+        assert_eq!(samples.points.first().map(|p| p.x), Some(0.0));
+        assert_eq!(samples.points.last().map(|p| p.x), Some(2.0 * PI));
+    }
+
+    #[test]
+    fn test_point_cap_is_never_exceeded() {
+        let wild = |x: f64| (1.0 / x).sin() / x;
+        for cap in [0, 1, 2, 5, 33, 34, 100, 777] {
+            let samples = sample_adaptive(wild, -1.0, 1.0, 1e-6, cap);
+            let total = samples.points.len();
+            assert!(total <= cap.max(2), "{} points for a cap of {}", total, cap);
+        }
+        assert!(sample_adaptive(wild, -1.0, 1.0, 1e-6, 100).truncated);
+    }
+
+    #[test]
+    fn test_undefined_ends_are_excluded_to_the_boundary() {
+        let samples = sample_adaptive(f64::ln, -1.0, 1.0, 1e-3, 500);
+        assert_eq!(samples.excluded.len(), 1);
+        let [from, to] = samples.excluded[0];
+        assert_eq!(from, -1.0);
+        assert!(to > 0.0 && to < 1e-3);
+        assert!(samples.points.iter().all(|p| p.x >= to));
+    }
+}
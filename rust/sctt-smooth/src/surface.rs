@@ -0,0 +1,316 @@
+//! Software rendering of surfaces z = f(x, y)
+//!
+//! Everything a 2D canvas needs to draw a shaded surface without a GPU:
+//! an orthographic orbit camera, a sampled height grid, depth-sorted quads
+//! for the painter's algorithm, picking by inverse projection, and a frame
+//! budget controller that trades grid resolution for speed.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Consecutive over-budget frames before the resolution is halved
+const SLOW_FRAMES: usize = 3;
+/// Consecutive comfortable frames before the resolution is doubled again
+const FAST_FRAMES: usize = 60;
+
+/// Orthographic camera orbiting the origin of the unit cube
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Camera {
+    /// Rotation about the z-axis, in radians
+    pub azimuth: f64,
+    /// Angle above the xy-plane, in radians
+    pub elevation: f64,
+    /// Pixels per world unit
+    pub zoom: f64,
+}
+
+/// A projected point: pixel coordinates and depth (larger is nearer)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projected {
+    pub x: f64,
+    pub y: f64,
+    pub depth: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera { azimuth: -0.6, elevation: 0.5, zoom: 150.0 }
+    }
+}
+
+impl Camera {
+    /// Right, up and towards-viewer unit vectors
+    fn basis(&self) -> [[f64; 3]; 3] {
+        let (sa, ca) = self.azimuth.sin_cos();
+        let (se, ce) = self.elevation.sin_cos();
+        [
+            [-sa, ca, 0.0],
+            [-se * ca, -se * sa, ce],
+            [ce * ca, ce * sa, se],
+        ]
+    }
+
+    /// Project a world point onto a `width` × `height` canvas
+    pub fn project(&self, p: [f64; 3], width: f64, height: f64) -> Projected {
+        let [right, up, toward] = self.basis();
+        Projected {
+            x: width / 2.0 + self.zoom * dot(p, right),
+            y: height / 2.0 - self.zoom * dot(p, up),
+            depth: dot(p, toward),
+        }
+    }
+
+    /// Drag to orbit; elevation stays short of the poles
+    pub fn orbit(&mut self, dx: f64, dy: f64) {
+        let limit = std::f64::consts::FRAC_PI_2 - 0.01;
+        self.azimuth -= dx * 0.01;
+        self.elevation = (self.elevation + dy * 0.01).clamp(-limit, limit);
+    }
+
+    /// Wheel to zoom; `steps` > 0 zooms in
+    pub fn zoom_by(&mut self, steps: f64) {
+        self.zoom = (self.zoom * 1.1f64.powf(steps)).clamp(10.0, 2000.0);
+    }
+
+    /// Screen directions of the x, y and z axes, for an axis tripod
+    pub fn tripod(&self) -> [[f64; 2]; 3] {
+        let [right, up, _] = self.basis();
+        [0, 1, 2].map(|axis| [right[axis], -up[axis]])
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Heights of f sampled on an `n` × `n` cell grid
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SurfaceGrid {
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    pub n: usize,
+    /// Row-major `(n + 1)²` samples; `z[j * (n + 1) + i]` is at (xᵢ, yⱼ)
+    pub z: Vec<f64>,
+    pub z_range: (f64, f64),
+}
+
+impl SurfaceGrid {
+    pub fn sample(f: impl Fn(f64, f64) -> f64, x_range: (f64, f64), y_range: (f64, f64), n: usize) -> Self {
+        let n = n.max(1);
+        let points: Vec<(f64, f64)> = (0..=n)
+            .flat_map(|j| (0..=n).map(move |i| (i, j)))
+            .map(|(i, j)| (lerp(x_range, i as f64 / n as f64), lerp(y_range, j as f64 / n as f64)))
+            .collect();
+        Self::from_samples(points.iter().map(|&(x, y)| f(x, y)).collect(), x_range, y_range, n)
+    }
+
+    /// Build from values already evaluated in bulk at [`SurfaceGrid::sample`]'s points
+    ///
+    /// Expects `(n + 1)²` values; missing ones are left as holes and extra ones dropped.
+    pub fn from_samples(mut z: Vec<f64>, x_range: (f64, f64), y_range: (f64, f64), n: usize) -> Self {
+        z.resize((n + 1) * (n + 1), f64::NAN);
+        let finite = z.iter().copied().filter(|v| v.is_finite());
+        let (lo, hi) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        let z_range = if lo <= hi { (lo, hi) } else { (0.0, 0.0) };
+        SurfaceGrid { x_range, y_range, n, z, z_range }
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        self.z[j * (self.n + 1) + i]
+    }
+
+    /// Sample (i, j) in world coordinates, scaled into [-1, 1]³
+    pub fn world(&self, i: usize, j: usize) -> [f64; 3] {
+        let (lo, hi) = self.z_range;
+        let z = if hi > lo { 2.0 * (self.height(i, j) - lo) / (hi - lo) - 1.0 } else { 0.0 };
+        [2.0 * i as f64 / self.n as f64 - 1.0, 2.0 * j as f64 / self.n as f64 - 1.0, z]
+    }
+
+    /// World coordinates back to (x, y, f(x, y))
+    pub fn data(&self, world: [f64; 3]) -> [f64; 3] {
+        let (lo, hi) = self.z_range;
+        [
+            lerp(self.x_range, (world[0] + 1.0) / 2.0),
+            lerp(self.y_range, (world[1] + 1.0) / 2.0),
+            lerp((lo, hi), (world[2] + 1.0) / 2.0),
+        ]
+    }
+
+    /// Projected cells, back to front, skipping cells with non-finite samples
+    pub fn quads(&self, camera: &Camera, width: f64, height: f64) -> Vec<Quad> {
+        let mut quads = Vec::with_capacity(self.n * self.n);
+        for j in 0..self.n {
+            for i in 0..self.n {
+                let cell = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                if cell.iter().any(|&(a, b)| !self.height(a, b).is_finite()) {
+                    continue;
+                }
+                let world = cell.map(|(a, b)| self.world(a, b));
+                let projected = world.map(|p| camera.project(p, width, height));
+                quads.push(Quad {
+                    cell: (i, j),
+                    world,
+                    corners: projected.map(|p| [p.x, p.y]),
+                    depth: projected.iter().map(|p| p.depth).sum::<f64>() / 4.0,
+                    level: (world.iter().map(|p| p[2]).sum::<f64>() / 4.0 + 1.0) / 2.0,
+                });
+            }
+        }
+        quads.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+        quads
+    }
+
+    /// The surface point under a pixel, as (x, y, f(x, y))
+    pub fn pick(&self, camera: &Camera, width: f64, height: f64, px: f64, py: f64) -> Option<[f64; 3]> {
+        // Nearest quads are last; the first hit from the front is visible
+        self.quads(camera, width, height).iter().rev().find_map(|quad| {
+            [[0, 1, 2], [0, 2, 3]].iter().find_map(|&[a, b, c]| {
+                let w = barycentric([px, py], quad.corners[a], quad.corners[b], quad.corners[c])?;
+                let point = [0, 1, 2].map(|k| w[0] * quad.world[a][k] + w[1] * quad.world[b][k] + w[2] * quad.world[c][k]);
+                Some(self.data(point))
+            })
+        })
+    }
+}
+
+fn lerp((a, b): (f64, f64), t: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Barycentric weights of `p` in triangle `abc`, if it lies inside
+fn barycentric(p: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> Option<[f64; 3]> {
+    let det = (b[1] - c[1]) * (a[0] - c[0]) + (c[0] - b[0]) * (a[1] - c[1]);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let wa = ((b[1] - c[1]) * (p[0] - c[0]) + (c[0] - b[0]) * (p[1] - c[1])) / det;
+    let wb = ((c[1] - a[1]) * (p[0] - c[0]) + (a[0] - c[0]) * (p[1] - c[1])) / det;
+    let wc = 1.0 - wa - wb;
+    let eps = -1e-9;
+    (wa >= eps && wb >= eps && wc >= eps).then_some([wa, wb, wc])
+}
+
+/// One projected grid cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quad {
+    pub cell: (usize, usize),
+    pub world: [[f64; 3]; 4],
+    /// Pixel coordinates of the corners, in winding order
+    pub corners: [[f64; 2]; 4],
+    /// Mean depth of the corners; larger is nearer
+    pub depth: f64,
+    /// Mean height in [0, 1], for coloring
+    pub level: f64,
+}
+
+/// Height map: blue at the bottom through green and yellow to red at the top
+pub fn height_color(level: f64) -> [u8; 3] {
+    const STOPS: [[f64; 3]; 4] = [[59.0, 76.0, 192.0], [80.0, 180.0, 120.0], [240.0, 210.0, 80.0], [200.0, 50.0, 40.0]];
+    let t = level.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let k = (t.floor() as usize).min(STOPS.len() - 2);
+    let s = t - k as f64;
+    [0, 1, 2].map(|c| (STOPS[k][c] + s * (STOPS[k + 1][c] - STOPS[k][c])).round() as u8)
+}
+
+/// Picks the grid resolution from measured frame times
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolutionController {
+    /// Frame time budget in milliseconds
+    pub budget_ms: f64,
+    pub resolution: usize,
+    pub min: usize,
+    pub max: usize,
+    slow_streak: usize,
+    fast_streak: usize,
+}
+
+impl ResolutionController {
+    pub fn new(budget_ms: f64, min: usize, max: usize) -> Self {
+        ResolutionController { budget_ms, resolution: max, min, max, slow_streak: 0, fast_streak: 0 }
+    }
+
+    /// Record one frame's duration and return the resolution for the next
+    pub fn record(&mut self, frame_ms: f64) -> usize {
+        if frame_ms > self.budget_ms {
+            self.fast_streak = 0;
+            self.slow_streak += 1;
+            if self.slow_streak >= SLOW_FRAMES {
+                self.slow_streak = 0;
+                self.resolution = (self.resolution / 2).max(self.min);
+            }
+        } else {
+            self.slow_streak = 0;
+            // Only grow back when there is clear headroom, to avoid flapping
+            if frame_ms < self.budget_ms / 4.0 {
+                self.fast_streak += 1;
+            }
+            if self.fast_streak >= FAST_FRAMES {
+                self.fast_streak = 0;
+                self.resolution = (self.resolution * 2).min(self.max);
+            }
+        }
+        self.resolution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_projection_of_known_point() {
+        // Looking along -x: y is to the right, z is up
+        let camera = Camera { azimuth: 0.0, elevation: 0.0, zoom: 100.0 };
+        let p = camera.project([0.0, 1.0, 1.0], 800.0, 600.0);
+        assert!((p.x - 500.0).abs() < 1e-9 && (p.y - 200.0).abs() < 1e-9);
+        assert!(camera.project([1.0, 0.0, 0.0], 800.0, 600.0).depth > camera.project([-1.0, 0.0, 0.0], 800.0, 600.0).depth);
+    }
+
+    #[test]
+    fn test_quads_are_sorted_back_to_front() {
+        let grid = SurfaceGrid::sample(|x, _| x, (-1.0, 1.0), (-1.0, 1.0), 8);
+        let camera = Camera { azimuth: 0.0, elevation: 0.3, zoom: 100.0 };
+        let quads = grid.quads(&camera, 400.0, 400.0);
+        assert_eq!(quads.len(), 64);
+        assert!(quads.windows(2).all(|w| w[0].depth <= w[1].depth));
+        // The camera sits on the +x side, so the last cells drawn are at the largest x
+        assert_eq!(quads.last().unwrap().cell.0, 7);
+        assert_eq!(quads.first().unwrap().cell.0, 0);
+    }
+
+    #[test]
+    fn test_missing_samples_are_holes() {
+        // Six of nine samples: the top row of the 2 × 2-cell grid is missing
+        let grid = SurfaceGrid::from_samples(vec![0.0; 6], (0.0, 1.0), (0.0, 1.0), 2);
+        let camera = Camera { azimuth: 0.0, elevation: 0.3, zoom: 100.0 };
+        assert_eq!(grid.quads(&camera, 400.0, 400.0).len(), 2);
+    }
+
+    #[test]
+    fn test_pick_inverts_projection() {
+        let grid = SurfaceGrid::sample(|x, y| x * x - y * y, (-2.0, 2.0), (-2.0, 2.0), 16);
+        let camera = Camera { azimuth: 0.0, elevation: FRAC_PI_2 - 0.01, zoom: 100.0 };
+        let [x, y, z] = grid.pick(&camera, 400.0, 400.0, 200.0, 200.0).unwrap();
+        assert!(x.abs() < 0.05 && y.abs() < 0.05 && z.abs() < 0.05);
+        assert!(grid.pick(&camera, 400.0, 400.0, 5.0, 5.0).is_none());
+    }
+
+    #[test]
+    fn test_controller_halves_resolution_after_slow_frames() {
+        let mut controller = ResolutionController::new(16.0, 8, 64);
+        assert_eq!(controller.record(40.0), 64);
+        assert_eq!(controller.record(40.0), 64);
+        assert_eq!(controller.record(40.0), 32);
+        for _ in 0..SLOW_FRAMES * 4 {
+            controller.record(40.0);
+        }
+        assert_eq!(controller.resolution, 8);
+        for _ in 0..FAST_FRAMES {
+            controller.record(1.0);
+        }
+        assert_eq!(controller.resolution, 16);
+    }
+}
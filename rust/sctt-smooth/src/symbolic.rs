@@ -0,0 +1,284 @@
+//! Symbolic derivatives and antiderivatives of [`Expr`]
+//!
+//! [`Expr::derivative`] applies the sum, product, quotient, power and chain
+//! rules, and takes `min`, `max` and `if` apart into cases on the same
+//! test, so that the derivative has its corners where the function does.
+//! [`Expr::antiderivative`] knows only the blackboard cases: powers
+//! of the variable other than `x⁻¹`, `sin`, `cos` and `exp` of the variable
+//! itself, constants, and sums and constant multiples of those.
+//!
+//! Both build their results with the same constructors [`Expr::simplify`]
+//! uses: constant arithmetic is folded (division only when it comes out
+//! whole), `+ 0`, `* 1`, `* 0`, `^ 1` and `^ 0` are dropped, a constant
+//! factor is moved to the front, and signs are gathered, so that `d/dx sin`
+//! twice is `-sin(x)` rather than `-sin(x) * 1 * 1`.
+
+use crate::expr::{Expr, Func};
+
+impl Expr {
+    /// The derivative with respect to `variable`, simplified
+    pub fn derivative(&self, variable: &str) -> Expr {
+        let d = |e: &Expr| e.derivative(variable);
+        match self {
+            Expr::Num(_) => Expr::Num(0.0),
+            Expr::Var(v) => Expr::Num(if v == variable { 1.0 } else { 0.0 }),
+            Expr::Neg(a) => neg(d(a)),
+            Expr::Add(a, b) => add(d(a), d(b)),
+            Expr::Sub(a, b) => sub(d(a), d(b)),
+            Expr::Mul(a, b) => add(mul(d(a), b.simplify()), mul(a.simplify(), d(b))),
+            Expr::Div(a, b) => {
+                let (a, b, da, db) = (a.simplify(), b.simplify(), d(a), d(b));
+                div(sub(mul(da, b.clone()), mul(a, db)), pow(b, Expr::Num(2.0)))
+            }
+            Expr::Pow(a, b) => {
+                let (base, exponent) = (a.simplify(), b.simplify());
+                if !exponent.variables().iter().any(|v| v == variable) {
+                    let lowered = pow(base, sub(exponent.clone(), Expr::Num(1.0)));
+                    mul(mul(exponent, lowered), d(a))
+                } else if !base.variables().iter().any(|v| v == variable) {
+                    mul(mul(pow(base.clone(), exponent), call(Func::Ln, base)), d(b))
+                } else {
+                    // d(a^b) = a^b (b' ln a + b a' / a)
+                    let through_exponent = mul(d(b), call(Func::Ln, base.clone()));
+                    let through_base = div(mul(exponent.clone(), d(a)), base.clone());
+                    mul(pow(base, exponent), add(through_exponent, through_base))
+                }
+            }
+            Expr::Call(f, a) => {
+                let inner = a.simplify();
+                let outer = match f {
+                    Func::Sin => call(Func::Cos, inner),
+                    Func::Cos => neg(call(Func::Sin, inner)),
+                    Func::Tan => div(Expr::Num(1.0), pow(call(Func::Cos, inner), Expr::Num(2.0))),
+                    Func::Exp => call(Func::Exp, inner),
+                    Func::Ln => div(Expr::Num(1.0), inner),
+                    Func::Sqrt => div(Expr::Num(1.0), mul(Expr::Num(2.0), call(Func::Sqrt, inner))),
+                };
+                mul(outer, d(a))
+            }
+            Expr::Min(a, b) => cases(sub(a.simplify(), b.simplify()), 0.0, d(a), d(b)),
+            Expr::Max(a, b) => cases(sub(a.simplify(), b.simplify()), 0.0, d(b), d(a)),
+            Expr::Cases { test, at, below, above } => cases(test.simplify(), *at, d(below), d(above)),
+        }
+    }
+
+    /// An antiderivative with respect to `variable`, simplified, with no
+    /// constant term; `None` for a form not covered (see the
+    /// [module](self)), such as `exp(x²)`
+    pub fn antiderivative(&self, variable: &str) -> Option<Expr> {
+        let constant = |e: &Expr| !e.variables().iter().any(|v| v == variable);
+        let x = || Expr::Var(variable.to_string());
+        let integral = |e: &Expr| e.antiderivative(variable);
+        Some(match self {
+            c if constant(c) => mul(c.simplify(), x()),
+            Expr::Var(_) => div(pow(x(), Expr::Num(2.0)), Expr::Num(2.0)),
+            Expr::Neg(a) => neg(integral(a)?),
+            Expr::Add(a, b) => add(integral(a)?, integral(b)?),
+            Expr::Sub(a, b) => sub(integral(a)?, integral(b)?),
+            Expr::Mul(c, f) | Expr::Mul(f, c) if constant(c) => mul(c.simplify(), integral(f)?),
+            Expr::Div(f, c) if constant(c) => div(integral(f)?, c.simplify()),
+            Expr::Pow(base, n) if matches!(**base, Expr::Var(_)) && constant(n) => {
+                // `x⁻¹` integrates to a logarithm, only on one side of 0
+                if n.eval(0.0) == -1.0 {
+                    return None;
+                }
+                let raised = add(n.simplify(), Expr::Num(1.0));
+                div(pow(x(), raised.clone()), raised)
+            }
+            Expr::Call(f, a) if matches!(**a, Expr::Var(_)) => match f {
+                Func::Sin => neg(call(Func::Cos, x())),
+                Func::Cos => call(Func::Sin, x()),
+                Func::Exp => call(Func::Exp, x()),
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+
+    /// The same function with constant arithmetic folded and trivial
+    /// operations dropped; see the [module](self)
+    pub fn simplify(&self) -> Expr {
+        match self {
+            Expr::Num(_) | Expr::Var(_) => self.clone(),
+            Expr::Neg(a) => neg(a.simplify()),
+            Expr::Add(a, b) => add(a.simplify(), b.simplify()),
+            Expr::Sub(a, b) => sub(a.simplify(), b.simplify()),
+            Expr::Mul(a, b) => mul(a.simplify(), b.simplify()),
+            Expr::Div(a, b) => div(a.simplify(), b.simplify()),
+            Expr::Pow(a, b) => pow(a.simplify(), b.simplify()),
+            Expr::Call(f, a) => call(*f, a.simplify()),
+            Expr::Min(a, b) => fold(&a.simplify(), &b.simplify(), f64::min)
+                .unwrap_or_else(|| Expr::Min(Box::new(a.simplify()), Box::new(b.simplify()))),
+            Expr::Max(a, b) => fold(&a.simplify(), &b.simplify(), f64::max)
+                .unwrap_or_else(|| Expr::Max(Box::new(a.simplify()), Box::new(b.simplify()))),
+            Expr::Cases { test, at, below, above } => cases(test.simplify(), *at, below.simplify(), above.simplify()),
+        }
+    }
+}
+
+/// `op` applied to two numbers, when the result is worth writing as one
+fn fold(a: &Expr, b: &Expr, op: fn(f64, f64) -> f64) -> Option<Expr> {
+    let (Expr::Num(x), Expr::Num(y)) = (a, b) else { return None };
+    let result = op(*x, *y);
+    result.is_finite().then_some(Expr::Num(result))
+}
+
+fn call(f: Func, a: Expr) -> Expr {
+    Expr::Call(f, Box::new(a))
+}
+
+/// `below` where `test ≤ at`, `above` elsewhere, as one of them when the
+/// test is a number or the two agree
+fn cases(test: Expr, at: f64, below: Expr, above: Expr) -> Expr {
+    match test {
+        Expr::Num(n) => if n <= at { below } else { above },
+        _ if below == above => below,
+        test => Expr::Cases { test: Box::new(test), at, below: Box::new(below), above: Box::new(above) },
+    }
+}
+
+fn neg(a: Expr) -> Expr {
+    match a {
+        Expr::Num(n) => Expr::Num(-n),
+        Expr::Neg(a) => *a,
+        a => Expr::Neg(Box::new(a)),
+    }
+}
+
+fn add(a: Expr, b: Expr) -> Expr {
+    if let Some(sum) = fold(&a, &b, |x, y| x + y) {
+        return sum;
+    }
+    match (a, b) {
+        (Expr::Num(0.0), e) | (e, Expr::Num(0.0)) => e,
+        (a, Expr::Neg(b)) => sub(a, *b),
+        (a, Expr::Num(n)) if n < 0.0 => sub(a, Expr::Num(-n)),
+        (a, b) => Expr::Add(Box::new(a), Box::new(b)),
+    }
+}
+
+fn sub(a: Expr, b: Expr) -> Expr {
+    if let Some(difference) = fold(&a, &b, |x, y| x - y) {
+        return difference;
+    }
+    match (a, b) {
+        (e, Expr::Num(0.0)) => e,
+        (Expr::Num(0.0), e) => neg(e),
+        (a, b) if a == b => Expr::Num(0.0),
+        (a, Expr::Neg(b)) => add(a, *b),
+        (a, Expr::Num(n)) if n < 0.0 => add(a, Expr::Num(-n)),
+        (a, b) => Expr::Sub(Box::new(a), Box::new(b)),
+    }
+}
+
+fn mul(a: Expr, b: Expr) -> Expr {
+    if let Some(product) = fold(&a, &b, |x, y| x * y) {
+        return product;
+    }
+    match (a, b) {
+        (Expr::Num(0.0), _) | (_, Expr::Num(0.0)) => Expr::Num(0.0),
+        (Expr::Num(1.0), e) | (e, Expr::Num(1.0)) => e,
+        (Expr::Num(-1.0), e) | (e, Expr::Num(-1.0)) => neg(e),
+        // Coefficients go first, and gather there
+        (e, n @ Expr::Num(_)) if !matches!(e, Expr::Num(_)) => mul(n, e),
+        (Expr::Num(x), Expr::Mul(b, c)) if matches!(*b, Expr::Num(_)) => mul(mul(Expr::Num(x), *b), *c),
+        (Expr::Num(x), Expr::Div(a, d)) if matches!(*d, Expr::Num(y) if (x / y).fract() == 0.0) => {
+            mul(div(Expr::Num(x), *d), *a)
+        }
+        (Expr::Neg(a), b) => neg(mul(*a, b)),
+        (a, Expr::Neg(b)) => neg(mul(a, *b)),
+        (a, b) => Expr::Mul(Box::new(a), Box::new(b)),
+    }
+}
+
+fn div(a: Expr, b: Expr) -> Expr {
+    let whole = |q: &Expr| matches!(q, Expr::Num(n) if n.fract() == 0.0);
+    if let Some(quotient) = fold(&a, &b, |x, y| x / y).filter(whole) {
+        return quotient;
+    }
+    match (a, b) {
+        (Expr::Num(0.0), _) => Expr::Num(0.0),
+        (e, Expr::Num(1.0)) => e,
+        (a, b) => Expr::Div(Box::new(a), Box::new(b)),
+    }
+}
+
+fn pow(a: Expr, b: Expr) -> Expr {
+    if let Some(power) = fold(&a, &b, f64::powf) {
+        return power;
+    }
+    match (a, b) {
+        (e, Expr::Num(1.0)) => e,
+        (_, Expr::Num(0.0)) => Expr::Num(1.0),
+        (a, b) => Expr::Pow(Box::new(a), Box::new(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::parse;
+
+    fn derivative(source: &str) -> Expr {
+        parse(source).unwrap().derivative("x")
+    }
+
+    #[test]
+    fn test_derivatives_print_simply() {
+        let printed = |source: &str| derivative(source).to_string();
+        assert_eq!(printed("x³ - 2x + 1"), "3 * x^2 - 2");
+        assert_eq!(printed("5x^4 + x² + 7"), "20 * x^3 + 2 * x");
+        assert_eq!(printed("x·sin(x)"), "sin(x) + x * cos(x)");
+        assert_eq!(printed("exp(sin(x²))"), "exp(sin(x^2)) * (cos(x^2) * (2 * x))");
+        assert_eq!(printed("1 / x"), "-1 / x^2");
+        assert_eq!(printed("cos(x)"), "-sin(x)");
+        assert_eq!(printed("42"), "0");
+        assert_eq!(printed("2^x"), "2^x * ln(2)");
+        assert_eq!(parse("(x + 0) * 1 - -(2 + 3)").unwrap().simplify().to_string(), "x + 5");
+        assert_eq!(printed("max(x², 1 - x)"), "if(x^2 - (1 - x) ≤ 0, -1, 2 * x)");
+        assert_eq!(printed("x ∧ 2x"), "if(x - 2 * x ≤ 0, 1, 2)");
+        assert_eq!(printed("if(x ≤ 1, x², 2x - 1)"), "if(x ≤ 1, 2 * x, 2)");
+        assert_eq!(printed("min(x, x)"), "1");
+    }
+
+    #[test]
+    fn test_antiderivatives() {
+        let printed = |source: &str| parse(source).unwrap().antiderivative("x").map(|e| e.to_string());
+        assert_eq!(printed("3x² - 2x + 1").as_deref(), Some("x^3 - x^2 + x"));
+        assert_eq!(printed("sin(x) + cos(x)").as_deref(), Some("-cos(x) + sin(x)"));
+        assert_eq!(printed("exp(x) / 2").as_deref(), Some("exp(x) / 2"));
+        assert_eq!(printed("π").as_deref(), Some("π * x"));
+        assert_eq!(printed("x^0.5").as_deref(), Some("x^1.5 / 1.5"));
+        assert_eq!(printed("exp(x²)"), None);
+        assert_eq!(printed("1 / x"), None);
+        assert_eq!(printed("x^-1"), None);
+        assert_eq!(printed("x sin(x)"), None);
+    }
+
+    #[test]
+    fn test_derivatives_agree_with_differences() {
+        let corpus = [
+            "x³ - 2x + 1",
+            "x sin(x)",
+            "exp(sin(x²))",
+            "sin(x) / (1 + x²)",
+            "tan(x)",
+            "ln(1 + x²)",
+            "sqrt(x)",
+            "x^x",
+            "2^x",
+            "-cos(3x)",
+            "(x - 1)^3 (x + 2)",
+            "1 / (1 + exp(-x))",
+        ];
+        let h = 1e-6;
+        for source in corpus {
+            let (f, df) = (parse(source).unwrap(), derivative(source));
+            for x in [0.3, 0.9, 1.7] {
+                let difference = (f.eval(x + h) - f.eval(x - h)) / (2.0 * h);
+                let exact = df.eval(x);
+                assert!((exact - difference).abs() < 1e-6 * exact.abs().max(1.0), "d/dx {} at {}: {}", source, x, df);
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Taylor series expansion
+//!
+//! [`TaylorSeries::of`] reads the coefficients `f⁽ⁿ⁾(c) / n!` of a
+//! [`SmoothFunction`] straight off a [`Jet`], which carries them in that
+//! form, up to [`JET_ORDER`]; past it they come from symbolic derivatives.
+//!
+//! A series of order `n` has its coefficients up to `(x - c)ⁿ` and knows
+//! nothing past them, so arithmetic keeps only what it knows: a sum,
+//! product or quotient has the lower order of the two, a composite the lower order of
+//! the two, a derivative one less and an integral one more. Series about
+//! different centers are not combined; [`TaylorSeries::recenter`] one of
+//! them first.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+#[cfg(feature = "wasm-bindings")]
+use wasm_bindgen::prelude::*;
+
+use crate::dual::Jet;
+use crate::expr::Expr;
+use crate::{SmoothFunction, JET_ORDER};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TaylorError {
+    #[error("A series about {left} and one about {right} do not combine; recenter one first")]
+    CenterMismatch { left: f64, right: f64 },
+
+    #[error("The divisor is 0 at {center}, so the quotient has no series about it")]
+    ZeroDivisor { center: f64 },
+}
+
+pub type Result<T> = std::result::Result<T, TaylorError>;
+
+/// `Σ aₙ (x - c)ⁿ`, with the coefficients `aₙ` in order from `a₀`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "wasm-bindings", wasm_bindgen)]
+pub struct TaylorSeries {
+    center: f64,
+    coefficients: Vec<f64>,
+    /// Printed by [`TaylorSeries::to_expression`]
+    variable: String,
+}
+
+#[cfg_attr(feature = "wasm-bindings", wasm_bindgen)]
+impl TaylorSeries {
+    #[cfg_attr(feature = "wasm-bindings", wasm_bindgen(constructor))]
+    pub fn new(center: f64) -> TaylorSeries {
+        TaylorSeries {
+            center,
+            coefficients: Vec::new(),
+            variable: "x".to_string(),
+        }
+    }
+
+    pub fn add_term(&mut self, coefficient: f64) {
+        self.coefficients.push(coefficient);
+    }
+
+    pub fn evaluate(&self, x: f64) -> f64 {
+        let dx = x - self.center;
+        let mut result = 0.0;
+        let mut power = 1.0;
+
+        for coeff in &self.coefficients {
+            result += coeff * power;
+            power *= dx;
+        }
+
+        result
+    }
+
+    pub fn truncate(&mut self, order: usize) {
+        self.coefficients.truncate(order + 1);
+    }
+
+    /// The polynomial in `x - c`, simplified, in the syntax
+    /// [`SmoothFunction::try_new`] reads; zero terms are left out
+    pub fn to_expression(&self) -> String {
+        let shift = Expr::Sub(Box::new(Expr::Var(self.variable.clone())), Box::new(Expr::Num(self.center)));
+        let mut polynomial: Option<Expr> = None;
+        for (n, &coefficient) in self.coefficients.iter().enumerate().filter(|(_, c)| **c != 0.0) {
+            let power = Expr::Pow(Box::new(shift.clone()), Box::new(Expr::Num(n as f64)));
+            let term = |a: f64| Box::new(Expr::Mul(Box::new(Expr::Num(a)), Box::new(power.clone())));
+            polynomial = Some(match polynomial {
+                None => *term(coefficient),
+                Some(sum) if coefficient > 0.0 => Expr::Add(Box::new(sum), term(coefficient)),
+                Some(sum) => Expr::Sub(Box::new(sum), term(-coefficient)),
+            });
+        }
+        polynomial.unwrap_or(Expr::Num(0.0)).simplify().to_string()
+    }
+}
+
+impl TaylorSeries {
+    /// The series of `f` about `center` up to `(x - center)^order`
+    pub fn of(f: &SmoothFunction, center: f64, order: usize) -> TaylorSeries {
+        let jet = f.ast().eval(Jet::<{ JET_ORDER + 1 }>::variable(center));
+        let mut coefficients: Vec<f64> = jet.coefficients.iter().copied().take(order + 1).collect();
+        if order > JET_ORDER {
+            let mut derivative = f.nth_derivative(JET_ORDER);
+            let mut factorial: f64 = (1..=JET_ORDER).map(|i| i as f64).product();
+            for n in JET_ORDER + 1..=order {
+                derivative = derivative.derivative();
+                factorial *= n as f64;
+                coefficients.push(derivative.evaluate(center) / factorial);
+            }
+        }
+        TaylorSeries { center, coefficients, variable: f.variable().to_string() }
+    }
+
+    pub fn center(&self) -> f64 {
+        self.center
+    }
+
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    /// The largest `|series - f|` at `samples + 1` evenly spaced points of
+    /// `[a, b]`, skipping any where `f` is undefined
+    pub fn max_error_on(&self, f: &SmoothFunction, (a, b): (f64, f64), samples: usize) -> f64 {
+        let samples = samples.max(1);
+        let x = |k: usize| a + (b - a) * k as f64 / samples as f64;
+        (0..=samples).map(|k| (self.evaluate(x(k)) - f.evaluate(x(k))).abs()).fold(0.0, f64::max)
+    }
+
+    /// The highest power with a coefficient; `None` for an empty series
+    pub fn order(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    /// The sum, to the lower of the two orders
+    pub fn add(&self, other: &TaylorSeries) -> Result<TaylorSeries> {
+        self.same_center(other)?;
+        let sum = self.coefficients.iter().zip(&other.coefficients).map(|(a, b)| a + b);
+        Ok(self.with(sum.collect()))
+    }
+
+    /// The Cauchy product, to the lower of the two orders
+    pub fn mul(&self, other: &TaylorSeries) -> Result<TaylorSeries> {
+        self.same_center(other)?;
+        let length = self.coefficients.len().min(other.coefficients.len());
+        Ok(self.with(cauchy(&self.coefficients, &other.coefficients, length)))
+    }
+
+    /// The quotient, to the lower of the two orders; `other` must not be 0
+    /// at the center
+    pub fn div(&self, other: &TaylorSeries) -> Result<TaylorSeries> {
+        self.same_center(other)?;
+        let length = self.coefficients.len().min(other.coefficients.len());
+        if length == 0 {
+            return Ok(self.with(Vec::new()));
+        }
+        let lead = other.coefficients[0];
+        if lead == 0.0 {
+            return Err(TaylorError::ZeroDivisor { center: self.center });
+        }
+        // Solve `self = quotient · other` one coefficient at a time
+        let mut quotient: Vec<f64> = Vec::with_capacity(length);
+        for k in 0..length {
+            let known: f64 = (1..=k).map(|i| other.coefficients[i] * quotient[k - i]).sum();
+            quotient.push((self.coefficients[k] - known) / lead);
+        }
+        Ok(self.with(quotient))
+    }
+
+    /// Every coefficient times `factor`, to the same order
+    pub fn scale(&self, factor: f64) -> TaylorSeries {
+        self.with(self.coefficients.iter().map(|a| a * factor).collect())
+    }
+
+    /// Term by term, to one order less
+    pub fn derivative(&self) -> TaylorSeries {
+        let derivative = self.coefficients.iter().enumerate().skip(1).map(|(n, a)| n as f64 * a);
+        self.with(derivative.collect())
+    }
+
+    /// Term by term, with constant term 0, to one order more
+    pub fn integrate(&self) -> TaylorSeries {
+        let integral = self.coefficients.iter().enumerate().map(|(n, a)| a / (n + 1) as f64);
+        self.with(std::iter::once(0.0).chain(integral).collect())
+    }
+
+    /// The same polynomial written about `center`, to the same order
+    ///
+    /// The polynomial is moved exactly, but as a series of the function it
+    /// came from it is only as good as the truncation was near `center`.
+    pub fn recenter(&self, center: f64) -> TaylorSeries {
+        let shift = center - self.center;
+        // Synthetic division by `x - shift`, once per coefficient
+        let mut coefficients = self.coefficients.clone();
+        for k in 0..coefficients.len() {
+            for n in (k..coefficients.len() - 1).rev() {
+                coefficients[n] += shift * coefficients[n + 1];
+            }
+        }
+        TaylorSeries { center, ..self.with(coefficients) }
+    }
+
+    /// `self ∘ inner`, about `inner`'s center, to the lower of the two
+    /// orders
+    ///
+    /// `self` is first [recentered](TaylorSeries::recenter) at `inner`'s
+    /// value there, unless it is already about exactly that point. The
+    /// comparison is exact on purpose: recentering only moves the
+    /// polynomial, so a shift by rounding error costs nothing.
+    pub fn compose(&self, inner: &TaylorSeries) -> TaylorSeries {
+        let length = self.coefficients.len().min(inner.coefficients.len());
+        let outer = match inner.coefficients.first() {
+            Some(&value) if value != self.center => self.recenter(value),
+            _ => self.clone(),
+        };
+        // Horner's rule in `inner - value`, which has no constant term
+        let mut shifted = inner.coefficients.clone();
+        if let Some(value) = shifted.first_mut() {
+            *value = 0.0;
+        }
+        let mut composite = Vec::new();
+        for &a in outer.coefficients[..length].iter().rev() {
+            composite = cauchy(&composite, &shifted, length);
+            composite.resize(length, 0.0);
+            composite[0] += a;
+        }
+        TaylorSeries { center: inner.center, ..self.with(composite) }
+    }
+
+    fn same_center(&self, other: &TaylorSeries) -> Result<()> {
+        if self.center == other.center {
+            Ok(())
+        } else {
+            Err(TaylorError::CenterMismatch { left: self.center, right: other.center })
+        }
+    }
+
+    /// A series about the same center in the same variable
+    fn with(&self, coefficients: Vec<f64>) -> TaylorSeries {
+        TaylorSeries { center: self.center, coefficients, variable: self.variable.clone() }
+    }
+}
+
+/// The first `length` coefficients of the product of `a` and `b`
+fn cauchy(a: &[f64], b: &[f64], length: usize) -> Vec<f64> {
+    (0..length)
+        .map(|k| (0..=k).filter_map(|i| Some(a.get(i)? * b.get(k - i)?)).sum())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(expression: &str) -> SmoothFunction {
+        SmoothFunction::try_new(expression.to_string(), "x".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_series_of_sin() {
+        let sin = function("sin(x)");
+        let series = TaylorSeries::of(&sin, 0.0, 5);
+        let expected = [0.0, 1.0, 0.0, -1.0 / 6.0, 0.0, 1.0 / 120.0];
+        assert_eq!(series.coefficients().len(), expected.len());
+        for (coefficient, expected) in series.coefficients().iter().zip(expected) {
+            assert!((coefficient - expected).abs() < 1e-15, "{} vs {}", coefficient, expected);
+        }
+        assert!(series.max_error_on(&sin, (-1.0, 1.0), 200) < 1e-3);
+        // Past the jet, symbolically
+        let long = TaylorSeries::of(&sin, 0.0, JET_ORDER + 3);
+        assert!((long.coefficients()[JET_ORDER + 3] + 1.0 / 1_307_674_368_000.0).abs() < 1e-25);
+        assert!(long.max_error_on(&sin, (-1.0, 1.0), 200) < 1e-13);
+    }
+
+    #[test]
+    fn test_series_print_as_expressions() {
+        let square = TaylorSeries::of(&function("x²"), 1.0, 4);
+        assert_eq!(square.to_expression(), "1 + 2 * (x - 1) + (x - 1)^2");
+        let reread = function(&square.to_expression());
+        assert!(square.max_error_on(&reread, (-3.0, 3.0), 10) < 1e-12);
+
+        let mut by_hand = TaylorSeries::new(-1.0);
+        by_hand.add_term(0.0);
+        by_hand.add_term(-3.0);
+        assert_eq!(by_hand.to_expression(), "-3 * (x + 1)");
+        assert_eq!(TaylorSeries::new(0.0).to_expression(), "0");
+    }
+
+    fn assert_close(series: &TaylorSeries, expected: &TaylorSeries) {
+        assert_eq!(series.center(), expected.center());
+        assert_eq!(series.order(), expected.order());
+        for (a, b) in series.coefficients().iter().zip(expected.coefficients()) {
+            assert!((a - b).abs() < 1e-14, "{:?} vs {:?}", series.coefficients(), expected.coefficients());
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_keeps_the_lower_order() {
+        let (sin, cos) = (TaylorSeries::of(&function("sin(x)"), 0.0, 9), TaylorSeries::of(&function("cos(x)"), 0.0, 7));
+        assert_close(&sin.add(&cos).unwrap(), &TaylorSeries::of(&function("sin(x) + cos(x)"), 0.0, 7));
+        assert_close(&sin.mul(&cos).unwrap(), &TaylorSeries::of(&function("sin(x) cos(x)"), 0.0, 7));
+        assert_close(&sin.scale(-2.0), &TaylorSeries::of(&function("-2 sin(x)"), 0.0, 9));
+        assert_close(&sin.derivative(), &TaylorSeries::of(&function("cos(x)"), 0.0, 8));
+        assert_close(&cos.integrate(), &TaylorSeries::of(&function("sin(x)"), 0.0, 8));
+        assert_eq!(TaylorSeries::new(0.0).derivative().order(), None);
+    }
+
+    #[test]
+    fn test_division() {
+        let (sin, cos) = (TaylorSeries::of(&function("sin(x)"), 0.0, 9), TaylorSeries::of(&function("cos(x)"), 0.0, 7));
+        assert_close(&sin.div(&cos).unwrap(), &TaylorSeries::of(&function("tan(x)"), 0.0, 7));
+        let about_one = |expression| TaylorSeries::of(&function(expression), 1.0, 6);
+        assert_close(&about_one("1").div(&about_one("x²")).unwrap(), &about_one("1 / x²"));
+        assert_eq!(cos.div(&sin), Err(TaylorError::ZeroDivisor { center: 0.0 }));
+        assert_eq!(cos.div(&TaylorSeries::new(0.0)).unwrap().order(), None);
+    }
+
+    #[test]
+    fn test_different_centers_do_not_combine() {
+        let square = |center| TaylorSeries::of(&function("x²"), center, 2);
+        assert_eq!(square(0.0).add(&square(1.0)), Err(TaylorError::CenterMismatch { left: 0.0, right: 1.0 }));
+        assert_eq!(square(2.0).mul(&square(0.0)), Err(TaylorError::CenterMismatch { left: 2.0, right: 0.0 }));
+        // A polynomial moves exactly
+        assert_close(&square(0.0).recenter(1.0), &square(1.0));
+        assert!(square(0.0).recenter(1.0).add(&square(1.0)).is_ok());
+    }
+
+    #[test]
+    fn test_composition() {
+        let order = 11;
+        let sin = TaylorSeries::of(&function("sin(x)"), 0.0, order);
+        let square = TaylorSeries::of(&function("x²"), 0.0, order);
+        assert_close(&sin.compose(&square), &TaylorSeries::of(&function("sin(x²)"), 0.0, order));
+        // `1 + x` is 1 at 0, so `x²` is recentered there first
+        let shifted = TaylorSeries::of(&function("1 + x"), 0.0, 4);
+        assert_close(&square.compose(&shifted), &TaylorSeries::of(&function("(1 + x)²"), 0.0, 4));
+        // About another center, where the outer series is already about `cos(0.25)`
+        let cos = TaylorSeries::of(&function("cos(x)"), 0.25, 6);
+        let exp = TaylorSeries::of(&function("exp(x)"), 0.25f64.cos(), 6);
+        assert_close(&exp.compose(&cos), &TaylorSeries::of(&function("exp(cos(x))"), 0.25, 6));
+    }
+}
@@ -0,0 +1,162 @@
+//! `#[wasm_bindgen]` exports, compiled only with the `wasm-bindings` feature
+
+use wasm_bindgen::prelude::*;
+
+use crate::geodesic::BezierPath;
+use crate::sampling;
+use crate::{SmoothFunction, TaylorSeries};
+
+/// Hand a JSON result to JS, surfacing serializer failures as exceptions
+fn to_js(value: serde_json::Value) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// `expr` as a function of `x`; one that does not read is thrown
+fn function_of_x(expr: &str) -> Result<SmoothFunction, JsValue> {
+    SmoothFunction::try_new(expr.to_string(), "x".to_string()).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// WASM bindings
+#[wasm_bindgen]
+pub struct WasmSmoothFunction {
+    inner: SmoothFunction,
+}
+
+#[wasm_bindgen]
+impl WasmSmoothFunction {
+    /// Throws when `expression` does not read as a function of `variable`
+    #[wasm_bindgen(constructor)]
+    pub fn new(expression: String, variable: String) -> Result<WasmSmoothFunction, JsValue> {
+        let inner = SmoothFunction::try_new(expression, variable).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmSmoothFunction { inner })
+    }
+
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.inner.evaluate(x)
+    }
+
+    pub fn derivative(&self) -> WasmSmoothFunction {
+        WasmSmoothFunction {
+            inner: self.inner.derivative(),
+        }
+    }
+
+    pub fn nth_derivative(&self, n: usize) -> WasmSmoothFunction {
+        WasmSmoothFunction {
+            inner: self.inner.nth_derivative(n),
+        }
+    }
+
+    /// A symbolic antiderivative, or null past polynomials, `sin`, `cos`,
+    /// `exp`, and sums and constant multiples of them
+    pub fn antiderivative(&self) -> JsValue {
+        match self.inner.antiderivative() {
+            Some(inner) => WasmSmoothFunction { inner }.into(),
+            None => JsValue::NULL,
+        }
+    }
+
+    /// The derivative at `x` by dual numbers, without building it
+    pub fn derivative_at(&self, x: f64) -> f64 {
+        self.inner.derivative_at(x)
+    }
+
+    /// `[[x, ∫ₐˣ]]` at `n + 1` evenly spaced `x` from `a` to `b`; an
+    /// integrand that is not finite on the way is thrown
+    pub fn antiderivative_values(&self, a: f64, b: f64, n: usize) -> Result<JsValue, JsValue> {
+        let values = self.inner.antiderivative_values(a, b, n).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&values).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Plot points on `[start, end]`, dense only where the curve bends
+    ///
+    /// Returns `{ points: [{x, y, refined}], excluded: [[from, to]], truncated }`;
+    /// break the drawn path across each excluded interval.
+    pub fn sample_adaptive(&self, start: f64, end: f64, tol: f64, max_points: usize) -> Result<JsValue, JsValue> {
+        let samples = sampling::sample_adaptive(|x| self.inner.evaluate(x), start, end, tol, max_points);
+        to_js(serde_json::to_value(samples).map_err(|e| JsValue::from_str(&e.to_string()))?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn expression(&self) -> String {
+        self.inner.expression().to_string()
+    }
+}
+
+// Global exported functions for direct WASM use
+#[wasm_bindgen]
+pub fn evaluate(expr: &str, x: f64) -> Result<f64, JsValue> {
+    Ok(function_of_x(expr)?.evaluate(x))
+}
+
+#[wasm_bindgen]
+pub fn derivative(expr: &str, x: f64, order: usize) -> Result<f64, JsValue> {
+    Ok(function_of_x(expr)?.nth_derivative(order).evaluate(x))
+}
+
+/// `∫ₐᵇ expr dx`; see [`SmoothFunction::integrate`]
+#[wasm_bindgen]
+pub fn definite_integral(expr: &str, a: f64, b: f64) -> Result<f64, JsValue> {
+    function_of_x(expr)?.integrate(a, b).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// The series of `expr` about `center` to `order`, to plot against it;
+/// see [`TaylorSeries::of`]
+#[wasm_bindgen]
+pub fn taylor(expr: &str, center: f64, order: usize) -> Result<TaylorSeries, JsValue> {
+    Ok(TaylorSeries::of(&function_of_x(expr)?, center, order))
+}
+
+// Chain rule demonstration
+#[wasm_bindgen]
+pub fn chain_rule(f_expr: &str, g_expr: &str, x: f64) -> Result<JsValue, JsValue> {
+    let f = function_of_x(f_expr)?;
+    let g = function_of_x(g_expr)?;
+
+    let gx = g.evaluate(x);
+    let f_prime_gx = f.derivative().evaluate(gx);
+    let g_prime_x = g.derivative().evaluate(x);
+    let result = f_prime_gx * g_prime_x;
+
+    to_js(serde_json::json!({
+        "f": f_expr,
+        "g": g_expr,
+        "x": x,
+        "g(x)": gx,
+        "f'(g(x))": f_prime_gx,
+        "g'(x)": g_prime_x,
+        "result": result,
+        "formula": format!("(f∘g)'({}) = f'(g({})) · g'({}) = {} · {} = {}", 
+                          x, x, x, f_prime_gx, g_prime_x, result)
+    }))
+}
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+/// Frames of a geodesic straightening run, for animation
+///
+/// `control` is a flat `[x0, y0, x1, y1, ...]` list of Bézier control points.
+/// Each frame carries the path's energy and `samples + 1` points along it.
+#[wasm_bindgen]
+pub fn geodesic_frames(control: Vec<f64>, rate: f64, steps: usize, samples: usize) -> Result<JsValue, JsValue> {
+    if control.len() < 4 || !control.len().is_multiple_of(2) {
+        return Ok(JsValue::NULL);
+    }
+    let mut points: Vec<_> = control.chunks(2).map(|p| [p[0], p[1]]).collect();
+    let end = points.pop().unwrap_or_default();
+    let start = points.remove(0);
+    let path = BezierPath::new(start, points, end);
+    let frames: Vec<_> = path.straighten(rate)
+        .take(steps + 1)
+        .map(|frame| serde_json::json!({
+            "energy": frame.energy(),
+            "points": frame.sample(samples),
+        }))
+        .collect();
+
+    serde_wasm_bindgen::to_value(&frames).map_err(|e| JsValue::from_str(&e.to_string()))
+}
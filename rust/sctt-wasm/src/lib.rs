@@ -1,5 +1,4 @@
 use wasm_bindgen::prelude::*;
-use serde::{Deserialize, Serialize};
 
 #[wasm_bindgen]
 pub struct SCTTEngine {
@@ -15,6 +14,8 @@ impl SCTTEngine {
         }
     }
 
+    /// The checker's [`CheckResponse`](sctt_checker::CheckResponse) as
+    /// JSON, `{ success, type, message, diagnostics, details }`
     #[wasm_bindgen]
     pub fn typecheck(&self, code: &str) -> String {
         let result = self.checker.check(code);
@@ -0,0 +1,31 @@
+//! The engine's typecheck against the checker facade behind it
+
+use sctt_checker::CheckResponse;
+use sctt_wasm::SCTTEngine;
+
+fn typecheck(code: &str) -> CheckResponse {
+    serde_json::from_str(&SCTTEngine::new().typecheck(code)).unwrap()
+}
+
+#[test]
+fn test_typecheck_reports_the_type() {
+    let report = typecheck("λx. sin(x)");
+    assert!(report.success);
+    assert_eq!(report.ty.as_deref(), Some("C∞(ℝ → ℝ)"));
+    assert!(report.diagnostics.is_empty());
+}
+
+#[test]
+fn test_typecheck_failures_carry_diagnostics() {
+    let report = typecheck("");
+    assert!(!report.success && report.ty.is_none());
+    assert_eq!(report.diagnostics.len(), 1);
+}
+
+#[test]
+fn test_typecheck_json_shape() {
+    let json: serde_json::Value = serde_json::from_str(&SCTTEngine::new().typecheck("⟨i⟩ 2 * i")).unwrap();
+    assert_eq!(json["success"], true);
+    assert_eq!(json["type"], "Path ℝ");
+    assert!(json["diagnostics"].as_array().unwrap().is_empty());
+}
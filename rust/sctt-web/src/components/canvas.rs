@@ -0,0 +1,12 @@
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+/// The 2D drawing context of a canvas, or why it is unavailable
+pub fn context_2d(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d, String> {
+    canvas
+        .get_context("2d")
+        .map_err(|e| format!("canvas refused a 2d context: {:?}", e))?
+        .ok_or_else(|| "canvas has no 2d context".to_string())?
+        .dyn_into::<CanvasRenderingContext2d>()
+        .map_err(|_| "2d context is not a CanvasRenderingContext2d".to_string())
+}
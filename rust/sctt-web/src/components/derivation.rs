@@ -0,0 +1,91 @@
+use leptos::*;
+use sctt_core::simp::{self, Derivation};
+
+/// Which engine produces a derivation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deriver {
+    Interval,
+    Simp,
+}
+
+impl Deriver {
+    pub fn derive(self, source: &str) -> Result<Derivation, simp::ParseError> {
+        match self {
+            Deriver::Interval => simp::derive_interval(source),
+            Deriver::Simp => simp::derive_simp(source),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// An equational derivation as a three-column table: relation, expression,
+/// and the law justifying the line, linked to its reference entry
+pub fn derivation_html(derivation: &Derivation) -> String {
+    let mut html = String::from("<table class=\"derivation\">");
+    html.push_str(&format!(
+        "<tr><td class=\"relation\"></td><td class=\"expression\"><code>{}</code></td><td class=\"justification\"></td></tr>",
+        escape_html(&derivation.start)
+    ));
+    for step in &derivation.steps {
+        html.push_str(&format!(
+            "<tr><td class=\"relation\">=</td><td class=\"expression\"><code>{}</code></td>\
+             <td class=\"justification\"><a href=\"/reference#{}\">{}</a></td></tr>",
+            escape_html(&step.after),
+            step.rule.reference(),
+            escape_html(step.rule.tag())
+        ));
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// Derives `source` when rendered, so the chain always matches the engine
+#[component]
+pub fn LiveDerivation(#[prop(into)] source: String, deriver: Deriver) -> impl IntoView {
+    match deriver.derive(&source) {
+        Ok(derivation) => view! { <div class="derivation-view" inner_html=derivation_html(&derivation)/> }.into_view(),
+        Err(e) => view! { <p class="derivation-error">{format!("Cannot derive `{}`: {}", source, e)}</p> }.into_view(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_de_morgan_lesson_derivation() {
+        let html = derivation_html(&Deriver::Interval.derive("¬(i ∧ j)").unwrap());
+        assert_eq!(html.matches("/reference#de-morgan-laws").count(), 1);
+        assert!(html.contains("<code>¬i ∨ ¬j</code>"));
+    }
+
+    #[test]
+    fn test_expressions_are_escaped() {
+        let derivation = Derivation {
+            start: "a < b && <script>".to_string(),
+            steps: vec![simp::Step {
+                rule: simp::Rule::Unit,
+                before: "a < b && <script>".to_string(),
+                after: "\"x\" > 'y'".to_string(),
+            }],
+        };
+        let html = derivation_html(&derivation);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("a &lt; b &amp;&amp; &lt;script&gt;"));
+        assert!(html.contains("&quot;x&quot; &gt; &#39;y&#39;"));
+    }
+}
@@ -0,0 +1,136 @@
+use leptos::*;
+use leptos::html::Canvas;
+use web_sys::HtmlCanvasElement;
+use super::canvas::context_2d;
+use sctt_smooth::geodesic::BezierPath;
+use std::time::Duration;
+
+const RATE: f64 = 0.05;
+const FRAMES: usize = 120;
+
+fn initial_path() -> BezierPath {
+    BezierPath::new([0.0, 0.0], vec![[0.2, 1.5], [1.1, -1.0]], [1.0, 0.0])
+}
+
+#[component]
+pub fn GeodesicDemo() -> impl IntoView {
+    let canvas_ref = create_node_ref::<Canvas>();
+    let (path, set_path) = create_signal(initial_path());
+    let (frame, set_frame) = create_signal(0usize);
+    let (running, set_running) = create_signal(false);
+
+    // Draw the current path, its control polygon and the fixed endpoints
+    let draw_path = move |path: &BezierPath| {
+        if let Some(canvas) = canvas_ref.get() {
+            let canvas_element: HtmlCanvasElement = canvas.into();
+            let ctx = match context_2d(&canvas_element) {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    logging::error!("{}", e);
+                    return;
+                }
+            };
+
+            let width = canvas_element.width() as f64;
+            let height = canvas_element.height() as f64;
+            // The unit interval on the x-axis, [-2, 2] on the y-axis
+            let to_px = |p: [f64; 2]| (width * (0.1 + 0.8 * p[0]), height / 2.0 - p[1] * height / 4.0);
+
+            ctx.clear_rect(0.0, 0.0, width, height);
+
+            // Control polygon
+            ctx.set_stroke_style(&"#cbd5e1".into());
+            ctx.set_line_width(1.0);
+            ctx.begin_path();
+            for (i, p) in path.control.iter().enumerate() {
+                let (x, y) = to_px(*p);
+                if i == 0 { ctx.move_to(x, y) } else { ctx.line_to(x, y) }
+            }
+            ctx.stroke();
+
+            // The path itself
+            ctx.set_stroke_style(&"#6366f1".into());
+            ctx.set_line_width(2.0);
+            ctx.begin_path();
+            for (i, p) in path.sample(100).into_iter().enumerate() {
+                let (x, y) = to_px(p);
+                if i == 0 { ctx.move_to(x, y) } else { ctx.line_to(x, y) }
+            }
+            ctx.stroke();
+
+            // Endpoints stay put
+            ctx.set_fill_style(&"#0f172a".into());
+            for p in [path.start(), path.end()] {
+                let (x, y) = to_px(p);
+                ctx.fill_rect(x - 3.0, y - 3.0, 6.0, 6.0);
+            }
+        }
+    };
+
+    create_effect(move |_| {
+        let current = path.get();
+        request_animation_frame(move || draw_path(&current));
+    });
+
+    let start = move |_| {
+        if running.get() {
+            return;
+        }
+        set_running.set(true);
+        let mut frames = path.get().straighten(RATE).skip(1).take(FRAMES);
+        let handle = store_value(None::<IntervalHandle>);
+        handle.set_value(set_interval_with_handle(
+            move || match frames.next() {
+                Some(next) => {
+                    set_path.set(next);
+                    set_frame.update(|f| *f += 1);
+                }
+                None => {
+                    set_running.set(false);
+                    if let Some(h) = handle.get_value() {
+                        h.clear();
+                    }
+                }
+            },
+            Duration::from_millis(30),
+        ).ok());
+    };
+
+    let reset = move |_| {
+        if !running.get() {
+            set_path.set(initial_path());
+            set_frame.set(0);
+        }
+    };
+
+    view! {
+        <div class="geodesic-demo">
+            <h3>"Straightening a Path"</h3>
+            <p>
+                "In SCTT a path is a first-class object: a smooth map from the interval "
+                "into a space, with fixed endpoints. Because paths are values, we can ask "
+                "questions about them as a whole — here, how much energy E(γ) = ∫₀¹ |γ'(t)|² dt "
+                "a path spends getting from one end to the other."
+            </p>
+            <p>
+                "The energy is a differentiable functional of the path, computed by forward-mode "
+                "automatic differentiation through its control points. Gradient descent with the "
+                "endpoints held fixed deforms the path — each frame is a homotopy step — until it "
+                "reaches the geodesic: the straight line at constant speed."
+            </p>
+            <div class="controls">
+                <button on:click=start disabled=move || running.get()>"Straighten"</button>
+                <button on:click=reset disabled=move || running.get()>"Reset"</button>
+                <span class="energy">
+                    {move || format!("frame {} · E(γ) = {:.4}", frame.get(), path.get().energy())}
+                </span>
+            </div>
+            <canvas
+                ref=canvas_ref
+                width="600"
+                height="400"
+                class="visualization-canvas"
+            />
+        </div>
+    }
+}
@@ -2,8 +2,18 @@ pub mod playground;
 pub mod type_checker;
 pub mod smooth_visualizer;
 pub mod path_viewer;
+pub mod geodesic_demo;
+pub mod surface_plot;
+pub mod derivation;
+pub mod canvas;
+pub mod smoothness_explainer;
 
 pub use playground::*;
 pub use type_checker::*;
 pub use smooth_visualizer::*;
-pub use path_viewer::*;
\ No newline at end of file
+pub use path_viewer::*;
+pub use geodesic_demo::*;
+pub use surface_plot::*;
+pub use derivation::*;
+pub use canvas::*;
+pub use smoothness_explainer::*;
\ No newline at end of file
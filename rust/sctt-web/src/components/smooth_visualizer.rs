@@ -1,30 +1,159 @@
 use leptos::*;
 use leptos::html::Canvas;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use super::canvas::context_2d;
+use sctt_smooth::dual::{Dual, Scalar};
+use sctt_smooth::hover::{anchor_card, hover_graph, HoverInfo, HoverTarget, NumberFormat};
+use sctt_smooth::sampling::sample_adaptive;
 use std::f64::consts::PI;
 
+/// Half-width of the tangent segment, in x units
+const TANGENT_REACH: f64 = 0.75;
+/// Rough advance of one card character at the card font
+const CARD_CHAR_WIDTH: f64 = 7.0;
+const CARD_LINE_HEIGHT: f64 = 16.0;
+const CARD_PADDING: f64 = 8.0;
+const CARD_GAP: f64 = 14.0;
+/// Cap on samples per plotted curve
+const MAX_PLOT_POINTS: usize = 2_000;
+
+/// The plotted function, written once over dual numbers so the curve, its
+/// derivative and the hover readout all come from the same definition
+fn plotted(name: &str) -> fn(Dual) -> Dual {
+    match name {
+        "sin" => |x| x.sin(),
+        "cos" => |x| x.cos(),
+        "sin(x²)" => |x| (x * x).sin(),
+        "exp" => |x| x.exp() * Dual::constant(0.1),
+        _ => |x| x * Dual::constant(0.5),
+    }
+}
+
+/// Maps between canvas pixels and data coordinates: x spans ±2π, y spans ±2
+#[derive(Clone, Copy)]
+struct View {
+    width: f64,
+    height: f64,
+}
+
+impl View {
+    fn data_x(&self, px: f64) -> f64 {
+        (px - self.width / 2.0) * 4.0 * PI / self.width
+    }
+
+    /// Half a pixel of y, the chord error the sampler may leave
+    fn tolerance(&self) -> f64 {
+        2.0 / self.height
+    }
+
+    fn pixel(&self, [x, y]: [f64; 2]) -> (f64, f64) {
+        (x * self.width / (4.0 * PI) + self.width / 2.0, self.height / 2.0 - y * self.height / 4.0)
+    }
+}
+
+/// Adaptive samples drawn as polylines, broken wherever `y` is undefined
+fn plot_curve(ctx: &CanvasRenderingContext2d, view: View, y: impl Fn(f64) -> f64) {
+    let samples = sample_adaptive(y, view.data_x(0.0), view.data_x(view.width), view.tolerance(), MAX_PLOT_POINTS);
+    ctx.begin_path();
+    for segment in samples.segments() {
+        for (i, point) in segment.iter().enumerate() {
+            let (px, py) = view.pixel([point.x, point.y]);
+            if i == 0 {
+                ctx.move_to(px, py);
+            } else {
+                ctx.line_to(px, py);
+            }
+        }
+    }
+    ctx.stroke();
+}
+
+/// Tangent segment, marker, and an info card that keeps clear of the cursor
+fn draw_hover(ctx: &CanvasRenderingContext2d, view: View, info: &HoverInfo, cursor: (f64, f64)) {
+    let (ax, ay) = view.pixel(info.tangent[0]);
+    let (bx, by) = view.pixel(info.tangent[1]);
+    ctx.set_stroke_style(&"#f59e0b".into());
+    ctx.set_line_width(2.0);
+    ctx.begin_path();
+    ctx.move_to(ax, ay);
+    ctx.line_to(bx, by);
+    ctx.stroke();
+
+    let (px, py) = view.pixel(info.point);
+    ctx.set_fill_style(&"#f59e0b".into());
+    ctx.begin_path();
+    let _ = ctx.arc(px, py, 4.0, 0.0, 2.0 * PI);
+    ctx.fill();
+
+    let rows: Vec<String> = info.lines.iter().map(|(label, value)| format!("{} = {}", label, value)).collect();
+    let longest = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    let card = [
+        longest as f64 * CARD_CHAR_WIDTH + 2.0 * CARD_PADDING,
+        rows.len() as f64 * CARD_LINE_HEIGHT + 2.0 * CARD_PADDING,
+    ];
+    let [left, top] = anchor_card([cursor.0, cursor.1], card, [view.width, view.height], CARD_GAP);
+
+    ctx.set_fill_style(&"rgba(15, 23, 42, 0.9)".into());
+    ctx.fill_rect(left, top, card[0], card[1]);
+    ctx.set_fill_style(&"#f8fafc".into());
+    ctx.set_font("12px monospace");
+    for (i, row) in rows.iter().enumerate() {
+        let baseline = top + CARD_PADDING + (i as f64 + 0.75) * CARD_LINE_HEIGHT;
+        let _ = ctx.fill_text(row, left + CARD_PADDING, baseline);
+    }
+}
+
 #[component]
 pub fn SmoothVisualizer() -> impl IntoView {
     let canvas_ref = create_node_ref::<Canvas>();
     let (function_type, set_function_type) = create_signal("sin".to_string());
-    
+    let (show_derivative, set_show_derivative) = create_signal(false);
+    let (cursor, set_cursor) = create_signal(None::<(f64, f64)>);
+    let (shift_held, set_shift_held) = create_signal(false);
+
+    // Holding Shift reads the derivative's curve instead of the function's
+    let keydown = window_event_listener(ev::keydown, move |e| {
+        if e.key() == "Shift" {
+            set_shift_held.set(true);
+        }
+    });
+    let keyup = window_event_listener(ev::keyup, move |e| {
+        if e.key() == "Shift" {
+            set_shift_held.set(false);
+        }
+    });
+    on_cleanup(move || {
+        keydown.remove();
+        keyup.remove();
+    });
+
+    let hover_target = move || {
+        if show_derivative.get() && shift_held.get() {
+            HoverTarget::Derivative
+        } else {
+            HoverTarget::Function
+        }
+    };
+
     // Draw function on canvas
     let draw_function = move || {
         if let Some(canvas) = canvas_ref.get() {
             let canvas_element: HtmlCanvasElement = canvas.into();
-            let ctx = canvas_element
-                .get_context("2d")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<CanvasRenderingContext2d>()
-                .unwrap();
-            
+            let ctx = match context_2d(&canvas_element) {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    logging::error!("{}", e);
+                    return;
+                }
+            };
+
             let width = canvas_element.width() as f64;
             let height = canvas_element.height() as f64;
-            
+            let view = View { width, height };
+
             // Clear canvas
             ctx.clear_rect(0.0, 0.0, width, height);
-            
+
             // Draw axes
             ctx.set_stroke_style(&"#64748b".into());
             ctx.set_line_width(1.0);
@@ -34,41 +163,32 @@ pub fn SmoothVisualizer() -> impl IntoView {
             ctx.move_to(width / 2.0, 0.0);
             ctx.line_to(width / 2.0, height);
             ctx.stroke();
-            
+
             // Draw function
+            let f = plotted(&function_type.get_untracked());
             ctx.set_stroke_style(&"#6366f1".into());
             ctx.set_line_width(2.0);
-            ctx.begin_path();
-            
-            let func = function_type.get();
-            let scale = 4.0; // x range from -2π to 2π
-            
-            for px in 0..(width as i32) {
-                let x = (px as f64 - width / 2.0) * scale * PI / width;
-                let y = match func.as_str() {
-                    "sin" => x.sin(),
-                    "cos" => x.cos(),
-                    "sin(x²)" => (x * x).sin(),
-                    "exp" => x.exp().min(10.0).max(-10.0) / 10.0,
-                    _ => x / 2.0,
-                };
-                
-                let py = height / 2.0 - y * height / 4.0;
-                
-                if px == 0 {
-                    ctx.move_to(px as f64, py);
-                } else {
-                    ctx.line_to(px as f64, py);
-                }
+            plot_curve(&ctx, view, |x| f(Dual::variable(x)).value);
+
+            if show_derivative.get_untracked() {
+                ctx.set_stroke_style(&"#10b981".into());
+                ctx.set_line_width(1.5);
+                plot_curve(&ctx, view, |x| f(Dual::variable(x)).deriv);
+            }
+
+            if let Some((cx, cy)) = cursor.get_untracked() {
+                let info = hover_graph(f, view.data_x(cx), hover_target(), TANGENT_REACH, &NumberFormat::default());
+                draw_hover(&ctx, view, &info, (cx, cy));
             }
-            
-            ctx.stroke();
         }
     };
-    
-    // Draw when component mounts and when function changes
+
+    // Redraw on mount and whenever the function, overlays or hover change
     create_effect(move |_| {
-        function_type.get();
+        function_type.track();
+        show_derivative.track();
+        cursor.track();
+        shift_held.track();
         request_animation_frame(move || draw_function());
     });
 
@@ -84,13 +204,28 @@ pub fn SmoothVisualizer() -> impl IntoView {
                     <option value="sin(x²)">"sin(x²)"</option>
                     <option value="exp">"exp(x)"</option>
                 </select>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=show_derivative
+                        on:change=move |_| set_show_derivative.update(|d| *d = !*d)
+                    />
+                    " Show f′"
+                </label>
+                <span class="hint">
+                    {move || if show_derivative.get() { "Hold Shift to read f′ instead of f" } else { "" }}
+                </span>
             </div>
             <canvas
                 ref=canvas_ref
                 width="600"
                 height="400"
                 class="visualization-canvas"
+                on:mousemove=move |ev: ev::MouseEvent| {
+                    set_cursor.set(Some((ev.offset_x() as f64, ev.offset_y() as f64)));
+                }
+                on:mouseleave=move |_| set_cursor.set(None)
             />
         </div>
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,132 @@
+use leptos::*;
+use leptos::html::Canvas;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use super::canvas::context_2d;
+use sctt_checker::explain::{explain_smoothness, Evidence, Span};
+
+/// Pixels kept clear around the zoomed plot
+const PLOT_MARGIN: f64 = 12.0;
+
+/// The source around `span` as (before, inside, after); spans count characters
+fn split_at_span(source: &str, span: Span) -> (String, String, String) {
+    let before = source.chars().take(span.start).collect();
+    let inside = source.chars().skip(span.start).take(span.end.saturating_sub(span.start)).collect();
+    let after = source.chars().skip(span.end).collect();
+    (before, inside, after)
+}
+
+/// The function and its derivative around the bend, with the bend marked
+fn draw_evidence(ctx: &CanvasRenderingContext2d, width: f64, height: f64, evidence: &Evidence) {
+    ctx.clear_rect(0.0, 0.0, width, height);
+    let points = evidence.samples.iter().chain(&evidence.derivative);
+    let (x0, x1) = points.clone().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p[0]), hi.max(p[0])));
+    let (y0, y1) = points.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p[1]), hi.max(p[1])));
+    if x1 <= x0 {
+        return;
+    }
+    let (y0, y1) = if y1 > y0 { (y0, y1) } else { (y0 - 1.0, y1 + 1.0) };
+    let px = |x: f64| PLOT_MARGIN + (x - x0) / (x1 - x0) * (width - 2.0 * PLOT_MARGIN);
+    let py = |y: f64| height - PLOT_MARGIN - (y - y0) / (y1 - y0) * (height - 2.0 * PLOT_MARGIN);
+
+    ctx.set_stroke_style(&"#ef4444".into());
+    ctx.set_line_width(1.0);
+    ctx.begin_path();
+    ctx.move_to(px(evidence.at), 0.0);
+    ctx.line_to(px(evidence.at), height);
+    ctx.stroke();
+
+    for (curve, color) in [(&evidence.samples, "#6366f1"), (&evidence.derivative, "#10b981")] {
+        ctx.set_stroke_style(&color.into());
+        ctx.set_line_width(2.0);
+        ctx.begin_path();
+        for (i, [x, y]) in curve.iter().enumerate() {
+            if i == 0 {
+                ctx.move_to(px(*x), py(*y));
+            } else {
+                ctx.line_to(px(*x), py(*y));
+            }
+        }
+        ctx.stroke();
+    }
+}
+
+/// "Why is this not smooth?" for the code in the editor: each construct or
+/// join that bends, highlighted in the source, with the slopes on either
+/// side and a zoomed plot of the function (indigo) and its derivative (green)
+#[component]
+pub fn SmoothnessExplainer(code: ReadSignal<String>) -> impl IntoView {
+    let canvas_ref = create_node_ref::<Canvas>();
+    let report = create_memo(move |_| {
+        let code = code.get();
+        (!code.trim().is_empty()).then(|| explain_smoothness(&code))
+    });
+    let (selected, set_selected) = create_signal(0usize);
+
+    let issue = move || match report.get() {
+        Some(Ok(report)) => report.issues.get(selected.get()).cloned(),
+        _ => None,
+    };
+
+    create_effect(move |_| {
+        report.track();
+        set_selected.set(0);
+    });
+
+    create_effect(move |_| {
+        let evidence = issue().and_then(|issue| issue.evidence);
+        request_animation_frame(move || {
+            let (Some(canvas), Some(evidence)) = (canvas_ref.get_untracked(), evidence) else {
+                return;
+            };
+            let canvas: HtmlCanvasElement = canvas.into();
+            match context_2d(&canvas) {
+                Ok(ctx) => draw_evidence(&ctx, canvas.width() as f64, canvas.height() as f64, &evidence),
+                Err(e) => logging::error!("{}", e),
+            }
+        });
+    });
+
+    view! {
+        <div class="smoothness-panel">
+            <h3>"Smoothness"</h3>
+            {move || match report.get() {
+                None => ().into_view(),
+                // Not an expression or path the explainer reads
+                Some(Err(e)) => view! { <div class="smoothness-unavailable">{e.to_string()}</div> }.into_view(),
+                Some(Ok(report)) if report.smooth => view! {
+                    <div class="smoothness-ok">"✓ No issues: nothing here bends"</div>
+                }.into_view(),
+                Some(Ok(report)) => {
+                    let source = report.source.clone();
+                    report.issues.into_iter().enumerate().map(|(i, issue)| {
+                        let (before, inside, after) = split_at_span(&source, issue.span);
+                        let slopes = issue.evidence.as_ref().map(|e| format!(
+                            "at {:.4}: slope {:.4} on the left, {:.4} on the right", e.at, e.left_slope, e.right_slope
+                        ));
+                        view! {
+                            <div
+                                class=move || if selected.get() == i { "smoothness-issue selected" } else { "smoothness-issue" }
+                                on:click=move |_| set_selected.set(i)
+                            >
+                                <code class="smoothness-source">
+                                    {before}<mark class="not-smooth">{inside}</mark>{after}
+                                </code>
+                                <p class="smoothness-reason">{issue.reason}</p>
+                                <p class="smoothness-slopes">
+                                    {slopes.unwrap_or_else(|| "No bend in the probed range".to_string())}
+                                </p>
+                            </div>
+                        }
+                    }).collect_view()
+                }
+            }}
+            <canvas
+                ref=canvas_ref
+                class="smoothness-plot"
+                width="320"
+                height="160"
+                style:display=move || if issue().and_then(|i| i.evidence).is_some() { "block" } else { "none" }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,177 @@
+use leptos::*;
+use leptos::html::Canvas;
+use web_sys::HtmlCanvasElement;
+use super::canvas::context_2d;
+use sctt_smooth::surface::{height_color, Camera, ResolutionController, SurfaceGrid};
+
+/// Frame time budget in milliseconds before the grid gets coarser
+const FRAME_BUDGET_MS: f64 = 16.0;
+
+/// The saddle z = x² - y², the gallery's surface example
+pub fn saddle(x: f64, y: f64) -> f64 {
+    x * x - y * y
+}
+
+fn now_ms() -> f64 {
+    window().performance().map(|p| p.now()).unwrap_or(0.0)
+}
+
+#[component]
+pub fn SurfacePlot(
+    f: fn(f64, f64) -> f64,
+    #[prop(default = (-2.0, 2.0))] x_range: (f64, f64),
+    #[prop(default = (-2.0, 2.0))] y_range: (f64, f64),
+) -> impl IntoView {
+    let canvas_ref = create_node_ref::<Canvas>();
+    let (camera, set_camera) = create_signal(Camera::default());
+    let (wireframe, set_wireframe) = create_signal(false);
+    let (readout, set_readout) = create_signal(None::<[f64; 3]>);
+    let controller = store_value(ResolutionController::new(FRAME_BUDGET_MS, 8, 48));
+    let (resolution, set_resolution) = create_signal(controller.with_value(|c| c.resolution));
+    let drag = store_value(None::<(f64, f64)>);
+
+    let grid = create_memo(move |_| SurfaceGrid::sample(f, x_range, y_range, resolution.get()));
+
+    let draw = move || {
+        let Some(canvas) = canvas_ref.get() else { return };
+        let canvas_element: HtmlCanvasElement = canvas.into();
+        let ctx = match context_2d(&canvas_element) {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                logging::error!("{}", e);
+                return;
+            }
+        };
+
+        let started = now_ms();
+        let width = canvas_element.width() as f64;
+        let height = canvas_element.height() as f64;
+        let camera = camera.get_untracked();
+        ctx.clear_rect(0.0, 0.0, width, height);
+
+        // Painter's algorithm: quads arrive back to front
+        grid.with_untracked(|grid| {
+            for quad in grid.quads(&camera, width, height) {
+                let [r, g, b] = height_color(quad.level);
+                let color = format!("rgb({}, {}, {})", r, g, b);
+                ctx.begin_path();
+                ctx.move_to(quad.corners[0][0], quad.corners[0][1]);
+                for corner in &quad.corners[1..] {
+                    ctx.line_to(corner[0], corner[1]);
+                }
+                ctx.close_path();
+                ctx.set_fill_style(&color.as_str().into());
+                ctx.fill();
+                if wireframe.get_untracked() {
+                    ctx.set_stroke_style(&"rgba(15, 23, 42, 0.4)".into());
+                    ctx.set_line_width(0.5);
+                } else {
+                    // Stroke in the fill color to hide seams between quads
+                    ctx.set_stroke_style(&color.as_str().into());
+                    ctx.set_line_width(1.0);
+                }
+                ctx.stroke();
+            }
+        });
+
+        // Axis tripod in the lower-left corner
+        let origin = (40.0, height - 40.0);
+        for (axis, (label, color)) in camera.tripod().iter().zip([("x", "#dc2626"), ("y", "#16a34a"), ("z", "#2563eb")]) {
+            let end = (origin.0 + 25.0 * axis[0], origin.1 + 25.0 * axis[1]);
+            ctx.set_stroke_style(&color.into());
+            ctx.set_line_width(2.0);
+            ctx.begin_path();
+            ctx.move_to(origin.0, origin.1);
+            ctx.line_to(end.0, end.1);
+            ctx.stroke();
+            ctx.set_fill_style(&color.into());
+            let _ = ctx.fill_text(label, end.0 + 3.0, end.1 + 3.0);
+        }
+
+        // Degrade gracefully: a coarser grid when frames run over budget
+        let next = controller.try_update_value(|c| c.record(now_ms() - started)).unwrap_or_default();
+        if next != resolution.get_untracked() {
+            set_resolution.set(next);
+        }
+    };
+
+    create_effect(move |_| {
+        camera.track();
+        wireframe.track();
+        grid.track();
+        request_animation_frame(draw);
+    });
+
+    let on_mousedown = move |ev: ev::MouseEvent| {
+        drag.set_value(Some((ev.offset_x() as f64, ev.offset_y() as f64)));
+    };
+    let on_mouseup = move |_| drag.set_value(None);
+    let on_mousemove = move |ev: ev::MouseEvent| {
+        let (x, y) = (ev.offset_x() as f64, ev.offset_y() as f64);
+        match drag.get_value() {
+            Some((last_x, last_y)) => {
+                set_camera.update(|c| c.orbit(x - last_x, y - last_y));
+                drag.set_value(Some((x, y)));
+            }
+            None => {
+                let Some(canvas) = canvas_ref.get_untracked() else { return };
+                let (width, height) = (canvas.width() as f64, canvas.height() as f64);
+                let camera = camera.get_untracked();
+                set_readout.set(grid.with_untracked(|g| g.pick(&camera, width, height, x, y)));
+            }
+        }
+    };
+    let on_wheel = move |ev: ev::WheelEvent| {
+        ev.prevent_default();
+        set_camera.update(|c| c.zoom_by(-ev.delta_y().signum()));
+    };
+
+    view! {
+        <div class="surface-plot">
+            <div class="controls">
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=wireframe
+                        on:change=move |_| set_wireframe.update(|w| *w = !*w)
+                    />
+                    " Wireframe"
+                </label>
+                <button on:click=move |_| set_camera.set(Camera::default())>"Reset view"</button>
+                <span class="readout">
+                    {move || match readout.get() {
+                        Some([x, y, z]) => format!("f({:.3}, {:.3}) = {:.4}", x, y, z),
+                        None => String::new(),
+                    }}
+                </span>
+                <span class="resolution">{move || format!("{0}×{0} grid", resolution.get())}</span>
+            </div>
+            <canvas
+                ref=canvas_ref
+                width="600"
+                height="400"
+                class="visualization-canvas"
+                on:mousedown=on_mousedown
+                on:mouseup=on_mouseup
+                on:mouseleave=on_mouseup
+                on:mousemove=on_mousemove
+                on:wheel=on_wheel
+            />
+        </div>
+    }
+}
+
+#[component]
+pub fn SaddleDemo() -> impl IntoView {
+    view! {
+        <div class="saddle-demo">
+            <h3>"A Saddle Surface"</h3>
+            <p>
+                "z = x² - y² curves up along x and down along y, so the origin is a critical "
+                "point that is neither a minimum nor a maximum. Drag to orbit, scroll to zoom, "
+                "and hover to read off the value under the cursor."
+            </p>
+            <SurfacePlot f=saddle/>
+        </div>
+    }
+}
@@ -6,17 +6,22 @@ use wasm_bindgen::prelude::*;
 mod components;
 mod pages;
 mod state;
+mod storage;
 
 use components::*;
 use pages::*;
 use state::*;
+use storage::{BrowserStorage, ChunkedStore};
 
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
     
     // Global state for the app
-    provide_context(create_rw_signal(AppState::default()));
+    let mut store = ChunkedStore::new();
+    let saved = store.load(&BrowserStorage).ok().flatten();
+    provide_context(create_rw_signal(saved.unwrap_or_default()));
+    provide_context(store_value(store));
     
     view! {
         <Stylesheet id="leptos" href="/pkg/sctt-web.css"/>
@@ -173,9 +178,9 @@ fn ThemeToggle() -> impl IntoView {
         set_theme.set(new_theme);
         // Apply theme to document
         if let Some(doc) = document() {
-            let _ = doc.document_element()
-                .unwrap()
-                .set_attribute("data-theme", new_theme);
+            if let Some(root) = doc.document_element() {
+                let _ = root.set_attribute("data-theme", new_theme);
+            }
         }
     };
     
@@ -188,24 +193,21 @@ fn ThemeToggle() -> impl IntoView {
 
 #[component]
 fn SaveButton() -> impl IntoView {
-    let state = use_context::<RwSignal<AppState>>().unwrap();
+    let (Some(state), Some(store)) = (app_context::<RwSignal<AppState>>(), app_context::<StoredValue<ChunkedStore>>()) else {
+        return ().into_view();
+    };
+    let (notice, set_notice) = create_signal(None::<String>);
     
     let save = move |_| {
-        // Save current work to localStorage
-        if let Some(storage) = window().local_storage().ok().flatten() {
-            let _ = storage.set_item("sctt_work", &state.get().to_json());
-        }
-        // Show toast notification
-        spawn_local(async {
-            // Would show toast here
-        });
+        set_notice.set(save_work(state, store));
     };
     
     view! {
         <button class="save-button" on:click=save title="Save work (⌘S)">
             "💾"
         </button>
-    }
+        {move || notice.get().map(|n| view! { <span class="save-notice">{n}</span> })}
+    }.into_view()
 }
 
 #[component]
@@ -268,7 +270,9 @@ fn KeyboardShortcuts() -> impl IntoView {
     use web_sys::KeyboardEvent;
     
     let navigate = use_navigate();
-    let state = use_context::<RwSignal<AppState>>().unwrap();
+    let (Some(state), Some(store)) = (app_context::<RwSignal<AppState>>(), app_context::<StoredValue<ChunkedStore>>()) else {
+        return ().into_view();
+    };
     
     let handle_shortcut = move |e: KeyboardEvent| {
         let key = e.key();
@@ -286,9 +290,8 @@ fn KeyboardShortcuts() -> impl IntoView {
                 },
                 "s" | "S" => {
                     e.prevent_default();
-                    // Save work
-                    if let Some(storage) = window().local_storage().ok().flatten() {
-                        let _ = storage.set_item("sctt_work", &state.get().to_json());
+                    if let Some(notice) = save_work(state, store) {
+                        log::warn!("{}", notice);
                     }
                 },
                 "/" | "?" => {
@@ -302,16 +305,28 @@ fn KeyboardShortcuts() -> impl IntoView {
     
     view! {
         <div on:keydown=window_event_listener(keydown, handle_shortcut)/>
-    }
+    }.into_view()
 }
 
 // Helper functions
+
+/// Persist the app state, returning a notice if data had to be dropped
+fn save_work(state: RwSignal<AppState>, store: StoredValue<ChunkedStore>) -> Option<String> {
+    let result = store.try_update_value(|store| store.save(&state.get(), &mut BrowserStorage))?;
+    match result {
+        Ok(report) => report.notice(),
+        Err(e) => Some(e.to_string()),
+    }
+}
 fn document() -> Option<web_sys::Document> {
     web_sys::window()?.document()
 }
 
-fn window() -> web_sys::Window {
-    web_sys::window().expect("Window should exist")
+/// Context provided by [`App`]; components mounted outside it render nothing
+pub(crate) fn app_context<T: Clone + 'static>() -> Option<T> {
+    let value = use_context::<T>();
+    debug_assert!(value.is_some(), "{} is provided by App", std::any::type_name::<T>());
+    value
 }
 
 // WASM entry point
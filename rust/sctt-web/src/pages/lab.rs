@@ -1,10 +1,15 @@
 use leptos::*;
 use leptos::html::Textarea;
+use sctt_core::record::{Diagnostic, RunRecord, RunResult, Severity};
+use sctt_core::skeleton;
 use crate::state::AppState;
+use crate::components::{saddle, SmoothVisualizer, SmoothnessExplainer, SurfacePlot};
 
 #[component]
 pub fn LabPage() -> impl IntoView {
-    let state = use_context::<RwSignal<AppState>>().unwrap();
+    let Some(state) = crate::app_context::<RwSignal<AppState>>() else {
+        return ().into_view();
+    };
     let (code, set_code) = create_signal(String::new());
     let (output, set_output) = create_signal(OutputData::default());
     let (is_running, set_is_running) = create_signal(false);
@@ -14,7 +19,12 @@ pub fn LabPage() -> impl IntoView {
     create_effect(move |_| {
         state.update(|s| s.current_code = code.get());
     });
-    
+
+    // Stored results from another engine version are not shown as they are
+    state.update(|s| {
+        s.recheck_stale(|code| run_record(code, &analyze_code(code)));
+    });
+
     let run_code = move |_| {
         set_is_running.set(true);
         let code_text = code.get();
@@ -25,12 +35,13 @@ pub fn LabPage() -> impl IntoView {
             leptos::set_timeout(
                 move || {
                     let result = analyze_code(&code_text);
+                    let run = run_record(&code_text, &result);
                     set_output.set(result);
                     set_is_running.set(false);
                     
                     // Add to history
                     state.update(|s| {
-                        s.add_to_history(code_text.clone(), format!("{:?}", result));
+                        s.add_to_history(code_text.clone(), run);
                     });
                 },
                 std::time::Duration::from_millis(300)
@@ -73,6 +84,7 @@ pub fn LabPage() -> impl IntoView {
                 
                 <div class="lab-output">
                     <OutputPanel output=output/>
+                    <SmoothnessExplainer code=code/>
                     
                     <Show when=show_visualization>
                         <VisualizationPanel output=output/>
@@ -85,7 +97,7 @@ pub fn LabPage() -> impl IntoView {
                 <SnippetsPanel/>
             </div>
         </div>
-    }
+    }.into_view()
 }
 
 #[component]
@@ -95,6 +107,8 @@ fn Editor(
     is_running: ReadSignal<bool>,
 ) -> impl IntoView {
     let textarea_ref = create_node_ref::<Textarea>();
+    // Definitions with a missing or `?` body, by header line
+    let actions = create_memo(move |_| skeleton::actions(&code.get()));
     
     // Syntax highlighting would go here
     let highlighted_code = move || {
@@ -109,7 +123,21 @@ fn Editor(
         <div class="editor-container">
             <div class="editor-gutter">
                 {(1..=30).map(|n| view! { 
-                    <div class="line-number">{n}</div> 
+                    <div class="line-number">
+                        {move || actions.get().into_iter().find(|a| a.line + 1 == n).map(|action| {
+                            let title = format!("Generate skeleton: {} = {}", action.name, action.skeleton);
+                            view! {
+                                <button
+                                    class="code-action"
+                                    title=title
+                                    on:click=move |_| set_code.set(action.apply(&code.get_untracked()))
+                                >
+                                    "💡"
+                                </button>
+                            }
+                        })}
+                        {n}
+                    </div> 
                 }).collect_view()}
             </div>
             
@@ -277,18 +305,26 @@ fn OutputPanel(output: ReadSignal<OutputData>) -> impl IntoView {
 
 #[component]
 fn VisualizationPanel(output: ReadSignal<OutputData>) -> impl IntoView {
+    let (viz_type, set_viz_type) = create_signal("2D Plot".to_string());
+    
     view! {
         <div class="visualization-panel">
             <div class="viz-header">
                 <h3>"Visualization"</h3>
-                <select class="viz-type">
+                <select class="viz-type" on:change=move |e| set_viz_type.set(event_target_value(&e))>
                     <option>"2D Plot"</option>
                     <option>"3D Surface"</option>
                     <option>"Path Animation"</option>
                 </select>
             </div>
             
-            <canvas id="viz-canvas" class="viz-canvas"/>
+            <Show
+                when=move || viz_type.get() == "3D Surface"
+                fallback=|| view! { <SmoothVisualizer/> }
+            >
+                // Two-variable functions are not parsed from the editor yet
+                <SurfacePlot f=saddle/>
+            </Show>
             
             <div class="viz-controls">
                 <button class="btn-icon" title="Zoom in">"🔍+"</button>
@@ -302,7 +338,9 @@ fn VisualizationPanel(output: ReadSignal<OutputData>) -> impl IntoView {
 
 #[component]
 fn HistoryPanel() -> impl IntoView {
-    let state = use_context::<RwSignal<AppState>>().unwrap();
+    let Some(state) = crate::app_context::<RwSignal<AppState>>() else {
+        return ().into_view();
+    };
     
     view! {
         <div class="history-panel">
@@ -310,24 +348,26 @@ fn HistoryPanel() -> impl IntoView {
             <div class="history-list">
                 <For
                     each=move || state.get().history.clone()
-                    key=|h| h.timestamp.clone()
+                    key=|h| h.run.timestamp.clone()
                     children=move |entry| {
                         view! {
                             <div class="history-entry">
                                 <code>{entry.code}</code>
-                                <span class="history-time">{entry.timestamp}</span>
+                                <span class="history-time">{entry.run.timestamp}</span>
                             </div>
                         }
                     }
                 />
             </div>
         </div>
-    }
+    }.into_view()
 }
 
 #[component]
 fn SnippetsPanel() -> impl IntoView {
-    let state = use_context::<RwSignal<AppState>>().unwrap();
+    let Some(state) = crate::app_context::<RwSignal<AppState>>() else {
+        return ().into_view();
+    };
     
     view! {
         <div class="snippets-panel">
@@ -354,7 +394,7 @@ fn SnippetsPanel() -> impl IntoView {
                 />
             </div>
         </div>
-    }
+    }.into_view()
 }
 
 // Helper types and functions
@@ -397,6 +437,21 @@ fn analyze_code(code: &str) -> OutputData {
     }
 }
 
+/// The history's record of one run of `code`
+fn run_record(code: &str, output: &OutputData) -> RunRecord {
+    let result = match (&output.status, &output.type_info) {
+        (Status::Error, _) => RunResult::Failed { error: output.status_text.clone() },
+        (_, ty) => RunResult::Checked { ty: ty.clone().unwrap_or_default() },
+    };
+    let run = RunRecord::new(code, chrono::Utc::now().to_rfc3339(), result)
+        .with_engine("sctt-web", env!("CARGO_PKG_VERSION"))
+        .with_duration(std::time::Duration::from_millis(output.execution_time.into()));
+    output.messages.iter().fold(run, |run, message| {
+        let diagnostic = Diagnostic { severity: Severity::Info, message: message.clone(), span: None, suggestion: None };
+        run.with_diagnostic(diagnostic)
+    })
+}
+
 fn get_suggestions(prefix: &str) -> Vec<String> {
     let all = vec![
         "lambda", "Path", "sin", "cos", "exp", "derivative", 
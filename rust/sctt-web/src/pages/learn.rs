@@ -1,10 +1,13 @@
 use leptos::*;
 use leptos_router::*;
 use crate::state::AppState;
+use crate::components::{Deriver, LiveDerivation};
 
 #[component]
 pub fn LearnPage() -> impl IntoView {
-    let state = use_context::<RwSignal<AppState>>().unwrap();
+    let Some(state) = crate::app_context::<RwSignal<AppState>>() else {
+        return ().into_view();
+    };
     let (current_lesson, set_current_lesson) = create_signal(0);
     let (code_input, set_code_input) = create_signal(String::new());
     let (feedback, set_feedback) = create_signal(String::new());
@@ -118,6 +121,12 @@ pub fn LearnPage() -> impl IntoView {
                             <pre class="code-example">
                                 <code>{move || lesson().example}</code>
                             </pre>
+                            {move || {
+                                let lesson = lesson();
+                                lesson.derivation.map(|deriver| view! {
+                                    <LiveDerivation source=lesson.example deriver=deriver/>
+                                })
+                            }}
                         </div>
                         
                         <div class="lesson-exercise">
@@ -217,7 +226,7 @@ pub fn LearnPage() -> impl IntoView {
                 </aside>
             </div>
         </div>
-    }
+    }.into_view()
 }
 
 #[derive(Clone, Default)]
@@ -230,6 +239,8 @@ struct Lesson {
     solution: String,
     hint: String,
     pro_tip: String,
+    /// Show the engine's derivation of `example` under it
+    derivation: Option<Deriver>,
 }
 
 impl Lesson {
@@ -255,6 +266,7 @@ fn get_lessons() -> Vec<Lesson> {
             solution: "λx. x²".to_string(),
             hint: "Use λx. followed by x squared (x²)".to_string(),
             pro_tip: "You can type x^2 and it will be displayed as x²".to_string(),
+            derivation: None,
         },
         Lesson {
             id: "derivatives".to_string(),
@@ -270,6 +282,7 @@ fn get_lessons() -> Vec<Lesson> {
             solution: "∂(λx. sin(x))".to_string(),
             hint: "Wrap the function in ∂(...)".to_string(),
             pro_tip: "SCTT verifies derivatives are correct at the type level!".to_string(),
+            derivation: None,
         },
         Lesson {
             id: "paths".to_string(),
@@ -285,6 +298,7 @@ fn get_lessons() -> Vec<Lesson> {
             solution: "⟨t⟩ t".to_string(),
             hint: "The simplest path is just ⟨t⟩ t".to_string(),
             pro_tip: "Paths in SCTT compute! They're not just proofs.".to_string(),
+            derivation: None,
         },
         Lesson {
             id: "composition".to_string(),
@@ -299,6 +313,7 @@ fn get_lessons() -> Vec<Lesson> {
             solution: "cos ∘ (λx. x²)".to_string(),
             hint: "Put cos first, then ∘, then the square function".to_string(),
             pro_tip: "The chain rule is built into SCTT's type system!".to_string(),
+            derivation: None,
         },
         Lesson {
             id: "types".to_string(),
@@ -318,6 +333,45 @@ fn get_lessons() -> Vec<Lesson> {
             solution: "sin : C∞(ℝ, ℝ)".to_string(),
             hint: "sine maps real numbers to real numbers smoothly".to_string(),
             pro_tip: "Types help SCTT optimize and verify your code!".to_string(),
+            derivation: None,
+        },
+        Lesson {
+            id: "de-morgan".to_string(),
+            title: "The De Morgan Interval".to_string(),
+            content: r#"
+                <p>Path dimensions live in the interval <code>I</code>, which has meets <code>∧</code>,
+                joins <code>∨</code> and a reversal <code>¬</code> swapping 0 and 1.</p>
+                <p>Reversal turns meets into joins and back again — the De Morgan laws:</p>
+                <p><code>¬(i ∧ j) = ¬i ∨ ¬j</code> and <code>¬(i ∨ j) = ¬i ∧ ¬j</code></p>
+                <p>Together with <code>¬¬i = i</code> they push every negation down onto a variable.
+                Unlike in Boolean logic, <code>i ∧ ¬i</code> is not 0: at <code>i = ½</code> it is ½.</p>
+            "#.to_string(),
+            example: "¬(i ∧ ¬j)".to_string(),
+            prompt: "Push the negation inside ¬(i ∨ j):".to_string(),
+            solution: "¬i ∧ ¬j".to_string(),
+            hint: "Negate each side and swap ∨ for ∧".to_string(),
+            pro_tip: "Each line of the derivation above is computed by SCTT's interval normalizer.".to_string(),
+            derivation: Some(Deriver::Interval),
+        },
+        Lesson {
+            id: "simp".to_string(),
+            title: "Computing by Simplification".to_string(),
+            content: r#"
+                <p>Terms compute. The <code>simp</code> tactic rewrites a term one law at a time:</p>
+                <ul>
+                    <li>β: <code>(λx. b) a</code> becomes <code>b</code> with <code>a</code> for <code>x</code></li>
+                    <li>path β: <code>(⟨i⟩ b) @ r</code> becomes <code>b</code> with <code>r</code> for <code>i</code></li>
+                    <li>projections: <code>π₁ (a, b)</code> becomes <code>a</code></li>
+                    <li>and the interval laws, like <code>i ∧ 1 = i</code></li>
+                </ul>
+                <p>It always rewrites the outermost redex first, as in the derivation below.</p>
+            "#.to_string(),
+            example: "π₁ ((λx. (x, ¬x)) i) ∧ 1".to_string(),
+            prompt: "What does (⟨t⟩ t ∨ ¬t) @ 0 simplify to?".to_string(),
+            solution: "1".to_string(),
+            hint: "Substitute 0 for t, then ¬0 = 1".to_string(),
+            pro_tip: "Path β at an endpoint is how a path's boundary computes.".to_string(),
+            derivation: Some(Deriver::Simp),
         },
     ]
 }
\ No newline at end of file
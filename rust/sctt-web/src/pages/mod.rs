@@ -11,6 +11,8 @@ pub use reference::ReferencePage;
 use leptos::*;
 use leptos_router::*;
 
+use crate::components::{GeodesicDemo, SaddleDemo};
+
 // Example page for specific examples
 #[component]
 pub fn ExamplePage() -> impl IntoView {
@@ -21,6 +23,11 @@ pub fn ExamplePage() -> impl IntoView {
         <div class="example-page">
             <h1>"Example: " {id}</h1>
             // Load specific example based on ID
+            {move || match id().as_str() {
+                "geodesic" => view! { <GeodesicDemo/> }.into_view(),
+                "saddle" => view! { <SaddleDemo/> }.into_view(),
+                _ => view! { <p>"No such example."</p> }.into_view(),
+            }}
         </div>
     }
 }
\ No newline at end of file
@@ -59,6 +59,12 @@ pub fn ReferencePage() -> impl IntoView {
                         >
                             "Functions"
                         </button>
+                        <button 
+                            class=move || if selected_category.get() == "laws" { "filter active" } else { "filter" }
+                            on:click=move |_| set_selected_category.set("laws".to_string())
+                        >
+                            "Laws"
+                        </button>
                     </div>
                 </div>
             </header>
@@ -92,7 +98,7 @@ fn ReferenceCard(item: ReferenceItem) -> impl IntoView {
     let (expanded, set_expanded) = create_signal(false);
     
     view! {
-        <article class="reference-card">
+        <article class="reference-card" id=anchor(item.name)>
             <header 
                 class="reference-card-header"
                 on:click=move |_| set_expanded.update(|e| *e = !*e)
@@ -129,7 +135,7 @@ fn ReferenceCard(item: ReferenceItem) -> impl IntoView {
                         <div class="related-links">
                             {item.related.iter().map(|link| {
                                 view! { 
-                                    <a href=format!("#{}",link) class="related-link">
+                                    <a href=format!("#{}", anchor(link)) class="related-link">
                                         {link}
                                     </a> 
                                 }
@@ -142,6 +148,11 @@ fn ReferenceCard(item: ReferenceItem) -> impl IntoView {
     }
 }
 
+/// Fragment id of a reference entry: `De Morgan laws` is `#de-morgan-laws`
+fn anchor(name: &str) -> String {
+    name.to_lowercase().split_whitespace().collect::<Vec<_>>().join("-")
+}
+
 #[derive(Clone)]
 struct ReferenceItem {
     name: &'static str,
@@ -259,5 +270,57 @@ fn get_reference_items() -> Vec<ReferenceItem> {
             ],
             related: vec!["→", "application", "η-expansion"],
         },
+        ReferenceItem {
+            name: "De Morgan laws",
+            category: "laws",
+            type_signature: "¬(i ∧ j) = ¬i ∨ ¬j,  ¬(i ∨ j) = ¬i ∧ ¬j",
+            description: "Reversing the interval exchanges meets and joins",
+            example: "¬(i ∧ ¬j) = ¬i ∨ ¬¬j = ¬i ∨ j",
+            properties: vec![
+                "Push negations down to variables",
+                "Hold in every De Morgan algebra",
+                "Used by the interval normalizer",
+            ],
+            related: vec!["Interval negation", "Lattice laws", "I"],
+        },
+        ReferenceItem {
+            name: "Interval negation",
+            category: "laws",
+            type_signature: "¬¬i = i,  ¬0 = 1,  ¬1 = 0",
+            description: "Reversal is an involution swapping the endpoints",
+            example: "(⟨t⟩ p @ ¬t) reverses the path p",
+            properties: vec![
+                "¬ is its own inverse",
+                "i ∧ ¬i need not be 0",
+                "Interpreted as 1 - i",
+            ],
+            related: vec!["De Morgan laws", "I"],
+        },
+        ReferenceItem {
+            name: "Lattice laws",
+            category: "laws",
+            type_signature: "i ∧ 1 = i,  i ∨ 0 = i,  i ∧ (i ∨ j) = i, …",
+            description: "Units, annihilation, idempotence, absorption and distributivity of ∧ and ∨",
+            example: "i ∧ (j ∨ k) = (i ∧ j) ∨ (i ∧ k)",
+            properties: vec![
+                "∧ is min, ∨ is max",
+                "0 and 1 are bottom and top",
+                "Normal forms are joins of meets",
+            ],
+            related: vec!["De Morgan laws", "I"],
+        },
+        ReferenceItem {
+            name: "Computation rules",
+            category: "laws",
+            type_signature: "(λx. b) a = b[a/x],  (⟨i⟩ b) @ r = b[r/i],  π₁ (a, b) = a",
+            description: "How functions, paths and pairs compute when taken apart",
+            example: "π₁ ((λx. (x, ¬x)) i) = π₁ (i, ¬i) = i",
+            properties: vec![
+                "β-reduction for functions",
+                "Path β fixes a path's endpoints",
+                "Applied outermost first by simp",
+            ],
+            related: vec!["λ", "Path", "Lattice laws"],
+        },
     ]
 }
\ No newline at end of file
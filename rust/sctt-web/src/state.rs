@@ -1,4 +1,7 @@
+use sctt_core::compat::{Format, Policy};
+use sctt_core::record::{RunRecord, SchemaError};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -20,11 +23,39 @@ pub struct SavedSnippet {
     pub created_at: String,
 }
 
+/// One lab run; entries stored before [`RunRecord`] existed are migrated
+/// on load
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Value")]
 pub struct HistoryEntry {
     pub code: String,
-    pub result: String,
-    pub timestamp: String,
+    pub run: RunRecord,
+    /// Pinned entries survive storage degradation
+    #[serde(default)]
+    pub pinned: bool,
+    /// Sampled (x, y) points of the result, if any
+    #[serde(default)]
+    pub samples: Vec<(f64, f64)>,
+}
+
+impl TryFrom<Value> for HistoryEntry {
+    type Error = SchemaError;
+
+    fn try_from(value: Value) -> Result<Self, SchemaError> {
+        let code = value.get("code").and_then(Value::as_str).unwrap_or_default().to_string();
+        let pinned = value.get("pinned").and_then(Value::as_bool).unwrap_or(false);
+        let samples = match value.get("samples") {
+            Some(samples) => Vec::<(f64, f64)>::deserialize(samples)
+                .map_err(|e| SchemaError { path: "$.samples".to_string(), message: e.to_string() })?,
+            None => Vec::new(),
+        };
+        let run = match value.get("run") {
+            Some(run) => RunRecord::load_value(run.clone())?,
+            // Pre-schema entries kept `result` and `timestamp` at the top
+            None => RunRecord::load_value(value)?,
+        };
+        Ok(HistoryEntry { code, run, pinned, samples })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,11 +103,12 @@ impl AppState {
         serde_json::from_str(json).ok()
     }
     
-    pub fn add_to_history(&mut self, code: String, result: String) {
+    pub fn add_to_history(&mut self, code: String, run: RunRecord) {
         self.history.push(HistoryEntry {
             code,
-            result,
-            timestamp: chrono::Utc::now().to_rfc3339(),
+            run,
+            pinned: false,
+            samples: Vec::new(),
         });
         
         // Keep only last 100 entries
@@ -85,6 +117,20 @@ impl AppState {
         }
     }
     
+    /// Run again every history entry whose result came from an engine this
+    /// one does not trust (see [`sctt_core::compat`]); returns how many
+    pub fn recheck_stale(&mut self, mut run: impl FnMut(&str) -> RunRecord) -> usize {
+        let mut rechecked = 0;
+        for entry in &mut self.history {
+            if entry.run.verdict(Format::HistoryEntry).action == Some(Policy::Recheck) {
+                entry.run = run(&entry.code);
+                entry.samples.clear();
+                rechecked += 1;
+            }
+        }
+        rechecked
+    }
+    
     pub fn save_snippet(&mut self, name: String, description: String, tags: Vec<String>) {
         let snippet = SavedSnippet {
             id: uuid::Uuid::new_v4().to_string(),
@@ -0,0 +1,606 @@
+//! Chunked, compressed persistence of [`AppState`]
+//!
+//! The state is split into independently versioned chunks, each deflated and
+//! written under its own key, with a manifest tying them together. Only chunks
+//! whose contents changed since the last save are rewritten.
+//!
+//! When the browser reports that storage is full, the persisted copy is
+//! degraded in a fixed order until it fits:
+//!
+//! 1. drop unpinned history entries
+//! 2. trim samples from the remaining stored results
+//!
+//! Progress and snippets are never touched. If the state still does not fit,
+//! the save fails with [`StorageError::QuotaExceeded`].
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use sctt_core::compat::ENGINE_VERSION;
+
+use crate::state::{AppState, HistoryEntry, SavedSnippet, SessionData, UserPreferences};
+
+pub const MANIFEST_KEY: &str = "sctt_manifest";
+/// Key of the single-blob format written before chunking
+pub const LEGACY_KEY: &str = "sctt_work";
+/// Version 1 stored chunks as plain JSON; version 2 deflates and base64-encodes them
+pub const CHUNK_FORMAT_VERSION: u32 = 2;
+/// Encoded chunks larger than this go to IndexedDB rather than localStorage,
+/// when the storage has an IndexedDB layer
+pub const INDEXED_DB_THRESHOLD: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChunkKind {
+    Settings,
+    Progress,
+    Snippets,
+    History,
+}
+
+impl ChunkKind {
+    pub const ALL: [ChunkKind; 4] = [
+        ChunkKind::Settings,
+        ChunkKind::Progress,
+        ChunkKind::Snippets,
+        ChunkKind::History,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            ChunkKind::Settings => "sctt_chunk_settings",
+            ChunkKind::Progress => "sctt_chunk_progress",
+            ChunkKind::Snippets => "sctt_chunk_snippets",
+            ChunkKind::History => "sctt_chunk_history",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backend {
+    LocalStorage,
+    IndexedDb,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkEntry {
+    pub kind: ChunkKind,
+    pub revision: u64,
+    pub backend: Backend,
+    pub size: usize,
+    /// Engine that wrote the chunk; empty for chunks written before it was recorded
+    #[serde(default)]
+    pub engine_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub chunks: Vec<ChunkEntry>,
+}
+
+impl Manifest {
+    fn new() -> Self {
+        Manifest { format_version: CHUNK_FORMAT_VERSION, chunks: Vec::new() }
+    }
+
+    pub fn entry(&self, kind: ChunkKind) -> Option<&ChunkEntry> {
+        self.chunks.iter().find(|c| c.kind == kind)
+    }
+
+    fn set(&mut self, entry: ChunkEntry) {
+        self.chunks.retain(|c| c.kind != entry.kind);
+        self.chunks.push(entry);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageError {
+    QuotaExceeded,
+    Unavailable,
+    Corrupt(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::QuotaExceeded => {
+                write!(f, "Browser storage is full; your progress and snippets could not be saved")
+            }
+            StorageError::Unavailable => write!(f, "Browser storage is unavailable"),
+            StorageError::Corrupt(msg) => write!(f, "Saved work is unreadable: {}", msg),
+        }
+    }
+}
+
+/// Key-value storage the chunks are written to
+pub trait ChunkStorage {
+    /// Whether chunks can be written to `backend`; localStorage always can
+    fn supports(&self, backend: Backend) -> bool;
+    fn get(&self, backend: Backend, key: &str) -> Option<String>;
+    fn put(&mut self, backend: Backend, key: &str, value: &str) -> Result<(), StorageError>;
+}
+
+/// A step taken to make the persisted copy fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Degradation {
+    DroppedUnpinnedHistory(usize),
+    TrimmedSamples(usize),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SaveReport {
+    pub written: Vec<ChunkKind>,
+    pub degradations: Vec<Degradation>,
+}
+
+impl SaveReport {
+    /// Message to show the user when the save had to drop data
+    pub fn notice(&self) -> Option<String> {
+        if self.degradations.is_empty() {
+            return None;
+        }
+        let parts: Vec<String> = self.degradations.iter().map(|d| match d {
+            Degradation::DroppedUnpinnedHistory(n) => format!("dropped {} unpinned history entries", n),
+            Degradation::TrimmedSamples(n) => format!("trimmed {} samples from stored results", n),
+        }).collect();
+        Some(format!("Storage is nearly full: {}. Pin results you want to keep.", parts.join(", ")))
+    }
+}
+
+/// Tracks what has been written so saves only touch dirty chunks
+#[derive(Debug, Default)]
+pub struct ChunkedStore {
+    manifest: Option<Manifest>,
+    written: HashMap<ChunkKind, u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnippetsChunk {
+    current_code: String,
+    saved_snippets: Vec<SavedSnippet>,
+}
+
+impl ChunkedStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Chunks whose contents differ from what was last written or loaded
+    pub fn dirty_chunks(&self, state: &AppState) -> Vec<ChunkKind> {
+        ChunkKind::ALL
+            .into_iter()
+            .filter(|&kind| self.is_dirty(kind, &chunk_json(state, kind)))
+            .collect()
+    }
+
+    /// Persist `state`, degrading the stored copy if storage is full
+    pub fn save(&mut self, state: &AppState, storage: &mut impl ChunkStorage) -> Result<SaveReport, StorageError> {
+        let mut persisted = state.clone();
+        let mut degradations = Vec::new();
+
+        loop {
+            match self.write(&persisted, storage) {
+                Ok(written) => return Ok(SaveReport { written, degradations }),
+                Err(StorageError::QuotaExceeded) => match degrade(&mut persisted) {
+                    Some(step) => degradations.push(step),
+                    None => return Err(StorageError::QuotaExceeded),
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Load the saved state, migrating older chunk formats and the legacy blob
+    pub fn load(&mut self, storage: &impl ChunkStorage) -> Result<Option<AppState>, StorageError> {
+        let Some(raw) = storage.get(Backend::LocalStorage, MANIFEST_KEY) else {
+            return Ok(storage
+                .get(Backend::LocalStorage, LEGACY_KEY)
+                .and_then(|json| AppState::from_json(&json)));
+        };
+        let manifest: Manifest = serde_json::from_str(&raw)
+            .map_err(|e| StorageError::Corrupt(e.to_string()))?;
+        if manifest.format_version > CHUNK_FORMAT_VERSION {
+            return Err(StorageError::Corrupt(format!(
+                "chunk format {} is newer than this version understands",
+                manifest.format_version
+            )));
+        }
+
+        let current = manifest.format_version == CHUNK_FORMAT_VERSION;
+        let mut state = AppState::default();
+        self.written.clear();
+        for entry in &manifest.chunks {
+            let payload = storage.get(entry.backend, entry.kind.key())
+                .ok_or_else(|| StorageError::Corrupt(format!("missing chunk {:?}", entry.kind)))?;
+            let json = decode(manifest.format_version, &payload)?;
+            apply_chunk(&mut state, entry.kind, &json)?;
+            if current {
+                self.written.insert(entry.kind, content_hash(&json));
+            }
+        }
+        // Chunks from an older format are rewritten on the next save
+        self.manifest = current.then_some(manifest);
+
+        Ok(Some(state))
+    }
+
+    fn is_dirty(&self, kind: ChunkKind, json: &str) -> bool {
+        let in_manifest = self.manifest.as_ref().is_some_and(|m| m.entry(kind).is_some());
+        !in_manifest || self.written.get(&kind) != Some(&content_hash(json))
+    }
+
+    fn write(&mut self, state: &AppState, storage: &mut impl ChunkStorage) -> Result<Vec<ChunkKind>, StorageError> {
+        let mut manifest = self.manifest.clone().unwrap_or_else(Manifest::new);
+        let mut written = Vec::new();
+        let mut hashes = Vec::new();
+
+        for kind in ChunkKind::ALL {
+            let json = chunk_json(state, kind);
+            if !self.is_dirty(kind, &json) {
+                continue;
+            }
+            let payload = encode(&json);
+            let backend = if payload.len() > INDEXED_DB_THRESHOLD && storage.supports(Backend::IndexedDb) {
+                Backend::IndexedDb
+            } else {
+                Backend::LocalStorage
+            };
+            storage.put(backend, kind.key(), &payload)?;
+
+            let revision = manifest.entry(kind).map_or(1, |e| e.revision + 1);
+            manifest.set(ChunkEntry {
+                kind,
+                revision,
+                backend,
+                size: payload.len(),
+                engine_version: ENGINE_VERSION.to_string(),
+            });
+            written.push(kind);
+            hashes.push((kind, content_hash(&json)));
+        }
+
+        if !written.is_empty() {
+            let raw = serde_json::to_string(&manifest).expect("manifest is serializable");
+            storage.put(Backend::LocalStorage, MANIFEST_KEY, &raw)?;
+        }
+        self.manifest = Some(manifest);
+        self.written.extend(hashes);
+
+        Ok(written)
+    }
+}
+
+fn degrade(state: &mut AppState) -> Option<Degradation> {
+    let unpinned = state.history.iter().filter(|h| !h.pinned).count();
+    if unpinned > 0 {
+        state.history.retain(|h| h.pinned);
+        return Some(Degradation::DroppedUnpinnedHistory(unpinned));
+    }
+
+    let samples: usize = state.history.iter().map(|h| h.samples.len()).sum();
+    if samples > 0 {
+        state.history.iter_mut().for_each(|h| h.samples.clear());
+        return Some(Degradation::TrimmedSamples(samples));
+    }
+
+    None
+}
+
+fn chunk_json(state: &AppState, kind: ChunkKind) -> String {
+    let json = match kind {
+        ChunkKind::Settings => serde_json::to_string(&state.preferences),
+        ChunkKind::Progress => serde_json::to_string(&state.session),
+        ChunkKind::Snippets => serde_json::to_string(&SnippetsChunk {
+            current_code: state.current_code.clone(),
+            saved_snippets: state.saved_snippets.clone(),
+        }),
+        ChunkKind::History => serde_json::to_string(&state.history),
+    };
+    json.expect("app state is serializable")
+}
+
+fn apply_chunk(state: &mut AppState, kind: ChunkKind, json: &str) -> Result<(), StorageError> {
+    let corrupt = |e: serde_json::Error| StorageError::Corrupt(format!("{:?}: {}", kind, e));
+    match kind {
+        ChunkKind::Settings => state.preferences = serde_json::from_str::<UserPreferences>(json).map_err(corrupt)?,
+        ChunkKind::Progress => state.session = serde_json::from_str::<SessionData>(json).map_err(corrupt)?,
+        ChunkKind::Snippets => {
+            let chunk: SnippetsChunk = serde_json::from_str(json).map_err(corrupt)?;
+            state.current_code = chunk.current_code;
+            state.saved_snippets = chunk.saved_snippets;
+        }
+        ChunkKind::History => state.history = serde_json::from_str::<Vec<HistoryEntry>>(json).map_err(corrupt)?,
+    }
+    Ok(())
+}
+
+fn encode(json: &str) -> String {
+    STANDARD.encode(miniz_oxide::deflate::compress_to_vec(json.as_bytes(), 6))
+}
+
+fn decode(format_version: u32, payload: &str) -> Result<String, StorageError> {
+    if format_version < 2 {
+        return Ok(payload.to_string());
+    }
+    let compressed = STANDARD.decode(payload)
+        .map_err(|e| StorageError::Corrupt(e.to_string()))?;
+    let bytes = miniz_oxide::inflate::decompress_to_vec(&compressed)
+        .map_err(|e| StorageError::Corrupt(format!("{:?}", e)))?;
+    String::from_utf8(bytes).map_err(|e| StorageError::Corrupt(e.to_string()))
+}
+
+fn content_hash(json: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    json.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Browser storage backed by `window.localStorage`
+///
+/// There is no IndexedDB layer in the web app yet, so every chunk, however
+/// large, is written to and recorded as localStorage.
+pub struct BrowserStorage;
+
+impl ChunkStorage for BrowserStorage {
+    fn supports(&self, backend: Backend) -> bool {
+        backend == Backend::LocalStorage
+    }
+
+    fn get(&self, _backend: Backend, key: &str) -> Option<String> {
+        web_sys::window()?.local_storage().ok()??.get_item(key).ok()?
+    }
+
+    fn put(&mut self, _backend: Backend, key: &str, value: &str) -> Result<(), StorageError> {
+        let storage = web_sys::window()
+            .and_then(|w| w.local_storage().ok().flatten())
+            .ok_or(StorageError::Unavailable)?;
+        // setItem only throws QuotaExceededError once storage is accessible
+        storage.set_item(key, value).map_err(|_| StorageError::QuotaExceeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sctt_core::record::{RunRecord, RunResult, ENGINE};
+
+    /// In-memory storage that rejects writes past a byte budget
+    #[derive(Default)]
+    struct MemoryStorage {
+        items: HashMap<String, (Backend, String)>,
+        quota: Option<usize>,
+        puts: Vec<String>,
+        local_only: bool,
+    }
+
+    impl MemoryStorage {
+        fn with_quota(quota: usize) -> Self {
+            MemoryStorage { quota: Some(quota), ..Default::default() }
+        }
+
+        fn used_without(&self, key: &str) -> usize {
+            self.items.iter().filter(|(k, _)| *k != key).map(|(_, (_, v))| v.len()).sum()
+        }
+    }
+
+    impl ChunkStorage for MemoryStorage {
+        fn supports(&self, backend: Backend) -> bool {
+            !self.local_only || backend == Backend::LocalStorage
+        }
+
+        fn get(&self, backend: Backend, key: &str) -> Option<String> {
+            self.items.get(key).filter(|(b, _)| *b == backend).map(|(_, v)| v.clone())
+        }
+
+        fn put(&mut self, backend: Backend, key: &str, value: &str) -> Result<(), StorageError> {
+            if self.quota.is_some_and(|q| self.used_without(key) + value.len() > q) {
+                return Err(StorageError::QuotaExceeded);
+            }
+            self.puts.push(key.to_string());
+            self.items.insert(key.to_string(), (backend, value.to_string()));
+            Ok(())
+        }
+    }
+
+    /// Deterministic, poorly compressible text
+    fn noise(seed: u64, len: usize) -> String {
+        let mut x = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (0..len).map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            char::from(b'!' + (x % 90) as u8)
+        }).collect()
+    }
+
+    fn entry(pinned: bool, samples: usize, seed: u64) -> HistoryEntry {
+        let code = noise(seed, 2_000);
+        HistoryEntry {
+            run: RunRecord::new(&code, "", RunResult::Checked { ty: "ok".to_string() }),
+            code,
+            pinned,
+            samples: (0..samples).map(|i| (i as f64, i as f64)).collect(),
+        }
+    }
+
+    fn state_with_history(history: Vec<HistoryEntry>) -> AppState {
+        AppState {
+            current_code: "λx. x".to_string(),
+            session: SessionData {
+                completed_tutorials: vec!["paths".to_string()],
+                ..Default::default()
+            },
+            history,
+            ..Default::default()
+        }
+    }
+
+    fn stored_size(state: &AppState) -> usize {
+        let mut storage = MemoryStorage::default();
+        ChunkedStore::new().save(state, &mut storage).unwrap();
+        storage.used_without("")
+    }
+
+    #[test]
+    fn test_round_trip_and_incremental_writes() {
+        let mut state = state_with_history(vec![entry(false, 3, 1)]);
+        let mut storage = MemoryStorage::default();
+        let mut store = ChunkedStore::new();
+
+        let report = store.save(&state, &mut storage).unwrap();
+        assert_eq!(report.written, ChunkKind::ALL.to_vec());
+        assert!(store.dirty_chunks(&state).is_empty());
+
+        state.preferences.font_size = 18;
+        assert_eq!(store.dirty_chunks(&state), vec![ChunkKind::Settings]);
+        storage.puts.clear();
+        let report = store.save(&state, &mut storage).unwrap();
+        assert_eq!(report.written, vec![ChunkKind::Settings]);
+        assert_eq!(storage.puts, vec![ChunkKind::Settings.key(), MANIFEST_KEY]);
+        assert_eq!(store.manifest().unwrap().entry(ChunkKind::Settings).unwrap().revision, 2);
+
+        let loaded = ChunkedStore::new().load(&storage).unwrap().unwrap();
+        assert_eq!(loaded.to_json(), state.to_json());
+    }
+
+    #[test]
+    fn test_quota_drops_unpinned_history_first() {
+        let state = state_with_history(vec![entry(false, 2, 1), entry(true, 2, 2)]);
+        let pinned_only = state_with_history(vec![entry(true, 2, 2)]);
+        let mut storage = MemoryStorage::with_quota(stored_size(&pinned_only) + 16);
+
+        let report = ChunkedStore::new().save(&state, &mut storage).unwrap();
+        assert_eq!(report.degradations, vec![Degradation::DroppedUnpinnedHistory(1)]);
+        assert!(report.notice().unwrap().contains("unpinned history"));
+
+        let loaded = ChunkedStore::new().load(&storage).unwrap().unwrap();
+        assert_eq!(loaded.history.len(), 1);
+        assert!(loaded.history[0].pinned);
+        assert_eq!(loaded.history[0].samples.len(), 2);
+    }
+
+    #[test]
+    fn test_quota_then_trims_samples() {
+        let mut bulky = entry(true, 0, 2);
+        bulky.samples = (0..4_000).map(|i| (i as f64 * 0.37, (i as f64).sin())).collect();
+        let state = state_with_history(vec![entry(false, 0, 1), bulky]);
+        let mut trimmed = state_with_history(vec![entry(true, 0, 2)]);
+        trimmed.history[0].samples.clear();
+        let mut storage = MemoryStorage::with_quota(stored_size(&trimmed) + 16);
+
+        let report = ChunkedStore::new().save(&state, &mut storage).unwrap();
+        assert_eq!(
+            report.degradations,
+            vec![Degradation::DroppedUnpinnedHistory(1), Degradation::TrimmedSamples(4_000)]
+        );
+
+        let loaded = ChunkedStore::new().load(&storage).unwrap().unwrap();
+        assert!(loaded.history[0].samples.is_empty());
+        assert_eq!(loaded.session.completed_tutorials, vec!["paths".to_string()]);
+    }
+
+    #[test]
+    fn test_quota_never_touches_progress_or_snippets() {
+        let state = state_with_history(vec![entry(false, 2, 1)]);
+        let mut storage = MemoryStorage::with_quota(64);
+
+        let err = ChunkedStore::new().save(&state, &mut storage).unwrap_err();
+        assert_eq!(err, StorageError::QuotaExceeded);
+        assert!(storage.get(Backend::LocalStorage, MANIFEST_KEY).is_none());
+    }
+
+    #[test]
+    fn test_large_chunks_prefer_indexed_db() {
+        let state = state_with_history((0..64).map(|i| entry(false, 0, i)).collect());
+        let mut storage = MemoryStorage::default();
+        let mut store = ChunkedStore::new();
+        store.save(&state, &mut storage).unwrap();
+
+        let manifest = store.manifest().unwrap();
+        assert_eq!(manifest.entry(ChunkKind::History).unwrap().backend, Backend::IndexedDb);
+        assert_eq!(manifest.entry(ChunkKind::Settings).unwrap().backend, Backend::LocalStorage);
+        assert_eq!(ChunkedStore::new().load(&storage).unwrap().unwrap().history.len(), 64);
+
+        // Without an IndexedDB layer the manifest says where the chunk really went
+        let mut storage = MemoryStorage { local_only: true, ..Default::default() };
+        let mut store = ChunkedStore::new();
+        store.save(&state, &mut storage).unwrap();
+        assert_eq!(store.manifest().unwrap().entry(ChunkKind::History).unwrap().backend, Backend::LocalStorage);
+        assert_eq!(ChunkedStore::new().load(&storage).unwrap().unwrap().history.len(), 64);
+    }
+
+    #[test]
+    fn test_migrates_older_chunk_format() {
+        let state = state_with_history(vec![entry(false, 1, 1)]);
+        let mut storage = MemoryStorage::default();
+        let mut manifest = Manifest { format_version: 1, chunks: Vec::new() };
+        for kind in ChunkKind::ALL {
+            let json = chunk_json(&state, kind);
+            manifest.chunks.push(ChunkEntry {
+                kind,
+                revision: 1,
+                backend: Backend::LocalStorage,
+                size: json.len(),
+                engine_version: String::new(),
+            });
+            storage.put(Backend::LocalStorage, kind.key(), &json).unwrap();
+        }
+        storage.put(Backend::LocalStorage, MANIFEST_KEY, &serde_json::to_string(&manifest).unwrap()).unwrap();
+
+        let mut store = ChunkedStore::new();
+        let loaded = store.load(&storage).unwrap().unwrap();
+        assert_eq!(loaded.to_json(), state.to_json());
+
+        // The next save rewrites everything in the current format
+        assert_eq!(store.dirty_chunks(&loaded), ChunkKind::ALL.to_vec());
+        store.save(&loaded, &mut storage).unwrap();
+        assert_eq!(store.manifest().unwrap().format_version, CHUNK_FORMAT_VERSION);
+        assert_eq!(ChunkedStore::new().load(&storage).unwrap().unwrap().to_json(), state.to_json());
+    }
+
+    #[test]
+    fn test_pre_schema_history_entries_load() {
+        let legacy = r#"[{"code":"sin(x)","timestamp":"2025-03-01T12:00:00+00:00","pinned":true,"result":"OutputData { status: Success, status_text: \"Type check passed\", messages: [], type_info: Some(\"C∞(ℝ, ℝ)\"), execution_time: 42 }"}]"#;
+        let mut state = AppState::default();
+        apply_chunk(&mut state, ChunkKind::History, legacy).unwrap();
+        let entry = &state.history[0];
+        assert!(entry.pinned);
+        assert_eq!(entry.run.result, RunResult::Checked { ty: "C∞(ℝ, ℝ)".to_string() });
+        assert_eq!(entry.run.duration_ms, 42);
+
+        // Rewritten in the current shape, which loads as is
+        let json = chunk_json(&state, ChunkKind::History);
+        assert!(json.contains("\"schema_version\":1"));
+        apply_chunk(&mut state, ChunkKind::History, &json).unwrap();
+        assert_eq!(state.history[0].run.timestamp, "2025-03-01T12:00:00+00:00");
+    }
+
+    #[test]
+    fn test_results_from_another_engine_are_checked_again() {
+        let mut old = entry(true, 2, 1);
+        old.run = old.run.with_engine(ENGINE, "0.0.1");
+        let state = state_with_history(vec![entry(false, 2, 2), old]);
+        let mut storage = MemoryStorage::default();
+        ChunkedStore::new().save(&state, &mut storage).unwrap();
+
+        let mut loaded = ChunkedStore::new().load(&storage).unwrap().unwrap();
+        let mut rechecked = Vec::new();
+        let count = loaded.recheck_stale(|code| {
+            rechecked.push(code.to_string());
+            RunRecord::new(code, "now", RunResult::Checked { ty: "again".to_string() })
+        });
+        assert_eq!(count, 1);
+        assert_eq!(rechecked, vec![state.history[1].code.clone()]);
+        assert_eq!(loaded.history[1].run.result, RunResult::Checked { ty: "again".to_string() });
+        assert!(loaded.history[1].samples.is_empty() && loaded.history[1].pinned);
+        assert_eq!(loaded.history[0].run, state.history[0].run);
+    }
+}
@@ -0,0 +1,445 @@
+//! REST API payloads and worked examples
+//!
+//! The request and response bodies of `sctt-server`'s REST routes, shared by
+//! the server, the `sctt-client` crate and the tests. [`fixtures`] holds named
+//! request/response pairs for every route; [`examples`] serves the same pairs
+//! as JSON at `GET /api/examples`, so the documentation cannot drift from
+//! what the tests exercise.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "proof-assistant")]
+use crate::ProofState;
+
+/// Body of every non-2xx response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeCheckRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeCheckResponse {
+    pub success: bool,
+    pub result: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompileRequest {
+    pub code: String,
+    /// `none`, `basic` or `aggressive`
+    pub optimization: String,
+    /// Return the module without its proof certificate, which is kept in
+    /// the artifact cache instead
+    #[serde(default)]
+    pub strip_certificates: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompileResponse {
+    pub success: bool,
+    pub wasm: Option<Vec<u8>>,
+    pub error: Option<String>,
+    /// Where the bytes of the compiled module go, before any stripping
+    #[serde(default)]
+    pub size: Option<SizeBreakdown>,
+    /// File name of the detached certificate in the artifact cache, when
+    /// `wasm` was stripped
+    #[serde(default)]
+    pub certificate: Option<String>,
+}
+
+/// Encoded bytes of a module by section; the parts sum to `total`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBreakdown {
+    pub code: usize,
+    pub types: usize,
+    /// The `proof` custom section: the certificate's overhead
+    pub proof: usize,
+    pub srcmap: usize,
+    /// Header, imports, exports and any other section
+    pub other: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProveRequest {
+    pub statement: String,
+    pub tactics: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProveResponse {
+    pub success: bool,
+    pub proof_state: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Evaluate a one-variable smooth expression such as `sin(x) + x²`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateRequest {
+    pub expression: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateResponse {
+    pub success: bool,
+    pub expression: String,
+    pub input: f64,
+    pub result: Option<f64>,
+    /// Derivative at `input`
+    pub slope: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// Evaluate one expression at many points; fails as a whole if any point is
+/// undefined
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateBatchRequest {
+    pub expression: String,
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateBatchResponse {
+    pub success: bool,
+    pub expression: String,
+    pub results: Vec<f64>,
+    pub slopes: Vec<f64>,
+    pub error: Option<String>,
+}
+
+/// An expression, or a piecewise path such as `t on [0, 0.5]; 1 - t on [0.5, 1]`,
+/// whose smoothness to explain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplainSmoothnessRequest {
+    pub code: String,
+}
+
+/// `fix` and `unsafe_fix` definitions, each checked with the ones before it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckTerminationRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub created: bool,
+    /// Bearer token for the session's REST writes; only the creator gets it
+    pub edit_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub document: String,
+    pub version: u64,
+    pub users: Vec<String>,
+    pub proof_state: String,
+    pub driver: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeginTransactionRequest {
+    /// Who is scripting, for the activity log
+    pub client: String,
+    /// Seconds of silence before the transaction rolls back
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeginTransactionResponse {
+    pub transaction: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApplyInTransactionRequest {
+    pub goal_id: usize,
+    pub tactic: String,
+}
+
+/// The transaction's fork after a step: its open goals and the steps so far
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyInTransactionResponse {
+    pub proof_state: ProofState,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    pub transaction: String,
+    /// `true` after commit, `false` after rollback
+    pub committed: bool,
+}
+
+/// Rename a definition in the session document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenameRequest {
+    pub old: String,
+    pub new: String,
+}
+
+/// A rewritten name; line and column are 1-based
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenamedAt {
+    pub line: usize,
+    pub column: usize,
+    /// The definition the occurrence is in
+    pub definition: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenameResponse {
+    pub occurrences: Vec<RenamedAt>,
+    /// Every definition checked against the renamed document
+    pub rechecked: Vec<String>,
+    /// The document version after the rename
+    pub version: u64,
+}
+
+/// A saved proof, as listed at `GET /api/proofs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofRecord {
+    pub id: String,
+    pub name: String,
+    pub statement: String,
+    pub proof: String,
+    pub created_at: String,
+    /// Engine the proof was saved with; empty for proofs saved before it
+    /// was recorded
+    #[serde(default)]
+    pub engine_version: String,
+    /// Set when the proof was saved with an engine the current one may
+    /// replay differently; the proof is still returned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notice: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveProofRequest {
+    pub name: String,
+    pub statement: String,
+    pub proof: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveProofResponse {
+    pub success: bool,
+    pub id: String,
+}
+
+/// One named request/response pair
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Example {
+    pub name: String,
+    /// `None` for routes without a body
+    pub request: Option<serde_json::Value>,
+    pub response: serde_json::Value,
+}
+
+/// The examples for one route
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteExamples {
+    pub method: String,
+    pub path: String,
+    pub examples: Vec<Example>,
+}
+
+/// Request/response pairs for every route, as served at `GET /api/examples`
+pub fn examples() -> Vec<RouteExamples> {
+    fn route<Req: Serialize, Res: Serialize>(
+        method: &str,
+        path: &str,
+        fixtures: Vec<fixtures::Fixture<Req, Res>>,
+    ) -> RouteExamples {
+        RouteExamples {
+            method: method.to_string(),
+            path: path.to_string(),
+            examples: fixtures
+                .into_iter()
+                .map(|f| Example {
+                    name: f.name.to_string(),
+                    request: f.request.and_then(|r| serde_json::to_value(r).ok()),
+                    response: serde_json::to_value(f.response).unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+
+    vec![
+        route("GET", "/api/health", fixtures::health()),
+        route("POST", "/api/typecheck", fixtures::typecheck()),
+        route("POST", "/api/compile", fixtures::compile()),
+        route("POST", "/api/prove", fixtures::prove()),
+        route("POST", "/api/evaluate", fixtures::evaluate()),
+        route("POST", "/api/evaluate/batch", fixtures::evaluate_batch()),
+        route("POST", "/api/session", fixtures::create_session()),
+        route("POST", "/api/session/:id/transactions", fixtures::begin_transaction()),
+        route("POST", "/api/session/:id/transactions/:txn/commit", fixtures::commit_transaction()),
+        route("POST", "/api/session/:id/rename", fixtures::rename()),
+    ]
+}
+
+/// Named request/response pairs, the single source for tests and docs
+///
+/// Responses are what the server answers, except that generated ids and
+/// tokens are placeholders and compiled modules are cut to their header.
+pub mod fixtures {
+    use super::*;
+
+    pub struct Fixture<Req, Res> {
+        pub name: &'static str,
+        pub request: Option<Req>,
+        pub response: Res,
+    }
+
+    fn fixture<Req, Res>(name: &'static str, request: Req, response: Res) -> Fixture<Req, Res> {
+        Fixture { name, request: Some(request), response }
+    }
+
+    pub fn health() -> Vec<Fixture<(), HealthResponse>> {
+        vec![Fixture {
+            name: "healthy",
+            request: None,
+            response: HealthResponse { status: "healthy".to_string(), version: env!("CARGO_PKG_VERSION").to_string() },
+        }]
+    }
+
+    pub fn typecheck() -> Vec<Fixture<TypeCheckRequest, TypeCheckResponse>> {
+        vec![fixture(
+            "universe",
+            TypeCheckRequest { code: "Type".to_string() },
+            TypeCheckResponse { success: true, result: Some("Universe(Succ(1))".to_string()), error: None },
+        )]
+    }
+
+    pub fn compile() -> Vec<Fixture<CompileRequest, CompileResponse>> {
+        vec![fixture(
+            "identity",
+            CompileRequest { code: "λx. x".to_string(), optimization: "basic".to_string(), strip_certificates: false },
+            CompileResponse {
+                success: true,
+                wasm: Some(b"\0asm\x01\0\0\0".to_vec()),
+                error: None,
+                size: Some(SizeBreakdown { other: 8, total: 8, ..SizeBreakdown::default() }),
+                certificate: None,
+            },
+        )]
+    }
+
+    pub fn prove() -> Vec<Fixture<ProveRequest, ProveResponse>> {
+        vec![fixture(
+            "no tactics",
+            ProveRequest { statement: "Type".to_string(), tactics: Vec::new() },
+            ProveResponse {
+                success: true,
+                proof_state: Some("Goals: 1\n================\n\nGoal 1: 0\n⊢ Universe(Zero)\n".to_string()),
+                error: None,
+            },
+        )]
+    }
+
+    pub fn evaluate() -> Vec<Fixture<EvaluateRequest, EvaluateResponse>> {
+        vec![
+            fixture(
+                "square",
+                EvaluateRequest { expression: "x²".to_string(), value: 3.0 },
+                EvaluateResponse {
+                    success: true,
+                    expression: "x²".to_string(),
+                    input: 3.0,
+                    result: Some(9.0),
+                    slope: Some(6.0),
+                    error: None,
+                },
+            ),
+            fixture(
+                "undefined",
+                EvaluateRequest { expression: "ln(x)".to_string(), value: -1.0 },
+                EvaluateResponse {
+                    success: false,
+                    expression: "ln(x)".to_string(),
+                    input: -1.0,
+                    result: None,
+                    slope: None,
+                    error: Some("`ln(x)` is undefined at -1".to_string()),
+                },
+            ),
+        ]
+    }
+
+    pub fn evaluate_batch() -> Vec<Fixture<EvaluateBatchRequest, EvaluateBatchResponse>> {
+        vec![fixture(
+            "square on a grid",
+            EvaluateBatchRequest { expression: "x²".to_string(), values: vec![0.0, 1.0, 2.0] },
+            EvaluateBatchResponse {
+                success: true,
+                expression: "x²".to_string(),
+                results: vec![0.0, 1.0, 4.0],
+                slopes: vec![0.0, 2.0, 4.0],
+                error: None,
+            },
+        )]
+    }
+
+    pub fn create_session() -> Vec<Fixture<(), SessionResponse>> {
+        vec![Fixture {
+            name: "new session",
+            request: None,
+            response: SessionResponse {
+                id: "4f6c1c9e-0000-4000-8000-000000000000".to_string(),
+                created: true,
+                edit_token: "9b2d7a10-0000-4000-8000-000000000000".to_string(),
+            },
+        }]
+    }
+
+    pub fn begin_transaction() -> Vec<Fixture<BeginTransactionRequest, BeginTransactionResponse>> {
+        vec![fixture(
+            "lemma bot",
+            BeginTransactionRequest { client: "lemma-bot".to_string(), timeout_secs: Some(30) },
+            BeginTransactionResponse { transaction: "txn-1".to_string(), timeout_secs: 30 },
+        )]
+    }
+
+    /// Against a session whose document one edit set to [`RENAME_DOCUMENT`]
+    pub fn rename() -> Vec<Fixture<RenameRequest, RenameResponse>> {
+        vec![fixture(
+            "identity to same",
+            RenameRequest { old: "id".to_string(), new: "same".to_string() },
+            RenameResponse {
+                occurrences: vec![
+                    RenamedAt { line: 1, column: 1, definition: "id".to_string() },
+                    RenamedAt { line: 2, column: 29, definition: "twice".to_string() },
+                    RenamedAt { line: 2, column: 35, definition: "twice".to_string() },
+                ],
+                rechecked: vec!["same".to_string(), "twice".to_string()],
+                version: 2,
+            },
+        )]
+    }
+
+    pub const RENAME_DOCUMENT: &str = "id (A : U) (a : A) : A = a\ntwice (A : U) (a : A) : A = id A (id A a)\n";
+
+    pub fn commit_transaction() -> Vec<Fixture<(), TransactionOutcome>> {
+        vec![Fixture {
+            name: "committed",
+            request: None,
+            response: TransactionOutcome { transaction: "txn-1".to_string(), committed: true },
+        }]
+    }
+}
@@ -0,0 +1,375 @@
+//! Conversion between sctt-core's named terms and kernel terms
+//!
+//! `sctt_core::Term` is what the editor tools and the playground produce:
+//! named variables, with types kept apart from terms. The kernel in
+//! [`sctt_typechecker`] uses De Bruijn indices and treats types as terms.
+//! [`to_debruijn`] resolves names so a named term can be checked by the
+//! kernel, and [`to_named`] goes back, inventing binder names (`x0`, `x1`,
+//! ... for terms, `i0`, `i1`, ... for intervals) that avoid every name in
+//! scope.
+//!
+//! As in [`surface`], term and interval variables are indexed separately,
+//! since the kernel's `⟨i⟩ t` binds only an interval variable. A name refers
+//! to its innermost binder whichever sort that binder is.
+//!
+//! Only the fragment both sides share converts: lambdas, application, path
+//! lambdas and application, interval constants and operations, and the
+//! universe, function, Π and path types. Anything else is
+//! [`ElabError::Unsupported`].
+//!
+//! [`sctt_typechecker`]: crate::sctt_typechecker
+//! [`surface`]: crate::surface
+
+use std::fmt;
+
+use sctt_core::{Level as NamedLevel, Term as Named, Type as NamedType};
+
+use crate::sctt_typechecker::{DeBruijnIndex, IntervalPoint, Level, Term};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElabError {
+    Unbound(String),
+    /// A term variable used as an interval point
+    WrongSort { name: String, expected: &'static str },
+    /// A construct with no counterpart on the other side
+    Unsupported(String),
+    /// A kernel index past the end of the scope it was converted in
+    OutOfScope(usize),
+}
+
+impl fmt::Display for ElabError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElabError::Unbound(name) => write!(f, "unbound variable `{}`", name),
+            ElabError::WrongSort { name, expected } => write!(f, "`{}` is not {}", name, expected),
+            ElabError::Unsupported(what) => write!(f, "{} cannot be converted", what),
+            ElabError::OutOfScope(index) => write!(f, "variable #{} is not in scope", index),
+        }
+    }
+}
+
+impl std::error::Error for ElabError {}
+
+/// Translate a named term to the kernel, with `scope` the term variables
+/// already bound, innermost last
+pub fn to_debruijn(term: &Named, scope: &[String]) -> Result<Term, ElabError> {
+    Binders::new(scope).term(term)
+}
+
+/// Translate a named type to the kernel term for it
+pub fn type_to_debruijn(ty: &NamedType, scope: &[String]) -> Result<Term, ElabError> {
+    Binders::new(scope).ty(ty)
+}
+
+/// Translate a kernel term back to a named one; the free term variables
+/// are named by `scope`, innermost last
+pub fn to_named(term: &Term, scope: &[String]) -> Result<Named, ElabError> {
+    Names::new(scope).term(term)
+}
+
+/// Translate a kernel term in type position back to a named type
+pub fn type_to_named(term: &Term, scope: &[String]) -> Result<NamedType, ElabError> {
+    Names::new(scope).ty(term)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sort {
+    Term,
+    Interval,
+}
+
+/// Binders in scope for `to_debruijn`, both sorts interleaved, innermost last
+struct Binders {
+    bound: Vec<(String, Sort)>,
+}
+
+impl Binders {
+    fn new(scope: &[String]) -> Self {
+        Binders { bound: scope.iter().map(|name| (name.clone(), Sort::Term)).collect() }
+    }
+
+    /// The innermost binder of `name`, indexed among the binders of its sort
+    fn resolve(&self, name: &str) -> Result<(Sort, DeBruijnIndex), ElabError> {
+        let at = self.bound.iter().rposition(|(n, _)| n == name).ok_or_else(|| ElabError::Unbound(name.to_string()))?;
+        let sort = self.bound[at].1;
+        let index = self.bound[at + 1..].iter().filter(|(_, s)| *s == sort).count();
+        Ok((sort, DeBruijnIndex(index)))
+    }
+
+    fn under<T>(
+        &mut self,
+        name: &str,
+        sort: Sort,
+        body: &T,
+        convert: fn(&mut Self, &T) -> Result<Term, ElabError>,
+    ) -> Result<Term, ElabError> {
+        self.bound.push((name.to_string(), sort));
+        let result = convert(self, body);
+        self.bound.pop();
+        result
+    }
+
+    fn term(&mut self, term: &Named) -> Result<Term, ElabError> {
+        Ok(match term {
+            Named::Var(x) => match self.resolve(x)? {
+                (Sort::Term, index) => Term::Var(index),
+                (Sort::Interval, index) => Term::Interval(IntervalPoint::Var(index)),
+            },
+            Named::Lambda { param, param_type, body } => {
+                let domain = self.ty(param_type)?;
+                Term::Lambda(Box::new(domain), Box::new(self.under(param, Sort::Term, &**body, Self::term)?))
+            }
+            Named::App { func, arg } => Term::App(Box::new(self.term(func)?), Box::new(self.term(arg)?)),
+            Named::PathLambda { param, body } => {
+                Term::PathLambda(Box::new(self.under(param, Sort::Interval, &**body, Self::term)?))
+            }
+            Named::PathApp { path, point } => Term::PathApp(Box::new(self.term(path)?), self.point(point)?),
+            Named::IZero | Named::IOne | Named::IMeet(..) | Named::IJoin(..) | Named::INeg(_) => {
+                Term::Interval(self.point(term)?)
+            }
+            Named::Base => Term::Base,
+            Named::Loop => Term::Loop,
+            Named::CircleElim { motive, base_case, loop_case, scrutinee } => Term::CircleElim {
+                motive: Box::new(self.ty(motive)?),
+                base_case: Box::new(self.term(base_case)?),
+                loop_case: Box::new(self.term(loop_case)?),
+                scrutinee: Box::new(self.term(scrutinee)?),
+            },
+            _ => return Err(ElabError::Unsupported(format!("`{}`", term))),
+        })
+    }
+
+    fn ty(&mut self, ty: &NamedType) -> Result<Term, ElabError> {
+        Ok(match ty {
+            NamedType::Universe(NamedLevel::Const(0)) => Term::Universe(Level::Zero),
+            NamedType::Universe(NamedLevel::Const(n)) => Term::Universe(Level::Succ(*n as usize)),
+            NamedType::Function { domain, codomain, is_smooth: false } => {
+                let domain = self.ty(domain)?;
+                // No name can refer to the binder of a non-dependent function
+                Term::Pi(Box::new(domain), Box::new(self.under("", Sort::Term, &**codomain, Self::ty)?))
+            }
+            NamedType::Pi { param, domain, codomain } => {
+                let domain = self.ty(domain)?;
+                Term::Pi(Box::new(domain), Box::new(self.under(param, Sort::Term, &**codomain, Self::ty)?))
+            }
+            NamedType::Path { space, start, end } => Term::PathType(
+                Box::new(self.ty(space)?),
+                Box::new(self.term(start)?),
+                Box::new(self.term(end)?),
+            ),
+            NamedType::Circle => Term::Circle,
+            _ => return Err(ElabError::Unsupported(format!("`{}`", ty))),
+        })
+    }
+
+    fn point(&self, term: &Named) -> Result<IntervalPoint, ElabError> {
+        Ok(match term {
+            Named::IZero => IntervalPoint::Zero,
+            Named::IOne => IntervalPoint::One,
+            Named::Var(x) => match self.resolve(x)? {
+                (Sort::Interval, index) => IntervalPoint::Var(index),
+                (Sort::Term, _) => {
+                    return Err(ElabError::WrongSort { name: x.clone(), expected: "an interval variable" })
+                }
+            },
+            Named::INeg(r) => IntervalPoint::Neg(Box::new(self.point(r)?)),
+            Named::IMeet(r, s) => IntervalPoint::Meet(Box::new(self.point(r)?), Box::new(self.point(s)?)),
+            Named::IJoin(r, s) => IntervalPoint::Join(Box::new(self.point(r)?), Box::new(self.point(s)?)),
+            _ => return Err(ElabError::Unsupported(format!("`{}` as an interval point", term))),
+        })
+    }
+}
+
+/// Names in scope for `to_named`, one stack per sort, innermost last
+struct Names {
+    terms: Vec<String>,
+    intervals: Vec<String>,
+}
+
+impl Names {
+    fn new(scope: &[String]) -> Self {
+        Names { terms: scope.to_vec(), intervals: Vec::new() }
+    }
+
+    fn lookup(names: &[String], DeBruijnIndex(k): DeBruijnIndex) -> Result<String, ElabError> {
+        names.iter().rev().nth(k).cloned().ok_or(ElabError::OutOfScope(k))
+    }
+
+    /// `prefix` followed by the first number, from the depth up, giving a
+    /// name no binder in scope has
+    fn fresh(&self, prefix: &str, depth: usize) -> String {
+        let mut n = depth;
+        loop {
+            let name = format!("{}{}", prefix, n);
+            if !self.terms.contains(&name) && !self.intervals.contains(&name) {
+                return name;
+            }
+            n += 1;
+        }
+    }
+
+    fn under<T, R>(&mut self, sort: Sort, body: &T, convert: fn(&mut Self, &T) -> Result<R, ElabError>) -> Result<(String, R), ElabError> {
+        let name = match sort {
+            Sort::Term => self.fresh("x", self.terms.len()),
+            Sort::Interval => self.fresh("i", self.intervals.len()),
+        };
+        let stack = match sort {
+            Sort::Term => &mut self.terms,
+            Sort::Interval => &mut self.intervals,
+        };
+        stack.push(name.clone());
+        let result = convert(self, body);
+        match sort {
+            Sort::Term => self.terms.pop(),
+            Sort::Interval => self.intervals.pop(),
+        };
+        Ok((name, result?))
+    }
+
+    fn term(&mut self, term: &Term) -> Result<Named, ElabError> {
+        Ok(match term {
+            Term::Var(index) => Named::Var(Self::lookup(&self.terms, *index)?),
+            Term::Lambda(domain, body) => {
+                let param_type = Box::new(self.ty(domain)?);
+                let (param, body) = self.under(Sort::Term, &**body, Self::term)?;
+                Named::Lambda { param, param_type, body: Box::new(body) }
+            }
+            Term::App(f, a) => Named::App { func: Box::new(self.term(f)?), arg: Box::new(self.term(a)?) },
+            Term::PathLambda(body) => {
+                let (param, body) = self.under(Sort::Interval, &**body, Self::term)?;
+                Named::PathLambda { param, body: Box::new(body) }
+            }
+            Term::PathApp(path, r) => Named::PathApp { path: Box::new(self.term(path)?), point: Box::new(self.point(r)?) },
+            Term::Interval(r) => self.point(r)?,
+            Term::Base => Named::Base,
+            Term::Loop => Named::Loop,
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => Named::CircleElim {
+                motive: Box::new(self.ty(motive)?),
+                base_case: Box::new(self.term(base_case)?),
+                loop_case: Box::new(self.term(loop_case)?),
+                scrutinee: Box::new(self.term(scrutinee)?),
+            },
+            Term::Universe(_) | Term::Pi(..) | Term::PathType(..) | Term::Circle => {
+                return Err(ElabError::Unsupported("a type in term position".to_string()))
+            }
+            Term::Transport(..) => return Err(ElabError::Unsupported("transport".to_string())),
+            Term::Hcomp(..) => return Err(ElabError::Unsupported("hcomp".to_string())),
+        })
+    }
+
+    fn ty(&mut self, term: &Term) -> Result<NamedType, ElabError> {
+        Ok(match term {
+            Term::Universe(Level::Zero) => NamedType::Universe(NamedLevel::ZERO),
+            Term::Universe(Level::Succ(n)) => NamedType::Universe(NamedLevel::Const(*n as u32)),
+            Term::Universe(Level::Omega) => return Err(ElabError::Unsupported("the universe Typeω".to_string())),
+            Term::Pi(domain, codomain) if !mentions(codomain, 0) => {
+                let domain = Box::new(self.ty(domain)?);
+                let (_, codomain) = self.under(Sort::Term, &**codomain, Self::ty)?;
+                NamedType::Function { domain, codomain: Box::new(codomain), is_smooth: false }
+            }
+            Term::Pi(domain, codomain) => {
+                let domain = Box::new(self.ty(domain)?);
+                let (param, codomain) = self.under(Sort::Term, &**codomain, Self::ty)?;
+                NamedType::Pi { param, domain, codomain: Box::new(codomain) }
+            }
+            Term::PathType(space, start, end) => NamedType::Path {
+                space: Box::new(self.ty(space)?),
+                start: Box::new(self.term(start)?),
+                end: Box::new(self.term(end)?),
+            },
+            Term::Circle => NamedType::Circle,
+            Term::Var(_) => return Err(ElabError::Unsupported("a type variable".to_string())),
+            _ => return Err(ElabError::Unsupported("a term in type position".to_string())),
+        })
+    }
+
+    fn point(&self, r: &IntervalPoint) -> Result<Named, ElabError> {
+        Ok(match r {
+            IntervalPoint::Zero => Named::IZero,
+            IntervalPoint::One => Named::IOne,
+            IntervalPoint::Var(index) => Named::Var(Self::lookup(&self.intervals, *index)?),
+            IntervalPoint::Neg(r) => Named::INeg(Box::new(self.point(r)?)),
+            IntervalPoint::Meet(r, s) => Named::IMeet(Box::new(self.point(r)?), Box::new(self.point(s)?)),
+            IntervalPoint::Join(r, s) => Named::IJoin(Box::new(self.point(r)?), Box::new(self.point(s)?)),
+        })
+    }
+}
+
+/// Whether term variable `k` occurs in `term`
+fn mentions(term: &Term, k: usize) -> bool {
+    match term {
+        Term::Var(DeBruijnIndex(i)) => *i == k,
+        Term::Universe(_) | Term::Interval(_) | Term::Circle | Term::Base | Term::Loop => false,
+        Term::Lambda(a, b) | Term::Pi(a, b) => mentions(a, k) || mentions(b, k + 1),
+        Term::App(f, a) => mentions(f, k) || mentions(a, k),
+        Term::PathType(a, x, y) => mentions(a, k) || mentions(x, k) || mentions(y, k),
+        Term::PathLambda(body) | Term::PathApp(body, _) => mentions(body, k),
+        Term::Transport(line, _, _, base) => mentions(line, k) || mentions(base, k),
+        Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+            [motive, base_case, loop_case, scrutinee].iter().any(|t| mentions(t, k))
+        }
+        Term::Hcomp(ty, faces, base) => {
+            mentions(ty, k) || faces.iter().any(|(_, _, tube)| mentions(tube, k)) || mentions(base, k)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sctt_core::simp::parse_term;
+
+    fn scope(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_named_terms_round_trip() {
+        let ctx = scope(&["p", "f"]);
+        for source in [
+            "λ(x : Type). λ(y : Type → Type). y x",
+            "⟨i⟩ p @ (¬i ∧ 1)",
+            "λ(q : Path Type f f). ⟨j⟩ ⟨k⟩ q @ (j ∨ ¬k)",
+            "⟨i⟩ i",
+            "λ(s : S¹). λ(l : Path S¹ s s). ⟨i⟩ l @ i",
+        ] {
+            let named = parse_term(source).unwrap();
+            let kernel = to_debruijn(&named, &ctx).unwrap();
+            let back = to_named(&kernel, &ctx).unwrap();
+            assert!(back.alpha_eq(&named), "{} came back as {}", named, back);
+            assert_eq!(to_debruijn(&back, &ctx).unwrap(), kernel);
+        }
+    }
+
+    #[test]
+    fn test_shadowing_resolves_to_the_innermost_binder() {
+        let shadowed = parse_term("λ(x : Type). λ(x : Type). x").unwrap();
+        let Term::Lambda(_, outer) = to_debruijn(&shadowed, &[]).unwrap() else { panic!("not a lambda") };
+        assert_eq!(*outer, Term::Lambda(Box::new(Term::Universe(Level::Zero)), Box::new(Term::Var(DeBruijnIndex(0)))));
+
+        // An interval binder shadows a term variable of the same name
+        let across = parse_term("λ(i : Type). ⟨i⟩ i").unwrap();
+        assert_eq!(
+            to_debruijn(&across, &[]).unwrap(),
+            Term::Lambda(
+                Box::new(Term::Universe(Level::Zero)),
+                Box::new(Term::PathLambda(Box::new(Term::Interval(IntervalPoint::Var(DeBruijnIndex(0)))))),
+            )
+        );
+
+        // Fresh names avoid the ones in scope
+        let kernel = Term::Lambda(Box::new(Term::Universe(Level::Zero)), Box::new(Term::Var(DeBruijnIndex(1))));
+        assert_eq!(to_named(&kernel, &scope(&["x1"])).unwrap().to_string(), "λx2. x1");
+    }
+
+    #[test]
+    fn test_errors_name_the_problem() {
+        assert_eq!(to_debruijn(&parse_term("λ(x : Type). y").unwrap(), &[]), Err(ElabError::Unbound("y".to_string())));
+        assert_eq!(
+            to_debruijn(&parse_term("λ(x : Type). p @ x").unwrap(), &scope(&["p"])),
+            Err(ElabError::WrongSort { name: "x".to_string(), expected: "an interval variable" })
+        );
+        assert!(matches!(to_debruijn(&parse_term("(a, b)").unwrap(), &scope(&["a", "b"])), Err(ElabError::Unsupported(_))));
+        assert_eq!(to_named(&Term::Var(DeBruijnIndex(0)), &[]), Err(ElabError::OutOfScope(0)));
+    }
+}
@@ -0,0 +1,360 @@
+//! Proof certificates of compiled modules: size, stripping, detached checks
+//!
+//! The compiler embeds its proof certificate as a `proof` custom section,
+//! and a source map may ride along as `srcmap`. This module works on the
+//! encoded bytes only, so it needs neither the compiler nor an encoder:
+//!
+//! - [`size_report`] attributes every byte of a module to code, types,
+//!   proof, srcmap or other; the parts sum to the module's length.
+//! - [`strip_certificates`] drops the proof and srcmap sections, moving
+//!   them into a [`DetachedCertificate`] for the artifact cache.
+//! - [`verify_detached`] checks that a stripped module and a certificate
+//!   belong together.
+//!
+//! The two halves link both ways: the stripped module carries a
+//! `proof-link` section holding the certificate's [`digest`], and the
+//! certificate records the BLAKE3 hash of the stripped module (and of the
+//! certified one it came from). Swapping either file breaks a link.
+//!
+//! [`digest`]: DetachedCertificate::digest
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use sctt_core::compat::{self, Format, Verdict, ENGINE_VERSION};
+
+use crate::api::SizeBreakdown;
+
+pub const PROOF_SECTION: &str = "proof";
+pub const SRCMAP_SECTION: &str = "srcmap";
+/// Custom section of a stripped module naming its certificate
+pub const LINK_SECTION: &str = "proof-link";
+
+const MAGIC: &[u8; 4] = b"\0asm";
+const HEADER_LEN: usize = 8;
+const CUSTOM: u8 = 0;
+const TYPE: u8 = 1;
+const CODE: u8 = 10;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CertificateError {
+    #[error("not a wasm module")]
+    NotWasm,
+
+    #[error("module is truncated at byte {0}")]
+    Truncated(usize),
+
+    #[error("module still carries its `{0}` section")]
+    NotStripped(String),
+
+    #[error("module has no `proof-link` section")]
+    Unlinked,
+
+    #[error("module links to certificate {linked}, not {digest}")]
+    LinkMismatch { linked: String, digest: String },
+
+    #[error("certificate was issued for module {expected}, not {actual}")]
+    HashMismatch { expected: String, actual: String },
+
+    /// Issued by an engine whose certificates this one does not accept
+    #[error("{0}")]
+    Incompatible(Verdict),
+}
+
+pub type Result<T> = std::result::Result<T, CertificateError>;
+
+/// The proof (and srcmap) sections of a module, stored apart from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetachedCertificate {
+    /// BLAKE3 of the module the sections were cut from
+    pub certified_hash: String,
+    /// BLAKE3 of the stripped module, `proof-link` section included
+    pub stripped_hash: String,
+    /// Custom sections as (name, payload), in module order
+    pub sections: Vec<(String, Vec<u8>)>,
+    /// Engine that compiled the module; empty in certificates from before
+    /// it was recorded
+    #[serde(default)]
+    pub engine_version: String,
+}
+
+impl DetachedCertificate {
+    /// What the stripped module's `proof-link` names: a hash of everything
+    /// but `stripped_hash`, which is only known once the link is written
+    pub fn digest(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.certified_hash.as_bytes());
+        hasher.update(self.engine_version.as_bytes());
+        for (name, data) in &self.sections {
+            hasher.update(&(name.len() as u64).to_le_bytes());
+            hasher.update(name.as_bytes());
+            hasher.update(&(data.len() as u64).to_le_bytes());
+            hasher.update(data);
+        }
+        hasher.finalize().to_hex().to_string()
+    }
+}
+
+/// A stripped module and the certificate cut from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stripped {
+    pub wasm: Vec<u8>,
+    pub certificate: DetachedCertificate,
+}
+
+/// One top-level section: its id, custom name, and encoded bytes from the
+/// id byte to the end of the payload
+struct Section<'a> {
+    id: u8,
+    name: Option<String>,
+    bytes: &'a [u8],
+}
+
+fn leb_u32(bytes: &[u8], at: &mut usize) -> Result<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*at).ok_or(CertificateError::Truncated(*at))?;
+        *at += 1;
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(CertificateError::Truncated(*at))
+}
+
+fn push_leb(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn sections(wasm: &[u8]) -> Result<Vec<Section<'_>>> {
+    if wasm.len() < HEADER_LEN || &wasm[..4] != MAGIC {
+        return Err(CertificateError::NotWasm);
+    }
+    let mut sections = Vec::new();
+    let mut at = HEADER_LEN;
+    while at < wasm.len() {
+        let start = at;
+        let id = wasm[at];
+        at += 1;
+        let len = leb_u32(wasm, &mut at)? as usize;
+        let end = at.checked_add(len).filter(|&end| end <= wasm.len()).ok_or(CertificateError::Truncated(at))?;
+        let name = if id == CUSTOM {
+            let name_len = leb_u32(wasm, &mut at)? as usize;
+            let name = wasm.get(at..at + name_len).ok_or(CertificateError::Truncated(at))?;
+            Some(String::from_utf8_lossy(name).into_owned())
+        } else {
+            None
+        };
+        sections.push(Section { id, name, bytes: &wasm[start..end] });
+        at = end;
+    }
+    Ok(sections)
+}
+
+fn custom_section(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    push_leb(&mut payload, name.len());
+    payload.extend_from_slice(name.as_bytes());
+    payload.extend_from_slice(data);
+    let mut section = vec![CUSTOM];
+    push_leb(&mut section, payload.len());
+    section.extend(payload);
+    section
+}
+
+/// The payload of a custom section, after its name
+fn custom_payload<'a>(section: &Section<'a>) -> &'a [u8] {
+    let name_len = section.name.as_ref().map_or(0, |n| n.len());
+    let mut at = 1;
+    let _ = leb_u32(section.bytes, &mut at);
+    let mut name_at = at;
+    let _ = leb_u32(section.bytes, &mut name_at);
+    &section.bytes[name_at + name_len..]
+}
+
+fn is_certificate(section: &Section) -> bool {
+    matches!(section.name.as_deref(), Some(PROOF_SECTION | SRCMAP_SECTION))
+}
+
+pub fn hash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// Encoded bytes by section; each section counts with its id and length
+/// prefix, and the 8-byte header counts as other
+pub fn size_report(wasm: &[u8]) -> Result<SizeBreakdown> {
+    let mut size = SizeBreakdown { other: HEADER_LEN, total: wasm.len(), ..SizeBreakdown::default() };
+    for section in sections(wasm)? {
+        let bucket = match (section.id, section.name.as_deref()) {
+            (CODE, _) => &mut size.code,
+            (TYPE, _) => &mut size.types,
+            (CUSTOM, Some(PROOF_SECTION)) => &mut size.proof,
+            (CUSTOM, Some(SRCMAP_SECTION)) => &mut size.srcmap,
+            _ => &mut size.other,
+        };
+        *bucket += section.bytes.len();
+    }
+    Ok(size)
+}
+
+/// Cut the proof and srcmap sections out into a detached certificate and
+/// link the stripped module to it
+pub fn strip_certificates(wasm: &[u8]) -> Result<Stripped> {
+    let sections = sections(wasm)?;
+    let mut stripped = wasm[..HEADER_LEN].to_vec();
+    let mut detached = Vec::new();
+    for section in &sections {
+        if is_certificate(section) {
+            detached.push((section.name.clone().unwrap_or_default(), custom_payload(section).to_vec()));
+        } else if section.name.as_deref() != Some(LINK_SECTION) {
+            stripped.extend_from_slice(section.bytes);
+        }
+    }
+
+    let mut certificate = DetachedCertificate {
+        certified_hash: hash(wasm),
+        stripped_hash: String::new(),
+        sections: detached,
+        engine_version: ENGINE_VERSION.to_string(),
+    };
+    stripped.extend(custom_section(LINK_SECTION, certificate.digest().as_bytes()));
+    certificate.stripped_hash = hash(&stripped);
+    Ok(Stripped { wasm: stripped, certificate })
+}
+
+/// Whether `stripped` is the module `certificate` was cut from, by an
+/// engine this one accepts certificates from
+pub fn verify_detached(stripped: &[u8], certificate: &DetachedCertificate) -> Result<()> {
+    let verdict = compat::assess(Format::Certificate, Some(&certificate.engine_version));
+    if !verdict.is_current() {
+        return Err(CertificateError::Incompatible(verdict));
+    }
+    let sections = sections(stripped)?;
+    if let Some(section) = sections.iter().find(|s| is_certificate(s)) {
+        return Err(CertificateError::NotStripped(section.name.clone().unwrap_or_default()));
+    }
+    let link = sections
+        .iter()
+        .find(|s| s.name.as_deref() == Some(LINK_SECTION))
+        .ok_or(CertificateError::Unlinked)?;
+    let linked = String::from_utf8_lossy(custom_payload(link)).into_owned();
+    let digest = certificate.digest();
+    if linked != digest {
+        return Err(CertificateError::LinkMismatch { linked, digest });
+    }
+    let actual = hash(stripped);
+    if actual != certificate.stripped_hash {
+        return Err(CertificateError::HashMismatch { expected: certificate.stripped_hash.clone(), actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(id: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![id];
+        push_leb(&mut bytes, payload.len());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    /// `() -> i64` returning `n`, with a proof certificate and a source map
+    fn module(n: u8, proof: &[u8]) -> Vec<u8> {
+        let mut wasm = b"\0asm\x01\0\0\0".to_vec();
+        wasm.extend(section(TYPE, &[0x01, 0x60, 0x00, 0x01, 0x7e]));
+        wasm.extend(section(3, &[0x01, 0x00]));
+        wasm.extend(section(CODE, &[0x01, 0x04, 0x00, 0x42, n, 0x0b]));
+        wasm.extend(custom_section(PROOF_SECTION, proof));
+        wasm.extend(custom_section(SRCMAP_SECTION, b"{\"version\":3}"));
+        wasm
+    }
+
+    #[test]
+    fn test_breakdown_sums_to_the_encoded_size() {
+        let wasm = module(7, &[0xaa; 300]);
+        let size = size_report(&wasm).unwrap();
+        assert_eq!(size.code + size.types + size.proof + size.srcmap + size.other, wasm.len());
+        assert_eq!(size.total, wasm.len());
+        assert_eq!((size.types, size.code), (7, 8));
+        // Id, two length bytes, name length, name, payload
+        assert_eq!(size.proof, 1 + 2 + 1 + PROOF_SECTION.len() + 300);
+        assert_eq!(size.other, 8 + 4);
+
+        assert_eq!(size_report(b"\0asm\x01\0\0\0").unwrap().other, 8);
+        assert_eq!(size_report(b"ELF"), Err(CertificateError::NotWasm));
+        assert!(matches!(size_report(&wasm[..wasm.len() - 1]), Err(CertificateError::Truncated(_))));
+    }
+
+    #[test]
+    fn test_stripping_removes_the_certificate_sections() {
+        let wasm = module(7, &[0xaa; 300]);
+        let stripped = strip_certificates(&wasm).unwrap();
+        let size = size_report(&stripped.wasm).unwrap();
+        assert_eq!((size.proof, size.srcmap), (0, 0));
+        assert_eq!(size.code, size_report(&wasm).unwrap().code);
+        assert!(stripped.wasm.len() < wasm.len());
+
+        let names: Vec<&str> = stripped.certificate.sections.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, [PROOF_SECTION, SRCMAP_SECTION]);
+        assert_eq!(stripped.certificate.sections[0].1, vec![0xaa; 300]);
+        assert_eq!(stripped.certificate.certified_hash, hash(&wasm));
+
+        // Certificates go to disk as JSON
+        let json = serde_json::to_string(&stripped.certificate).unwrap();
+        assert_eq!(serde_json::from_str::<DetachedCertificate>(&json).unwrap(), stripped.certificate);
+    }
+
+    #[test]
+    fn test_detached_verification_needs_the_matching_pair() {
+        let a = strip_certificates(&module(7, b"proof of a")).unwrap();
+        let b = strip_certificates(&module(9, b"proof of b")).unwrap();
+        assert_eq!(verify_detached(&a.wasm, &a.certificate), Ok(()));
+        assert_eq!(verify_detached(&b.wasm, &b.certificate), Ok(()));
+
+        // Either file swapped
+        assert!(matches!(verify_detached(&a.wasm, &b.certificate), Err(CertificateError::LinkMismatch { .. })));
+        assert!(matches!(verify_detached(&b.wasm, &a.certificate), Err(CertificateError::LinkMismatch { .. })));
+
+        // Same code, different proof: the link names another certificate
+        let forged = strip_certificates(&module(7, b"another proof")).unwrap();
+        assert_eq!(forged.wasm.len(), a.wasm.len());
+        assert!(verify_detached(&forged.wasm, &a.certificate).is_err());
+
+        // A certificate edited to match a foreign link still fails on the hash
+        let mut relinked = b.certificate.clone();
+        relinked.stripped_hash = a.certificate.stripped_hash.clone();
+        assert!(matches!(verify_detached(&b.wasm, &relinked), Err(CertificateError::HashMismatch { .. })));
+
+        let unstripped = module(7, b"proof of a");
+        assert_eq!(verify_detached(&unstripped, &a.certificate), Err(CertificateError::NotStripped(PROOF_SECTION.to_string())));
+    }
+
+    #[test]
+    fn test_certificates_from_an_incompatible_engine_are_refused() {
+        let current = strip_certificates(&module(7, b"proof")).unwrap();
+        assert_eq!(current.certificate.engine_version, ENGINE_VERSION);
+
+        // Written before certificates recorded their engine
+        let json = r#"{"certified_hash":"a","stripped_hash":"b","sections":[]}"#;
+        let old: DetachedCertificate = serde_json::from_str(json).unwrap();
+        let err = verify_detached(&current.wasm, &old).unwrap_err();
+        assert!(matches!(&err, CertificateError::Incompatible(v) if v.action == Some(compat::Policy::Refuse)));
+        assert!(err.to_string().contains("re-compile"), "{}", err);
+
+        // The version is covered by the link, so it cannot be edited to pass
+        let mut relabeled = current.certificate.clone();
+        relabeled.engine_version = format!("{}+local", ENGINE_VERSION);
+        assert!(matches!(verify_detached(&current.wasm, &relabeled), Err(CertificateError::LinkMismatch { .. })));
+    }
+}
@@ -14,6 +14,15 @@ use wasm_bindgen::JsCast;
 use web_sys::{WebSocket, MessageEvent, CloseEvent, ErrorEvent};
 use gloo_timers::callback::Interval;
 
+pub use crate::protocol::{ClientMessage, ProofAction, ServerMessage};
+use crate::presence::{badges, BadgeKind, NodeBadge, Presence};
+use crate::rename;
+
+/// Milliseconds on the browser clock, as [`Presence`] expects
+fn now_ms() -> u64 {
+    js_sys::Date::now() as u64
+}
+
 /// Collaborative editor component
 #[derive(Properties, PartialEq)]
 pub struct CollaborativeEditorProps {
@@ -26,11 +35,26 @@ pub fn collaborative_editor(props: &CollaborativeEditorProps) -> Html {
     let websocket = use_state(|| None::<WebSocket>);
     let local_version = use_state(|| 0u64);
     let pending_ops = use_state(|| VecDeque::<Operation>::new());
+    let driving = use_state(DriverView::default);
+    let presence = use_state(Presence::default);
+    let focus = use_state(|| None::<usize>);
+    let cursor = use_state(|| CursorPosition { line: 0, column: 0 });
+    // Re-render once a second so stale cursors drop and pulses end
+    let clock = use_state(now_ms);
+    {
+        let clock = clock.clone();
+        use_effect_with((), move |_| {
+            let interval = Interval::new(1_000, move || clock.set(now_ms()));
+            move || drop(interval)
+        });
+    }
     
     // Connect to collaboration server
     use_effect_with(props.session_id.clone(), {
         let websocket = websocket.clone();
         let session = session.clone();
+        let driving = driving.clone();
+        let presence = presence.clone();
         
         move |session_id| {
             let ws_url = format!("wss://sctt.example.com/collaborate/{}", session_id);
@@ -39,10 +63,15 @@ pub fn collaborative_editor(props: &CollaborativeEditorProps) -> Html {
                 Ok(ws) => {
                     // Set up message handler
                     let session = session.clone();
+                    let driving = driving.clone();
+                    let presence = presence.clone();
                     let onmessage = Closure::wrap(Box::new(move |e: MessageEvent| {
-                        if let Ok(text) = e.data().dyn_into::<js_sys::JsString>() {
-                            let msg: ServerMessage = serde_json::from_str(&text.as_string().unwrap()).unwrap();
-                            handle_server_message(msg, &session);
+                        let Some(text) = e.data().as_string() else { return };
+                        match serde_json::from_str::<ServerMessage>(&text) {
+                            Ok(msg) => handle_server_message(msg, &session, &driving, &presence),
+                            Err(e) => web_sys::console::error_1(&JsValue::from_str(
+                                &format!("Malformed server message: {}", e),
+                            )),
                         }
                     }) as Box<dyn FnMut(MessageEvent)>);
                     
@@ -91,6 +120,42 @@ pub fn collaborative_editor(props: &CollaborativeEditorProps) -> Html {
         })
     };
     
+    let send = {
+        let websocket = websocket.clone();
+        Callback::from(move |msg: ClientMessage| {
+            if let (Some(ws), Ok(json)) = (&*websocket, serde_json::to_string(&msg)) {
+                let _ = ws.send_with_str(&json);
+            }
+        })
+    };
+    
+    let on_cursor = {
+        let cursor = cursor.clone();
+        let focus = focus.clone();
+        let send = send.clone();
+        Callback::from(move |at: CursorPosition| {
+            cursor.set(at.clone());
+            send.emit(ClientMessage::CursorUpdate { cursor: at, focus: *focus });
+        })
+    };
+    // Focusing a goal, by clicking it or a collaborator's badge on it,
+    // scrolls it into view here and tells everyone else
+    let on_focus = {
+        let focus = focus.clone();
+        let cursor = cursor.clone();
+        let send = send.clone();
+        Callback::from(move |goal: usize| {
+            focus.set(Some(goal));
+            if let Some(card) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.get_element_by_id(&format!("goal-{}", goal)))
+            {
+                card.scroll_into_view();
+            }
+            send.emit(ClientMessage::CursorUpdate { cursor: (*cursor).clone(), focus: Some(goal) });
+        })
+    };
+    
     html! {
         <div class="collaborative-editor">
             <div class="session-header">
@@ -109,12 +174,18 @@ pub fn collaborative_editor(props: &CollaborativeEditorProps) -> Html {
                 <CollaborativeCodeEditor 
                     document={session.document.clone()}
                     on_edit={on_edit}
-                    cursors={get_cursor_positions(&session)}
+                    on_cursor={on_cursor}
+                    cursors={get_cursor_positions(&session, &presence, *clock)}
                 />
                 
                 <div class="proof-panel">
                     <ProofCollaboration 
                         proof_state={session.proof_state.clone()}
+                        driving={(*driving).clone()}
+                        badges={badges(&presence.focuses(*clock), &session.users, *clock)}
+                        focus={*focus}
+                        on_focus={on_focus}
+                        on_message={send}
                     />
                 </div>
             </div>
@@ -148,6 +219,7 @@ fn user_avatar(props: &UserAvatarProps) -> Html {
 struct CollaborativeCodeEditorProps {
     document: Document,
     on_edit: Callback<Operation>,
+    on_cursor: Callback<CursorPosition>,
     cursors: Vec<CursorInfo>,
 }
 
@@ -157,29 +229,65 @@ fn collaborative_code_editor(props: &CollaborativeCodeEditorProps) -> Html {
     
     let on_input = {
         let on_edit = props.on_edit.clone();
+        let on_cursor = props.on_cursor.clone();
         let content = content.clone();
         
         Callback::from(move |e: InputEvent| {
-            let target = e.target().unwrap();
-            let textarea = target.dyn_ref::<web_sys::HtmlTextAreaElement>().unwrap();
+            let Some(textarea) = e.target_dyn_into::<web_sys::HtmlTextAreaElement>() else { return };
             let new_content = textarea.value();
             
             // Calculate operation
             let op = calculate_operation(&content, &new_content);
             on_edit.emit(op);
             
+            if let Ok(Some(caret)) = textarea.selection_start() {
+                on_cursor.emit(cursor_at(&new_content, caret as usize));
+            }
             content.set(new_content);
         })
     };
     
+    // Right-clicking a defined name offers to rename it
+    let renaming = use_state(|| None::<String>);
+    let on_context_menu = {
+        let content = content.clone();
+        let renaming = renaming.clone();
+        Callback::from(move |e: MouseEvent| {
+            let Some(textarea) = e.target_dyn_into::<web_sys::HtmlTextAreaElement>() else { return };
+            let Ok(Some(caret)) = textarea.selection_start() else { return };
+            let defined = crate::cubicaltt::translate(&content).unwrap_or_default();
+            if let Some(word) = word_at(&content, caret as usize).filter(|w| defined.iter().any(|(_, d)| d.name == *w)) {
+                e.prevent_default();
+                renaming.set(Some(word));
+            }
+        })
+    };
+    let on_rename = {
+        let on_edit = props.on_edit.clone();
+        let content = content.clone();
+        let renaming = renaming.clone();
+        Callback::from(move |source: Option<String>| {
+            if let Some(source) = source {
+                on_edit.emit(Operation::Replace { pos: 0, len: content.len(), text: source.clone() });
+                content.set(source);
+            }
+            renaming.set(None);
+        })
+    };
+    
     html! {
         <div class="collab-code-editor">
             <textarea 
                 value={(*content).clone()}
                 oninput={on_input}
+                oncontextmenu={on_context_menu}
                 class="code-area"
             />
             
+            {renaming.as_ref().map(|old| html! {
+                <RenameMenu source={(*content).clone()} old={old.clone()} on_done={on_rename} />
+            }).unwrap_or_default()}
+            
             {props.cursors.iter().map(|cursor| {
                 html! {
                     <div 
@@ -197,27 +305,198 @@ fn collaborative_code_editor(props: &CollaborativeCodeEditorProps) -> Html {
     }
 }
 
+#[derive(Properties, PartialEq)]
+struct RenameMenuProps {
+    source: String,
+    old: String,
+    /// The renamed source, or `None` when cancelled
+    on_done: Callback<Option<String>>,
+}
+
+/// Rename a definition, previewing every occurrence that would change
+#[function_component(RenameMenu)]
+fn rename_menu(props: &RenameMenuProps) -> Html {
+    let new = use_state(String::new);
+    let on_input = {
+        let new = new.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<web_sys::HtmlInputElement>() {
+                new.set(input.value());
+            }
+        })
+    };
+    let planned = (!new.is_empty()).then(|| {
+        rename::plan(&[rename::Document::new("session", &props.source)], &props.old, &new)
+    });
+    let apply = {
+        let on_done = props.on_done.clone();
+        let renamed = planned.as_ref().and_then(|p| p.as_ref().ok()).and_then(|r| r.changed.first().cloned());
+        Callback::from(move |_: MouseEvent| on_done.emit(renamed.as_ref().map(|d| d.source.clone())))
+    };
+    let cancel = {
+        let on_done = props.on_done.clone();
+        Callback::from(move |_: MouseEvent| on_done.emit(None))
+    };
+    
+    html! {
+        <div class="rename-menu">
+            <label>{format!("Rename `{}` to ", props.old)}<input value={(*new).clone()} oninput={on_input} /></label>
+            {match &planned {
+                None => html! {},
+                Some(Ok(rename)) => html! {
+                    <ul class="rename-preview">
+                        {rename.occurrences.iter().map(|o| html! {
+                            <li>{format!("{} in {}", o.location, o.definition)}</li>
+                        }).collect::<Html>()}
+                    </ul>
+                },
+                Some(Err(e)) => html! { <div class="rename-error">{e.to_string()}</div> },
+            }}
+            <button class="btn btn-small" disabled={!matches!(planned, Some(Ok(_)))} onclick={apply}>{"Rename"}</button>
+            <button class="btn btn-small" onclick={cancel}>{"Cancel"}</button>
+        </div>
+    }
+}
+
+/// The identifier around a caret given in UTF-16 units
+fn word_at(text: &str, caret: usize) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut units = 0;
+    let at = chars.iter().position(|c| {
+        let reached = units >= caret;
+        units += c.len_utf16();
+        reached
+    }).unwrap_or(chars.len());
+    let is_word = |c: &char| c.is_alphanumeric() || matches!(c, '_' | '\'');
+    let start = chars[..at].iter().rposition(|c| !is_word(c)).map_or(0, |i| i + 1);
+    let end = chars[at..].iter().position(|c| !is_word(c)).map_or(chars.len(), |i| at + i);
+    (start < end).then(|| chars[start..end].iter().collect())
+}
+
+/// Who drives the shared proof, as this client sees it
+#[derive(Clone, Default, PartialEq)]
+struct DriverView {
+    /// This connection's id, from the server's welcome
+    me: Option<String>,
+    driver: Option<String>,
+    /// A pending control request, shown to the driver
+    requested_by: Option<String>,
+    /// Toast text after a rejected action
+    notice: Option<String>,
+}
+
+impl DriverView {
+    fn is_driving(&self) -> bool {
+        self.me.is_some() && self.me == self.driver
+    }
+}
+
+/// Tactics offered as one-click buttons on each goal
+const GOAL_TACTICS: [&str; 4] = ["intro x", "assumption", "reflexivity", "simp"];
+
 #[derive(Properties, PartialEq)]
 struct ProofCollaborationProps {
     proof_state: ProofState,
+    driving: DriverView,
+    /// Collaborators' focus badges, by goal
+    badges: Vec<NodeBadge>,
+    focus: Option<usize>,
+    on_focus: Callback<usize>,
+    on_message: Callback<ClientMessage>,
 }
 
 #[function_component(ProofCollaboration)]
 fn proof_collaboration(props: &ProofCollaborationProps) -> Html {
+    let driving = &props.driving;
+    let is_driving = driving.is_driving();
+    let request_control = {
+        let on_message = props.on_message.clone();
+        Callback::from(move |_: MouseEvent| on_message.emit(ClientMessage::RequestControl))
+    };
+    
     html! {
         <div class="proof-collaboration">
             <h3>{"Collaborative Proof"}</h3>
             
+            <div class="proof-driver">
+                {match (&driving.driver, is_driving) {
+                    (_, true) => html! { <span class="driver you">{"You are driving"}</span> },
+                    (Some(driver), false) => html! {
+                        <>
+                            <span class="driver">{format!("{} is driving", driver)}</span>
+                            <button class="btn btn-small" onclick={request_control.clone()}>{"Request control"}</button>
+                        </>
+                    },
+                    (None, false) => html! {
+                        <button class="btn btn-small" onclick={request_control.clone()}>{"Take control"}</button>
+                    },
+                }}
+                {match (&driving.requested_by, is_driving) {
+                    (Some(requester), true) => {
+                        let on_message = props.on_message.clone();
+                        let to = requester.clone();
+                        let grant = Callback::from(move |_: MouseEvent| {
+                            on_message.emit(ClientMessage::GrantControl { to: to.clone() })
+                        });
+                        html! {
+                            <span class="control-request">
+                                {format!("{} requests control ", requester)}
+                                <button class="btn btn-small" onclick={grant}>{"Grant"}</button>
+                            </span>
+                        }
+                    }
+                    _ => html! {},
+                }}
+            </div>
+            
+            {driving.notice.as_ref().map(|notice| html! {
+                <div class="toast">
+                    <span>{notice}</span>
+                    if !driving.is_driving() {
+                        <button class="btn btn-small" onclick={request_control.clone()}>{"Request control"}</button>
+                    }
+                </div>
+            }).unwrap_or_default()}
+            
+            // Goals stay browsable for everyone; only the driver gets live tactic buttons
             <div class="proof-goals">
                 {props.proof_state.goals.iter().map(|goal| {
+                    let goal_id = goal.id;
+                    let focus_here = {
+                        let on_focus = props.on_focus.clone();
+                        Callback::from(move |_: MouseEvent| on_focus.emit(goal_id))
+                    };
                     html! {
-                        <div class="goal-card">
-                            <div class="goal-header">
+                        <div
+                            id={format!("goal-{}", goal.id)}
+                            class={classes!("goal-card", (props.focus == Some(goal.id)).then_some("focused"))}
+                        >
+                            <div class="goal-header" onclick={focus_here}>
                                 {format!("Goal {}", goal.id)}
+                                <span class="focus-badges">
+                                    {props.badges.iter().filter(|b| b.node == goal.id).map(|badge| {
+                                        focus_badge(badge, &props.on_focus)
+                                    }).collect::<Html>()}
+                                </span>
                             </div>
                             <div class="goal-content">
                                 {&goal.conclusion}
                             </div>
+                            <div class="goal-tactics">
+                                {GOAL_TACTICS.iter().map(|tactic| {
+                                    let on_message = props.on_message.clone();
+                                    let (goal_id, tactic) = (goal.id, tactic.to_string());
+                                    let label = tactic.clone();
+                                    let apply = Callback::from(move |_: MouseEvent| {
+                                        on_message.emit(ClientMessage::ProofAction {
+                                            action: ProofAction::ApplyTactic { goal_id, tactic: tactic.clone() },
+                                        })
+                                    });
+                                    html! {
+                                        <button class="btn btn-small" disabled={!is_driving} onclick={apply}>{label}</button>
+                                    }
+                                }).collect::<Html>()}
+                            </div>
                         </div>
                     }
                 }).collect::<Html>()}
@@ -238,6 +517,29 @@ fn proof_collaboration(props: &ProofCollaborationProps) -> Html {
     }
 }
 
+/// One collaborator's badge on a goal; clicking it jumps there too
+fn focus_badge(badge: &NodeBadge, on_focus: &Callback<usize>) -> Html {
+    let style = |color: &str| format!("left: {}px; background-color: {}", badge.offset, color);
+    match &badge.kind {
+        BadgeKind::User { initial, color, pulsing, .. } => {
+            let node = badge.node;
+            let on_focus = on_focus.clone();
+            let jump = Callback::from(move |e: MouseEvent| {
+                e.stop_propagation();
+                on_focus.emit(node)
+            });
+            html! {
+                <span class={classes!("focus-badge", pulsing.then_some("pulse"))} style={style(color)} onclick={jump}>
+                    {initial}
+                </span>
+            }
+        }
+        BadgeKind::Overflow { count } => html! {
+            <span class="focus-badge overflow" style={style("var(--text-secondary)")}>{format!("+{}", count)}</span>
+        },
+    }
+}
+
 /// Operational Transformation implementation
 pub struct OperationalTransform;
 
@@ -334,81 +636,12 @@ impl OperationalTransform {
         }
     }
     
-    /// Apply operation to document
+    /// Apply operation to document; out-of-range edits are dropped
     pub fn apply(doc: &mut String, op: &Operation) {
-        match op {
-            Operation::Insert { pos, text } => {
-                doc.insert_str(*pos, text);
-            }
-            Operation::Delete { pos, len } => {
-                doc.drain(*pos..*pos + len);
-            }
-            Operation::Replace { pos, len, text } => {
-                doc.drain(*pos..*pos + len);
-                doc.insert_str(*pos, text);
-            }
-        }
+        op.apply(doc);
     }
 }
 
-/// Server messages
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ServerMessage {
-    Welcome {
-        session: Session,
-        user_id: String,
-    },
-    UserJoined {
-        user: User,
-    },
-    UserLeft {
-        user_id: String,
-    },
-    Operation {
-        op: Operation,
-        user_id: String,
-        version: u64,
-    },
-    CursorUpdate {
-        user_id: String,
-        cursor: CursorPosition,
-    },
-    ProofUpdate {
-        proof_state: ProofState,
-    },
-    Error {
-        message: String,
-    },
-}
-
-/// Client messages
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ClientMessage {
-    Join {
-        user_name: String,
-    },
-    Operation {
-        op: Operation,
-        version: u64,
-    },
-    CursorUpdate {
-        cursor: CursorPosition,
-    },
-    ProofAction {
-        action: ProofAction,
-    },
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ProofAction {
-    ApplyTactic {
-        goal_id: usize,
-        tactic: String,
-    },
-    Undo,
-    Redo,
-}
-
 /// Cursor information for display
 #[derive(Clone, PartialEq)]
 struct CursorInfo {
@@ -420,8 +653,46 @@ struct CursorInfo {
 
 // Helper functions
 
-fn handle_server_message(msg: ServerMessage, session: &UseStateHandle<Session>) {
+fn handle_server_message(
+    msg: ServerMessage,
+    session: &UseStateHandle<Session>,
+    driving: &UseStateHandle<DriverView>,
+    presence: &UseStateHandle<Presence>,
+) {
     match msg {
+        // Our own cursor comes back in the broadcast too
+        ServerMessage::CursorUpdate { ref user_id, .. } if driving.me.as_ref() == Some(user_id) => {}
+        ServerMessage::CursorUpdate { .. } => {
+            let mut p = (**presence).clone();
+            let now = now_ms();
+            p.observe(&msg, now);
+            p.expire(now);
+            presence.set(p);
+        }
+        ServerMessage::Welcome { user_id, .. } => {
+            driving.set(DriverView { me: Some(user_id), ..(**driving).clone() });
+        }
+        ServerMessage::ProofUpdate { proof_state } => {
+            let mut s = (**session).clone();
+            s.proof_state = proof_state;
+            session.set(s);
+        }
+        ServerMessage::DriverChanged { driver, .. } => {
+            driving.set(DriverView { driver, requested_by: None, notice: None, ..(**driving).clone() });
+        }
+        ServerMessage::ControlRequested { user_id } => {
+            driving.set(DriverView { requested_by: Some(user_id), ..(**driving).clone() });
+        }
+        ServerMessage::NotDriving { driver } => {
+            let notice = match &driver {
+                Some(driver) => format!("{} is driving the proof", driver),
+                None => "Nobody is driving the proof".to_string(),
+            };
+            driving.set(DriverView { driver, notice: Some(notice), ..(**driving).clone() });
+        }
+        ServerMessage::Busy { message } => {
+            driving.set(DriverView { notice: Some(message), ..(**driving).clone() });
+        }
         ServerMessage::Operation { op, .. } => {
             apply_operation(session, &op);
         }
@@ -431,6 +702,9 @@ fn handle_server_message(msg: ServerMessage, session: &UseStateHandle<Session>)
             session.set(s);
         }
         ServerMessage::UserLeft { user_id } => {
+            let mut p = (**presence).clone();
+            p.forget(&user_id);
+            presence.set(p);
             let mut s = (**session).clone();
             s.users.retain(|u| u.id != user_id);
             session.set(s);
@@ -447,6 +721,24 @@ fn apply_operation(session: &UseStateHandle<Session>, op: &Operation) {
     session.set(s);
 }
 
+/// Line and column of a caret given in UTF-16 units, as the DOM reports it
+fn cursor_at(text: &str, caret: usize) -> CursorPosition {
+    let mut units = 0;
+    let mut at = CursorPosition { line: 0, column: 0 };
+    for c in text.chars() {
+        if units >= caret {
+            break;
+        }
+        units += c.len_utf16();
+        if c == '\n' {
+            at = CursorPosition { line: at.line + 1, column: 0 };
+        } else {
+            at.column += 1;
+        }
+    }
+    at
+}
+
 fn calculate_operation(old: &str, new: &str) -> Operation {
     // Simplified diff - would use proper diff algorithm
     if new.len() > old.len() {
@@ -468,13 +760,14 @@ fn calculate_operation(old: &str, new: &str) -> Operation {
     }
 }
 
-fn get_cursor_positions(session: &Session) -> Vec<CursorInfo> {
-    session.users.iter().map(|user| {
+fn get_cursor_positions(session: &Session, presence: &Presence, now: u64) -> Vec<CursorInfo> {
+    presence.cursors(now).into_iter().map(|(id, cursor)| {
+        let user = session.users.iter().find(|u| u.id == id);
         CursorInfo {
-            user_name: user.name.clone(),
-            x: (user.cursor.column * 8) as i32,
-            y: (user.cursor.line * 20) as i32,
-            color: user.color.clone(),
+            user_name: user.map_or_else(|| id.to_string(), |u| u.name.clone()),
+            x: (cursor.column * 8) as i32,
+            y: (cursor.line * 20) as i32,
+            color: user.map_or_else(|| crate::presence::avatar_color(id).to_string(), |u| u.color.clone()),
         }
     }).collect()
 }
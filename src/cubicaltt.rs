@@ -0,0 +1,743 @@
+//! Import of a cubicaltt subset
+//!
+//! Translates small cubicaltt files (the refl, funExt and hcomp examples
+//! people bring from cubicaltt or Cubical Agda) into [`surface`] definitions.
+//! Anything outside the subset is reported with its location; a file is
+//! translated completely or not at all.
+//!
+//! Supported:
+//! - `module M where` headers and `--` / `{- -}` comments, which are dropped
+//! - definitions `name (x y : A) (z : B) : T = body`
+//! - `U`, Π types `(x : A) -> B` and arrows `A -> B`
+//! - lambdas `\(x : A) -> b` and `\x -> b`, and application
+//! - `Path A a b`, and `PathP (<i> A) a b` when `A` does not mention `i`
+//! - path lambdas `<i j> b`, applications `p @ r` with `0`, `1`, `-r`,
+//!   `r /\ s` and `r \/ s`
+//! - `comp (<i> A) a []` and `transp (<i> A) 0 a` as transport from 0 to 1,
+//!   `transport p a` for `p : Path U A B`
+//! - `hcomp A a [ (i = 0) -> u ]`, and `comp` with faces when the line is
+//!   constant
+//!
+//! Data types (higher inductive or not), `split`, `let`/`where`, Σ types,
+//! Glue types, `fill`, `undefined` and `import` are rejected.
+//!
+//! [`surface`]: crate::surface
+
+use std::fmt;
+
+use crate::surface::{self, Definition, Face, Interval, Surface, SurfaceError};
+
+/// A 1-based line and column in the imported file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Why part of a file could not be translated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub location: Location,
+    /// The construct, e.g. `higher inductive type`; `None` for syntax errors
+    pub construct: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.construct {
+            Some(construct) => write!(f, "{}: unsupported {}: {}", self.location, construct, self.message),
+            None => write!(f, "{}: {}", self.location, self.message),
+        }
+    }
+}
+
+/// Every problem in the file, in order; nothing is translated
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub problems: Vec<Problem>,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "cannot import: {} problem(s)", self.problems.len())?;
+        for problem in &self.problems {
+            writeln!(f, "  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// A translated definition and whether it checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Imported {
+    pub definition: Definition,
+    pub location: Location,
+    pub checked: Result<(), SurfaceError>,
+}
+
+/// A translated file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub definitions: Vec<Imported>,
+}
+
+impl Import {
+    /// The translation as `.sctt` source
+    pub fn source(&self) -> String {
+        let defs: Vec<String> = self.definitions.iter().map(|d| d.definition.to_string()).collect();
+        defs.join("\n\n") + "\n"
+    }
+
+    pub fn all_checked(&self) -> bool {
+        self.definitions.iter().all(|d| d.checked.is_ok())
+    }
+
+    /// One line per definition: where it came from and whether it checks
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        for d in &self.definitions {
+            let verdict = match &d.checked {
+                Ok(()) => "checks".to_string(),
+                Err(e) => format!("does not check: {}", e),
+            };
+            out.push_str(&format!("{} {} {}\n", d.location, d.definition.name, verdict));
+        }
+        let ok = self.definitions.iter().filter(|d| d.checked.is_ok()).count();
+        out.push_str(&format!("{} of {} definitions check\n", ok, self.definitions.len()));
+        out
+    }
+}
+
+/// Parse and translate without checking
+pub fn translate(source: &str) -> Result<Vec<(Location, Definition)>, ImportError> {
+    Ok(parse(source)?.into_iter().map(|p| (p.location, p.definition)).collect())
+}
+
+/// A definition with where its names are in the file
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Parsed {
+    pub location: Location,
+    pub definition: Definition,
+    /// Where the defined name is written
+    pub name_at: Location,
+    /// Variables no binder of the definition captures, in source order:
+    /// these resolve to earlier definitions
+    pub free: Vec<(Location, String)>,
+}
+
+pub(crate) fn parse(source: &str) -> Result<Vec<Parsed>, ImportError> {
+    let mut definitions = Vec::new();
+    let mut problems = Vec::new();
+    for block in blocks(source) {
+        match parse_block(&block) {
+            Ok(Some(parsed)) => definitions.push(parsed),
+            Ok(None) => {}
+            Err(problem) => problems.push(problem),
+        }
+    }
+    if problems.is_empty() { Ok(definitions) } else { Err(ImportError { problems }) }
+}
+
+/// Could `word` name a definition?
+pub(crate) fn is_definition_name(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '\''))
+        && !KEYWORDS.contains(&word)
+        && !UNSUPPORTED.iter().any(|(w, _)| *w == word)
+}
+
+/// Translate, then check every definition with the kernel
+pub fn import(source: &str) -> Result<Import, ImportError> {
+    let translated = translate(source)?;
+    let defs: Vec<Definition> = translated.iter().map(|(_, d)| d.clone()).collect();
+    let definitions = translated
+        .into_iter()
+        .zip(surface::check(&defs))
+        .map(|((location, definition), checked)| Imported { definition, location, checked })
+        .collect();
+    Ok(Import { definitions })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Num(u32),
+    Sym(&'static str),
+}
+
+type Spanned = (Location, Tok);
+
+/// One top-level declaration: a line starting in column 1 and the indented
+/// lines after it
+struct Block {
+    tokens: Vec<Spanned>,
+    end: Location,
+}
+
+impl Block {
+    fn location(&self) -> Location {
+        self.tokens.first().map_or(self.end, |(l, _)| *l)
+    }
+}
+
+const SYMBOLS: [&str; 17] = [
+    "->", "/\\", "\\/", "→", "λ", "(", ")", "[", "]", "<", ">", ":", "=", "@", ",", "-", "\\",
+];
+
+/// Words the parser reads as syntax rather than names
+const KEYWORDS: [&str; 9] = ["module", "data", "U", "Path", "PathP", "comp", "hcomp", "transp", "transport"];
+
+/// Words whose constructs are outside the subset
+const UNSUPPORTED: [(&str, &str); 17] = [
+    ("split", "pattern-matching function"),
+    ("let", "local definition"),
+    ("in", "local definition"),
+    ("where", "local definition"),
+    ("Glue", "Glue type"),
+    ("glue", "Glue type"),
+    ("unglue", "Glue type"),
+    ("fill", "fill"),
+    ("undefined", "undefined"),
+    ("import", "import"),
+    ("mutual", "mutual block"),
+    ("opaque", "opacity directive"),
+    ("transparent", "opacity directive"),
+    ("transparent_all", "opacity directive"),
+    ("Id", "identity type"),
+    ("idJ", "identity type"),
+    ("PathS", "PathS"),
+];
+
+fn blocks(source: &str) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut in_comment = 0usize;
+    for (n, line) in source.lines().enumerate() {
+        let starts_block = in_comment == 0 && line.chars().next().is_some_and(|c| !c.is_whitespace());
+        let tokens = tokenize_line(line, n + 1, &mut in_comment);
+        if starts_block && !tokens.is_empty() {
+            blocks.push(Block { tokens, end: Location { line: n + 1, column: line.chars().count() + 1 } });
+        } else if let Some(block) = blocks.last_mut() {
+            block.tokens.extend(tokens);
+            block.end = Location { line: n + 1, column: line.chars().count() + 1 };
+        } else if let Some((location, _)) = tokens.first() {
+            // Indented text before any declaration
+            blocks.push(Block { tokens: tokens.clone(), end: *location });
+        }
+    }
+    blocks
+}
+
+fn tokenize_line(line: &str, number: usize, in_comment: &mut usize) -> Vec<Spanned> {
+    let chars: Vec<char> = line.chars().collect();
+    let rest = |i: usize| -> String { chars[i..].iter().collect() };
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let at = Location { line: number, column: i + 1 };
+        let tail = rest(i);
+        if *in_comment > 0 {
+            if tail.starts_with("-}") {
+                *in_comment -= 1;
+                i += 2;
+            } else if tail.starts_with("{-") {
+                *in_comment += 1;
+                i += 2;
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if tail.starts_with("--") {
+            break;
+        } else if tail.starts_with("{-") {
+            *in_comment += 1;
+            i += 2;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            tokens.push((at, Tok::Num(digits.parse().unwrap_or(u32::MAX))));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '\'' | '.')) {
+                i += 1;
+            }
+            tokens.push((at, Tok::Ident(chars[start..i].iter().collect())));
+        } else if let Some(sym) = SYMBOLS.iter().find(|s| tail.starts_with(**s)) {
+            tokens.push((at, Tok::Sym(sym)));
+            i += sym.chars().count();
+        } else {
+            // Unknown characters become single-character symbols the parser rejects
+            tokens.push((at, Tok::Ident(c.to_string())));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+fn problem(location: Location, message: impl Into<String>) -> Problem {
+    Problem { location, construct: None, message: message.into() }
+}
+
+fn unsupported(location: Location, construct: &str, message: impl Into<String>) -> Problem {
+    Problem { location, construct: Some(construct.to_string()), message: message.into() }
+}
+
+/// `None` for a module header, which carries nothing to translate
+fn parse_block(block: &Block) -> Result<Option<Parsed>, Problem> {
+    let mut p = Parser { tokens: &block.tokens, pos: 0, end: block.end, locals: Vec::new(), free: Vec::new() };
+    match p.peek() {
+        Some(Tok::Ident(word)) if word == "module" => return Ok(None),
+        Some(Tok::Ident(word)) if word == "data" => {
+            let location = p.location();
+            let higher = block.tokens.iter().any(|(_, t)| *t == Tok::Sym("<"));
+            return Err(if higher {
+                unsupported(location, "higher inductive type", "path constructors have no counterpart here")
+            } else {
+                unsupported(location, "data type", "only definitions can be imported")
+            });
+        }
+        _ => {}
+    }
+    let name_at = p.location();
+    let definition = p.definition()?;
+    match p.tokens.get(p.pos) {
+        Some((location, _)) => Err(problem(*location, "unexpected input after the definition")),
+        None => Ok(Some(Parsed { location: block.location(), definition, name_at, free: p.free })),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Spanned],
+    pos: usize,
+    end: Location,
+    /// Term binders around the current position, innermost last
+    locals: Vec<String>,
+    free: Vec<(Location, String)>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Tok> {
+        self.tokens.get(self.pos + offset).map(|(_, t)| t)
+    }
+
+    fn location(&self) -> Location {
+        self.tokens.get(self.pos).map_or(self.end, |(l, _)| *l)
+    }
+
+    fn eat(&mut self, sym: &str) -> bool {
+        let found = matches!(self.peek(), Some(Tok::Sym(s)) if *s == sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_arrow(&mut self) -> bool {
+        self.eat("->") || self.eat("→")
+    }
+
+    fn expect(&mut self, sym: &str) -> Result<(), Problem> {
+        if self.eat(sym) { Ok(()) } else { Err(problem(self.location(), format!("expected `{}`", sym))) }
+    }
+
+    fn ident(&mut self) -> Result<String, Problem> {
+        let location = self.location();
+        match self.peek().cloned() {
+            Some(Tok::Ident(name)) => {
+                check_word(location, &name)?;
+                if !name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_') {
+                    return Err(problem(location, format!("unexpected `{}`", name)));
+                }
+                if name.contains('.') {
+                    return Err(unsupported(location, "projection", format!("`{}` uses Σ-type projections", name)));
+                }
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(problem(location, "expected a name")),
+        }
+    }
+
+    fn definition(&mut self) -> Result<Definition, Problem> {
+        let name = self.ident()?;
+        let params = self.telescope()?;
+        self.expect(":")?;
+        let ty = self.expr()?;
+        self.expect("=")?;
+        let body = self.expr()?;
+        Ok(Definition { name, params, ty, body })
+    }
+
+    /// Is the next `(` a binder group `(x y : A)`?
+    fn at_binder(&self) -> bool {
+        if !matches!(self.peek(), Some(Tok::Sym("("))) {
+            return false;
+        }
+        let mut k = 1;
+        while let Some(Tok::Ident(_)) = self.peek_at(k) {
+            k += 1;
+        }
+        k > 1 && matches!(self.peek_at(k), Some(Tok::Sym(":")))
+    }
+
+    fn telescope(&mut self) -> Result<Vec<(String, Surface)>, Problem> {
+        let mut params = Vec::new();
+        while self.at_binder() {
+            self.expect("(")?;
+            let mut names = Vec::new();
+            while let Some(Tok::Ident(_)) = self.peek() {
+                names.push(self.ident()?);
+            }
+            self.expect(":")?;
+            let ty = self.expr()?;
+            self.expect(")")?;
+            // In scope for later groups and whatever the telescope binds over
+            self.locals.extend(names.iter().cloned());
+            params.extend(names.into_iter().map(|x| (x, ty.clone())));
+        }
+        Ok(params)
+    }
+
+    fn expr(&mut self) -> Result<Surface, Problem> {
+        let scope = self.locals.len();
+        let result = self.binder_or_arrow();
+        self.locals.truncate(scope);
+        result
+    }
+
+    fn binder_or_arrow(&mut self) -> Result<Surface, Problem> {
+        if self.eat("\\") || self.eat("λ") {
+            let params = if self.at_binder() {
+                self.telescope()?.into_iter().map(|(x, a)| (x, Some(Box::new(a)))).collect()
+            } else {
+                let mut names = Vec::new();
+                while let Some(Tok::Ident(_)) = self.peek() {
+                    names.push((self.ident()?, None));
+                }
+                if names.is_empty() {
+                    return Err(problem(self.location(), "expected a lambda parameter"));
+                }
+                self.locals.extend(names.iter().map(|(x, _)| x.clone()));
+                names
+            };
+            if !self.eat_arrow() {
+                return Err(problem(self.location(), "expected `->` after the lambda parameters"));
+            }
+            let body = self.expr()?;
+            return Ok(params.into_iter().rev().fold(body, |b, (x, a)| Surface::Lambda(x, a, Box::new(b))));
+        }
+        if self.eat("<") {
+            let mut names = Vec::new();
+            while let Some(Tok::Ident(_)) = self.peek() {
+                names.push(self.ident()?);
+            }
+            self.expect(">")?;
+            let body = self.expr()?;
+            return Ok(names.into_iter().rev().fold(body, |b, i| Surface::PathLambda(i, Box::new(b))));
+        }
+        if self.at_binder() {
+            let location = self.location();
+            let params = self.telescope()?;
+            if matches!(self.peek(), Some(Tok::Ident(s)) if s == "*") {
+                return Err(unsupported(location, "Σ type", "dependent pairs are not in the kernel"));
+            }
+            if !self.eat_arrow() {
+                return Err(unsupported(location, "Σ type or telescope", "expected `->` after the binders"));
+            }
+            let body = self.expr()?;
+            return Ok(params.into_iter().rev().fold(body, |b, (x, a)| Surface::Pi(x, Box::new(a), Box::new(b))));
+        }
+        let lhs = self.at()?;
+        if self.eat_arrow() {
+            let rhs = self.expr()?;
+            return Ok(Surface::Pi("_".to_string(), Box::new(lhs), Box::new(rhs)));
+        }
+        if let Some(Tok::Ident(star)) = self.peek() {
+            if star == "*" {
+                return Err(unsupported(self.location(), "Σ type", "dependent pairs are not in the kernel"));
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn at(&mut self) -> Result<Surface, Problem> {
+        let mut lhs = self.app()?;
+        while self.eat("@") {
+            lhs = Surface::PathApp(Box::new(lhs), self.interval_atom()?);
+        }
+        Ok(lhs)
+    }
+
+    fn starts_atom(&self) -> bool {
+        match self.peek() {
+            Some(Tok::Ident(name)) => name != "*",
+            Some(Tok::Sym("(")) => true,
+            _ => false,
+        }
+    }
+
+    fn app(&mut self) -> Result<Surface, Problem> {
+        let location = self.location();
+        if let Some(Tok::Ident(head)) = self.peek().cloned() {
+            match head.as_str() {
+                "Path" => {
+                    self.pos += 1;
+                    let (a, x, y) = (self.atom()?, self.atom()?, self.atom()?);
+                    return Ok(Surface::Path(Box::new(a), Box::new(x), Box::new(y)));
+                }
+                "PathP" => {
+                    self.pos += 1;
+                    let line = self.atom()?;
+                    let ty = constant_line(location, line, "PathP")?;
+                    let (x, y) = (self.atom()?, self.atom()?);
+                    return Ok(Surface::Path(Box::new(ty), Box::new(x), Box::new(y)));
+                }
+                "comp" => return self.comp(location),
+                "hcomp" => {
+                    self.pos += 1;
+                    let ty = self.atom()?;
+                    let base = self.atom()?;
+                    let faces = self.system()?;
+                    return Ok(Surface::Hcomp { ty: Box::new(ty), faces, base: Box::new(base) });
+                }
+                "transp" => {
+                    self.pos += 1;
+                    let line = self.atom()?;
+                    let restriction_at = self.location();
+                    if self.interval_atom()? != Interval::Zero {
+                        return Err(unsupported(restriction_at, "transp restriction", "only `transp A 0 a` is supported"));
+                    }
+                    let base = self.atom()?;
+                    return Ok(transport(line, base));
+                }
+                "transport" => {
+                    self.pos += 1;
+                    let (line, base) = (self.atom()?, self.atom()?);
+                    return Ok(transport(line, base));
+                }
+                _ => {}
+            }
+        }
+        let mut lhs = self.atom()?;
+        while self.starts_atom() {
+            lhs = Surface::App(Box::new(lhs), Box::new(self.atom()?));
+        }
+        Ok(lhs)
+    }
+
+    /// `comp line base system`
+    fn comp(&mut self, location: Location) -> Result<Surface, Problem> {
+        self.pos += 1;
+        let line = self.atom()?;
+        let base = self.atom()?;
+        let faces = self.system()?;
+        if faces.is_empty() {
+            return Ok(transport(line, base));
+        }
+        let ty = constant_line(location, line, "comp with faces")?;
+        Ok(Surface::Hcomp { ty: Box::new(ty), faces, base: Box::new(base) })
+    }
+
+    /// `[ (i = 0) -> u, (j = 1) -> v ]`
+    fn system(&mut self) -> Result<Vec<Face>, Problem> {
+        self.expect("[")?;
+        let mut faces = Vec::new();
+        if self.eat("]") {
+            return Ok(faces);
+        }
+        loop {
+            let location = self.location();
+            self.expect("(")?;
+            let lhs = self.interval()?;
+            self.expect("=")?;
+            let rhs = self.interval()?;
+            self.expect(")")?;
+            if !self.eat_arrow() {
+                return Err(problem(self.location(), "expected `->` after the face"));
+            }
+            let tube = self.expr()?;
+            if !matches!(tube, Surface::PathLambda(..)) {
+                return Err(unsupported(location, "face", "each face must be a path lambda `<j> u`"));
+            }
+            faces.push(Face { lhs, rhs, tube });
+            if self.eat("]") {
+                return Ok(faces);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn atom(&mut self) -> Result<Surface, Problem> {
+        let location = self.location();
+        match self.peek().cloned() {
+            Some(Tok::Ident(name)) if name == "U" => {
+                self.pos += 1;
+                Ok(Surface::Universe)
+            }
+            Some(Tok::Ident(_)) => {
+                let name = self.ident()?;
+                if !self.locals.contains(&name) {
+                    self.free.push((location, name.clone()));
+                }
+                Ok(Surface::Var(name))
+            }
+            Some(Tok::Sym("(")) => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                if self.eat(",") {
+                    return Err(unsupported(location, "pair", "dependent pairs are not in the kernel"));
+                }
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some(Tok::Num(_)) => Err(problem(location, "an interval point can only follow `@`")),
+            Some(Tok::Sym(s)) => Err(problem(location, format!("unexpected `{}`", s))),
+            None => Err(problem(location, "unexpected end of definition")),
+        }
+    }
+
+    fn interval(&mut self) -> Result<Interval, Problem> {
+        let mut lhs = self.interval_meet()?;
+        while self.eat("\\/") {
+            lhs = Interval::Join(Box::new(lhs), Box::new(self.interval_meet()?));
+        }
+        Ok(lhs)
+    }
+
+    fn interval_meet(&mut self) -> Result<Interval, Problem> {
+        let mut lhs = self.interval_atom()?;
+        while self.eat("/\\") {
+            lhs = Interval::Meet(Box::new(lhs), Box::new(self.interval_atom()?));
+        }
+        Ok(lhs)
+    }
+
+    fn interval_atom(&mut self) -> Result<Interval, Problem> {
+        let location = self.location();
+        if self.eat("-") {
+            return Ok(Interval::Neg(Box::new(self.interval_atom()?)));
+        }
+        if self.eat("(") {
+            let inner = self.interval()?;
+            self.expect(")")?;
+            return Ok(inner);
+        }
+        match self.peek().cloned() {
+            Some(Tok::Num(0)) => {
+                self.pos += 1;
+                Ok(Interval::Zero)
+            }
+            Some(Tok::Num(1)) => {
+                self.pos += 1;
+                Ok(Interval::One)
+            }
+            Some(Tok::Ident(_)) => Ok(Interval::Var(self.ident()?)),
+            _ => Err(problem(location, "expected an interval point")),
+        }
+    }
+}
+
+fn check_word(location: Location, word: &str) -> Result<(), Problem> {
+    match UNSUPPORTED.iter().find(|(w, _)| *w == word) {
+        Some((_, construct)) => Err(unsupported(location, construct, format!("`{}` is outside the importable subset", word))),
+        None => Ok(()),
+    }
+}
+
+fn transport(line: Surface, base: Surface) -> Surface {
+    Surface::Transport { line: Box::new(line), from: Interval::Zero, to: Interval::One, base: Box::new(base) }
+}
+
+/// The type of a line `<i> A` that does not depend on `i`
+fn constant_line(location: Location, line: Surface, what: &str) -> Result<Surface, Problem> {
+    match line {
+        Surface::PathLambda(i, ty) if !mentions_interval(&ty, &i) => Ok(*ty),
+        _ => Err(unsupported(location, what, "only lines of types that are constant in the interval")),
+    }
+}
+
+fn mentions_interval(term: &Surface, i: &str) -> bool {
+    fn in_point(r: &Interval, i: &str) -> bool {
+        match r {
+            Interval::Zero | Interval::One => false,
+            Interval::Var(j) => j == i,
+            Interval::Neg(r) => in_point(r, i),
+            Interval::Meet(r, s) | Interval::Join(r, s) => in_point(r, i) || in_point(s, i),
+        }
+    }
+    match term {
+        Surface::Var(_) | Surface::Universe => false,
+        Surface::Pi(_, a, b) | Surface::App(a, b) => mentions_interval(a, i) || mentions_interval(b, i),
+        Surface::Lambda(_, a, b) => a.as_ref().is_some_and(|a| mentions_interval(a, i)) || mentions_interval(b, i),
+        Surface::Path(a, x, y) => [a, x, y].iter().any(|t| mentions_interval(t, i)),
+        Surface::PathLambda(j, b) => j != i && mentions_interval(b, i),
+        Surface::PathApp(p, r) => mentions_interval(p, i) || in_point(r, i),
+        Surface::Transport { line, from, to, base } => {
+            mentions_interval(line, i) || in_point(from, i) || in_point(to, i) || mentions_interval(base, i)
+        }
+        Surface::Hcomp { ty, faces, base } => {
+            mentions_interval(ty, i)
+                || mentions_interval(base, i)
+                || faces.iter().any(|f| in_point(&f.lhs, i) || in_point(&f.rhs, i) || mentions_interval(&f.tube, i))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translates_path_syntax() {
+        let source = "module test where\n\n-- a comment\nrefl (A : U) (a : A) : Path A a a = <i> a\n\n\
+                      inv (A : U) (a b : A) (p : Path A a b) : Path A b a =\n  <i> p @ -i\n";
+        let defs = translate(source).unwrap();
+        assert_eq!(defs.len(), 2);
+        assert_eq!(defs[1].0, Location { line: 6, column: 1 });
+        assert_eq!(
+            defs[1].1.to_string(),
+            "def inv (A : Type) (a : A) (b : A) (p : Path A a b) : Path A b a :=\n  ⟨i⟩ p @ ¬i"
+        );
+    }
+
+    #[test]
+    fn test_every_problem_is_reported_with_its_location() {
+        let source = "ok (A : U) : U = A\n\nbad (A : U) : U = let x = A in x\n\n{- block\n comment -}\n\
+                      worse : U = fill (<i> U) U []\n";
+        let err = translate(source).unwrap_err();
+        let locations: Vec<_> = err.problems.iter().map(|p| p.location.to_string()).collect();
+        assert_eq!(locations, ["3:19", "7:13"]);
+        assert_eq!(err.problems[0].construct.as_deref(), Some("local definition"));
+        assert!(err.to_string().contains("7:13: unsupported fill"));
+    }
+
+    #[test]
+    fn test_comp_maps_to_transport_or_hcomp() {
+        let defs = translate("t (A : U) (a : A) : A = comp (<i> A) a []\nh (A : U) (a : A) : A = comp (<i> A) a [ (i = 0) -> <j> a ]\n").unwrap();
+        assert!(matches!(defs[0].1.body, Surface::Transport { .. }));
+        assert!(matches!(defs[1].1.body, Surface::Hcomp { ref faces, .. } if faces.len() == 1));
+
+        let varying = translate("v (P : Path U U U) (a : U) : U = comp (<i> P @ i) a [ (i = 0) -> <j> a ]\n").unwrap_err();
+        assert_eq!(varying.problems[0].construct.as_deref(), Some("comp with faces"));
+    }
+}
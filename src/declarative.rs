@@ -0,0 +1,228 @@
+//! Declarative proof blocks
+//!
+//! A forward-reasoning layer over the tactic script grammar:
+//!
+//! ```text
+//! intro x
+//! have h1 : Path ℝ (x * (x + 1)) (x * x + x) by ring
+//! have h2 : Path ℝ (x * x + x) (x + x * x)
+//!   ring
+//! show Path ℝ (x * (x + 1)) (x + x * x)
+//! from h1, h2
+//! ```
+//!
+//! - `have h : T by t` proves `T` with `t` and adds `h : T` to the goal; an
+//!   indented block may take the place of `by t`
+//! - `show T` checks that the goal is convertible with `T`
+//! - `from h` closes the goal with `exact h`; `from h1, h2, ...` chains path
+//!   facts through `transitivity`
+//!
+//! Blocks are elaborated by [`ProofAssistant::run_declarative`] into plain
+//! tactics, so replay, export and the kernel never see this syntax.
+//!
+//! [`ProofAssistant::run_declarative`]: crate::proof_assistant::ProofAssistant::run_declarative
+
+use std::fmt;
+
+use crate::proof_assistant::Tactic;
+
+/// One line of a declarative block
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Tactic(Tactic),
+    /// `proof` is `None` for a bare `have`, which leaves the lemma as the next goal
+    Have { name: String, ty: String, proof: Option<Vec<Step>> },
+    Show(String),
+    From(Vec<String>),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Tactic(t) => write!(f, "{}", t),
+            Step::Have { name, ty, proof } => {
+                write!(f, "have {} : {}", name, ty)?;
+                match proof.as_deref() {
+                    Some([Step::Tactic(t)]) => write!(f, " by {}", t),
+                    _ => Ok(()),
+                }
+            }
+            Step::Show(ty) => write!(f, "show {}", ty),
+            Step::From(facts) => write!(f, "from {}", facts.join(", ")),
+        }
+    }
+}
+
+/// Parse a block; indentation is relative to its first line
+pub fn parse_block(source: &str) -> Result<Vec<Step>, String> {
+    let lines: Vec<(usize, usize, &str)> = source.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.len() - line.trim_start().len(), line.trim()))
+        .filter(|(_, _, text)| !text.is_empty() && !text.starts_with("--"))
+        .collect();
+    let Some(&(_, indent, _)) = lines.first() else {
+        return Ok(Vec::new());
+    };
+
+    let mut pos = 0;
+    let steps = parse_lines(&lines, &mut pos, indent)?;
+    match lines.get(pos) {
+        Some((n, _, _)) => Err(format!("line {}: inconsistent indentation", n)),
+        None => Ok(steps),
+    }
+}
+
+fn parse_lines(lines: &[(usize, usize, &str)], pos: &mut usize, indent: usize) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    while let Some(&(n, ind, text)) = lines.get(*pos) {
+        if ind < indent {
+            break;
+        }
+        if ind > indent {
+            return Err(format!("line {}: unexpected indentation", n));
+        }
+        *pos += 1;
+
+        let text = text.strip_suffix('.').unwrap_or(text).trim();
+        let step = parse_step(text).map_err(|e| format!("line {}: {}", n, e))?;
+        let step = match step {
+            // A bare `have` followed by a deeper block is proved by that block
+            Step::Have { name, ty, proof: None } if lines.get(*pos).is_some_and(|l| l.1 > indent) => {
+                let block = parse_lines(lines, pos, lines[*pos].1)?;
+                Step::Have { name, ty, proof: Some(block) }
+            }
+            step => step,
+        };
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+fn parse_step(text: &str) -> Result<Step, String> {
+    let (keyword, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let rest = rest.trim();
+    match keyword {
+        "have" => {
+            let (decl, by) = match rest.rsplit_once(" by ") {
+                Some((decl, tactic)) => (decl, Some(Tactic::parse(tactic)?)),
+                None => (rest, None),
+            };
+            let (name, ty) = decl.split_once(':').ok_or("expected `have <name> : <type>`")?;
+            let (name, ty) = (name.trim(), ty.trim());
+            if name.is_empty() || ty.is_empty() {
+                return Err("expected `have <name> : <type>`".to_string());
+            }
+            Ok(Step::Have {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                proof: by.map(|t| vec![Step::Tactic(t)]),
+            })
+        }
+        "show" if !rest.is_empty() => Ok(Step::Show(rest.to_string())),
+        "from" => {
+            let facts: Vec<String> = rest.split(',').map(|h| h.trim().to_string()).collect();
+            if facts.iter().any(|h| h.is_empty()) {
+                return Err("expected `from <fact>, ...`".to_string());
+            }
+            Ok(Step::From(facts))
+        }
+        _ => Tactic::parse(text).map(Step::Tactic),
+    }
+}
+
+/// Print a block in the form [`parse_block`] reads back
+pub fn render_block(steps: &[Step]) -> String {
+    let mut out = String::new();
+    render_into(&mut out, steps, 0);
+    out
+}
+
+fn render_into(out: &mut String, steps: &[Step], depth: usize) {
+    for step in steps {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&step.to_string());
+        out.push('\n');
+        if let Step::Have { proof: Some(block), .. } = step {
+            if !matches!(block.as_slice(), [Step::Tactic(_)]) {
+                render_into(out, block, depth + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof_assistant::ProofAssistant;
+    use crate::statement;
+
+    const STATEMENT: &str = "Π (x : ℝ). Path ℝ ((x + 1) * (x + 1)) (x * x + 2 * x + 1)";
+
+    const TRANSITIVITY: &str = "
+        intro x.
+        have h1 : Path ℝ ((x + 1) * (x + 1)) ((x + 1) * x + (x + 1)) by ring
+        have h2 : Path ℝ ((x + 1) * x + (x + 1)) (x * x + 2 * x + 1)
+          ring
+        show Path ℝ ((x + 1) * (x + 1)) (x * x + 2 * x + 1)
+        from h1, h2
+    ";
+
+    fn started() -> ProofAssistant {
+        let mut assistant = ProofAssistant::new();
+        assistant.start_statement("square", STATEMENT).unwrap();
+        assistant
+    }
+
+    #[test]
+    fn test_transitivity_compiles_to_tactics_and_replays() {
+        let mut assistant = started();
+        let tactics = assistant.run_declarative(TRANSITIVITY).unwrap();
+        assert!(assistant.goal_ids().is_empty());
+
+        let have = |h: &str, ty: &str| Tactic::Have(h.to_string(), ty.to_string());
+        let mid = statement::parse_expr("(x + 1) * x + (x + 1)").unwrap().to_string();
+        assert_eq!(tactics, vec![
+            Tactic::Intro("x".to_string()),
+            have("h1", "Path ℝ ((x + 1) * (x + 1)) ((x + 1) * x + (x + 1))"),
+            Tactic::Ring,
+            have("h2", "Path ℝ ((x + 1) * x + (x + 1)) (x * x + 2 * x + 1)"),
+            Tactic::Ring,
+            Tactic::Transitivity(mid),
+            Tactic::Exact("h1".to_string()),
+            Tactic::Exact("h2".to_string()),
+        ]);
+
+        // Replaying the recorded tactics closes the proof without the block
+        let mut replayed = started();
+        for cmd in assistant.history() {
+            replayed.apply_tactic(cmd.tactic.clone(), cmd.goal_id).unwrap();
+        }
+        assert!(replayed.goal_ids().is_empty());
+
+        // The generated script reads back as the same block
+        let script = assistant.generate_proof_script();
+        let mut again = started();
+        assert_eq!(again.run_declarative(&script).unwrap(), tactics);
+        assert!(again.goal_ids().is_empty());
+    }
+
+    #[test]
+    fn test_show_mismatch_names_both_types() {
+        let mut assistant = started();
+        let err = assistant.run_declarative("intro x\nshow Path ℝ x 0").unwrap_err();
+        let goal = statement::parse_statement("Path ℝ ((x + 1) * (x + 1)) (x * x + 2 * x + 1)").unwrap();
+        assert!(err.contains("`Path ℝ x 0`"), "{}", err);
+        assert!(err.contains(&format!("`{}`", goal)), "{}", err);
+        // A failed block leaves the proof state untouched
+        assert_eq!(assistant.goal_ids(), vec![0]);
+    }
+
+    #[test]
+    fn test_parse_render_round_trip() {
+        let steps = parse_block(TRANSITIVITY).unwrap();
+        assert!(matches!(&steps[2], Step::Have { proof: Some(block), .. } if block.len() == 1));
+        assert_eq!(parse_block(&render_block(&steps)).unwrap(), steps);
+        assert!(parse_block("from h1,").is_err());
+        assert!(parse_block("ring\n    ring").is_err());
+    }
+}
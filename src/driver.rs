@@ -0,0 +1,428 @@
+//! Proof driver token
+//!
+//! In a shared proof session exactly one connection drives: only its proof
+//! actions are applied, while everyone else keeps browsing goals live. The
+//! token starts with the first connection (the session creator) and moves
+//! when the driver grants a request, when the driver has been idle past the
+//! timeout with a request pending, or when the driver disconnects.
+//!
+//! [`DriverToken`] is a pure state machine that takes the current time as an
+//! argument; [`ProofRoom`] wires it to a session's assistant and activity log.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::lifecycle::SessionGuard;
+use crate::proof_assistant::{ProofAssistant, ProofCommand, Tactic};
+use crate::protocol::{ClientMessage, ProofAction, ServerMessage};
+use crate::transaction::Transaction;
+use crate::{ProofState, ProofStep};
+
+/// How long a driver may sit idle before a pending request takes control
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Rejection for a proof action from a connection that is not driving
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotDriving {
+    pub driver: Option<String>,
+}
+
+impl fmt::Display for NotDriving {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.driver {
+            Some(driver) => write!(f, "{} is driving; request control to apply tactics", driver),
+            None => write!(f, "nobody is driving; request control to apply tactics"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoffReason {
+    /// The first connection of a session takes the token
+    Joined,
+    /// The driver handed control over
+    Granted,
+    /// A pending request took control from an idle driver
+    IdleTimeout,
+    /// The driver disconnected
+    Disconnected,
+}
+
+/// One change of driver, as recorded in the activity log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handoff {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub reason: HandoffReason,
+}
+
+impl fmt::Display for Handoff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |who: &Option<String>| who.clone().unwrap_or_else(|| "nobody".to_string());
+        match self.reason {
+            HandoffReason::Joined => write!(f, "{} started driving", name(&self.to)),
+            HandoffReason::Granted => write!(f, "{} handed control to {}", name(&self.from), name(&self.to)),
+            HandoffReason::IdleTimeout => {
+                write!(f, "{} took control after {} was idle", name(&self.to), name(&self.from))
+            }
+            HandoffReason::Disconnected => {
+                write!(f, "{} disconnected; {} is driving", name(&self.from), name(&self.to))
+            }
+        }
+    }
+}
+
+/// Who drives a proof session, and who is waiting to
+#[derive(Debug, Clone)]
+pub struct DriverToken {
+    driver: Option<String>,
+    /// Connections in the order they joined
+    connected: Vec<String>,
+    requested_by: Option<String>,
+    last_activity: Instant,
+    idle_timeout: Duration,
+}
+
+impl DriverToken {
+    pub fn new(idle_timeout: Duration, now: Instant) -> Self {
+        DriverToken {
+            driver: None,
+            connected: Vec::new(),
+            requested_by: None,
+            last_activity: now,
+            idle_timeout,
+        }
+    }
+
+    pub fn driver(&self) -> Option<&str> {
+        self.driver.as_deref()
+    }
+
+    pub fn requested_by(&self) -> Option<&str> {
+        self.requested_by.as_deref()
+    }
+
+    /// Register a connection; the first one to arrive drives
+    pub fn join(&mut self, conn: &str, now: Instant) -> Option<Handoff> {
+        if !self.connected.iter().any(|c| c == conn) {
+            self.connected.push(conn.to_string());
+        }
+        if self.driver.is_none() {
+            return Some(self.hand_to(Some(conn.to_string()), HandoffReason::Joined, now));
+        }
+        None
+    }
+
+    /// Unregister a connection; a departing driver releases the token to the
+    /// pending requester, else to the longest-connected remaining connection
+    pub fn leave(&mut self, conn: &str, now: Instant) -> Option<Handoff> {
+        self.connected.retain(|c| c != conn);
+        if self.requested_by.as_deref() == Some(conn) {
+            self.requested_by = None;
+        }
+        if self.driver.as_deref() != Some(conn) {
+            return None;
+        }
+        let next = self.requested_by.clone().or_else(|| self.connected.first().cloned());
+        Some(self.hand_to(next, HandoffReason::Disconnected, now))
+    }
+
+    /// Gate a proof action; the driver's actions count as activity
+    pub fn authorize(&mut self, conn: &str, now: Instant) -> Result<(), NotDriving> {
+        if self.driver.as_deref() == Some(conn) {
+            self.last_activity = now;
+            Ok(())
+        } else {
+            Err(NotDriving { driver: self.driver.clone() })
+        }
+    }
+
+    /// Ask for control, which is immediate when nobody drives or the driver
+    /// is already idle; otherwise the request waits for a grant or a timeout
+    pub fn request(&mut self, conn: &str, now: Instant) -> Option<Handoff> {
+        if self.driver.as_deref() == Some(conn) || !self.connected.iter().any(|c| c == conn) {
+            return None;
+        }
+        self.requested_by = Some(conn.to_string());
+        if self.driver.is_none() {
+            return Some(self.hand_to(Some(conn.to_string()), HandoffReason::Granted, now));
+        }
+        self.tick(now)
+    }
+
+    /// Hand control from `by` to `to`; only the driver may grant, and only
+    /// to a connected session member
+    pub fn grant(&mut self, by: &str, to: &str, now: Instant) -> Result<Option<Handoff>, NotDriving> {
+        self.authorize(by, now)?;
+        if by == to || !self.connected.iter().any(|c| c == to) {
+            return Ok(None);
+        }
+        Ok(Some(self.hand_to(Some(to.to_string()), HandoffReason::Granted, now)))
+    }
+
+    /// Steal on timeout: a pending request takes over from an idle driver
+    pub fn tick(&mut self, now: Instant) -> Option<Handoff> {
+        let requester = self.requested_by.clone()?;
+        if now.saturating_duration_since(self.last_activity) < self.idle_timeout {
+            return None;
+        }
+        Some(self.hand_to(Some(requester), HandoffReason::IdleTimeout, now))
+    }
+
+    fn hand_to(&mut self, to: Option<String>, reason: HandoffReason, now: Instant) -> Handoff {
+        if self.requested_by == to {
+            self.requested_by = None;
+        }
+        let from = std::mem::replace(&mut self.driver, to.clone());
+        self.last_activity = now;
+        Handoff { from, to, reason }
+    }
+}
+
+/// A message for one connection or for the whole session
+#[derive(Debug)]
+pub enum Outgoing {
+    Reply(ServerMessage),
+    Broadcast(ServerMessage),
+}
+
+/// A shared proof session: the assistant, its activity log and the token
+pub struct ProofRoom {
+    pub session: SessionGuard,
+    pub token: DriverToken,
+    pub(crate) history: Vec<ProofStep>,
+    /// Assistant history length before each entry of `history`
+    pub(crate) marks: Vec<usize>,
+    /// Undone entries with the commands that redo them, most recent last
+    undone: Vec<(ProofStep, Vec<ProofCommand>)>,
+    /// The open scripted transaction, which locks out interactive writers
+    pub(crate) transaction: Option<Transaction>,
+    pub(crate) next_transaction: u64,
+}
+
+impl ProofRoom {
+    pub fn new(session: SessionGuard, idle_timeout: Duration, now: Instant) -> Self {
+        ProofRoom {
+            session,
+            token: DriverToken::new(idle_timeout, now),
+            history: Vec::new(),
+            marks: Vec::new(),
+            undone: Vec::new(),
+            transaction: None,
+            next_transaction: 0,
+        }
+    }
+
+    pub fn join(&mut self, conn: &str, now: Instant) -> Vec<Outgoing> {
+        match self.token.join(conn, now) {
+            Some(handoff) => vec![self.announce(handoff)],
+            None => vec![Outgoing::Reply(ServerMessage::DriverChanged {
+                driver: self.token.driver().map(str::to_string),
+                message: format!("{} is driving", self.token.driver().unwrap_or("nobody")),
+            })],
+        }
+    }
+
+    pub fn leave(&mut self, conn: &str, now: Instant) -> Vec<Outgoing> {
+        self.token.leave(conn, now).map(|h| self.announce(h)).into_iter().collect()
+    }
+
+    /// Run the idle and transaction timeouts; call periodically
+    pub fn tick(&mut self, now: Instant) -> Vec<Outgoing> {
+        self.expire_transaction(now);
+        self.token.tick(now).map(|h| self.announce(h)).into_iter().collect()
+    }
+
+    /// React to a client message; document edits and cursors are not the
+    /// room's business and produce nothing
+    pub fn handle(&mut self, conn: &str, msg: ClientMessage, now: Instant) -> Vec<Outgoing> {
+        match msg {
+            ClientMessage::ProofAction { action } => {
+                if let Err(rejection) = self.token.authorize(conn, now) {
+                    return vec![Outgoing::Reply(ServerMessage::NotDriving { driver: rejection.driver })];
+                }
+                // Interactive writes are rejected, not queued, while a
+                // transaction is open: a queued tactic would target goals
+                // the transaction may have replaced
+                if let Some(txn) = &self.transaction {
+                    return vec![Outgoing::Reply(ServerMessage::Busy { message: txn.busy_message() })];
+                }
+                self.apply(conn, action)
+            }
+            ClientMessage::RequestControl => match self.token.request(conn, now) {
+                Some(handoff) => vec![self.announce(handoff)],
+                None if self.token.requested_by() == Some(conn) => {
+                    self.session.log(format!("{} requested control", conn));
+                    vec![Outgoing::Broadcast(ServerMessage::ControlRequested { user_id: conn.to_string() })]
+                }
+                None => Vec::new(),
+            },
+            ClientMessage::GrantControl { to } => match self.token.grant(conn, &to, now) {
+                Ok(handoff) => handoff.map(|h| self.announce(h)).into_iter().collect(),
+                Err(rejection) => vec![Outgoing::Reply(ServerMessage::NotDriving { driver: rejection.driver })],
+            },
+            ClientMessage::Join { .. } | ClientMessage::Operation { .. } | ClientMessage::CursorUpdate { .. } => {
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn proof_state(&self) -> ProofState {
+        let assistant = &self.session.assistant;
+        ProofState {
+            goals: assistant.goal_ids().into_iter().filter_map(|id| assistant.goal(id).cloned()).collect(),
+            history: self.history.clone(),
+        }
+    }
+
+    fn apply(&mut self, conn: &str, action: ProofAction) -> Vec<Outgoing> {
+        let (goal_id, tactic) = match action {
+            ProofAction::ApplyTactic { goal_id, tactic } => (goal_id, tactic),
+            ProofAction::Undo => return self.undo(conn),
+            ProofAction::Redo => return self.redo(conn),
+        };
+        let tactic = match Tactic::parse(&tactic) {
+            Ok(tactic) => tactic,
+            Err(message) => return vec![Outgoing::Reply(ServerMessage::Error { message })],
+        };
+
+        let mark = self.session.assistant.history().len();
+        match apply_step(&mut self.session.assistant, tactic.clone(), goal_id) {
+            Ok(step) => {
+                self.history.push(step);
+                self.marks.push(mark);
+                self.undone.clear();
+            }
+            Err(message) => return vec![Outgoing::Reply(ServerMessage::Error { message })],
+        }
+        self.session.log(format!("{} applied `{}` to goal {}", conn, tactic, goal_id));
+        self.update()
+    }
+
+    /// Take back the latest entry; a committed transaction goes as a whole
+    fn undo(&mut self, conn: &str) -> Vec<Outgoing> {
+        let (Some(step), Some(mark)) = (self.history.pop(), self.marks.pop()) else {
+            return vec![Outgoing::Reply(ServerMessage::Error { message: "Nothing to undo".to_string() })];
+        };
+        let commands = self.session.assistant.history()[mark..].to_vec();
+        if let Err(message) = self.session.assistant.rewind(mark) {
+            self.history.push(step);
+            self.marks.push(mark);
+            return vec![Outgoing::Reply(ServerMessage::Error { message })];
+        }
+        self.session.log(format!("{} undid `{}`", conn, step.tactic));
+        self.undone.push((step, commands));
+        self.update()
+    }
+
+    fn redo(&mut self, conn: &str) -> Vec<Outgoing> {
+        let Some((step, commands)) = self.undone.pop() else {
+            return vec![Outgoing::Reply(ServerMessage::Error { message: "Nothing to redo".to_string() })];
+        };
+        let mark = self.session.assistant.history().len();
+        for cmd in &commands {
+            if let Err(message) = self.session.assistant.apply_tactic(cmd.tactic.clone(), cmd.goal_id) {
+                // Leave the session as it was before the partial redo
+                let _ = self.session.assistant.rewind(mark);
+                return vec![Outgoing::Reply(ServerMessage::Error { message })];
+            }
+        }
+        self.session.log(format!("{} redid `{}`", conn, step.tactic));
+        self.history.push(step);
+        self.marks.push(mark);
+        self.update()
+    }
+
+    pub(crate) fn update(&self) -> Vec<Outgoing> {
+        vec![Outgoing::Broadcast(ServerMessage::ProofUpdate { proof_state: self.proof_state() })]
+    }
+
+    pub(crate) fn clear_redo(&mut self) {
+        self.undone.clear();
+    }
+
+    fn announce(&mut self, handoff: Handoff) -> Outgoing {
+        self.session.log(handoff.to_string());
+        Outgoing::Broadcast(ServerMessage::DriverChanged {
+            driver: handoff.to.clone(),
+            message: handoff.to_string(),
+        })
+    }
+}
+
+/// Apply one tactic and describe it as a history entry
+pub(crate) fn apply_step(assistant: &mut ProofAssistant, tactic: Tactic, goal_id: usize) -> Result<ProofStep, String> {
+    let goal_before = assistant.goal(goal_id).cloned().ok_or("Goal not found")?;
+    let open = assistant.goal_ids();
+    assistant.apply_tactic(tactic.clone(), goal_id)?;
+    let goals_after = assistant.goal_ids().into_iter()
+        .filter(|id| !open.contains(id))
+        .filter_map(|id| assistant.goal(id).cloned())
+        .collect();
+    Ok(ProofStep { tactic: tactic.to_string(), goal_before, goals_after, steps: Vec::new() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    fn session(now: Instant) -> DriverToken {
+        let mut token = DriverToken::new(TIMEOUT, now);
+        token.join("alice", now);
+        token.join("bob", now);
+        token
+    }
+
+    #[test]
+    fn test_creator_drives_and_grants() {
+        let now = Instant::now();
+        let mut token = session(now);
+        assert_eq!(token.driver(), Some("alice"));
+        assert_eq!(token.authorize("bob", now), Err(NotDriving { driver: Some("alice".to_string()) }));
+        assert!(token.grant("bob", "bob", now).is_err());
+
+        assert_eq!(token.request("bob", now), None);
+        assert_eq!(token.requested_by(), Some("bob"));
+        let handoff = token.grant("alice", "bob", now).unwrap().unwrap();
+        assert_eq!(handoff.to_string(), "alice handed control to bob");
+        assert_eq!(token.driver(), Some("bob"));
+        assert_eq!(token.requested_by(), None);
+        assert!(token.authorize("bob", now).is_ok());
+    }
+
+    #[test]
+    fn test_request_steals_from_idle_driver() {
+        let start = Instant::now();
+        let mut token = session(start);
+        token.request("bob", start + TIMEOUT / 2);
+        // Activity within the timeout keeps the token
+        token.authorize("alice", start + TIMEOUT / 2).unwrap();
+        assert_eq!(token.tick(start + TIMEOUT), None);
+
+        let handoff = token.tick(start + TIMEOUT * 2).unwrap();
+        assert_eq!(handoff.reason, HandoffReason::IdleTimeout);
+        assert_eq!(token.driver(), Some("bob"));
+        // Without a pending request an idle driver keeps driving
+        assert_eq!(token.tick(start + TIMEOUT * 10), None);
+    }
+
+    #[test]
+    fn test_disconnect_releases_token() {
+        let now = Instant::now();
+        let mut token = session(now);
+        token.join("carol", now);
+        token.request("carol", now);
+
+        assert_eq!(token.leave("bob", now), None);
+        let handoff = token.leave("alice", now).unwrap();
+        assert_eq!((handoff.reason, handoff.to.as_deref()), (HandoffReason::Disconnected, Some("carol")));
+
+        token.leave("carol", now);
+        assert_eq!(token.driver(), None);
+        assert_eq!(token.join("dave", now).map(|h| h.reason), Some(HandoffReason::Joined));
+    }
+}
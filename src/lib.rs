@@ -2,30 +2,98 @@
 //!
 //! A complete proof assistant with web interface, collaborative features,
 //! and advanced visualization capabilities.
+//!
+//! # Feature flags
+//!
+//! The default `client` feature enables everything below. Embedders that only
+//! need the kernel can depend on the crate with `default-features = false,
+//! features = ["kernel"]`, which pulls in no WASM, web, or cryptography crates.
+//!
+//! | Feature           | Enables                                             |
+//! |-------------------|-----------------------------------------------------|
+//! | `kernel`          | `sctt_typechecker`: terms, values, NbE checking; `surface` names, `cubicaltt` import and checked `rename` |
+//! | `bridge`          | `bridge`: named `sctt_core` terms to and from kernel terms |
+//! | `compiler`        | `sctt_to_wasm`: SCTT → WASM with proof certificates |
+//! | `certificates`    | `certificate`: size breakdown, stripping and detached verification of compiled modules |
+//! | `proof-assistant` | `proof_assistant`, `statement`, `templates`, `declarative`, `protocol`, `driver`, `transaction`, `presence` |
+//! | `smooth`          | `smooth`: the `sctt_smooth` dual numbers, Taylor series and geodesics |
+//! | `wasm-bindings`   | the `#[wasm_bindgen]` `ScttSystem` facade          |
+//! | `web`             | Yew UI (`web_interface`) and `collaborative` editing |
+//! | `visualization`   | canvas renderers in `visualization`               |
+//! | `service`         | `service`: route handlers and `Config` shared by both servers |
+//! | `router`          | `server`: the axum router over `service`, servable in-process |
+//! | `server`          | the `sctt-server` binary: `router` plus SQLite proof storage (sqlx) |
 
+#[cfg(feature = "kernel")]
 pub mod sctt_typechecker;
+#[cfg(feature = "kernel")]
+pub mod surface;
+#[cfg(feature = "kernel")]
+pub mod cubicaltt;
+#[cfg(feature = "kernel")]
+pub mod rename;
+#[cfg(feature = "bridge")]
+pub mod bridge;
+#[cfg(feature = "compiler")]
 pub mod sctt_to_wasm;
+#[cfg(feature = "certificates")]
+pub mod certificate;
+#[cfg(feature = "proof-assistant")]
 pub mod proof_assistant;
+#[cfg(feature = "proof-assistant")]
+pub mod statement;
+#[cfg(feature = "proof-assistant")]
+pub mod templates;
+#[cfg(feature = "proof-assistant")]
+pub mod declarative;
+#[cfg(feature = "proof-assistant")]
+pub mod protocol;
+#[cfg(feature = "proof-assistant")]
+pub mod driver;
+#[cfg(feature = "proof-assistant")]
+pub mod transaction;
+#[cfg(feature = "proof-assistant")]
+pub mod presence;
+#[cfg(feature = "service")]
+pub mod service;
+#[cfg(feature = "router")]
+pub mod server;
+#[cfg(feature = "web")]
 pub mod web_interface;
+#[cfg(feature = "web")]
 pub mod collaborative;
+#[cfg(feature = "visualization")]
 pub mod visualization;
+pub mod lifecycle;
+pub mod api;
+
+#[cfg(feature = "smooth")]
+pub use sctt_smooth as smooth;
 
+#[cfg(feature = "wasm-bindings")]
 use wasm_bindgen::prelude::*;
+#[cfg(feature = "proof-assistant")]
 use serde::{Deserialize, Serialize};
 
 // Re-export main types
+#[cfg(feature = "kernel")]
 pub use sctt_typechecker::{Term, TypeChecker, Context, Value};
+#[cfg(feature = "compiler")]
 pub use sctt_to_wasm::{ScttToWasmCompiler, OptLevel};
+#[cfg(feature = "proof-assistant")]
 pub use proof_assistant::{ProofAssistant, Tactic, Goal};
 
 /// Main SCTT system interface for web
+#[cfg(all(feature = "compiler", feature = "proof-assistant", feature = "wasm-bindings"))]
 #[wasm_bindgen]
 pub struct ScttSystem {
     type_checker: TypeChecker,
     compiler: ScttToWasmCompiler,
     assistant: ProofAssistant,
+    _guard: lifecycle::Guard,
 }
 
+#[cfg(all(feature = "compiler", feature = "proof-assistant", feature = "wasm-bindings"))]
 #[wasm_bindgen]
 impl ScttSystem {
     #[wasm_bindgen(constructor)]
@@ -36,6 +104,7 @@ impl ScttSystem {
             type_checker: TypeChecker::new(),
             compiler: ScttToWasmCompiler::new(OptLevel::Basic),
             assistant: ProofAssistant::new(),
+            _guard: lifecycle::Guard::new(lifecycle::Registry::global(), lifecycle::ResourceKind::Engine, "ScttSystem"),
         }
     }
 
@@ -53,10 +122,11 @@ impl ScttSystem {
     #[wasm_bindgen]
     pub fn compile_to_wasm(&mut self, code: &str) -> Result<Vec<u8>, JsValue> {
         let term = self.parse_term(code)?;
-        let ir = self.compiler.sctt_to_ir(&term);
+        let compile_error = |e: sctt_to_wasm::CompileError| JsValue::from_str(&e.to_string());
+        let ir = self.compiler.sctt_to_ir(&term).map_err(compile_error)?;
         let optimized = self.compiler.optimize(ir);
-        let wasm_module = self.compiler.ir_to_wasm(&optimized);
-        Ok(wasm_module.encode())
+        let wasm_module = self.compiler.ir_to_wasm(&optimized).map_err(compile_error)?;
+        wasm_module.encode().map_err(compile_error)
     }
 
     /// Apply a proof tactic
@@ -71,12 +141,27 @@ impl ScttSystem {
         Ok(self.assistant.render_proof_state())
     }
 
+    /// Run a proof script; the body may use declarative `have`/`show`/`from` blocks
+    #[wasm_bindgen]
+    pub fn run_proof_script(&mut self, script: &str) -> Result<String, JsValue> {
+        self.assistant.run_script(script)
+            .map_err(|e| JsValue::from_str(&e))?;
+        
+        Ok(self.assistant.render_proof_state())
+    }
+
     /// Get current proof state
     #[wasm_bindgen]
     pub fn get_proof_state(&self) -> String {
         self.assistant.render_proof_state()
     }
 
+    /// Engine-side resources that have outlived their expected lifetime
+    #[wasm_bindgen]
+    pub fn leak_report(&self) -> Vec<String> {
+        lifecycle::Registry::global().leaks().iter().map(|leak| leak.to_string()).collect()
+    }
+
     /// Parse SCTT code
     fn parse_term(&self, code: &str) -> Result<Term, JsValue> {
         // Simplified parser - would use proper parser combinator in production
@@ -85,7 +170,8 @@ impl ScttSystem {
 }
 
 /// Session management for collaborative editing
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub users: Vec<User>,
@@ -93,7 +179,8 @@ pub struct Session {
     pub proof_state: ProofState,
 }
 
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub name: String,
@@ -101,40 +188,70 @@ pub struct User {
     pub color: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
     pub content: String,
     pub version: u64,
     pub operations: Vec<Operation>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CursorPosition {
     pub line: usize,
     pub column: usize,
 }
 
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Operation {
     Insert { pos: usize, text: String },
     Delete { pos: usize, len: usize },
     Replace { pos: usize, len: usize, text: String },
 }
 
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+impl Operation {
+    /// Apply to a document; an edit outside it or inside a character is
+    /// refused, leaving the document unchanged
+    pub fn apply(&self, doc: &mut String) -> bool {
+        let (pos, len) = match self {
+            Operation::Insert { pos, .. } => (*pos, 0),
+            Operation::Delete { pos, len } | Operation::Replace { pos, len, .. } => (*pos, *len),
+        };
+        let end = match pos.checked_add(len) {
+            Some(end) if end <= doc.len() && doc.is_char_boundary(pos) && doc.is_char_boundary(end) => end,
+            _ => return false,
+        };
+        match self {
+            Operation::Insert { text, .. } | Operation::Replace { text, .. } => doc.replace_range(pos..end, text),
+            Operation::Delete { .. } => doc.replace_range(pos..end, ""),
+        }
+        true
+    }
+}
+
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofState {
     pub goals: Vec<Goal>,
     pub history: Vec<ProofStep>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[cfg(feature = "proof-assistant")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofStep {
     pub tactic: String,
     pub goal_before: Goal,
     pub goals_after: Vec<Goal>,
+    /// The individual steps of a committed transaction, undone as one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<ProofStep>,
 }
 
 /// Simple parser module
+#[cfg(feature = "proof-assistant")]
 mod parser {
     use super::*;
     
@@ -161,7 +278,7 @@ mod parser {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "compiler", feature = "proof-assistant", feature = "wasm-bindings"))]
 mod tests {
     use super::*;
     use wasm_bindgen_test::*;
@@ -0,0 +1,387 @@
+//! Resource lifecycle: cleanup guards and leak detection
+//!
+//! Every long-lived resource (proof session, compile job, engine instance,
+//! websocket registration) is owned by a [`Guard`]. Dropping the guard runs
+//! the resource's cleanup and removes it from its [`Registry`], so every
+//! cancellation path (timeout, user cancel, disconnect) is just a drop.
+//! The registry remembers where each live guard was created and reports the
+//! ones that outlive their expected lifetime.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+#[cfg(feature = "proof-assistant")]
+use crate::proof_assistant::{GoalSnapshot, ProofAssistant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    ProofSession,
+    CompileJob,
+    Engine,
+    Connection,
+}
+
+impl ResourceKind {
+    /// How long a resource of this kind may reasonably stay alive
+    pub fn default_lifetime(&self) -> Duration {
+        match self {
+            ResourceKind::ProofSession => Duration::from_secs(4 * 60 * 60),
+            ResourceKind::CompileJob => Duration::from_secs(60),
+            ResourceKind::Engine => Duration::from_secs(24 * 60 * 60),
+            ResourceKind::Connection => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResourceKind::ProofSession => write!(f, "proof session"),
+            ResourceKind::CompileJob => write!(f, "compile job"),
+            ResourceKind::Engine => write!(f, "engine instance"),
+            ResourceKind::Connection => write!(f, "connection"),
+        }
+    }
+}
+
+/// A live resource as seen by the registry
+#[derive(Debug, Clone)]
+pub struct ResourceInfo {
+    pub id: u64,
+    pub kind: ResourceKind,
+    pub label: String,
+    /// Source location that created the guard
+    pub site: &'static Location<'static>,
+    /// Creation time, relative to the Unix epoch
+    pub created: Duration,
+}
+
+/// A resource that has outlived its expected lifetime
+#[derive(Debug, Clone)]
+pub struct LeakReport {
+    pub resource: ResourceInfo,
+    pub age: Duration,
+    pub expected: Duration,
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} `{}` alive for {}s (expected < {}s), created at {}",
+            self.resource.kind,
+            self.resource.label,
+            self.age.as_secs(),
+            self.expected.as_secs(),
+            self.resource.site,
+        )
+    }
+}
+
+#[derive(Default)]
+struct RegistryInner {
+    next_id: u64,
+    live: HashMap<u64, ResourceInfo>,
+    lifetimes: HashMap<ResourceKind, Duration>,
+}
+
+/// Book of live guards, shared by everything that creates them
+#[derive(Clone, Default)]
+pub struct Registry {
+    inner: Arc<Mutex<RegistryInner>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// The process-wide registry
+    pub fn global() -> &'static Registry {
+        static GLOBAL: OnceLock<Registry> = OnceLock::new();
+        GLOBAL.get_or_init(Registry::new)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RegistryInner> {
+        // A panicking cleanup must not take the whole registry down with it
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Override the expected lifetime for one kind of resource
+    pub fn set_expected_lifetime(&self, kind: ResourceKind, lifetime: Duration) {
+        self.lock().lifetimes.insert(kind, lifetime);
+    }
+
+    pub fn expected_lifetime(&self, kind: ResourceKind) -> Duration {
+        self.lock().lifetimes.get(&kind).copied().unwrap_or_else(|| kind.default_lifetime())
+    }
+
+    /// All live resources, oldest first
+    pub fn live(&self) -> Vec<ResourceInfo> {
+        let mut live: Vec<_> = self.lock().live.values().cloned().collect();
+        live.sort_by_key(|r| r.id);
+        live
+    }
+
+    /// Live resources older than their expected lifetime
+    pub fn leaks(&self) -> Vec<LeakReport> {
+        let now = now();
+        self.live()
+            .into_iter()
+            .filter_map(|resource| {
+                let age = now.saturating_sub(resource.created);
+                let expected = self.expected_lifetime(resource.kind);
+                (age >= expected).then_some(LeakReport { resource, age, expected })
+            })
+            .collect()
+    }
+
+    #[track_caller]
+    fn register(&self, kind: ResourceKind, label: String) -> u64 {
+        let mut inner = self.lock();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.live.insert(id, ResourceInfo {
+            id,
+            kind,
+            label,
+            site: Location::caller(),
+            created: now(),
+        });
+        id
+    }
+
+    fn deregister(&self, id: u64) {
+        self.lock().live.remove(&id);
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm-bindings"))]
+fn now() -> Duration {
+    Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm-bindings")))]
+fn now() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Owner of a registered resource; dropping it runs the cleanup
+pub struct Guard {
+    id: u64,
+    registry: Registry,
+    cleanup: Vec<Box<dyn FnOnce() + Send>>,
+}
+
+impl Guard {
+    #[track_caller]
+    pub fn new(registry: &Registry, kind: ResourceKind, label: impl Into<String>) -> Self {
+        Guard {
+            id: registry.register(kind, label.into()),
+            registry: registry.clone(),
+            cleanup: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Register a cleanup action; actions run in reverse order on drop
+    pub fn on_drop(&mut self, action: impl FnOnce() + Send + 'static) {
+        self.cleanup.push(Box::new(action));
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        while let Some(action) = self.cleanup.pop() {
+            action();
+        }
+        self.registry.deregister(self.id);
+    }
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Guard").field("id", &self.id).finish()
+    }
+}
+
+/// A compile in progress, with its partial artifact on disk
+///
+/// Dropping the job before [`CompileJob::finish`] (cancel, timeout) deletes
+/// the partial artifact.
+#[derive(Debug)]
+pub struct CompileJob {
+    artifact: PathBuf,
+    file: fs::File,
+    _guard: Guard,
+}
+
+impl CompileJob {
+    #[track_caller]
+    pub fn new(registry: &Registry, dir: &Path, label: &str) -> io::Result<Self> {
+        let mut guard = Guard::new(registry, ResourceKind::CompileJob, label);
+        let artifact = dir.join(format!("sctt-{}-{}.wasm.partial", std::process::id(), guard.id()));
+        let file = fs::File::create(&artifact)?;
+        let path = artifact.clone();
+        guard.on_drop(move || {
+            let _ = fs::remove_file(path);
+        });
+        Ok(CompileJob { artifact, file, _guard: guard })
+    }
+
+    pub fn artifact_path(&self) -> &Path {
+        &self.artifact
+    }
+
+    /// Append a chunk of output to the artifact
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.file.write_all(bytes)
+    }
+
+    /// Complete the job, returning the artifact's contents
+    pub fn finish(mut self) -> io::Result<Vec<u8>> {
+        self.file.flush()?;
+        fs::read(&self.artifact)
+    }
+}
+
+/// Per-session side tables that must not outlive their session
+#[cfg(feature = "proof-assistant")]
+#[derive(Clone, Default)]
+pub struct SessionResources {
+    pub snapshots: Arc<Mutex<HashMap<String, Vec<GoalSnapshot>>>>,
+    pub activity: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+/// An open proof session
+///
+/// Owns the session's assistant, its saved snapshots and its activity-log
+/// writer; dropping it (close, disconnect, timeout) removes all three.
+#[cfg(feature = "proof-assistant")]
+pub struct SessionGuard {
+    pub id: String,
+    pub assistant: ProofAssistant,
+    resources: SessionResources,
+    _guard: Guard,
+}
+
+#[cfg(feature = "proof-assistant")]
+impl SessionGuard {
+    #[track_caller]
+    pub fn open(registry: &Registry, resources: &SessionResources, id: &str) -> Self {
+        let mut guard = Guard::new(registry, ResourceKind::ProofSession, id);
+        resources.snapshots.lock().unwrap_or_else(|e| e.into_inner()).insert(id.to_string(), Vec::new());
+        resources.activity.lock().unwrap_or_else(|e| e.into_inner()).insert(id.to_string(), Vec::new());
+
+        let (snapshots, activity, key) = (resources.snapshots.clone(), resources.activity.clone(), id.to_string());
+        guard.on_drop(move || {
+            snapshots.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+            activity.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+        });
+
+        SessionGuard {
+            id: id.to_string(),
+            assistant: ProofAssistant::new(),
+            resources: resources.clone(),
+            _guard: guard,
+        }
+    }
+
+    /// Save a snapshot of one goal under this session
+    pub fn snapshot(&self, goal_id: usize) -> Result<(), String> {
+        let snapshot = self.assistant.snapshot_goal(goal_id)?;
+        if let Some(saved) = self.resources.snapshots.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&self.id) {
+            saved.push(snapshot);
+        }
+        Ok(())
+    }
+
+    /// Append a line to the session's activity log
+    pub fn log(&self, line: impl Into<String>) {
+        if let Some(log) = self.resources.activity.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&self.id) {
+            log.push(line.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sctt-lifecycle-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_cancelled_compile_leaves_no_temp_files() {
+        let registry = Registry::new();
+        let dir = scratch_dir("compile");
+
+        let mut job = CompileJob::new(&registry, &dir, "main.sctt").unwrap();
+        job.write(b"\0asm").unwrap();
+        assert!(job.artifact_path().exists());
+        assert_eq!(registry.live().len(), 1);
+
+        // Cancelling mid-way is just dropping the job
+        drop(job);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(registry.live().is_empty());
+
+        let mut job = CompileJob::new(&registry, &dir, "main.sctt").unwrap();
+        job.write(b"\0asm").unwrap();
+        assert_eq!(job.finish().unwrap(), b"\0asm");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "proof-assistant")]
+    #[test]
+    fn test_dropping_a_session_removes_its_snapshots_and_log() {
+        let registry = Registry::new();
+        let resources = SessionResources::default();
+
+        let mut session = SessionGuard::open(&registry, &resources, "s1");
+        session.assistant
+            .start_proof("id", crate::Term::Universe(crate::sctt_typechecker::Level::Zero))
+            .unwrap();
+        session.snapshot(0).unwrap();
+        session.log("started id");
+        assert_eq!(resources.snapshots.lock().unwrap()["s1"].len(), 1);
+        assert_eq!(resources.activity.lock().unwrap()["s1"], vec!["started id"]);
+
+        drop(session);
+        assert!(resources.snapshots.lock().unwrap().is_empty());
+        assert!(resources.activity.lock().unwrap().is_empty());
+        assert!(registry.live().is_empty());
+    }
+
+    #[test]
+    fn test_leak_detector_flags_a_leaked_guard() {
+        let registry = Registry::new();
+        registry.set_expected_lifetime(ResourceKind::Connection, Duration::ZERO);
+
+        let scoped = Guard::new(&registry, ResourceKind::Connection, "scoped");
+        drop(scoped);
+        std::mem::forget(Guard::new(&registry, ResourceKind::Connection, "leaked"));
+
+        let leaks = registry.leaks();
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].resource.label, "leaked");
+        assert!(leaks[0].resource.site.file().ends_with("lifecycle.rs"));
+        assert!(leaks[0].to_string().starts_with("connection `leaked` alive for"));
+    }
+}
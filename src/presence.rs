@@ -0,0 +1,238 @@
+//! Remote presence: text cursors and proof-tree focus
+//!
+//! Each `CursorUpdate` carries a collaborator's text cursor and, from
+//! clients that know about it, the id of the goal they are looking at.
+//! [`Presence`] keeps the latest of each per connection and forgets both
+//! once a collaborator has been quiet for [`STALE_AFTER_MS`]; [`badges`]
+//! lays the live focuses out as avatar-colored badges on the proof tree.
+//!
+//! Times are milliseconds on whatever clock the caller has (`Date.now()`
+//! in the browser), since `Instant` is unavailable on `wasm32`.
+
+use std::collections::BTreeMap;
+
+use crate::protocol::ServerMessage;
+use crate::{CursorPosition, User};
+
+/// How long a remote cursor or focus survives without a fresh update
+pub const STALE_AFTER_MS: u64 = 30_000;
+
+/// How long a badge pulses after its owner moves focus onto the node
+pub const PULSE_MS: u64 = 800;
+
+/// Badges drawn side by side on one node before the rest fold into "+n"
+pub const MAX_STACKED: usize = 3;
+
+/// Horizontal distance between stacked badges, in pixels
+pub const BADGE_SPACING: u32 = 14;
+
+/// Avatar colors handed out to collaborators whose user record has none
+const PALETTE: [&str; 8] = [
+    "#ef4444", "#f59e0b", "#10b981", "#3b82f6", "#8b5cf6", "#ec4899", "#14b8a6", "#f97316",
+];
+
+/// A stable avatar color for a connection id
+pub fn avatar_color(user_id: &str) -> &'static str {
+    let hash = user_id.bytes().fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    PALETTE[hash as usize % PALETTE.len()]
+}
+
+/// What one collaborator last told us
+#[derive(Debug, Clone)]
+struct Remote {
+    cursor: CursorPosition,
+    focus: Option<usize>,
+    seen: u64,
+    /// When `focus` last changed, for the pulse
+    focused_at: u64,
+}
+
+/// Live cursors and focuses of everyone else in the session
+#[derive(Debug, Clone, Default)]
+pub struct Presence {
+    remotes: BTreeMap<String, Remote>,
+}
+
+impl Presence {
+    /// Record a cursor update; a repeated focus refreshes it without pulsing
+    pub fn update(&mut self, user_id: &str, cursor: CursorPosition, focus: Option<usize>, now: u64) {
+        let focused_at = match self.remotes.get(user_id) {
+            Some(remote) if remote.focus == focus => remote.focused_at,
+            _ => now,
+        };
+        self.remotes.insert(user_id.to_string(), Remote { cursor, focus, seen: now, focused_at });
+    }
+
+    /// Feed a server message; anything but cursor updates and departures is ignored
+    pub fn observe(&mut self, msg: &ServerMessage, now: u64) {
+        match msg {
+            ServerMessage::CursorUpdate { user_id, cursor, focus } => {
+                self.update(user_id, cursor.clone(), *focus, now)
+            }
+            ServerMessage::UserLeft { user_id } => self.forget(user_id),
+            _ => {}
+        }
+    }
+
+    pub fn forget(&mut self, user_id: &str) {
+        self.remotes.remove(user_id);
+    }
+
+    /// Drop collaborators not heard from within [`STALE_AFTER_MS`]
+    pub fn expire(&mut self, now: u64) {
+        self.remotes.retain(|_, remote| !is_stale(remote.seen, now));
+    }
+
+    /// Live text cursors, by connection id
+    pub fn cursors(&self, now: u64) -> Vec<(&str, &CursorPosition)> {
+        self.live(now).map(|(id, remote)| (id, &remote.cursor)).collect()
+    }
+
+    /// Live focuses as `(user, goal, focused_at)`
+    pub fn focuses(&self, now: u64) -> Vec<Focus> {
+        self.live(now)
+            .filter_map(|(id, remote)| {
+                remote.focus.map(|node| Focus { user_id: id.to_string(), node, focused_at: remote.focused_at })
+            })
+            .collect()
+    }
+
+    fn live(&self, now: u64) -> impl Iterator<Item = (&str, &Remote)> {
+        self.remotes.iter().filter(move |(_, r)| !is_stale(r.seen, now)).map(|(id, r)| (id.as_str(), r))
+    }
+}
+
+/// The staleness rule shared by text cursors and focuses
+fn is_stale(seen: u64, now: u64) -> bool {
+    now.saturating_sub(seen) > STALE_AFTER_MS
+}
+
+/// One collaborator looking at one goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Focus {
+    pub user_id: String,
+    pub node: usize,
+    pub focused_at: u64,
+}
+
+/// A badge to draw on a proof-tree node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeBadge {
+    pub node: usize,
+    /// Position in the node's stack, left to right
+    pub slot: usize,
+    /// Pixels from the node's badge anchor
+    pub offset: u32,
+    pub kind: BadgeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BadgeKind {
+    /// One collaborator; clicking jumps to `node`
+    User { user_id: String, initial: char, color: String, pulsing: bool },
+    /// Collaborators past [`MAX_STACKED`] on the same node
+    Overflow { count: usize },
+}
+
+/// Lay out focus badges per node
+///
+/// Badges on a node are ordered by who focused it first, so a newcomer
+/// lands at the end of the stack instead of shuffling the others; names
+/// and colors come from `users`, falling back to [`avatar_color`].
+pub fn badges(focuses: &[Focus], users: &[User], now: u64) -> Vec<NodeBadge> {
+    let mut by_node: BTreeMap<usize, Vec<&Focus>> = BTreeMap::new();
+    for focus in focuses {
+        by_node.entry(focus.node).or_default().push(focus);
+    }
+    let mut out = Vec::new();
+    for (node, mut stack) in by_node {
+        stack.sort_by(|a, b| a.focused_at.cmp(&b.focused_at).then_with(|| a.user_id.cmp(&b.user_id)));
+        let shown = if stack.len() > MAX_STACKED { MAX_STACKED - 1 } else { stack.len() };
+        for (slot, focus) in stack.iter().take(shown).enumerate() {
+            let user = users.iter().find(|u| u.id == focus.user_id);
+            let name = user.map_or(focus.user_id.as_str(), |u| u.name.as_str());
+            let color = match user {
+                Some(u) if !u.color.is_empty() => u.color.clone(),
+                _ => avatar_color(&focus.user_id).to_string(),
+            };
+            out.push(NodeBadge {
+                node,
+                slot,
+                offset: slot as u32 * BADGE_SPACING,
+                kind: BadgeKind::User {
+                    user_id: focus.user_id.clone(),
+                    initial: name.chars().next().unwrap_or('?'),
+                    color,
+                    pulsing: now.saturating_sub(focus.focused_at) < PULSE_MS,
+                },
+            });
+        }
+        if shown < stack.len() {
+            out.push(NodeBadge {
+                node,
+                slot: shown,
+                offset: shown as u32 * BADGE_SPACING,
+                kind: BadgeKind::Overflow { count: stack.len() - shown },
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: usize) -> CursorPosition {
+        CursorPosition { line, column: 0 }
+    }
+
+    fn user(id: &str, name: &str, color: &str) -> User {
+        User { id: id.to_string(), name: name.to_string(), cursor: at(0), color: color.to_string() }
+    }
+
+    #[test]
+    fn test_focus_expires_with_the_text_cursor() {
+        let mut presence = Presence::default();
+        presence.update("a", at(1), Some(2), 0);
+        presence.update("b", at(3), None, 10_000);
+        assert_eq!(presence.focuses(STALE_AFTER_MS).len(), 1);
+        assert_eq!(presence.cursors(STALE_AFTER_MS).len(), 2);
+
+        let later = STALE_AFTER_MS + 1;
+        assert!(presence.focuses(later).is_empty());
+        assert_eq!(presence.cursors(later), vec![("b", &at(3))]);
+        presence.expire(later);
+        presence.update("b", at(3), Some(2), later);
+        assert_eq!(presence.focuses(later)[0].user_id, "b");
+    }
+
+    #[test]
+    fn test_badges_stack_in_focus_order_and_overflow() {
+        let focus = |id: &str, node, focused_at| Focus { user_id: id.to_string(), node, focused_at };
+        let users = [user("d", "Dana", "#000000")];
+        let focuses = [focus("d", 1, 0), focus("a", 1, 5), focus("c", 1, 2), focus("b", 1, 2), focus("e", 7, 0)];
+        let laid = badges(&focuses, &users, 10_000);
+
+        let on_one: Vec<_> = laid.iter().filter(|b| b.node == 1).collect();
+        assert_eq!(on_one.len(), MAX_STACKED);
+        assert!(matches!(&on_one[0].kind, BadgeKind::User { initial: 'D', color, .. } if color == "#000000"));
+        assert!(matches!(&on_one[1].kind, BadgeKind::User { user_id, .. } if user_id == "b"));
+        assert_eq!(on_one[2].kind, BadgeKind::Overflow { count: 2 });
+        assert_eq!(on_one[2].offset, 2 * BADGE_SPACING);
+        assert!(matches!(&laid[3].kind, BadgeKind::User { color, .. } if color == avatar_color("e")));
+    }
+
+    #[test]
+    fn test_only_a_changed_focus_pulses() {
+        let mut presence = Presence::default();
+        presence.update("a", at(0), Some(4), 0);
+        presence.update("a", at(5), Some(4), PULSE_MS);
+        let pulsing = |p: &Presence, now| {
+            badges(&p.focuses(now), &[], now).iter().any(|b| matches!(b.kind, BadgeKind::User { pulsing: true, .. }))
+        };
+        assert!(!pulsing(&presence, PULSE_MS));
+        presence.update("a", at(5), Some(6), 2 * PULSE_MS);
+        assert!(pulsing(&presence, 2 * PULSE_MS + 1));
+    }
+}
@@ -7,8 +7,11 @@
 //! - Proof script generation
 
 use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use serde::{Deserialize, Serialize};
 use crate::sctt_typechecker::{Term, Value, Context, TypeChecker};
+use crate::statement::{self, Env, Statement};
+use crate::declarative::{self, Step};
 
 /// Main proof assistant engine
 pub struct ProofAssistant {
@@ -18,10 +21,25 @@ pub struct ProofAssistant {
     hint_db: HintDatabase,
     automation: AutomationEngine,
     history: Vec<ProofCommand>,
+    theorem: Option<(String, Term)>,
+    /// Name and source of a proof started from a surface statement
+    surface: Option<(String, String)>,
+    /// Declarative blocks and the history spans they compiled to
+    blocks: Vec<DeclarativeSpan>,
+    /// Goal being replaced and ids handed out by the current tactic
+    replacing: Option<usize>,
+    allocated: Vec<usize>,
+}
+
+#[derive(Clone)]
+struct DeclarativeSpan {
+    start: usize,
+    end: usize,
+    source: String,
 }
 
 /// A proof goal
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Goal {
     pub id: usize,
     pub context: Vec<Hypothesis>,
@@ -29,7 +47,7 @@ pub struct Goal {
     pub term: Option<Term>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Hypothesis {
     pub name: String,
     pub ty: String,
@@ -37,7 +55,7 @@ pub struct Hypothesis {
 }
 
 /// Available proof tactics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Tactic {
     // Basic tactics
     Intro(String),
@@ -84,13 +102,13 @@ pub enum Tactic {
     Ltac(String, Vec<TacticExpr>),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     LeftToRight,
     RightToLeft,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TacticExpr {
     Tactic(Tactic),
     Match(String, Vec<(Pattern, TacticExpr)>),
@@ -99,7 +117,7 @@ pub enum TacticExpr {
     Idtac,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Pattern {
     Var(String),
     Constructor(String, Vec<Pattern>),
@@ -130,18 +148,42 @@ pub struct AutomationEngine {
 pub enum SearchStrategy {
     BreadthFirst,
     DepthFirst,
-    BestFirst(Box<dyn Fn(&Goal) -> f64>),
+    BestFirst(fn(&Goal) -> f64),
     IterativeDeepening,
     MonteCarlo,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProofCommand {
     pub tactic: Tactic,
     pub goal_id: usize,
     pub timestamp: u64,
 }
 
+/// Engine version recorded in goal snapshots
+pub const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A self-contained copy of one goal, suitable for sharing as a permalink
+///
+/// Carries the originating statement and the tactic script that reaches the
+/// goal, so a viewer can rebuild the live state instead of trusting the copy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoalSnapshot {
+    pub engine_version: String,
+    pub theorem: String,
+    pub statement: Term,
+    pub goal: Goal,
+    pub script: Vec<ProofCommand>,
+}
+
+/// Outcome of reconstructing a snapshot in the viewer's engine
+pub enum GoalReplay {
+    /// The script replayed and reached a structurally identical goal
+    Live(ProofAssistant),
+    /// Replay failed; only the recorded snapshot can be shown
+    Static { snapshot: GoalSnapshot, reason: String },
+}
+
 impl ProofAssistant {
     pub fn new() -> Self {
         ProofAssistant {
@@ -151,6 +193,11 @@ impl ProofAssistant {
             hint_db: HintDatabase::new(),
             automation: AutomationEngine::new(),
             history: Vec::new(),
+            theorem: None,
+            surface: None,
+            blocks: Vec::new(),
+            replacing: None,
+            allocated: Vec::new(),
         }
     }
 
@@ -160,21 +207,211 @@ impl ProofAssistant {
             id: 0,
             context: Vec::new(),
             conclusion: format!("{:?}", statement),
-            term: Some(statement),
+            term: Some(statement.clone()),
         };
         
         self.goals = vec![goal];
+        self.history.clear();
+        self.blocks.clear();
+        self.theorem = Some((name.to_string(), statement));
+        self.surface = None;
+        Ok(())
+    }
+
+    /// Start a proof of a surface statement such as `Π (x y : ℝ). Path ℝ (x + y) (y + x)`
+    pub fn start_statement(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let stmt = statement::parse_statement(source).map_err(|e| e.to_string())?;
+        Env::prelude().check_statement(&stmt).map_err(|e| e.to_string())?;
+        
+        self.goals = vec![Goal {
+            id: 0,
+            context: Vec::new(),
+            conclusion: stmt.to_string(),
+            term: None,
+        }];
+        self.history.clear();
+        self.blocks.clear();
+        self.theorem = None;
+        self.surface = Some((name.to_string(), source.to_string()));
+        Ok(())
+    }
+
+    /// Run a script: a `theorem <name> : <statement>` header and a declarative block
+    pub fn run_script(&mut self, script: &str) -> Result<Vec<Tactic>, String> {
+        let (header, body) = script.trim_start().split_once('\n').unwrap_or((script.trim(), ""));
+        let (name, source) = header.trim()
+            .strip_prefix("theorem")
+            .and_then(|rest| rest.split_once(':'))
+            .ok_or("Script must start with `theorem <name> : <statement>`")?;
+        self.start_statement(name.trim(), source)?;
+        self.run_declarative(body)
+    }
+
+    /// Elaborate a declarative block against the first open goal
+    ///
+    /// Returns the tactics the block compiled to. They are recorded in the
+    /// history like any others; on failure the proof state is left untouched.
+    pub fn run_declarative(&mut self, source: &str) -> Result<Vec<Tactic>, String> {
+        let steps = declarative::parse_block(source)?;
+        let goal = *self.goal_ids().first().ok_or("No goals")?;
+        let (saved_goals, start) = (self.goals.clone(), self.history.len());
+        
+        if let Err(e) = self.elaborate(&steps, goal) {
+            self.goals = saved_goals;
+            self.history.truncate(start);
+            return Err(e);
+        }
+        
+        self.blocks.push(DeclarativeSpan {
+            start,
+            end: self.history.len(),
+            source: declarative::render_block(&steps),
+        });
+        Ok(self.history[start..].iter().map(|cmd| cmd.tactic.clone()).collect())
+    }
+
+    /// Run `steps` against `goal`, returning the goals it leaves open
+    fn elaborate(&mut self, steps: &[Step], goal: usize) -> Result<Vec<usize>, String> {
+        let mut open = vec![goal];
+        
+        for step in steps {
+            let focus = *open.first().ok_or_else(|| format!("`{}`: no goals left", step))?;
+            match step {
+                Step::Tactic(tactic) => {
+                    let spawned = self.apply_tactic_spawning(tactic.clone(), focus)?;
+                    open.splice(0..1, spawned);
+                }
+                Step::Have { name, ty, proof } => {
+                    let spawned = self.apply_tactic_spawning(Tactic::Have(name.clone(), ty.clone()), focus)?;
+                    match proof {
+                        None => {
+                            open.splice(0..1, spawned);
+                        }
+                        Some(block) => {
+                            if !self.elaborate(block, spawned[0])?.is_empty() {
+                                return Err(format!("`have {} : {}` is not closed by its proof", name, ty));
+                            }
+                            open.splice(0..1, spawned[1..].iter().copied());
+                        }
+                    }
+                }
+                Step::Show(ty) => self.check_show(focus, ty)?,
+                Step::From(facts) => {
+                    let mut current = focus;
+                    for (i, fact) in facts.iter().enumerate() {
+                        if i + 1 == facts.len() {
+                            self.apply_tactic_spawning(Tactic::Exact(fact.clone()), current)?;
+                        } else {
+                            let mid = self.path_target(current, fact)?;
+                            let spawned = self.apply_tactic_spawning(Tactic::Transitivity(mid), current)?;
+                            self.apply_tactic_spawning(Tactic::Exact(fact.clone()), spawned[0])?;
+                            current = spawned[1];
+                        }
+                    }
+                    open.remove(0);
+                }
+            }
+        }
+        
+        Ok(open)
+    }
+
+    fn check_show(&self, goal_id: usize, ty: &str) -> Result<(), String> {
+        let goal = self.goal(goal_id).ok_or("Goal not found")?;
+        let shown = statement::parse_statement(ty).map_err(|e| format!("show: {}", e))?;
+        let convertible = statement::parse_statement(&goal.conclusion)
+            .is_ok_and(|actual| actual.convertible(&shown));
+        if convertible {
+            Ok(())
+        } else {
+            Err(format!("show: `{}` is not convertible with the goal `{}`", ty, goal.conclusion))
+        }
+    }
+
+    /// Right endpoint of the path fact `name` in the goal's context
+    fn path_target(&self, goal_id: usize, name: &str) -> Result<String, String> {
+        let goal = self.goal(goal_id).ok_or("Goal not found")?;
+        let hyp = goal.context.iter().rev().find(|h| h.name == name)
+            .ok_or_else(|| format!("from: unknown fact `{}`", name))?;
+        match statement::parse_statement(&hyp.ty) {
+            Ok(Statement::Path(_, _, target)) => Ok(target.to_string()),
+            _ => Err(format!("from: `{}` is not a path", name)),
+        }
+    }
+
+    /// Ids of the open goals, in order
+    pub fn goal_ids(&self) -> Vec<usize> {
+        self.goals.iter().map(|g| g.id).collect()
+    }
+
+    /// Look up an open goal by id
+    pub fn goal(&self, goal_id: usize) -> Option<&Goal> {
+        self.goals.iter().find(|g| g.id == goal_id)
+    }
+
+    /// Tactics applied so far, in order
+    pub fn history(&self) -> &[ProofCommand] {
+        &self.history
+    }
+
+    /// An independent copy of this proof, rebuilt by replaying its history
+    pub fn fork(&self) -> Result<ProofAssistant, String> {
+        self.replay_prefix(self.history.len())
+    }
+
+    /// Take back every tactic after the first `len`
+    pub fn rewind(&mut self, len: usize) -> Result<(), String> {
+        *self = self.replay_prefix(len)?;
         Ok(())
     }
 
+    fn replay_prefix(&self, len: usize) -> Result<ProofAssistant, String> {
+        let mut replayed = ProofAssistant::new();
+        match (&self.theorem, &self.surface) {
+            (Some((name, statement)), _) => replayed.start_proof(name, statement.clone())?,
+            (None, Some((name, source))) => replayed.start_statement(name, source)?,
+            (None, None) => return Err("No proof in progress".to_string()),
+        }
+        let len = len.min(self.history.len());
+        for cmd in &self.history[..len] {
+            replayed.apply_tactic(cmd.tactic.clone(), cmd.goal_id)?;
+        }
+        // Keep the original timestamps and the blocks that survive the cut
+        replayed.history = self.history[..len].to_vec();
+        replayed.blocks = self.blocks.iter().filter(|b| b.end <= len).cloned().collect();
+        Ok(replayed)
+    }
+
+    /// Capture a single open goal together with the script that reaches it
+    pub fn snapshot_goal(&self, goal_id: usize) -> Result<GoalSnapshot, String> {
+        let goal = self.goal(goal_id).ok_or("Goal not found")?;
+        let (theorem, statement) = self.theorem.as_ref().ok_or("No proof in progress")?;
+        
+        Ok(GoalSnapshot {
+            engine_version: ENGINE_VERSION.to_string(),
+            theorem: theorem.clone(),
+            statement: statement.clone(),
+            goal: goal.clone(),
+            script: self.history.clone(),
+        })
+    }
+
     /// Apply a tactic to a goal
     pub fn apply_tactic(&mut self, tactic: Tactic, goal_id: usize) -> Result<(), String> {
+        self.apply_tactic_spawning(tactic, goal_id).map(|_| ())
+    }
+
+    /// Apply a tactic, returning the ids of the subgoals it created
+    fn apply_tactic_spawning(&mut self, tactic: Tactic, goal_id: usize) -> Result<Vec<usize>, String> {
         let goal = self.goals.iter()
             .find(|g| g.id == goal_id)
             .ok_or("Goal not found")?
             .clone();
         
+        self.replacing = Some(goal_id);
+        self.allocated.clear();
         let new_goals = self.execute_tactic(tactic.clone(), &goal)?;
+        let spawned = new_goals.iter().map(|g| g.id).collect();
         
         // Replace goal with new subgoals
         self.goals.retain(|g| g.id != goal_id);
@@ -187,7 +424,7 @@ impl ProofAssistant {
             timestamp: current_timestamp(),
         });
         
-        Ok(())
+        Ok(spawned)
     }
 
     fn execute_tactic(&mut self, tactic: Tactic, goal: &Goal) -> Result<Vec<Goal>, String> {
@@ -195,6 +432,8 @@ impl ProofAssistant {
             Tactic::Intro(name) => self.tactic_intro(goal, &name),
             Tactic::Apply(term) => self.tactic_apply(goal, &term),
             Tactic::Exact(term) => self.tactic_exact(goal, &term),
+            Tactic::Transitivity(mid) => self.tactic_transitivity(goal, &mid),
+            Tactic::Have(name, ty) => self.tactic_have(goal, &name, &ty),
             Tactic::Assumption => self.tactic_assumption(goal),
             Tactic::Reflexivity => self.tactic_reflexivity(goal),
             Tactic::Auto(depth) => self.tactic_auto(goal, depth),
@@ -225,6 +464,20 @@ impl ProofAssistant {
                 conclusion: format!("{:?}", b_ty),
                 term: Some(b_ty.as_ref().clone()),
             }])
+        } else if let Ok(Statement::Pi(x, ty, body)) = statement::parse_statement(&goal.conclusion) {
+            let mut new_context = goal.context.clone();
+            new_context.push(Hypothesis {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                value: None,
+            });
+            
+            Ok(vec![Goal {
+                id: self.next_goal_id(),
+                context: new_context,
+                conclusion: body.rename(&x, name).to_string(),
+                term: None,
+            }])
         } else {
             Err("Cannot introduce: goal is not a Pi type".to_string())
         }
@@ -244,6 +497,19 @@ impl ProofAssistant {
     }
 
     fn tactic_exact(&mut self, goal: &Goal, term_str: &str) -> Result<Vec<Goal>, String> {
+        // A named hypothesis closes a textual goal it is convertible with
+        if let Some(hyp) = goal.context.iter().rev().find(|h| h.name == term_str) {
+            let matches = match (statement::parse_statement(&hyp.ty), statement::parse_statement(&goal.conclusion)) {
+                (Ok(fact), Ok(target)) => fact.convertible(&target),
+                _ => hyp.ty == goal.conclusion,
+            };
+            return if matches {
+                Ok(vec![])
+            } else {
+                Err(format!("`{} : {}` does not match the goal `{}`", hyp.name, hyp.ty, goal.conclusion))
+            };
+        }
+        
         let term = self.parse_term(term_str)?;
         let term_type = self.type_checker.infer(&self.context, &term)
             .map_err(|e| format!("{:?}", e))?;
@@ -256,6 +522,40 @@ impl ProofAssistant {
         }
     }
 
+    fn tactic_transitivity(&mut self, goal: &Goal, mid: &str) -> Result<Vec<Goal>, String> {
+        // Split `Path A a c` at `mid` into `Path A a mid` and `Path A mid c`
+        let Ok(Statement::Path(ty, start, end)) = statement::parse_statement(&goal.conclusion) else {
+            return Err("Cannot apply transitivity: goal is not a path".to_string());
+        };
+        let mid = statement::parse_expr(mid).map_err(|e| e.to_string())?;
+        goal_env(goal).check(&mid, &ty).map_err(|e| e.to_string())?;
+        
+        let left = Statement::Path(ty.clone(), start, mid.clone());
+        let right = Statement::Path(ty, mid, end);
+        Ok(vec![
+            Goal { id: self.next_goal_id(), context: goal.context.clone(), conclusion: left.to_string(), term: None },
+            Goal { id: self.next_goal_id(), context: goal.context.clone(), conclusion: right.to_string(), term: None },
+        ])
+    }
+
+    fn tactic_have(&mut self, goal: &Goal, name: &str, ty: &str) -> Result<Vec<Goal>, String> {
+        // The lemma comes first, then the goal with the lemma in scope
+        let lemma = statement::parse_statement(ty).map_err(|e| e.to_string())?;
+        goal_env(goal).check_statement(&lemma).map_err(|e| e.to_string())?;
+        
+        let mut context = goal.context.clone();
+        context.push(Hypothesis {
+            name: name.to_string(),
+            ty: lemma.to_string(),
+            value: None,
+        });
+        
+        Ok(vec![
+            Goal { id: self.next_goal_id(), context: goal.context.clone(), conclusion: lemma.to_string(), term: None },
+            Goal { id: self.next_goal_id(), context, conclusion: goal.conclusion.clone(), term: goal.term.clone() },
+        ])
+    }
+
     fn tactic_assumption(&mut self, goal: &Goal) -> Result<Vec<Goal>, String> {
         // Search context for matching assumption
         for hyp in &goal.context {
@@ -403,33 +703,45 @@ impl ProofAssistant {
         
         for (i, goal) in self.goals.iter().enumerate() {
             output.push_str(&format!("\nGoal {}: {}\n", i + 1, goal.id));
-            
-            if !goal.context.is_empty() {
-                output.push_str("Context:\n");
-                for hyp in &goal.context {
-                    output.push_str(&format!("  {} : {}\n", hyp.name, hyp.ty));
-                }
-            }
-            
-            output.push_str(&format!("⊢ {}\n", goal.conclusion));
+            render_goal_body(&mut output, goal);
         }
         
         output
     }
 
     /// Generate proof script
+    ///
+    /// Tactics that came from a declarative block are printed as that block.
     pub fn generate_proof_script(&self) -> String {
         let mut script = String::new();
+        let mut i = 0;
         
-        for cmd in &self.history {
-            script.push_str(&format!("{:?}.\n", cmd.tactic));
+        while i < self.history.len() {
+            match self.blocks.iter().find(|b| b.start == i && b.end > i) {
+                Some(block) => {
+                    script.push_str(&block.source);
+                    i = block.end;
+                }
+                None => {
+                    script.push_str(&format!("{}.\n", self.history[i].tactic));
+                    i += 1;
+                }
+            }
         }
         
         script
     }
 
+    /// A fresh goal id; the goal being replaced may hand its id on
     fn next_goal_id(&mut self) -> usize {
-        self.goals.len()
+        let mut id = self.goals.len();
+        while self.allocated.contains(&id)
+            || self.goals.iter().any(|g| g.id == id && Some(id) != self.replacing)
+        {
+            id += 1;
+        }
+        self.allocated.push(id);
+        id
     }
 
     fn parse_term(&self, s: &str) -> Result<Term, String> {
@@ -450,16 +762,83 @@ impl ProofAssistant {
         term.clone()
     }
 
-    fn is_ring_equation(&self, _goal: &Goal) -> bool {
-        false // Simplified
+    fn is_ring_equation(&self, goal: &Goal) -> bool {
+        statement::parse_statement(&goal.conclusion)
+            .is_ok_and(|s| s.ring_equation().is_some())
     }
 
-    fn solve_ring_equation(&self, _goal: &Goal) -> bool {
-        false // Simplified
+    fn solve_ring_equation(&self, goal: &Goal) -> bool {
+        match statement::parse_statement(&goal.conclusion) {
+            Ok(s) => s.ring_equation().is_some_and(|(lhs, rhs)| statement::ring_equal(lhs, rhs)),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Statement environment of a goal: the prelude plus its ℝ-typed hypotheses
+fn goal_env(goal: &Goal) -> Env {
+    let mut env = Env::prelude();
+    for hyp in &goal.context {
+        if let Ok(ty) = statement::parse_ty(&hyp.ty) {
+            env.bind(&hyp.name, ty);
+        }
     }
+    env
 }
 
 impl Tactic {
+    /// Parse one tactic in script syntax, e.g. `intro x` or `try (ring)`
+    pub fn parse(src: &str) -> Result<Tactic, String> {
+        let src = src.trim();
+        let (name, arg) = src.split_once(char::is_whitespace).unwrap_or((src, ""));
+        let arg = arg.trim();
+        let inner = || -> Result<Box<Tactic>, String> {
+            let body = arg.strip_prefix('(').and_then(|a| a.strip_suffix(')')).unwrap_or(arg);
+            Tactic::parse(body).map(Box::new)
+        };
+        let word = || -> Result<String, String> {
+            if arg.is_empty() {
+                Err(format!("`{}` expects an argument", name))
+            } else {
+                Ok(arg.to_string())
+            }
+        };
+        
+        Ok(match (name, arg.is_empty()) {
+            ("intro", _) => Tactic::Intro(word()?),
+            ("apply", _) => Tactic::Apply(word()?),
+            ("exact", _) => Tactic::Exact(word()?),
+            ("assumption", true) => Tactic::Assumption,
+            ("reflexivity", true) => Tactic::Reflexivity,
+            ("symmetry", true) => Tactic::Symmetry,
+            ("transitivity", _) => Tactic::Transitivity(word()?),
+            ("rewrite", _) => match arg.strip_prefix("<-") {
+                Some(h) => Tactic::Rewrite(h.trim().to_string(), Direction::RightToLeft),
+                None => Tactic::Rewrite(word()?, Direction::LeftToRight),
+            },
+            ("induction", _) => Tactic::Induction(word()?),
+            ("case", _) => Tactic::Case(word()?),
+            ("destruct", _) => Tactic::Destruct(word()?),
+            ("path_intro", true) => Tactic::PathIntro,
+            ("path_elim", _) => Tactic::PathElim(word()?),
+            ("auto", true) => Tactic::Auto(5),
+            ("auto", false) => Tactic::Auto(arg.parse().map_err(|_| format!("bad depth `{}`", arg))?),
+            ("simp", true) => Tactic::Simp,
+            ("ring", true) => Tactic::Ring,
+            ("omega", true) => Tactic::Omega,
+            ("hammer", true) => Tactic::Hammer,
+            ("have", _) => {
+                let (h, ty) = arg.split_once(':').ok_or("expected `have <name> : <type>`")?;
+                Tactic::Have(h.trim().to_string(), ty.trim().to_string())
+            }
+            ("suffices", _) => Tactic::Suffices(word()?),
+            ("by_contradiction", true) => Tactic::ByContradiction,
+            ("try", false) => Tactic::Try(inner()?),
+            ("repeat", false) => Tactic::Repeat(inner()?),
+            _ => return Err(format!("unknown tactic `{}`", src)),
+        })
+    }
+
     pub fn from_name(name: &str) -> Option<Tactic> {
         match name {
             "intro" => Some(Tactic::Intro("x".to_string())),
@@ -472,6 +851,42 @@ impl Tactic {
     }
 }
 
+/// Script syntax, as read by [`Tactic::parse`]
+impl fmt::Display for Tactic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tactic::Intro(x) => write!(f, "intro {}", x),
+            Tactic::Apply(t) => write!(f, "apply {}", t),
+            Tactic::Exact(t) => write!(f, "exact {}", t),
+            Tactic::Assumption => write!(f, "assumption"),
+            Tactic::Reflexivity => write!(f, "reflexivity"),
+            Tactic::Symmetry => write!(f, "symmetry"),
+            Tactic::Transitivity(mid) => write!(f, "transitivity {}", mid),
+            Tactic::Rewrite(h, Direction::LeftToRight) => write!(f, "rewrite {}", h),
+            Tactic::Rewrite(h, Direction::RightToLeft) => write!(f, "rewrite <- {}", h),
+            Tactic::Induction(x) => write!(f, "induction {}", x),
+            Tactic::Case(x) => write!(f, "case {}", x),
+            Tactic::Destruct(x) => write!(f, "destruct {}", x),
+            Tactic::PathIntro => write!(f, "path_intro"),
+            Tactic::PathElim(p) => write!(f, "path_elim {}", p),
+            Tactic::Auto(depth) => write!(f, "auto {}", depth),
+            Tactic::Simp => write!(f, "simp"),
+            Tactic::Ring => write!(f, "ring"),
+            Tactic::Omega => write!(f, "omega"),
+            Tactic::Hammer => write!(f, "hammer"),
+            Tactic::Have(h, ty) => write!(f, "have {} : {}", h, ty),
+            Tactic::Suffices(ty) => write!(f, "suffices {}", ty),
+            Tactic::ByContradiction => write!(f, "by_contradiction"),
+            Tactic::Try(t) => write!(f, "try ({})", t),
+            Tactic::Repeat(t) => write!(f, "repeat ({})", t),
+            // No surface syntax yet
+            Tactic::Transport(..) | Tactic::Hcomp(_) | Tactic::First(_) | Tactic::Solve(_) | Tactic::Ltac(..) => {
+                write!(f, "{:?}", self)
+            }
+        }
+    }
+}
+
 impl HintDatabase {
     pub fn new() -> Self {
         HintDatabase {
@@ -499,7 +914,195 @@ impl AutomationEngine {
     }
 }
 
+impl GoalSnapshot {
+    /// Encode as a URL fragment (`goal=<percent-encoded JSON>`)
+    pub fn to_fragment(&self) -> String {
+        let json = serde_json::to_string(self).expect("goal snapshots are serializable");
+        format!("goal={}", percent_encode(&json))
+    }
+
+    /// Decode a fragment produced by [`GoalSnapshot::to_fragment`]
+    pub fn from_fragment(fragment: &str) -> Result<GoalSnapshot, String> {
+        let encoded = fragment.trim_start_matches('#')
+            .strip_prefix("goal=")
+            .ok_or("Not a goal permalink")?;
+        let json = percent_decode(encoded)?;
+        serde_json::from_str(&json).map_err(|e| format!("Malformed goal snapshot: {}", e))
+    }
+
+    /// Render the snapshot read-only, in the same layout as the proof state
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        
+        output.push_str(&format!("Theorem {} : {}\n", self.theorem, self.statement));
+        if !self.script.is_empty() {
+            output.push_str("Script:\n");
+            for cmd in &self.script {
+                output.push_str(&format!("  {}.\n", cmd.tactic));
+            }
+        }
+        output.push_str("================\n");
+        output.push_str(&format!("\nGoal {}\n", self.goal.id));
+        render_goal_body(&mut output, &self.goal);
+        
+        output
+    }
+
+    /// Replay the script prefix to reconstruct the live proof state
+    ///
+    /// Falls back to [`GoalReplay::Static`] when the statement or script no
+    /// longer checks, or when it reaches a different goal.
+    pub fn replay(&self) -> GoalReplay {
+        let mut assistant = ProofAssistant::new();
+        let replayed = assistant.start_proof(&self.theorem, self.statement.clone())
+            .and_then(|()| {
+                self.script.iter().try_for_each(|cmd| {
+                    assistant.apply_tactic(cmd.tactic.clone(), cmd.goal_id)
+                })
+            });
+        
+        let reason = match replayed {
+            Err(e) => format!("script no longer replays: {}", e),
+            Ok(()) => match assistant.goal(self.goal.id) {
+                Some(goal) if *goal == self.goal => return GoalReplay::Live(assistant),
+                Some(_) => "replay reached a different goal".to_string(),
+                None => "replay no longer reaches this goal".to_string(),
+            },
+        };
+        
+        GoalReplay::Static { snapshot: self.clone(), reason }
+    }
+}
+
+impl GoalReplay {
+    pub fn is_live(&self) -> bool {
+        matches!(self, GoalReplay::Live(_))
+    }
+
+    /// Banner explaining why only the static snapshot is shown
+    pub fn banner(&self) -> Option<String> {
+        match self {
+            GoalReplay::Live(_) => None,
+            GoalReplay::Static { snapshot, reason } => Some(format!(
+                "Showing a static snapshot from SCTT {}: {} under SCTT {}",
+                snapshot.engine_version, reason, ENGINE_VERSION,
+            )),
+        }
+    }
+}
+
+fn render_goal_body(output: &mut String, goal: &Goal) {
+    if !goal.context.is_empty() {
+        output.push_str("Context:\n");
+        for hyp in &goal.context {
+            output.push_str(&format!("  {} : {}\n", hyp.name, hyp.ty));
+        }
+    }
+    
+    output.push_str(&format!("⊢ {}\n", goal.conclusion));
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> Result<String, String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3).ok_or("Truncated escape in permalink")?;
+            out.push(u8::from_str_radix(hex, 16).map_err(|_| "Bad escape in permalink")?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| "Permalink is not UTF-8".to_string())
+}
+
 fn current_timestamp() -> u64 {
     // In WASM, use performance.now()
     0
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sctt_typechecker::Level;
+
+    fn universe() -> Box<Term> {
+        Box::new(Term::Universe(Level::Zero))
+    }
+
+    fn two_intros() -> ProofAssistant {
+        let mut assistant = ProofAssistant::new();
+        let statement = Term::Pi(universe(), Box::new(Term::Pi(universe(), universe())));
+        assistant.start_proof("const", statement).unwrap();
+        assistant.apply_tactic(Tactic::Intro("A".to_string()), 0).unwrap();
+        assistant.apply_tactic(Tactic::Intro("B".to_string()), 1).unwrap();
+        assistant
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_preserves_hypotheses() {
+        let assistant = two_intros();
+        let snapshot = assistant.snapshot_goal(1).unwrap();
+        let decoded = GoalSnapshot::from_fragment(&format!("#{}", snapshot.to_fragment())).unwrap();
+        assert_eq!(decoded, snapshot);
+        
+        let names: Vec<_> = decoded.goal.context.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["A", "B"]);
+        assert_eq!(decoded.goal.id, 1);
+        
+        let rendered = decoded.render();
+        assert!(rendered.find("  A :").unwrap() < rendered.find("  B :").unwrap());
+        assert!(rendered.contains("Goal 1"));
+        assert!(rendered.starts_with("Theorem const : Π (x0 : Type). Π (x1 : Type). Type\n"));
+        assert!(rendered.contains("  intro A.\n"));
+    }
+
+    #[test]
+    fn test_replay_reaches_identical_goal() {
+        let snapshot = two_intros().snapshot_goal(1).unwrap();
+        match snapshot.replay() {
+            GoalReplay::Live(assistant) => assert_eq!(assistant.goal(1), Some(&snapshot.goal)),
+            GoalReplay::Static { reason, .. } => panic!("replay failed: {}", reason),
+        }
+    }
+
+    #[test]
+    fn test_fork_is_independent_and_rewind_takes_back_tactics() {
+        let mut assistant = two_intros();
+        let mut fork = assistant.fork().unwrap();
+        fork.apply_tactic(Tactic::Assumption, 1).unwrap();
+        assert_eq!(assistant.history().len(), 2);
+        assert_eq!(fork.history().len(), 3);
+
+        assistant.rewind(1).unwrap();
+        assert_eq!(assistant.history().len(), 1);
+        let goal = assistant.goal(1).unwrap();
+        assert_eq!(goal.context.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), ["A"]);
+    }
+
+    #[test]
+    fn test_stale_snapshot_falls_back_to_static() {
+        let mut snapshot = two_intros().snapshot_goal(1).unwrap();
+        snapshot.statement = Term::Universe(Level::Zero);
+        
+        let replay = snapshot.replay();
+        assert!(!replay.is_live());
+        assert!(replay.banner().unwrap().contains("no longer replays"));
+    }
+}
@@ -0,0 +1,99 @@
+//! Collaboration wire protocol
+//!
+//! JSON messages exchanged over a session's websocket. They live outside
+//! `collaborative` so the server can speak the protocol without the Yew UI.
+//!
+//! Fields added after a message first shipped are optional and left out
+//! when unset, so older peers keep decoding what newer ones send and the
+//! other way round.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CursorPosition, Operation, ProofState, Session, User};
+
+/// Server messages
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+    Welcome {
+        session: Session,
+        user_id: String,
+    },
+    UserJoined {
+        user: User,
+    },
+    UserLeft {
+        user_id: String,
+    },
+    Operation {
+        op: Operation,
+        user_id: String,
+        version: u64,
+    },
+    CursorUpdate {
+        user_id: String,
+        cursor: CursorPosition,
+        /// The goal this user is looking at in the proof tree
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        focus: Option<usize>,
+    },
+    ProofUpdate {
+        proof_state: ProofState,
+    },
+    /// The proof driver changed, or a newcomer learns who drives
+    DriverChanged {
+        driver: Option<String>,
+        message: String,
+    },
+    /// Someone asked the driver for control
+    ControlRequested {
+        user_id: String,
+    },
+    /// A proof action from a connection that does not hold the driver token
+    NotDriving {
+        driver: Option<String>,
+    },
+    /// A scripted transaction holds the session; the action was not applied
+    Busy {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Client messages
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Join {
+        user_name: String,
+    },
+    Operation {
+        op: Operation,
+        version: u64,
+    },
+    CursorUpdate {
+        cursor: CursorPosition,
+        /// Id of the focused goal; clients without proof-tree focus omit it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        focus: Option<usize>,
+    },
+    ProofAction {
+        action: ProofAction,
+    },
+    /// Ask the current driver for control of the proof
+    RequestControl,
+    /// Hand control to another connection; only the driver may send this
+    GrantControl {
+        to: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ProofAction {
+    ApplyTactic {
+        goal_id: usize,
+        tactic: String,
+    },
+    Undo,
+    Redo,
+}
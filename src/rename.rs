@@ -0,0 +1,338 @@
+//! Checked rename of a global definition
+//!
+//! A workspace is a list of documents in the importable [`cubicaltt`]
+//! syntax, checked in order as one file: a definition sees every earlier
+//! one, and a later definition of the same name shadows an earlier one.
+//! [`rename_definition`] renames the definition a name currently resolves
+//! to. Occurrences come from the parser's scope information, so binders of
+//! the old name and the references they capture, comments, and references
+//! to a shadowed definition of the same name are left alone.
+//!
+//! Every definition is checked again with the rewritten sources and the
+//! rename is written only if nothing that checked before stops checking;
+//! otherwise no document changes.
+//!
+//! [`cubicaltt`]: crate::cubicaltt
+
+use std::fmt;
+
+use crate::cubicaltt::{self, ImportError, Location, Parsed};
+use crate::surface;
+
+/// A named source file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub name: String,
+    pub source: String,
+}
+
+impl Document {
+    pub fn new(name: &str, source: &str) -> Self {
+        Document { name: name.to_string(), source: source.to_string() }
+    }
+}
+
+/// Where a workspace's sources live
+pub trait Workspace {
+    fn documents(&self) -> Vec<Document>;
+    fn write(&mut self, name: &str, source: &str) -> Result<(), String>;
+}
+
+impl Workspace for Vec<Document> {
+    fn documents(&self) -> Vec<Document> {
+        self.clone()
+    }
+
+    fn write(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let doc = self.iter_mut().find(|d| d.name == name).ok_or_else(|| format!("no document `{}`", name))?;
+        doc.source = source.to_string();
+        Ok(())
+    }
+}
+
+/// One rewritten name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub document: String,
+    pub location: Location,
+    /// The definition the occurrence is in
+    pub definition: String,
+}
+
+/// A definition the rename would stop from checking
+#[derive(Debug, Clone, PartialEq)]
+pub struct Broken {
+    pub document: String,
+    pub definition: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameError {
+    NotDefined(String),
+    InvalidName(String),
+    /// Some definition already has the new name
+    NameInUse(String),
+    Parse { document: String, error: ImportError },
+    /// The rewritten workspace no longer checks; nothing was written
+    Breaks(Vec<Broken>),
+    /// Writing a document failed; the ones already written were restored
+    Write { document: String, message: String },
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenameError::NotDefined(name) => write!(f, "no definition named `{}`", name),
+            RenameError::InvalidName(name) => write!(f, "`{}` cannot name a definition", name),
+            RenameError::NameInUse(name) => write!(f, "`{}` is already defined", name),
+            RenameError::Parse { document, error } => write!(f, "{}: {}", document, error),
+            RenameError::Breaks(broken) => {
+                write!(f, "the rename would break ")?;
+                for (i, b) in broken.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    write!(f, "{}`{}` in {} ({})", sep, b.definition, b.document, b.error)?;
+                }
+                Ok(())
+            }
+            RenameError::Write { document, message } => write!(f, "could not write {}: {}", document, message),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// A checked rename, ready to write
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    pub old: String,
+    pub new: String,
+    pub occurrences: Vec<Occurrence>,
+    /// Changed documents with their rewritten sources
+    pub changed: Vec<Document>,
+    /// Definitions checked again, by name, in workspace order
+    pub rechecked: Vec<String>,
+}
+
+/// Work out and check a rename without writing anything
+pub fn plan(documents: &[Document], old: &str, new: &str) -> Result<Rename, RenameError> {
+    if !cubicaltt::is_definition_name(new) {
+        return Err(RenameError::InvalidName(new.to_string()));
+    }
+    let parsed = parse_all(documents)?;
+    if parsed.iter().any(|(_, p)| p.definition.name == new) {
+        return Err(RenameError::NameInUse(new.to_string()));
+    }
+    // The definition `old` resolves to after the whole workspace; nothing
+    // after it can see another definition of that name
+    let target = parsed
+        .iter()
+        .rposition(|(_, p)| p.definition.name == old)
+        .ok_or_else(|| RenameError::NotDefined(old.to_string()))?;
+
+    let mut occurrences = Vec::new();
+    for (k, (doc, p)) in parsed[target..].iter().enumerate() {
+        // A definition does not see itself: its own `old` is the shadowed one
+        let at = if k == 0 { vec![p.name_at] } else { references(p, old) };
+        for location in at {
+            occurrences.push(Occurrence {
+                document: documents[*doc].name.clone(),
+                location,
+                definition: p.definition.name.clone(),
+            });
+        }
+    }
+
+    let mut changed = Vec::new();
+    let mut rewritten = documents.to_vec();
+    for (i, doc) in documents.iter().enumerate() {
+        let at: Vec<Location> = occurrences.iter().filter(|o| o.document == doc.name).map(|o| o.location).collect();
+        if !at.is_empty() {
+            rewritten[i].source = rewrite(&doc.source, &at, old.chars().count(), new);
+            changed.push(rewritten[i].clone());
+        }
+    }
+
+    let before = check_all(&parsed);
+    let after_parsed = parse_all(&rewritten)?;
+    let after = check_all(&after_parsed);
+    let broken: Vec<Broken> = before
+        .iter()
+        .zip(&after)
+        .zip(&after_parsed)
+        .filter_map(|((was, now), (doc, p))| match (was, now) {
+            (Ok(()), Err(e)) => Some(Broken {
+                document: documents[*doc].name.clone(),
+                definition: p.definition.name.clone(),
+                error: e.to_string(),
+            }),
+            _ => None,
+        })
+        .collect();
+    if !broken.is_empty() {
+        return Err(RenameError::Breaks(broken));
+    }
+
+    Ok(Rename {
+        old: old.to_string(),
+        new: new.to_string(),
+        occurrences,
+        changed,
+        rechecked: after_parsed.iter().map(|(_, p)| p.definition.name.clone()).collect(),
+    })
+}
+
+/// Rename the definition `old` resolves to, writing every changed document
+/// or none
+pub fn rename_definition(workspace: &mut impl Workspace, old: &str, new: &str) -> Result<Rename, RenameError> {
+    let documents = workspace.documents();
+    let rename = plan(&documents, old, new)?;
+    for (i, doc) in rename.changed.iter().enumerate() {
+        if let Err(message) = workspace.write(&doc.name, &doc.source) {
+            for written in &rename.changed[..i] {
+                if let Some(original) = documents.iter().find(|d| d.name == written.name) {
+                    let _ = workspace.write(&original.name, &original.source);
+                }
+            }
+            return Err(RenameError::Write { document: doc.name.clone(), message });
+        }
+    }
+    Ok(rename)
+}
+
+/// Every definition with the index of its document
+fn parse_all(documents: &[Document]) -> Result<Vec<(usize, Parsed)>, RenameError> {
+    let mut all = Vec::new();
+    for (i, doc) in documents.iter().enumerate() {
+        let parsed = cubicaltt::parse(&doc.source)
+            .map_err(|error| RenameError::Parse { document: doc.name.clone(), error })?;
+        all.extend(parsed.into_iter().map(|p| (i, p)));
+    }
+    Ok(all)
+}
+
+fn check_all(parsed: &[(usize, Parsed)]) -> Vec<Result<(), surface::SurfaceError>> {
+    let definitions: Vec<_> = parsed.iter().map(|(_, p)| p.definition.clone()).collect();
+    surface::check(&definitions)
+}
+
+fn references(parsed: &Parsed, name: &str) -> Vec<Location> {
+    parsed.free.iter().filter(|(_, n)| n == name).map(|(at, _)| *at).collect()
+}
+
+/// Replace the `len`-character names starting at `at` with `new`
+fn rewrite(source: &str, at: &[Location], len: usize, new: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    for (n, line) in source.split('\n').enumerate() {
+        if n > 0 {
+            out.push('\n');
+        }
+        let mut starts: Vec<usize> = at.iter().filter(|l| l.line == n + 1).map(|l| l.column - 1).collect();
+        starts.sort_unstable();
+        let mut skip = 0;
+        for (col, c) in line.chars().enumerate() {
+            if starts.binary_search(&col).is_ok() {
+                out.push_str(new);
+                skip = len;
+            }
+            if skip > 0 {
+                skip -= 1;
+            } else {
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRELUDE: &str = "\
+id (A : U) (a : A) : A = a
+
+const (A B : U) (a : A) (b : B) : A = a
+";
+
+    /// Shadows the prelude's `id`: `twice` uses the prelude one, `back` the
+    /// user one, and `again` binds `id` locally
+    const USER: &str = "\
+twice (A : U) (a : A) : A = id A (id A a)
+
+id (A : U) (x : A) : A = x
+
+back (A : U) (a : A) : A = id A a -- id in a comment
+
+again (A : U) (id : A) : A = id
+
+pick (A : U) (a b : A) : A = const A A b a
+";
+
+    fn workspace() -> Vec<Document> {
+        vec![Document::new("prelude", PRELUDE), Document::new("user", USER)]
+    }
+
+    #[test]
+    fn test_rename_rewrites_only_what_resolves_to_the_definition() {
+        let mut ws = workspace();
+        let rename = rename_definition(&mut ws, "id", "same").unwrap();
+
+        assert_eq!(ws[0].source, PRELUDE);
+        let lines: Vec<&str> = ws[1].source.lines().collect();
+        assert_eq!(lines[0], "twice (A : U) (a : A) : A = id A (id A a)");
+        assert_eq!(lines[2], "same (A : U) (x : A) : A = x");
+        assert_eq!(lines[4], "back (A : U) (a : A) : A = same A a -- id in a comment");
+        assert_eq!(lines[6], "again (A : U) (id : A) : A = id");
+        assert_eq!(rename.occurrences.len(), 2);
+        assert_eq!(rename.occurrences[1], Occurrence {
+            document: "user".to_string(),
+            location: Location { line: 5, column: 28 },
+            definition: "back".to_string(),
+        });
+        assert_eq!(rename.rechecked.len(), 7);
+    }
+
+    #[test]
+    fn test_bad_names_are_refused() {
+        let ws = workspace();
+        assert_eq!(plan(&ws, "id", "const"), Err(RenameError::NameInUse("const".to_string())));
+        assert_eq!(plan(&ws, "id", "hcomp"), Err(RenameError::InvalidName("hcomp".to_string())));
+        assert_eq!(plan(&ws, "nope", "yes"), Err(RenameError::NotDefined("nope".to_string())));
+    }
+
+    #[test]
+    fn test_a_captured_reference_rolls_the_rename_back() {
+        // `back` binds `a`, which would capture its renamed reference
+        let mut ws = workspace();
+        let err = rename_definition(&mut ws, "id", "a").unwrap_err();
+        assert!(matches!(&err, RenameError::Breaks(b) if b.len() == 1 && b[0].definition == "back"), "{}", err);
+        assert_eq!(ws, workspace());
+    }
+
+    #[test]
+    fn test_a_failed_write_restores_the_written_documents() {
+        struct Flaky {
+            docs: Vec<Document>,
+            writes: usize,
+        }
+        impl Workspace for Flaky {
+            fn documents(&self) -> Vec<Document> {
+                self.docs.clone()
+            }
+            fn write(&mut self, name: &str, source: &str) -> Result<(), String> {
+                self.writes += 1;
+                if self.writes == 2 {
+                    return Err("disk full".to_string());
+                }
+                self.docs.write(name, source)
+            }
+        }
+
+        // `const` is defined in one document and used in the other
+        let mut ws = Flaky { docs: workspace(), writes: 0 };
+        let err = rename_definition(&mut ws, "const", "first").unwrap_err();
+        assert_eq!(err, RenameError::Write { document: "user".to_string(), message: "disk full".to_string() });
+        assert_eq!(ws.docs, workspace());
+    }
+}
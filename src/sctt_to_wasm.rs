@@ -6,6 +6,7 @@
 //! 3. Optimization passes for proof compression
 
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::rc::Rc;
 use wasm_encoder::{
     CodeSection, EntityType, ExportKind, ExportSection, Function, FunctionSection,
@@ -16,7 +17,7 @@ use wasm_encoder::{
 use crate::sctt_typechecker::{Term, Value, Level, DeBruijnIndex, IntervalPoint};
 
 /// CPS Intermediate Representation preserving dependent types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CpsIr {
     /// Continuation variable
     KVar(String),
@@ -99,7 +100,7 @@ pub enum CpsIr {
 }
 
 /// IR Types with proof annotations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum IrType {
     /// Base types
     I32,
@@ -127,14 +128,14 @@ pub enum IrType {
     },
     
     /// Proof type
-    Proof(ProofProp),
+    Proof(Box<ProofProp>),
     
     /// Universe (for type-level computation)
     Universe(Level),
 }
 
 /// Proof terms for verification
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ProofTerm {
     /// Axiom (trusted base)
     Axiom(String),
@@ -188,7 +189,7 @@ pub enum ProofTerm {
 }
 
 /// Propositions that can be proven
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ProofProp {
     /// Type equality
     Equal(IrType, IrType),
@@ -214,7 +215,7 @@ pub enum ProofProp {
 pub struct MemoryRegion(pub usize);
 
 /// Linearity proof for resources
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LinearityProof {
     pub resource_id: String,
     pub creation_point: usize,
@@ -223,47 +224,47 @@ pub struct LinearityProof {
 }
 
 /// Memory safety proof
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MemorySafetyProof {
     pub bounds_check: BoundsProof,
     pub alignment: AlignmentProof,
     pub no_use_after_free: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BoundsProof {
     pub lower_bound: i64,
     pub upper_bound: i64,
     pub in_range: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AlignmentProof {
     pub alignment: usize,
     pub offset: usize,
     pub is_aligned: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UsageProof {
     pub single_use: bool,
     pub consumption_site: Option<usize>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FunctionProof {
     pub termination: Option<TerminationProof>,
     pub memory_safety: bool,
     pub side_effects: Vec<SideEffect>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TerminationProof {
     pub metric: Box<CpsIr>,
     pub decreasing: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SideEffect {
     Memory,
     IO,
@@ -291,6 +292,35 @@ pub struct ScttToWasmCompiler {
     subproof_cache: HashMap<ProofTerm, String>,
 }
 
+/// Compilation errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    /// A term or IR node the pipeline does not lower yet
+    Unsupported(&'static str),
+    /// A load whose bounds proof does not hold
+    MemorySafety { lower_bound: i64, upper_bound: i64 },
+    /// More functions than a WASM function index can address
+    TooManyFunctions(usize),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::Unsupported(what) => write!(f, "cannot compile {} yet", what),
+            CompileError::MemorySafety { lower_bound, upper_bound } => write!(
+                f,
+                "load outside its proven bounds [{}, {})",
+                lower_bound, upper_bound
+            ),
+            CompileError::TooManyFunctions(n) => write!(f, "{} functions exceed the WASM index space", n),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+pub type Result<T> = std::result::Result<T, CompileError>;
+
 #[derive(Debug, Clone, Copy)]
 pub enum OptLevel {
     None,
@@ -311,71 +341,71 @@ impl ScttToWasmCompiler {
     }
 
     /// Stage 1: SCTT → CPS-IR transformation
-    pub fn sctt_to_ir(&mut self, term: &Term) -> CpsIr {
+    pub fn sctt_to_ir(&mut self, term: &Term) -> Result<CpsIr> {
         let cont = self.fresh_cont();
         self.cps_transform(term, &cont)
     }
 
-    fn cps_transform(&mut self, term: &Term, cont: &str) -> CpsIr {
+    fn cps_transform(&mut self, term: &Term, cont: &str) -> Result<CpsIr> {
         match term {
             Term::Var(idx) => {
                 let var_name = format!("v{}", idx.0);
                 let proof = ProofTerm::Axiom(format!("var_axiom_{}", idx.0));
-                CpsIr::ContApp {
+                Ok(CpsIr::ContApp {
                     cont: cont.to_string(),
                     value: Box::new(CpsIr::Var(var_name, proof)),
-                }
+                })
             }
             
             Term::Lambda(ty, body) => {
                 let param = self.fresh_var();
                 let body_cont = self.fresh_cont();
-                let body_cps = self.cps_transform(body, &body_cont);
+                let body_cps = self.cps_transform(body, &body_cont)?;
                 
                 let proof = ProofTerm::Lambda {
                     param: param.clone(),
                     body: Box::new(ProofTerm::Axiom("lambda_proof".to_string())),
                 };
                 
-                CpsIr::Lambda {
+                Ok(CpsIr::Lambda {
                     param,
                     param_ty: self.type_to_ir_type(ty),
                     body: Box::new(body_cps),
                     cont: Box::new(CpsIr::KVar(cont.to_string())),
                     proof,
-                }
+                })
             }
             
             Term::App(func, arg) => {
                 let func_cont = self.fresh_cont();
                 let arg_cont = self.fresh_cont();
                 
-                let func_cps = self.cps_transform(func, &func_cont);
-                let arg_cps = self.cps_transform(arg, &arg_cont);
+                let func_cps = self.cps_transform(func, &func_cont)?;
+                let arg_cps = self.cps_transform(arg, &arg_cont)?;
                 
                 let proof = ProofTerm::App {
                     func: Box::new(ProofTerm::Axiom("app_func".to_string())),
                     arg: Box::new(ProofTerm::Axiom("app_arg".to_string())),
                 };
                 
-                CpsIr::App {
+                Ok(CpsIr::App {
                     func: Box::new(func_cps),
                     arg: Box::new(arg_cps),
                     cont: cont.to_string(),
                     proof,
-                }
+                })
             }
             
-            _ => todo!("Other SCTT term transformations"),
+            _ => Err(CompileError::Unsupported("this term former")),
         }
     }
 
     /// Stage 2: IR → WASM with proof certificates
-    pub fn ir_to_wasm(&mut self, ir: &CpsIr) -> WasmModule {
+    pub fn ir_to_wasm(&mut self, ir: &CpsIr) -> Result<WasmModule> {
         let mut module = WasmModule::new();
         
         // Generate main function
-        let main_func = self.compile_ir_to_wasm(ir);
+        let main_func = self.compile_ir_to_wasm(ir)?;
         module.add_function("main", main_func);
         
         // Embed proof certificate
@@ -386,10 +416,10 @@ impl ScttToWasmCompiler {
         let verifier = self.generate_runtime_verifier();
         module.add_function("verify", verifier);
         
-        module
+        Ok(module)
     }
 
-    fn compile_ir_to_wasm(&mut self, ir: &CpsIr) -> WasmFunction {
+    fn compile_ir_to_wasm(&mut self, ir: &CpsIr) -> Result<WasmFunction> {
         let mut func = WasmFunction::new();
         
         match ir {
@@ -406,7 +436,7 @@ impl ScttToWasmCompiler {
             }
             
             CpsIr::Let { name, value, body, proof, .. } => {
-                let value_func = self.compile_ir_to_wasm(value);
+                let value_func = self.compile_ir_to_wasm(value)?;
                 func.append(value_func);
                 func.add_instruction(WasmInstruction::LocalSet(name.clone()));
                 
@@ -414,7 +444,7 @@ impl ScttToWasmCompiler {
                     func.add_proof_check(proof);
                 }
                 
-                let body_func = self.compile_ir_to_wasm(body);
+                let body_func = self.compile_ir_to_wasm(body)?;
                 func.append(body_func);
             }
             
@@ -431,11 +461,15 @@ impl ScttToWasmCompiler {
             
             CpsIr::Load { ptr, offset, cont, safety_proof } => {
                 // Compile-time bounds check
-                if !safety_proof.bounds_check.in_range {
-                    panic!("Memory safety violation at compile time");
+                let bounds = &safety_proof.bounds_check;
+                if !bounds.in_range {
+                    return Err(CompileError::MemorySafety {
+                        lower_bound: bounds.lower_bound,
+                        upper_bound: bounds.upper_bound,
+                    });
                 }
                 
-                let ptr_func = self.compile_ir_to_wasm(ptr);
+                let ptr_func = self.compile_ir_to_wasm(ptr)?;
                 func.append(ptr_func);
                 func.add_instruction(WasmInstruction::I32Const(*offset as i32));
                 func.add_instruction(WasmInstruction::I32Add);
@@ -443,10 +477,10 @@ impl ScttToWasmCompiler {
                 func.add_instruction(WasmInstruction::LocalSet(cont.clone()));
             }
             
-            _ => todo!("Other IR to WASM compilations"),
+            _ => return Err(CompileError::Unsupported("this IR node")),
         }
         
-        func
+        Ok(func)
     }
 
     /// Stage 3: Optimization passes
@@ -538,9 +572,7 @@ impl ScttToWasmCompiler {
     fn collect_subproofs(&mut self, ir: &CpsIr) {
         match ir {
             CpsIr::Let { proof, .. } | CpsIr::Lambda { proof, .. } => {
-                if let Some(id) = self.subproof_cache.get(proof) {
-                    // Already cached
-                } else {
+                if !self.subproof_cache.contains_key(proof) {
                     let id = self.fresh_var();
                     self.subproof_cache.insert(proof.clone(), id);
                 }
@@ -683,7 +715,7 @@ impl ScttToWasmCompiler {
     }
 
     fn should_keep_proof(&self, _proof: &ProofTerm) -> bool {
-        self.opt_level as u8 < OptLevel::Aggressive as u8
+        (self.opt_level as u8) < OptLevel::Aggressive as u8
     }
 
     fn proof_affects_runtime(&self, _proof: &ProofTerm) -> bool {
@@ -721,7 +753,7 @@ impl WasmModule {
         self.custom_sections.push((name.to_string(), data));
     }
 
-    pub fn encode(&self) -> Vec<u8> {
+    pub fn encode(&self) -> Result<Vec<u8>> {
         let mut module = Module::new();
         
         // Add type section
@@ -734,7 +766,8 @@ impl WasmModule {
         // Add function section
         let mut functions = FunctionSection::new();
         for i in 0..self.functions.len() {
-            functions.function(i as u32);
+            let index = u32::try_from(i).map_err(|_| CompileError::TooManyFunctions(self.functions.len()))?;
+            functions.function(index);
         }
         module.section(&functions);
         
@@ -752,12 +785,12 @@ impl WasmModule {
         // Add custom sections
         for (name, data) in &self.custom_sections {
             module.section(&wasm_encoder::CustomSection {
-                name: name.as_str(),
-                data: &data,
+                name: name.as_str().into(),
+                data: data.as_slice().into(),
             });
         }
         
-        module.finish()
+        Ok(module.finish())
     }
 }
 
@@ -819,7 +852,7 @@ impl WasmInstruction {
             WasmInstruction::I32Const(n) => Instruction::I32Const(*n),
             WasmInstruction::I64Const(n) => Instruction::I64Const(*n),
             WasmInstruction::I32Add => Instruction::I32Add,
-            WasmInstruction::I64Load(mem_arg) => {
+            WasmInstruction::I64Load => {
                 Instruction::I64Load(wasm_encoder::MemArg {
                     offset: 0,
                     align: 3,
@@ -850,7 +883,7 @@ mod tests {
             Box::new(Term::Var(DeBruijnIndex(0))),
         );
         
-        let cps = compiler.sctt_to_ir(&id);
+        let cps = compiler.sctt_to_ir(&id).unwrap();
         
         match cps {
             CpsIr::Lambda { .. } => (),
@@ -901,10 +934,39 @@ mod tests {
             },
         };
         
-        let mut wasm = compiler.compile_ir_to_wasm(&safe_load);
+        let mut wasm = compiler.compile_ir_to_wasm(&safe_load).unwrap();
         assert!(wasm.instructions.len() > 0);
     }
 
+    #[test]
+    fn test_out_of_bounds_load_is_an_error() {
+        let mut compiler = ScttToWasmCompiler::new(OptLevel::Basic);
+        
+        let unsafe_load = CpsIr::Load {
+            ptr: Box::new(CpsIr::Const(3000)),
+            offset: 0,
+            cont: "k".to_string(),
+            safety_proof: MemorySafetyProof {
+                bounds_check: BoundsProof {
+                    lower_bound: 0,
+                    upper_bound: 2000,
+                    in_range: false,
+                },
+                alignment: AlignmentProof {
+                    alignment: 8,
+                    offset: 0,
+                    is_aligned: true,
+                },
+                no_use_after_free: true,
+            },
+        };
+        
+        assert_eq!(
+            compiler.compile_ir_to_wasm(&unsafe_load).err(),
+            Some(CompileError::MemorySafety { lower_bound: 0, upper_bound: 2000 })
+        );
+    }
+
     #[test] 
     fn test_optimization_pipeline() {
         let mut compiler = ScttToWasmCompiler::new(OptLevel::Aggressive);
@@ -945,7 +1007,7 @@ mod tests {
         }
         
         let start = Instant::now();
-        let _wasm = compiler.ir_to_wasm(&ir);
+        let _wasm = compiler.ir_to_wasm(&ir).unwrap();
         let elapsed = start.elapsed();
         
         // Check we meet performance target: > 10,000 lines/second
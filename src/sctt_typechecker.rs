@@ -4,18 +4,20 @@
 //! - Type universe hierarchy with cumulative levels
 //! - Dependent function types (Π-types)
 //! - Path types with smooth structure
+//! - The circle S¹, a higher inductive type with a point and a loop
 //! - Bidirectional type checking
 //! - Normalization by evaluation (NbE)
 
 use std::collections::HashMap;
 use std::rc::Rc;
+use serde::{Deserialize, Serialize};
 
 /// De Bruijn index for variable representation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DeBruijnIndex(pub usize);
 
 /// Universe levels for the type hierarchy
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Level {
     Zero,
     Succ(usize),
@@ -42,7 +44,7 @@ impl Level {
 }
 
 /// Interval points for path types (De Morgan algebra structure)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum IntervalPoint {
     Zero,                                    // 0 endpoint
     One,                                     // 1 endpoint
@@ -52,8 +54,48 @@ pub enum IntervalPoint {
     Neg(Box<IntervalPoint>),                // ¬i
 }
 
+impl IntervalPoint {
+    /// Apply the endpoint laws: `¬0 = 1`, `¬¬i = i`, `0 ∧ i = 0`, `1 ∨ i = 1`, ...
+    pub fn simplify(&self) -> IntervalPoint {
+        use IntervalPoint::*;
+        match self {
+            Neg(r) => match r.simplify() {
+                Zero => One,
+                One => Zero,
+                Neg(inner) => *inner,
+                other => Neg(Box::new(other)),
+            },
+            Meet(r, s) => match (r.simplify(), s.simplify()) {
+                (Zero, _) | (_, Zero) => Zero,
+                (One, other) | (other, One) => other,
+                (r, s) => Meet(Box::new(r), Box::new(s)),
+            },
+            Join(r, s) => match (r.simplify(), s.simplify()) {
+                (One, _) | (_, One) => One,
+                (Zero, other) | (other, Zero) => other,
+                (r, s) => Join(Box::new(r), Box::new(s)),
+            },
+            Zero | One | Var(_) => self.clone(),
+        }
+    }
+
+    /// Replace interval variable `depth` (counting path binders outward)
+    /// with the closed point `r`, lowering the variables bound further out
+    fn substitute(&self, depth: usize, r: &IntervalPoint) -> IntervalPoint {
+        use IntervalPoint::*;
+        match self {
+            Var(DeBruijnIndex(k)) if *k == depth => r.clone(),
+            Var(DeBruijnIndex(k)) if *k > depth => Var(DeBruijnIndex(k - 1)),
+            Zero | One | Var(_) => self.clone(),
+            Neg(s) => Neg(Box::new(s.substitute(depth, r))),
+            Meet(s, t) => Meet(Box::new(s.substitute(depth, r)), Box::new(t.substitute(depth, r))),
+            Join(s, t) => Join(Box::new(s.substitute(depth, r)), Box::new(t.substitute(depth, r))),
+        }
+    }
+}
+
 /// Core term language with dependent types and paths
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Term {
     /// Variable with De Bruijn index
     Var(DeBruijnIndex),
@@ -87,6 +129,158 @@ pub enum Term {
     
     /// Homogeneous composition
     Hcomp(Box<Term>, Vec<(IntervalPoint, IntervalPoint, Box<Term>)>, Box<Term>),
+
+    /// The circle S¹, a higher inductive type
+    Circle,
+
+    /// The point of the circle
+    Base,
+
+    /// The path around the circle, from `Base` to `Base`
+    Loop,
+
+    /// Map out of the circle into the type `motive`: `base_case` is the
+    /// image of `Base`, `loop_case` a path from it to itself, the image of
+    /// `Loop`
+    CircleElim { motive: Box<Term>, base_case: Box<Term>, loop_case: Box<Term>, scrutinee: Box<Term> },
+}
+
+const BINDER: u8 = 0;
+const AT: u8 = 1;
+const APP: u8 = 2;
+const ATOM: u8 = 3;
+
+impl Term {
+    fn precedence(&self) -> u8 {
+        match self {
+            Term::Var(_) | Term::Universe(_) | Term::Interval(_) | Term::Circle | Term::Base | Term::Loop => ATOM,
+            Term::Lambda(..) | Term::Pi(..) | Term::PathLambda(..) => BINDER,
+            Term::PathApp(..) => AT,
+            Term::App(..) | Term::PathType(..) | Term::Transport(..) | Term::Hcomp(..) | Term::CircleElim { .. } => APP,
+        }
+    }
+
+    /// Print under `terms` term binders and `intervals` interval binders,
+    /// named `x0, x1, ...` and `i0, i1, ...` from the outside in
+    fn fmt_at(&self, f: &mut std::fmt::Formatter, terms: usize, intervals: usize, min: u8) -> std::fmt::Result {
+        if self.precedence() < min {
+            write!(f, "(")?;
+            self.fmt_at(f, terms, intervals, BINDER)?;
+            return write!(f, ")");
+        }
+        match self {
+            Term::Var(DeBruijnIndex(k)) if *k < terms => write!(f, "x{}", terms - 1 - k),
+            Term::Var(DeBruijnIndex(k)) => write!(f, "#{}", k),
+            Term::Universe(Level::Zero) => write!(f, "Type"),
+            Term::Universe(Level::Succ(n)) => write!(f, "Type{}", n),
+            Term::Universe(Level::Omega) => write!(f, "Typeω"),
+            Term::Lambda(a, body) | Term::Pi(a, body) => {
+                let binder = if matches!(self, Term::Lambda(..)) { "λ" } else { "Π" };
+                write!(f, "{} (x{} : ", binder, terms)?;
+                a.fmt_at(f, terms, intervals, BINDER)?;
+                write!(f, "). ")?;
+                body.fmt_at(f, terms + 1, intervals, BINDER)
+            }
+            Term::App(g, a) => {
+                g.fmt_at(f, terms, intervals, APP)?;
+                write!(f, " ")?;
+                a.fmt_at(f, terms, intervals, ATOM)
+            }
+            Term::PathType(a, x, y) => {
+                write!(f, "Path")?;
+                for part in [a, x, y] {
+                    write!(f, " ")?;
+                    part.fmt_at(f, terms, intervals, ATOM)?;
+                }
+                Ok(())
+            }
+            Term::PathLambda(body) => {
+                write!(f, "⟨i{}⟩ ", intervals)?;
+                body.fmt_at(f, terms, intervals + 1, BINDER)
+            }
+            Term::PathApp(p, r) => {
+                p.fmt_at(f, terms, intervals, APP)?;
+                write!(f, " @ ")?;
+                r.fmt_at(f, intervals, true)
+            }
+            Term::Interval(r) => r.fmt_at(f, intervals, true),
+            Term::Transport(line, from, to, base) => {
+                write!(f, "transport ")?;
+                line.fmt_at(f, terms, intervals, ATOM)?;
+                write!(f, " ")?;
+                from.fmt_at(f, intervals, true)?;
+                write!(f, " ")?;
+                to.fmt_at(f, intervals, true)?;
+                write!(f, " ")?;
+                base.fmt_at(f, terms, intervals, ATOM)
+            }
+            Term::Hcomp(ty, faces, base) => {
+                write!(f, "hcomp ")?;
+                ty.fmt_at(f, terms, intervals, ATOM)?;
+                write!(f, " [")?;
+                for (i, (lhs, rhs, tube)) in faces.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    lhs.fmt_at(f, intervals, false)?;
+                    write!(f, " = ")?;
+                    rhs.fmt_at(f, intervals, false)?;
+                    write!(f, " ↦ ")?;
+                    tube.fmt_at(f, terms, intervals, BINDER)?;
+                }
+                write!(f, "] ")?;
+                base.fmt_at(f, terms, intervals, ATOM)
+            }
+            Term::Circle => write!(f, "S¹"),
+            Term::Base => write!(f, "base"),
+            Term::Loop => write!(f, "loop"),
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+                write!(f, "S¹-elim[")?;
+                motive.fmt_at(f, terms, intervals, BINDER)?;
+                write!(f, "]")?;
+                for t in [scrutinee, base_case, loop_case] {
+                    write!(f, " ")?;
+                    t.fmt_at(f, terms, intervals, ATOM)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl IntervalPoint {
+    fn fmt_at(&self, f: &mut std::fmt::Formatter, intervals: usize, atomic: bool) -> std::fmt::Result {
+        match self {
+            IntervalPoint::Zero => write!(f, "0"),
+            IntervalPoint::One => write!(f, "1"),
+            IntervalPoint::Var(DeBruijnIndex(k)) if *k < intervals => write!(f, "i{}", intervals - 1 - k),
+            IntervalPoint::Var(DeBruijnIndex(k)) => write!(f, "#{}", k),
+            IntervalPoint::Neg(r) => {
+                write!(f, "¬")?;
+                r.fmt_at(f, intervals, true)
+            }
+            IntervalPoint::Meet(r, s) | IntervalPoint::Join(r, s) => {
+                let op = if matches!(self, IntervalPoint::Meet(..)) { "∧" } else { "∨" };
+                if atomic {
+                    write!(f, "(")?;
+                }
+                r.fmt_at(f, intervals, true)?;
+                write!(f, " {} ", op)?;
+                s.fmt_at(f, intervals, true)?;
+                if atomic {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Surface syntax, with binders named by depth as in `bridge`
+impl std::fmt::Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.fmt_at(f, 0, 0, BINDER)
+    }
 }
 
 /// Values for normalization by evaluation
@@ -99,6 +293,11 @@ pub enum Value {
     PathType(Box<Value>, Box<Value>, Box<Value>),
     PathLambda(PathClosure),
     Interval(IntervalPoint),
+    Circle,
+    Base,
+    Loop,
+    /// `Loop @ i` at a point that is not an endpoint
+    LoopAt(IntervalPoint),
 }
 
 /// Neutral values (cannot reduce further)
@@ -108,6 +307,7 @@ pub enum Neutral {
     App(Box<Neutral>, Box<Value>),
     PathApp(Box<Neutral>, IntervalPoint),
     Transport(Box<Value>, IntervalPoint, IntervalPoint, Box<Neutral>),
+    CircleElim { motive: Box<Value>, base_case: Box<Value>, loop_case: Box<Value>, scrutinee: Box<Neutral> },
 }
 
 /// Closures capture environments
@@ -185,8 +385,27 @@ pub enum TypeError {
     NotAUniverse(Value),
     InvalidInterval,
     UnificationFailure,
+    /// A term former the evaluator does not implement yet
+    Unsupported(&'static str),
 }
 
+impl std::fmt::Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TypeError::UnboundVariable(idx) => write!(f, "unbound variable #{}", idx.0),
+            TypeError::TypeMismatch { .. } => write!(f, "type mismatch"),
+            TypeError::NotAFunction(_) => write!(f, "applied something that is not a function"),
+            TypeError::NotAPath(_) => write!(f, "applied something that is not a path to an interval point"),
+            TypeError::NotAUniverse(_) => write!(f, "expected a type"),
+            TypeError::InvalidInterval => write!(f, "invalid interval point"),
+            TypeError::UnificationFailure => write!(f, "conversion check gave up"),
+            TypeError::Unsupported(what) => write!(f, "not supported yet: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for TypeError {}
+
 pub type Result<T> = std::result::Result<T, TypeError>;
 
 /// Main type checker implementation
@@ -207,7 +426,7 @@ impl TypeChecker {
             (Term::Lambda(_, body), Value::Pi(a_ty, closure)) => {
                 let extended_ctx = ctx.extend(a_ty.as_ref().clone());
                 let b_ty = self.apply_closure(closure.clone(), 
-                    Value::Neutral(Neutral::Var(DeBruijnIndex(ctx.types.len()))));
+                    Value::Neutral(Neutral::Var(DeBruijnIndex(ctx.types.len()))))?;
                 self.check(&extended_ctx, body, &b_ty)
             }
             
@@ -217,8 +436,8 @@ impl TypeChecker {
                 let at_zero = self.substitute_interval(body, &IntervalPoint::Zero);
                 let at_one = self.substitute_interval(body, &IntervalPoint::One);
                 
-                let val_zero = self.eval(&ctx.env, &at_zero);
-                let val_one = self.eval(&ctx.env, &at_one);
+                let val_zero = self.boundary(ctx, &at_zero)?;
+                let val_one = self.boundary(ctx, &at_one)?;
                 
                 self.check_equal(ctx, &val_zero, start, a_ty)?;
                 self.check_equal(ctx, &val_one, end, a_ty)?;
@@ -247,11 +466,11 @@ impl TypeChecker {
             }
             
             Term::Pi(a, b) => {
-                let a_val = self.eval(&ctx.env, a);
+                let a_val = self.eval(&ctx.env, a)?;
                 let a_level = self.infer_universe_level(ctx, &a_val)?;
                 
                 let extended_ctx = ctx.extend(a_val.clone());
-                let b_val = self.eval(&extended_ctx.env, b);
+                let b_val = self.eval(&extended_ctx.env, b)?;
                 let b_level = self.infer_universe_level(&extended_ctx, &b_val)?;
                 
                 Ok(Value::Universe(a_level.max(b_level)))
@@ -262,20 +481,17 @@ impl TypeChecker {
                 match fun_ty {
                     Value::Pi(a_ty, closure) => {
                         self.check(ctx, arg, &a_ty)?;
-                        let arg_val = self.eval(&ctx.env, arg);
-                        Ok(self.apply_closure(closure, arg_val))
+                        let arg_val = self.eval(&ctx.env, arg)?;
+                        self.apply_closure(closure, arg_val)
                     }
                     _ => Err(TypeError::NotAFunction(fun_ty)),
                 }
             }
             
             Term::PathType(a, start, end) => {
-                let a_val = self.eval(&ctx.env, a);
+                let a_val = self.eval(&ctx.env, a)?;
                 let a_level = self.infer_universe_level(ctx, &a_val)?;
                 
-                let start_val = self.eval(&ctx.env, start);
-                let end_val = self.eval(&ctx.env, end);
-                
                 self.check(ctx, start, &a_val)?;
                 self.check(ctx, end, &a_val)?;
                 
@@ -292,7 +508,7 @@ impl TypeChecker {
             
             Term::Lambda(a, _) => {
                 // Lambda requires type annotation in inference mode
-                let a_val = self.eval(&ctx.env, a);
+                let a_val = self.eval(&ctx.env, a)?;
                 self.check(ctx, a, &Value::Universe(Level::Omega))?;
                 Err(TypeError::TypeMismatch { 
                     expected: Value::Universe(Level::Omega), 
@@ -300,178 +516,262 @@ impl TypeChecker {
                 })
             }
             
-            _ => todo!("Other term inference cases"),
+            Term::PathLambda(_) => Err(TypeError::Unsupported("inferring a path lambda")),
+            Term::Interval(_) => Err(TypeError::Unsupported("inferring an interval point")),
+            Term::Transport(line, r, s, base) => {
+                let (from, to) = self.line_endpoints(ctx, line, r, s)?;
+                self.check(ctx, base, &from)?;
+                Ok(to)
+            }
+            Term::Hcomp(..) => Err(TypeError::Unsupported("inferring homogeneous composition")),
+            Term::Circle => Ok(Value::Universe(Level::Zero)),
+            Term::Base => Ok(Value::Circle),
+            Term::Loop => Ok(Value::PathType(Box::new(Value::Circle), Box::new(Value::Base), Box::new(Value::Base))),
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+                self.infer_universe_level(ctx, &self.infer(ctx, motive)?)?;
+                let motive = self.eval(&ctx.env, motive)?;
+                self.check(ctx, base_case, &motive)?;
+                let base = Box::new(self.eval(&ctx.env, base_case)?);
+                self.check(ctx, loop_case, &Value::PathType(Box::new(motive.clone()), base.clone(), base))?;
+                self.check(ctx, scrutinee, &Value::Circle)?;
+                Ok(motive)
+            }
         }
     }
 
+    /// The types at `r` and `s` of a line of types: a path lambda, or any
+    /// path between types when `r` and `s` are endpoints
+    fn line_endpoints(&self, ctx: &Context, line: &Term, r: &IntervalPoint, s: &IntervalPoint) -> Result<(Value, Value)> {
+        if let Term::PathLambda(body) = line {
+            let at = |point: &IntervalPoint| -> Result<Value> {
+                let ty = self.substitute_interval(body, point);
+                self.infer_universe_level(ctx, &self.infer(ctx, &ty)?)?;
+                self.eval(&ctx.env, &ty)
+            };
+            return Ok((at(r)?, at(s)?));
+        }
+        match self.infer(ctx, line)? {
+            Value::PathType(universe, start, end) => {
+                self.infer_universe_level(ctx, &universe)?;
+                let pick = |point: &IntervalPoint| match point.simplify() {
+                    IntervalPoint::Zero => Ok(start.as_ref().clone()),
+                    IntervalPoint::One => Ok(end.as_ref().clone()),
+                    _ => Err(TypeError::InvalidInterval),
+                };
+                Ok((pick(r)?, pick(s)?))
+            }
+            other => Err(TypeError::NotAPath(other)),
+        }
+    }
+
+    /// Evaluate a path lambda's body at an endpoint; a path applied to 0 or
+    /// 1 computes to that end of its type even when the path is a variable
+    fn boundary(&self, ctx: &Context, term: &Term) -> Result<Value> {
+        if let Term::PathApp(path, point) = term {
+            let point = point.simplify();
+            if matches!(point, IntervalPoint::Zero | IntervalPoint::One) {
+                if let Value::PathType(_, start, end) = self.infer(ctx, path)? {
+                    return Ok(if point == IntervalPoint::Zero { *start } else { *end });
+                }
+            }
+        }
+        self.eval(&ctx.env, term)
+    }
+
     /// Normalize a term by evaluation
-    pub fn normalize(&self, env: &Environment, term: &Term) -> Term {
-        let val = self.eval(env, term);
+    pub fn normalize(&self, env: &Environment, term: &Term) -> Result<Term> {
+        let val = self.eval(env, term)?;
         self.quote(env.values.len(), &val)
     }
 
     /// Evaluate term to value
-    pub fn eval(&self, env: &Environment, term: &Term) -> Value {
+    pub fn eval(&self, env: &Environment, term: &Term) -> Result<Value> {
         match term {
             Term::Var(idx) => {
-                env.lookup(*idx).cloned()
-                    .unwrap_or(Value::Neutral(Neutral::Var(*idx)))
+                Ok(env.lookup(*idx).cloned()
+                    .unwrap_or(Value::Neutral(Neutral::Var(*idx))))
             }
             
-            Term::Universe(level) => Value::Universe(*level),
+            Term::Universe(level) => Ok(Value::Universe(*level)),
             
-            Term::Lambda(ty, body) => {
-                Value::Lambda(Closure {
+            Term::Lambda(_, body) => {
+                Ok(Value::Lambda(Closure {
                     env: env.clone(),
                     body: body.clone(),
-                })
+                }))
             }
             
             Term::Pi(a, b) => {
-                let a_val = self.eval(env, a);
-                Value::Pi(
+                let a_val = self.eval(env, a)?;
+                Ok(Value::Pi(
                     Box::new(a_val),
                     Closure {
                         env: env.clone(),
                         body: b.clone(),
                     },
-                )
+                ))
             }
             
             Term::App(fun, arg) => {
-                let fun_val = self.eval(env, fun);
-                let arg_val = self.eval(env, arg);
+                let fun_val = self.eval(env, fun)?;
+                let arg_val = self.eval(env, arg)?;
                 self.apply_value(fun_val, arg_val)
             }
             
             Term::PathType(a, start, end) => {
-                Value::PathType(
-                    Box::new(self.eval(env, a)),
-                    Box::new(self.eval(env, start)),
-                    Box::new(self.eval(env, end)),
-                )
+                Ok(Value::PathType(
+                    Box::new(self.eval(env, a)?),
+                    Box::new(self.eval(env, start)?),
+                    Box::new(self.eval(env, end)?),
+                ))
             }
             
             Term::PathLambda(body) => {
-                Value::PathLambda(PathClosure {
+                Ok(Value::PathLambda(PathClosure {
                     env: env.clone(),
                     body: body.clone(),
-                })
+                }))
             }
             
             Term::PathApp(path, i) => {
-                let path_val = self.eval(env, path);
+                let path_val = self.eval(env, path)?;
                 self.apply_path(path_val, i.clone())
             }
             
-            Term::Interval(i) => Value::Interval(i.clone()),
+            Term::Interval(i) => Ok(Value::Interval(i.clone())),
             
-            _ => todo!("Other evaluation cases"),
+            Term::Transport(..) => Err(TypeError::Unsupported("evaluating transport")),
+            Term::Hcomp(..) => Err(TypeError::Unsupported("evaluating homogeneous composition")),
+            Term::Circle => Ok(Value::Circle),
+            Term::Base => Ok(Value::Base),
+            Term::Loop => Ok(Value::Loop),
+            Term::CircleElim { motive, base_case, loop_case, scrutinee } => {
+                let base_case = self.eval(env, base_case)?;
+                let loop_case = self.eval(env, loop_case)?;
+                match self.eval(env, scrutinee)? {
+                    Value::Base => Ok(base_case),
+                    Value::LoopAt(i) => self.apply_path(loop_case, i),
+                    Value::Neutral(n) => Ok(Value::Neutral(Neutral::CircleElim {
+                        motive: Box::new(self.eval(env, motive)?),
+                        base_case: Box::new(base_case),
+                        loop_case: Box::new(loop_case),
+                        scrutinee: Box::new(n),
+                    })),
+                    other => Err(TypeError::TypeMismatch { expected: Value::Circle, found: other }),
+                }
+            }
         }
     }
 
     /// Quote value back to term (for normalization)
-    fn quote(&self, level: usize, value: &Value) -> Term {
+    fn quote(&self, level: usize, value: &Value) -> Result<Term> {
         match value {
             Value::Neutral(n) => self.quote_neutral(level, n),
             
-            Value::Universe(l) => Term::Universe(*l),
+            Value::Universe(l) => Ok(Term::Universe(*l)),
             
             Value::Lambda(closure) => {
                 let var = Value::Neutral(Neutral::Var(DeBruijnIndex(level)));
-                let body_val = self.apply_closure(closure.clone(), var);
-                let body = self.quote(level + 1, &body_val);
-                Term::Lambda(
+                let body_val = self.apply_closure(closure.clone(), var)?;
+                let body = self.quote(level + 1, &body_val)?;
+                Ok(Term::Lambda(
                     Box::new(Term::Universe(Level::Zero)), // placeholder type
                     Box::new(body),
-                )
+                ))
             }
             
             Value::Pi(a, closure) => {
-                let a_term = self.quote(level, a);
+                let a_term = self.quote(level, a)?;
                 let var = Value::Neutral(Neutral::Var(DeBruijnIndex(level)));
-                let b_val = self.apply_closure(closure.clone(), var);
-                let b_term = self.quote(level + 1, &b_val);
-                Term::Pi(Box::new(a_term), Box::new(b_term))
+                let b_val = self.apply_closure(closure.clone(), var)?;
+                let b_term = self.quote(level + 1, &b_val)?;
+                Ok(Term::Pi(Box::new(a_term), Box::new(b_term)))
             }
             
             Value::PathType(a, start, end) => {
-                Term::PathType(
-                    Box::new(self.quote(level, a)),
-                    Box::new(self.quote(level, start)),
-                    Box::new(self.quote(level, end)),
-                )
+                Ok(Term::PathType(
+                    Box::new(self.quote(level, a)?),
+                    Box::new(self.quote(level, start)?),
+                    Box::new(self.quote(level, end)?),
+                ))
             }
             
             Value::PathLambda(closure) => {
-                let body_val = self.eval(&closure.env, &closure.body);
-                Term::PathLambda(Box::new(self.quote(level, &body_val)))
+                let body_val = self.eval(&closure.env, &closure.body)?;
+                Ok(Term::PathLambda(Box::new(self.quote(level, &body_val)?)))
             }
             
-            Value::Interval(i) => Term::Interval(i.clone()),
+            Value::Interval(i) => Ok(Term::Interval(i.clone())),
+            Value::Circle => Ok(Term::Circle),
+            Value::Base => Ok(Term::Base),
+            Value::Loop => Ok(Term::Loop),
+            Value::LoopAt(i) => Ok(Term::PathApp(Box::new(Term::Loop), i.clone())),
         }
     }
 
-    fn quote_neutral(&self, level: usize, neutral: &Neutral) -> Term {
+    fn quote_neutral(&self, level: usize, neutral: &Neutral) -> Result<Term> {
         match neutral {
-            Neutral::Var(idx) => Term::Var(*idx),
+            Neutral::Var(idx) => Ok(Term::Var(*idx)),
             
             Neutral::App(fun, arg) => {
-                Term::App(
-                    Box::new(self.quote_neutral(level, fun)),
-                    Box::new(self.quote(level, arg)),
-                )
+                Ok(Term::App(
+                    Box::new(self.quote_neutral(level, fun)?),
+                    Box::new(self.quote(level, arg)?),
+                ))
             }
             
             Neutral::PathApp(path, i) => {
-                Term::PathApp(
-                    Box::new(self.quote_neutral(level, path)),
+                Ok(Term::PathApp(
+                    Box::new(self.quote_neutral(level, path)?),
                     i.clone(),
-                )
+                ))
             }
             
-            _ => todo!("Other neutral quoting cases"),
+            Neutral::Transport(..) => Err(TypeError::Unsupported("quoting a stuck transport")),
+
+            Neutral::CircleElim { motive, base_case, loop_case, scrutinee } => Ok(Term::CircleElim {
+                motive: Box::new(self.quote(level, motive)?),
+                base_case: Box::new(self.quote(level, base_case)?),
+                loop_case: Box::new(self.quote(level, loop_case)?),
+                scrutinee: Box::new(self.quote_neutral(level, scrutinee)?),
+            }),
         }
     }
 
     /// Apply function value to argument
-    fn apply_value(&self, fun: Value, arg: Value) -> Value {
+    fn apply_value(&self, fun: Value, arg: Value) -> Result<Value> {
         match fun {
             Value::Lambda(closure) => self.apply_closure(closure, arg),
-            Value::Neutral(n) => Value::Neutral(Neutral::App(Box::new(n), Box::new(arg))),
-            _ => panic!("Cannot apply non-function"),
+            Value::Neutral(n) => Ok(Value::Neutral(Neutral::App(Box::new(n), Box::new(arg)))),
+            other => Err(TypeError::NotAFunction(other)),
         }
     }
 
     /// Apply path to interval point
-    fn apply_path(&self, path: Value, i: IntervalPoint) -> Value {
+    fn apply_path(&self, path: Value, i: IntervalPoint) -> Result<Value> {
         match path {
             Value::PathLambda(closure) => {
-                let subst = self.substitute_interval(&closure.body, &i);
+                let subst = self.substitute_interval(&closure.body, &i.simplify());
                 self.eval(&closure.env, &subst)
             }
-            Value::Neutral(n) => Value::Neutral(Neutral::PathApp(Box::new(n), i)),
-            _ => panic!("Cannot apply non-path"),
+            Value::Neutral(n) => Ok(Value::Neutral(Neutral::PathApp(Box::new(n), i))),
+            Value::Loop => Ok(match i.simplify() {
+                IntervalPoint::Zero | IntervalPoint::One => Value::Base,
+                i => Value::LoopAt(i),
+            }),
+            other => Err(TypeError::NotAPath(other)),
         }
     }
 
     /// Apply closure to value
-    fn apply_closure(&self, closure: Closure, arg: Value) -> Value {
+    fn apply_closure(&self, closure: Closure, arg: Value) -> Result<Value> {
         let extended_env = closure.env.extend(arg);
         self.eval(&extended_env, &closure.body)
     }
 
-    /// Substitute interval variable in term
+    /// Substitute a closed point for the innermost path binder's variable
     fn substitute_interval(&self, term: &Term, i: &IntervalPoint) -> Term {
-        // Simplified for demonstration - full implementation would handle all cases
-        match term {
-            Term::Interval(_) => Term::Interval(i.clone()),
-            Term::Lambda(ty, body) => Term::Lambda(
-                Box::new(self.substitute_interval(ty, i)),
-                Box::new(self.substitute_interval(body, i)),
-            ),
-            _ => term.clone(),
-        }
+        substitute_interval_at(term, 0, i)
     }
 
     /// Check equality of values (conversion checking)
@@ -497,8 +797,8 @@ impl TypeChecker {
             
             (Value::Lambda(c1), Value::Lambda(c2)) => {
                 let var = Value::Neutral(Neutral::Var(DeBruijnIndex(level)));
-                let v1 = self.apply_closure(c1.clone(), var.clone());
-                let v2 = self.apply_closure(c2.clone(), var);
+                let v1 = self.apply_closure(c1.clone(), var.clone())?;
+                let v2 = self.apply_closure(c2.clone(), var)?;
                 self.values_equal(level + 1, &v1, &v2, depth + 1)
             }
             
@@ -508,11 +808,21 @@ impl TypeChecker {
                     return Ok(false);
                 }
                 let var = Value::Neutral(Neutral::Var(DeBruijnIndex(level)));
-                let b1 = self.apply_closure(c1.clone(), var.clone());
-                let b2 = self.apply_closure(c2.clone(), var);
+                let b1 = self.apply_closure(c1.clone(), var.clone())?;
+                let b2 = self.apply_closure(c2.clone(), var)?;
                 self.values_equal(level + 1, &b1, &b2, depth + 1)
             }
             
+            (Value::PathType(a1, x1, y1), Value::PathType(a2, x2, y2)) => {
+                Ok(self.values_equal(level, a1, a2, depth + 1)?
+                    && self.values_equal(level, x1, x2, depth + 1)?
+                    && self.values_equal(level, y1, y2, depth + 1)?)
+            }
+
+            (Value::Circle, Value::Circle) | (Value::Base, Value::Base) | (Value::Loop, Value::Loop) => Ok(true),
+
+            (Value::LoopAt(i), Value::LoopAt(j)) => Ok(i.simplify() == j.simplify()),
+
             (Value::Neutral(n1), Value::Neutral(n2)) => {
                 self.neutrals_equal(level, n1, n2, depth)
             }
@@ -532,6 +842,14 @@ impl TypeChecker {
                 }
                 self.values_equal(level, a1, a2, depth + 1)
             }
+
+            (
+                Neutral::CircleElim { motive: m1, base_case: b1, loop_case: l1, scrutinee: s1 },
+                Neutral::CircleElim { motive: m2, base_case: b2, loop_case: l2, scrutinee: s2 },
+            ) => Ok(self.neutrals_equal(level, s1, s2, depth + 1)?
+                && self.values_equal(level, m1, m2, depth + 1)?
+                && self.values_equal(level, b1, b2, depth + 1)?
+                && self.values_equal(level, l1, l2, depth + 1)?),
             
             _ => Ok(false),
         }
@@ -546,6 +864,33 @@ impl TypeChecker {
     }
 }
 
+fn substitute_interval_at(term: &Term, depth: usize, r: &IntervalPoint) -> Term {
+    let go = |t: &Term| Box::new(substitute_interval_at(t, depth, r));
+    let point = |p: &IntervalPoint| p.substitute(depth, r).simplify();
+    match term {
+        Term::Var(_) | Term::Universe(_) | Term::Circle | Term::Base | Term::Loop => term.clone(),
+        Term::Lambda(ty, body) => Term::Lambda(go(ty), go(body)),
+        Term::App(f, a) => Term::App(go(f), go(a)),
+        Term::Pi(a, b) => Term::Pi(go(a), go(b)),
+        Term::PathType(a, x, y) => Term::PathType(go(a), go(x), go(y)),
+        Term::PathLambda(body) => Term::PathLambda(Box::new(substitute_interval_at(body, depth + 1, r))),
+        Term::PathApp(p, s) => Term::PathApp(go(p), point(s)),
+        Term::Interval(s) => Term::Interval(point(s)),
+        Term::Transport(line, s, t, base) => Term::Transport(go(line), point(s), point(t), go(base)),
+        Term::Hcomp(ty, faces, base) => Term::Hcomp(
+            go(ty),
+            faces.iter().map(|(s, t, tube)| (point(s), point(t), go(tube))).collect(),
+            go(base),
+        ),
+        Term::CircleElim { motive, base_case, loop_case, scrutinee } => Term::CircleElim {
+            motive: go(motive),
+            base_case: go(base_case),
+            loop_case: go(loop_case),
+            scrutinee: go(scrutinee),
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -573,10 +918,25 @@ mod tests {
             )),
         );
         
-        let id_type_val = tc.eval(&ctx.env, &id_type);
+        let id_type_val = tc.eval(&ctx.env, &id_type).unwrap();
         assert!(tc.check(&ctx, &id_term, &id_type_val).is_ok());
     }
 
+    #[test]
+    fn test_display_names_binders_by_depth() {
+        let id = Term::Lambda(
+            Box::new(Term::Universe(Level::Zero)),
+            Box::new(Term::Lambda(Box::new(Term::Var(DeBruijnIndex(0))), Box::new(Term::Var(DeBruijnIndex(0))))),
+        );
+        assert_eq!(id.to_string(), "λ (x0 : Type). λ (x1 : x0). x1");
+
+        let point = IntervalPoint::Neg(Box::new(IntervalPoint::Var(DeBruijnIndex(0))));
+        let reversed = Term::PathLambda(Box::new(Term::PathApp(Box::new(Term::Loop), point)));
+        assert_eq!(reversed.to_string(), "⟨i0⟩ loop @ ¬i0");
+        let applied = Term::App(Box::new(id), Box::new(Term::Circle));
+        assert_eq!(applied.to_string(), "(λ (x0 : Type). λ (x1 : x0). x1) S¹");
+    }
+
     #[test]
     fn test_path_reflexivity() {
         let tc = TypeChecker::new();
@@ -606,7 +966,7 @@ mod tests {
             )),
         );
         
-        let refl_type_val = tc.eval(&ctx.env, &refl_type);
+        let refl_type_val = tc.eval(&ctx.env, &refl_type).unwrap();
         assert!(tc.check(&ctx, &refl_term, &refl_type_val).is_ok());
     }
 
@@ -624,7 +984,7 @@ mod tests {
             Box::new(Term::Var(DeBruijnIndex(0))),
         );
         
-        let normalized = tc.normalize(&env, &app);
+        let normalized = tc.normalize(&env, &app).unwrap();
         assert_eq!(normalized, Term::Var(DeBruijnIndex(0)));
     }
 
@@ -643,6 +1003,82 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ill_formed_terms_are_errors() {
+        let tc = TypeChecker::new();
+        let env = Environment::new();
+
+        // Fuzz input that used to panic in eval: Type₀ Type₀
+        let bad_app = Term::App(
+            Box::new(Term::Universe(Level::Zero)),
+            Box::new(Term::Universe(Level::Zero)),
+        );
+        assert!(matches!(tc.eval(&env, &bad_app), Err(TypeError::NotAFunction(_))));
+
+        let bad_path = Term::PathApp(Box::new(Term::Universe(Level::Zero)), IntervalPoint::One);
+        assert!(matches!(tc.normalize(&env, &bad_path), Err(TypeError::NotAPath(_))));
+
+        let transport = Term::Transport(
+            Box::new(Term::Universe(Level::Zero)),
+            IntervalPoint::Zero,
+            IntervalPoint::One,
+            Box::new(Term::Var(DeBruijnIndex(0))),
+        );
+        assert!(matches!(tc.eval(&env, &transport), Err(TypeError::Unsupported(_))));
+    }
+
+    /// `λ(s : S¹). S¹-elim s base loop_case`: the map sending `Base` to
+    /// `Base` and `Loop` to `loop_case`
+    fn circle_map(loop_case: Term) -> Term {
+        Term::Lambda(
+            Box::new(Term::Circle),
+            Box::new(Term::CircleElim {
+                motive: Box::new(Term::Circle),
+                base_case: Box::new(Term::Base),
+                loop_case: Box::new(loop_case),
+                scrutinee: Box::new(Term::Var(DeBruijnIndex(0))),
+            }),
+        )
+    }
+
+    #[test]
+    fn test_constant_map_out_of_the_circle() {
+        let tc = TypeChecker::new();
+        let ctx = Context::new();
+        let constant = circle_map(Term::PathLambda(Box::new(Term::Base)));
+        let endo = tc.eval(&ctx.env, &Term::Pi(Box::new(Term::Circle), Box::new(Term::Circle))).unwrap();
+        assert!(tc.check(&ctx, &constant, &endo).is_ok());
+
+        // ⟨i⟩ constant (Loop @ i) is the constant path at Base
+        let image = Term::PathLambda(Box::new(Term::App(
+            Box::new(constant.clone()),
+            Box::new(Term::PathApp(Box::new(Term::Loop), IntervalPoint::Var(DeBruijnIndex(0)))),
+        )));
+        assert_eq!(tc.normalize(&ctx.env, &image).unwrap(), Term::PathLambda(Box::new(Term::Base)));
+        let at_base = Term::App(Box::new(constant), Box::new(Term::Base));
+        assert_eq!(tc.normalize(&ctx.env, &at_base).unwrap(), Term::Base);
+    }
+
+    #[test]
+    fn test_loop_computes_at_its_endpoints() {
+        let tc = TypeChecker::new();
+        let ctx = Context::new();
+        let identity = circle_map(Term::Loop);
+        let endo = tc.eval(&ctx.env, &Term::Pi(Box::new(Term::Circle), Box::new(Term::Circle))).unwrap();
+        assert!(tc.check(&ctx, &identity, &endo).is_ok());
+
+        let at = |point: IntervalPoint| Term::App(Box::new(identity.clone()), Box::new(Term::PathApp(Box::new(Term::Loop), point)));
+        let i = IntervalPoint::Var(DeBruijnIndex(0));
+        assert_eq!(tc.normalize(&ctx.env, &at(i.clone())).unwrap(), Term::PathApp(Box::new(Term::Loop), i));
+        let one = IntervalPoint::Neg(Box::new(IntervalPoint::Zero));
+        assert_eq!(tc.normalize(&ctx.env, &at(one)).unwrap(), Term::Base);
+
+        // The loop case has to be a loop at the base case
+        assert!(tc.check(&ctx, &circle_map(Term::Base), &endo).is_err());
+        let stuck = Term::App(Box::new(identity), Box::new(Term::Var(DeBruijnIndex(0))));
+        assert!(matches!(tc.normalize(&ctx.env, &stuck).unwrap(), Term::CircleElim { .. }));
+    }
+
     #[test]
     fn test_confluence_property() {
         let tc = TypeChecker::new();
@@ -665,8 +1101,8 @@ mod tests {
         );
         
         // Normalize from different starting points
-        let norm1 = tc.normalize(&env, &two);
-        let norm2 = tc.normalize(&env, &two);
+        let norm1 = tc.normalize(&env, &two).unwrap();
+        let norm2 = tc.normalize(&env, &two).unwrap();
         
         assert_eq!(norm1, norm2, "Confluence property violated");
     }
@@ -0,0 +1,221 @@
+//! The axum API router over [`Service`]
+//!
+//! Routes decode requests into [`api`](crate::api) DTOs, call the service and
+//! encode its answers, mapping [`ServiceError::status`] onto HTTP statuses.
+//! The `sctt-server` binary serves [`router`] over its SQLite proof store;
+//! tests and embedders can serve it over a [`MemoryStore`](crate::service::MemoryStore).
+
+use axum::{
+    extract::{Path, State, WebSocketUpgrade},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use axum::extract::ws::{Message, WebSocket};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::services::ServeDir;
+
+use crate::api::{
+    ApiError, ApplyInTransactionRequest, BeginTransactionRequest, CheckTerminationRequest, CompileRequest,
+    EvaluateBatchRequest, EvaluateRequest, ExplainSmoothnessRequest, ProveRequest, RenameRequest, SaveProofRequest,
+    TypeCheckRequest,
+};
+use crate::service::{self, Config, ProofStore, Service, ServiceError};
+
+pub type AppState<S> = Arc<Service<S>>;
+
+/// Every API route, the websocket endpoint and the static frontend over `state`
+pub fn router<S: ProofStore + 'static>(state: AppState<S>, config: &Config) -> Router {
+    let origins = if config.cors_origins.is_empty() {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.cors_origins.iter().filter_map(|o| HeaderValue::from_str(o).ok()))
+    };
+
+    Router::new()
+        // API routes
+        .route("/api/health", get(|State(s): State<AppState<S>>| async move { Json(s.health().await) }))
+        .route("/api/examples", get(|State(s): State<AppState<S>>| async move { Json(s.examples().await) }))
+        .route("/api/typecheck", post(|State(s): State<AppState<S>>, Json(req): Json<TypeCheckRequest>| async move {
+            Json(s.typecheck(req).await)
+        }))
+        .route("/api/evaluate", post(|State(s): State<AppState<S>>, Json(req): Json<EvaluateRequest>| async move {
+            Json(s.evaluate(req).await)
+        }))
+        .route("/api/evaluate/batch", post(
+            |State(s): State<AppState<S>>, Json(req): Json<EvaluateBatchRequest>| async move {
+                Json(s.evaluate_batch(req).await)
+            },
+        ))
+        .route("/api/smooth/explain", post(
+            |State(s): State<AppState<S>>, Json(req): Json<ExplainSmoothnessRequest>| async move {
+                answer(s.explain_smoothness(req).await)
+            },
+        ))
+        .route("/api/termination", post(
+            |State(s): State<AppState<S>>, Json(req): Json<CheckTerminationRequest>| async move {
+                answer(s.check_termination(req).await)
+            },
+        ))
+        .route("/api/compile", post(compile::<S>))
+        .route("/api/prove", post(|State(s): State<AppState<S>>, Json(req): Json<ProveRequest>| async move {
+            Json(s.prove(req).await)
+        }))
+        .route("/api/session", post(|State(s): State<AppState<S>>| async move { Json(s.create_session().await) }))
+        .route("/api/session/:id", get(|State(s): State<AppState<S>>, Path(id): Path<String>| async move {
+            answer(s.session(&id).await)
+        }))
+        .route("/api/session/:id/transactions", post(
+            |State(s): State<AppState<S>>,
+             Path(id): Path<String>,
+             headers: HeaderMap,
+             Json(req): Json<BeginTransactionRequest>| async move {
+                answer(s.begin_transaction(&id, bearer(&headers), req).await)
+            },
+        ))
+        .route("/api/session/:id/transactions/:txn/apply", post(
+            |State(s): State<AppState<S>>,
+             Path((id, txn)): Path<(String, String)>,
+             headers: HeaderMap,
+             Json(req): Json<ApplyInTransactionRequest>| async move {
+                answer(s.apply_in_transaction(&id, &txn, bearer(&headers), req).await)
+            },
+        ))
+        .route("/api/session/:id/transactions/:txn/commit", post(
+            |State(s): State<AppState<S>>, Path((id, txn)): Path<(String, String)>, headers: HeaderMap| async move {
+                answer(s.commit_transaction(&id, &txn, bearer(&headers)).await)
+            },
+        ))
+        .route("/api/session/:id/transactions/:txn/rollback", post(
+            |State(s): State<AppState<S>>, Path((id, txn)): Path<(String, String)>, headers: HeaderMap| async move {
+                answer(s.rollback_transaction(&id, &txn, bearer(&headers)).await)
+            },
+        ))
+        .route("/api/session/:id/rename", post(
+            |State(s): State<AppState<S>>,
+             Path(id): Path<String>,
+             headers: HeaderMap,
+             Json(req): Json<RenameRequest>| async move {
+                answer(s.rename(&id, bearer(&headers), req).await)
+            },
+        ))
+        .route("/api/proofs", get(|State(s): State<AppState<S>>| async move { answer(s.list_proofs().await) }))
+        .route("/api/proof/:id", get(|State(s): State<AppState<S>>, Path(id): Path<String>| async move {
+            answer(s.get_proof(&id).await)
+        }).post(|State(s): State<AppState<S>>, Path(id): Path<String>, Json(req): Json<SaveProofRequest>| async move {
+            answer(s.save_proof(&id, req).await)
+        }))
+        .route("/api/runs", get(|State(s): State<AppState<S>>| async move { Json(s.runs().await) }))
+        .route("/api/openapi.json", get(|| async { Json(service::openapi()) }))
+        .route("/api/diagnostics/resources", get(|State(s): State<AppState<S>>| async move {
+            Json(s.resource_diagnostics().await)
+        }))
+
+        // WebSocket endpoint
+        .route("/ws/:session_id", get(websocket_handler::<S>))
+
+        // Static files
+        .nest_service("/", ServeDir::new(&config.static_dir))
+
+        .layer(CorsLayer::new().allow_origin(origins).allow_methods(Any).allow_headers(Any))
+        .with_state(state)
+}
+
+/// Encode a service answer, or its error as status plus [`ApiError`] body
+fn answer<T: serde::Serialize>(result: Result<T, ServiceError>) -> Response {
+    match result {
+        Ok(value) => Json(value).into_response(),
+        Err(e) => {
+            let status = StatusCode::from_u16(e.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            (status, Json::<ApiError>(e.body())).into_response()
+        }
+    }
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+async fn compile<S: ProofStore + 'static>(
+    State(state): State<AppState<S>>,
+    Json(req): Json<CompileRequest>,
+) -> impl IntoResponse {
+    match tokio::task::spawn_blocking(move || state.compile(req)).await {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiError { error: e.to_string() })).into_response(),
+    }
+}
+
+async fn websocket_handler<S: ProofStore + 'static>(
+    ws: WebSocketUpgrade,
+    Path(session_id): Path<String>,
+    State(state): State<AppState<S>>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_socket(socket, session_id, state))
+}
+
+async fn handle_socket<S: ProofStore>(mut socket: WebSocket, session_id: String, state: AppState<S>) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let sink = Box::new(move |text: String| tx.send(text).is_ok());
+    let Ok(conn_id) = state.connect(&session_id, sink) else {
+        let _ = socket.close().await;
+        return;
+    };
+
+    loop {
+        tokio::select! {
+            outgoing = rx.recv() => match outgoing {
+                Some(text) if socket.send(Message::Text(text)).await.is_ok() => {}
+                _ => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => state.message(&session_id, &conn_id, &text),
+                Some(Ok(_)) => {}
+                _ => break,
+            },
+        }
+    }
+
+    state.disconnect(&session_id, &conn_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::MemoryStore;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    /// The adapter wires routes to the service and maps its errors
+    #[tokio::test]
+    async fn smoke() {
+        let config = Config::default();
+        let app = router(Arc::new(Service::new(&config, MemoryStore::default())), &config);
+
+        let health = app.clone().oneshot(Request::get("/api/health").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+
+        let missing = Request::get("/api/session/missing").body(Body::empty()).unwrap();
+        let missing = app.clone().oneshot(missing).await.unwrap();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let body = to_bytes(missing.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<ApiError>(&body).unwrap().error, "Session not found");
+
+        let code = "fix loop (n : ℕ) : ℕ := loop n";
+        let termination = Request::post("/api/termination")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&CheckTerminationRequest { code: code.to_string() }).unwrap()))
+            .unwrap();
+        let termination = app.oneshot(termination).await.unwrap();
+        assert_eq!(termination.status(), StatusCode::OK);
+        let body = to_bytes(termination.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(report["statuses"]["loop"].get("Rejected").is_some());
+    }
+}
@@ -0,0 +1,1046 @@
+//! Framework-agnostic request handling for the SCTT servers
+//!
+//! Both server binaries are thin adapters over [`Service`]: the axum API
+//! server (`src/bin/sctt-server.rs`, which adds proof storage in SQLite) and
+//! the actix server in `rust/sctt-server` (which adds Leptos SSR and the
+//! cross-origin-isolated `/wasm` assets). An adapter decodes the request
+//! into an [`api`](crate::api) DTO, calls the method for the route, and
+//! encodes the answer or maps [`ServiceError::status`] onto its own status
+//! type. Websocket adapters register a [`Sink`] per connection and forward
+//! incoming text to [`Service::message`].
+//!
+//! Typecheck, evaluate and compile runs are kept in a bounded run log of
+//! [`RunRecord`]s, served at `GET /api/runs`; [`openapi`] describes the
+//! routes and publishes the record schema as a component.
+//!
+//! Both binaries read the same [`Config`]:
+//!
+//! | Flag                    | Environment          | Default                   |
+//! |-------------------------|----------------------|---------------------------|
+//! | `--host <ip>`           | `SCTT_HOST`          | `0.0.0.0`                 |
+//! | `--port <n>`            | `SCTT_PORT`          | `3000`                    |
+//! | `--database-url <url>`  | `DATABASE_URL`       | `sqlite:sctt.db`          |
+//! | `--static-dir <dir>`    | `SCTT_STATIC_DIR`    | `dist`                    |
+//! | `--content-dir <dir>`   | `SCTT_CONTENT_DIR`   | `content`                 |
+//! | `--artifacts-dir <dir>` | `SCTT_ARTIFACTS_DIR` | `$TMPDIR/sctt-artifacts`  |
+//! | `--cors-origin <url>`   | `SCTT_CORS_ORIGINS`  | any origin                |
+//!
+//! Flags win over the environment. `--cors-origin` may be repeated;
+//! `SCTT_CORS_ORIGINS` is comma-separated.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockWriteGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use sctt_checker::explain::SmoothnessReport;
+use sctt_core::compat::{self, Format, ENGINE_VERSION};
+use sctt_core::record::{self, RunRecord, RunResult, SampleSummary};
+use sctt_core::termination::{TerminationReport, TerminationStatus};
+
+use crate::api::{
+    self, ApiError, ApplyInTransactionRequest, ApplyInTransactionResponse, BeginTransactionRequest,
+    BeginTransactionResponse, CheckTerminationRequest, EvaluateBatchRequest, EvaluateBatchResponse, EvaluateRequest,
+    EvaluateResponse, ExplainSmoothnessRequest, HealthResponse, ProofRecord, ProveRequest, ProveResponse, RenameRequest,
+    RenameResponse,
+    RenamedAt, RouteExamples, SaveProofRequest, SaveProofResponse, SessionInfo, SessionResponse, TransactionOutcome, TypeCheckRequest, TypeCheckResponse,
+};
+#[cfg(feature = "compiler")]
+use crate::api::{CompileRequest, CompileResponse};
+#[cfg(feature = "compiler")]
+use crate::certificate;
+use crate::driver::{Outgoing, ProofRoom, DEFAULT_IDLE_TIMEOUT};
+use crate::lifecycle::{Guard, Registry, ResourceKind, SessionGuard, SessionResources};
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::rename::{self, RenameError};
+use crate::transaction::{TransactionError, DEFAULT_TRANSACTION_TIMEOUT};
+use crate::{Context, Document, Operation, ProofAssistant, Session, Tactic, TypeChecker};
+
+/// Compiles running longer than this are cancelled
+pub const COMPILE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often adapters should call [`Service::tick`]
+pub const TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runs kept in the run log
+pub const RUN_LOG_LEN: usize = 100;
+
+/// Server settings shared by every binary; see the module docs for the flags
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub addr: SocketAddr,
+    pub database_url: String,
+    /// Built frontend served at `/`
+    pub static_dir: PathBuf,
+    /// Markdown content (blog posts, lessons)
+    pub content_dir: PathBuf,
+    /// Where compile jobs write partial artifacts and detached certificates
+    pub artifacts_dir: PathBuf,
+    /// Allowed CORS origins; empty allows any
+    pub cors_origins: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 3000),
+            database_url: "sqlite:sctt.db".to_string(),
+            static_dir: PathBuf::from("dist"),
+            content_dir: PathBuf::from("content"),
+            artifacts_dir: std::env::temp_dir().join("sctt-artifacts"),
+            cors_origins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    UnknownFlag(String),
+    MissingValue(String),
+    Invalid { setting: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::UnknownFlag(flag) => write!(f, "unknown flag {}", flag),
+            ConfigError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            ConfigError::Invalid { setting, value } => write!(f, "invalid {}: {:?}", setting, value),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Read the process's arguments (without the program name) and environment
+    pub fn load() -> Result<Self, ConfigError> {
+        Config::from_sources(std::env::args().skip(1), |name| std::env::var(name).ok())
+    }
+
+    /// Defaults, overridden by `env`, overridden by `args`
+    pub fn from_sources(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+        let mut host = config.addr.ip();
+        let mut port = config.addr.port();
+
+        let settings = [
+            ("--host", "SCTT_HOST"),
+            ("--port", "SCTT_PORT"),
+            ("--database-url", "DATABASE_URL"),
+            ("--static-dir", "SCTT_STATIC_DIR"),
+            ("--content-dir", "SCTT_CONTENT_DIR"),
+            ("--artifacts-dir", "SCTT_ARTIFACTS_DIR"),
+        ];
+        let mut values: Vec<(&str, String)> = settings
+            .iter()
+            .filter_map(|(flag, var)| env(var).map(|value| (*flag, value)))
+            .collect();
+        if let Some(origins) = env("SCTT_CORS_ORIGINS") {
+            config.cors_origins = origins.split(',').map(str::trim).filter(|o| !o.is_empty()).map(str::to_string).collect();
+        }
+
+        let mut flag_origins = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let known = settings.iter().map(|(f, _)| *f).chain(["--cors-origin"]).find(|f| *f == flag);
+            let Some(known) = known else {
+                return Err(ConfigError::UnknownFlag(flag));
+            };
+            let value = inline.or_else(|| args.next()).ok_or_else(|| ConfigError::MissingValue(flag.clone()))?;
+            if known == "--cors-origin" {
+                flag_origins.push(value);
+            } else {
+                values.push((known, value));
+            }
+        }
+        if !flag_origins.is_empty() {
+            config.cors_origins = flag_origins;
+        }
+
+        let invalid = |setting: &str, value: &str| ConfigError::Invalid { setting: setting.to_string(), value: value.to_string() };
+        for (flag, value) in values {
+            match flag {
+                "--host" => host = value.parse().map_err(|_| invalid("host", &value))?,
+                "--port" => port = value.parse().map_err(|_| invalid("port", &value))?,
+                "--database-url" => config.database_url = value,
+                "--static-dir" => config.static_dir = PathBuf::from(value),
+                "--content-dir" => config.content_dir = PathBuf::from(value),
+                _ => config.artifacts_dir = PathBuf::from(value),
+            }
+        }
+        config.addr = SocketAddr::new(host, port);
+        Ok(config)
+    }
+}
+
+/// A handled request the service could not satisfy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceError {
+    NotFound(String),
+    /// A session write without the session's edit token
+    Unauthorized,
+    Conflict(String),
+    Unprocessable(String),
+    /// The proof store failed
+    Storage(String),
+}
+
+impl ServiceError {
+    /// The HTTP status an adapter should answer with
+    pub fn status(&self) -> u16 {
+        match self {
+            ServiceError::NotFound(_) => 404,
+            ServiceError::Unauthorized => 401,
+            ServiceError::Conflict(_) => 409,
+            ServiceError::Unprocessable(_) => 422,
+            ServiceError::Storage(_) => 500,
+        }
+    }
+
+    /// The response body
+    pub fn body(&self) -> ApiError {
+        ApiError { error: self.to_string() }
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServiceError::NotFound(message)
+            | ServiceError::Conflict(message)
+            | ServiceError::Unprocessable(message)
+            | ServiceError::Storage(message) => f.write_str(message),
+            ServiceError::Unauthorized => f.write_str("a valid session edit token is required"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<TransactionError> for ServiceError {
+    fn from(e: TransactionError) -> Self {
+        match e {
+            TransactionError::Busy { .. } => ServiceError::Conflict(e.to_string()),
+            TransactionError::Unknown(_) => ServiceError::NotFound(e.to_string()),
+            TransactionError::NoProof(_) | TransactionError::Tactic(_) => ServiceError::Unprocessable(e.to_string()),
+        }
+    }
+}
+
+impl From<RenameError> for ServiceError {
+    fn from(e: RenameError) -> Self {
+        match e {
+            RenameError::NotDefined(_) => ServiceError::NotFound(e.to_string()),
+            RenameError::NameInUse(_) => ServiceError::Conflict(e.to_string()),
+            RenameError::InvalidName(_) | RenameError::Parse { .. } | RenameError::Breaks(_) => {
+                ServiceError::Unprocessable(e.to_string())
+            }
+            RenameError::Write { .. } => ServiceError::Storage(e.to_string()),
+        }
+    }
+}
+
+/// Attach the replay warning to a proof saved with an older engine
+fn with_notice(mut proof: ProofRecord) -> ProofRecord {
+    proof.notice = compat::assess(Format::ProofScript, Some(&proof.engine_version)).notice();
+    proof
+}
+
+fn session_not_found() -> ServiceError {
+    ServiceError::NotFound("Session not found".to_string())
+}
+
+/// Where saved proofs live; the axum server keeps them in SQLite
+pub trait ProofStore: Send + Sync {
+    /// The most recent proofs first
+    fn list(&self) -> impl Future<Output = Result<Vec<ProofRecord>, String>> + Send;
+    fn get(&self, id: &str) -> impl Future<Output = Result<Option<ProofRecord>, String>> + Send;
+    fn save(&self, id: &str, proof: SaveProofRequest) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+/// Proofs kept for the life of the process
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    proofs: Mutex<Vec<ProofRecord>>,
+}
+
+impl MemoryStore {
+    fn proofs(&self) -> MutexGuard<'_, Vec<ProofRecord>> {
+        self.proofs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ProofStore for MemoryStore {
+    async fn list(&self) -> Result<Vec<ProofRecord>, String> {
+        Ok(self.proofs().iter().rev().take(100).cloned().collect())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<ProofRecord>, String> {
+        Ok(self.proofs().iter().find(|p| p.id == id).cloned())
+    }
+
+    async fn save(&self, id: &str, proof: SaveProofRequest) -> Result<(), String> {
+        let mut proofs = self.proofs();
+        if proofs.iter().any(|p| p.id == id) {
+            return Err(format!("a proof with id {} already exists", id));
+        }
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        proofs.push(ProofRecord {
+            id: id.to_string(),
+            name: proof.name,
+            statement: proof.statement,
+            proof: proof.proof,
+            created_at: created.as_secs().to_string(),
+            engine_version: ENGINE_VERSION.to_string(),
+            notice: None,
+        });
+        Ok(())
+    }
+}
+
+/// Delivers serialized server messages to one websocket; `false` once closed
+pub type Sink = Box<dyn Fn(String) -> bool + Send + Sync>;
+
+struct Connection {
+    id: String,
+    sink: Sink,
+    _registration: Guard,
+}
+
+struct SessionState {
+    document: String,
+    version: u64,
+    users: Vec<String>,
+    /// Bearer token for REST writes, handed to the session's creator
+    edit_token: String,
+    /// The shared proof and its driver token
+    room: ProofRoom,
+}
+
+/// Handler logic for every route, over whichever proof store the server has
+pub struct Service<S = MemoryStore> {
+    sessions: RwLock<HashMap<String, SessionState>>,
+    connections: Mutex<HashMap<String, Vec<Connection>>>,
+    runs: Mutex<VecDeque<RunRecord>>,
+    registry: Registry,
+    resources: SessionResources,
+    #[cfg_attr(not(feature = "compiler"), allow(dead_code))]
+    artifacts: PathBuf,
+    store: S,
+}
+
+impl<S: ProofStore> Service<S> {
+    pub fn new(config: &Config, store: S) -> Self {
+        Service::with_registry(config, store, Registry::global().clone())
+    }
+
+    /// Track sessions, jobs and connections in `registry` instead of the global one
+    pub fn with_registry(config: &Config, store: S, registry: Registry) -> Self {
+        Service {
+            sessions: RwLock::new(HashMap::new()),
+            connections: Mutex::new(HashMap::new()),
+            runs: Mutex::new(VecDeque::new()),
+            registry,
+            resources: SessionResources::default(),
+            artifacts: config.artifacts_dir.clone(),
+            store,
+        }
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    fn sessions(&self) -> RwLockWriteGuard<'_, HashMap<String, SessionState>> {
+        self.sessions.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn connections(&self) -> MutexGuard<'_, HashMap<String, Vec<Connection>>> {
+        self.connections.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn log_run(&self, code: &str, started: Instant, result: RunResult) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let run = RunRecord::new(code, record::rfc3339(now.as_secs()), result)
+            .with_engine("sctt-system", env!("CARGO_PKG_VERSION"))
+            .with_duration(started.elapsed());
+        let mut runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        if runs.len() == RUN_LOG_LEN {
+            runs.pop_back();
+        }
+        runs.push_front(run);
+    }
+
+    /// Recent typecheck, evaluate and compile runs, newest first
+    pub async fn runs(&self) -> Vec<RunRecord> {
+        self.runs.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    /// Run `f` on a session the bearer of `token` may write to
+    fn authorized<T>(
+        &self,
+        id: &str,
+        token: Option<&str>,
+        f: impl FnOnce(&mut SessionState) -> Result<T, ServiceError>,
+    ) -> Result<T, ServiceError> {
+        let mut sessions = self.sessions();
+        let session = sessions.get_mut(id).ok_or_else(session_not_found)?;
+        if token != Some(session.edit_token.as_str()) {
+            return Err(ServiceError::Unauthorized);
+        }
+        f(session)
+    }
+
+    pub async fn health(&self) -> HealthResponse {
+        HealthResponse { status: "healthy".to_string(), version: env!("CARGO_PKG_VERSION").to_string() }
+    }
+
+    /// Worked request/response pairs for every route
+    pub async fn examples(&self) -> Vec<RouteExamples> {
+        api::examples()
+    }
+
+    pub async fn typecheck(&self, req: TypeCheckRequest) -> TypeCheckResponse {
+        let started = Instant::now();
+        let inferred = crate::parser::parse(&req.code)
+            .and_then(|term| TypeChecker::new().infer(&Context::new(), &term).map_err(|e| format!("{:?}", e)));
+        let result = match &inferred {
+            Ok(ty) => RunResult::Checked { ty: format!("{:?}", ty) },
+            Err(e) => RunResult::Failed { error: e.clone() },
+        };
+        self.log_run(&req.code, started, result);
+        match inferred {
+            Ok(ty) => TypeCheckResponse { success: true, result: Some(format!("{:?}", ty)), error: None },
+            Err(e) => TypeCheckResponse { success: false, result: None, error: Some(e) },
+        }
+    }
+
+    pub async fn evaluate(&self, req: EvaluateRequest) -> EvaluateResponse {
+        let started = Instant::now();
+        let evaluation = sctt_checker::lite::evaluate_smooth(&req.expression, req.value);
+        let result = match &evaluation {
+            Ok(evaluation) => RunResult::Evaluated { samples: SampleSummary::of(&[evaluation.value]) },
+            Err(e) => RunResult::Failed { error: e.to_string() },
+        };
+        self.log_run(&req.expression, started, result);
+        match evaluation {
+            Ok(evaluation) => EvaluateResponse {
+                success: true,
+                expression: req.expression,
+                input: req.value,
+                result: Some(evaluation.value),
+                slope: Some(evaluation.slope),
+                error: None,
+            },
+            Err(e) => EvaluateResponse {
+                success: false,
+                expression: req.expression,
+                input: req.value,
+                result: None,
+                slope: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    pub async fn evaluate_batch(&self, req: EvaluateBatchRequest) -> EvaluateBatchResponse {
+        let started = Instant::now();
+        let evaluations: Result<Vec<_>, _> =
+            req.values.iter().map(|&x| sctt_checker::lite::evaluate_smooth(&req.expression, x)).collect();
+        let result = match &evaluations {
+            Ok(evaluations) => {
+                let values: Vec<f64> = evaluations.iter().map(|e| e.value).collect();
+                RunResult::Evaluated { samples: SampleSummary::of(&values) }
+            }
+            Err(e) => RunResult::Failed { error: e.to_string() },
+        };
+        self.log_run(&req.expression, started, result);
+        match evaluations {
+            Ok(evaluations) => EvaluateBatchResponse {
+                success: true,
+                expression: req.expression,
+                results: evaluations.iter().map(|e| e.value).collect(),
+                slopes: evaluations.iter().map(|e| e.slope).collect(),
+                error: None,
+            },
+            Err(e) => EvaluateBatchResponse {
+                success: false,
+                expression: req.expression,
+                results: Vec::new(),
+                slopes: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    /// Where and why an expression or piecewise path fails to be smooth
+    pub async fn explain_smoothness(&self, req: ExplainSmoothnessRequest) -> Result<SmoothnessReport, ServiceError> {
+        sctt_checker::explain::explain_smoothness(&req.code).map_err(|e| ServiceError::Unprocessable(e.to_string()))
+    }
+
+    /// The termination status of each definition, and the ones resting on
+    /// an `unsafe_fix`; a rejected recursive call is a status, but code that
+    /// does not parse or check is unprocessable
+    pub async fn check_termination(&self, req: CheckTerminationRequest) -> Result<TerminationReport, ServiceError> {
+        use sctt_core::definitions::Definitions;
+        use sctt_core::{termination, TypeError};
+
+        let unprocessable = |e: TypeError| ServiceError::Unprocessable(e.render(&req.code));
+        let fixes = termination::parse_fixes(&req.code).map_err(|e| unprocessable(e.into()))?;
+        let mut defs = Definitions::new();
+        let mut rejected = Vec::new();
+        for fix in &fixes {
+            match defs.define_fix(fix) {
+                Ok(_) => {}
+                Err(e) => match e.unlocated() {
+                    TypeError::Termination(reason) => rejected.push((fix.name.clone(), reason.clone())),
+                    _ => return Err(unprocessable(e)),
+                },
+            }
+        }
+        let mut report = defs.termination();
+        for (name, reason) in rejected {
+            report.statuses.insert(name, TerminationStatus::Rejected(reason));
+        }
+        Ok(report)
+    }
+
+    /// Compile on a worker thread, giving up after [`COMPILE_TIMEOUT`]
+    ///
+    /// This blocks the calling thread; adapters run it on their runtime's
+    /// blocking pool. An abandoned worker's partial artifact is removed when
+    /// it finishes.
+    #[cfg(feature = "compiler")]
+    pub fn compile(&self, req: CompileRequest) -> CompileResponse {
+        use crate::lifecycle::CompileJob;
+        use crate::{OptLevel, ScttToWasmCompiler};
+
+        let started = Instant::now();
+        let code = req.code.clone();
+        let strip = req.strip_certificates;
+        let failed = |e: String| {
+            self.log_run(&code, started, RunResult::Failed { error: e.clone() });
+            CompileResponse { success: false, wasm: None, error: Some(e), size: None, certificate: None }
+        };
+        let mut job = match CompileJob::new(&self.registry, &self.artifacts, "api/compile") {
+            Ok(job) => job,
+            Err(e) => return failed(e.to_string()),
+        };
+        let opt_level = match req.optimization.as_str() {
+            "none" => OptLevel::None,
+            "aggressive" => OptLevel::Aggressive,
+            _ => OptLevel::Basic,
+        };
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let work = || -> Result<Vec<u8>, String> {
+                let mut compiler = ScttToWasmCompiler::new(opt_level);
+                let term = crate::parser::parse(&req.code)?;
+                let ir = compiler.sctt_to_ir(&term).map_err(|e| e.to_string())?;
+                let optimized = compiler.optimize(ir);
+                let wasm_module = compiler.ir_to_wasm(&optimized).map_err(|e| e.to_string())?;
+                let bytes = wasm_module.encode().map_err(|e| e.to_string())?;
+                job.write(&bytes).map_err(|e| e.to_string())?;
+                job.finish().map_err(|e| e.to_string())
+            };
+            let _ = tx.send(work());
+        });
+
+        match rx.recv_timeout(COMPILE_TIMEOUT) {
+            Ok(Ok(wasm)) => {
+                let size = match certificate::size_report(&wasm) {
+                    Ok(size) => size,
+                    Err(e) => return failed(e.to_string()),
+                };
+                let (wasm, certificate) = if strip {
+                    match self.detach_certificate(&wasm) {
+                        Ok((stripped, name)) => (stripped, Some(name)),
+                        Err(e) => return failed(e),
+                    }
+                } else {
+                    (wasm, None)
+                };
+                let hash = record::code_hash(&String::from_utf8_lossy(&wasm));
+                self.log_run(&code, started, RunResult::Compiled { size: wasm.len(), hash });
+                CompileResponse { success: true, wasm: Some(wasm), error: None, size: Some(size), certificate }
+            }
+            Ok(Err(e)) => failed(e),
+            Err(_) => failed(format!("Compilation cancelled after {}s", COMPILE_TIMEOUT.as_secs())),
+        }
+    }
+
+    /// Strip a compiled module, writing its certificate into the artifact
+    /// cache as `<stripped hash>.cert.json`; returns the stripped module and
+    /// the certificate's file name
+    #[cfg(feature = "compiler")]
+    fn detach_certificate(&self, wasm: &[u8]) -> Result<(Vec<u8>, String), String> {
+        let stripped = certificate::strip_certificates(wasm).map_err(|e| e.to_string())?;
+        let name = format!("{}.cert.json", stripped.certificate.stripped_hash);
+        let json = serde_json::to_vec_pretty(&stripped.certificate).map_err(|e| e.to_string())?;
+        std::fs::write(self.artifacts.join(&name), json).map_err(|e| e.to_string())?;
+        Ok((stripped.wasm, name))
+    }
+
+    /// Start a proof of the statement and run the tactics on its first goal
+    pub async fn prove(&self, req: ProveRequest) -> ProveResponse {
+        let failed = |e: String| ProveResponse { success: false, proof_state: None, error: Some(e) };
+        let mut assistant = ProofAssistant::new();
+        let term = match crate::parser::parse(&req.statement) {
+            Ok(term) => term,
+            Err(e) => return failed(e),
+        };
+        if let Err(e) = assistant.start_proof("goal", term) {
+            return failed(e);
+        }
+        for tactic in req.tactics.iter().filter_map(|name| Tactic::from_name(name)) {
+            let _ = assistant.apply_tactic(tactic, 0);
+        }
+        ProveResponse { success: true, proof_state: Some(assistant.render_proof_state()), error: None }
+    }
+
+    pub async fn create_session(&self) -> SessionResponse {
+        let id = uuid::Uuid::new_v4().to_string();
+        let edit_token = uuid::Uuid::new_v4().to_string();
+        let session = SessionState {
+            document: String::new(),
+            version: 0,
+            users: Vec::new(),
+            edit_token: edit_token.clone(),
+            room: ProofRoom::new(
+                SessionGuard::open(&self.registry, &self.resources, &id),
+                DEFAULT_IDLE_TIMEOUT,
+                Instant::now(),
+            ),
+        };
+        self.sessions().insert(id.clone(), session);
+        SessionResponse { id, created: true, edit_token }
+    }
+
+    pub async fn session(&self, id: &str) -> Result<SessionInfo, ServiceError> {
+        let sessions = self.sessions.read().unwrap_or_else(|e| e.into_inner());
+        let session = sessions.get(id).ok_or_else(session_not_found)?;
+        Ok(SessionInfo {
+            id: id.to_string(),
+            document: session.document.clone(),
+            version: session.version,
+            users: session.users.clone(),
+            proof_state: session.room.session.assistant.render_proof_state(),
+            driver: session.room.token.driver().map(str::to_string),
+        })
+    }
+
+    /// Transactions write to the session, so they need its edit `token`
+    pub async fn begin_transaction(
+        &self,
+        id: &str,
+        token: Option<&str>,
+        req: BeginTransactionRequest,
+    ) -> Result<BeginTransactionResponse, ServiceError> {
+        let timeout = req.timeout_secs.map(Duration::from_secs).unwrap_or(DEFAULT_TRANSACTION_TIMEOUT);
+        let transaction = self.authorized(id, token, |session| {
+            Ok(session.room.begin_transaction(&req.client, timeout, Instant::now())?)
+        })?;
+        Ok(BeginTransactionResponse { transaction, timeout_secs: timeout.as_secs() })
+    }
+
+    pub async fn apply_in_transaction(
+        &self,
+        id: &str,
+        txn: &str,
+        token: Option<&str>,
+        req: ApplyInTransactionRequest,
+    ) -> Result<ApplyInTransactionResponse, ServiceError> {
+        let proof_state = self.authorized(id, token, |session| {
+            Ok(session.room.apply_in_transaction(txn, req.goal_id, &req.tactic, Instant::now())?)
+        })?;
+        Ok(ApplyInTransactionResponse { proof_state })
+    }
+
+    /// Publish the transaction's steps to the session and its connections
+    pub async fn commit_transaction(
+        &self,
+        id: &str,
+        txn: &str,
+        token: Option<&str>,
+    ) -> Result<TransactionOutcome, ServiceError> {
+        let outgoing = self.authorized(id, token, |session| Ok(session.room.commit(txn, Instant::now())?))?;
+        self.deliver(id, None, outgoing);
+        Ok(TransactionOutcome { transaction: txn.to_string(), committed: true })
+    }
+
+    pub async fn rollback_transaction(
+        &self,
+        id: &str,
+        txn: &str,
+        token: Option<&str>,
+    ) -> Result<TransactionOutcome, ServiceError> {
+        self.authorized(id, token, |session| Ok(session.room.rollback(txn)?))?;
+        Ok(TransactionOutcome { transaction: txn.to_string(), committed: false })
+    }
+
+    /// Rename a definition in the session document and broadcast the new
+    /// document; nothing changes unless every definition that checked
+    /// still does
+    pub async fn rename(&self, id: &str, token: Option<&str>, req: RenameRequest) -> Result<RenameResponse, ServiceError> {
+        let (response, outgoing) = self.authorized(id, token, |session| {
+            let mut documents = vec![rename::Document::new(id, &session.document)];
+            let renamed = rename::rename_definition(&mut documents, &req.old, &req.new)?;
+            let op = Operation::Replace { pos: 0, len: session.document.len(), text: documents.remove(0).source };
+            op.apply(&mut session.document);
+            session.version += 1;
+            let response = RenameResponse {
+                occurrences: renamed
+                    .occurrences
+                    .into_iter()
+                    .map(|o| RenamedAt { line: o.location.line, column: o.location.column, definition: o.definition })
+                    .collect(),
+                rechecked: renamed.rechecked,
+                version: session.version,
+            };
+            let user_id = "rename".to_string();
+            Ok((response, vec![Outgoing::Broadcast(ServerMessage::Operation { op, user_id, version: session.version })]))
+        })?;
+        self.deliver(id, None, outgoing);
+        Ok(response)
+    }
+
+    pub async fn list_proofs(&self) -> Result<Vec<ProofRecord>, ServiceError> {
+        let proofs = self.store.list().await.map_err(ServiceError::Storage)?;
+        Ok(proofs.into_iter().map(with_notice).collect())
+    }
+
+    pub async fn get_proof(&self, id: &str) -> Result<ProofRecord, ServiceError> {
+        self.store
+            .get(id)
+            .await
+            .map_err(ServiceError::Storage)?
+            .map(with_notice)
+            .ok_or_else(|| ServiceError::NotFound("Proof not found".to_string()))
+    }
+
+    pub async fn save_proof(&self, id: &str, req: SaveProofRequest) -> Result<SaveProofResponse, ServiceError> {
+        self.store.save(id, req).await.map_err(ServiceError::Storage)?;
+        Ok(SaveProofResponse { success: true, id: id.to_string() })
+    }
+
+    /// Live engine resources and the ones that have outlived their lifetime
+    pub async fn resource_diagnostics(&self) -> serde_json::Value {
+        let live: Vec<_> = self
+            .registry
+            .live()
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "id": r.id,
+                    "kind": r.kind.to_string(),
+                    "label": r.label,
+                    "site": r.site.to_string(),
+                })
+            })
+            .collect();
+        let leaks: Vec<_> = self.registry.leaks().iter().map(|leak| leak.to_string()).collect();
+        serde_json::json!({ "live": live, "leaks": leaks })
+    }
+
+    /// Open a websocket connection to a session, returning its connection id
+    ///
+    /// The connection is welcomed through `sink` and joins the session's
+    /// proof room; the session's first connection drives.
+    pub fn connect(&self, session_id: &str, sink: Sink) -> Result<String, ServiceError> {
+        let conn_id = uuid::Uuid::new_v4().to_string();
+        let joined = {
+            let mut sessions = self.sessions();
+            let session = sessions.get_mut(session_id).ok_or_else(session_not_found)?;
+            let welcome = ServerMessage::Welcome {
+                session: Session {
+                    id: session_id.to_string(),
+                    users: Vec::new(),
+                    document: Document {
+                        content: session.document.clone(),
+                        version: session.version,
+                        operations: Vec::new(),
+                    },
+                    proof_state: session.room.proof_state(),
+                },
+                user_id: conn_id.clone(),
+            };
+            let mut out = vec![Outgoing::Reply(welcome)];
+            out.extend(session.room.join(&conn_id, Instant::now()));
+            out
+        };
+        let connection = Connection {
+            id: conn_id.clone(),
+            sink,
+            _registration: Guard::new(&self.registry, ResourceKind::Connection, session_id),
+        };
+        self.connections().entry(session_id.to_string()).or_default().push(connection);
+        self.deliver(session_id, Some(&conn_id), joined);
+        Ok(conn_id)
+    }
+
+    /// Handle one text frame from a connection; undecodable frames are ignored
+    pub fn message(&self, session_id: &str, conn_id: &str, text: &str) {
+        let Ok(msg) = serde_json::from_str::<ClientMessage>(text) else {
+            return;
+        };
+        let outgoing = {
+            let mut sessions = self.sessions();
+            let Some(session) = sessions.get_mut(session_id) else {
+                return;
+            };
+            match msg {
+                ClientMessage::Operation { op, version } => {
+                    if !op.apply(&mut session.document) {
+                        return;
+                    }
+                    session.version = version + 1;
+                    vec![Outgoing::Broadcast(ServerMessage::Operation {
+                        op,
+                        user_id: conn_id.to_string(),
+                        version: session.version,
+                    })]
+                }
+                // Relayed as sent; whether a cursor or focus is stale is
+                // for each receiving client to judge
+                ClientMessage::CursorUpdate { cursor, focus } => {
+                    vec![Outgoing::Broadcast(ServerMessage::CursorUpdate {
+                        user_id: conn_id.to_string(),
+                        cursor,
+                        focus,
+                    })]
+                }
+                // Proof actions and control requests go through the driver token
+                msg => session.room.handle(conn_id, msg, Instant::now()),
+            }
+        };
+        self.deliver(session_id, Some(conn_id), outgoing);
+    }
+
+    /// Close a connection; a disconnecting driver releases the token
+    pub fn disconnect(&self, session_id: &str, conn_id: &str) {
+        let released = match self.sessions().get_mut(session_id) {
+            Some(session) => session.room.leave(conn_id, Instant::now()),
+            None => Vec::new(),
+        };
+        {
+            let mut connections = self.connections();
+            if let Some(conns) = connections.get_mut(session_id) {
+                conns.retain(|c| c.id != conn_id);
+                if conns.is_empty() {
+                    connections.remove(session_id);
+                }
+            }
+        }
+        self.deliver(session_id, None, released);
+    }
+
+    /// Hand the token away from idle drivers and roll back quiet
+    /// transactions; call every [`TICK_INTERVAL`]
+    pub fn tick(&self, now: Instant) {
+        let handoffs: Vec<_> = self
+            .sessions()
+            .iter_mut()
+            .flat_map(|(id, session)| session.room.tick(now).into_iter().map(|out| (id.clone(), out)).collect::<Vec<_>>())
+            .collect();
+        for (id, out) in handoffs {
+            self.deliver(&id, None, vec![out]);
+        }
+    }
+
+    /// Send replies to the connection they answer and broadcasts to the
+    /// whole session, dropping connections whose sink has closed
+    fn deliver(&self, session_id: &str, reply_to: Option<&str>, outgoing: Vec<Outgoing>) {
+        let mut connections = self.connections();
+        let Some(conns) = connections.get_mut(session_id) else {
+            return;
+        };
+        for out in outgoing {
+            let (msg, only) = match out {
+                Outgoing::Reply(msg) => match reply_to {
+                    Some(conn) => (msg, Some(conn)),
+                    None => continue,
+                },
+                Outgoing::Broadcast(msg) => (msg, None),
+            };
+            let Ok(text) = serde_json::to_string(&msg) else {
+                continue;
+            };
+            conns.retain(|c| only.is_some_and(|id| id != c.id) || (c.sink)(text.clone()));
+        }
+    }
+}
+
+/// OpenAPI description of the REST routes, with the [`RunRecord`] schema
+/// as a component
+///
+/// Request and response bodies of the other routes are documented by
+/// example at `GET /api/examples`.
+pub fn openapi() -> serde_json::Value {
+    use serde_json::json;
+
+    let mut paths = serde_json::Map::new();
+    let mut operation = |method: &str, path: &str, description: &str, schema: serde_json::Value| {
+        // OpenAPI writes path parameters as `{id}`
+        let path = path
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => format!("{{{}}}", name),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let entry = paths.entry(path).or_insert_with(|| json!({}));
+        entry[method.to_lowercase()] = json!({
+            "responses": {
+                "200": { "description": description, "content": { "application/json": { "schema": schema } } },
+                "default": { "$ref": "#/components/responses/Error" },
+            }
+        });
+    };
+    for route in api::examples() {
+        operation(&route.method, &route.path, "See `GET /api/examples` for worked bodies", json!({}));
+    }
+    operation(
+        "POST",
+        "/api/smooth/explain",
+        "Where and why the code fails to be smooth",
+        json!({ "type": "object" }),
+    );
+    operation(
+        "POST",
+        "/api/termination",
+        "The termination status of each definition, and what taints it",
+        json!({ "type": "object" }),
+    );
+    operation(
+        "GET",
+        "/api/runs",
+        "Recent runs, newest first",
+        json!({ "type": "array", "items": { "$ref": "#/components/schemas/RunRecord" } }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": "SCTT API", "version": env!("CARGO_PKG_VERSION") },
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "RunRecord": record::schema(),
+                "ApiError": {
+                    "type": "object",
+                    "properties": { "error": { "type": "string" } },
+                    "required": ["error"],
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "The request could not be satisfied",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/ApiError" } } },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_flags_override_environment_overrides_defaults() {
+        let config = Config::from_sources(
+            args(&["--port", "8080", "--static-dir=site"]),
+            env(&[("SCTT_PORT", "9000"), ("DATABASE_URL", "sqlite::memory:"), ("SCTT_HOST", "127.0.0.1")]),
+        )
+        .unwrap();
+        assert_eq!(config.addr, "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(config.database_url, "sqlite::memory:");
+        assert_eq!(config.static_dir, PathBuf::from("site"));
+        assert_eq!(config.content_dir, Config::default().content_dir);
+        assert!(config.cors_origins.is_empty());
+    }
+
+    #[test]
+    fn test_cors_origins_from_either_source() {
+        let from_env = Config::from_sources(
+            Vec::new(),
+            env(&[("SCTT_CORS_ORIGINS", "https://a.example, https://b.example,")]),
+        )
+        .unwrap();
+        assert_eq!(from_env.cors_origins, ["https://a.example", "https://b.example"]);
+
+        let from_flags = Config::from_sources(
+            args(&["--cors-origin", "https://c.example", "--cors-origin=https://d.example"]),
+            env(&[("SCTT_CORS_ORIGINS", "https://a.example")]),
+        )
+        .unwrap();
+        assert_eq!(from_flags.cors_origins, ["https://c.example", "https://d.example"]);
+    }
+
+    #[test]
+    fn test_bad_flags_are_reported() {
+        let load = |a: &[&str]| Config::from_sources(args(a), env(&[]));
+        assert_eq!(load(&["--verbose"]), Err(ConfigError::UnknownFlag("--verbose".to_string())));
+        assert_eq!(load(&["--port"]), Err(ConfigError::MissingValue("--port".to_string())));
+        assert_eq!(
+            load(&["--port", "http"]),
+            Err(ConfigError::Invalid { setting: "port".to_string(), value: "http".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_openapi_publishes_the_run_record_schema() {
+        let doc = openapi();
+        assert_eq!(doc["components"]["schemas"]["RunRecord"], record::schema());
+        let runs = &doc["paths"]["/api/runs"]["get"]["responses"]["200"]["content"]["application/json"]["schema"];
+        assert_eq!(runs["items"]["$ref"], "#/components/schemas/RunRecord");
+        assert!(doc["paths"]["/api/session/{id}/transactions"]["post"].is_object());
+    }
+
+    #[test]
+    fn test_transaction_errors_map_to_statuses() {
+        let busy = TransactionError::Busy { id: "txn-1".to_string(), owner: "bot".to_string() };
+        assert_eq!(ServiceError::from(busy).status(), 409);
+        assert_eq!(ServiceError::from(TransactionError::Unknown("txn-9".to_string())).status(), 404);
+        assert_eq!(ServiceError::from(TransactionError::NoProof("none".to_string())).status(), 422);
+        assert_eq!(ServiceError::Unauthorized.body().error, "a valid session edit token is required");
+    }
+
+    #[test]
+    fn test_proofs_from_an_older_engine_are_replayed_with_a_warning() {
+        let proof = |engine_version: &str| ProofRecord {
+            id: "p".to_string(),
+            name: "id".to_string(),
+            statement: "Type".to_string(),
+            proof: "intro A".to_string(),
+            created_at: "0".to_string(),
+            engine_version: engine_version.to_string(),
+            notice: None,
+        };
+        assert_eq!(with_notice(proof(ENGINE_VERSION)).notice, None);
+        let old = with_notice(proof(""));
+        assert_eq!(old.proof, "intro A");
+        assert!(old.notice.unwrap().contains("replaying"));
+    }
+}
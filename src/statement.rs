@@ -0,0 +1,580 @@
+//! Surface statements over ℝ
+//!
+//! A small named-variable language for theorem statements such as
+//! `Π (x y : ℝ). Path ℝ (x + y) (y + x)`. It is what the statement wizard
+//! generates and what textual goals are phrased in:
+//! - Π and Σ binders over simple types
+//! - Path types between real-valued expressions
+//! - A commutative-ring normalizer backing the `ring` tactic
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Simple types: ℝ and functions between them
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ty {
+    Real,
+    Arrow(Box<Ty>, Box<Ty>),
+}
+
+/// Real-valued expressions
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Var(String),
+    App(Box<Expr>, Box<Expr>),
+    /// Infix operator: a builtin symbol (`+`, `-`, `*`) or a backticked name
+    BinOp(String, Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Pi(String, Ty, Box<Statement>),
+    Sigma(String, Ty, Box<Statement>),
+    Path(Ty, Expr, Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementError {
+    Parse(String),
+    Unbound(String),
+    Mismatch { expected: Ty, found: Ty },
+    NotAFunction(Expr),
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StatementError::Parse(msg) => write!(f, "parse error: {}", msg),
+            StatementError::Unbound(name) => write!(f, "unbound variable `{}`", name),
+            StatementError::Mismatch { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            StatementError::NotAFunction(e) => write!(f, "`{}` is not a function", e),
+        }
+    }
+}
+
+pub const BUILTIN_OPS: [&str; 3] = ["+", "-", "*"];
+
+impl Ty {
+    pub fn arrow(a: Ty, b: Ty) -> Ty {
+        Ty::Arrow(Box::new(a), Box::new(b))
+    }
+
+    /// `ℝ → ℝ → ℝ`
+    pub fn binary_op() -> Ty {
+        Ty::arrow(Ty::Real, Ty::arrow(Ty::Real, Ty::Real))
+    }
+}
+
+impl Statement {
+    /// Leading Π binders, outermost first
+    pub fn leading_binders(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        let mut s = self;
+        while let Statement::Pi(x, _, body) = s {
+            names.push(x.as_str());
+            s = body;
+        }
+        names
+    }
+
+    /// The equation under any leading Π binders over ℝ, if it is one over ℝ
+    pub fn ring_equation(&self) -> Option<(&Expr, &Expr)> {
+        match self {
+            Statement::Pi(_, Ty::Real, body) => body.ring_equation(),
+            Statement::Path(Ty::Real, lhs, rhs) => Some((lhs, rhs)),
+            _ => None,
+        }
+    }
+
+    /// Rename free occurrences of `from` to `to`
+    pub fn rename(&self, from: &str, to: &str) -> Statement {
+        match self {
+            Statement::Pi(x, ty, body) | Statement::Sigma(x, ty, body) => {
+                let body = if x == from { body.as_ref().clone() } else { body.rename(from, to) };
+                let rebuild = if matches!(self, Statement::Pi(..)) { Statement::Pi } else { Statement::Sigma };
+                rebuild(x.clone(), ty.clone(), Box::new(body))
+            }
+            Statement::Path(ty, a, b) => Statement::Path(ty.clone(), a.rename(from, to), b.rename(from, to)),
+        }
+    }
+
+    /// Equality up to the names of bound variables
+    pub fn convertible(&self, other: &Statement) -> bool {
+        match (self, other) {
+            (Statement::Pi(x, a, s), Statement::Pi(y, b, t))
+            | (Statement::Sigma(x, a, s), Statement::Sigma(y, b, t)) => {
+                a == b && s.convertible(&t.rename(y, x))
+            }
+            (Statement::Path(a, l1, r1), Statement::Path(b, l2, r2)) => a == b && l1 == l2 && r1 == r2,
+            _ => false,
+        }
+    }
+}
+
+impl Expr {
+    pub fn rename(&self, from: &str, to: &str) -> Expr {
+        match self {
+            Expr::Var(x) if x == from => Expr::Var(to.to_string()),
+            Expr::Num(_) | Expr::Var(_) => self.clone(),
+            Expr::App(f, a) => Expr::App(Box::new(f.rename(from, to)), Box::new(a.rename(from, to))),
+            Expr::BinOp(op, a, b) => {
+                let op = if op == from { to.to_string() } else { op.clone() };
+                Expr::BinOp(op, Box::new(a.rename(from, to)), Box::new(b.rename(from, to)))
+            }
+            Expr::Neg(e) => Expr::Neg(Box::new(e.rename(from, to))),
+        }
+    }
+
+    fn is_atom(&self) -> bool {
+        matches!(self, Expr::Num(_) | Expr::Var(_))
+    }
+}
+
+/// Typing environment for statements
+#[derive(Debug, Clone, Default)]
+pub struct Env {
+    bindings: Vec<(String, Ty)>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env::default()
+    }
+
+    /// The standard smooth functions on ℝ
+    pub fn prelude() -> Self {
+        let mut env = Env::new();
+        for f in ["sin", "cos", "exp"] {
+            env.bind(f, Ty::arrow(Ty::Real, Ty::Real));
+        }
+        env
+    }
+
+    pub fn bind(&mut self, name: &str, ty: Ty) {
+        self.bindings.push((name.to_string(), ty));
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&Ty> {
+        self.bindings.iter().rev().find(|(n, _)| n == name).map(|(_, ty)| ty)
+    }
+
+    pub fn infer(&self, e: &Expr) -> Result<Ty, StatementError> {
+        match e {
+            Expr::Num(_) => Ok(Ty::Real),
+            Expr::Var(x) => self.lookup(x).cloned().ok_or_else(|| StatementError::Unbound(x.clone())),
+            Expr::App(f, a) => match self.infer(f)? {
+                Ty::Arrow(dom, cod) => {
+                    self.check(a, &dom)?;
+                    Ok(*cod)
+                }
+                Ty::Real => Err(StatementError::NotAFunction(f.as_ref().clone())),
+            },
+            Expr::BinOp(op, a, b) if BUILTIN_OPS.contains(&op.as_str()) => {
+                self.check(a, &Ty::Real)?;
+                self.check(b, &Ty::Real)?;
+                Ok(Ty::Real)
+            }
+            Expr::BinOp(op, a, b) => {
+                let f = Expr::App(Box::new(Expr::App(Box::new(Expr::Var(op.clone())), a.clone())), b.clone());
+                self.infer(&f)
+            }
+            Expr::Neg(e) => {
+                self.check(e, &Ty::Real)?;
+                Ok(Ty::Real)
+            }
+        }
+    }
+
+    pub fn check(&self, e: &Expr, ty: &Ty) -> Result<(), StatementError> {
+        let found = self.infer(e)?;
+        if &found == ty {
+            Ok(())
+        } else {
+            Err(StatementError::Mismatch { expected: ty.clone(), found })
+        }
+    }
+
+    pub fn check_statement(&self, s: &Statement) -> Result<(), StatementError> {
+        match s {
+            Statement::Pi(x, ty, body) | Statement::Sigma(x, ty, body) => {
+                let mut env = self.clone();
+                env.bind(x, ty.clone());
+                env.check_statement(body)
+            }
+            Statement::Path(ty, a, b) => {
+                self.check(a, ty)?;
+                self.check(b, ty)
+            }
+        }
+    }
+}
+
+// Ring normalization
+
+/// Polynomial with monomials as sorted lists of opaque atoms
+type Polynomial = BTreeMap<Vec<String>, f64>;
+
+fn add_into(p: &mut Polynomial, q: Polynomial, sign: f64) {
+    for (m, c) in q {
+        *p.entry(m).or_insert(0.0) += sign * c;
+    }
+}
+
+fn to_polynomial(e: &Expr) -> Polynomial {
+    let mut p = Polynomial::new();
+    match e {
+        Expr::Num(n) => {
+            p.insert(Vec::new(), *n);
+        }
+        Expr::Neg(a) => add_into(&mut p, to_polynomial(a), -1.0),
+        Expr::BinOp(op, a, b) if op == "+" || op == "-" => {
+            add_into(&mut p, to_polynomial(a), 1.0);
+            add_into(&mut p, to_polynomial(b), if op == "+" { 1.0 } else { -1.0 });
+        }
+        Expr::BinOp(op, a, b) if op == "*" => {
+            for (ma, ca) in to_polynomial(a) {
+                for (mb, cb) in to_polynomial(b) {
+                    let mut m: Vec<String> = ma.iter().chain(&mb).cloned().collect();
+                    m.sort();
+                    *p.entry(m).or_insert(0.0) += ca * cb;
+                }
+            }
+        }
+        // Variables, applications and unknown operators are opaque atoms
+        _ => {
+            p.insert(vec![e.to_string()], 1.0);
+        }
+    }
+    p
+}
+
+/// Whether two expressions are equal as elements of a commutative ring
+pub fn ring_equal(lhs: &Expr, rhs: &Expr) -> bool {
+    let mut diff = to_polynomial(lhs);
+    add_into(&mut diff, to_polynomial(rhs), -1.0);
+    diff.values().all(|c| c.abs() < 1e-9)
+}
+
+// Parsing
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Num(f64),
+    Op(String),
+    Sym(char),
+    Arrow,
+    Pi,
+    Sigma,
+    Path,
+    Real,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, StatementError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut s = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit() || **d == '.') {
+                s.push(d);
+                chars.next();
+            }
+            let n = s.parse().map_err(|_| StatementError::Parse(format!("bad number `{}`", s)))?;
+            tokens.push(Token::Num(n));
+        } else if c.is_alphabetic() && c != 'Π' && c != 'Σ' && c != 'ℝ' || c == '_' {
+            let mut s = String::new();
+            while let Some(&d) = chars.peek().filter(|d| d.is_alphanumeric() || **d == '_' || **d == '\'') {
+                s.push(d);
+                chars.next();
+            }
+            tokens.push(match s.as_str() {
+                "forall" => Token::Pi,
+                "exists" => Token::Sigma,
+                "Path" => Token::Path,
+                "R" => Token::Real,
+                _ => Token::Ident(s),
+            });
+        } else if c == '`' {
+            chars.next();
+            let name: String = chars.by_ref().take_while(|&d| d != '`').collect();
+            tokens.push(Token::Op(name));
+        } else {
+            chars.next();
+            tokens.push(match c {
+                'Π' => Token::Pi,
+                'Σ' => Token::Sigma,
+                'ℝ' => Token::Real,
+                '→' => Token::Arrow,
+                '-' if chars.peek() == Some(&'>') => {
+                    chars.next();
+                    Token::Arrow
+                }
+                '+' | '-' | '*' => Token::Op(c.to_string()),
+                '(' | ')' | '.' | ':' => Token::Sym(c),
+                _ => return Err(StatementError::Parse(format!("unexpected `{}`", c))),
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn expect(&mut self, t: Token) -> Result<(), StatementError> {
+        match self.next() {
+            Some(ref got) if *got == t => Ok(()),
+            got => Err(StatementError::Parse(format!("expected {:?}, found {:?}", t, got))),
+        }
+    }
+
+    fn finish(&self) -> Result<(), StatementError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(t) => Err(StatementError::Parse(format!("unexpected trailing {:?}", t))),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, StatementError> {
+        match self.next() {
+            Some(Token::Pi) => self.binder(Statement::Pi),
+            Some(Token::Sigma) => self.binder(Statement::Sigma),
+            Some(Token::Path) => {
+                let ty = self.ty_atom()?;
+                let a = self.atom()?;
+                let b = self.atom()?;
+                Ok(Statement::Path(ty, a, b))
+            }
+            t => Err(StatementError::Parse(format!("expected a statement, found {:?}", t))),
+        }
+    }
+
+    fn binder(&mut self, make: fn(String, Ty, Box<Statement>) -> Statement) -> Result<Statement, StatementError> {
+        self.expect(Token::Sym('('))?;
+        let mut names = Vec::new();
+        while let Some(Token::Ident(x)) = self.peek() {
+            names.push(x.clone());
+            self.pos += 1;
+        }
+        if names.is_empty() {
+            return Err(StatementError::Parse("binder without names".to_string()));
+        }
+        self.expect(Token::Sym(':'))?;
+        let ty = self.ty()?;
+        self.expect(Token::Sym(')'))?;
+        self.expect(Token::Sym('.'))?;
+        let body = self.statement()?;
+        Ok(names.into_iter().rev().fold(body, |body, x| make(x, ty.clone(), Box::new(body))))
+    }
+
+    fn ty(&mut self) -> Result<Ty, StatementError> {
+        let dom = self.ty_atom()?;
+        if self.peek() == Some(&Token::Arrow) {
+            self.pos += 1;
+            Ok(Ty::arrow(dom, self.ty()?))
+        } else {
+            Ok(dom)
+        }
+    }
+
+    fn ty_atom(&mut self) -> Result<Ty, StatementError> {
+        match self.next() {
+            Some(Token::Real) => Ok(Ty::Real),
+            Some(Token::Sym('(')) => {
+                let ty = self.ty()?;
+                self.expect(Token::Sym(')'))?;
+                Ok(ty)
+            }
+            t => Err(StatementError::Parse(format!("expected a type, found {:?}", t))),
+        }
+    }
+
+    /// Named infix operators bind loosest, then `+`/`-`, then `*`
+    fn expr(&mut self) -> Result<Expr, StatementError> {
+        let mut lhs = self.sum()?;
+        while let Some(Token::Op(op)) = self.peek().filter(|t| !is_builtin(t)).cloned() {
+            self.pos += 1;
+            lhs = Expr::BinOp(op, Box::new(lhs), Box::new(self.sum()?));
+        }
+        Ok(lhs)
+    }
+
+    fn sum(&mut self) -> Result<Expr, StatementError> {
+        let mut lhs = self.product()?;
+        while let Some(Token::Op(op)) = self.peek().filter(|t| is_op(t, "+") || is_op(t, "-")).cloned() {
+            self.pos += 1;
+            lhs = Expr::BinOp(op, Box::new(lhs), Box::new(self.product()?));
+        }
+        Ok(lhs)
+    }
+
+    fn product(&mut self) -> Result<Expr, StatementError> {
+        let mut lhs = self.unary()?;
+        while self.peek().is_some_and(|t| is_op(t, "*")) {
+            self.pos += 1;
+            lhs = Expr::BinOp("*".to_string(), Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, StatementError> {
+        if self.peek().is_some_and(|t| is_op(t, "-")) {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let mut e = self.atom()?;
+        while matches!(self.peek(), Some(Token::Ident(_) | Token::Num(_) | Token::Sym('('))) {
+            e = Expr::App(Box::new(e), Box::new(self.atom()?));
+        }
+        Ok(e)
+    }
+
+    fn atom(&mut self) -> Result<Expr, StatementError> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::Ident(x)) => Ok(Expr::Var(x)),
+            Some(Token::Sym('(')) => {
+                let e = self.expr()?;
+                self.expect(Token::Sym(')'))?;
+                Ok(e)
+            }
+            t => Err(StatementError::Parse(format!("expected an expression, found {:?}", t))),
+        }
+    }
+}
+
+fn is_op(t: &Token, op: &str) -> bool {
+    matches!(t, Token::Op(o) if o == op)
+}
+
+fn is_builtin(t: &Token) -> bool {
+    BUILTIN_OPS.iter().any(|op| is_op(t, op))
+}
+
+pub fn parse_statement(src: &str) -> Result<Statement, StatementError> {
+    let mut p = Parser { tokens: tokenize(src)?, pos: 0 };
+    let s = p.statement()?;
+    p.finish()?;
+    Ok(s)
+}
+
+pub fn parse_expr(src: &str) -> Result<Expr, StatementError> {
+    let mut p = Parser { tokens: tokenize(src)?, pos: 0 };
+    let e = p.expr()?;
+    p.finish()?;
+    Ok(e)
+}
+
+pub fn parse_ty(src: &str) -> Result<Ty, StatementError> {
+    let mut p = Parser { tokens: tokenize(src)?, pos: 0 };
+    let ty = p.ty()?;
+    p.finish()?;
+    Ok(ty)
+}
+
+// Printing
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ty::Real => write!(f, "ℝ"),
+            Ty::Arrow(a, b) if matches!(**a, Ty::Arrow(..)) => write!(f, "({}) → {}", a, b),
+            Ty::Arrow(a, b) => write!(f, "{} → {}", a, b),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arg = |e: &Expr| if e.is_atom() { e.to_string() } else { format!("({})", e) };
+        match self {
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Var(x) => write!(f, "{}", x),
+            Expr::App(func, a) if matches!(**func, Expr::App(..)) => write!(f, "{} {}", func, arg(a)),
+            Expr::App(func, a) => write!(f, "{} {}", arg(func), arg(a)),
+            Expr::BinOp(op, a, b) if BUILTIN_OPS.contains(&op.as_str()) => {
+                write!(f, "{} {} {}", arg(a), op, arg(b))
+            }
+            Expr::BinOp(op, a, b) => write!(f, "{} `{}` {}", arg(a), op, arg(b)),
+            Expr::Neg(e) => write!(f, "-{}", arg(e)),
+        }
+    }
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (sym, x, ty, mut body) = match self {
+            Statement::Pi(x, ty, body) => ("Π", x, ty, body.as_ref()),
+            Statement::Sigma(x, ty, body) => ("Σ", x, ty, body.as_ref()),
+            Statement::Path(ty, a, b) => {
+                let ty = if matches!(ty, Ty::Arrow(..)) { format!("({})", ty) } else { ty.to_string() };
+                let arg = |e: &Expr| if e.is_atom() { e.to_string() } else { format!("({})", e) };
+                return write!(f, "Path {} {} {}", ty, arg(a), arg(b));
+            }
+        };
+        // Group consecutive binders of the same kind and type
+        let mut names = vec![x.as_str()];
+        loop {
+            match (self, body) {
+                (Statement::Pi(..), Statement::Pi(y, ty2, rest))
+                | (Statement::Sigma(..), Statement::Sigma(y, ty2, rest)) if ty2 == ty => {
+                    names.push(y.as_str());
+                    body = rest;
+                }
+                _ => break,
+            }
+        }
+        write!(f, "{} ({} : {}). {}", sym, names.join(" "), ty, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_print_round_trip() {
+        let src = "Π (x y : ℝ). Path ℝ ((x + y) * x) (sin x `f` -y)";
+        let s = parse_statement(src).unwrap();
+        assert_eq!(s.leading_binders(), vec!["x", "y"]);
+        assert_eq!(parse_statement(&s.to_string()).unwrap(), s);
+    }
+
+    #[test]
+    fn test_check_statement() {
+        let env = Env::prelude();
+        let ok = parse_statement("Σ (x : ℝ). Path ℝ (sin x) 0").unwrap();
+        assert!(env.check_statement(&ok).is_ok());
+
+        let bad = parse_statement("Path (ℝ → ℝ) sin 1").unwrap();
+        assert!(matches!(env.check_statement(&bad), Err(StatementError::Mismatch { .. })));
+    }
+
+    #[test]
+    fn test_ring_equal() {
+        let lhs = parse_expr("(x + y) * (x - y)").unwrap();
+        let rhs = parse_expr("x * x - y * y").unwrap();
+        assert!(ring_equal(&lhs, &rhs));
+        assert!(!ring_equal(&parse_expr("x `f` y").unwrap(), &parse_expr("y `f` x").unwrap()));
+    }
+}
@@ -0,0 +1,436 @@
+//! Named surface syntax for kernel terms
+//!
+//! The kernel works on De Bruijn indices, which nobody wants to read or
+//! write. [`Surface`] is the same term language with names: it prints as
+//! `.sctt` source and [`elaborate`]s to a kernel [`Term`]. Term variables
+//! and interval variables live in separate scopes, as in the kernel, where
+//! `⟨i⟩ t` binds only an interval variable.
+//!
+//! A file is a list of [`Definition`]s checked in order by [`check`]. Earlier
+//! definitions are in scope for later ones with their types but not their
+//! bodies: the kernel has no δ-unfolding, so a definition is opaque once
+//! checked.
+
+use std::fmt;
+
+use crate::sctt_typechecker::{Context, DeBruijnIndex, IntervalPoint, Level, Term, TypeChecker};
+
+/// Points of the interval, by name
+#[derive(Debug, Clone, PartialEq)]
+pub enum Interval {
+    Zero,
+    One,
+    Var(String),
+    Neg(Box<Interval>),
+    Meet(Box<Interval>, Box<Interval>),
+    Join(Box<Interval>, Box<Interval>),
+}
+
+/// One face `r = s ↦ u` of an `hcomp`; `u` is a path lambda
+#[derive(Debug, Clone, PartialEq)]
+pub struct Face {
+    pub lhs: Interval,
+    pub rhs: Interval,
+    pub tube: Surface,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Surface {
+    Var(String),
+    /// `Type`, the lowest universe
+    Universe,
+    /// `Π (x : A). B`; the binder `_` prints as `A → B`
+    Pi(String, Box<Surface>, Box<Surface>),
+    /// `λ (x : A). b`, or `λ x. b` without an annotation
+    Lambda(String, Option<Box<Surface>>, Box<Surface>),
+    App(Box<Surface>, Box<Surface>),
+    /// `Path A a b`
+    Path(Box<Surface>, Box<Surface>, Box<Surface>),
+    /// `⟨i⟩ b`
+    PathLambda(String, Box<Surface>),
+    /// `p @ r`
+    PathApp(Box<Surface>, Interval),
+    /// `transport line r s a`: carry `a` from `line @ r` to `line @ s`
+    Transport { line: Box<Surface>, from: Interval, to: Interval, base: Box<Surface> },
+    /// `hcomp A [r = s ↦ u, ...] a`
+    Hcomp { ty: Box<Surface>, faces: Vec<Face>, base: Box<Surface> },
+}
+
+/// `def name (x : A) ... : T := body`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: String,
+    pub params: Vec<(String, Surface)>,
+    pub ty: Surface,
+    pub body: Surface,
+}
+
+impl Definition {
+    /// The type with the parameters abstracted: `Π (x : A). ... T`
+    pub fn closed_type(&self) -> Surface {
+        self.params.iter().rev().fold(self.ty.clone(), |ty, (x, a)| {
+            Surface::Pi(x.clone(), Box::new(a.clone()), Box::new(ty))
+        })
+    }
+
+    /// The body with the parameters abstracted: `λ (x : A). ... body`
+    pub fn closed_body(&self) -> Surface {
+        self.params.iter().rev().fold(self.body.clone(), |body, (x, a)| {
+            Surface::Lambda(x.clone(), Some(Box::new(a.clone())), Box::new(body))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SurfaceError {
+    Unbound(String),
+    /// A term variable used as an interval point, or the other way round
+    WrongSort { name: String, expected: &'static str },
+    Kernel(String),
+}
+
+impl fmt::Display for SurfaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SurfaceError::Unbound(name) => write!(f, "unbound variable `{}`", name),
+            SurfaceError::WrongSort { name, expected } => write!(f, "`{}` is not {}", name, expected),
+            SurfaceError::Kernel(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SurfaceError {}
+
+/// Names in scope, innermost last
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    terms: Vec<String>,
+    intervals: Vec<String>,
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Scope::default()
+    }
+
+    pub fn bind(&mut self, name: &str) {
+        self.terms.push(name.to_string());
+    }
+
+    fn index(names: &[String], name: &str) -> Option<DeBruijnIndex> {
+        names.iter().rev().position(|n| n == name).map(DeBruijnIndex)
+    }
+}
+
+/// Translate to a kernel term, resolving names against `scope`
+pub fn elaborate(scope: &mut Scope, term: &Surface) -> Result<Term, SurfaceError> {
+    Ok(match term {
+        Surface::Var(x) => match Scope::index(&scope.terms, x) {
+            Some(idx) => Term::Var(idx),
+            None if Scope::index(&scope.intervals, x).is_some() => {
+                return Err(SurfaceError::WrongSort { name: x.clone(), expected: "a term" })
+            }
+            None => return Err(SurfaceError::Unbound(x.clone())),
+        },
+        Surface::Universe => Term::Universe(Level::Zero),
+        Surface::Pi(x, a, b) => {
+            let a = elaborate(scope, a)?;
+            Term::Pi(Box::new(a), Box::new(under(scope, x, b)?))
+        }
+        Surface::Lambda(x, a, body) => {
+            let a = match a {
+                Some(a) => elaborate(scope, a)?,
+                // The kernel checks lambdas against a Π type and ignores the annotation
+                None => Term::Universe(Level::Zero),
+            };
+            Term::Lambda(Box::new(a), Box::new(under(scope, x, body)?))
+        }
+        Surface::App(f, a) => Term::App(Box::new(elaborate(scope, f)?), Box::new(elaborate(scope, a)?)),
+        Surface::Path(a, x, y) => Term::PathType(
+            Box::new(elaborate(scope, a)?),
+            Box::new(elaborate(scope, x)?),
+            Box::new(elaborate(scope, y)?),
+        ),
+        Surface::PathLambda(i, body) => Term::PathLambda(Box::new(under_interval(scope, i, body)?)),
+        Surface::PathApp(p, r) => Term::PathApp(Box::new(elaborate(scope, p)?), point(scope, r)?),
+        Surface::Transport { line, from, to, base } => Term::Transport(
+            Box::new(elaborate(scope, line)?),
+            point(scope, from)?,
+            point(scope, to)?,
+            Box::new(elaborate(scope, base)?),
+        ),
+        Surface::Hcomp { ty, faces, base } => Term::Hcomp(
+            Box::new(elaborate(scope, ty)?),
+            faces
+                .iter()
+                .map(|face| Ok((point(scope, &face.lhs)?, point(scope, &face.rhs)?, Box::new(elaborate(scope, &face.tube)?))))
+                .collect::<Result<_, SurfaceError>>()?,
+            Box::new(elaborate(scope, base)?),
+        ),
+    })
+}
+
+fn under(scope: &mut Scope, x: &str, body: &Surface) -> Result<Term, SurfaceError> {
+    scope.terms.push(x.to_string());
+    let result = elaborate(scope, body);
+    scope.terms.pop();
+    result
+}
+
+fn under_interval(scope: &mut Scope, i: &str, body: &Surface) -> Result<Term, SurfaceError> {
+    scope.intervals.push(i.to_string());
+    let result = elaborate(scope, body);
+    scope.intervals.pop();
+    result
+}
+
+fn point(scope: &Scope, r: &Interval) -> Result<IntervalPoint, SurfaceError> {
+    Ok(match r {
+        Interval::Zero => IntervalPoint::Zero,
+        Interval::One => IntervalPoint::One,
+        Interval::Var(i) => match Scope::index(&scope.intervals, i) {
+            Some(idx) => IntervalPoint::Var(idx),
+            None if Scope::index(&scope.terms, i).is_some() => {
+                return Err(SurfaceError::WrongSort { name: i.clone(), expected: "an interval variable" })
+            }
+            None => return Err(SurfaceError::Unbound(i.clone())),
+        },
+        Interval::Neg(r) => IntervalPoint::Neg(Box::new(point(scope, r)?)),
+        Interval::Meet(r, s) => IntervalPoint::Meet(Box::new(point(scope, r)?), Box::new(point(scope, s)?)),
+        Interval::Join(r, s) => IntervalPoint::Join(Box::new(point(scope, r)?), Box::new(point(scope, s)?)),
+    })
+}
+
+/// Check definitions in order; each result says whether that one checked
+///
+/// A definition whose type elaborates stays in scope for the ones after it
+/// even if its body fails, so one mistake does not cascade.
+pub fn check(definitions: &[Definition]) -> Vec<Result<(), SurfaceError>> {
+    let tc = TypeChecker::new();
+    let mut ctx = Context::new();
+    let mut scope = Scope::new();
+    let mut results = Vec::with_capacity(definitions.len());
+
+    for def in definitions {
+        let ty = match elaborate(&mut scope, &def.closed_type())
+            .and_then(|ty| tc.eval(&ctx.env, &ty).map_err(|e| SurfaceError::Kernel(e.to_string())))
+        {
+            Ok(ty) => ty,
+            Err(e) => {
+                results.push(Err(e));
+                continue;
+            }
+        };
+        let checked = elaborate(&mut scope, &def.closed_body())
+            .and_then(|body| tc.check(&ctx, &body, &ty).map_err(|e| SurfaceError::Kernel(e.to_string())));
+        results.push(checked);
+        ctx = ctx.extend(ty);
+        scope.bind(&def.name);
+    }
+    results
+}
+
+const BINDER: u8 = 0;
+const AT: u8 = 1;
+const APP: u8 = 2;
+const ATOM: u8 = 3;
+
+impl Surface {
+    fn precedence(&self) -> u8 {
+        match self {
+            Surface::Var(_) | Surface::Universe => ATOM,
+            Surface::Pi(..) | Surface::Lambda(..) | Surface::PathLambda(..) => BINDER,
+            Surface::PathApp(..) => AT,
+            Surface::App(..) | Surface::Path(..) | Surface::Transport { .. } | Surface::Hcomp { .. } => APP,
+        }
+    }
+
+    fn fmt_at(&self, f: &mut fmt::Formatter, min: u8) -> fmt::Result {
+        if self.precedence() < min {
+            write!(f, "(")?;
+            self.fmt_at(f, BINDER)?;
+            return write!(f, ")");
+        }
+        match self {
+            Surface::Var(x) => write!(f, "{}", x),
+            Surface::Universe => write!(f, "Type"),
+            Surface::Pi(x, a, b) if x == "_" => {
+                a.fmt_at(f, AT)?;
+                write!(f, " → ")?;
+                b.fmt_at(f, BINDER)
+            }
+            Surface::Pi(x, a, b) => {
+                write!(f, "Π ({} : ", x)?;
+                a.fmt_at(f, BINDER)?;
+                write!(f, "). ")?;
+                b.fmt_at(f, BINDER)
+            }
+            Surface::Lambda(x, Some(a), body) => {
+                write!(f, "λ ({} : ", x)?;
+                a.fmt_at(f, BINDER)?;
+                write!(f, "). ")?;
+                body.fmt_at(f, BINDER)
+            }
+            Surface::Lambda(x, None, body) => {
+                write!(f, "λ {}. ", x)?;
+                body.fmt_at(f, BINDER)
+            }
+            Surface::App(g, a) => {
+                g.fmt_at(f, APP)?;
+                write!(f, " ")?;
+                a.fmt_at(f, ATOM)
+            }
+            Surface::Path(a, x, y) => {
+                write!(f, "Path ")?;
+                for (i, part) in [a, x, y].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    part.fmt_at(f, ATOM)?;
+                }
+                Ok(())
+            }
+            Surface::PathLambda(i, body) => {
+                write!(f, "⟨{}⟩ ", i)?;
+                body.fmt_at(f, BINDER)
+            }
+            Surface::PathApp(p, r) => {
+                p.fmt_at(f, APP)?;
+                write!(f, " @ ")?;
+                r.fmt_at(f, true)
+            }
+            Surface::Transport { line, from, to, base } => {
+                write!(f, "transport ")?;
+                line.fmt_at(f, ATOM)?;
+                write!(f, " ")?;
+                from.fmt_at(f, true)?;
+                write!(f, " ")?;
+                to.fmt_at(f, true)?;
+                write!(f, " ")?;
+                base.fmt_at(f, ATOM)
+            }
+            Surface::Hcomp { ty, faces, base } => {
+                write!(f, "hcomp ")?;
+                ty.fmt_at(f, ATOM)?;
+                write!(f, " [")?;
+                for (i, face) in faces.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} = {} ↦ ", face.lhs, face.rhs)?;
+                    face.tube.fmt_at(f, BINDER)?;
+                }
+                write!(f, "] ")?;
+                base.fmt_at(f, ATOM)
+            }
+        }
+    }
+}
+
+impl Interval {
+    fn fmt_at(&self, f: &mut fmt::Formatter, atomic: bool) -> fmt::Result {
+        match self {
+            Interval::Zero => write!(f, "0"),
+            Interval::One => write!(f, "1"),
+            Interval::Var(i) => write!(f, "{}", i),
+            Interval::Neg(r) => {
+                write!(f, "¬")?;
+                r.fmt_at(f, true)
+            }
+            Interval::Meet(r, s) | Interval::Join(r, s) => {
+                let op = if matches!(self, Interval::Meet(..)) { "∧" } else { "∨" };
+                if atomic {
+                    write!(f, "(")?;
+                }
+                r.fmt_at(f, true)?;
+                write!(f, " {} ", op)?;
+                s.fmt_at(f, true)?;
+                if atomic {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_at(f, false)
+    }
+}
+
+impl fmt::Display for Surface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_at(f, BINDER)
+    }
+}
+
+impl fmt::Display for Definition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "def {}", self.name)?;
+        for (x, a) in &self.params {
+            write!(f, " ({} : {})", x, a)?;
+        }
+        write!(f, " : {} :=\n  {}", self.ty, self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(x: &str) -> Box<Surface> {
+        Box::new(Surface::Var(x.to_string()))
+    }
+
+    /// sym (A : Type) (a b : A) (p : Path A a b) : Path A b a := ⟨i⟩ p @ ¬i
+    fn sym() -> Definition {
+        Definition {
+            name: "sym".to_string(),
+            params: vec![
+                ("A".to_string(), Surface::Universe),
+                ("a".to_string(), Surface::Var("A".to_string())),
+                ("b".to_string(), Surface::Var("A".to_string())),
+                ("p".to_string(), Surface::Path(var("A"), var("a"), var("b"))),
+            ],
+            ty: Surface::Path(var("A"), var("b"), var("a")),
+            body: Surface::PathLambda(
+                "i".to_string(),
+                Box::new(Surface::PathApp(var("p"), Interval::Neg(Box::new(Interval::Var("i".to_string()))))),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_prints_sctt_source() {
+        assert_eq!(
+            sym().to_string(),
+            "def sym (A : Type) (a : A) (b : A) (p : Path A a b) : Path A b a :=\n  ⟨i⟩ p @ ¬i"
+        );
+        let arrow = Surface::Pi("_".to_string(), var("A"), Box::new(Surface::Pi("_".to_string(), var("A"), var("A"))));
+        assert_eq!(arrow.to_string(), "A → A → A");
+    }
+
+    #[test]
+    fn test_symmetry_checks_and_a_wrong_endpoint_does_not() {
+        assert_eq!(check(&[sym()]), vec![Ok(())]);
+
+        let mut wrong = sym();
+        wrong.ty = Surface::Path(var("A"), var("a"), var("b"));
+        assert!(matches!(check(&[wrong])[0], Err(SurfaceError::Kernel(_))));
+    }
+
+    #[test]
+    fn test_scopes_are_sorted() {
+        let mut scope = Scope::new();
+        scope.bind("x");
+        let bad = Surface::PathLambda("i".to_string(), Box::new(Surface::PathApp(var("p"), Interval::Var("x".to_string()))));
+        scope.bind("p");
+        assert_eq!(
+            elaborate(&mut scope, &bad),
+            Err(SurfaceError::WrongSort { name: "x".to_string(), expected: "an interval variable" })
+        );
+        assert_eq!(elaborate(&mut scope, &Surface::Var("i".to_string())), Err(SurfaceError::Unbound("i".to_string())));
+    }
+}
@@ -0,0 +1,267 @@
+//! Statement templates for common theorem shapes
+//!
+//! Each template is plain data: parameter slots with expected types and a
+//! statement source with `{slot}` placeholders. The wizard in the web UI fills
+//! the slots, validating each against the current environment, and generates
+//! the statement plus a starting tactic skeleton.
+
+use std::collections::HashMap;
+
+use crate::proof_assistant::Tactic;
+use crate::statement::{self, Env, Statement, StatementError, Ty, BUILTIN_OPS};
+
+/// A parameterized theorem shape
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub slots: Vec<Slot>,
+    pub source: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct Slot {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Expected type, in statement syntax
+    pub ty: &'static str,
+    /// Variables the template binds around this slot
+    pub scope: &'static [&'static str],
+    /// Used in operator position: `+` stays as is, a name `f` becomes `` `f` ``
+    pub infix: bool,
+}
+
+/// A generated statement with its tactic skeleton
+#[derive(Debug, Clone)]
+pub struct Instantiation {
+    pub source: String,
+    pub statement: Statement,
+    pub skeleton: Vec<Tactic>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    MissingSlot(&'static str),
+    InvalidSlot { slot: &'static str, error: StatementError },
+    InvalidStatement(StatementError),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TemplateError::MissingSlot(slot) => write!(f, "`{}` is not filled in", slot),
+            TemplateError::InvalidSlot { slot, error } => write!(f, "`{}`: {}", slot, error),
+            TemplateError::InvalidStatement(e) => write!(f, "generated statement is ill-typed: {}", e),
+        }
+    }
+}
+
+impl Slot {
+    fn expected(&self) -> Result<Ty, StatementError> {
+        let ty = statement::parse_ty(self.ty);
+        debug_assert!(ty.is_ok(), "template slot type `{}` does not parse", self.ty);
+        ty
+    }
+
+    /// Check one slot's input against its expected type in `env`
+    pub fn validate(&self, env: &Env, input: &str) -> Result<(), StatementError> {
+        let input = input.trim();
+        let expected = self.expected()?;
+        if self.infix && BUILTIN_OPS.contains(&input) {
+            return if expected == Ty::binary_op() {
+                Ok(())
+            } else {
+                Err(StatementError::Mismatch { expected, found: Ty::binary_op() })
+            };
+        }
+
+        let mut env = env.clone();
+        for x in self.scope {
+            env.bind(x, Ty::Real);
+        }
+        env.check(&statement::parse_expr(input)?, &expected)
+    }
+
+    fn render(&self, input: &str) -> String {
+        let input = input.trim();
+        if self.infix && !BUILTIN_OPS.contains(&input) {
+            format!("`{}`", input)
+        } else {
+            input.to_string()
+        }
+    }
+}
+
+impl Template {
+    /// Fill the slots, validate them, and generate the statement
+    pub fn instantiate(&self, env: &Env, values: &HashMap<&str, &str>) -> Result<Instantiation, TemplateError> {
+        let mut source = self.source.to_string();
+        for slot in &self.slots {
+            let input = values.get(slot.name)
+                .filter(|v| !v.trim().is_empty())
+                .ok_or(TemplateError::MissingSlot(slot.name))?;
+            slot.validate(env, input)
+                .map_err(|error| TemplateError::InvalidSlot { slot: slot.name, error })?;
+            source = source.replace(&format!("{{{}}}", slot.name), &slot.render(input));
+        }
+
+        let statement = statement::parse_statement(&source).map_err(TemplateError::InvalidStatement)?;
+        env.check_statement(&statement).map_err(TemplateError::InvalidStatement)?;
+        let skeleton = statement.leading_binders()
+            .into_iter()
+            .map(|x| Tactic::Intro(x.to_string()))
+            .collect();
+
+        Ok(Instantiation { source: statement.to_string(), statement, skeleton })
+    }
+}
+
+impl Instantiation {
+    /// Statement and skeleton as editor text
+    pub fn script(&self) -> String {
+        let mut script = format!("theorem : {}\n", self.source);
+        for tactic in &self.skeleton {
+            if let Tactic::Intro(x) = tactic {
+                script.push_str(&format!("  intro {}.\n", x));
+            }
+        }
+        script
+    }
+}
+
+/// The built-in template library
+pub fn library() -> Vec<Template> {
+    const REAL: &str = "ℝ";
+    const FUNCTION: &str = "ℝ → ℝ";
+    const BINARY_OP: &str = "ℝ → ℝ → ℝ";
+
+    vec![
+        Template {
+            id: "equality",
+            name: "Equality of two expressions",
+            description: "Two real expressions in x agree for every x",
+            slots: vec![
+                Slot { name: "lhs", description: "Left-hand side", ty: REAL, scope: &["x"], infix: false },
+                Slot { name: "rhs", description: "Right-hand side", ty: REAL, scope: &["x"], infix: false },
+            ],
+            source: "Π (x : ℝ). Path ℝ ({lhs}) ({rhs})",
+        },
+        Template {
+            id: "commutativity",
+            name: "Commutativity",
+            description: "A binary operation on ℝ does not depend on argument order",
+            slots: vec![
+                Slot { name: "op", description: "The operation", ty: BINARY_OP, scope: &[], infix: true },
+            ],
+            source: "Π (x y : ℝ). Path ℝ (x {op} y) (y {op} x)",
+        },
+        Template {
+            id: "associativity",
+            name: "Associativity",
+            description: "A binary operation on ℝ does not depend on grouping",
+            slots: vec![
+                Slot { name: "op", description: "The operation", ty: BINARY_OP, scope: &[], infix: true },
+            ],
+            source: "Π (x y z : ℝ). Path ℝ ((x {op} y) {op} z) (x {op} (y {op} z))",
+        },
+        Template {
+            id: "path",
+            name: "Path between endpoints",
+            description: "A path in ℝ from one given point to another",
+            slots: vec![
+                Slot { name: "start", description: "Start point", ty: REAL, scope: &[], infix: false },
+                Slot { name: "end", description: "End point", ty: REAL, scope: &[], infix: false },
+            ],
+            source: "Path ℝ ({start}) ({end})",
+        },
+        Template {
+            id: "existence",
+            name: "Existence",
+            description: "Some x is sent to the given value (a Σ-type witness)",
+            slots: vec![
+                Slot { name: "f", description: "The function", ty: FUNCTION, scope: &[], infix: false },
+                Slot { name: "value", description: "The value to reach", ty: REAL, scope: &[], infix: false },
+            ],
+            source: "Σ (x : ℝ). Path ℝ (({f}) x) ({value})",
+        },
+        Template {
+            id: "funext",
+            name: "Function extensionality",
+            description: "Two functions are equal as points of ℝ → ℝ",
+            slots: vec![
+                Slot { name: "f", description: "First function", ty: FUNCTION, scope: &[], infix: false },
+                Slot { name: "g", description: "Second function", ty: FUNCTION, scope: &[], infix: false },
+            ],
+            source: "Path (ℝ → ℝ) ({f}) ({g})",
+        },
+    ]
+}
+
+/// Look up a template by id
+pub fn template(id: &str) -> Option<Template> {
+    library().into_iter().find(|t| t.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProofAssistant;
+
+    fn fill(id: &str, values: &[(&str, &str)]) -> Result<Instantiation, TemplateError> {
+        template(id).unwrap().instantiate(&Env::prelude(), &values.iter().cloned().collect())
+    }
+
+    #[test]
+    fn test_every_template_instantiates() {
+        let cases: [(&str, &[(&str, &str)]); 6] = [
+            ("equality", &[("lhs", "x * x"), ("rhs", "x * x + 0")]),
+            ("commutativity", &[("op", "*")]),
+            ("associativity", &[("op", "+")]),
+            ("path", &[("start", "0"), ("end", "sin 1")]),
+            ("existence", &[("f", "exp"), ("value", "1")]),
+            ("funext", &[("f", "sin"), ("g", "cos")]),
+        ];
+        assert_eq!(cases.len(), library().len());
+        for (id, values) in cases {
+            let inst = fill(id, values).unwrap_or_else(|e| panic!("{}: {}", id, e));
+            assert_eq!(statement::parse_statement(&inst.source).unwrap(), inst.statement);
+        }
+    }
+
+    #[test]
+    fn test_slot_validation() {
+        assert_eq!(fill("path", &[("start", "0")]).unwrap_err(), TemplateError::MissingSlot("end"));
+        assert!(matches!(
+            fill("existence", &[("f", "1"), ("value", "0")]),
+            Err(TemplateError::InvalidSlot { slot: "f", error: StatementError::Mismatch { .. } })
+        ));
+        assert!(matches!(
+            fill("equality", &[("lhs", "y"), ("rhs", "x")]),
+            Err(TemplateError::InvalidSlot { slot: "lhs", error: StatementError::Unbound(_) })
+        ));
+
+        let mut env = Env::prelude();
+        env.bind("max", Ty::binary_op());
+        let inst = template("commutativity").unwrap()
+            .instantiate(&env, &[("op", "max")].into_iter().collect())
+            .unwrap();
+        assert_eq!(inst.source, "Π (x y : ℝ). Path ℝ (x `max` y) (y `max` x)");
+    }
+
+    #[test]
+    fn test_commutativity_of_addition_closes_by_ring() {
+        let inst = fill("commutativity", &[("op", "+")]).unwrap();
+        assert_eq!(inst.skeleton, vec![Tactic::Intro("x".to_string()), Tactic::Intro("y".to_string())]);
+
+        let mut assistant = ProofAssistant::new();
+        assistant.start_statement("add_comm", &inst.source).unwrap();
+        for tactic in inst.skeleton {
+            let goal = assistant.goal_ids()[0];
+            assistant.apply_tactic(tactic, goal).unwrap();
+        }
+        let goal = assistant.goal_ids()[0];
+        assistant.apply_tactic(Tactic::Ring, goal).unwrap();
+        assert!(assistant.goal_ids().is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+//! Scripted proof transactions
+//!
+//! Automation clients (proof search, lemma maintenance bots) try a batch of
+//! tactics against a shared session and publish it only if every step
+//! succeeds. A transaction works on a fork of the session's proof and locks
+//! the session: interactive proof actions are rejected with
+//! [`ServerMessage::Busy`](crate::protocol::ServerMessage::Busy) until it
+//! commits, rolls back, or times out. Committing swaps the fork in and
+//! records the batch as one history entry, which undo takes back as a whole.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::driver::{apply_step, Outgoing, ProofRoom};
+use crate::proof_assistant::{ProofAssistant, Tactic};
+use crate::{ProofState, ProofStep};
+
+/// How long a transaction may sit without a request before it rolls back
+pub const DEFAULT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    /// Another transaction holds the session
+    Busy { id: String, owner: String },
+    /// No open transaction has this id: never begun, finished, or timed out
+    Unknown(String),
+    /// The session has no proof to fork
+    NoProof(String),
+    /// A tactic failed; the transaction stays open so the client can roll back
+    Tactic(String),
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionError::Busy { id, owner } => write!(f, "transaction {} by {} holds the session", id, owner),
+            TransactionError::Unknown(id) => write!(f, "no open transaction {}", id),
+            TransactionError::NoProof(message) => write!(f, "cannot begin a transaction: {}", message),
+            TransactionError::Tactic(message) => write!(f, "tactic failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+/// A batch of speculative steps on a fork of the session's proof
+pub struct Transaction {
+    pub id: String,
+    pub owner: String,
+    fork: ProofAssistant,
+    steps: Vec<ProofStep>,
+    /// Session assistant history length when the transaction began
+    mark: usize,
+    /// Goals open when the transaction began
+    open: Vec<usize>,
+    timeout: Duration,
+    deadline: Instant,
+}
+
+impl Transaction {
+    /// Steps applied so far, in order
+    pub fn steps(&self) -> &[ProofStep] {
+        &self.steps
+    }
+
+    pub(crate) fn busy_message(&self) -> String {
+        format!("{} is running transaction {}; try again when it finishes", self.owner, self.id)
+    }
+}
+
+impl ProofRoom {
+    /// The open transaction, if any
+    pub fn transaction(&self) -> Option<&Transaction> {
+        self.transaction.as_ref()
+    }
+
+    /// Fork the session's proof and lock out interactive writers
+    pub fn begin_transaction(&mut self, owner: &str, timeout: Duration, now: Instant) -> Result<String, TransactionError> {
+        self.expire_transaction(now);
+        if let Some(txn) = &self.transaction {
+            return Err(TransactionError::Busy { id: txn.id.clone(), owner: txn.owner.clone() });
+        }
+        let assistant = &self.session.assistant;
+        let fork = assistant.fork().map_err(TransactionError::NoProof)?;
+        self.next_transaction += 1;
+        let id = format!("txn-{}", self.next_transaction);
+        self.transaction = Some(Transaction {
+            id: id.clone(),
+            owner: owner.to_string(),
+            fork,
+            steps: Vec::new(),
+            mark: assistant.history().len(),
+            open: assistant.goal_ids(),
+            timeout,
+            deadline: now + timeout,
+        });
+        self.session.log(format!("{} began transaction {}", owner, id));
+        Ok(id)
+    }
+
+    /// Apply a tactic to the fork, returning the fork's goals and the steps
+    /// so far; nothing is visible to the session until commit
+    pub fn apply_in_transaction(
+        &mut self,
+        id: &str,
+        goal_id: usize,
+        tactic: &str,
+        now: Instant,
+    ) -> Result<ProofState, TransactionError> {
+        let txn = self.open_transaction(id, now)?;
+        let tactic = Tactic::parse(tactic).map_err(TransactionError::Tactic)?;
+        let step = apply_step(&mut txn.fork, tactic, goal_id).map_err(TransactionError::Tactic)?;
+        txn.steps.push(step);
+        Ok(ProofState {
+            goals: txn.fork.goal_ids().into_iter().filter_map(|id| txn.fork.goal(id).cloned()).collect(),
+            history: txn.steps.clone(),
+        })
+    }
+
+    /// Publish every step at once, as a single undoable history entry
+    pub fn commit(&mut self, id: &str, now: Instant) -> Result<Vec<Outgoing>, TransactionError> {
+        self.open_transaction(id, now)?;
+        let Some(txn) = self.transaction.take() else {
+            return Err(TransactionError::Unknown(id.to_string()));
+        };
+        let Some(first) = txn.steps.first() else {
+            self.session.log(format!("{} committed empty transaction {}", txn.owner, txn.id));
+            return Ok(Vec::new());
+        };
+
+        let fork = &txn.fork;
+        let group = ProofStep {
+            tactic: txn.steps.iter().map(|s| s.tactic.as_str()).collect::<Vec<_>>().join("; "),
+            goal_before: first.goal_before.clone(),
+            goals_after: fork.goal_ids().into_iter()
+                .filter(|id| !txn.open.contains(id))
+                .filter_map(|id| fork.goal(id).cloned())
+                .collect(),
+            steps: txn.steps.clone(),
+        };
+        self.session.log(format!("{} committed transaction {} ({} steps)", txn.owner, txn.id, txn.steps.len()));
+        for step in &txn.steps {
+            self.session.log(format!("  applied `{}` to goal {}", step.tactic, step.goal_before.id));
+        }
+
+        self.session.assistant = txn.fork;
+        self.history.push(group);
+        self.marks.push(txn.mark);
+        self.clear_redo();
+        Ok(self.update())
+    }
+
+    /// Discard every step; the session is exactly as it was at begin
+    pub fn rollback(&mut self, id: &str) -> Result<(), TransactionError> {
+        match self.transaction.take() {
+            Some(txn) if txn.id == id => {
+                self.session.log(format!("{} rolled back transaction {}", txn.owner, txn.id));
+                Ok(())
+            }
+            other => {
+                self.transaction = other;
+                Err(TransactionError::Unknown(id.to_string()))
+            }
+        }
+    }
+
+    /// Roll back a transaction whose client has gone quiet
+    pub(crate) fn expire_transaction(&mut self, now: Instant) {
+        if self.transaction.as_ref().is_some_and(|txn| now >= txn.deadline) {
+            if let Some(txn) = self.transaction.take() {
+                self.session.log(format!("transaction {} by {} timed out and was rolled back", txn.id, txn.owner));
+            }
+        }
+    }
+
+    /// Look up a live transaction; every request pushes its deadline back
+    fn open_transaction(&mut self, id: &str, now: Instant) -> Result<&mut Transaction, TransactionError> {
+        self.expire_transaction(now);
+        match self.transaction.as_mut() {
+            Some(txn) if txn.id == id => {
+                txn.deadline = now + txn.timeout;
+                Ok(txn)
+            }
+            _ => Err(TransactionError::Unknown(id.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::{Registry, SessionGuard, SessionResources};
+    use crate::sctt_typechecker::Level;
+    use crate::Term;
+
+    fn room(now: Instant) -> ProofRoom {
+        let registry = Registry::new();
+        let mut session = SessionGuard::open(&registry, &SessionResources::default(), "s1");
+        let universe = || Box::new(Term::Universe(Level::Zero));
+        session.assistant.start_proof("const", Term::Pi(universe(), Box::new(Term::Pi(universe(), universe())))).unwrap();
+        ProofRoom::new(session, Duration::from_secs(60), now)
+    }
+
+    #[test]
+    fn test_one_transaction_at_a_time() {
+        let now = Instant::now();
+        let mut room = room(now);
+        let id = room.begin_transaction("bot", DEFAULT_TRANSACTION_TIMEOUT, now).unwrap();
+        assert_eq!(
+            room.begin_transaction("other", DEFAULT_TRANSACTION_TIMEOUT, now),
+            Err(TransactionError::Busy { id: id.clone(), owner: "bot".to_string() })
+        );
+        assert_eq!(room.rollback("txn-99"), Err(TransactionError::Unknown("txn-99".to_string())));
+        room.rollback(&id).unwrap();
+        assert!(room.begin_transaction("other", DEFAULT_TRANSACTION_TIMEOUT, now).is_ok());
+    }
+
+    #[test]
+    fn test_quiet_transaction_rolls_back() {
+        let start = Instant::now();
+        let timeout = Duration::from_secs(10);
+        let mut room = room(start);
+        let id = room.begin_transaction("bot", timeout, start).unwrap();
+        // Each request pushes the deadline back
+        room.apply_in_transaction(&id, 0, "intro A", start + timeout / 2).unwrap();
+        room.tick(start + timeout);
+        assert!(room.transaction().is_some());
+
+        room.tick(start + timeout * 2);
+        assert!(room.transaction().is_none());
+        assert_eq!(room.session.assistant.history().len(), 0);
+        assert!(matches!(room.commit(&id, start + timeout * 2), Err(TransactionError::Unknown(_))));
+    }
+}
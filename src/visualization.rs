@@ -11,6 +11,7 @@ use plotters_canvas::CanvasBackend;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlCanvasElement;
 use serde::{Deserialize, Serialize};
+use sctt_cubical::Homotopy;
 use std::collections::HashMap;
 
 use crate::proof_assistant::{Goal, ProofStep};
@@ -193,6 +194,25 @@ pub struct Point {
     pub coords: Vec<f64>,
 }
 
+impl HomotopyViewer {
+    /// Add the paths `H(s, -)` at `frames + 1` evenly spaced `s`, drawn as
+    /// graphs `t ↦ (t, H(s, t))`, so stepping through them animates the
+    /// deformation
+    pub fn add_homotopy(&mut self, homotopy: &Homotopy, frames: usize) {
+        let frames = frames.max(1);
+        for k in 0..=frames {
+            let path = homotopy.at(k as f64 / frames as f64);
+            let (start, end) = path.endpoints();
+            self.paths.push(HomotopyPath {
+                start: Point { coords: vec![0.0, start] },
+                end: Point { coords: vec![1.0, end] },
+                interpolation: Box::new(move |t| Point { coords: vec![t, path.evaluate(t)] }),
+                dimension: 2,
+            });
+        }
+    }
+}
+
 impl Visualizer {
     pub fn new(canvas: HtmlCanvasElement) -> Self {
         Visualizer {
@@ -349,6 +369,13 @@ impl Visualizer {
         Ok(())
     }
     
+    /// Replace the paths in the homotopy diagram with slices of `homotopy`
+    pub fn show_homotopy(&mut self, homotopy: &Homotopy, frames: usize) {
+        self.homotopy_viewer.paths.clear();
+        self.homotopy_viewer.dimension = 2;
+        self.homotopy_viewer.add_homotopy(homotopy, frames);
+    }
+
     /// Render homotopy diagram
     pub fn render_homotopy(&self) -> Result<(), JsValue> {
         let backend = CanvasBackend::with_canvas_object(self.canvas.clone())
@@ -519,17 +546,21 @@ impl PerformanceProfiler {
     }
     
     pub fn render_timeline(&self, canvas_id: &str) -> Result<(), JsValue> {
-        let document = web_sys::window().unwrap().document().unwrap();
-        let canvas = document.get_element_by_id(canvas_id).unwrap();
-        let canvas: HtmlCanvasElement = canvas.dyn_into()?;
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .ok_or_else(|| JsValue::from_str("no document to render the timeline into"))?;
+        let canvas = document.get_element_by_id(canvas_id)
+            .ok_or_else(|| JsValue::from_str(&format!("no element with id `{}`", canvas_id)))?;
+        let canvas: HtmlCanvasElement = canvas.dyn_into()
+            .map_err(|_| JsValue::from_str(&format!("element `{}` is not a canvas", canvas_id)))?;
         
         let backend = CanvasBackend::with_canvas_object(canvas)?;
         let root = backend.into_drawing_area();
         root.fill(&WHITE)?;
         
-        if !self.samples.is_empty() {
-            let min_time = self.samples.first().unwrap().timestamp;
-            let max_time = self.samples.last().unwrap().timestamp;
+        if let (Some(first), Some(last)) = (self.samples.first(), self.samples.last()) {
+            let min_time = first.timestamp;
+            let max_time = last.timestamp;
             let max_duration = self.samples.iter()
                 .map(|s| s.duration)
                 .fold(0.0, f64::max);
@@ -14,6 +14,12 @@ use gloo_timers::callback::Timeout;
 use serde::{Deserialize, Serialize};
 
 use crate::{ScttSystem, Session, User, Document, Operation};
+use crate::api::{HealthResponse, SizeBreakdown};
+use sctt_core::compat::VersionWatch;
+use crate::certificate;
+use crate::proof_assistant::{GoalReplay, GoalSnapshot};
+use crate::statement::Env;
+use crate::templates;
 
 /// Main application component
 #[function_component(App)]
@@ -21,6 +27,7 @@ pub fn app() -> Html {
     html! {
         <BrowserRouter>
             <div class="sctt-app">
+                <VersionBanner />
                 <Header />
                 <Switch<Route> render={switch} />
                 <Footer />
@@ -43,6 +50,8 @@ pub enum Route {
     Collaborate { id: String },
     #[at("/docs")]
     Documentation,
+    #[at("/goal")]
+    GoalView,
 }
 
 fn switch(routes: Route) -> Html {
@@ -53,6 +62,48 @@ fn switch(routes: Route) -> Html {
         Route::Visualize => html! { <Visualizer /> },
         Route::Collaborate { id } => html! { <CollaborativeEditor session_id={id} /> },
         Route::Documentation => html! { <Documentation /> },
+        Route::GoalView => html! { <GoalViewer /> },
+    }
+}
+
+/// Offers a reload once the server runs another version than this tab;
+/// the server is asked whenever the tab regains focus
+#[function_component(VersionBanner)]
+fn version_banner() -> Html {
+    let watch = use_mut_ref(|| VersionWatch::new(env!("CARGO_PKG_VERSION")));
+    let available = use_state_eq(|| None::<String>);
+    {
+        let available = available.clone();
+        use_effect_with((), move |_| {
+            let listener = web_sys::window().map(|window| {
+                gloo::events::EventListener::new(&window, "focus", move |_| {
+                    let watch = watch.clone();
+                    let available = available.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let Ok(response) = gloo::net::http::Request::get("/api/health").send().await else { return };
+                        let Ok(health) = response.json::<HealthResponse>().await else { return };
+                        watch.borrow_mut().observe(&health.version);
+                        available.set(watch.borrow().available().map(str::to_string));
+                    });
+                })
+            });
+            move || drop(listener)
+        });
+    }
+    let reload = Callback::from(|_: MouseEvent| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.location().reload();
+        }
+    });
+
+    match &*available {
+        Some(version) => html! {
+            <div class="version-banner">
+                {format!("A new version ({}) is available.", version)}
+                <button class="btn" onclick={reload}>{"Reload"}</button>
+            </div>
+        },
+        None => html! {},
     }
 }
 
@@ -177,13 +228,14 @@ pub fn playground() -> Html {
     let output = use_state(|| String::new());
     let system = use_state(ScttSystem::new);
     let tab = use_state(|| "editor");
+    let size = use_state(|| None::<SizeBreakdown>);
     
     let on_code_change = {
         let code = code.clone();
         Callback::from(move |e: Event| {
-            let target = e.target().unwrap();
-            let textarea = target.dyn_ref::<HtmlTextAreaElement>().unwrap();
-            code.set(textarea.value());
+            if let Some(textarea) = e.target_dyn_into::<HtmlTextAreaElement>() {
+                code.set(textarea.value());
+            }
         })
     };
     
@@ -203,12 +255,36 @@ pub fn playground() -> Html {
     let on_compile = {
         let code = code.clone();
         let output = output.clone();
+        let size = size.clone();
         let mut system = system.clone();
         
         Callback::from(move |_| {
             match system.compile_to_wasm(&code) {
-                Ok(wasm) => output.set(format!("✓ Compiled: {} bytes", wasm.len())),
-                Err(e) => output.set(format!("✗ Compilation error: {:?}", e)),
+                Ok(wasm) => {
+                    let breakdown = certificate::size_report(&wasm).ok();
+                    match &breakdown {
+                        Some(b) => output.set(format!("✓ Compiled: {} bytes, {} of them proof certificate", b.total, b.proof)),
+                        None => output.set(format!("✓ Compiled: {} bytes", wasm.len())),
+                    }
+                    size.set(breakdown);
+                }
+                Err(e) => {
+                    output.set(format!("✗ Compilation error: {:?}", e));
+                    size.set(None);
+                }
+            }
+        })
+    };
+    
+    let on_prove = {
+        let code = code.clone();
+        let output = output.clone();
+        let mut system = system.clone();
+        
+        Callback::from(move |_| {
+            match system.run_proof_script(&code) {
+                Ok(state) => output.set(format!("✓ Script checked\n{}", state)),
+                Err(e) => output.set(format!("✗ Proof error: {:?}", e)),
             }
         })
     };
@@ -220,6 +296,7 @@ pub fn playground() -> Html {
                     <div class="toolbar">
                         <button onclick={on_run} class="btn">{"Type Check"}</button>
                         <button onclick={on_compile} class="btn">{"Compile"}</button>
+                        <button onclick={on_prove} class="btn">{"Check Proof"}</button>
                         <button class="btn">{"Share"}</button>
                         <select class="example-selector">
                             <option>{"Identity function"}</option>
@@ -263,9 +340,15 @@ pub fn playground() -> Html {
                             {match &**tab {
                                 "output" => html! { <pre>{&*output}</pre> },
                                 "proof" => html! { 
-                                    <ProofStateView system={(*system).clone()} />
+                                    <>
+                                        <ProofStateView system={(*system).clone()} />
+                                        <StatementWizard on_generate={
+                                            let code = code.clone();
+                                            Callback::from(move |script: String| code.set(script))
+                                        } />
+                                    </>
                                 },
-                                "wasm" => html! { <WasmView /> },
+                                "wasm" => html! { <WasmView size={(*size).clone()} /> },
                                 _ => html! {},
                             }}
                         </div>
@@ -298,11 +381,185 @@ fn proof_state_view(props: &ProofStateProps) -> Html {
     }
 }
 
+#[derive(Properties, PartialEq)]
+struct StatementWizardProps {
+    on_generate: Callback<String>,
+}
+
+/// Dialog for filling a statement template slot by slot
+#[function_component(StatementWizard)]
+fn statement_wizard(props: &StatementWizardProps) -> Html {
+    let library = templates::library();
+    let selected = use_state(|| library[0].id);
+    let values = use_state(std::collections::HashMap::<&'static str, String>::new);
+    let error = use_state(|| None::<String>);
+    
+    let template = templates::template(*selected).unwrap_or_else(|| library[0].clone());
+    let env = Env::prelude();
+    
+    let on_select = {
+        let selected = selected.clone();
+        let values = values.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input = e.target_unchecked_into::<HtmlInputElement>();
+            if let Some(t) = templates::library().into_iter().find(|t| t.id == input.value()) {
+                selected.set(t.id);
+                values.set(Default::default());
+                error.set(None);
+            }
+        })
+    };
+    
+    let on_generate = {
+        let values = values.clone();
+        let error = error.clone();
+        let template = template.clone();
+        let callback = props.on_generate.clone();
+        Callback::from(move |_| {
+            let filled = values.iter().map(|(k, v)| (*k, v.as_str())).collect();
+            match template.instantiate(&Env::prelude(), &filled) {
+                Ok(inst) => {
+                    error.set(None);
+                    callback.emit(inst.script());
+                }
+                Err(e) => error.set(Some(e.to_string())),
+            }
+        })
+    };
+    
+    html! {
+        <div class="statement-wizard">
+            <h4>{"New statement"}</h4>
+            <select onchange={on_select}>
+                {library.iter().map(|t| html! {
+                    <option value={t.id} selected={t.id == *selected}>{t.name}</option>
+                }).collect::<Html>()}
+            </select>
+            <p class="description">{template.description}</p>
+            {template.slots.iter().map(|slot| {
+                let value = values.get(slot.name).cloned().unwrap_or_default();
+                let status = if value.trim().is_empty() {
+                    html! {}
+                } else {
+                    match slot.validate(&env, &value) {
+                        Ok(()) => html! { <span class="slot-ok">{"✓"}</span> },
+                        Err(e) => html! { <span class="slot-error">{e.to_string()}</span> },
+                    }
+                };
+                let oninput = {
+                    let values = values.clone();
+                    let name = slot.name;
+                    Callback::from(move |e: InputEvent| {
+                        let input = e.target_unchecked_into::<HtmlInputElement>();
+                        let mut next = (*values).clone();
+                        next.insert(name, input.value());
+                        values.set(next);
+                    })
+                };
+                html! {
+                    <label class="slot">
+                        <span>{format!("{} : {}", slot.name, slot.ty)}</span>
+                        <input placeholder={slot.description} value={value} {oninput} />
+                        {status}
+                    </label>
+                }
+            }).collect::<Html>()}
+            <button class="btn" onclick={on_generate}>{"Generate"}</button>
+            {if let Some(e) = &*error {
+                html! { <p class="error">{e}</p> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct WasmViewProps {
+    size: Option<SizeBreakdown>,
+}
+
+/// The last compiled module as a bar of its sections, so the proof
+/// certificate's share of the artifact is visible at a glance
 #[function_component(WasmView)]
-fn wasm_view() -> Html {
+fn wasm_view(props: &WasmViewProps) -> Html {
+    let Some(size) = &props.size else {
+        return html! {
+            <div class="wasm-view">
+                <p>{"WebAssembly output will appear here"}</p>
+            </div>
+        };
+    };
+    let parts = [
+        ("code", size.code),
+        ("types", size.types),
+        ("proof", size.proof),
+        ("srcmap", size.srcmap),
+        ("other", size.other),
+    ];
+    let percent = |n: usize| if size.total == 0 { 0.0 } else { 100.0 * n as f64 / size.total as f64 };
+
     html! {
         <div class="wasm-view">
-            <p>{"WebAssembly output will appear here"}</p>
+            <div class="size-bar">
+                {parts.iter().filter(|(_, n)| *n > 0).map(|(name, n)| html! {
+                    <div
+                        class={classes!("size-segment", format!("size-{}", name))}
+                        style={format!("width: {:.2}%", percent(*n))}
+                        title={format!("{}: {} bytes", name, n)}
+                    />
+                }).collect::<Html>()}
+            </div>
+            <ul class="size-legend">
+                {parts.iter().map(|(name, n)| html! {
+                    <li>
+                        <span class={classes!("size-swatch", format!("size-{}", name))} />
+                        {format!("{} {} bytes ({:.1}%)", name, n, percent(*n))}
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+            <p>{format!("{} bytes in total", size.total)}</p>
+        </div>
+    }
+}
+
+/// Read-only viewer for goal permalinks (`/goal#goal=...`)
+#[function_component(GoalViewer)]
+fn goal_viewer() -> Html {
+    let fragment = web_sys::window()
+        .and_then(|w| w.location().hash().ok())
+        .unwrap_or_default();
+    
+    let snapshot = match GoalSnapshot::from_fragment(&fragment) {
+        Ok(snapshot) => snapshot,
+        Err(e) => return html! {
+            <div class="goal-viewer">
+                <div class="container"><p class="error">{e}</p></div>
+            </div>
+        },
+    };
+    let replay = snapshot.replay();
+    
+    html! {
+        <div class="goal-viewer">
+            <div class="container">
+                {if let Some(banner) = replay.banner() {
+                    html! { <div class="banner stale">{banner}</div> }
+                } else {
+                    html! {}
+                }}
+                <pre class="proof-state">{snapshot.render()}</pre>
+                {if let GoalReplay::Live(_) = replay {
+                    html! {
+                        <a class="btn btn-primary" href={format!("/playground#{}", snapshot.to_fragment())}>
+                            {"Open in my playground"}
+                        </a>
+                    }
+                } else {
+                    html! {}
+                }}
+            </div>
         </div>
     }
 }
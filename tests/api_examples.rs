@@ -0,0 +1,58 @@
+//! The payloads served at `GET /api/examples` are valid API payloads
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use sctt_system::api::*;
+
+fn decodes<T: DeserializeOwned>(value: &Value, what: &str) {
+    if let Err(e) = serde_json::from_value::<T>(value.clone()) {
+        panic!("{} does not decode: {}\n{}", what, e, value);
+    }
+}
+
+fn check<Req: DeserializeOwned, Res: DeserializeOwned>(route: &RouteExamples) {
+    for example in &route.examples {
+        let what = |part: &str| format!("{} {} `{}` {}", route.method, route.path, example.name, part);
+        match &example.request {
+            Some(request) => decodes::<Req>(request, &what("request")),
+            None => assert_eq!(std::any::type_name::<Req>(), "()", "{} has no request", what("example")),
+        }
+        decodes::<Res>(&example.response, &what("response"));
+    }
+}
+
+#[test]
+fn examples_decode_into_the_dtos() {
+    // Through JSON text, as a client reading the endpoint would see them
+    let served = serde_json::to_string(&examples()).unwrap();
+    let routes: Vec<RouteExamples> = serde_json::from_str(&served).unwrap();
+    assert!(!routes.is_empty());
+
+    for route in &routes {
+        assert!(!route.examples.is_empty(), "{} has no examples", route.path);
+        match route.path.as_str() {
+            "/api/health" => check::<(), HealthResponse>(route),
+            "/api/typecheck" => check::<TypeCheckRequest, TypeCheckResponse>(route),
+            "/api/compile" => check::<CompileRequest, CompileResponse>(route),
+            "/api/prove" => check::<ProveRequest, ProveResponse>(route),
+            "/api/evaluate" => check::<EvaluateRequest, EvaluateResponse>(route),
+            "/api/evaluate/batch" => check::<EvaluateBatchRequest, EvaluateBatchResponse>(route),
+            "/api/session" => check::<(), SessionResponse>(route),
+            "/api/session/:id/transactions" => check::<BeginTransactionRequest, BeginTransactionResponse>(route),
+            "/api/session/:id/transactions/:txn/commit" => check::<(), TransactionOutcome>(route),
+            "/api/session/:id/rename" => check::<RenameRequest, RenameResponse>(route),
+            other => panic!("no DTOs registered for {}", other),
+        }
+    }
+}
+
+#[test]
+fn example_names_are_unique_per_route() {
+    for route in examples() {
+        let mut names: Vec<_> = route.examples.iter().map(|e| e.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), route.examples.len(), "duplicate example name on {}", route.path);
+    }
+}
@@ -0,0 +1,69 @@
+//! Driver handoff over the collaboration protocol
+//!
+//! Feeds a shared proof session the JSON frames two websocket clients would
+//! send and checks what each of them is told back.
+
+#![cfg(feature = "proof-assistant")]
+
+use std::time::{Duration, Instant};
+
+use sctt_system::driver::{Outgoing, ProofRoom};
+use sctt_system::lifecycle::{Registry, SessionGuard, SessionResources};
+use sctt_system::protocol::{ClientMessage, ServerMessage};
+use sctt_system::sctt_typechecker::Level;
+use sctt_system::Term;
+
+fn frame(room: &mut ProofRoom, conn: &str, json: &str, now: Instant) -> Vec<Outgoing> {
+    let msg: ClientMessage = serde_json::from_str(json).expect("client frame parses");
+    room.handle(conn, msg, now)
+}
+
+fn tactic(goal_id: usize, tactic: &str) -> String {
+    format!(r#"{{"ProofAction":{{"action":{{"ApplyTactic":{{"goal_id":{},"tactic":"{}"}}}}}}}}"#, goal_id, tactic)
+}
+
+#[test]
+fn only_the_driver_applies_tactics() {
+    let registry = Registry::new();
+    let resources = SessionResources::default();
+    let mut session = SessionGuard::open(&registry, &resources, "s1");
+    let universe = || Box::new(Term::Universe(Level::Zero));
+    session.assistant.start_proof("const", Term::Pi(universe(), Box::new(Term::Pi(universe(), universe())))).unwrap();
+
+    let start = Instant::now();
+    let mut room = ProofRoom::new(session, Duration::from_secs(30), start);
+    room.join("alice", start);
+    room.join("bob", start);
+
+    // Bob is not driving: rejected, and the goal is untouched
+    match frame(&mut room, "bob", &tactic(0, "intro A"), start).as_slice() {
+        [Outgoing::Reply(ServerMessage::NotDriving { driver })] => assert_eq!(driver.as_deref(), Some("alice")),
+        other => panic!("expected a not-driving rejection, got {:?}", other),
+    }
+    assert_eq!(room.session.assistant.goal_ids(), vec![0]);
+
+    // Alice drives: applied and broadcast to everyone
+    match frame(&mut room, "alice", &tactic(0, "intro A"), start).as_slice() {
+        [Outgoing::Broadcast(ServerMessage::ProofUpdate { proof_state })] => {
+            assert_eq!(proof_state.history.len(), 1);
+            assert_eq!(proof_state.history[0].tactic, "intro A");
+        }
+        other => panic!("expected a proof update, got {:?}", other),
+    }
+
+    // Bob asks, Alice grants, and now Bob's tactic lands
+    assert!(matches!(
+        frame(&mut room, "bob", r#""RequestControl""#, start).as_slice(),
+        [Outgoing::Broadcast(ServerMessage::ControlRequested { .. })]
+    ));
+    frame(&mut room, "alice", r#"{"GrantControl":{"to":"bob"}}"#, start);
+    let goal = room.session.assistant.goal_ids()[0];
+    assert!(matches!(
+        frame(&mut room, "bob", &tactic(goal, "intro B"), start).as_slice(),
+        [Outgoing::Broadcast(ServerMessage::ProofUpdate { .. })]
+    ));
+
+    let log = resources.activity.lock().unwrap()["s1"].clone();
+    assert!(log.contains(&"alice started driving".to_string()));
+    assert!(log.contains(&"alice handed control to bob".to_string()));
+}
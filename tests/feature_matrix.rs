@@ -0,0 +1,39 @@
+//! Feature matrix checks
+//!
+//! The minimal `kernel` configuration is what embedders build, so CI must
+//! notice when a change drags WASM, web, or compiler dependencies back into it.
+//! The remaining features are checked one at a time by `.github/workflows/features.yml`.
+
+use std::path::Path;
+use std::process::Command;
+
+fn cargo_check(features: &str) {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let status = Command::new(env!("CARGO"))
+        .args(["check", "--lib", "--no-default-features", "--features", features])
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .arg("--target-dir")
+        .arg(Path::new(env!("CARGO_TARGET_TMPDIR")).join("feature-matrix"))
+        .status()
+        .expect("failed to spawn cargo");
+    assert!(status.success(), "`--features {}` does not build", features);
+}
+
+#[test]
+fn kernel_only_builds() {
+    cargo_check("kernel");
+}
+
+#[cfg(feature = "kernel")]
+#[test]
+fn kernel_infers_universes() {
+    use sctt_system::sctt_typechecker::{Level, Value};
+    use sctt_system::{Context, Term, TypeChecker};
+
+    let checker = TypeChecker::new();
+    let ty = checker
+        .infer(&Context::new(), &Term::Universe(Level::Zero))
+        .expect("Type : Type₁");
+    assert!(matches!(ty, Value::Universe(Level::Succ(_))));
+}
@@ -0,0 +1,146 @@
+//! Panic policy
+//!
+//! Library code must not panic on input users can reach: in WASM a panic
+//! kills the page, on the server it kills the worker. This scans the library
+//! sources of every crate (binaries and test modules excluded) for panicking
+//! calls and fails when one appears outside the allowlist below.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PATTERNS: [&str; 9] = [
+    ".unwrap()",
+    ".expect(",
+    "panic!(",
+    "todo!(",
+    "unimplemented!(",
+    "unreachable!(",
+    "assert!(",
+    "assert_eq!(",
+    "assert_ne!(",
+];
+
+/// Sites that cannot fail, keyed by file and a snippet of the line
+const ALLOWLIST: [(&str, &str, &str); 4] = [
+    (
+        "src/proof_assistant.rs",
+        "goal snapshots are serializable",
+        "derived Serialize over strings and vectors has no failure case",
+    ),
+    (
+        "rust/sctt-web/src/storage.rs",
+        "manifest is serializable",
+        "derived Serialize over strings and integers has no failure case",
+    ),
+    (
+        "rust/sctt-web/src/storage.rs",
+        "app state is serializable",
+        "derived Serialize over strings, numbers and vectors has no failure case",
+    ),
+    (
+        "rust/sctt-smooth/src/dual.rs",
+        "a jet carries at least its value",
+        "an associated const, so the assertion fails the build rather than panicking",
+    ),
+];
+
+fn rust_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if path.file_name().is_some_and(|name| name != "bin") {
+                rust_files(&path, out);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "rs") && !path.ends_with("main.rs") {
+            out.push(path);
+        }
+    }
+}
+
+/// Library sources: the root crate and every `rust/` crate with a `lib.rs`
+fn library_sources(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    rust_files(&root.join("src"), &mut files);
+    if let Ok(crates) = fs::read_dir(root.join("rust")) {
+        for krate in crates.flatten() {
+            let src = krate.path().join("src");
+            if src.join("lib.rs").exists() {
+                rust_files(&src, &mut files);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Lines that may panic, with `#[cfg(test)]` items and comments skipped
+fn panicking_lines(source: &str) -> Vec<(usize, &str)> {
+    let mut hits = Vec::new();
+    let mut in_test = false;
+    let mut depth = 0i32;
+    let mut opened = false;
+    for (i, line) in source.lines().enumerate() {
+        let code = line.trim();
+        if in_test {
+            depth += code.matches('{').count() as i32 - code.matches('}').count() as i32;
+            opened |= code.contains('{');
+            if (opened && depth <= 0) || (!opened && code.ends_with(';')) {
+                in_test = false;
+            }
+            continue;
+        }
+        if code == "#[cfg(test)]" || code.starts_with("#[cfg(all(test,") {
+            in_test = true;
+            depth = 0;
+            opened = false;
+            continue;
+        }
+        if code.starts_with("//") {
+            continue;
+        }
+        // `debug_assert!` is compiled out of release builds
+        let code = code.replace("self.expect(", "").replace("debug_assert", "");
+        if PATTERNS.iter().any(|p| code.contains(p)) {
+            hits.push((i + 1, line.trim()));
+        }
+    }
+    hits
+}
+
+#[test]
+fn scanner_skips_tests_and_comments() {
+    let source = "fn f() -> u8 {\n    x.unwrap()\n}\n// y.unwrap()\nfn g() { self.expect(')')?; }\n\
+                  #[cfg(test)]\nmod tests {\n    fn t() { z.unwrap(); }\n}\nfn h() { todo!() }\n\
+                  fn i(n: usize) { assert!(n > 0); debug_assert!(n > 1); }\nfn j() { debug_assert_eq!(1, 1); }\n\
+                  #[cfg(all(test, feature = \"wasm\"))]\nmod wasm { fn k() { assert_eq!(1, 2); } }\n";
+    let hits: Vec<usize> = panicking_lines(source).into_iter().map(|(line, _)| line).collect();
+    assert_eq!(hits, vec![2, 10, 11]);
+}
+
+#[test]
+fn library_code_does_not_panic() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut violations = Vec::new();
+    let mut used = [false; ALLOWLIST.len()];
+    for path in library_sources(root) {
+        let Ok(source) = fs::read_to_string(&path) else { continue };
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        for (line, code) in panicking_lines(&source) {
+            let allowed = ALLOWLIST
+                .iter()
+                .position(|(file, snippet, _)| *file == relative && code.contains(snippet));
+            match allowed {
+                Some(i) => used[i] = true,
+                None => violations.push(format!("{}:{}: {}", relative, line, code)),
+            }
+        }
+    }
+    assert!(
+        violations.is_empty(),
+        "library code may panic; return an error instead (or allowlist with a reason):\n{}",
+        violations.join("\n")
+    );
+    let stale: Vec<_> = ALLOWLIST.iter().zip(used).filter(|(_, used)| !used).map(|(entry, _)| entry.0).collect();
+    assert!(stale.is_empty(), "allowlist entries no longer match anything: {:?}", stale);
+}
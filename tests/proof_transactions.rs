@@ -0,0 +1,110 @@
+//! Transactional tactic batches against a shared proof session
+//!
+//! Drives a session the way the REST transaction endpoints and an
+//! interactive websocket client would, and checks what each of them sees.
+
+#![cfg(feature = "proof-assistant")]
+
+use std::time::{Duration, Instant};
+
+use sctt_system::driver::{Outgoing, ProofRoom};
+use sctt_system::lifecycle::{Registry, SessionGuard, SessionResources};
+use sctt_system::protocol::{ClientMessage, ProofAction, ServerMessage};
+use sctt_system::sctt_typechecker::Level;
+use sctt_system::transaction::{TransactionError, DEFAULT_TRANSACTION_TIMEOUT};
+use sctt_system::Term;
+
+fn room(resources: &SessionResources, now: Instant) -> ProofRoom {
+    let registry = Registry::new();
+    let mut session = SessionGuard::open(&registry, resources, "s1");
+    let universe = || Box::new(Term::Universe(Level::Zero));
+    session.assistant.start_proof("const", Term::Pi(universe(), Box::new(Term::Pi(universe(), universe())))).unwrap();
+    let mut room = ProofRoom::new(session, Duration::from_secs(60), now);
+    room.join("alice", now);
+    room
+}
+
+fn action(room: &mut ProofRoom, action: ProofAction, now: Instant) -> Vec<Outgoing> {
+    room.handle("alice", ClientMessage::ProofAction { action }, now)
+}
+
+#[test]
+fn failed_batch_rolls_back_cleanly() {
+    let now = Instant::now();
+    let mut room = room(&SessionResources::default(), now);
+    let before = room.proof_state();
+
+    let txn = room.begin_transaction("bot", DEFAULT_TRANSACTION_TIMEOUT, now).unwrap();
+    room.apply_in_transaction(&txn, 0, "intro A", now).unwrap();
+    let failed = room.apply_in_transaction(&txn, 42, "intro B", now);
+    assert!(matches!(failed, Err(TransactionError::Tactic(_))));
+    room.rollback(&txn).unwrap();
+
+    let after = room.proof_state();
+    assert_eq!(after.goals, before.goals);
+    assert!(after.history.is_empty());
+    assert!(room.session.assistant.history().is_empty());
+}
+
+#[test]
+fn committed_batch_is_one_undoable_group() {
+    let now = Instant::now();
+    let resources = SessionResources::default();
+    let mut room = room(&resources, now);
+    let goals_before = room.proof_state().goals;
+
+    let txn = room.begin_transaction("bot", DEFAULT_TRANSACTION_TIMEOUT, now).unwrap();
+    room.apply_in_transaction(&txn, 0, "intro A", now).unwrap();
+    let goal = room.transaction().unwrap().steps()[0].goals_after[0].id;
+    room.apply_in_transaction(&txn, goal, "intro B", now).unwrap();
+    // Speculative steps stay off the session until commit
+    assert!(room.proof_state().history.is_empty());
+
+    match room.commit(&txn, now).unwrap().as_slice() {
+        [Outgoing::Broadcast(ServerMessage::ProofUpdate { proof_state })] => {
+            assert_eq!(proof_state.history.len(), 1);
+            let group = &proof_state.history[0];
+            assert_eq!(group.tactic, "intro A; intro B");
+            assert_eq!(group.steps.iter().map(|s| s.tactic.as_str()).collect::<Vec<_>>(), ["intro A", "intro B"]);
+        }
+        other => panic!("expected a proof update, got {:?}", other),
+    }
+    assert_eq!(room.session.assistant.history().len(), 2);
+
+    // One undo reverts the whole group
+    action(&mut room, ProofAction::Undo, now);
+    assert!(room.proof_state().history.is_empty());
+    assert_eq!(room.proof_state().goals, goals_before);
+    assert!(room.session.assistant.history().is_empty());
+
+    // ...and one redo brings it back
+    action(&mut room, ProofAction::Redo, now);
+    assert_eq!(room.proof_state().history.len(), 1);
+    assert_eq!(room.session.assistant.history().len(), 2);
+
+    let log = resources.activity.lock().unwrap()["s1"].clone();
+    assert!(log.contains(&"bot committed transaction txn-1 (2 steps)".to_string()));
+    assert!(log.contains(&"  applied `intro A` to goal 0".to_string()));
+}
+
+#[test]
+fn interactive_tactics_are_rejected_during_a_transaction() {
+    let now = Instant::now();
+    let mut room = room(&SessionResources::default(), now);
+    let txn = room.begin_transaction("bot", DEFAULT_TRANSACTION_TIMEOUT, now).unwrap();
+
+    let tactic = ProofAction::ApplyTactic { goal_id: 0, tactic: "intro A".to_string() };
+    match action(&mut room, tactic, now).as_slice() {
+        [Outgoing::Reply(ServerMessage::Busy { message })] => assert!(message.contains("bot")),
+        other => panic!("expected a busy rejection, got {:?}", other),
+    }
+    assert!(room.session.assistant.history().is_empty());
+
+    // Once the transaction is gone the driver writes again
+    room.rollback(&txn).unwrap();
+    let tactic = ProofAction::ApplyTactic { goal_id: 0, tactic: "intro A".to_string() };
+    assert!(matches!(
+        action(&mut room, tactic, now).as_slice(),
+        [Outgoing::Broadcast(ServerMessage::ProofUpdate { .. })]
+    ));
+}
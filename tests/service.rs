@@ -0,0 +1,257 @@
+//! The shared service layer against the API fixtures
+//!
+//! Both server binaries only translate HTTP to and from these calls, so this
+//! is the integration suite for the REST API: every fixture request must get
+//! its fixture response, session writes need the edit token, and websocket
+//! connections see each other's edits and proof-tree focus.
+
+#![cfg(feature = "service")]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use sctt_core::record::{RunRecord, RunResult};
+use sctt_core::termination::TerminationStatus;
+use sctt_system::api::{
+    fixtures, BeginTransactionRequest, CheckTerminationRequest, ExplainSmoothnessRequest, RenameRequest,
+    SaveProofRequest,
+};
+use sctt_system::lifecycle::{Registry, ResourceKind};
+use sctt_system::presence::{badges, BadgeKind, Presence, STALE_AFTER_MS};
+use sctt_system::protocol::ServerMessage;
+use sctt_system::service::{Config, MemoryStore, Service, ServiceError, Sink};
+
+/// The service only awaits its proof store, which is ready at once here
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn service() -> Service {
+    Service::with_registry(&Config::default(), MemoryStore::default(), Registry::new())
+}
+
+fn recorder() -> (Sink, Arc<Mutex<Vec<String>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let seen = seen.clone();
+        Box::new(move |text: String| {
+            seen.lock().unwrap().push(text);
+            true
+        })
+    };
+    (sink, seen)
+}
+
+#[test]
+fn fixture_requests_get_fixture_responses() {
+    let service = service();
+    block_on(async {
+        assert_eq!(service.health().await, fixtures::health().remove(0).response);
+        assert_eq!(service.examples().await, sctt_system::api::examples());
+        for f in fixtures::typecheck() {
+            assert_eq!(service.typecheck(f.request.unwrap()).await, f.response, "typecheck `{}`", f.name);
+        }
+        for f in fixtures::prove() {
+            assert_eq!(service.prove(f.request.unwrap()).await, f.response, "prove `{}`", f.name);
+        }
+        for f in fixtures::evaluate() {
+            assert_eq!(service.evaluate(f.request.unwrap()).await, f.response, "evaluate `{}`", f.name);
+        }
+        for f in fixtures::evaluate_batch() {
+            assert_eq!(service.evaluate_batch(f.request.unwrap()).await, f.response, "batch `{}`", f.name);
+        }
+    });
+}
+
+#[test]
+fn session_writes_need_the_edit_token() {
+    let service = service();
+    block_on(async {
+        let session = service.create_session().await;
+        assert!(session.created);
+        assert_eq!(service.session(&session.id).await.unwrap().version, 0);
+        assert_eq!(service.session("missing").await.unwrap_err().status(), 404);
+
+        let request = fixtures::begin_transaction().remove(0).request.unwrap();
+        let refused = service.begin_transaction(&session.id, Some("guess"), request.clone()).await;
+        assert_eq!(refused, Err(ServiceError::Unauthorized));
+        assert_eq!(refused.unwrap_err().status(), 401);
+
+        // Authorized, but a fresh session has no proof to fork yet
+        let token = Some(session.edit_token.as_str());
+        let no_proof = service.begin_transaction(&session.id, token, request).await.unwrap_err();
+        assert_eq!(no_proof.status(), 422);
+        let unknown = service.rollback_transaction(&session.id, "txn-9", token).await.unwrap_err();
+        assert_eq!(unknown.status(), 404);
+        let missing = BeginTransactionRequest { client: "bot".to_string(), timeout_secs: None };
+        assert_eq!(service.begin_transaction("missing", token, missing).await.unwrap_err().status(), 404);
+    });
+}
+
+#[test]
+fn connections_share_edits_and_release_on_disconnect() {
+    let service = service();
+    let session = block_on(service.create_session());
+    let (alice_sink, alice_seen) = recorder();
+    let (bob_sink, bob_seen) = recorder();
+    let alice = service.connect(&session.id, alice_sink).unwrap();
+    let bob = service.connect(&session.id, bob_sink).unwrap();
+    assert!(service.connect("missing", recorder().0).is_err());
+    assert_eq!(service.registry().live().iter().filter(|r| r.kind == ResourceKind::Connection).count(), 2);
+
+    service.message(&session.id, &alice, r#"{"Operation":{"op":{"Insert":{"pos":0,"text":"refl"}},"version":0}}"#);
+    // Out of range: dropped, not applied
+    service.message(&session.id, &bob, r#"{"Operation":{"op":{"Delete":{"pos":10,"len":3}},"version":1}}"#);
+    service.message(&session.id, &bob, "not json");
+
+    let info = block_on(service.session(&session.id)).unwrap();
+    assert_eq!((info.document.as_str(), info.version), ("refl", 1));
+    assert_eq!(info.driver.as_deref(), Some(alice.as_str()));
+    assert!(bob_seen.lock().unwrap().iter().any(|m| m.contains("refl")));
+    assert!(alice_seen.lock().unwrap()[0].contains("Welcome"));
+
+    service.disconnect(&session.id, &alice);
+    assert_eq!(block_on(service.session(&session.id)).unwrap().driver.as_deref(), Some(bob.as_str()));
+    assert_eq!(service.registry().live().iter().filter(|r| r.kind == ResourceKind::Connection).count(), 1);
+}
+
+#[test]
+fn focus_broadcasts_reach_the_other_proof_tree() {
+    let service = service();
+    let session = block_on(service.create_session());
+    let (bob_sink, bob_seen) = recorder();
+    let alice = service.connect(&session.id, recorder().0).unwrap();
+    service.connect(&session.id, bob_sink).unwrap();
+
+    // An older client without the focus field is still relayed
+    service.message(&session.id, &alice, r#"{"CursorUpdate":{"cursor":{"line":0,"column":4}}}"#);
+    service.message(&session.id, &alice, r#"{"CursorUpdate":{"cursor":{"line":2,"column":0},"focus":3}}"#);
+
+    let mut bob_view = Presence::default();
+    for text in bob_seen.lock().unwrap().iter() {
+        bob_view.observe(&serde_json::from_str::<ServerMessage>(text).unwrap(), 1_000);
+    }
+    let focuses = bob_view.focuses(1_000);
+    assert_eq!((focuses.len(), focuses[0].user_id.as_str(), focuses[0].node), (1, alice.as_str(), 3));
+    let laid = badges(&focuses, &[], 1_000);
+    assert!(matches!(&laid[..], [b] if b.node == 3 && matches!(&b.kind, BadgeKind::User { user_id, .. } if *user_id == alice)));
+    assert!(bob_view.focuses(1_001 + STALE_AFTER_MS).is_empty());
+}
+
+#[test]
+fn renames_rewrite_the_session_document_or_nothing() {
+    let service = service();
+    let session = block_on(service.create_session());
+    let (sink, seen) = recorder();
+    let conn = service.connect(&session.id, sink).unwrap();
+    let insert = serde_json::json!({ "Operation": { "op": { "Insert": { "pos": 0, "text": fixtures::RENAME_DOCUMENT } }, "version": 0 } });
+    service.message(&session.id, &conn, &insert.to_string());
+    let token = Some(session.edit_token.as_str());
+
+    block_on(async {
+        let broken = RenameRequest { old: "id".to_string(), new: "a".to_string() };
+        let err = service.rename(&session.id, token, broken).await.unwrap_err();
+        assert_eq!(err.status(), 422);
+        assert!(err.to_string().contains("`twice`"), "{}", err);
+        assert_eq!(service.session(&session.id).await.unwrap().document, fixtures::RENAME_DOCUMENT);
+        let taken = RenameRequest { old: "id".to_string(), new: "twice".to_string() };
+        assert_eq!(service.rename(&session.id, token, taken).await.unwrap_err().status(), 409);
+
+        for f in fixtures::rename() {
+            let request = f.request.unwrap();
+            assert_eq!(service.rename(&session.id, None, request.clone()).await, Err(ServiceError::Unauthorized));
+            assert_eq!(service.rename(&session.id, token, request).await.unwrap(), f.response, "rename `{}`", f.name);
+        }
+        let info = service.session(&session.id).await.unwrap();
+        assert!(info.document.ends_with("= same A (same A a)\n"));
+        assert_eq!(info.version, 2);
+    });
+    assert!(seen.lock().unwrap().last().unwrap().contains("same A (same A a)"));
+}
+
+#[test]
+fn proofs_round_trip_through_the_store() {
+    let service = service();
+    block_on(async {
+        let proof = SaveProofRequest { name: "id".to_string(), statement: "Type".to_string(), proof: "intro A".to_string() };
+        assert_eq!(service.save_proof("p1", proof.clone()).await.unwrap().id, "p1");
+        assert_eq!(service.save_proof("p1", proof).await.unwrap_err().status(), 500);
+        let saved = service.get_proof("p1").await.unwrap();
+        assert_eq!(saved.statement, "Type");
+        assert!(!saved.engine_version.is_empty());
+        assert_eq!(saved.notice, None);
+        assert_eq!(service.get_proof("p2").await.unwrap_err().status(), 404);
+        assert_eq!(service.list_proofs().await.unwrap().len(), 1);
+    });
+}
+
+#[test]
+fn runs_are_logged_as_valid_run_records() {
+    let service = service();
+    block_on(async {
+        for f in fixtures::typecheck() {
+            service.typecheck(f.request.unwrap()).await;
+        }
+        for f in fixtures::evaluate() {
+            service.evaluate(f.request.unwrap()).await;
+        }
+    });
+
+    let runs = block_on(service.runs());
+    assert_eq!(runs.len(), fixtures::typecheck().len() + fixtures::evaluate().len());
+    // Newest first: the last evaluate fixture is `ln(x)` at -1
+    assert!(matches!(&runs[0].result, RunResult::Failed { error } if error.contains("undefined")));
+    assert!(runs.iter().any(|r| r.result == RunResult::Checked { ty: "Universe(Succ(1))".to_string() }));
+    for run in &runs {
+        let json = serde_json::to_string(run).unwrap();
+        assert_eq!(&RunRecord::validate(&json).unwrap(), run);
+        assert!(run.engine_versions.contains_key("sctt-system"));
+    }
+}
+
+#[test]
+fn smoothness_is_explained_or_rejected() {
+    let service = service();
+    let explain = |code: &str| block_on(service.explain_smoothness(ExplainSmoothnessRequest { code: code.to_string() }));
+
+    let kinked = explain("t on [0, 0.5]; 1 - t on [0.5, 1]").unwrap();
+    assert!(!kinked.smooth);
+    assert!((kinked.issues[0].evidence.as_ref().unwrap().at - 0.5).abs() < 1e-9);
+    assert!(explain("sin(x)").unwrap().smooth);
+    assert_eq!(explain("max(x,").unwrap_err().status(), 422);
+}
+
+#[test]
+fn termination_is_reported_per_definition() {
+    let service = service();
+    let check = |code: &str| block_on(service.check_termination(CheckTerminationRequest { code: code.to_string() }));
+
+    let report = check(
+        "fix plus (m : ℕ) (n : ℕ) : ℕ := natrec[ℕ] n (λ(k : ℕ). λ(r : ℕ). succ (plus k n)) m
+         unsafe_fix spin (n : ℕ) : ℕ := spin n
+         fix user (n : ℕ) : ℕ := plus n (spin n)
+         fix loop (n : ℕ) : ℕ := loop n",
+    )
+    .unwrap();
+    assert_eq!(report.statuses["plus"], TerminationStatus::Structural);
+    assert_eq!(report.statuses["spin"], TerminationStatus::Unchecked);
+    assert!(report.tainted["user"].contains("spin"));
+    match &report.statuses["loop"] {
+        TerminationStatus::Rejected(e) => assert!(e.span().is_some()),
+        other => panic!("expected `loop` to be rejected, got {:?}", other),
+    }
+    assert_eq!(check("fix bad (n : ℕ) : ℕ := π₁ n").unwrap_err().status(), 422);
+}